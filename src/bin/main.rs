@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use deptrack::{CargoDiscovery, CrateDependencyGraph, GitOps};
+use clap::{Args, Parser, Subcommand};
+use deptrack::utils::output::JsonlWriter;
+use deptrack::{CargoDiscovery, CrateDependencyGraph, GitOps, ReportIssue};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -12,10 +13,29 @@ struct Cli {
     #[arg(short, long, default_value = ".")]
     path: PathBuf,
 
-    /// output format (json or human)
+    /// output format (json, jsonl, yaml, toml or human)
     #[arg(short, long, default_value = "human", global = true)]
     format: OutputFormat,
 
+    /// disable colors in human output (NO_COLOR is honored as well)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// only look at the crates and changed files below these paths, globs
+    /// relative to the repository root (e.g. `crates/networking/**`)
+    #[arg(long, value_name = "GLOB", global = true)]
+    scope: Vec<String>,
+
+    /// fail on Cargo.toml files that do not parse instead of skipping them
+    /// with a warning; check-versions reports them as errors
+    #[arg(long, global = true)]
+    strict_parse: bool,
+
+    /// give up on graph building, hashing and git operations once the run
+    /// took this many seconds
+    #[arg(long, value_name = "SECONDS", global = true)]
+    timeout: Option<u64>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -23,24 +43,108 @@ struct Cli {
 #[derive(Clone, Debug)]
 enum OutputFormat {
     Json,
+    /// one compact json record per line, check-versions streams them as it goes
+    Jsonl,
+    Yaml,
+    Toml,
     Human,
 }
 
+impl OutputFormat {
+    /// `value` in this format, the structured formats render the same json report
+    fn render<T: serde::Serialize>(&self, value: &T) -> Result<String> {
+        let value = serde_json::to_value(value)?;
+        Ok(match self {
+            OutputFormat::Yaml => deptrack::utils::output::to_yaml(&value),
+            OutputFormat::Toml => deptrack::utils::output::to_toml(&value)?,
+            OutputFormat::Jsonl => deptrack::utils::output::to_jsonl(&value),
+            OutputFormat::Json | OutputFormat::Human => serde_json::to_string_pretty(&value)?,
+        })
+    }
+}
+
 impl std::str::FromStr for OutputFormat {
     type Err = String;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "json" => Ok(OutputFormat::Json),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "toml" => Ok(OutputFormat::Toml),
             "human" => Ok(OutputFormat::Human),
             _ => Err(format!(
-                "invalid output format: {}, use 'json' or 'human'",
+                "invalid output format: {}, use 'json', 'jsonl', 'yaml', 'toml' or 'human'",
                 s
             )),
         }
     }
 }
 
+/// exit status of the process, so scripts can branch on why deptrack failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// checks found errors, or the command failed for any other reason
+    Errors = 1,
+    /// checks found only warnings and `--fail-on warning` was given
+    Warnings = 2,
+    /// invalid arguments or flags
+    Usage = 10,
+    /// the repository could not be opened or a git operation failed
+    Repository = 20,
+}
+
+impl Exit {
+    /// the status for `error`: the one it was raised with, 20 for repository
+    /// and git errors of the library, 1 otherwise
+    fn of(error: &anyhow::Error) -> Self {
+        if let Some(failure) = error.downcast_ref::<Failure>() {
+            return failure.exit;
+        }
+        let repository = error.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<deptrack::Error>(),
+                Some(
+                    deptrack::Error::RepositoryNotFound { .. }
+                        | deptrack::Error::GitError(_)
+                        | deptrack::Error::GitDiscoverError(_)
+                        | deptrack::Error::RefNotFound { .. }
+                        | deptrack::Error::InvalidRef { .. }
+                        | deptrack::Error::DiffError { .. }
+                )
+            )
+        });
+        if repository {
+            Exit::Repository
+        } else {
+            Exit::Errors
+        }
+    }
+}
+
+/// a failure that ends the process with a specific exit status
+#[derive(Debug)]
+struct Failure {
+    exit: Exit,
+    message: String,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+fn fail(exit: Exit, message: impl Into<String>) -> anyhow::Error {
+    Failure {
+        exit,
+        message: message.into(),
+    }
+    .into()
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// analyze repository and show dependency information
@@ -52,10 +156,90 @@ enum Commands {
         /// show detailed dependency graph
         #[arg(short, long)]
         graph: bool,
+
+        /// also score version bumps of the crates changed since this reference
+        #[arg(long)]
+        since: Option<String>,
+
+        /// show the health score of every crate
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// list every group of crates that depend on each other (strongly
+        /// connected component, over any kind of dependency) with the edges
+        /// forming it
+        #[arg(long)]
+        sccs: bool,
     },
 
     /// check version bumps for changed crates between git refs
     CheckVersions {
+        #[command(flatten)]
+        args: CheckVersionsArgs,
+    },
+
+    /// compare two check-versions reports saved with `--format json`: issues
+    /// introduced and resolved, and crates whose compliance changed
+    CompareReports {
+        /// the earlier report, e.g. of the base branch
+        old: PathBuf,
+
+        /// the later report
+        new: PathBuf,
+
+        /// lowest severity of introduced issues that fails: error (exit 1) or
+        /// warning (exit 2 when only warnings were introduced)
+        #[arg(long, value_name = "SEVERITY", default_value = "error")]
+        fail_on: deptrack::IssueSeverity,
+    },
+
+    /// run every check in one pass and write a single report
+    Report {
+        /// base reference (branch, tag, or commit)
+        from: String,
+
+        /// target reference (branch, tag, or commit)
+        to: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// write the report as markdown, whatever --format says
+        #[arg(long)]
+        markdown: bool,
+
+        /// severity tables to check with: strict, standard or lenient
+        /// (overrides the severity tables and preset of deptrack.toml)
+        #[arg(long, value_name = "PRESET")]
+        severity_preset: Option<deptrack::SeverityPreset>,
+
+        /// lowest severity that fails: error (exit 1) or warning (exit 2 when
+        /// there are warnings but no errors)
+        #[arg(long, value_name = "SEVERITY", default_value = "error")]
+        fail_on: deptrack::IssueSeverity,
+    },
+
+    /// compare the commits of each changed crate with its new changelog entries
+    ChangelogCoverage {
+        /// base reference (branch, tag, or commit)
+        from: String,
+
+        /// target reference (branch, tag, or commit)
+        to: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// commits from which a crate whose changelog grew by at most one
+        /// generic entry is flagged
+        #[arg(long, value_name = "N", default_value_t = deptrack::DEFAULT_MIN_COMMITS)]
+        min_commits: usize,
+    },
+
+    /// turn the report issues between git refs into a checklist per owner
+    Tasks {
         /// base reference (branch, tag, or commit)
         from: String,
 
@@ -66,17 +250,102 @@ enum Commands {
         #[arg(short, long)]
         path: Option<PathBuf>,
 
-        /// skip changelog validation
+        /// write the checklist as markdown, whatever --format says
         #[arg(long)]
-        skip_changelog: bool,
+        markdown: bool,
 
-        /// check all crates (not just those with changes)
+        /// leave warnings out
         #[arg(long)]
-        all_crates: bool,
+        errors_only: bool,
+
+        /// severity tables to check with: strict, standard or lenient
+        /// (overrides the severity tables and preset of deptrack.toml)
+        #[arg(long, value_name = "PRESET")]
+        severity_preset: Option<deptrack::SeverityPreset>,
+    },
+
+    /// show the workspace-internal dependency tree of a crate (or of every top-level crate)
+    Tree {
+        /// crate to start from
+        #[arg(value_name = "CRATE")]
+        crate_name: Option<String>,
 
-        /// show detailed issue tables (errors and warnings)
+        /// path to the repository (optional, defaults to current directory)
         #[arg(short, long)]
-        verbose: bool,
+        path: Option<PathBuf>,
+
+        /// maximum depth to descend to
+        #[arg(short, long)]
+        depth: Option<usize>,
+
+        /// show dependents instead of dependencies
+        #[arg(short, long)]
+        invert: bool,
+
+        /// leave dev-dependencies out
+        #[arg(long)]
+        no_dev: bool,
+    },
+
+    /// show the state of one crate: version, dependencies, dependents, last
+    /// release, changelog and recent commits
+    Crate {
+        /// crate to describe
+        #[arg(value_name = "CRATE")]
+        crate_name: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// number of recent commits touching the crate to list
+        #[arg(long, default_value_t = 10)]
+        history: usize,
+    },
+
+    /// list the test targets affected by changes between git refs and the cargo test commands to run them
+    TestPlan {
+        /// base reference (branch, tag, or commit)
+        from: String,
+
+        /// target reference (branch, tag, or commit)
+        to: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+
+    /// emit a CI matrix of the crates affected by changes between git refs
+    CiMatrix {
+        /// base reference (branch, tag, or commit)
+        from: String,
+
+        /// target reference (branch, tag, or commit)
+        to: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// github (matrix json for fromJSON) or gitlab (child pipeline yaml)
+        #[arg(long, default_value = "github")]
+        provider: deptrack::CiProvider,
+    },
+
+    /// answer json-rpc queries (affected-by, dependents, check) on stdin/stdout, one message per line
+    Daemon {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+
+    /// check git, the checkout, the manifests and deptrack.toml, and tell how
+    /// to fix what would make other commands fail or give incomplete answers
+    Doctor {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
     },
 
     /// git operations and change tracking
@@ -85,6 +354,93 @@ enum Commands {
         command: GitCommands,
     },
 
+    /// filesystem snapshots and comparison
+    Fs {
+        #[command(subcommand)]
+        command: FsCommands,
+    },
+
+    /// hash release artifacts into a manifest and verify them against it
+    Manifest {
+        #[command(subcommand)]
+        command: ManifestCommands,
+    },
+
+    /// fingerprint crate sources and compare fingerprints, change detection
+    /// for trees that are not git checkouts
+    Fingerprint {
+        #[command(subcommand)]
+        command: FingerprintCommands,
+    },
+
+    /// external dependency reports
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommands,
+    },
+
+    /// export the crate graph for other build systems
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+
+    /// plan and execute releases of the crates changed since a git ref
+    Release {
+        #[command(subcommand)]
+        command: ReleaseCommands,
+    },
+
+    /// publish check-versions results on github pull requests
+    Github {
+        #[command(subcommand)]
+        command: GithubCommands,
+    },
+
+    #[cfg(feature = "serve")]
+    /// serve an html dashboard of the dependency graph, a check-versions report and every crate
+    Serve {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// address to listen on
+        #[arg(short, long, default_value = deptrack::DEFAULT_BIND_ADDRESS)]
+        bind: String,
+
+        /// json output of `check-versions` to show, re-read on every request
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+
+    #[cfg(feature = "tui")]
+    /// browse workspaces, crates, their dependencies and issues in the terminal
+    Tui {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+
+    /// show the dependency graph as statistics, dot, json or an svg or png image
+    Graph {
+        /// output format: dot, json, stats, svg or png
+        #[arg(short = 'o', long = "output", default_value = "stats")]
+        output: GraphFormat,
+
+        /// image file for svg and png (defaults to deptrack-graph.svg or .png)
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+
+        /// open the image with the default viewer once written
+        #[arg(long)]
+        open: bool,
+
+        /// with stats: break the statistics down per workspace and count the
+        /// dependencies between each pair of workspaces
+        #[arg(long)]
+        per_workspace: bool,
+    },
+
     #[cfg(debug_assertions)]
     /// [debug] list all workspaces in the repository
     Workspaces,
@@ -97,17 +453,13 @@ enum Commands {
         deps: bool,
     },
 
-    #[cfg(debug_assertions)]
-    /// [debug] show dependency graph in various formats
-    Graph {
-        /// output format: dot, json, or stats
-        #[arg(short = 'o', long = "output", default_value = "stats")]
-        output: GraphFormat,
-    },
-
     #[cfg(debug_assertions)]
     /// [debug] show statistics about the repository
-    Stats,
+    Stats {
+        /// order of the per-crate table: name, loc, files, size or dependents
+        #[arg(long, default_value = "name")]
+        sort_by: deptrack::CrateMetricsSort,
+    },
 }
 
 #[derive(Subcommand)]
@@ -117,9 +469,25 @@ enum GitCommands {
         /// path to the repository (optional, defaults to current directory)
         #[arg(short, long)]
         path: Option<PathBuf>,
+
+        /// list the remote-tracking branches instead
+        #[arg(short, long, conflicts_with = "all")]
+        remote: bool,
+
+        /// list local and remote-tracking branches
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// list tags with the commit they point at, their date and, for annotated
+    /// tags, their message
+    Tags {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
     },
 
-    /// show current branch
+    /// show the current branch and commit, or the commit of a detached HEAD
     CurrentBranch {
         /// path to the repository (optional, defaults to current directory)
         #[arg(short, long)]
@@ -142,6 +510,16 @@ enum GitCommands {
         #[arg(short, long)]
         extension: Option<String>,
 
+        /// only diff paths matching this glob relative to the repository root
+        /// (e.g. `crates/foo`, a directory includes everything below it),
+        /// repeatable
+        #[arg(long, value_name = "PATHSPEC")]
+        include: Vec<String>,
+
+        /// leave out paths matching this glob (e.g. `**/*.md`), repeatable
+        #[arg(long, value_name = "PATHSPEC")]
+        exclude: Vec<String>,
+
         /// show only changes unique to the 'to' branch (excluding merged changes from 'from')
         /// this uses "from...to" (three-dot) syntax to compare from merge-base
         #[arg(short = 'u', long)]
@@ -149,438 +527,3493 @@ enum GitCommands {
     },
 }
 
-#[cfg(debug_assertions)]
-#[derive(Clone, Debug)]
-enum GraphFormat {
-    Dot,
-    Json,
-    Stats,
-}
+#[derive(Subcommand)]
+enum FsCommands {
+    /// scan and hash a directory, then save the tree as a json snapshot
+    Snapshot {
+        /// directory to snapshot
+        dir: PathBuf,
 
-#[cfg(debug_assertions)]
-impl std::str::FromStr for GraphFormat {
-    type Err = String;
+        /// file to write the snapshot to
+        #[arg(short, long)]
+        output: PathBuf,
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "dot" => Ok(GraphFormat::Dot),
-            "json" => Ok(GraphFormat::Json),
-            "stats" => Ok(GraphFormat::Stats),
-            _ => Err(format!(
-                "invalid graph format: {}, use 'dot', 'json', or 'stats'",
-                s
-            )),
-        }
-    }
-}
+        /// earlier snapshot whose hashes are reused for files with unchanged size and mtime
+        #[arg(long)]
+        cache: Option<PathBuf>,
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+        /// only include files matching this expression, e.g. "ext == 'rs' && size > 10k"
+        #[arg(long)]
+        filter: Option<String>,
+    },
 
-    match cli.command {
-        Commands::Analyze { path, graph } => {
-            let repo_path = path.as_ref().unwrap_or(&cli.path);
-            handle_analyze(repo_path, &cli.format, graph)?;
-        }
-        Commands::CheckVersions {
-            from,
-            to,
-            path,
-            skip_changelog,
-            all_crates,
-            verbose,
-        } => {
-            let repo_path = path.as_ref().unwrap_or(&cli.path);
-            handle_check_versions(
-                repo_path,
-                &cli.format,
-                &from,
-                &to,
-                skip_changelog,
-                all_crates,
-                verbose,
-            )?;
-        }
-        Commands::Git { command } => {
-            handle_git(&cli.path, &cli.format, command)?;
-        }
-        #[cfg(debug_assertions)]
-        Commands::Workspaces => {
-            handle_debug_workspaces(&cli.path, &cli.format)?;
-        }
-        #[cfg(debug_assertions)]
-        Commands::Crates { deps } => {
-            handle_debug_crates(&cli.path, &cli.format, deps)?;
-        }
-        #[cfg(debug_assertions)]
-        Commands::Graph { output } => {
-            handle_debug_graph(&cli.path, &cli.format, output)?;
-        }
-        #[cfg(debug_assertions)]
-        Commands::Stats => {
-            handle_debug_stats(&cli.path, &cli.format)?;
-        }
-    }
+    /// list files matching a filter expression
+    Find {
+        /// directory to search
+        dir: PathBuf,
 
-    Ok(())
-}
+        /// expression such as "ext == 'rs' && size > 10k && !path ~ 'tests/**'";
+        /// `name` is the file name without extension, `filename` the whole name
+        #[arg(long)]
+        filter: String,
 
-fn handle_analyze(path: &PathBuf, format: &OutputFormat, show_graph: bool) -> Result<()> {
-    // canonicalize path for display
-    let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        /// instead of listing matches, show how the filter evaluates for this file
+        #[arg(long, value_name = "FILE")]
+        explain_filter: Option<PathBuf>,
+    },
 
-    let workspaces =
-        CargoDiscovery::discover_workspaces(path).context("failed to discover cargo workspace")?;
-    let graph = CrateDependencyGraph::build_from_repository(path)
-        .context("failed to build dependency graph")?;
+    /// compare two snapshots and report added, removed and modified files
+    Diff {
+        /// older snapshot
+        snapshot_a: PathBuf,
 
-    let total_crates: usize = workspaces.iter().map(|w| w.members.len()).sum();
+        /// newer snapshot
+        snapshot_b: PathBuf,
+    },
 
-    match format {
-        OutputFormat::Json => {
-            let stats = graph.get_statistics();
-            let output = serde_json::json!({
-                "repository_path": repo_path,
-                "workspaces": workspaces.len(),
-                "crates": total_crates,
-                "has_cycles": stats.has_cycles,
-                "statistics": stats,
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        }
-        OutputFormat::Human => {
+    /// list the crates affected by the differences between two snapshots of
+    /// a cargo tree
+    Impact {
+        /// older snapshot
+        snapshot_a: PathBuf,
+
+        /// newer snapshot
+        snapshot_b: PathBuf,
+
+        /// path to the newer tree (defaults to the directory the newer
+        /// snapshot was taken of)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+
+    /// hash every file and record the hashes in its `user.deptrack.tag.*`
+    /// extended attributes
+    Tag {
+        /// directory to tag
+        dir: PathBuf,
+
+        /// hashes to record: crc32, sha1, sha256, sha512, blake3, xxh64, xxh128,
+        /// git-blob, all or fast
+        #[arg(long, default_value = "blake3")]
+        hash: deptrack::HashType,
+    },
+
+    /// check every file against the hashes `fs tag` recorded on it
+    Verify {
+        /// directory to verify
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ManifestCommands {
+    /// hash artifacts with every supported algorithm and write the manifest json
+    Create {
+        /// files, directories or glob patterns relative to the repository
+        /// (defaults to `artifacts.patterns` from deptrack.toml)
+        artifacts: Vec<String>,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// also hash the source tree of every crate
+        #[arg(long)]
+        crates: bool,
+
+        /// file to write the manifest to
+        #[arg(short, long, default_value = "artifacts.json")]
+        output: PathBuf,
+    },
+
+    /// re-hash the artifacts listed in a manifest and report any that changed
+    Verify {
+        /// manifest written by `manifest create`
+        manifest: PathBuf,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FingerprintCommands {
+    /// hash the source files of every crate and write the fingerprints json
+    Create {
+        /// path to the tree (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// file to write the fingerprints to
+        #[arg(short, long, default_value = "fingerprints.json")]
+        output: PathBuf,
+    },
+
+    /// list the files and crates that changed since an earlier fingerprint
+    Compare {
+        /// fingerprints of the older tree
+        old: PathBuf,
+
+        /// fingerprints of the newer tree (defaults to fingerprinting --path now)
+        new: Option<PathBuf>,
+
+        /// path to the newer tree (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DepsCommands {
+    /// list external dependencies trailing their latest release on the registry
+    Outdated {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// compare the versions in Cargo.lock instead of the lowest accepted by each requirement
+        #[arg(long)]
+        locked: bool,
+
+        /// only report dependencies at least this far behind: patch, minor or major
+        #[arg(long, default_value = "minor")]
+        min_behind: deptrack::BumpLevel,
+
+        /// sparse index to query
+        #[arg(long, default_value = deptrack::CRATES_IO_INDEX)]
+        index: String,
+    },
+
+    /// list external crates resolved to more than one version in Cargo.lock
+    Duplicates {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+
+    /// rewrite internal dependency requirements that no longer accept the
+    /// current version of the workspace crate they name, and add one to path
+    /// dependencies of publishable crates that have none
+    Sync {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// only report stale or missing requirements, failing when there are any
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    /// map crates to bazel rust_library labels
+    Bazel {
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// build (BUILD.bazel skeletons) or query (one `from to` label pair per edge)
+        #[arg(short, long, default_value = "build")]
+        output: deptrack::BazelOutput,
+
+        /// label of each crate, {path}, {name} and {workspace} are substituted
+        #[arg(long, default_value = deptrack::DEFAULT_LABEL_TEMPLATE)]
+        label_template: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GithubCommands {
+    /// post or update a sticky pull request comment with a report and set a commit status,
+    /// authenticating with GITHUB_TOKEN
+    Comment {
+        /// report from `deptrack --format json check-versions`, "-" for stdin
+        report: PathBuf,
+
+        /// repository as owner/name (defaults to GITHUB_REPOSITORY)
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// pull request number (defaults to the one in GITHUB_EVENT_PATH)
+        #[arg(long)]
+        pr: Option<u64>,
+
+        /// commit to set the status on (defaults to the pull request head)
+        #[arg(long)]
+        sha: Option<String>,
+
+        /// only post the comment
+        #[arg(long)]
+        no_status: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReleaseCommands {
+    /// show the versions, file updates, tags and publish order of a release
+    Plan {
+        #[command(flatten)]
+        args: ReleaseArgs,
+    },
+
+    /// bump versions, update requirements, promote changelogs, commit and tag
+    Execute {
+        #[command(flatten)]
+        args: ReleaseArgs,
+
+        /// print every action without applying any of them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// draft changelog entries from the commit subjects of the crates changed
+    /// between git refs, formatted with the entry template of [templates]
+    Draft {
+        /// base reference (branch, tag, or commit)
+        from: String,
+
+        /// target reference (branch, tag, or commit)
+        #[arg(default_value = "HEAD")]
+        to: String,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+
+        /// add the entries to the `## [Unreleased]` section of each changelog
+        #[arg(long)]
+        write: bool,
+    },
+
+    /// render the release notes of a crate version from its changelog, with
+    /// the release notes template of [templates]
+    Notes {
+        /// crate whose changelog to read
+        #[arg(value_name = "CRATE")]
+        crate_name: String,
+
+        /// version to render, the crate's current version when omitted
+        #[arg(value_name = "VERSION")]
+        crate_version: Option<semver::Version>,
+
+        /// path to the repository (optional, defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct CheckVersionsArgs {
+    /// base reference (branch, tag, or commit); when omitted, the merge-base
+    /// with the upstream branch, or else the last release tag
+    from: Option<String>,
+
+    /// target reference (branch, tag, or commit), HEAD when omitted
+    to: Option<String>,
+
+    /// check everything changed since this reference: commits up to HEAD plus
+    /// uncommitted changes, instead of FROM and TO
+    #[arg(long, value_name = "REF", conflicts_with_all = ["from", "to"])]
+    changed_since: Option<String>,
+
+    /// path to the repository (optional, defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+
+    /// skip changelog validation
+    #[arg(long)]
+    skip_changelog: bool,
+
+    /// check all crates (not just those with changes)
+    #[arg(long)]
+    all_crates: bool,
+
+    /// show detailed issue tables (errors and warnings) and who committed to
+    /// each failing crate
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// affect dependents at most this many dependencies away from a changed
+    /// crate (overrides impact.max_transitive_depth)
+    #[arg(long, value_name = "DEPTH")]
+    max_depth: Option<usize>,
+
+    /// severity tables to check with: strict, standard or lenient (overrides
+    /// the severity tables and preset of deptrack.toml)
+    #[arg(long, value_name = "PRESET")]
+    severity_preset: Option<deptrack::SeverityPreset>,
+
+    /// neither read nor write the analysis cache in .deptrack/cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// report how long each phase of the check took
+    #[arg(long)]
+    timings: bool,
+
+    /// lowest severity that fails the check: error (exit 1) or warning
+    /// (exit 2 when there are warnings but no errors)
+    #[arg(long, value_name = "SEVERITY", default_value = "error")]
+    fail_on: deptrack::IssueSeverity,
+
+    /// stop at the first crate a check could not be run for, instead of
+    /// reporting it as an analysis_failed error and checking the rest
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// write a patch per crate fixing its mechanical issues (missing version
+    /// bumps and changelog sections, stale requirements) to .deptrack/fixes,
+    /// to review and `git apply`; the tree itself is left alone
+    #[arg(long)]
+    write_patches: bool,
+
+    /// print those patches instead of writing them
+    #[arg(long)]
+    emit_patches: bool,
+
+    #[command(flatten)]
+    notify: NotifyArgs,
+}
+
+#[derive(Args)]
+struct NotifyArgs {
+    /// post a summary to this slack or teams compatible webhook when done,
+    /// falls back to DEPTRACK_NOTIFY_WEBHOOK
+    #[arg(long, value_name = "URL")]
+    notify_webhook: Option<String>,
+
+    /// message template for the webhook (e.g. "{status}: {errors} error(s)")
+    #[arg(long, value_name = "TEMPLATE")]
+    notify_template: Option<String>,
+
+    /// link to the published report to include in the message
+    #[arg(long, value_name = "URL")]
+    report_url: Option<String>,
+}
+
+#[derive(Args)]
+struct ReleaseArgs {
+    /// reference of the previous release (branch, tag, or commit)
+    from: String,
+
+    /// path to the repository (optional, defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+
+    /// bump level for crates with changes: patch, minor or major
+    #[arg(short, long, default_value = "patch")]
+    level: deptrack::BumpLevel,
+
+    /// bump level for crates affected only through their dependencies
+    #[arg(long, default_value = "patch")]
+    transitive_level: deptrack::BumpLevel,
+
+    /// leave the updated files uncommitted (also skips tagging)
+    #[arg(long)]
+    no_commit: bool,
+
+    /// do not create release tags
+    #[arg(long)]
+    no_tag: bool,
+}
+
+#[derive(Clone, Debug)]
+enum GraphFormat {
+    Dot,
+    Json,
+    Stats,
+    Image(deptrack::ImageFormat),
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" => Ok(GraphFormat::Dot),
+            "json" => Ok(GraphFormat::Json),
+            "stats" => Ok(GraphFormat::Stats),
+            "svg" => Ok(GraphFormat::Image(deptrack::ImageFormat::Svg)),
+            "png" => Ok(GraphFormat::Image(deptrack::ImageFormat::Png)),
+            _ => Err(format!(
+                "invalid graph format: {}, use 'dot', 'json', 'stats', 'svg' or 'png'",
+                s
+            )),
+        }
+    }
+}
+
+fn main() -> std::process::ExitCode {
+    use std::process::ExitCode;
+
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            let _ = error.print();
+            // --help and --version end up here as well
+            return if error.use_stderr() {
+                ExitCode::from(Exit::Usage as u8)
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::from(Exit::of(&error) as u8)
+        }
+    }
+}
+
+/// set from `--scope` before any command runs
+static SCOPE: std::sync::OnceLock<deptrack::Scope> = std::sync::OnceLock::new();
+
+/// set from `--strict-parse` before any command runs
+static STRICT_PARSE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn strict_parse() -> bool {
+    STRICT_PARSE.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// set from `--timeout` before any command runs, graphs hand it on to their
+/// analyses
+static CANCEL: std::sync::OnceLock<deptrack::CancelToken> = std::sync::OnceLock::new();
+
+/// the dependency graph of the repository at `path`, restricted to `--scope`;
+/// manifests discovery skipped are warned about, or with `--strict-parse`
+/// fail the build
+fn build_graph<P: AsRef<std::path::Path>>(path: P) -> deptrack::Result<CrateDependencyGraph> {
+    let graph = discover_graph(path)?;
+    if graph.broken_manifests.is_empty() {
+        return Ok(graph);
+    }
+    if strict_parse() {
+        let broken: Vec<String> = graph
+            .broken_manifests
+            .iter()
+            .map(|broken| broken.to_string())
+            .collect();
+        return Err(deptrack::Error::WorkspaceError {
+            reason: format!("broken manifests:\n  {}", broken.join("\n  ")),
+        });
+    }
+    for broken in &graph.broken_manifests {
+        eprintln!("warning: skipped {}", broken);
+    }
+    Ok(graph)
+}
+
+/// with `--strict-parse` broken manifests are reported as errors
+fn use_strict_parse(config: &mut deptrack::DeptrackConfig) {
+    if strict_parse() {
+        config.direct_severity.broken_manifest = deptrack::IssueSeverity::Error;
+    }
+}
+
+/// [`build_graph`] for commands reporting broken manifests themselves
+fn discover_graph<P: AsRef<std::path::Path>>(path: P) -> deptrack::Result<CrateDependencyGraph> {
+    let cancel = CANCEL.get().cloned().unwrap_or_default();
+    match SCOPE.get() {
+        Some(scope) => CrateDependencyGraph::build_scoped_with_cancel(path, scope.clone(), &cancel),
+        None => CrateDependencyGraph::build_from_repository_with_cancel(path, &cancel),
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    if cli.no_color {
+        deptrack::utils::table::disable_color();
+    }
+    STRICT_PARSE.store(cli.strict_parse, std::sync::atomic::Ordering::Relaxed);
+    if let Some(timeout) = cli.timeout {
+        let timeout = std::time::Duration::from_secs(timeout);
+        let _ = CANCEL.set(deptrack::CancelToken::with_timeout(timeout));
+    }
+    if !cli.scope.is_empty() {
+        let scope =
+            deptrack::Scope::new(&cli.scope).map_err(|e| fail(Exit::Usage, e.to_string()))?;
+        let _ = SCOPE.set(scope);
+    }
+
+    match cli.command {
+        Commands::Analyze {
+            path,
+            graph,
+            since,
+            verbose,
+            sccs,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_analyze(
+                repo_path,
+                &cli.format,
+                graph,
+                since.as_deref(),
+                verbose,
+                sccs,
+            )?;
+        }
+        Commands::CheckVersions { args } => {
+            handle_check_versions(&cli.path, &cli.format, &args)?;
+        }
+        Commands::CompareReports { old, new, fail_on } => {
+            handle_compare_reports(&cli.format, &old, &new, fail_on)?;
+        }
+        Commands::Report {
+            from,
+            to,
+            path,
+            markdown,
+            severity_preset,
+            fail_on,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_report(
+                repo_path,
+                &cli.format,
+                &from,
+                &to,
+                markdown,
+                severity_preset,
+                fail_on,
+            )?;
+        }
+        Commands::ChangelogCoverage {
+            from,
+            to,
+            path,
+            min_commits,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_changelog_coverage(repo_path, &cli.format, &from, &to, min_commits)?;
+        }
+        Commands::Tasks {
+            from,
+            to,
+            path,
+            markdown,
+            errors_only,
+            severity_preset,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_tasks(
+                repo_path,
+                &cli.format,
+                &from,
+                &to,
+                markdown,
+                errors_only,
+                severity_preset,
+            )?;
+        }
+        Commands::Crate {
+            crate_name,
+            path,
+            history,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_crate(repo_path, &cli.format, &crate_name, history)?;
+        }
+        Commands::Tree {
+            crate_name,
+            path,
+            depth,
+            invert,
+            no_dev,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            let options = deptrack::TreeOptions::new()
+                .max_depth(depth)
+                .invert(invert)
+                .no_dev(no_dev);
+            handle_tree(repo_path, &cli.format, crate_name.as_deref(), &options)?;
+        }
+        Commands::TestPlan { from, to, path } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_test_plan(repo_path, &cli.format, &from, &to)?;
+        }
+        Commands::CiMatrix {
+            from,
+            to,
+            path,
+            provider,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_ci_matrix(repo_path, &from, &to, provider)?;
+        }
+        Commands::Doctor { path } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            handle_doctor(repo_path, &cli.format)?;
+        }
+        Commands::Git { command } => {
+            handle_git(&cli.path, &cli.format, command)?;
+        }
+        Commands::Fs { command } => {
+            handle_fs(&cli.format, command)?;
+        }
+        Commands::Daemon { path } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            let mut daemon =
+                deptrack::Daemon::new(repo_path).context("failed to build dependency graph")?;
+            daemon
+                .run(std::io::stdin().lock(), std::io::stdout().lock())
+                .context("failed to serve requests")?;
+        }
+        Commands::Manifest { command } => {
+            handle_manifest(&cli.path, &cli.format, command)?;
+        }
+        Commands::Fingerprint { command } => {
+            handle_fingerprint(&cli.path, &cli.format, command)?;
+        }
+        Commands::Deps { command } => {
+            handle_deps(&cli.path, &cli.format, command)?;
+        }
+        Commands::Export { command } => {
+            handle_export(&cli.path, &cli.format, command)?;
+        }
+        Commands::Github { command } => {
+            handle_github(&cli.format, command)?;
+        }
+        Commands::Release { command } => {
+            handle_release(&cli.path, &cli.format, command)?;
+        }
+        #[cfg(feature = "serve")]
+        Commands::Serve { path, bind, report } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            println!("serving the dashboard on http://{}", bind);
+            deptrack::DashboardServer::new(repo_path)
+                .report_path(report)
+                .serve(&bind)
+                .with_context(|| format!("failed to serve on {}", bind))?;
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui { path } => {
+            let repo_path = path.as_ref().unwrap_or(&cli.path);
+            deptrack::utils::tui::run(repo_path).context("failed to run the terminal ui")?;
+        }
+        Commands::Graph {
+            output,
+            file,
+            open,
+            per_workspace,
+        } => {
+            handle_graph(&cli.path, &cli.format, output, file, open, per_workspace)?;
+        }
+        #[cfg(debug_assertions)]
+        Commands::Workspaces => {
+            handle_debug_workspaces(&cli.path, &cli.format)?;
+        }
+        #[cfg(debug_assertions)]
+        Commands::Crates { deps } => {
+            handle_debug_crates(&cli.path, &cli.format, deps)?;
+        }
+        #[cfg(debug_assertions)]
+        Commands::Stats { sort_by } => {
+            handle_debug_stats(&cli.path, &cli.format, sort_by)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_analyze(
+    path: &PathBuf,
+    format: &OutputFormat,
+    show_graph: bool,
+    since: Option<&str>,
+    verbose: bool,
+    show_sccs: bool,
+) -> Result<()> {
+    // canonicalize path for display
+    let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+
+    let health = compute_health(path, &graph, since)?;
+    let sccs: Vec<_> = if show_sccs {
+        graph
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| !component.is_trivial())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
             let stats = graph.get_statistics();
+            let mut output = serde_json::json!({
+                "repository_path": repo_path,
+                "workspaces": graph.workspaces.len(),
+                "crates": graph.crates.len(),
+                "has_cycles": stats.has_cycles,
+                "cycles": graph.production_cycle_details(),
+                "statistics": stats,
+                "health": health,
+            });
+            if show_sccs {
+                output["sccs"] = serde_json::to_value(&sccs)?;
+            }
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            let stats = graph.get_statistics();
+
+            // display repository structure
+            graph.display_repository_structure(&repo_path);
+
+            // display dependency summary
+            graph.display_dependency_summary();
+
+            // always display cycles if detected
+            if stats.has_cycles {
+                println!();
+                println!("warning: cyclic dependencies detected");
+                println!();
+                graph.display_cycles();
+            }
+
+            if show_sccs {
+                println!();
+                if sccs.is_empty() {
+                    println!("strongly connected components: none, no crates depend on each other");
+                } else {
+                    println!("strongly connected components: {}", sccs.len());
+                }
+                for component in &sccs {
+                    println!("  scc {}: {}", component.id, component.members.join(", "));
+                    for edge in &component.edges {
+                        println!("    {} -> {}:", edge.from, edge.to);
+                        for declaration in &edge.declarations {
+                            println!("      {}", declaration);
+                        }
+                    }
+                }
+            }
+
+            if show_graph {
+                println!();
+                println!("additional graph statistics:");
+                println!("  crate count: {}", stats.crate_count);
+                println!("  cycle count: {}", stats.cycle_count);
+            }
+
+            println!();
+            health.display(verbose);
+        }
+    }
+
+    Ok(())
+}
+
+// health report of the repository, with bump compliance when a base ref is given
+fn compute_health(
+    path: &PathBuf,
+    graph: &CrateDependencyGraph,
+    since: Option<&str>,
+) -> Result<deptrack::HealthReport> {
+    use deptrack::{DeptrackConfig, GitRef, HealthScorer};
+
+    let config = DeptrackConfig::load_or_default(path);
+    let bumps = match since {
+        Some(since) => {
+            let base_ref = GitRef::from_string(since);
+            let impact = graph
+                .analyze_git_changes_with_config(path, &base_ref, &GitRef::Head, &config.impact)
+                .context("failed to analyze git changes")?;
+            Some(
+                graph
+                    .analyze_version_bumps_with_config(
+                        path,
+                        &base_ref,
+                        &impact.all_affected_crates,
+                        &impact.directly_affected_crates,
+                        &config,
+                    )
+                    .context("failed to analyze version bumps")?,
+            )
+        }
+        None => None,
+    };
+
+    HealthScorer::analyze(graph, path, &config, bumps.as_ref())
+        .context("failed to compute health score")
+}
+
+fn handle_check_versions(
+    default_path: &PathBuf,
+    format: &OutputFormat,
+    args: &CheckVersionsArgs,
+) -> Result<()> {
+    use deptrack::utils::timings;
+    use deptrack::{
+        AnalysisCache, BaseRef, ChangelogChecker, DeptrackConfig, ExternalChecker, FIXES_DIR,
+        FeatureChecker, FixPlanner, GitRef, LockfileChecker, ManifestChecker, MembershipChecker,
+        MsrvChecker, Notifier, NotifySummary, RequirementSync,
+    };
+
+    if args.timings {
+        timings::enable();
+    }
+
+    let path = args.path.as_ref().unwrap_or(default_path);
+    let (skip_changelog, all_crates, verbose) =
+        (args.skip_changelog, args.all_crates, args.verbose);
+    let notify = &args.notify;
+
+    // canonicalize path for display
+    let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+    // load configuration
+    let mut config = DeptrackConfig::load_or_default(path);
+    if args.max_depth.is_some() {
+        config.impact.max_transitive_depth = args.max_depth;
+    }
+    if let Some(preset) = args.severity_preset {
+        config.use_severity_preset(preset);
+    }
+    use_strict_parse(&mut config);
+
+    // build dependency graph, broken manifests are reported with membership
+    let graph = discover_graph(path).context("failed to build dependency graph")?;
+
+    // without FROM the base is detected, from the upstream branch or the last
+    // release of a workspace crate
+    let base_ref = match (&args.changed_since, &args.from) {
+        (None, None) => {
+            let names: Vec<String> = graph.crates.keys().map(|id| id.name.clone()).collect();
+            let detected = BaseRef::detect(path, &config.releases.tag_pattern, &names)
+                .context("failed to detect the base reference")?;
+            Some(detected.ok_or_else(|| {
+                fail(
+                    Exit::Usage,
+                    "no base reference given and none detected (no upstream branch HEAD is \
+                     ahead of, no release tag), pass FROM",
+                )
+            })?)
+        }
+        _ => None,
+    };
+    // --changed-since compares against the working directory
+    let (from_ref_str, to_ref_str) = match &args.changed_since {
+        Some(since) => (since.as_str(), "WORKING_DIR"),
+        None => (
+            args.from
+                .as_deref()
+                .or(base_ref.as_ref().map(|base| base.git_ref.as_str()))
+                .unwrap_or_default(),
+            args.to.as_deref().unwrap_or("HEAD"),
+        ),
+    };
+
+    // command line options take precedence over the environment, which takes
+    // precedence over [notify] in deptrack.toml
+    let notifier = notify
+        .notify_webhook
+        .clone()
+        .or_else(|| std::env::var("DEPTRACK_NOTIFY_WEBHOOK").ok())
+        .filter(|url| !url.is_empty())
+        .or_else(|| config.notify.webhook.clone())
+        .map(|url| {
+            let notifier = Notifier::new(url);
+            match notify
+                .notify_template
+                .as_ref()
+                .or(config.notify.template.as_ref())
+            {
+                Some(template) => notifier.template(template),
+                None => notifier,
+            }
+        });
+    let mut summary = NotifySummary {
+        from: from_ref_str.to_string(),
+        to: to_ref_str.to_string(),
+        report_url: notify
+            .report_url
+            .clone()
+            .or_else(|| config.notify.report_url.clone()),
+        ..Default::default()
+    };
+    // a failed notification is reported but does not change the outcome
+    let send = |summary: &NotifySummary| {
+        if let Some(notifier) = &notifier
+            && let Err(e) = notifier.send(summary)
+        {
+            eprintln!("warning: {}", e);
+        }
+    };
+
+    // analyze changes between refs
+    let from_ref = GitRef::from_string(from_ref_str);
+
+    let to_ref = GitRef::from_string(to_ref_str);
+
+    // results between two commits only change with the config, uncommitted
+    // changes make them uncacheable, and so does a --scope the cache does not
+    // record; a ref that does not resolve is reported by the analysis itself
+    let cache = if args.no_cache || args.changed_since.is_some() || SCOPE.get().is_some() {
+        None
+    } else {
+        AnalysisCache::for_refs(path, &from_ref, &to_ref, &config)
+            .ok()
+            .flatten()
+    };
+    let (impact_analysis, cached_versions) = match cache.as_ref().and_then(AnalysisCache::load) {
+        Some((impact, versions)) => (impact, Some(versions)),
+        None => {
+            let impact = match &args.changed_since {
+                Some(_) => graph.analyze_changes_since(path, &from_ref, &config.impact),
+                None => {
+                    graph.analyze_git_changes_with_config(path, &from_ref, &to_ref, &config.impact)
+                }
+            }
+            .context("failed to analyze git changes")?;
+            (impact, None)
+        }
+    };
+
+    // json lines consumers get every crate and issue as soon as its check ran
+    let mut stream = matches!(format, OutputFormat::Jsonl).then(JsonlWriter::stdout);
+    // every issue, listed in the report so runs can be compared
+    let mut issues: Vec<ReportIssue> = Vec::new();
+    // warnings the last runs all reported are escalated to errors
+    let mut history = match config.severity.escalate_after {
+        Some(runs) => Some(
+            deptrack::IssueHistory::load(path, runs).context("failed to read the issue history")?,
+        ),
+        None => None,
+    };
+
+    // find crates no workspace lists, members that point nowhere and
+    // manifests that do not parse; this concerns the whole repository, not
+    // what changed, so it runs even when nothing did
+    let mut membership_analysis = MembershipChecker::analyze(&graph, path, &config.direct_severity)
+        .context("failed to check workspace membership")?;
+    // crates still depending on one the changes removed fail to load as well
+    MembershipChecker::check_removed_crates(
+        &mut membership_analysis,
+        &graph,
+        path,
+        &impact_analysis.removed_crates,
+        &config.direct_severity,
+    );
+    escalate(
+        history.as_ref(),
+        "membership",
+        &mut membership_analysis.statuses,
+        |s| &mut s.issues,
+        &mut membership_analysis.total_errors,
+        &mut membership_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("membership", &membership_analysis.statuses, |s| &s.issues),
+    )?;
+
+    if impact_analysis.changed_files.is_empty() {
+        match format {
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+                let mut output = serde_json::json!({
+                    "from": from_ref_str,
+                    "to": to_ref_str,
+                    "repository_path": repo_path,
+                    "changed_files": 0,
+                    "directly_affected": 0,
+                    "total_affected": 0,
+                    "bumped": 0,
+                    "needing_bump": 0,
+                    "bump_percentage": 100.0,
+                    "all_bumped": true,
+                    "changelog_skipped": skip_changelog,
+                });
+                output["severity"] = serde_json::to_value(check_versions_severity(
+                    &config,
+                    skip_changelog,
+                    &[],
+                    &[],
+                ))?;
+                if let Some(base) = &base_ref {
+                    output["base_ref"] = serde_json::to_value(base)?;
+                }
+                if let Some(ignore) = &config.impact.ignore {
+                    output["ignore_filter"] = serde_json::to_value(ignore)?;
+                }
+                if args.timings {
+                    output["timings"] = serde_json::to_value(timings::take())?;
+                }
+                output["membership"] = membership_json(&membership_analysis);
+                if stream.is_none() {
+                    output["issues"] = serde_json::to_value(&issues)?;
+                }
+                match &mut stream {
+                    Some(stream) => stream.record("summary", &output)?,
+                    None => println!("{}", format.render(&output)?),
+                }
+            }
+            OutputFormat::Human => {
+                println!(
+                    "checking version bumps between {} and {}",
+                    from_ref_str, to_ref_str
+                );
+                if let Some(base) = &base_ref {
+                    println!("base: {}", base);
+                }
+                println!("repository: {}", repo_path.display());
+                println!();
+                println!(
+                    "no changes detected between {} and {}",
+                    from_ref_str, to_ref_str
+                );
+                display_membership_summary(&membership_analysis, verbose);
+                if args.timings {
+                    println!();
+                    timings::display(&timings::take());
+                }
+            }
+        }
+        save_history(&mut history, &issues);
+        summary.errors = membership_analysis.total_errors;
+        summary.warnings = membership_analysis.total_warnings;
+        send(&summary);
+        return check_outcome(
+            format,
+            args.fail_on,
+            membership_analysis.total_errors,
+            membership_analysis.total_warnings,
+        );
+    }
+
+    // analyze version bumps
+    let mut version_analysis = match cached_versions {
+        Some(versions) => versions,
+        None => {
+            let versions = timings::time("version analysis", || {
+                graph.analyze_version_bumps_with_config(
+                    path,
+                    &from_ref,
+                    &impact_analysis.all_affected_crates,
+                    &impact_analysis.directly_affected_crates,
+                    &config,
+                )
+            })
+            .context("failed to analyze version bumps")?;
+            // a cache that cannot be written only costs the next run time
+            if let Some(cache) = &cache
+                && let Err(e) = cache.store(&impact_analysis, &versions)
+            {
+                eprintln!("warning: could not write {}: {}", cache.path().display(), e);
+            }
+            versions
+        }
+    };
+    escalate(
+        history.as_ref(),
+        "version",
+        &mut version_analysis.crate_versions,
+        |s| &mut s.issues,
+        &mut version_analysis.total_errors,
+        &mut version_analysis.total_warnings,
+    );
+    if let Some(stream) = &mut stream {
+        stream_crates(stream, &version_analysis)?;
+    }
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("version", &version_analysis.crate_versions, |s| &s.issues),
+    )?;
+
+    // analyze changelogs if not skipped
+    let changelog_analysis = if !skip_changelog {
+        let mut analysis = timings::time("changelog analysis", || {
+            if all_crates {
+                ChangelogChecker::analyze_all(
+                    &graph,
+                    path,
+                    &config.changelog,
+                    &config.direct_severity,
+                )
+            } else {
+                ChangelogChecker::analyze_for_changes(
+                    &graph,
+                    path,
+                    &config.changelog,
+                    &config.direct_severity,
+                    &config.transitive_severity,
+                    &version_analysis,
+                    &impact_analysis,
+                )
+            }
+        })
+        .context("failed to analyze changelogs")?;
+        escalate(
+            history.as_ref(),
+            "changelog",
+            &mut analysis.statuses,
+            |s| &mut s.issues,
+            &mut analysis.total_errors,
+            &mut analysis.total_warnings,
+        );
+        record_issues(
+            &mut issues,
+            &mut stream,
+            args.fail_fast,
+            ReportIssue::collect("changelog", &analysis.statuses, |s| &s.issues),
+        )?;
+        Some(analysis)
+    } else {
+        None
+    };
+
+    // check publish-readiness of the affected crates' manifests
+    let mut manifest_analysis = if all_crates {
+        ManifestChecker::analyze_all(&graph, &config.direct_severity)
+    } else {
+        ManifestChecker::analyze(
+            &graph,
+            &impact_analysis.all_affected_crates,
+            &impact_analysis.directly_affected_crates,
+            &config.direct_severity,
+            &config.transitive_severity,
+        )
+    }
+    .context("failed to check crate manifests")?;
+    escalate(
+        history.as_ref(),
+        "manifest",
+        &mut manifest_analysis.statuses,
+        |s| &mut s.issues,
+        &mut manifest_analysis.total_errors,
+        &mut manifest_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("manifest", &manifest_analysis.statuses, |s| &s.issues),
+    )?;
+
+    // check that the affected crates agree on their rust-version
+    let mut msrv_analysis = if all_crates {
+        MsrvChecker::analyze_all(&graph, &config.direct_severity, &config.msrv)
+    } else {
+        MsrvChecker::analyze(
+            &graph,
+            &impact_analysis.all_affected_crates,
+            &impact_analysis.directly_affected_crates,
+            &config.direct_severity,
+            &config.transitive_severity,
+            &config.msrv,
+        )
+    }
+    .context("failed to check rust-version consistency")?;
+    escalate(
+        history.as_ref(),
+        "msrv",
+        &mut msrv_analysis.statuses,
+        |s| &mut s.issues,
+        &mut msrv_analysis.total_errors,
+        &mut msrv_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("msrv", &msrv_analysis.statuses, |s| &s.issues),
+    )?;
+
+    // audit the [features] tables of the affected crates
+    let mut feature_analysis = if all_crates {
+        FeatureChecker::analyze_all(&graph, &config.direct_severity)
+    } else {
+        FeatureChecker::analyze(
+            &graph,
+            &impact_analysis.all_affected_crates,
+            &impact_analysis.directly_affected_crates,
+            &config.direct_severity,
+            &config.transitive_severity,
+        )
+    }
+    .context("failed to audit crate features")?;
+    escalate(
+        history.as_ref(),
+        "features",
+        &mut feature_analysis.statuses,
+        |s| &mut s.issues,
+        &mut feature_analysis.total_errors,
+        &mut feature_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("features", &feature_analysis.statuses, |s| &s.issues),
+    )?;
+
+    // check that Cargo.lock agrees with the affected crates' versions
+    let mut lockfile_analysis = if all_crates {
+        LockfileChecker::analyze_all(&graph, path, &config.direct_severity)
+    } else {
+        LockfileChecker::analyze(
+            &graph,
+            path,
+            &impact_analysis.all_affected_crates,
+            &impact_analysis.directly_affected_crates,
+            &config.direct_severity,
+            &config.transitive_severity,
+        )
+    }
+    .context("failed to check Cargo.lock consistency")?;
+    escalate(
+        history.as_ref(),
+        "lockfile",
+        &mut lockfile_analysis.statuses,
+        |s| &mut s.issues,
+        &mut lockfile_analysis.total_errors,
+        &mut lockfile_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("lockfile", &lockfile_analysis.statuses, |s| &s.issues),
+    )?;
+
+    // run the external checks registered in deptrack.toml
+    let mut check_analysis = if all_crates {
+        ExternalChecker::analyze_all(&graph, path, &config.checks, &config.direct_severity)
+    } else {
+        ExternalChecker::analyze(
+            &graph,
+            path,
+            &config.checks,
+            &impact_analysis.all_affected_crates,
+            &impact_analysis.directly_affected_crates,
+            &config.direct_severity,
+            &config.transitive_severity,
+        )
+    }
+    .context("failed to run external checks")?;
+    escalate(
+        history.as_ref(),
+        "checks",
+        &mut check_analysis.statuses,
+        |s| &mut s.issues,
+        &mut check_analysis.total_errors,
+        &mut check_analysis.total_warnings,
+    );
+    record_issues(
+        &mut issues,
+        &mut stream,
+        args.fail_fast,
+        ReportIssue::collect("checks", &check_analysis.statuses, |s| &s.issues),
+    )?;
+
+    save_history(&mut history, &issues);
+
+    // mechanical fixes as patches to review, requirements only on the
+    // crates checked
+    let patches = if args.write_patches || args.emit_patches {
+        let mut requirements =
+            RequirementSync::analyze(&graph).context("failed to check internal requirements")?;
+        if !all_crates {
+            let affected = &impact_analysis.all_affected_crates;
+            requirements
+                .stale
+                .retain(|stale| affected.contains(&stale.dependency));
+            requirements
+                .missing
+                .retain(|missing| affected.contains(&missing.dependency));
+        }
+        let patches = FixPlanner::plan(
+            &graph,
+            path,
+            &version_analysis,
+            changelog_analysis.as_ref(),
+            &config.changelog,
+            &requirements,
+        )
+        .context("failed to prepare fix patches")?;
+        if args.write_patches {
+            FixPlanner::write_patches(&patches, &path.join(FIXES_DIR))
+                .context("failed to write fix patches")?;
+        }
+        Some(patches)
+    } else {
+        None
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let mut output = serde_json::json!({
+                "from": from_ref_str,
+                "to": to_ref_str,
+                "repository_path": repo_path,
+                "changed_files": impact_analysis.changed_files.len(),
+                "directly_affected": impact_analysis.directly_affected_crates.len(),
+                "total_affected": impact_analysis.all_affected_crates.len(),
+                "bumped": version_analysis.crates_bumped.len(),
+                "needing_bump": version_analysis.crates_needing_bump.len(),
+                "bump_percentage": version_analysis.bump_percentage(),
+                "all_bumped": version_analysis.all_bumped(),
+            });
+            let checked: Vec<deptrack::CrateId> = if all_crates {
+                graph.crates.keys().cloned().collect()
+            } else {
+                impact_analysis.all_affected_crates.clone()
+            };
+            let direct = if all_crates {
+                &checked
+            } else {
+                &impact_analysis.directly_affected_crates
+            };
+            output["severity"] = serde_json::to_value(check_versions_severity(
+                &config,
+                skip_changelog,
+                &checked,
+                direct,
+            ))?;
+
+            if let Some(base) = &base_ref {
+                output["base_ref"] = serde_json::to_value(base)?;
+            }
+
+            if let Some(ignore) = &config.impact.ignore {
+                output["ignore_filter"] = serde_json::to_value(ignore)?;
+            }
+
+            output["crate_changes"] =
+                serde_json::to_value(impact_analysis.changed_files_by_crate())?;
+            // rebuilding follows every dependency, releasing only those a
+            // published crate keeps
+            output["needs_rebuild"] =
+                serde_json::json!(display_names(&impact_analysis.needs_rebuild));
+            output["needs_release"] =
+                serde_json::json!(display_names(&impact_analysis.needs_release));
+
+            if !impact_analysis.dev_only_affected.is_empty() {
+                output["dev_only_affected"] =
+                    serde_json::json!(display_names(&impact_analysis.dev_only_affected));
+            }
+            if !impact_analysis.beyond_max_depth.is_empty() {
+                output["beyond_max_depth"] =
+                    serde_json::json!(display_names(&impact_analysis.beyond_max_depth));
+            }
+            if !impact_analysis.boundary_crates.is_empty() {
+                output["boundary_crates"] =
+                    serde_json::json!(display_names(&impact_analysis.boundary_crates));
+            }
+            if !impact_analysis.ignored_manifest_crates.is_empty() {
+                output["ignored_manifest_crates"] =
+                    serde_json::json!(display_names(&impact_analysis.ignored_manifest_crates));
+            }
+            if !impact_analysis.generated_files.is_empty() {
+                output["generated_files"] = serde_json::json!(impact_analysis.generated_files);
+            }
+            let new_crates = version_analysis.new_crates();
+            if !new_crates.is_empty() {
+                output["new_crates"] = serde_json::json!(display_names(&new_crates));
+            }
+            if !impact_analysis.removed_crates.is_empty() {
+                output["removed_crates"] = impact_analysis
+                    .removed_crates
+                    .iter()
+                    .map(|removed| {
+                        serde_json::json!({
+                            "name": removed.name,
+                            "version": removed.version,
+                            "path": removed.path,
+                            "referenced_by": display_names(&removed.referenced_by),
+                        })
+                    })
+                    .collect();
+            }
+
+            if !impact_analysis.manifest_changes.is_empty() {
+                let manifest_changes: std::collections::BTreeMap<String, Vec<String>> =
+                    impact_analysis
+                        .manifest_changes
+                        .iter()
+                        .map(|(id, diff)| (id.display_name(), diff.summary()))
+                        .collect();
+                output["manifest_changes"] = serde_json::to_value(manifest_changes)?;
+            }
+
+            // add version bump error/warning counts
+            output["version_bump_errors"] = serde_json::json!(version_analysis.total_errors);
+            output["version_bump_warnings"] = serde_json::json!(version_analysis.total_warnings);
+
+            output["manifest"] = serde_json::json!({
+                "analyzed_crates": manifest_analysis.statuses.len(),
+                "incomplete_crates": manifest_analysis
+                    .incomplete_crates
+                    .iter()
+                    .map(|id| id.display_name())
+                    .collect::<Vec<_>>(),
+                "unversioned_crates": display_names(&manifest_analysis.unversioned_crates),
+                "total_errors": manifest_analysis.total_errors,
+                "total_warnings": manifest_analysis.total_warnings,
+                "all_complete": manifest_analysis.all_complete(),
+            });
+
+            output["msrv"] = serde_json::json!({
+                "analyzed_crates": msrv_analysis.statuses.len(),
+                "mismatched_crates": msrv_analysis
+                    .mismatched_crates
+                    .iter()
+                    .map(|id| id.display_name())
+                    .collect::<Vec<_>>(),
+                "total_errors": msrv_analysis.total_errors,
+                "total_warnings": msrv_analysis.total_warnings,
+                "all_consistent": msrv_analysis.all_consistent(),
+            });
+
+            output["features"] = serde_json::json!({
+                "analyzed_crates": feature_analysis.statuses.len(),
+                "crates_with_issues": feature_analysis
+                    .crates_with_issues
+                    .iter()
+                    .map(|id| id.display_name())
+                    .collect::<Vec<_>>(),
+                "total_errors": feature_analysis.total_errors,
+                "total_warnings": feature_analysis.total_warnings,
+                "all_valid": feature_analysis.all_valid(),
+            });
+
+            output["lockfile"] = serde_json::json!({
+                "analyzed_crates": lockfile_analysis.statuses.len(),
+                "mismatched_crates": lockfile_analysis
+                    .mismatched_crates
+                    .iter()
+                    .map(|id| id.display_name())
+                    .collect::<Vec<_>>(),
+                "total_errors": lockfile_analysis.total_errors,
+                "total_warnings": lockfile_analysis.total_warnings,
+                "all_consistent": lockfile_analysis.all_consistent(),
+            });
+
+            output["membership"] = membership_json(&membership_analysis);
+
+            if !config.checks.is_empty() {
+                output["checks"] = serde_json::json!({
+                    "analyzed_crates": check_analysis.statuses.len(),
+                    "crates_with_issues": check_analysis
+                        .crates_with_issues
+                        .iter()
+                        .map(|id| id.display_name())
+                        .collect::<Vec<_>>(),
+                    "total_errors": check_analysis.total_errors,
+                    "total_warnings": check_analysis.total_warnings,
+                    "all_passed": check_analysis.all_passed(),
+                });
+            }
+
+            if let Some(ref analysis) = changelog_analysis {
+                output["changelog"] = serde_json::json!({
+                    "analyzed_crates": analysis.statuses.len(),
+                    "valid_changelogs": analysis.crates_with_valid_changelog.len(),
+                    "missing_changelogs": analysis.crates_missing_changelog.len(),
+                    "needing_updates": analysis.crates_needing_changelog_update.len(),
+                    "total_issues": analysis.total_issues,
+                    "total_errors": analysis.total_errors,
+                    "total_warnings": analysis.total_warnings,
+                    "compliance_percentage": analysis.compliance_percentage(),
+                    "all_valid": analysis.all_valid(),
+                });
+            } else {
+                output["changelog_skipped"] = serde_json::json!(true);
+            }
+
+            output["analysis_failures"] = serde_json::to_value(analysis_failures(&issues))?;
+
+            if let Some(patches) = &patches {
+                output["fix_patches"] = patches
+                    .iter()
+                    .map(|patch| {
+                        let mut entry = serde_json::json!({
+                            "crate": patch.crate_id.as_ref().map(|id| id.display_name()),
+                            "fixes": patch.fixes,
+                            "files": patch.files,
+                        });
+                        if args.write_patches {
+                            entry["path"] = serde_json::json!(
+                                std::path::Path::new(FIXES_DIR).join(&patch.file_name)
+                            );
+                        }
+                        if args.emit_patches {
+                            entry["diff"] = serde_json::json!(patch.diff);
+                        }
+                        entry
+                    })
+                    .collect();
+            }
+
+            if args.timings {
+                output["timings"] = serde_json::to_value(timings::take())?;
+            }
+
+            // streamed as records already
+            if stream.is_none() {
+                output["issues"] = serde_json::to_value(&issues)?;
+            }
+
+            match &mut stream {
+                Some(stream) => stream.record("summary", &output)?,
+                None => println!("{}", format.render(&output)?),
+            }
+        }
+        OutputFormat::Human => {
+            println!(
+                "checking version bumps between {} and {}",
+                from_ref_str, to_ref_str
+            );
+            if let Some(base) = &base_ref {
+                println!("base: {}", base);
+            }
+            println!("repository: {}", repo_path.display());
+            println!();
+
+            println!("detected changes:");
+            println!("  files changed: {}", impact_analysis.changed_files.len());
+            println!(
+                "  directly affected crates: {}",
+                impact_analysis.directly_affected_crates.len()
+            );
+            println!(
+                "  total affected crates: {}",
+                impact_analysis.all_affected_crates.len()
+            );
+            println!(
+                "  crates to rebuild: {}",
+                impact_analysis.needs_rebuild.len()
+            );
+            println!(
+                "  crates to release: {}",
+                impact_analysis.needs_release.len()
+            );
+            if !impact_analysis.dev_only_affected.is_empty() {
+                println!(
+                    "  dev-only dependents (not affected): {}",
+                    display_names(&impact_analysis.dev_only_affected).join(", ")
+                );
+            }
+            if !impact_analysis.boundary_crates.is_empty() {
+                println!(
+                    "  stopped at (pinned or re-exported): {}",
+                    display_names(&impact_analysis.boundary_crates).join(", ")
+                );
+            }
+            if !impact_analysis.ignored_manifest_crates.is_empty() {
+                println!(
+                    "  only ignored manifest sections changed (not affected): {}",
+                    display_names(&impact_analysis.ignored_manifest_crates).join(", ")
+                );
+            }
+            if !impact_analysis.generated_files.is_empty() {
+                println!(
+                    "  generated files (not affected): {}",
+                    impact_analysis.generated_files.len()
+                );
+            }
+            let new_crates = version_analysis.new_crates();
+            if !new_crates.is_empty() {
+                println!("  new crates: {}", display_names(&new_crates).join(", "));
+            }
+            if !impact_analysis.removed_crates.is_empty() {
+                let removed: Vec<String> = impact_analysis
+                    .removed_crates
+                    .iter()
+                    .map(|removed| format!("{} ({})", removed.name, removed.path.display()))
+                    .collect();
+                println!("  removed crates: {}", removed.join(", "));
+            }
+            if let Some(depth) = config.impact.max_transitive_depth
+                && !impact_analysis.beyond_max_depth.is_empty()
+            {
+                println!(
+                    "  note: not checking dependents more than {} dependencies away: {}",
+                    depth,
+                    display_names(&impact_analysis.beyond_max_depth).join(", ")
+                );
+            }
+            println!();
+
+            if !impact_analysis.manifest_changes.is_empty() {
+                let mut manifest_changes: Vec<_> =
+                    impact_analysis.manifest_changes.iter().collect();
+                manifest_changes.sort_by_key(|(id, _)| id.display_name());
+
+                println!("manifest changes:");
+                for (id, diff) in manifest_changes {
+                    println!("  {}:", id.display_name());
+                    for line in diff.summary() {
+                        println!("    - {}", line);
+                    }
+                }
+                println!();
+            }
+
+            version_analysis.display_table();
+
+            if verbose && (version_analysis.total_errors > 0 || version_analysis.total_warnings > 0)
+            {
+                println!();
+                version_analysis.display_issues();
+            }
+
+            if !manifest_analysis.all_complete() {
+                println!();
+                if !manifest_analysis.incomplete_crates.is_empty() {
+                    println!(
+                        "incomplete manifests: {} publishable crate(s) missing required metadata",
+                        manifest_analysis.incomplete_crates.len()
+                    );
+                }
+                if !manifest_analysis.unversioned_crates.is_empty() {
+                    println!(
+                        "unversioned path dependencies: {} publishable crate(s) depend on a path without a version",
+                        manifest_analysis.unversioned_crates.len()
+                    );
+                }
+                if verbose {
+                    println!();
+                    manifest_analysis.display_issues();
+                }
+            }
+
+            if !msrv_analysis.all_consistent() {
+                println!();
+                println!(
+                    "msrv mismatches: {} crate(s) with an inconsistent rust-version",
+                    msrv_analysis.mismatched_crates.len()
+                );
+                if verbose {
+                    println!();
+                    msrv_analysis.display_issues();
+                }
+            }
+
+            if !feature_analysis.all_valid() {
+                println!();
+                println!(
+                    "feature problems: {} crate(s) with invalid or unexposed features",
+                    feature_analysis.crates_with_issues.len()
+                );
+                if verbose {
+                    println!();
+                    feature_analysis.display_issues();
+                }
+            }
+
+            if !lockfile_analysis.all_consistent() {
+                println!();
+                println!(
+                    "lock file mismatches: {} crate(s) with a stale Cargo.lock entry",
+                    lockfile_analysis.mismatched_crates.len()
+                );
+                if verbose {
+                    println!();
+                    lockfile_analysis.display_issues();
+                }
+            }
+
+            display_membership_summary(&membership_analysis, verbose);
+
+            if !check_analysis.all_passed() {
+                println!();
+                println!(
+                    "external checks: {} crate(s) with reported issues",
+                    check_analysis.crates_with_issues.len()
+                );
+                if verbose {
+                    println!();
+                    check_analysis.display_issues();
+                }
+            }
+
+            if let Some(ref analysis) = changelog_analysis {
+                println!();
+                analysis.display_table();
+
+                if verbose && analysis.total_issues > 0 {
+                    println!();
+                    analysis.display_issues();
+                }
+            }
+
+            let failures = analysis_failures(&issues);
+            if !failures.is_empty() {
+                println!();
+                println!(
+                    "analysis failures: {} crate check(s) could not be run",
+                    failures.len()
+                );
+                for failure in failures {
+                    println!(
+                        "  [{}] {}: {}",
+                        failure.check, failure.crate_name, failure.message
+                    );
+                }
+            }
+
+            if verbose {
+                // the working directory has no commits of its own
+                let to = match to_ref_str {
+                    "WORKING_DIR" => "HEAD",
+                    to => to,
+                };
+                display_failing_authors(path, &graph, &issues, from_ref_str, to);
+            }
+
+            match &patches {
+                Some(patches) if patches.is_empty() => {
+                    println!();
+                    println!("no mechanical fixes to patch");
+                }
+                Some(patches) if args.emit_patches => {
+                    for patch in patches {
+                        println!();
+                        print!("{}", patch.contents());
+                    }
+                }
+                Some(patches) => {
+                    println!();
+                    println!(
+                        "wrote {} patch(es) to {}, review and apply with `git apply`:",
+                        patches.len(),
+                        FIXES_DIR
+                    );
+                    for patch in patches {
+                        println!("  {}: {}", patch.file_name, patch.fixes.join("; "));
+                    }
+                }
+                None => {}
+            }
+
+            if args.timings {
+                println!();
+                timings::display(&timings::take());
+            }
+        }
+    }
+
+    // check for errors and fail if any are present
+    let total_errors = version_analysis.total_errors
+        + manifest_analysis.total_errors
+        + msrv_analysis.total_errors
+        + feature_analysis.total_errors
+        + lockfile_analysis.total_errors
+        + membership_analysis.total_errors
+        + check_analysis.total_errors
+        + changelog_analysis
+            .as_ref()
+            .map(|a| a.total_errors)
+            .unwrap_or(0);
+
+    let total_warnings = version_analysis.total_warnings
+        + manifest_analysis.total_warnings
+        + msrv_analysis.total_warnings
+        + feature_analysis.total_warnings
+        + lockfile_analysis.total_warnings
+        + membership_analysis.total_warnings
+        + check_analysis.total_warnings
+        + changelog_analysis
+            .as_ref()
+            .map(|a| a.total_warnings)
+            .unwrap_or(0);
+
+    summary.errors = total_errors;
+    summary.warnings = total_warnings;
+    summary.affected_crates = impact_analysis
+        .all_affected_crates
+        .iter()
+        .map(|id| id.display_name())
+        .collect();
+    summary.affected_crates.sort();
+    send(&summary);
+
+    check_outcome(format, args.fail_on, total_errors, total_warnings)
+}
+
+// the membership section of the check-versions json report
+fn membership_json(analysis: &deptrack::MembershipAnalysis) -> serde_json::Value {
+    let mut crates: Vec<String> = analysis
+        .statuses
+        .keys()
+        .map(|id| id.display_name())
+        .collect();
+    crates.sort();
+    serde_json::json!({
+        "unlisted_crates": display_names(&analysis.unlisted_crates),
+        "missing_members": display_names(&analysis.missing_members),
+        "broken_manifests": display_names(&analysis.broken_manifests),
+        "stale_references": display_names(&analysis.stale_references),
+        "crates_with_issues": crates,
+        "total_errors": analysis.total_errors,
+        "total_warnings": analysis.total_warnings,
+        "all_listed": analysis.all_listed(),
+        "all_parsed": analysis.all_parsed(),
+    })
+}
+
+// counts of the membership problems, and with `verbose` every issue
+fn display_membership_summary(membership_analysis: &deptrack::MembershipAnalysis, verbose: bool) {
+    if !membership_analysis.all_listed()
+        || !membership_analysis.all_parsed()
+        || !membership_analysis.no_stale_references()
+    {
+        println!();
+        if !membership_analysis.all_listed() {
+            println!(
+                "workspace membership: {} crate(s) outside every workspace, {} member(s) matching no crate",
+                membership_analysis.unlisted_crates.len(),
+                membership_analysis.missing_members.len()
+            );
+        }
+        if !membership_analysis.all_parsed() {
+            println!(
+                "broken manifests: {} Cargo.toml file(s) skipped by discovery",
+                membership_analysis.broken_manifests.len()
+            );
+        }
+        if !membership_analysis.no_stale_references() {
+            println!(
+                "stale references: {} crate(s) still depending on a removed crate",
+                membership_analysis.stale_references.len()
+            );
+        }
+        if verbose {
+            println!();
+            membership_analysis.display_issues();
+        }
+    }
+}
+
+// a history that cannot be written only delays the next escalation
+fn save_history(history: &mut Option<deptrack::IssueHistory>, issues: &[ReportIssue]) {
+    if let Some(history) = history {
+        history.record(issues);
+        if let Err(e) = history.save() {
+            eprintln!(
+                "warning: could not write {}: {}",
+                history.path().display(),
+                e
+            );
+        }
+    }
+}
+
+// fails with the exit status of the worst issues found, warnings only with
+// `--fail-on warning`
+fn check_outcome(
+    format: &OutputFormat,
+    fail_on: deptrack::IssueSeverity,
+    total_errors: usize,
+    total_warnings: usize,
+) -> Result<()> {
+    if total_errors > 0 {
+        if matches!(format, OutputFormat::Human) {
+            println!();
+            println!(
+                "validation failed: {} error(s), {} warning(s)",
+                total_errors, total_warnings
+            );
+        }
+
+        return Err(fail(
+            Exit::Errors,
+            format!("validation failed with {} error(s)", total_errors),
+        ));
+    }
+
+    if total_warnings > 0 && fail_on == deptrack::IssueSeverity::Warning {
+        if matches!(format, OutputFormat::Human) {
+            println!();
+            println!("validation failed: {} warning(s)", total_warnings);
+        }
+        return Err(fail(
+            Exit::Warnings,
+            format!("validation failed with {} warning(s)", total_warnings),
+        ));
+    }
+
+    Ok(())
+}
+
+// display names of `ids`, as the json output lists crates
+fn display_names(ids: &[deptrack::CrateId]) -> Vec<String> {
+    ids.iter().map(|id| id.display_name()).collect()
+}
+
+// a `crate` record for every crate checked for a version bump
+fn stream_crates(
+    stream: &mut JsonlWriter<std::io::Stdout>,
+    analysis: &deptrack::VersionBumpAnalysis,
+) -> Result<()> {
+    let mut statuses: Vec<_> = analysis.crate_versions.values().collect();
+    statuses.sort_by_key(|status| status.crate_id.display_name());
+    for status in statuses {
+        stream.record(
+            "crate",
+            &serde_json::json!({
+                "crate": status.crate_id.display_name(),
+                "base_version": status.base_version.to_string(),
+                "current_version": status.current_version.to_string(),
+                "bumped": status.is_bumped,
+                "directly_changed": status.is_directly_changed,
+                "new": status.is_new,
+                "required_bump": status.required_bump,
+            }),
+        )?;
+    }
+    Ok(())
+}
+
+// who committed to each crate with errors between the two refs, so it is
+// clear who to ask for the missing bump or changelog entry
+fn display_failing_authors(
+    path: &PathBuf,
+    graph: &CrateDependencyGraph,
+    issues: &[ReportIssue],
+    from: &str,
+    to: &str,
+) {
+    let failing: std::collections::BTreeSet<&str> = issues
+        .iter()
+        .filter(|issue| issue.severity == deptrack::IssueSeverity::Error)
+        .map(|issue| issue.crate_name.as_str())
+        .collect();
+    if failing.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("authors of failing crates:");
+    for name in failing {
+        let Some(info) = graph
+            .crates
+            .values()
+            .find(|info| info.id.display_name() == name)
+        else {
+            continue;
+        };
+        let authors = match GitOps::commits_in_range(path, from, to, &info.path) {
+            Ok(commits) if commits.is_empty() => "no commits in range".to_string(),
+            Ok(commits) => deptrack::AuthorSummary::of(&commits)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            Err(e) => format!("unknown ({})", e),
+        };
+        println!("  {}: {}", name, authors);
+    }
+}
+
+/// escalate the chronic warnings of one check, moving them from
+/// `total_warnings` to `total_errors`; does nothing without a history
+fn escalate<S>(
+    history: Option<&deptrack::IssueHistory>,
+    check: &str,
+    statuses: &mut std::collections::HashMap<deptrack::CrateId, S>,
+    issues: impl Fn(&mut S) -> &mut Vec<deptrack::Issue>,
+    total_errors: &mut usize,
+    total_warnings: &mut usize,
+) {
+    if let Some(history) = history {
+        let escalated = history.escalate(check, statuses, issues);
+        *total_errors += escalated;
+        *total_warnings -= escalated;
+    }
+}
+
+// the rules the checks of check-versions ran with; with --all-crates every
+// crate is graded as a direct one
+fn check_versions_severity(
+    config: &deptrack::DeptrackConfig,
+    skip_changelog: bool,
+    affected: &[deptrack::CrateId],
+    direct: &[deptrack::CrateId],
+) -> deptrack::SeverityMetadata {
+    let mut checks = vec!["version"];
+    if !skip_changelog {
+        checks.push("changelog");
+    }
+    checks.extend(["manifest", "msrv", "features", "lockfile", "membership"]);
+    if !config.checks.is_empty() {
+        checks.push("checks");
+    }
+    deptrack::SeverityMetadata::new(config, &checks, affected, direct)
+}
+
+// the issues of crates a check could not be run for
+fn analysis_failures(issues: &[ReportIssue]) -> Vec<&ReportIssue> {
+    let failed = deptrack::IssueType::AnalysisFailed.to_string();
+    issues
+        .iter()
+        .filter(|issue| issue.issue_type == failed)
+        .collect()
+}
+
+// add the issues one check found, streaming them as `issue` records; with
+// `fail_fast` a crate the check could not be run for ends the run instead
+fn record_issues(
+    issues: &mut Vec<ReportIssue>,
+    stream: &mut Option<JsonlWriter<std::io::Stdout>>,
+    fail_fast: bool,
+    found: Vec<ReportIssue>,
+) -> Result<()> {
+    if fail_fast && let Some(failed) = analysis_failures(&found).first() {
+        return Err(fail(
+            Exit::Errors,
+            format!(
+                "{} check failed for {}: {}",
+                failed.check, failed.crate_name, failed.message
+            ),
+        ));
+    }
+    if let Some(stream) = stream {
+        for issue in &found {
+            stream.record("issue", issue)?;
+        }
+    }
+    issues.extend(found);
+    Ok(())
+}
+
+fn handle_compare_reports(
+    format: &OutputFormat,
+    old: &PathBuf,
+    new: &PathBuf,
+    fail_on: deptrack::IssueSeverity,
+) -> Result<()> {
+    use deptrack::{IssueSeverity, ReportComparison};
+
+    let read = |path: &PathBuf| -> Result<serde_json::Value> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read report {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse report {}", path.display()))
+    };
+    let comparison = ReportComparison::compare(&read(old)?, &read(new)?)
+        .map_err(|e| fail(Exit::Usage, e.to_string()))?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            println!("{}", format.render(&comparison)?);
+        }
+        OutputFormat::Human => comparison.display(),
+    }
+
+    let errors = comparison.introduced_count(IssueSeverity::Error);
+    let warnings = comparison.introduced_count(IssueSeverity::Warning);
+    if errors > 0 {
+        return Err(fail(
+            Exit::Errors,
+            format!("{} error(s) introduced", errors),
+        ));
+    }
+    if warnings > 0 && fail_on == IssueSeverity::Warning {
+        return Err(fail(
+            Exit::Warnings,
+            format!("{} warning(s) introduced", warnings),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_report(
+    path: &PathBuf,
+    format: &OutputFormat,
+    from: &str,
+    to: &str,
+    markdown: bool,
+    severity_preset: Option<deptrack::SeverityPreset>,
+    fail_on: deptrack::IssueSeverity,
+) -> Result<()> {
+    use deptrack::{DeptrackConfig, IssueSeverity, UnifiedReport};
+
+    let mut config = DeptrackConfig::load_or_default(path);
+    if let Some(preset) = severity_preset {
+        config.use_severity_preset(preset);
+    }
+    use_strict_parse(&mut config);
+    // broken manifests are reported with membership
+    let graph = discover_graph(path).context("failed to build dependency graph")?;
+    let report = UnifiedReport::build(&graph, path, from, to, &config)
+        .context("failed to build the report")?;
+
+    if markdown {
+        print!("{}", report.to_markdown());
+    } else {
+        match format {
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+                println!("{}", format.render(&report)?);
+            }
+            OutputFormat::Human => report.display(),
+        }
+    }
+
+    let errors = report.error_count();
+    let warnings = report.warning_count();
+    if errors > 0 {
+        return Err(fail(Exit::Errors, format!("{} error(s) found", errors)));
+    }
+    if warnings > 0 && fail_on == IssueSeverity::Warning {
+        return Err(fail(
+            Exit::Warnings,
+            format!("{} warning(s) found", warnings),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_doctor(path: &PathBuf, format: &OutputFormat) -> Result<()> {
+    let report = deptrack::Doctor::diagnose(path).context("failed to run the checks")?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            println!("{}", format.render(&report)?);
+        }
+        OutputFormat::Human => report.display(),
+    }
+
+    if report.error_count() > 0 {
+        return Err(fail(
+            Exit::Errors,
+            format!("{} checks failed", report.error_count()),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_git(default_path: &PathBuf, format: &OutputFormat, command: GitCommands) -> Result<()> {
+    match command {
+        GitCommands::Branches { path, remote, all } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let _git_repo =
+                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+
+            let local = if remote {
+                Vec::new()
+            } else {
+                GitOps::list_branches(repo_path).context("failed to list branches")?
+            };
+            let remotes = if remote || all {
+                GitOps::list_remote_branches(repo_path).context("failed to list remote branches")?
+            } else {
+                Vec::new()
+            };
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    // remote branches keep their `<remote>/` prefix
+                    let branches: Vec<&String> = local.iter().chain(&remotes).collect();
+                    println!("{}", format.render(&branches)?);
+                }
+                OutputFormat::Human => {
+                    if !remote {
+                        println!("branches:");
+                        for branch in &local {
+                            println!("  {}", branch);
+                        }
+                    }
+                    if remote || all {
+                        println!("remote branches:");
+                        for branch in &remotes {
+                            println!("  {}", branch);
+                        }
+                    }
+                }
+            }
+        }
+        GitCommands::Tags { path } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let _git_repo =
+                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+
+            let tags = GitOps::list_tags(repo_path).context("failed to list tags")?;
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&tags)?);
+                }
+                OutputFormat::Human if tags.is_empty() => println!("no tags"),
+                OutputFormat::Human => {
+                    let mut table =
+                        deptrack::utils::table::Table::new(["Tag", "Commit", "Date", "Message"]);
+                    for tag in &tags {
+                        table.row([
+                            tag.name.clone(),
+                            tag.target.get(..12).unwrap_or(&tag.target).to_string(),
+                            tag.date.clone(),
+                            // first line of the annotation
+                            tag.message
+                                .as_deref()
+                                .and_then(|message| message.lines().next())
+                                .unwrap_or_default()
+                                .to_string(),
+                        ]);
+                    }
+                    table.print();
+                }
+            }
+        }
+        GitCommands::CurrentBranch { path } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let _git_repo =
+                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+
+            let current =
+                GitOps::get_current_branch(repo_path).context("failed to get current branch")?;
+            // None on a branch without commits yet
+            let commit = GitOps::resolve_ref(repo_path, &deptrack::GitRef::Head).ok();
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let output = serde_json::json!({
+                        "current_branch": current.branch(),
+                        "detached": current.is_detached(),
+                        "commit": commit,
+                    });
+                    println!("{}", format.render(&output)?);
+                }
+                OutputFormat::Human => match &current {
+                    deptrack::CurrentRef::Branch(branch) => {
+                        println!("current branch: {}", branch);
+                        if let Some(commit) = &commit {
+                            println!("commit: {}", commit);
+                        }
+                    }
+                    deptrack::CurrentRef::Detached(commit) => {
+                        println!("no current branch, HEAD is detached");
+                        println!("commit: {}", commit);
+                    }
+                },
+            }
+        }
+        GitCommands::Changes {
+            from,
+            to,
+            path,
+            extension,
+            include,
+            exclude,
+            unique,
+        } => {
+            use deptrack::{DiffMode, GitRef, Pathspec};
+
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let _git_repo =
+                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+
+            let from_ref = GitRef::from_string(&from);
+            let to_ref = GitRef::from_string(&to);
+
+            let cancel = CANCEL.get().cloned().unwrap_or_default();
+            let pathspec = Pathspec::new(include, exclude);
+            let mode = if unique {
+                DiffMode::ThreeDot
+            } else {
+                DiffMode::TwoDot
+            };
+            let changed_files =
+                GitOps::list_changes(repo_path, &from_ref, &to_ref, mode, &pathspec, &cancel)
+                    .context("failed to list changes")?;
+
+            let filtered_changes: Vec<_> = if let Some(ext) = extension {
+                changed_files
+                    .changes
+                    .into_iter()
+                    .filter(|c| {
+                        c.path
+                            .extension()
+                            .and_then(|e| e.to_str())
+                            .map(|e| e == ext)
+                            .unwrap_or(false)
+                    })
+                    .collect()
+            } else {
+                changed_files.changes
+            };
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&filtered_changes)?);
+                }
+                OutputFormat::Human => {
+                    println!("changes from {} to {}:", from, to);
+                    for change in filtered_changes {
+                        println!("  {:?}: {}", change.change_type, change.path.display());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_deps(default_path: &PathBuf, format: &OutputFormat, command: DepsCommands) -> Result<()> {
+    use deptrack::{DuplicateChecker, FreshnessChecker, RequirementSync, SparseIndex};
+
+    match command {
+        DepsCommands::Outdated {
+            path,
+            locked,
+            min_behind,
+            index,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(repo_path).context("failed to build dependency graph")?;
+
+            let report = FreshnessChecker::analyze(&graph, &SparseIndex::new(&index), locked)
+                .context("failed to check dependency freshness")?;
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let mut output = serde_json::to_value(&report)?;
+                    output["index"] = serde_json::json!(index);
+                    output["locked"] = serde_json::json!(locked);
+                    output["min_behind"] = serde_json::json!(min_behind);
+                    output["dependencies"] = serde_json::json!(report.dependency_count());
+                    output["outdated"] = serde_json::json!(report.outdated_count(min_behind));
+                    println!("{}", format.render(&output)?);
+                }
+                OutputFormat::Human => report.display(min_behind),
+            }
+        }
+        DepsCommands::Duplicates { path } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(repo_path).context("failed to build dependency graph")?;
+
+            let report = DuplicateChecker::analyze(&graph)
+                .context("failed to check for duplicate dependencies")?;
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let mut output = serde_json::to_value(&report)?;
+                    output["count"] = serde_json::json!(report.duplicates.len());
+                    println!("{}", format.render(&output)?);
+                }
+                OutputFormat::Human => report.display(),
+            }
+        }
+        DepsCommands::Sync { path, check } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(repo_path).context("failed to build dependency graph")?;
+
+            let report = RequirementSync::analyze(&graph)
+                .context("failed to check internal requirements")?;
+            let updated = if check {
+                0
+            } else {
+                RequirementSync::apply(&report).context("failed to rewrite requirements")?
+            };
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let mut output = serde_json::to_value(&report)?;
+                    output["updated"] = serde_json::json!(updated);
+                    println!("{}", format.render(&output)?);
+                }
+                OutputFormat::Human => {
+                    report.display();
+                    if !check && updated > 0 {
+                        println!("updated {} requirements", updated);
+                    }
+                }
+            }
+
+            if check && !report.is_consistent() {
+                return Err(fail(
+                    Exit::Errors,
+                    "internal requirements are out of date, run `deptrack deps sync`",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_crate(
+    path: &PathBuf,
+    format: &OutputFormat,
+    crate_name: &str,
+    history: usize,
+) -> Result<()> {
+    use deptrack::{CrateDetail, DeptrackConfig};
 
-            // display repository structure
-            graph.display_repository_structure(&repo_path);
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+    let crate_id = graph
+        .find_crate_by_name(crate_name)
+        .map(|info| info.id.clone())
+        .ok_or_else(|| fail(Exit::Usage, format!("crate {} not found", crate_name)))?;
+    let detail = CrateDetail::collect(&graph, path, &crate_id, &config, history)
+        .context("failed to collect crate details")?
+        .ok_or_else(|| fail(Exit::Usage, format!("crate {} not found", crate_name)))?;
 
-            // display dependency summary
-            graph.display_dependency_summary();
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            println!("{}", format.render(&detail)?);
+        }
+        OutputFormat::Human => detail.display(),
+    }
 
-            // always display cycles if detected
-            if stats.has_cycles {
-                println!();
-                println!("warning: cyclic dependencies detected");
-                println!();
-                graph.display_cycles();
-            }
+    Ok(())
+}
 
-            if show_graph {
-                println!();
-                println!("additional graph statistics:");
-                println!("  crate count: {}", stats.crate_count);
-                println!("  cycle count: {}", stats.cycle_count);
-            }
+fn handle_tree(
+    path: &PathBuf,
+    format: &OutputFormat,
+    crate_name: Option<&str>,
+    options: &deptrack::TreeOptions,
+) -> Result<()> {
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+
+    let root = match crate_name {
+        Some(name) => Some(
+            graph
+                .find_crate_by_name(name)
+                .map(|info| info.id.clone())
+                .ok_or_else(|| fail(Exit::Usage, format!("crate {} not found", name)))?,
+        ),
+        None => None,
+    };
+    let tree = match &root {
+        Some(root) => graph.render_tree(root, options),
+        None => graph.render_forest(options),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let output = serde_json::json!({
+                "root": root.as_ref().map(|id| id.display_name()),
+                "invert": options.invert,
+                "max_depth": options.max_depth,
+                "lines": tree.lines().collect::<Vec<_>>(),
+            });
+            println!("{}", format.render(&output)?);
         }
+        OutputFormat::Human => print!("{}", tree),
     }
 
     Ok(())
 }
 
-fn handle_check_versions(
+fn handle_tasks(
+    path: &PathBuf,
+    format: &OutputFormat,
+    from: &str,
+    to: &str,
+    markdown: bool,
+    errors_only: bool,
+    severity_preset: Option<deptrack::SeverityPreset>,
+) -> Result<()> {
+    use deptrack::{DeptrackConfig, TaskList, UnifiedReport};
+
+    let mut config = DeptrackConfig::load_or_default(path);
+    if let Some(preset) = severity_preset {
+        config.use_severity_preset(preset);
+    }
+    use_strict_parse(&mut config);
+    // broken manifests are reported with membership
+    let graph = discover_graph(path).context("failed to build dependency graph")?;
+    let report = UnifiedReport::build(&graph, path, from, to, &config)
+        .context("failed to build the report")?;
+    let tasks = TaskList::from_report(&graph, path, &report, errors_only)
+        .context("failed to assign owners to the issues")?;
+
+    if markdown {
+        print!("{}", tasks.to_markdown());
+        return Ok(());
+    }
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            println!("{}", format.render(&tasks)?);
+        }
+        OutputFormat::Human => tasks.display(),
+    }
+    Ok(())
+}
+
+fn handle_changelog_coverage(
     path: &PathBuf,
     format: &OutputFormat,
     from_ref_str: &str,
     to_ref_str: &str,
-    skip_changelog: bool,
-    all_crates: bool,
-    verbose: bool,
+    min_commits: usize,
 ) -> Result<()> {
-    use deptrack::{ChangelogChecker, DeptrackConfig, GitRef};
+    use deptrack::{ChangelogCoverage, DeptrackConfig, GitRef};
 
-    // canonicalize path for display
-    let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+    let impact_analysis = graph
+        .analyze_git_changes_with_config(
+            path,
+            &GitRef::from_string(from_ref_str),
+            &GitRef::from_string(to_ref_str),
+            &config.impact,
+        )
+        .context("failed to analyze git changes")?;
+
+    let coverage = ChangelogCoverage::analyze(
+        &graph,
+        path,
+        from_ref_str,
+        to_ref_str,
+        &impact_analysis.directly_affected_crates,
+        &config.changelog,
+        min_commits,
+    )
+    .context("failed to compare commits with changelogs")?;
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let mut output = serde_json::to_value(&coverage)?;
+            output["from"] = serde_json::json!(from_ref_str);
+            output["to"] = serde_json::json!(to_ref_str);
+            output["overall_coverage"] = serde_json::json!(coverage.overall_coverage());
+            output["flagged"] = serde_json::json!(
+                coverage
+                    .flagged()
+                    .map(|c| c.crate_id.display_name())
+                    .collect::<Vec<_>>()
+            );
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            println!("changelog coverage for {}..{}", from_ref_str, to_ref_str);
+            println!();
+            coverage.display();
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_test_plan(
+    path: &PathBuf,
+    format: &OutputFormat,
+    from_ref_str: &str,
+    to_ref_str: &str,
+) -> Result<()> {
+    use deptrack::{DeptrackConfig, GitRef, TestPlanner};
 
-    // load configuration
     let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+
+    // tests of dev-dependents exercise the change too, so every dependency is
+    // followed whatever impact.propagation says
+    let impact_analysis = graph
+        .analyze_git_changes_ignoring(
+            path,
+            &GitRef::from_string(from_ref_str),
+            &GitRef::from_string(to_ref_str),
+            config.impact.ignore.as_ref(),
+        )
+        .context("failed to analyze git changes")?;
 
-    // build dependency graph
-    let graph = CrateDependencyGraph::build_from_repository(path)
-        .context("failed to build dependency graph")?;
+    let plan = TestPlanner::plan(&graph, &impact_analysis, path).context("failed to plan tests")?;
 
-    // analyze changes between refs
-    let from_ref = GitRef::from_string(from_ref_str);
-    let to_ref = GitRef::from_string(to_ref_str);
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let mut output = serde_json::to_value(&plan)?;
+            output["from"] = serde_json::json!(from_ref_str);
+            output["to"] = serde_json::json!(to_ref_str);
+            output["changed_files"] = serde_json::json!(impact_analysis.changed_files.len());
+            output["target_count"] = serde_json::json!(plan.target_count());
+            output["command_lines"] = serde_json::json!(
+                plan.commands
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+            );
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            println!("test plan for {}..{}", from_ref_str, to_ref_str);
+            println!();
+            plan.display();
+        }
+    }
+
+    Ok(())
+}
 
+fn handle_ci_matrix(
+    path: &PathBuf,
+    from_ref_str: &str,
+    to_ref_str: &str,
+    provider: deptrack::CiProvider,
+) -> Result<()> {
+    use deptrack::{CiMatrix, DeptrackConfig, GitRef, TestPlanner};
+
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+
+    // tests of dev-dependents exercise the change too, so every dependency is
+    // followed whatever impact.propagation says
     let impact_analysis = graph
-        .analyze_git_changes(path, &from_ref, &to_ref)
+        .analyze_git_changes_ignoring(
+            path,
+            &GitRef::from_string(from_ref_str),
+            &GitRef::from_string(to_ref_str),
+            config.impact.ignore.as_ref(),
+        )
         .context("failed to analyze git changes")?;
+    let plan = TestPlanner::plan(&graph, &impact_analysis, path).context("failed to plan tests")?;
 
-    if impact_analysis.changed_files.is_empty() {
-        match format {
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "from": from_ref_str,
-                    "to": to_ref_str,
-                    "repository_path": repo_path,
-                    "changed_files": 0,
-                    "directly_affected": 0,
-                    "total_affected": 0,
-                    "bumped": 0,
-                    "needing_bump": 0,
-                    "bump_percentage": 100.0,
-                    "all_bumped": true,
-                    "changelog_skipped": skip_changelog,
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
-            }
-            OutputFormat::Human => {
-                println!(
-                    "checking version bumps between {} and {}",
-                    from_ref_str, to_ref_str
-                );
-                println!("repository: {}", repo_path.display());
-                println!();
-                println!(
-                    "no changes detected between {} and {}",
-                    from_ref_str, to_ref_str
-                );
+    // the output is consumed by the CI system, so --format does not apply
+    let matrix = CiMatrix::from_plan(&graph, &plan, path);
+    print!("{}", matrix.render(provider));
+    if matches!(provider, deptrack::CiProvider::Github) {
+        println!();
+    }
+
+    Ok(())
+}
+
+fn handle_export(
+    default_path: &PathBuf,
+    format: &OutputFormat,
+    command: ExportCommands,
+) -> Result<()> {
+    use deptrack::{BazelExporter, BazelOutput};
+
+    match command {
+        ExportCommands::Bazel {
+            path,
+            output,
+            label_template,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(repo_path).context("failed to build dependency graph")?;
+
+            let targets = BazelExporter::new()
+                .label_template(label_template)
+                .targets(&graph, repo_path);
+
+            match (format, output) {
+                (
+                    OutputFormat::Json
+                    | OutputFormat::Jsonl
+                    | OutputFormat::Yaml
+                    | OutputFormat::Toml,
+                    _,
+                ) => {
+                    println!("{}", format.render(&targets)?);
+                }
+                (OutputFormat::Human, BazelOutput::Build) => {
+                    print!("{}", BazelExporter::build_files(&targets));
+                }
+                (OutputFormat::Human, BazelOutput::Query) => {
+                    print!("{}", BazelExporter::query_listing(&targets));
+                }
             }
         }
-        return Ok(());
     }
 
-    // analyze version bumps
-    let version_analysis = graph
-        .analyze_version_bumps(
+    Ok(())
+}
+
+fn handle_release_draft(
+    path: &PathBuf,
+    format: &OutputFormat,
+    from_ref_str: &str,
+    to_ref_str: &str,
+    write: bool,
+) -> Result<()> {
+    use deptrack::{ChangelogDraft, DeptrackConfig, GitRef};
+
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+    let impact_analysis = graph
+        .analyze_git_changes_with_config(
             path,
-            &from_ref,
-            &impact_analysis.all_affected_crates,
-            &impact_analysis.directly_affected_crates,
-            &config.direct_severity,
-            &config.transitive_severity,
+            &GitRef::from_string(from_ref_str),
+            &GitRef::from_string(to_ref_str),
+            &config.impact,
         )
-        .context("failed to analyze version bumps")?;
+        .context("failed to analyze git changes")?;
 
-    // analyze changelogs if not skipped
-    let changelog_analysis = if !skip_changelog {
-        let analysis = if all_crates {
-            ChangelogChecker::analyze_all(&graph, path, &config.changelog, &config.direct_severity)
-                .context("failed to analyze changelogs")?
-        } else {
-            ChangelogChecker::analyze_for_changes(
-                &graph,
-                path,
-                &config.changelog,
-                &config.direct_severity,
-                &config.transitive_severity,
-                &version_analysis,
-                &impact_analysis,
-            )
-            .context("failed to analyze changelogs")?
-        };
-        Some(analysis)
-    } else {
-        None
+    let draft = ChangelogDraft::from_commits(
+        &graph,
+        path,
+        from_ref_str,
+        to_ref_str,
+        &impact_analysis.directly_affected_crates,
+        &config,
+    )
+    .context("failed to draft changelog entries")?;
+    if write {
+        draft.write().context("failed to update changelogs")?;
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let mut output = serde_json::to_value(&draft)?;
+            output["from"] = serde_json::json!(from_ref_str);
+            output["to"] = serde_json::json!(to_ref_str);
+            output["written"] = serde_json::json!(write);
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            draft.display();
+            if write && !draft.is_empty() {
+                println!();
+                println!("added to the [Unreleased] section of each changelog");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_release_notes(
+    path: &PathBuf,
+    format: &OutputFormat,
+    crate_name: &str,
+    version: Option<semver::Version>,
+) -> Result<()> {
+    use deptrack::{ChangelogChecker, DeptrackConfig, parse_changelog};
+
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
+    let info = graph
+        .find_crate_by_name(crate_name)
+        .ok_or_else(|| fail(Exit::Usage, format!("crate {} not found", crate_name)))?;
+    let version = match version {
+        Some(version) => version,
+        None => semver::Version::parse(&info.version)
+            .with_context(|| format!("invalid version {} of {}", info.version, crate_name))?,
     };
 
+    let changelog_path = ChangelogChecker::changelog_path(&info.path, &config.changelog);
+    let changelog = parse_changelog(&changelog_path)
+        .with_context(|| format!("failed to read {}", changelog_path.display()))?;
+    let section = changelog.get_version(&version).ok_or_else(|| {
+        fail(
+            Exit::Errors,
+            format!("{} has no entry for {}", changelog_path.display(), version),
+        )
+    })?;
+    let notes = config
+        .templates
+        .for_crate(&info.id)
+        .render_release_notes(&info.id, section)
+        .context("failed to render release notes")?;
+
     match format {
-        OutputFormat::Json => {
-            let mut output = serde_json::json!({
-                "from": from_ref_str,
-                "to": to_ref_str,
-                "repository_path": repo_path,
-                "changed_files": impact_analysis.changed_files.len(),
-                "directly_affected": impact_analysis.directly_affected_crates.len(),
-                "total_affected": impact_analysis.all_affected_crates.len(),
-                "bumped": version_analysis.crates_bumped.len(),
-                "needing_bump": version_analysis.crates_needing_bump.len(),
-                "bump_percentage": version_analysis.bump_percentage(),
-                "all_bumped": version_analysis.all_bumped(),
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let output = serde_json::json!({
+                "crate": info.id.display_name(),
+                "version": version.to_string(),
+                "notes": notes,
             });
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => print!("{}", notes),
+    }
 
-            // add version bump error/warning counts
-            output["version_bump_errors"] = serde_json::json!(version_analysis.total_errors);
-            output["version_bump_warnings"] = serde_json::json!(version_analysis.total_warnings);
+    Ok(())
+}
 
-            if let Some(ref analysis) = changelog_analysis {
-                output["changelog"] = serde_json::json!({
-                    "analyzed_crates": analysis.statuses.len(),
-                    "valid_changelogs": analysis.crates_with_valid_changelog.len(),
-                    "missing_changelogs": analysis.crates_missing_changelog.len(),
-                    "needing_updates": analysis.crates_needing_changelog_update.len(),
-                    "total_issues": analysis.total_issues,
-                    "total_errors": analysis.total_errors,
-                    "total_warnings": analysis.total_warnings,
-                    "compliance_percentage": analysis.compliance_percentage(),
-                    "all_valid": analysis.all_valid(),
-                });
-            } else {
-                output["changelog_skipped"] = serde_json::json!(true);
-            }
+fn handle_release(
+    default_path: &PathBuf,
+    format: &OutputFormat,
+    command: ReleaseCommands,
+) -> Result<()> {
+    use deptrack::{DeptrackConfig, GitRef, ReleaseExecutor, ReleaseOptions, ReleasePlanner};
 
-            println!("{}", serde_json::to_string_pretty(&output)?);
+    let (args, execute, dry_run) = match command {
+        ReleaseCommands::Plan { args } => (args, false, true),
+        ReleaseCommands::Execute { args, dry_run } => (args, true, dry_run),
+        ReleaseCommands::Draft {
+            from,
+            to,
+            path,
+            write,
+        } => {
+            let path = path.as_ref().unwrap_or(default_path);
+            return handle_release_draft(path, format, &from, &to, write);
         }
-        OutputFormat::Human => {
-            println!(
-                "checking version bumps between {} and {}",
-                from_ref_str, to_ref_str
-            );
-            println!("repository: {}", repo_path.display());
-            println!();
-
-            println!("detected changes:");
-            println!("  files changed: {}", impact_analysis.changed_files.len());
-            println!(
-                "  directly affected crates: {}",
-                impact_analysis.directly_affected_crates.len()
-            );
-            println!(
-                "  total affected crates: {}",
-                impact_analysis.all_affected_crates.len()
-            );
-            println!();
+        ReleaseCommands::Notes {
+            crate_name,
+            crate_version,
+            path,
+        } => {
+            let path = path.as_ref().unwrap_or(default_path);
+            return handle_release_notes(path, format, &crate_name, crate_version);
+        }
+    };
+    let path = args.path.as_ref().unwrap_or(default_path);
 
-            version_analysis.display_table();
+    let config = DeptrackConfig::load_or_default(path);
+    let graph = build_graph(path).context("failed to build dependency graph")?;
 
-            if verbose && (version_analysis.total_errors > 0 || version_analysis.total_warnings > 0)
-            {
-                println!();
-                version_analysis.display_issues();
-            }
+    let options = ReleaseOptions::new()
+        .level(args.level)
+        .transitive_level(args.transitive_level)
+        .commit(!args.no_commit)
+        .tag(!args.no_tag);
+    let plan = ReleasePlanner::plan(
+        &graph,
+        path,
+        &GitRef::from_string(&args.from),
+        &config,
+        &options,
+    )
+    .context("failed to plan release")?;
 
-            if let Some(ref analysis) = changelog_analysis {
+    // every action is shown before anything is touched
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {}
+        OutputFormat::Human => {
+            plan.display();
+            if execute && dry_run && !plan.is_empty() {
                 println!();
-                analysis.display_table();
-
-                if verbose && analysis.total_issues > 0 {
-                    println!();
-                    analysis.display_issues();
-                }
+                println!("dry run, no changes were made");
             }
         }
     }
 
-    // check for errors and fail if any are present
-    let total_errors = version_analysis.total_errors
-        + changelog_analysis
-            .as_ref()
-            .map(|a| a.total_errors)
-            .unwrap_or(0);
-
-    if total_errors > 0 {
-        let total_warnings = version_analysis.total_warnings
-            + changelog_analysis
-                .as_ref()
-                .map(|a| a.total_warnings)
-                .unwrap_or(0);
+    let apply = execute && !dry_run && !plan.is_empty();
+    if apply {
+        ReleaseExecutor::execute(&plan, path).context("failed to execute release")?;
+    }
 
-        if matches!(format, OutputFormat::Human) {
-            println!();
-            println!(
-                "validation failed: {} error(s), {} warning(s)",
-                total_errors, total_warnings
-            );
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let mut output = serde_json::to_value(&plan)?;
+            output["dry_run"] = serde_json::json!(dry_run);
+            output["executed"] = serde_json::json!(apply);
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            if apply {
+                println!();
+                println!(
+                    "released {} crate(s) with {} action(s)",
+                    plan.bumps.len(),
+                    plan.actions.len()
+                );
+            }
         }
-
-        // return error to cause non-zero exit code
-        anyhow::bail!("validation failed with {} error(s)", total_errors);
     }
 
     Ok(())
 }
 
-fn handle_git(default_path: &PathBuf, format: &OutputFormat, command: GitCommands) -> Result<()> {
+fn handle_fs(format: &OutputFormat, command: FsCommands) -> Result<()> {
+    use deptrack::utils::alt::LogicExpr;
+    use deptrack::{
+        FilesystemExplorer, FsDirectory, HashCache, HashCacheSource, HashType, PredicateContext,
+    };
+
+    let parse_filter = |filter: &str| {
+        LogicExpr::parse(filter).map_err(|e| {
+            fail(
+                Exit::Usage,
+                format!("invalid filter expression '{}': {}", filter, e),
+            )
+        })
+    };
+
     match command {
-        GitCommands::Branches { path } => {
-            let repo_path = path.as_ref().unwrap_or(default_path);
-            let _git_repo =
-                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+        FsCommands::Snapshot {
+            dir,
+            output,
+            cache,
+            filter,
+        } => {
+            let mut directory = FsDirectory::new_root(&dir);
+            directory
+                .scan()
+                .with_context(|| format!("failed to scan {}", dir.display()))?;
+            if let Some(filter) = &filter {
+                let expr = parse_filter(filter)?;
+                directory = FilesystemExplorer::new(directory.path.clone())
+                    .retain_files_matching(&directory, &expr)
+                    .context("failed to apply filter")?;
+            }
+            directory
+                .metadata_scan()
+                .context("failed to read file metadata")?;
+            let hash_cache = match &cache {
+                Some(cache_path) => HashCache::load_snapshot(cache_path).with_context(|| {
+                    format!("failed to load cache snapshot {}", cache_path.display())
+                })?,
+                None => HashCache::new(),
+            };
+            let stats = directory
+                .hash_cached(HashType::BLAKE3, &HashCacheSource::Snapshot(&hash_cache))
+                .context("failed to hash files")?;
+            directory
+                .save_snapshot(&output)
+                .with_context(|| format!("failed to write snapshot {}", output.display()))?;
 
-            let branches = GitOps::list_branches(repo_path).context("failed to list branches")?;
             match format {
-                OutputFormat::Json => {
-                    println!("{}", serde_json::to_string_pretty(&branches)?);
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let result = serde_json::json!({
+                        "directory": dir,
+                        "snapshot": output,
+                        "hashes_reused": stats.reused,
+                        "hashes_computed": stats.computed,
+                    });
+                    println!("{}", format.render(&result)?);
                 }
                 OutputFormat::Human => {
-                    println!("branches:");
-                    for branch in branches {
-                        println!("  {}", branch);
+                    println!(
+                        "snapshot of {} written to {}",
+                        dir.display(),
+                        output.display()
+                    );
+                    if cache.is_some() {
+                        println!(
+                            "{} hashes reused, {} computed",
+                            stats.reused, stats.computed
+                        );
                     }
                 }
             }
         }
-        GitCommands::CurrentBranch { path } => {
-            let repo_path = path.as_ref().unwrap_or(default_path);
-            let _git_repo =
-                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+        FsCommands::Find {
+            dir,
+            filter,
+            explain_filter,
+        } => {
+            let expr = parse_filter(&filter)?;
+            if let Some(file) = explain_filter {
+                let context = PredicateContext::for_file(&file)
+                    .with_context(|| format!("failed to read {}", file.display()))?;
+                let (included, explanation) = expr
+                    .evaluate_explain(&context)
+                    .context("failed to evaluate filter")?;
+
+                match format {
+                    OutputFormat::Json
+                    | OutputFormat::Jsonl
+                    | OutputFormat::Yaml
+                    | OutputFormat::Toml => {
+                        let result = serde_json::json!({
+                            "file": file,
+                            "included": included,
+                            "explanation": explanation,
+                        });
+                        println!("{}", format.render(&result)?);
+                    }
+                    OutputFormat::Human => {
+                        let verdict = if included { "included" } else { "excluded" };
+                        println!("{} is {}\n", file.display(), verdict);
+                        print!("{}", explanation);
+                    }
+                }
+                return Ok(());
+            }
+
+            let explorer = FilesystemExplorer::new(&dir);
+            let directory = explorer
+                .scan_from_root()
+                .with_context(|| format!("failed to scan {}", dir.display()))?;
+            let mut files = explorer
+                .find_files_matching(&directory, &expr)
+                .context("failed to apply filter")?;
+            files.sort();
 
-            let branch =
-                GitOps::get_current_branch(repo_path).context("failed to get current branch")?;
             match format {
-                OutputFormat::Json => {
-                    let output = serde_json::json!({ "current_branch": branch });
-                    println!("{}", serde_json::to_string_pretty(&output)?);
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&files)?);
                 }
                 OutputFormat::Human => {
-                    println!("current branch: {}", branch);
+                    for file in &files {
+                        println!("{}", file.display());
+                    }
                 }
             }
         }
-        GitCommands::Changes {
-            from,
-            to,
+        FsCommands::Impact {
+            snapshot_a,
+            snapshot_b,
             path,
-            extension,
-            unique,
         } => {
-            use deptrack::GitRef;
+            let old = FsDirectory::load_snapshot(&snapshot_a)
+                .with_context(|| format!("failed to load snapshot {}", snapshot_a.display()))?;
+            let new = FsDirectory::load_snapshot(&snapshot_b)
+                .with_context(|| format!("failed to load snapshot {}", snapshot_b.display()))?;
+            let root = path.unwrap_or_else(|| new.path.clone());
+            let graph = build_graph(&root).context("failed to build dependency graph")?;
+            let config = deptrack::DeptrackConfig::load_or_default(&root);
+            let impact = graph
+                .analyze_snapshot_changes(&root, &old, &new, &config.impact)
+                .context("failed to compare snapshots")?;
+            display_change_impact(format, &impact)?;
+        }
+        FsCommands::Diff {
+            snapshot_a,
+            snapshot_b,
+        } => {
+            let old = FsDirectory::load_snapshot(&snapshot_a)
+                .with_context(|| format!("failed to load snapshot {}", snapshot_a.display()))?;
+            let new = FsDirectory::load_snapshot(&snapshot_b)
+                .with_context(|| format!("failed to load snapshot {}", snapshot_b.display()))?;
+            let diff = old.diff(&new);
 
-            let repo_path = path.as_ref().unwrap_or(default_path);
-            let _git_repo =
-                GitOps::get_repository_info(repo_path).context("failed to open git repository")?;
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&diff)?);
+                }
+                OutputFormat::Human => {
+                    if diff.is_empty() {
+                        println!("no differences");
+                        return Ok(());
+                    }
+                    for path in &diff.added {
+                        println!("  + {}", path.display());
+                    }
+                    for path in &diff.removed {
+                        println!("  - {}", path.display());
+                    }
+                    for file in &diff.modified {
+                        match file.size_delta() {
+                            Some(delta) => {
+                                println!("  ~ {} ({:+} bytes)", file.path.display(), delta)
+                            }
+                            None => println!("  ~ {}", file.path.display()),
+                        }
+                    }
+                    for change in &diff.permission_changes {
+                        println!(
+                            "  ! {}: {} -> {}",
+                            change.path.display(),
+                            change.old,
+                            change.new
+                        );
+                    }
+                    println!(
+                        "\n{} added, {} removed, {} modified, {} permission changes",
+                        diff.added.len(),
+                        diff.removed.len(),
+                        diff.modified.len(),
+                        diff.permission_changes.len()
+                    );
+                }
+            }
+        }
+        FsCommands::Tag { dir, hash } => {
+            let mut directory = FsDirectory::new_root(&dir);
+            directory
+                .scan()
+                .with_context(|| format!("failed to scan {}", dir.display()))?;
+            let tagged = directory
+                .tag(hash)
+                .with_context(|| format!("failed to tag files in {}", dir.display()))?;
 
-            let from_ref = GitRef::from_string(&from);
-            let to_ref = GitRef::from_string(&to);
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let result = serde_json::json!({
+                        "directory": dir,
+                        "tagged": tagged,
+                    });
+                    println!("{}", format.render(&result)?);
+                }
+                OutputFormat::Human => {
+                    println!("tagged {} files in {}", tagged, dir.display());
+                }
+            }
+        }
+        FsCommands::Verify { dir } => {
+            let mut directory = FsDirectory::new_root(&dir);
+            directory
+                .scan()
+                .with_context(|| format!("failed to scan {}", dir.display()))?;
+            let verification = directory
+                .verify_tags()
+                .with_context(|| format!("failed to verify files in {}", dir.display()))?;
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&verification)?);
+                }
+                OutputFormat::Human => {
+                    for mismatch in &verification.tampered {
+                        println!(
+                            "  ! {} ({} differ, mtime unchanged)",
+                            mismatch.path.display(),
+                            mismatch.fields.join(", ")
+                        );
+                    }
+                    for mismatch in &verification.drifted {
+                        println!(
+                            "  ~ {} ({} differ)",
+                            mismatch.path.display(),
+                            mismatch.fields.join(", ")
+                        );
+                    }
+                    for path in &verification.untagged {
+                        println!("  ? {} (untagged)", path.display());
+                    }
+                    println!(
+                        "{} verified, {} tampered, {} drifted, {} untagged",
+                        verification.verified.len(),
+                        verification.tampered.len(),
+                        verification.drifted.len(),
+                        verification.untagged.len()
+                    );
+                }
+            }
+
+            if !verification.is_ok() {
+                return Err(fail(
+                    Exit::Errors,
+                    format!("files in {} changed since they were tagged", dir.display()),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
 
-            let changed_files = if unique {
-                GitOps::list_unique_changes(repo_path, &from_ref, &to_ref)
-                    .context("failed to list unique changes")?
+fn handle_github(format: &OutputFormat, command: GithubCommands) -> Result<()> {
+    use deptrack::{GithubClient, pull_request_from_event, render_markdown, report_totals};
+
+    match command {
+        GithubCommands::Comment {
+            report,
+            repo,
+            pr,
+            sha,
+            no_status,
+        } => {
+            let contents = if report.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin()).context("failed to read report")?
             } else {
-                GitOps::list_changed_files(repo_path, &from_ref, &to_ref)
-                    .context("failed to list changes")?
+                std::fs::read_to_string(&report)
+                    .with_context(|| format!("failed to read report {}", report.display()))?
             };
+            let report: serde_json::Value =
+                serde_json::from_str(&contents).context("report is not valid json")?;
 
-            let filtered_changes: Vec<_> = if let Some(ext) = extension {
-                changed_files
-                    .changes
-                    .into_iter()
-                    .filter(|c| {
-                        c.path
-                            .extension()
-                            .and_then(|e| e.to_str())
-                            .map(|e| e == ext)
-                            .unwrap_or(false)
-                    })
-                    .collect()
+            let token = std::env::var("GITHUB_TOKEN").context("GITHUB_TOKEN is not set")?;
+            let Some(repo) = repo.or_else(|| std::env::var("GITHUB_REPOSITORY").ok()) else {
+                return Err(fail(
+                    Exit::Usage,
+                    "no repository given, pass --repo or set GITHUB_REPOSITORY",
+                ));
+            };
+            let event = std::env::var("GITHUB_EVENT_PATH")
+                .ok()
+                .and_then(pull_request_from_event);
+            let Some(pr) = pr.or(event.as_ref().map(|(number, _)| *number)) else {
+                return Err(fail(
+                    Exit::Usage,
+                    "no pull request given, pass --pr or run on a pull_request event",
+                ));
+            };
+            let sha = sha.or(event.map(|(_, head)| head));
+
+            let mut client = GithubClient::new(&repo, token);
+            if let Ok(api_url) = std::env::var("GITHUB_API_URL") {
+                client = client.api_url(api_url);
+            }
+
+            let comment_url = client
+                .upsert_comment(pr, &render_markdown(&report))
+                .context("failed to post pull request comment")?;
+            let (errors, warnings) = report_totals(&report);
+            let status_sha = match sha {
+                Some(sha) if !no_status => {
+                    client
+                        .set_status(
+                            &sha,
+                            errors == 0,
+                            &format!("{} error(s), {} warning(s)", errors, warnings),
+                            Some(&comment_url),
+                        )
+                        .context("failed to set commit status")?;
+                    Some(sha)
+                }
+                _ => None,
+            };
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    let output = serde_json::json!({
+                        "repository": repo,
+                        "pull_request": pr,
+                        "comment_url": comment_url,
+                        "status_sha": status_sha,
+                        "errors": errors,
+                        "warnings": warnings,
+                    });
+                    println!("{}", format.render(&output)?);
+                }
+                OutputFormat::Human => {
+                    println!("comment posted to {}#{}: {}", repo, pr, comment_url);
+                    if let Some(sha) = status_sha {
+                        println!(
+                            "status {} set on {}",
+                            if errors == 0 { "success" } else { "failure" },
+                            sha
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_manifest(
+    default_path: &PathBuf,
+    format: &OutputFormat,
+    command: ManifestCommands,
+) -> Result<()> {
+    use deptrack::{ArtifactManifest, DeptrackConfig};
+
+    match command {
+        ManifestCommands::Create {
+            artifacts,
+            path,
+            crates,
+            output,
+        } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let patterns = if artifacts.is_empty() {
+                DeptrackConfig::load_or_default(repo_path)
+                    .artifacts
+                    .patterns
             } else {
-                changed_files.changes
+                artifacts
             };
+            let mut paths = ArtifactManifest::resolve_patterns(repo_path, &patterns)
+                .context("failed to resolve artifacts")?;
+            if crates {
+                let graph = build_graph(repo_path).context("failed to build dependency graph")?;
+                paths.extend(graph.crates.values().map(|info| info.path.clone()));
+            }
+            if paths.is_empty() {
+                return Err(fail(
+                    Exit::Usage,
+                    "no artifacts given, pass paths or patterns, --crates, or set artifacts.patterns in deptrack.toml",
+                ));
+            }
+
+            let manifest =
+                ArtifactManifest::create(repo_path, &paths).context("failed to hash artifacts")?;
+            manifest
+                .save(&output)
+                .with_context(|| format!("failed to write manifest {}", output.display()))?;
 
             match format {
-                OutputFormat::Json => {
-                    println!("{}", serde_json::to_string_pretty(&filtered_changes)?);
+                OutputFormat::Json => print!("{}", manifest.to_json()?),
+                OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+                    println!("{}", format.render(&manifest)?)
+                }
+                OutputFormat::Human => println!(
+                    "{} artifacts hashed into {}",
+                    manifest.entries.len(),
+                    output.display()
+                ),
+            }
+        }
+        ManifestCommands::Verify { manifest, path } => {
+            let repo_path = path.as_ref().unwrap_or(default_path);
+            let loaded = ArtifactManifest::load(&manifest)
+                .with_context(|| format!("failed to load manifest {}", manifest.display()))?;
+            let verification = loaded
+                .verify(repo_path)
+                .context("failed to hash artifacts")?;
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&verification)?);
                 }
                 OutputFormat::Human => {
-                    println!("changes from {} to {}:", from, to);
-                    for change in filtered_changes {
-                        println!("  {:?}: {}", change.change_type, change.path.display());
+                    for path in &verification.missing {
+                        println!("  - {} (missing)", path);
+                    }
+                    for mismatch in &verification.mismatched {
+                        println!(
+                            "  ! {} ({} differ)",
+                            mismatch.path,
+                            mismatch.fields.join(", ")
+                        );
                     }
+                    println!(
+                        "{} verified, {} changed, {} missing",
+                        verification.verified.len(),
+                        verification.mismatched.len(),
+                        verification.missing.len()
+                    );
+                }
+            }
+
+            if !verification.is_ok() {
+                return Err(fail(
+                    Exit::Errors,
+                    format!("artifacts do not match {}", manifest.display()),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_fingerprint(
+    default_path: &PathBuf,
+    format: &OutputFormat,
+    command: FingerprintCommands,
+) -> Result<()> {
+    use deptrack::{DeptrackConfig, FingerprintSnapshot};
+
+    match command {
+        FingerprintCommands::Create { path, output } => {
+            let root = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(root).context("failed to build dependency graph")?;
+            let snapshot =
+                FingerprintSnapshot::collect(&graph, root).context("failed to hash crates")?;
+            snapshot
+                .save(&output)
+                .with_context(|| format!("failed to write fingerprints {}", output.display()))?;
+
+            match format {
+                OutputFormat::Json
+                | OutputFormat::Jsonl
+                | OutputFormat::Yaml
+                | OutputFormat::Toml => {
+                    println!("{}", format.render(&snapshot)?);
                 }
+                OutputFormat::Human => println!(
+                    "{} crates fingerprinted into {}",
+                    snapshot.crates.len(),
+                    output.display()
+                ),
             }
         }
+        FingerprintCommands::Compare { old, new, path } => {
+            let root = path.as_ref().unwrap_or(default_path);
+            let graph = build_graph(root).context("failed to build dependency graph")?;
+            let config = DeptrackConfig::load_or_default(root);
+            let load = |file: &PathBuf| {
+                FingerprintSnapshot::load(file)
+                    .with_context(|| format!("failed to load fingerprints {}", file.display()))
+            };
+            let old = load(&old)?;
+            let new = match &new {
+                Some(file) => load(file)?,
+                None => {
+                    FingerprintSnapshot::collect(&graph, root).context("failed to hash crates")?
+                }
+            };
+            let impact = graph
+                .analyze_fingerprint_changes(root, &old, &new, &config.impact)
+                .context("failed to compare fingerprints")?;
+            display_change_impact(format, &impact)?;
+        }
     }
 
     Ok(())
 }
 
+// changed files and affected crates of an analysis without git
+fn display_change_impact(
+    format: &OutputFormat,
+    impact: &deptrack::ChangeImpactAnalysis,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
+            let output = serde_json::json!({
+                "changed_files": impact.changed_files,
+                "crate_changes": impact.changed_files_by_crate(),
+                "directly_affected_crates": impact.directly_affected_crates,
+                "all_affected_crates": impact.all_affected_crates,
+            });
+            println!("{}", format.render(&output)?);
+        }
+        OutputFormat::Human => {
+            if impact.changed_files.is_empty() {
+                println!("no changes");
+                return Ok(());
+            }
+            println!("changed files:");
+            for file in &impact.changed_files {
+                println!("  {}", file.display());
+            }
+            println!();
+            println!("affected crates:");
+            for crate_id in &impact.all_affected_crates {
+                let direct = impact.directly_affected_crates.contains(crate_id);
+                println!(
+                    "  {}{}",
+                    crate_id.display_name(),
+                    if direct { "" } else { " (dependent)" }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(debug_assertions)]
 fn handle_debug_workspaces(path: &PathBuf, format: &OutputFormat) -> Result<()> {
     let workspaces =
         CargoDiscovery::discover_workspaces(path).context("failed to discover cargo workspace")?;
 
     match format {
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
             let output: Vec<_> = workspaces
                 .iter()
                 .map(|w| {
@@ -592,7 +4025,7 @@ fn handle_debug_workspaces(path: &PathBuf, format: &OutputFormat) -> Result<()>
                     })
                 })
                 .collect();
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!("{}", format.render(&output)?);
         }
         OutputFormat::Human => {
             println!("workspaces:");
@@ -625,7 +4058,7 @@ fn handle_debug_crates(path: &PathBuf, format: &OutputFormat, _deps: bool) -> Re
         }
 
         match format {
-            OutputFormat::Json => {
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
                 let output: Vec<_> = all_crates
                     .iter()
                     .map(|c| {
@@ -634,10 +4067,16 @@ fn handle_debug_crates(path: &PathBuf, format: &OutputFormat, _deps: bool) -> Re
                             "workspace": c.id.workspace,
                             "version": c.version,
                             "path": c.path,
+                            "description": c.metadata.description,
+                            "license": c.metadata.license,
+                            "rust_version": c.metadata.rust_version,
+                            "publish": c.metadata.publish,
+                            "edition": c.metadata.edition,
+                            "features": c.metadata.features,
                         })
                     })
                     .collect();
-                println!("{}", serde_json::to_string_pretty(&output)?);
+                println!("{}", format.render(&output)?);
             }
             OutputFormat::Human => {
                 println!("crates:");
@@ -656,32 +4095,97 @@ fn handle_debug_crates(path: &PathBuf, format: &OutputFormat, _deps: bool) -> Re
     Ok(())
 }
 
-#[cfg(debug_assertions)]
-fn handle_debug_graph(
+// per-workspace table and the cross-workspace dependencies of `stats`
+fn display_workspace_statistics(stats: &deptrack::GraphStatistics) {
+    use deptrack::utils::table::{Align, Cell, Table};
+
+    let mut table = Table::new([
+        "Workspace",
+        "Crates",
+        "Internal",
+        "Outgoing",
+        "Incoming",
+        "Confined Cycles",
+        "Spanning Cycles",
+    ]);
+    for column in 1..7 {
+        table = table.align(column, Align::Right);
+    }
+    for workspace in &stats.workspaces {
+        table.row([
+            Cell::new(&workspace.workspace),
+            Cell::new(workspace.crate_count.to_string()),
+            Cell::new(workspace.internal_dependencies.to_string()),
+            Cell::new(workspace.outgoing_dependencies.to_string()),
+            Cell::new(workspace.incoming_dependencies.to_string()),
+            Cell::new(workspace.confined_cycles.to_string()),
+            Cell::new(workspace.spanning_cycles.to_string()),
+        ]);
+    }
+    table.print();
+
+    if stats.cross_workspace_matrix.is_empty() {
+        println!();
+        println!("no dependencies between workspaces");
+        return;
+    }
+    println!();
+    println!("dependencies between workspaces:");
+    for (from, targets) in &stats.cross_workspace_matrix {
+        for (to, count) in targets {
+            println!("  {} -> {}: {}", from, to, count);
+        }
+    }
+}
+
+fn handle_graph(
     path: &PathBuf,
     format: &OutputFormat,
     graph_format: GraphFormat,
+    file: Option<PathBuf>,
+    open: bool,
+    per_workspace: bool,
 ) -> Result<()> {
     {
-        let graph =
-            CrateDependencyGraph::build_from_repository(path).context("failed to build graph")?;
+        let graph = build_graph(path).context("failed to build graph")?;
 
         match graph_format {
             GraphFormat::Stats => {
-                let stats = graph.get_statistics();
+                let stats = if per_workspace {
+                    graph.get_statistics_per_workspace()
+                } else {
+                    graph.get_statistics()
+                };
                 match format {
-                    OutputFormat::Json => {
-                        println!("{}", serde_json::to_string_pretty(&stats)?);
+                    OutputFormat::Json
+                    | OutputFormat::Jsonl
+                    | OutputFormat::Yaml
+                    | OutputFormat::Toml => {
+                        println!("{}", format.render(&stats)?);
                     }
                     OutputFormat::Human => {
                         println!("dependency graph statistics:");
                         println!("  crate count: {}", stats.crate_count);
                         println!("  dependency count: {}", stats.dependency_count);
                         println!("  has cycles: {}", stats.has_cycles);
+                        if per_workspace {
+                            println!();
+                            display_workspace_statistics(&stats);
+                        }
                     }
                 }
             }
             GraphFormat::Json => {
+                // every crate carries the id of its strongly connected component
+                let scc_ids: std::collections::HashMap<String, usize> = graph
+                    .strongly_connected_components()
+                    .into_iter()
+                    .flat_map(|component| {
+                        let id = component.id;
+                        component.members.into_iter().map(move |name| (name, id))
+                    })
+                    .collect();
+
                 // serialize the entire graph structure
                 let output: Vec<_> = graph.crates
                         .values()
@@ -693,32 +4197,36 @@ fn handle_debug_graph(
                                 "name": c.id.name,
                                 "workspace": c.id.workspace,
                                 "version": c.version,
+                                "description": c.metadata.description,
+                                "license": c.metadata.license,
+                                "rust_version": c.metadata.rust_version,
+                                "publish": c.metadata.publish,
+                                "edition": c.metadata.edition,
+                                "features": c.metadata.features,
                                 "dependencies": deps.iter().map(|id| id.display_name()).collect::<Vec<_>>(),
                                 "dependents": dependents.iter().map(|id| id.display_name()).collect::<Vec<_>>(),
+                                "scc": scc_ids.get(&c.id.display_name()),
                             })
                         })
                         .collect();
-                println!("{}", serde_json::to_string_pretty(&output)?);
+                println!("{}", format.render(&output)?);
             }
             GraphFormat::Dot => {
-                // generate DOT format for graphviz
-                println!("digraph dependencies {{");
-                println!("  rankdir=LR;");
-                println!("  node [shape=box];");
-
-                for crate_info in graph.crates.values() {
-                    let deps = graph.get_dependencies(&crate_info.id);
-
-                    for dep_id in deps {
-                        println!(
-                            "  \"{}\" -> \"{}\";",
-                            crate_info.id.display_name(),
-                            dep_id.display_name()
-                        );
-                    }
+                print!("{}", graph.to_dot());
+            }
+            GraphFormat::Image(image_format) => {
+                let file = file.unwrap_or_else(|| {
+                    PathBuf::from(format!("deptrack-graph.{}", image_format.extension()))
+                });
+                let image = deptrack::render_image(&graph, image_format)
+                    .context("failed to render the dependency graph")?;
+                std::fs::write(&file, image)
+                    .with_context(|| format!("failed to write {}", file.display()))?;
+                println!("graph written to {}", file.display());
+                if open {
+                    deptrack::open_file(&file)
+                        .with_context(|| format!("failed to open {}", file.display()))?;
                 }
-
-                println!("}}");
             }
         }
     }
@@ -726,23 +4234,34 @@ fn handle_debug_graph(
 }
 
 #[cfg(debug_assertions)]
-fn handle_debug_stats(path: &PathBuf, format: &OutputFormat) -> Result<()> {
+fn handle_debug_stats(
+    path: &PathBuf,
+    format: &OutputFormat,
+    sort_by: deptrack::CrateMetricsSort,
+) -> Result<()> {
     let workspaces =
         CargoDiscovery::discover_workspaces(path).context("failed to discover cargo workspace")?;
 
     {
-        let graph =
-            CrateDependencyGraph::build_from_repository(path).context("failed to build graph")?;
-        let stats = graph.get_statistics();
+        let graph = build_graph(path).context("failed to build graph")?;
+        let mut stats = graph
+            .get_statistics_with_metrics()
+            .context("failed to collect crate metrics")?;
+        deptrack::CrateMetrics::sort(&mut stats.crate_metrics, sort_by);
+        let duplicates = deptrack::DuplicateChecker::analyze(&graph)
+            .context("failed to check for duplicate dependencies")?;
+        let health = compute_health(path, &graph, None)?;
 
         match format {
-            OutputFormat::Json => {
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml | OutputFormat::Toml => {
                 let output = serde_json::json!({
                     "workspaces": workspaces.len(),
                     "crates": graph.crates.len(),
                     "statistics": stats,
+                    "duplicate_dependencies": duplicates.duplicates,
+                    "health": health,
                 });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+                println!("{}", format.render(&output)?);
             }
             OutputFormat::Human => {
                 println!("repository statistics:");
@@ -750,6 +4269,13 @@ fn handle_debug_stats(path: &PathBuf, format: &OutputFormat) -> Result<()> {
                 println!("  crates: {}", graph.crates.len());
                 println!("  dependency count: {}", stats.dependency_count);
                 println!("  has cycles: {}", stats.has_cycles);
+                println!("  duplicate dependencies: {}", duplicates.duplicates.len());
+                if !stats.crate_metrics.is_empty() {
+                    println!();
+                    deptrack::CrateMetrics::display_table(&stats.crate_metrics);
+                }
+                println!();
+                health.display(false);
             }
         }
     }
@@ -42,6 +42,27 @@ pub enum Error {
         line: usize,
         reason: String,
     },
+    ReleaseError {
+        reason: String,
+    },
+    RegistryError {
+        reason: String,
+    },
+    NotifyError {
+        reason: String,
+    },
+    GithubError {
+        reason: String,
+    },
+    RenderError {
+        reason: String,
+    },
+    ReportError {
+        reason: String,
+    },
+    Cancelled {
+        reason: String,
+    },
 }
 
 impl fmt::Display for Error {
@@ -97,6 +118,27 @@ impl fmt::Display for Error {
                     reason
                 )
             }
+            Error::ReleaseError { reason } => {
+                write!(f, "release error: {}", reason)
+            }
+            Error::RegistryError { reason } => {
+                write!(f, "registry error: {}", reason)
+            }
+            Error::NotifyError { reason } => {
+                write!(f, "notification error: {}", reason)
+            }
+            Error::GithubError { reason } => {
+                write!(f, "github error: {}", reason)
+            }
+            Error::RenderError { reason } => {
+                write!(f, "render error: {}", reason)
+            }
+            Error::ReportError { reason } => {
+                write!(f, "report error: {}", reason)
+            }
+            Error::Cancelled { reason } => {
+                write!(f, "analysis cancelled: {}", reason)
+            }
         }
     }
 }
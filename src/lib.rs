@@ -2,17 +2,86 @@ pub mod error;
 pub mod utils;
 
 pub use error::*;
+pub use utils::analysis_cache::{AnalysisCache, CACHE_DIR, CachedAnalysis};
+pub use utils::bazel_export::{BazelExporter, BazelOutput, BazelTarget, DEFAULT_LABEL_TEMPLATE};
+pub use utils::cancel::CancelToken;
 pub use utils::cargo_ops::{
-    CargoDiscovery, ChangeImpactAnalysis, CrateDependencyGraph, CrateId, CrateInfo,
-    GraphStatistics, VersionBumpAnalysis, VersionBumpStatus,
+    BrokenManifest, CargoDiscovery, ChangeImpactAnalysis, CrateDependencyGraph, CrateFingerprint,
+    CrateId, CrateInfo, CrateMetadata, CrateMetrics, CrateMetricsSort, CycleEdge,
+    DependencyDeclaration, FINGERPRINT_VERSION, FingerprintChange, FingerprintSnapshot,
+    GeneratedFiles, GraphStatistics, RemovedCrate, Scope, StronglyConnectedComponent, TreeOptions,
+    VersionBumpAnalysis, VersionBumpStatus, WorkspaceStatistics,
 };
 pub use utils::changelog::{
-    Changelog, ChangelogAnalysis, ChangelogConfig, ChangelogEntry, ChangelogStatus,
-    ChangelogVersion, has_version_entry, parse_changelog, validate_changelog, version_has_content,
+    Changelog, ChangelogAnalysis, ChangelogConfig, ChangelogEntry, ChangelogLink, ChangelogStatus,
+    ChangelogVersion, TemplateConfig, TemplateSet, has_version_entry, parse_changelog,
+    parse_changelog_content, validate_changelog, validate_links, version_has_content,
 };
 pub use utils::changelog_checker::ChangelogChecker;
-pub use utils::config::DeptrackConfig;
+pub use utils::changelog_coverage::{ChangelogCoverage, CrateCoverage, DEFAULT_MIN_COMMITS};
+pub use utils::ci_matrix::{CiJob, CiMatrix, CiProvider, MatrixEntry};
+pub use utils::codeowners::{CODEOWNERS_LOCATIONS, CodeOwners};
+pub use utils::config::{
+    ArtifactConfig, DeptrackConfig, ExternalCheckConfig, ImpactConfig, ImpactPropagation,
+    MsrvConfig, NotifyConfig, ReleasesConfig, ReportConfig, RequirementUpdate, SeveritySettings,
+};
+pub use utils::crate_detail::{CrateDetail, ReleaseTag};
+pub use utils::daemon::Daemon;
+#[cfg(feature = "serve")]
+pub use utils::dashboard::{DEFAULT_BIND_ADDRESS, DashboardServer};
+pub use utils::doctor::{Diagnosis, DiagnosisStatus, Doctor, DoctorReport};
+pub use utils::external_checker::{
+    ExternalCheckAnalysis, ExternalCheckStatus, ExternalChecker, ReportedIssue,
+};
+pub use utils::feature_checker::{FeatureAnalysis, FeatureChecker, FeatureStatus};
 pub use utils::filesystem::*;
-pub use utils::git_ops::{ChangeType, ChangedFiles, FileChange, GitOps, GitRef, GitRepository};
+pub use utils::fixes::{FIXES_DIR, FixPatch, FixPlanner, unified_diff};
+pub use utils::git_ops::{
+    AuthorSummary, BaseRef, BaseRefSource, ChangeType, ChangedFiles, CommitSummary, CurrentRef,
+    DEFAULT_TAG_PATTERN, DiffMode, FileChange, GitOps, GitRef, GitRepository, Pathspec, TagInfo,
+    TagPattern, latest_release_tag, release_tag_name,
+};
+pub use utils::github::{
+    COMMENT_MARKER, GITHUB_API_URL, GithubClient, STATUS_CONTEXT, pull_request_from_event,
+    render_markdown, report_totals,
+};
+pub use utils::graph_image::{ImageFormat, layout_svg, open_file, render_image};
+pub use utils::health::{CrateHealth, HealthReport, HealthScorer, WorkspaceHealth};
+pub use utils::issue_history::{HISTORY_FILE, HistoryFile, IssueHistory, IssueKey};
+pub use utils::lockfile_checker::{LockfileAnalysis, LockfileChecker, LockfileStatus};
+pub use utils::manifest_checker::{
+    ManifestAnalysis, ManifestChecker, ManifestStatus, UnversionedDependency,
+};
+pub use utils::membership_checker::{
+    MembershipAnalysis, MembershipChecker, MembershipStatus, NO_WORKSPACE,
+};
+pub use utils::msrv_checker::{MsrvAnalysis, MsrvChecker, MsrvStatus};
+pub use utils::notify::{DEFAULT_TEMPLATE, Notifier, NotifySummary};
+pub use utils::registry::{
+    CRATES_IO_INDEX, CrateFreshness, DependencyFreshness, DuplicateChecker, DuplicateDependency,
+    DuplicateReport, DuplicateVersion, FreshnessChecker, FreshnessReport, LockedPackage,
+    SparseIndex, VersionSource,
+};
+pub use utils::release::{
+    BumpLevel, ChangelogDraft, CrateDraft, PlannedBump, ReleaseAction, ReleaseExecutor,
+    ReleaseOptions, ReleasePlan, ReleasePlanner,
+};
+pub use utils::report::{
+    AnalysisSection, CheckTotals, CycleSection, LicenseSection, ManifestSection, UnifiedReport,
+    VersionSection,
+};
+pub use utils::report_compare::{ComplianceChange, CrateCompliance, ReportComparison, ReportIssue};
+pub use utils::requirement_sync::{
+    MissingRequirement, RequirementSync, RequirementSyncReport, StaleRequirement,
+};
 pub use utils::severity::{Issue, IssueSeverity, IssueType};
-pub use utils::severity_config::SeverityConfig;
+pub use utils::severity_config::{
+    SeverityConfig, SeverityMetadata, SeverityPreset, SeverityRule, SeverityScope,
+};
+pub use utils::tasks::{OwnerSource, OwnerTasks, TaskList, UNOWNED};
+pub use utils::test_plan::{
+    CrateTestPlan, TestCommand, TestPlan, TestPlanner, TestTarget, TestTargetKind,
+};
+#[cfg(feature = "tui")]
+pub use utils::tui::{TuiApp, fuzzy_score};
+pub use utils::version_policy::{CrateMaturity, PolicyRule, RequiredBump, VersionPolicy};
@@ -63,6 +63,7 @@ fn find_file_in_tree<'a>(directory: &'a FsDirectory, target_path: &str) -> Optio
                     return Some(found);
                 }
             }
+            FsElement::Symlink(_) => {}
         }
     }
     None
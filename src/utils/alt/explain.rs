@@ -0,0 +1,261 @@
+use super::evaluable::Evaluable;
+use super::expr::LogicExpr;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ExplainNode<'a, T> {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Not,
+    Leaf(&'a T),
+}
+
+// outcome of every node in an expression for one context
+// result is None for branches skipped by short-circuiting
+#[derive(Debug, Clone)]
+pub struct ExplanationTree<'a, T> {
+    pub node: ExplainNode<'a, T>,
+    pub result: Option<bool>,
+    pub children: Vec<ExplanationTree<'a, T>>,
+}
+
+impl<'a, T> ExplanationTree<'a, T> {
+    fn evaluated(node: ExplainNode<'a, T>, result: bool, children: Vec<Self>) -> Self {
+        Self {
+            node,
+            result: Some(result),
+            children,
+        }
+    }
+
+    // the shape of a subtree that was never evaluated
+    fn skipped(expr: &'a LogicExpr<T>) -> Self {
+        let (node, children) = match expr {
+            LogicExpr::And(l, r) => (ExplainNode::And, vec![Self::skipped(l), Self::skipped(r)]),
+            LogicExpr::Or(l, r) => (ExplainNode::Or, vec![Self::skipped(l), Self::skipped(r)]),
+            LogicExpr::Xor(l, r) => (ExplainNode::Xor, vec![Self::skipped(l), Self::skipped(r)]),
+            LogicExpr::Nand(l, r) => (ExplainNode::Nand, vec![Self::skipped(l), Self::skipped(r)]),
+            LogicExpr::Not(e) => (ExplainNode::Not, vec![Self::skipped(e)]),
+            LogicExpr::Leaf(p) => (ExplainNode::Leaf(p), Vec::new()),
+        };
+        Self {
+            node,
+            result: None,
+            children,
+        }
+    }
+
+    // leaf predicates that were evaluated, with their results
+    pub fn leaves(&self) -> Vec<(&'a T, bool)> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<(&'a T, bool)>) {
+        if let (ExplainNode::Leaf(predicate), Some(result)) = (&self.node, self.result) {
+            leaves.push((*predicate, result));
+        }
+        for child in &self.children {
+            child.collect_leaves(leaves);
+        }
+    }
+}
+
+impl<T> LogicExpr<T>
+where
+    T: Evaluable,
+{
+    // same semantics as evaluate(), including short-circuiting, but keeps a
+    // record of how every node evaluated
+    pub fn evaluate_explain(
+        &self,
+        context: &T::Context,
+    ) -> Result<(bool, ExplanationTree<'_, T>), T::Error> {
+        let tree = self.explain_node(context)?;
+        Ok((tree.result.unwrap_or(false), tree))
+    }
+
+    fn explain_node(&self, context: &T::Context) -> Result<ExplanationTree<'_, T>, T::Error> {
+        let tree = match self {
+            LogicExpr::And(left, right) => {
+                let left = left.explain_node(context)?;
+                if left.result == Some(false) {
+                    ExplanationTree::evaluated(
+                        ExplainNode::And,
+                        false,
+                        vec![left, ExplanationTree::skipped(right)],
+                    )
+                } else {
+                    let right = right.explain_node(context)?;
+                    let result = right.result == Some(true);
+                    ExplanationTree::evaluated(ExplainNode::And, result, vec![left, right])
+                }
+            }
+            LogicExpr::Or(left, right) => {
+                let left = left.explain_node(context)?;
+                if left.result == Some(true) {
+                    ExplanationTree::evaluated(
+                        ExplainNode::Or,
+                        true,
+                        vec![left, ExplanationTree::skipped(right)],
+                    )
+                } else {
+                    let right = right.explain_node(context)?;
+                    let result = right.result == Some(true);
+                    ExplanationTree::evaluated(ExplainNode::Or, result, vec![left, right])
+                }
+            }
+            LogicExpr::Xor(left, right) => {
+                let left = left.explain_node(context)?;
+                let right = right.explain_node(context)?;
+                let result = left.result != right.result;
+                ExplanationTree::evaluated(ExplainNode::Xor, result, vec![left, right])
+            }
+            LogicExpr::Nand(left, right) => {
+                let left = left.explain_node(context)?;
+                if left.result == Some(false) {
+                    ExplanationTree::evaluated(
+                        ExplainNode::Nand,
+                        true,
+                        vec![left, ExplanationTree::skipped(right)],
+                    )
+                } else {
+                    let right = right.explain_node(context)?;
+                    let result = right.result != Some(true);
+                    ExplanationTree::evaluated(ExplainNode::Nand, result, vec![left, right])
+                }
+            }
+            LogicExpr::Not(expr) => {
+                let inner = expr.explain_node(context)?;
+                let result = inner.result != Some(true);
+                ExplanationTree::evaluated(ExplainNode::Not, result, vec![inner])
+            }
+            LogicExpr::Leaf(predicate) => ExplanationTree::evaluated(
+                ExplainNode::Leaf(predicate),
+                predicate.evaluate(context)?,
+                Vec::new(),
+            ),
+        };
+        Ok(tree)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for ExplainNode<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExplainNode::And => write!(f, "and"),
+            ExplainNode::Or => write!(f, "or"),
+            ExplainNode::Xor => write!(f, "xor"),
+            ExplainNode::Nand => write!(f, "nand"),
+            ExplainNode::Not => write!(f, "not"),
+            ExplainNode::Leaf(predicate) => write!(f, "{}", predicate),
+        }
+    }
+}
+
+// one node per line, indented by depth:
+//   [match] and
+//   [match]   ext == 'rs'
+//   [fail ]   size >= 10240
+impl<T: fmt::Display> fmt::Display for ExplanationTree<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl<T: fmt::Display> ExplanationTree<'_, T> {
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let status = match self.result {
+            Some(true) => "match",
+            Some(false) => "fail ",
+            None => "skip ",
+        };
+        writeln!(f, "[{}] {}{}", status, "  ".repeat(depth), self.node)?;
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Display> Serialize for ExplanationTree<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ExplanationTree", 3)?;
+        state.serialize_field("expr", &self.node.to_string())?;
+        state.serialize_field("result", &self.result)?;
+        if self.children.is_empty() {
+            state.skip_field("children")?;
+        } else {
+            state.serialize_field("children", &self.children)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Named(&'static str, bool);
+
+    impl Evaluable for Named {
+        type Context = ();
+        type Error = &'static str;
+
+        fn evaluate(&self, _context: &Self::Context) -> Result<bool, Self::Error> {
+            Ok(self.1)
+        }
+    }
+
+    impl fmt::Display for Named {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_explain_matches_evaluate() {
+        let exprs = [
+            LogicExpr::and(Named("a", true).into(), Named("b", false).into()),
+            LogicExpr::or(Named("a", false).into(), Named("b", true).into()),
+            LogicExpr::xor(Named("a", true).into(), Named("b", true).into()),
+            LogicExpr::nand(Named("a", true).into(), Named("b", false).into()),
+            LogicExpr::negate(LogicExpr::and(
+                Named("a", true).into(),
+                Named("b", true).into(),
+            )),
+        ];
+
+        for expr in &exprs {
+            let (result, tree) = expr.evaluate_explain(&()).unwrap();
+            assert_eq!(result, expr.evaluate(&()).unwrap());
+            assert_eq!(tree.result, Some(result));
+        }
+    }
+
+    #[test]
+    fn test_explain_records_short_circuit() {
+        let expr = LogicExpr::and(
+            Named("ext", false).into(),
+            LogicExpr::negate(Named("path", true).into()),
+        );
+        let (result, tree) = expr.evaluate_explain(&()).unwrap();
+
+        assert!(!result);
+        assert_eq!(
+            tree.to_string(),
+            "[fail ] and\n[fail ]   ext\n[skip ]   not\n[skip ]     path\n"
+        );
+        let leaves: Vec<_> = tree.leaves().iter().map(|(p, r)| (p.0, *r)).collect();
+        assert_eq!(leaves, vec![("ext", false)]);
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["children"][1]["children"][0]["expr"], "path");
+        assert!(json["children"][1]["result"].is_null());
+    }
+}
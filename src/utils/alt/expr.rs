@@ -1,10 +1,13 @@
 use super::evaluable::Evaluable;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LogicExpr<T> {
     And(Box<LogicExpr<T>>, Box<LogicExpr<T>>),
     Or(Box<LogicExpr<T>>, Box<LogicExpr<T>>),
     Xor(Box<LogicExpr<T>>, Box<LogicExpr<T>>),
+    Nand(Box<LogicExpr<T>>, Box<LogicExpr<T>>),
     Not(Box<LogicExpr<T>>),
     Leaf(T),
 }
@@ -38,6 +41,22 @@ impl<T> ExprBuilder<T> {
         }
     }
 
+    pub fn nand(self, other: impl Into<LogicExpr<T>>) -> Self {
+        Self {
+            expr: LogicExpr::Nand(Box::new(self.expr), Box::new(other.into())),
+        }
+    }
+
+    // "this, except other", e.g. rust files except those under tests/
+    pub fn and_not(self, other: impl Into<LogicExpr<T>>) -> Self {
+        Self {
+            expr: LogicExpr::And(
+                Box::new(self.expr),
+                Box::new(LogicExpr::Not(Box::new(other.into()))),
+            ),
+        }
+    }
+
     pub fn negate(self) -> Self {
         Self {
             expr: LogicExpr::Not(Box::new(self.expr)),
@@ -62,6 +81,10 @@ impl<T> LogicExpr<T> {
         LogicExpr::Xor(Box::new(left), Box::new(right))
     }
 
+    pub fn nand(left: LogicExpr<T>, right: LogicExpr<T>) -> Self {
+        LogicExpr::Nand(Box::new(left), Box::new(right))
+    }
+
     pub fn negate(expr: LogicExpr<T>) -> Self {
         LogicExpr::Not(Box::new(expr))
     }
@@ -96,6 +119,13 @@ where
                 let right_result = right.evaluate(context)?;
                 Ok(left_result != right_result)
             }
+            LogicExpr::Nand(left, right) => {
+                let left_result = left.evaluate(context)?;
+                if !left_result {
+                    return Ok(true);
+                }
+                Ok(!right.evaluate(context)?)
+            }
             LogicExpr::Not(expr) => {
                 let result = expr.evaluate(context)?;
                 Ok(!result)
@@ -211,6 +241,30 @@ mod tests {
         assert!(expr.evaluate(&()).unwrap());
     }
 
+    #[test]
+    fn test_nand_evaluation() {
+        let true_pred = TestPredicate::new("true", true);
+        let false_pred = TestPredicate::new("false", false);
+
+        let expr = LogicExpr::nand(
+            LogicExpr::Leaf(true_pred.clone()),
+            LogicExpr::Leaf(true_pred.clone()),
+        );
+        assert!(!expr.evaluate(&()).unwrap());
+
+        let expr = LogicExpr::nand(
+            LogicExpr::Leaf(true_pred),
+            LogicExpr::Leaf(false_pred.clone()),
+        );
+        assert!(expr.evaluate(&()).unwrap());
+
+        let expr = LogicExpr::nand(
+            LogicExpr::Leaf(false_pred.clone()),
+            LogicExpr::Leaf(false_pred),
+        );
+        assert!(expr.evaluate(&()).unwrap());
+    }
+
     #[test]
     fn test_complex_expression() {
         let true_pred = TestPredicate::new("true", true);
@@ -273,6 +327,28 @@ mod tests {
         assert!(expr.evaluate(&()).unwrap());
     }
 
+    #[test]
+    fn test_builder_nand_and_not() {
+        let true_pred = TestPredicate::new("true", true);
+        let false_pred = TestPredicate::new("false", false);
+
+        let expr = ExprBuilder::new(true_pred.clone())
+            .nand(true_pred.clone())
+            .build();
+        assert!(!expr.evaluate(&()).unwrap());
+
+        // true except false = true, true except true = false
+        let expr = ExprBuilder::new(true_pred.clone())
+            .and_not(false_pred)
+            .build();
+        assert!(expr.evaluate(&()).unwrap());
+
+        let expr = ExprBuilder::new(true_pred.clone())
+            .and_not(true_pred)
+            .build();
+        assert!(!expr.evaluate(&()).unwrap());
+    }
+
     #[test]
     fn test_from_conversion() {
         let pred = TestPredicate::new("test", true);
@@ -1,5 +1,7 @@
 pub mod evaluable;
+pub mod explain;
 pub mod expr;
 
 pub use evaluable::Evaluable;
+pub use explain::{ExplainNode, ExplanationTree};
 pub use expr::{ExprBuilder, LogicExpr};
@@ -0,0 +1,109 @@
+// check-versions results cached per pair of commits, so ci jobs checking the
+// same pull request head skip the git diffing and version lookups
+
+use super::cargo_ops::integration::{ChangeImpactAnalysis, VersionBumpAnalysis};
+use super::config::DeptrackConfig;
+use super::git_ops::{GitOps, GitRef};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// cache directory, relative to the repository root
+pub const CACHE_DIR: &str = ".deptrack/cache";
+
+/// contents of one cache file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub deptrack_version: String,
+    pub config_hash: String,
+    /// commit the crates and their current versions were read from
+    pub head: String,
+    pub impact: ChangeImpactAnalysis,
+    pub versions: VersionBumpAnalysis,
+}
+
+/// the cache entry of one `from`..`to` comparison
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    path: PathBuf,
+    config_hash: String,
+    head: String,
+}
+
+impl AnalysisCache {
+    /// entry for comparing `from_ref` with `to_ref` under `config`; None when
+    /// the working tree has uncommitted changes, the results would depend on them
+    pub fn for_refs<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        config: &DeptrackConfig,
+    ) -> Result<Option<Self>> {
+        let repo_path = repo_path.as_ref();
+        if !GitOps::list_working_directory_changes(repo_path)?
+            .changes
+            .is_empty()
+        {
+            return Ok(None);
+        }
+
+        let root = GitOps::detect_repository_root(repo_path)?;
+        let from = GitOps::resolve_ref(repo_path, from_ref)?;
+        let to = GitOps::resolve_ref(repo_path, to_ref)?;
+        let head = GitOps::resolve_ref(repo_path, &GitRef::Head)?;
+        Ok(Some(Self {
+            path: root.join(CACHE_DIR).join(format!("{}_{}.json", from, to)),
+            config_hash: config_hash(config)?,
+            head,
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// the cached analyses; a missing, unreadable or stale entry is a miss
+    pub fn load(&self) -> Option<(ChangeImpactAnalysis, VersionBumpAnalysis)> {
+        let content = std::fs::read_to_string(&self.path).ok()?;
+        let cached: CachedAnalysis = serde_json::from_str(&content).ok()?;
+        let fresh = cached.deptrack_version == env!("CARGO_PKG_VERSION")
+            && cached.config_hash == self.config_hash
+            && cached.head == self.head;
+        fresh.then_some((cached.impact, cached.versions))
+    }
+
+    /// write the entry, replacing the file atomically so concurrent jobs
+    /// never read half of it
+    pub fn store(
+        &self,
+        impact: &ChangeImpactAnalysis,
+        versions: &VersionBumpAnalysis,
+    ) -> Result<()> {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        let gitignore = dir.join(".gitignore");
+        if !gitignore.exists() {
+            std::fs::write(gitignore, "*\n")?;
+        }
+
+        let cached = CachedAnalysis {
+            deptrack_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: self.config_hash.clone(),
+            head: self.head.clone(),
+            impact: impact.clone(),
+            versions: versions.clone(),
+        };
+        let json = serde_json::to_string(&cached).map_err(std::io::Error::from)?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(json.as_bytes())?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+// changing any setting, even one check-versions does not read, starts over
+fn config_hash(config: &DeptrackConfig) -> Result<String> {
+    let json = serde_json::to_vec(config).map_err(std::io::Error::from)?;
+    Ok(blake3::hash(&json).to_hex().to_string())
+}
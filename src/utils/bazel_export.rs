@@ -0,0 +1,289 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, DependencyType};
+use super::test_plan::relative_to;
+use crate::utils::toml_ops::TomlReader;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// label used when no template is given, a package per crate directory
+pub const DEFAULT_LABEL_TEMPLATE: &str = "//{path}:{name}";
+
+/// what the exporter emits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BazelOutput {
+    /// a BUILD.bazel skeleton per crate directory
+    Build,
+    /// one `from to` label pair per dependency edge
+    Query,
+}
+
+impl std::str::FromStr for BazelOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "build" => Ok(BazelOutput::Build),
+            "query" => Ok(BazelOutput::Query),
+            _ => Err(format!(
+                "invalid bazel output: {}, use 'build' or 'query'",
+                s
+            )),
+        }
+    }
+}
+
+/// a crate mapped to a bazel `rust_library` target
+#[derive(Debug, Clone, Serialize)]
+pub struct BazelTarget {
+    pub crate_id: CrateId,
+    pub label: String,
+    /// crate directory relative to the repository
+    pub path: PathBuf,
+    pub edition: Option<String>,
+    /// labels of normal and build dependencies
+    pub deps: Vec<String>,
+    /// labels of dev-dependencies, used by the `rust_test` target
+    pub dev_deps: Vec<String>,
+}
+
+impl BazelTarget {
+    /// target name, the part of the label after `:` (or its last path segment)
+    pub fn name(&self) -> &str {
+        match self.label.rsplit_once(':') {
+            Some((_, name)) => name,
+            None => self.label.rsplit('/').next().unwrap_or(&self.label),
+        }
+    }
+}
+
+/// maps the crate graph onto bazel target labels
+pub struct BazelExporter {
+    label_template: String,
+}
+
+impl Default for BazelExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BazelExporter {
+    pub fn new() -> Self {
+        Self {
+            label_template: DEFAULT_LABEL_TEMPLATE.to_string(),
+        }
+    }
+
+    /// template for target labels, `{path}` is the crate directory relative
+    /// to the repository, `{name}` the crate name and `{workspace}` its workspace
+    pub fn label_template(mut self, template: impl Into<String>) -> Self {
+        self.label_template = template.into();
+        self
+    }
+
+    /// label of a crate living in `path`
+    pub fn label(&self, crate_id: &CrateId, path: &Path) -> String {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.label_template
+            .replace("{path}", path.trim_matches('/'))
+            .replace("{name}", &crate_id.name)
+            .replace("{workspace}", &crate_id.workspace)
+    }
+
+    /// every crate of the graph as a target, sorted by label
+    pub fn targets<P: AsRef<Path>>(
+        &self,
+        graph: &CrateDependencyGraph,
+        repo_root: P,
+    ) -> Vec<BazelTarget> {
+        let repo_root = repo_root.as_ref();
+        let labels: HashMap<&CrateId, (String, PathBuf)> = graph
+            .crates
+            .iter()
+            .map(|(id, info)| {
+                let path = relative_to(&info.path, repo_root);
+                (id, (self.label(id, &path), path))
+            })
+            .collect();
+
+        let mut targets: Vec<BazelTarget> = labels
+            .iter()
+            .map(|(crate_id, (label, path))| {
+                let mut deps = Vec::new();
+                let mut dev_deps = Vec::new();
+                if let Some(&index) = graph.node_indices.get(*crate_id) {
+                    for edge in graph
+                        .graph
+                        .edges_directed(index, petgraph::Direction::Outgoing)
+                    {
+                        let Some((dep_label, _)) = labels.get(&graph.graph[edge.target()]) else {
+                            continue;
+                        };
+                        match edge.weight() {
+                            DependencyType::Dev => dev_deps.push(dep_label.clone()),
+                            DependencyType::Normal | DependencyType::Build => {
+                                deps.push(dep_label.clone())
+                            }
+                        }
+                    }
+                }
+                for list in [&mut deps, &mut dev_deps] {
+                    list.sort();
+                    list.dedup();
+                }
+                dev_deps.retain(|label| !deps.contains(label));
+
+                let edition =
+                    TomlReader::read_cargo_toml_with_workspace(&graph.crates[*crate_id].path)
+                        .ok()
+                        .and_then(|doc| doc.get_package_field("edition"))
+                        .and_then(|v| v.as_str().map(str::to_string));
+
+                BazelTarget {
+                    crate_id: (*crate_id).clone(),
+                    label: label.clone(),
+                    path: path.clone(),
+                    edition,
+                    deps,
+                    dev_deps,
+                }
+            })
+            .collect();
+        targets.sort_by(|a, b| a.label.cmp(&b.label));
+        targets
+    }
+
+    /// BUILD.bazel skeletons, one section per crate headed by the file path
+    pub fn build_files(targets: &[BazelTarget]) -> String {
+        let mut out = String::new();
+        for target in targets {
+            let build_file = target.path.join("BUILD.bazel");
+            let _ = writeln!(out, "# {}", build_file.display());
+            let _ = writeln!(
+                out,
+                "load(\"@rules_rust//rust:defs.bzl\", \"rust_library\", \"rust_test\")"
+            );
+            let _ = writeln!(out);
+            let _ = writeln!(out, "rust_library(");
+            let _ = writeln!(out, "    name = {:?},", target.name());
+            let _ = writeln!(out, "    srcs = glob([\"src/**/*.rs\"]),");
+            let _ = writeln!(
+                out,
+                "    crate_name = {:?},",
+                target.crate_id.name.replace('-', "_")
+            );
+            if let Some(edition) = &target.edition {
+                let _ = writeln!(out, "    edition = {:?},", edition);
+            }
+            write_label_list(&mut out, "deps", &target.deps);
+            let _ = writeln!(out, "    visibility = [\"//visibility:public\"],");
+            let _ = writeln!(out, ")");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "rust_test(");
+            let _ = writeln!(out, "    name = {:?},", format!("{}_test", target.name()));
+            let _ = writeln!(out, "    crate = {:?},", format!(":{}", target.name()));
+            write_label_list(&mut out, "deps", &target.dev_deps);
+            let _ = writeln!(out, ")");
+            let _ = writeln!(out);
+        }
+        out
+    }
+
+    /// one `from to` label pair per line, sorted, dev-dependencies included
+    pub fn query_listing(targets: &[BazelTarget]) -> String {
+        let mut edges: Vec<(&str, &str)> = targets
+            .iter()
+            .flat_map(|t| {
+                t.deps
+                    .iter()
+                    .chain(&t.dev_deps)
+                    .map(move |dep| (t.label.as_str(), dep.as_str()))
+            })
+            .collect();
+        edges.sort();
+        edges
+            .into_iter()
+            .map(|(from, to)| format!("{} {}\n", from, to))
+            .collect()
+    }
+}
+
+fn write_label_list(out: &mut String, attribute: &str, labels: &[String]) {
+    if labels.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "    {} = [", attribute);
+    for label in labels {
+        let _ = writeln!(out, "        {:?},", label);
+    }
+    let _ = writeln!(out, "    ],");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        for (name, deps) in [
+            ("core", ""),
+            (
+                "my-app",
+                "\n[dependencies]\ncore = { path = \"../core\" }\n\n[dev-dependencies]\ntestkit = { path = \"../testkit\" }\n",
+            ),
+            ("testkit", ""),
+        ] {
+            fs::create_dir_all(root.join("crates").join(name).join("src")).unwrap();
+            fs::write(
+                root.join("crates").join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n{}",
+                    name, deps
+                ),
+            )
+            .unwrap();
+        }
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let targets = BazelExporter::new().targets(&graph, root);
+        let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "//crates/core:core",
+                "//crates/my-app:my-app",
+                "//crates/testkit:testkit"
+            ]
+        );
+        assert_eq!(targets[1].deps, vec!["//crates/core:core"]);
+        assert_eq!(targets[1].dev_deps, vec!["//crates/testkit:testkit"]);
+
+        assert_eq!(
+            BazelExporter::query_listing(&targets),
+            "//crates/my-app:my-app //crates/core:core\n//crates/my-app:my-app //crates/testkit:testkit\n"
+        );
+
+        let build = BazelExporter::build_files(&targets);
+        assert!(build.contains("# crates/my-app/BUILD.bazel\n"));
+        assert!(build.contains("    crate_name = \"my_app\",\n    edition = \"2021\",\n    deps = [\n        \"//crates/core:core\",\n    ],\n"));
+        assert!(build.contains(
+            "    crate = \":my-app\",\n    deps = [\n        \"//crates/testkit:testkit\",\n"
+        ));
+
+        let custom = BazelExporter::new().label_template("@{workspace}//third_party/{name}");
+        let targets = custom.targets(&graph, root);
+        assert_eq!(targets[0].name(), "core");
+        assert!(targets[0].label.ends_with("//third_party/core"));
+    }
+}
@@ -0,0 +1,178 @@
+// cooperative cancellation of long running analyses: graph building, source
+// hashing and git operations check a token between steps and give up with
+// `Error::Cancelled` once it was cancelled or its deadline passed
+
+use crate::error::{Error, Result};
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// longest wait between two looks at a running git process
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// shared flag to abort an analysis, optionally with a deadline
+///
+/// clones share the flag, so an embedding application keeps one and hands
+/// the other to the analysis; cancelling either stops both
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// a token that is only cancelled by [`Self::cancel`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a token that is also cancelled `timeout` from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            timeout: Some(timeout),
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    /// stop every analysis holding this token or a clone of it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.timed_out()
+    }
+
+    /// true when the deadline passed, not when it was cancelled by hand
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// `Error::Cancelled` once the token is cancelled, to bail out with `?`
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(self.error());
+        }
+        Ok(())
+    }
+
+    fn error(&self) -> Error {
+        let reason = match self.timeout {
+            Some(timeout) if self.timed_out() => {
+                format!("timed out after {:?}", timeout)
+            }
+            _ => "cancelled".to_string(),
+        };
+        Error::Cancelled { reason }
+    }
+
+    // nothing can cancel a token without a deadline nobody else holds
+    fn can_cancel(&self) -> bool {
+        self.deadline.is_some() || Arc::strong_count(&self.cancelled) > 1
+    }
+
+    /// run `command` to completion like [`Command::output`], killing it when
+    /// the token is cancelled while it runs
+    pub fn output(&self, command: &mut Command) -> Result<Output> {
+        self.check()?;
+        if !self.can_cancel() {
+            return command.output().map_err(Error::IoError);
+        }
+
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(Error::IoError)?;
+        // drained on their own threads so a full pipe cannot stall the child
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let mut interval = Duration::from_millis(1);
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(Error::IoError)? {
+                break status;
+            }
+            if self.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(self.error());
+            }
+            std::thread::sleep(interval);
+            interval = (interval * 2).min(MAX_POLL_INTERVAL);
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_is_shared_by_clones() {
+        let token = CancelToken::new();
+        let analysis = token.clone();
+        assert!(analysis.check().is_ok());
+
+        token.cancel();
+        assert!(analysis.is_cancelled());
+        assert!(!analysis.timed_out());
+        assert_eq!(
+            analysis.check().unwrap_err().to_string(),
+            "analysis cancelled: cancelled"
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let token = CancelToken::with_timeout(Duration::ZERO);
+        assert!(token.timed_out());
+        assert_eq!(
+            token.check().unwrap_err().to_string(),
+            "analysis cancelled: timed out after 0ns"
+        );
+        assert!(
+            CancelToken::with_timeout(Duration::from_secs(3600))
+                .check()
+                .is_ok()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_kills_cancelled_command() {
+        let token = CancelToken::with_timeout(Duration::from_millis(50));
+        let start = Instant::now();
+        let result = token.output(Command::new("sleep").arg("5"));
+        assert!(matches!(result, Err(Error::Cancelled { .. })));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // a shared token runs the command the cancellable way too
+        let token = CancelToken::new();
+        let shared = token.clone();
+        let output = shared.output(Command::new("echo").arg("done")).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+    }
+}
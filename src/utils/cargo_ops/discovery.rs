@@ -1,19 +1,19 @@
-use super::types::{CrateId, CrateInfo, Workspace};
+use super::types::{
+    BrokenManifest, CrateId, CrateInfo, CrateMetadata, DependencyDeclaration, DependencyType,
+    Workspace,
+};
 use crate::error::{Error, Result};
 use crate::utils::filesystem::FilesystemExplorer;
-use crate::utils::toml_ops::TomlReader;
+use crate::utils::toml_ops::{TomlDocument, TomlError, TomlReader};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub struct CargoDiscovery;
 
 impl CargoDiscovery {
-    /// Discover all workspaces in a repository
-    pub fn discover_workspaces<P: AsRef<Path>>(repo_root: P) -> Result<Vec<Workspace>> {
-        let repo_root = repo_root.as_ref();
-        let explorer = FilesystemExplorer::new(repo_root.to_string_lossy().to_string());
-
-        // Find all Cargo.toml files
+    /// Find every Cargo.toml in a repository, build output and .git left out
+    pub fn find_manifests<P: AsRef<Path>>(repo_root: P) -> Result<Vec<PathBuf>> {
+        let explorer = FilesystemExplorer::new(repo_root.as_ref());
         let root_dir = explorer.scan_from_root().map_err(|e| {
             Error::IoError(std::io::Error::other(format!(
                 "Failed to scan repository: {}",
@@ -21,22 +21,86 @@ impl CargoDiscovery {
             )))
         })?;
 
-        let cargo_toml_files = explorer
+        Ok(explorer
             .find_files_by_extension(&root_dir, "toml")
             .into_iter()
             .filter(|path| path.ends_with("Cargo.toml"))
-            .collect::<Vec<_>>();
+            .collect())
+    }
 
-        let mut workspaces = Vec::new();
+    /// Find every Cargo.toml in a repository that discovery has to skip, see
+    /// [`Self::check_manifest`]
+    pub fn find_broken_manifests<P: AsRef<Path>>(repo_root: P) -> Result<Vec<BrokenManifest>> {
+        let mut broken: Vec<BrokenManifest> = Self::find_manifests(repo_root)?
+            .iter()
+            .filter_map(|path| Self::check_manifest(path))
+            .collect();
+        broken.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(broken)
+    }
+
+    /// What keeps the Cargo.toml at `path` from being used as a package or
+    /// workspace manifest: unreadable, invalid toml (with the position of the
+    /// error), a [package] without a name, `workspace.members` that is not
+    /// an array, or a fragment with neither [package] nor [workspace]
+    pub fn check_manifest(path: &Path) -> Option<BrokenManifest> {
+        let broken = |error: String, position: Option<(usize, usize)>| BrokenManifest {
+            path: path.to_path_buf(),
+            error,
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        };
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return Some(broken(e.to_string(), None)),
+        };
+        let manifest = match content.parse::<toml::Table>() {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let position = e.span().map(|span| line_and_column(&content, span.start));
+                let message: Vec<&str> = e
+                    .message()
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                return Some(broken(message.join("; "), position));
+            }
+        };
+
+        let package = manifest.get("package");
+        let workspace = manifest.get("workspace");
+        let error = if let Some(package) = package {
+            package
+                .get("name")
+                .and_then(toml::Value::as_str)
+                .is_none()
+                .then_some("[package] has no name")
+        } else if let Some(workspace) = workspace {
+            workspace
+                .get("members")
+                .is_some_and(|members| !members.is_array())
+                .then_some("workspace.members is not an array")
+        } else {
+            Some("neither [package] nor [workspace], cargo cannot load it")
+        };
+        error.map(|error| broken(error.to_string(), None))
+    }
 
-        for cargo_toml_path in cargo_toml_files {
-            let toml_path = PathBuf::from(cargo_toml_path);
+    /// Discover all workspaces in a repository
+    pub fn discover_workspaces<P: AsRef<Path>>(repo_root: P) -> Result<Vec<Workspace>> {
+        let cargo_toml_files = Self::find_manifests(repo_root)?;
+
+        let mut workspaces = Vec::new();
 
-            // Try to read and parse the Cargo.toml
+        for toml_path in cargo_toml_files {
+            // Try to read and parse the Cargo.toml, broken ones are reported
+            // by find_broken_manifests
             if let Ok(toml_doc) = TomlReader::read_file(&toml_path) {
                 // Check if this is a workspace Cargo.toml
                 if toml_doc.has_table("workspace")
-                    && let Some(workspace) = Self::parse_workspace(&toml_path, &toml_doc)?
+                    && let Ok(Some(workspace)) = Self::parse_workspace(&toml_path, &toml_doc)
                 {
                     workspaces.push(workspace);
                 }
@@ -186,14 +250,21 @@ impl CargoDiscovery {
             return Ok(None);
         }
 
-        let toml_doc =
-            TomlReader::read_file(&cargo_toml_path).map_err(|e| Error::FileReadError {
-                path: cargo_toml_path.clone(),
-                source: std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Failed to read Cargo.toml: {}", e),
-                ),
-            })?;
+        // manifests that do not parse are skipped here and reported by
+        // find_broken_manifests, so one bad file does not stop the analysis
+        let toml_doc = match TomlReader::read_cargo_toml_with_workspace(path) {
+            Ok(toml_doc) => toml_doc,
+            Err(TomlError::ParseError(_)) => return Ok(None),
+            Err(e) => {
+                return Err(Error::FileReadError {
+                    path: cargo_toml_path.clone(),
+                    source: std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Failed to read Cargo.toml: {}", e),
+                    ),
+                });
+            }
+        };
 
         // Check if this is a crate (has [package]) or workspace-only
         // workspace-only Cargo.toml files don't have a [package] section
@@ -201,27 +272,68 @@ impl CargoDiscovery {
             return Ok(None);
         };
 
-        let crate_name = package_table
-            .get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::WorkspaceError {
-                reason: "No package name found in Cargo.toml".to_string(),
-            })?
-            .to_string();
+        let Some(crate_name) = package_table.get("name").and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        let crate_name = crate_name.to_string();
 
         // version field is optional - some test packages don't have it
-        let version = package_table
-            .get("version")
-            .and_then(|v| v.as_str())
-            .unwrap_or("0.0.0") // default for packages without version
-            .to_string();
+        // version.workspace = true resolves through [workspace.package]
+        let version = toml_doc
+            .get_package_field("version")
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| "0.0.0".to_string()); // default for packages without version
 
         let crate_id = CrateId::new(workspace_name.to_string(), crate_name);
-        let crate_info = CrateInfo::new(crate_id, version, path.to_path_buf());
+        let crate_info = CrateInfo::new(crate_id, version, path.to_path_buf())
+            .with_metadata(Self::parse_metadata(&toml_doc));
 
         Ok(Some(crate_info))
     }
 
+    /// [package] metadata and [features] of a parsed Cargo.toml
+    fn parse_metadata(toml_doc: &TomlDocument) -> CrateMetadata {
+        let string = |field: &str| {
+            toml_doc
+                .get_package_field(field)
+                .and_then(|v| v.as_str().map(str::to_string))
+        };
+        let publish = match toml_doc.get_package_field("publish") {
+            Some(toml::Value::Boolean(publish)) => publish,
+            Some(toml::Value::Array(registries)) => !registries.is_empty(),
+            _ => true,
+        };
+        let features = toml_doc
+            .get_table("features")
+            .map(|features| {
+                features
+                    .iter()
+                    .map(|(name, enables)| {
+                        let enables = enables
+                            .as_array()
+                            .map(|values| {
+                                values
+                                    .iter()
+                                    .filter_map(|v| v.as_str().map(str::to_string))
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        (name.clone(), enables)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        CrateMetadata {
+            description: string("description"),
+            license: string("license"),
+            rust_version: string("rust-version"),
+            publish,
+            edition: string("edition"),
+            features,
+        }
+    }
+
     /// Discover all crates across all workspaces in a repository
     pub fn discover_all_crates<P: AsRef<Path>>(repo_root: P) -> Result<Vec<CrateInfo>> {
         let workspaces = Self::discover_workspaces(&repo_root)?;
@@ -248,18 +360,34 @@ impl CargoDiscovery {
     pub fn parse_local_dependencies_with_types(
         crate_info: &CrateInfo,
         all_crates: &[CrateInfo],
-    ) -> Result<Vec<(String, super::types::DependencyType)>> {
-        use super::types::DependencyType;
-
-        let toml_doc = TomlReader::read_file(&crate_info.cargo_toml_path).map_err(|e| {
-            Error::FileReadError {
-                path: crate_info.cargo_toml_path.clone(),
-                source: std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Failed to read Cargo.toml: {}", e),
-                ),
-            }
-        })?;
+    ) -> Result<Vec<(String, DependencyType)>> {
+        Ok(
+            Self::parse_local_dependency_declarations(crate_info, all_crates)?
+                .into_iter()
+                .map(|(name, declaration)| (name, declaration.kind))
+                .collect(),
+        )
+    }
+
+    /// Parse local dependencies from a crate's Cargo.toml with the section and
+    /// line declaring each
+    pub fn parse_local_dependency_declarations(
+        crate_info: &CrateInfo,
+        all_crates: &[CrateInfo],
+    ) -> Result<Vec<(String, DependencyDeclaration)>> {
+        let read_error = |e: String| Error::FileReadError {
+            path: crate_info.cargo_toml_path.clone(),
+            source: std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to read Cargo.toml: {}", e),
+            ),
+        };
+        let raw = std::fs::read_to_string(&crate_info.cargo_toml_path)
+            .map_err(|e| read_error(e.to_string()))?;
+        let toml_doc = TomlReader::read_file(&crate_info.cargo_toml_path)
+            .map_err(|e| read_error(e.to_string()))?;
+        // spans only, a manifest toml_edit rejects just goes without lines
+        let spanned = toml_edit::ImDocument::parse(raw.as_str()).ok();
 
         let mut local_deps = Vec::new();
 
@@ -267,15 +395,28 @@ impl CargoDiscovery {
         let crate_name_map: HashMap<&str, &CrateInfo> =
             all_crates.iter().map(|c| (c.id.name.as_str(), c)).collect();
 
-        // Check different dependency sections with their types
+        // Check different dependency sections with their types, at the root
+        // and under every target.<cfg>
         let dependency_sections = [
             ("dependencies", DependencyType::Normal),
             ("dev-dependencies", DependencyType::Dev),
             ("build-dependencies", DependencyType::Build),
         ];
+        let mut tables: Vec<(Option<&str>, &toml::value::Table)> = Vec::new();
+        if let Some(root) = toml_doc.content.as_table() {
+            tables.push((None, root));
+        }
+        for (target, table) in toml_doc.get_table("target").into_iter().flatten() {
+            if let Some(table) = table.as_table() {
+                tables.push((Some(target.as_str()), table));
+            }
+        }
 
-        for (section, dep_type) in dependency_sections {
-            if let Some(deps_table) = toml_doc.get_table(section) {
+        for (target, table) in tables {
+            for (section, dep_type) in dependency_sections {
+                let Some(deps_table) = table.get(section).and_then(toml::Value::as_table) else {
+                    continue;
+                };
                 for (dep_name, dep_value) in deps_table {
                     // Check if this dependency refers to a local crate
                     let is_local = if crate_name_map.contains_key(dep_name.as_str()) {
@@ -296,7 +437,22 @@ impl CargoDiscovery {
                     };
 
                     if is_local {
-                        local_deps.push((dep_name.clone(), dep_type));
+                        let line = spanned
+                            .as_ref()
+                            .and_then(|doc| declaration_line(doc, &raw, target, section, dep_name));
+                        let section = match target {
+                            Some(target) => format!("target.{}.{}", toml_key(target), section),
+                            None => section.to_string(),
+                        };
+                        local_deps.push((
+                            dep_name.clone(),
+                            DependencyDeclaration {
+                                kind: dep_type,
+                                manifest: crate_info.cargo_toml_path.clone(),
+                                section,
+                                line,
+                            },
+                        ));
                     }
                 }
             }
@@ -305,3 +461,45 @@ impl CargoDiscovery {
         Ok(local_deps)
     }
 }
+
+// 1-based line and column of the byte `offset` in `content`
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = content.get(..offset).unwrap_or(content);
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+// 1-based line of the key of `name` in the `section` table of a manifest,
+// or in `target.<target>.<section>` when given, `[section.name]` headers
+// included
+fn declaration_line(
+    doc: &toml_edit::ImDocument<&str>,
+    raw: &str,
+    target: Option<&str>,
+    section: &str,
+    name: &str,
+) -> Option<usize> {
+    let table = match target {
+        Some(target) => doc.get("target")?.get(target)?.get(section)?,
+        None => doc.get(section)?,
+    };
+    let (key, _) = table.as_table_like()?.get_key_value(name)?;
+    let start = key.span()?.start;
+    Some(raw[..start].matches('\n').count() + 1)
+}
+
+// `key` as written in a dotted toml path, quoted unless it is a bare key
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        key.to_string()
+    } else {
+        format!("'{}'", key)
+    }
+}
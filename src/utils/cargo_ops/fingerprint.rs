@@ -0,0 +1,388 @@
+// per-crate source fingerprints, a change detector that needs no git history
+// every source file of a crate is hashed and the crate fingerprint is the
+// hash over those (path, hash) pairs, so two snapshots of the same tree, say
+// two extracted release tarballs, show which crates and files differ
+// whole filesystem snapshots of a tree can be compared the same way
+
+use super::integration::{CRATE_SOURCES, ChangeImpactAnalysis, is_ignored};
+use super::types::{CrateDependencyGraph, CrateId};
+use crate::error::Result;
+use crate::utils::config::ImpactConfig;
+use crate::utils::filesystem::{FsDirectory, FsFile, HashType};
+use crate::utils::git_ops::{ChangeType, FileChange};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+pub const FINGERPRINT_VERSION: u32 = 1;
+
+/// source fingerprint of one crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateFingerprint {
+    pub crate_id: CrateId,
+    /// crate directory relative to the snapshot root, '/' separated
+    pub path: String,
+    /// blake3 over every file path and hash below
+    pub hash: String,
+    /// blake3 of every source file, keyed by its '/' separated path relative
+    /// to the crate directory
+    pub files: BTreeMap<String, String>,
+}
+
+/// fingerprints of every crate in a tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintSnapshot {
+    pub version: u32,
+    pub crates: Vec<CrateFingerprint>,
+}
+
+/// file that differs between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FingerprintChange {
+    pub crate_id: CrateId,
+    /// relative to the snapshot root, in the newer snapshot's layout when the
+    /// crate still exists
+    pub path: PathBuf,
+    pub change_type: ChangeType,
+}
+
+impl CrateFingerprint {
+    fn compute(graph: &CrateDependencyGraph, crate_id: &CrateId, root: &Path) -> Result<Self> {
+        let info = &graph.crates[crate_id];
+        let mut files = BTreeMap::new();
+        for path in graph.scan_crate_files(&info.path)? {
+            graph.cancel.check()?;
+            let mut file = FsFile::new(&path);
+            file.hash(HashType::BLAKE3)?;
+            let hash = file.hash_value(HashType::BLAKE3).unwrap_or_default();
+            files.insert(slash_path(relative(&path, &info.path)), hash.to_string());
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        for (path, hash) in &files {
+            hasher.update(path.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(hash.as_bytes());
+            hasher.update(b"\n");
+        }
+        Ok(CrateFingerprint {
+            crate_id: crate_id.clone(),
+            path: slash_path(relative(&info.path, root)),
+            hash: hasher.finalize().to_hex().to_string(),
+            files,
+        })
+    }
+}
+
+impl FingerprintSnapshot {
+    /// fingerprint every crate of the graph, with paths relative to `root`;
+    /// gives up between files once the graph's token is cancelled
+    pub fn collect<P: AsRef<Path>>(graph: &CrateDependencyGraph, root: P) -> Result<Self> {
+        let root = root.as_ref();
+        let mut crates = graph
+            .crates
+            .keys()
+            .map(|id| CrateFingerprint::compute(graph, id, root))
+            .collect::<Result<Vec<_>>>()?;
+        crates.sort_by_key(|fingerprint| fingerprint.crate_id.display_name());
+        Ok(FingerprintSnapshot {
+            version: FINGERPRINT_VERSION,
+            crates,
+        })
+    }
+
+    // the same crate in another snapshot; workspace names come from the
+    // directory a tree was unpacked into, so crates are matched by their
+    // relative path and name instead
+    fn find(&self, other: &CrateFingerprint) -> Option<&CrateFingerprint> {
+        self.crates
+            .iter()
+            .find(|f| f.path == other.path && f.crate_id.name == other.crate_id.name)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let snapshot: Self = serde_json::from_str(&content).map_err(std::io::Error::from)?;
+        if snapshot.version != FINGERPRINT_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unsupported fingerprint version {}", snapshot.version),
+            )
+            .into());
+        }
+        Ok(snapshot)
+    }
+
+    // (old, new) fingerprints of every crate that differs, either may be missing
+    fn changed_pairs<'a>(
+        &'a self,
+        newer: &'a FingerprintSnapshot,
+    ) -> Vec<(Option<&'a CrateFingerprint>, Option<&'a CrateFingerprint>)> {
+        let mut pairs = Vec::new();
+        for old in &self.crates {
+            match newer.find(old) {
+                Some(new) if new.hash == old.hash => {}
+                new => pairs.push((Some(old), new)),
+            }
+        }
+        for new in &newer.crates {
+            if self.find(new).is_none() {
+                pairs.push((None, Some(new)));
+            }
+        }
+        pairs
+    }
+
+    /// crates whose fingerprint differs in `newer`, added and removed crates
+    /// included, identified as in `newer` when they exist there
+    pub fn changed_crates(&self, newer: &FingerprintSnapshot) -> Vec<CrateId> {
+        self.changed_pairs(newer)
+            .into_iter()
+            .filter_map(|(old, new)| new.or(old).map(|f| f.crate_id.clone()))
+            .collect()
+    }
+
+    /// every file added, modified or deleted between this snapshot and `newer`
+    pub fn changes(&self, newer: &FingerprintSnapshot) -> Vec<FingerprintChange> {
+        let empty = BTreeMap::new();
+        let mut changes = Vec::new();
+        for (old, new) in self.changed_pairs(newer) {
+            let Some(current) = new.or(old) else {
+                continue;
+            };
+            let old_files = old.map_or(&empty, |f| &f.files);
+            let new_files = new.map_or(&empty, |f| &f.files);
+            let base = PathBuf::from(&current.path);
+
+            let paths: BTreeSet<&String> = old_files.keys().chain(new_files.keys()).collect();
+            for path in paths {
+                let change_type = match (old_files.get(path), new_files.get(path)) {
+                    (None, Some(_)) => ChangeType::Added,
+                    (Some(_), None) => ChangeType::Deleted,
+                    (Some(a), Some(b)) if a != b => ChangeType::Modified,
+                    _ => continue,
+                };
+                changes.push(FingerprintChange {
+                    crate_id: current.crate_id.clone(),
+                    path: base.join(path),
+                    change_type,
+                });
+            }
+        }
+        changes
+    }
+}
+
+impl CrateDependencyGraph {
+    /// impact of the differences between two fingerprint snapshots of the tree
+    /// at `root`, the graph describing the newer one; the counterpart of
+    /// analyze_git_changes_with_config for trees that are not git checkouts
+    pub fn analyze_fingerprint_changes<P: AsRef<Path>>(
+        &self,
+        root: P,
+        old: &FingerprintSnapshot,
+        new: &FingerprintSnapshot,
+        impact: &ImpactConfig,
+    ) -> Result<ChangeImpactAnalysis> {
+        let root = root.as_ref();
+        let mut changes = old.changes(new);
+        if let Some(ignore) = &impact.ignore {
+            changes.retain(|c| !is_ignored(ignore, &root.join(&c.path)));
+        }
+        if let Some(scope) = &self.scope {
+            changes.retain(|c| scope.contains(&c.path));
+        }
+        let generated_files =
+            take_generated(root, impact, &mut changes, |c| (&c.path, &c.change_type));
+
+        // removed crates have changed files but no longer affect anything
+        let mut directly_affected: Vec<CrateId> = Vec::new();
+        let mut crate_changes: HashMap<CrateId, Vec<FileChange>> = HashMap::new();
+        for change in &changes {
+            if !self.crates.contains_key(&change.crate_id) {
+                continue;
+            }
+            if !directly_affected.contains(&change.crate_id) {
+                directly_affected.push(change.crate_id.clone());
+            }
+            crate_changes
+                .entry(change.crate_id.clone())
+                .or_default()
+                .push(FileChange::new(
+                    change.path.clone(),
+                    change.change_type.clone(),
+                    false,
+                ));
+        }
+        let changed_files = changes.into_iter().map(|c| c.path).collect();
+        Ok(self.impact_of(
+            changed_files,
+            generated_files,
+            directly_affected,
+            crate_changes,
+            impact,
+        ))
+    }
+
+    /// impact of the differences between two filesystem snapshots (see
+    /// `FsDirectory::save_snapshot`) of the tree at `root`, the graph
+    /// describing the newer one; for trees that are neither git checkouts nor
+    /// fingerprinted. contents are compared by hash, or by size when the
+    /// snapshots were not hashed, and a file affects the crate whose sources
+    /// or Cargo.toml it is, the innermost one for nested crates
+    pub fn analyze_snapshot_changes<P: AsRef<Path>>(
+        &self,
+        root: P,
+        old: &FsDirectory,
+        new: &FsDirectory,
+        impact: &ImpactConfig,
+    ) -> Result<ChangeImpactAnalysis> {
+        let root = root.as_ref();
+        let diff = old.diff(new);
+        let mut changes: Vec<FileChange> = diff
+            .added
+            .into_iter()
+            .map(|path| (path, ChangeType::Added))
+            .chain(
+                diff.removed
+                    .into_iter()
+                    .map(|path| (path, ChangeType::Deleted)),
+            )
+            .chain(
+                diff.modified
+                    .into_iter()
+                    .map(|file| (file.path, ChangeType::Modified)),
+            )
+            .map(|(path, change_type)| FileChange::new(path, change_type, false))
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(ignore) = &impact.ignore {
+            changes.retain(|c| !is_ignored(ignore, &root.join(&c.path)));
+        }
+        if let Some(scope) = &self.scope {
+            changes.retain(|c| scope.contains(&c.path));
+        }
+        let generated_files =
+            take_generated(root, impact, &mut changes, |c| (&c.path, &c.change_type));
+
+        // crate directories relative to the root, innermost first
+        let mut crate_dirs: Vec<(PathBuf, &CrateId)> = self
+            .crates
+            .values()
+            .map(|info| (relative(&info.path, root), &info.id))
+            .collect();
+        crate_dirs.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+
+        let mut directly_affected: Vec<CrateId> = Vec::new();
+        let mut crate_changes: HashMap<CrateId, Vec<FileChange>> = HashMap::new();
+        for change in &changes {
+            self.cancel.check()?;
+            let owner = crate_dirs.iter().find_map(|(dir, id)| {
+                let inside = change.path.strip_prefix(dir).ok()?;
+                let first = inside.components().next()?.as_os_str();
+                (first == "Cargo.toml" || CRATE_SOURCES.iter().any(|source| first == *source))
+                    .then_some(*id)
+            });
+            let Some(crate_id) = owner else {
+                continue;
+            };
+            if !directly_affected.contains(crate_id) {
+                directly_affected.push(crate_id.clone());
+            }
+            crate_changes
+                .entry(crate_id.clone())
+                .or_default()
+                .push(change.clone());
+        }
+        let changed_files = changes.into_iter().map(|c| c.path).collect();
+        Ok(self.impact_of(
+            changed_files,
+            generated_files,
+            directly_affected,
+            crate_changes,
+            impact,
+        ))
+    }
+
+    // the impact of `changed_files` touching `directly_affected` crates, for
+    // the analyses without manifest history
+    fn impact_of(
+        &self,
+        changed_files: Vec<PathBuf>,
+        generated_files: Vec<PathBuf>,
+        directly_affected: Vec<CrateId>,
+        crate_changes: HashMap<CrateId, Vec<FileChange>>,
+        impact: &ImpactConfig,
+    ) -> ChangeImpactAnalysis {
+        let propagated = self.find_propagated_crates(&directly_affected, impact);
+        ChangeImpactAnalysis {
+            changed_files,
+            needs_rebuild: self.find_all_affected_crates(&directly_affected),
+            needs_release: self.find_release_crates(&directly_affected, impact),
+            directly_affected_crates: directly_affected,
+            all_affected_crates: propagated.affected,
+            dev_only_affected: propagated.dev_only,
+            beyond_max_depth: propagated.beyond_depth,
+            boundary_crates: propagated.boundaries,
+            crate_changes,
+            manifest_changes: HashMap::new(),
+            ignored_manifest_crates: Vec::new(),
+            generated_files,
+            removed_crates: Vec::new(),
+        }
+    }
+}
+
+// removes the generated files from `changes` and returns their paths;
+// markers are looked for in the newer tree at `root`
+fn take_generated<T>(
+    root: &Path,
+    impact: &ImpactConfig,
+    changes: &mut Vec<T>,
+    change: impl Fn(&T) -> (&PathBuf, &ChangeType),
+) -> Vec<PathBuf> {
+    let mut generated_files = Vec::new();
+    changes.retain(|item| {
+        let (path, change_type) = change(item);
+        let is_generated = impact.generated.is_generated(path, || {
+            if *change_type == ChangeType::Deleted {
+                return Ok(None);
+            }
+            Ok(std::fs::read_to_string(root.join(path)).ok())
+        });
+        if matches!(is_generated, Ok(true)) {
+            generated_files.push(path.clone());
+            return false;
+        }
+        true
+    });
+    generated_files
+}
+
+// `path` below `base`, comparing canonical forms when the two were spelled
+// differently (one relative, one absolute)
+pub(crate) fn relative(path: &Path, base: &Path) -> PathBuf {
+    if let Ok(relative) = path.strip_prefix(base) {
+        return relative.to_path_buf();
+    }
+    match (path.canonicalize(), base.canonicalize()) {
+        (Ok(path), Ok(base)) => path
+            .strip_prefix(&base)
+            .map(Path::to_path_buf)
+            .unwrap_or(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn slash_path(path: PathBuf) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,173 @@
+use super::scope::{PATH_MATCH_OPTIONS, matches_path_or_ancestor};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// lines at the top of a file searched for the `@generated` marker
+pub const MARKER_LINES: usize = 5;
+
+const MARKER: &str = "@generated";
+
+/// generated sources, whose changes affect no crate so regenerating stubs
+/// does not release every dependent
+///
+/// patterns are globs relative to the repository root
+/// (`crates/*/src/generated/**`, or just `crates/*/src/generated/` for
+/// everything below it); a pattern without a '/' matches file names
+/// anywhere (`*.pb.rs`). with `markers`, files mentioning `@generated` in
+/// their first lines (`// This file is @generated by prost-build.`) are
+/// generated too
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeneratedFiles {
+    #[serde(default, with = "patterns")]
+    patterns: Vec<glob::Pattern>,
+    #[serde(default)]
+    pub markers: bool,
+}
+
+impl GeneratedFiles {
+    pub fn new<I, S>(patterns: I, markers: bool) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| compile(pattern.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(GeneratedFiles { patterns, markers })
+    }
+
+    /// true when nothing can be recognized as generated
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && !self.markers
+    }
+
+    /// check if `path`, relative to the repository root, or a directory above
+    /// it matches one of the patterns
+    pub fn matches<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.patterns.iter().any(|pattern| {
+            if pattern.as_str().contains('/') {
+                matches_path_or_ancestor(pattern, path)
+            } else {
+                path.file_name().is_some_and(|name| {
+                    pattern.matches_with(&name.to_string_lossy(), PATH_MATCH_OPTIONS)
+                })
+            }
+        })
+    }
+
+    /// check if the file at `path` is generated, looking at the content
+    /// `read` returns only when no pattern matched and markers are enabled
+    pub fn is_generated<P, F>(&self, path: P, read: F) -> Result<bool>
+    where
+        P: AsRef<Path>,
+        F: FnOnce() -> Result<Option<String>>,
+    {
+        if self.matches(path) {
+            return Ok(true);
+        }
+        if !self.markers {
+            return Ok(false);
+        }
+        Ok(read()?.is_some_and(|content| has_marker(&content)))
+    }
+}
+
+/// check if `content` mentions `@generated` in its first [`MARKER_LINES`]
+pub fn has_marker(content: &str) -> bool {
+    content
+        .lines()
+        .take(MARKER_LINES)
+        .any(|line| line.contains(MARKER))
+}
+
+fn compile(pattern: &str) -> Result<glob::Pattern> {
+    let trimmed = pattern.trim_start_matches("./").trim_matches('/');
+    glob::Pattern::new(trimmed).map_err(|e| Error::WorkspaceError {
+        reason: format!("invalid generated file pattern {}: {}", pattern, e),
+    })
+}
+
+mod patterns {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(patterns: &[glob::Pattern], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(patterns.iter().map(glob::Pattern::as_str))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<glob::Pattern>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|pattern| super::compile(pattern).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_patterns() {
+        let generated = GeneratedFiles::new(["**/generated/**", "*.pb.rs"], false).unwrap();
+        assert!(generated.matches("crates/api/src/generated/mod.rs"));
+        assert!(generated.matches("generated/schema.rs"));
+        assert!(generated.matches("crates/api/src/proto/user.pb.rs"));
+        assert!(generated.matches("user.pb.rs"));
+        assert!(!generated.matches("crates/api/src/lib.rs"));
+        assert!(!generated.matches("crates/api/src/generated.rs"));
+
+        // patterns with a '/' are anchored at the repository root
+        let generated = GeneratedFiles::new(["./crates/*/src/gen/"], false).unwrap();
+        assert!(generated.matches("crates/api/src/gen"));
+        assert!(generated.matches("crates/api/src/gen/mod.rs"));
+        assert!(generated.matches("crates/api/src/gen/v1/user.rs"));
+        assert!(!generated.matches("crates/api/src/general.rs"));
+        assert!(!generated.matches("vendor/crates/api/src/gen"));
+        assert!(!generated.matches("vendor/crates/api/src/gen/mod.rs"));
+
+        assert!(GeneratedFiles::new(["[gen"], false).is_err());
+        assert!(GeneratedFiles::default().is_empty());
+    }
+
+    #[test]
+    fn test_generated_markers() {
+        assert!(has_marker(
+            "// This file is @generated by prost-build.\npub struct A;\n"
+        ));
+        assert!(!has_marker("pub struct A;\n"));
+        let late = format!("{}// @generated\n", "\n".repeat(MARKER_LINES));
+        assert!(!has_marker(&late));
+
+        let generated = GeneratedFiles::new(["*.pb.rs"], true).unwrap();
+        let content = || Ok(Some("// @generated\n".to_string()));
+        assert!(generated.is_generated("src/api.rs", content).unwrap());
+        assert!(!generated.is_generated("src/lib.rs", || Ok(None)).unwrap());
+        // patterns are checked without reading the file
+        assert!(
+            generated
+                .is_generated("src/api.pb.rs", || panic!("read"))
+                .unwrap()
+        );
+        let without_markers = GeneratedFiles::new(["*.pb.rs"], false).unwrap();
+        assert!(!without_markers.is_generated("src/api.rs", content).unwrap());
+    }
+
+    #[test]
+    fn test_generated_config() {
+        let generated: GeneratedFiles =
+            toml::from_str("patterns = [\"**/generated/**\"]\nmarkers = true\n").unwrap();
+        assert!(generated.markers);
+        assert!(generated.matches("src/generated/a.rs"));
+        assert!(toml::from_str::<GeneratedFiles>("patterns = [\"[gen\"]\n").is_err());
+    }
+}
@@ -1,45 +1,129 @@
 use super::discovery::CargoDiscovery;
-use super::types::{CrateDependencyGraph, CrateId, CrateInfo, DependencyType};
+use super::fingerprint::relative;
+use super::metrics::CrateMetrics;
+use super::scope::Scope;
+use super::types::{
+    CrateDependencyGraph, CrateId, CrateInfo, CycleEdge, DependencyType, StronglyConnectedComponent,
+};
 use crate::error::{Error, Result};
+use crate::utils::cancel::CancelToken;
+use crate::utils::timings;
 use petgraph::visit::EdgeRef;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
 
 impl CrateDependencyGraph {
     /// Build a complete dependency graph from a repository
     pub fn build_from_repository<P: AsRef<Path>>(repo_root: P) -> Result<Self> {
+        Self::build_from_repository_with_cancel(repo_root, &CancelToken::new())
+    }
+
+    /// like build_from_repository, giving up with `Error::Cancelled` once
+    /// `cancel` is; the graph keeps the token for its analyses
+    pub fn build_from_repository_with_cancel<P: AsRef<Path>>(
+        repo_root: P,
+        cancel: &CancelToken,
+    ) -> Result<Self> {
         let repo_root = repo_root.as_ref();
         let mut graph = Self::new();
+        graph.cancel = cancel.clone();
+
+        let (workspaces, all_crates) = timings::time("workspace discovery", || -> Result<_> {
+            // Step 1: Discover all workspaces, and the manifests discovery skips
+            let workspaces = CargoDiscovery::discover_workspaces(repo_root)?;
+            cancel.check()?;
+            graph.broken_manifests = CargoDiscovery::find_broken_manifests(repo_root)?;
+
+            // Step 2: Discover all crates across all workspaces
+            let mut all_crates = Vec::new();
+            for workspace in &workspaces {
+                cancel.check()?;
+                let workspace_crates = CargoDiscovery::discover_crates_in_workspace(workspace)?;
+                all_crates.extend(workspace_crates);
+            }
+            Ok((workspaces, all_crates))
+        })?;
+        graph.workspaces = workspaces;
+
+        timings::time("graph build", || -> Result<()> {
+            // Step 3: Add all crates to the graph
+            for crate_info in &all_crates {
+                graph.add_crate(crate_info.clone());
+            }
 
-        // Step 1: Discover all workspaces
-        let workspaces = CargoDiscovery::discover_workspaces(repo_root)?;
-        graph.workspaces = workspaces.clone();
+            // Step 4: Build dependency relationships
+            for crate_info in &all_crates {
+                cancel.check()?;
+                let local_deps =
+                    CargoDiscovery::parse_local_dependency_declarations(crate_info, &all_crates)?;
+
+                for (dep_name, declaration) in local_deps {
+                    // Find the target crate
+                    if let Some(target_crate) = all_crates.iter().find(|c| c.id.name == dep_name) {
+                        graph.add_declared_dependency(
+                            &crate_info.id,
+                            &target_crate.id,
+                            declaration,
+                        );
+                    }
+                }
+            }
+            Ok(())
+        })?;
 
-        // Step 2: Discover all crates across all workspaces
-        let mut all_crates = Vec::new();
-        for workspace in &workspaces {
-            let workspace_crates = CargoDiscovery::discover_crates_in_workspace(workspace)?;
-            all_crates.extend(workspace_crates);
-        }
+        Ok(graph)
+    }
 
-        // Step 3: Add all crates to the graph
-        for crate_info in &all_crates {
-            graph.add_crate(crate_info.clone());
-        }
+    /// Build the dependency graph of the crates of a repository within `scope`
+    ///
+    /// crates outside of it, their dependency edges and workspaces left without
+    /// crates are dropped, and change analyses skip files outside of it
+    pub fn build_scoped<P: AsRef<Path>>(repo_root: P, scope: Scope) -> Result<Self> {
+        Self::build_scoped_with_cancel(repo_root, scope, &CancelToken::new())
+    }
 
-        // Step 4: Build dependency relationships
-        for crate_info in &all_crates {
-            let local_deps =
-                CargoDiscovery::parse_local_dependencies_with_types(crate_info, &all_crates)?;
+    /// like build_scoped, giving up with `Error::Cancelled` once `cancel` is
+    pub fn build_scoped_with_cancel<P: AsRef<Path>>(
+        repo_root: P,
+        scope: Scope,
+        cancel: &CancelToken,
+    ) -> Result<Self> {
+        let repo_root = repo_root.as_ref();
+        let full = Self::build_from_repository_with_cancel(repo_root, cancel)?;
+        let mut graph = Self::new();
+        graph.cancel = full.cancel.clone();
 
-            for (dep_name, dep_type) in local_deps {
-                // Find the target crate
-                if let Some(target_crate) = all_crates.iter().find(|c| c.id.name == dep_name) {
-                    graph.add_dependency(&crate_info.id, &target_crate.id, dep_type);
-                }
+        // a crate is in scope with its manifest
+        for info in full.crates.values() {
+            if scope.contains(relative(&info.cargo_toml_path, repo_root)) {
+                graph.add_crate(info.clone());
             }
         }
-
+        for edge in full.graph.edge_references() {
+            graph.add_dependency(
+                &full.graph[edge.source()],
+                &full.graph[edge.target()],
+                *edge.weight(),
+            );
+        }
+        graph.declarations = full
+            .declarations
+            .into_iter()
+            .filter(|((from, to), _)| {
+                graph.crates.contains_key(from) && graph.crates.contains_key(to)
+            })
+            .collect();
+        graph.workspaces = full
+            .workspaces
+            .into_iter()
+            .filter(|workspace| graph.crates.keys().any(|id| id.workspace == workspace.name))
+            .collect();
+        graph.broken_manifests = full
+            .broken_manifests
+            .into_iter()
+            .filter(|broken| scope.contains(relative(&broken.path, repo_root)))
+            .collect();
+        graph.scope = Some(scope);
         Ok(graph)
     }
 
@@ -47,17 +131,18 @@ impl CrateDependencyGraph {
     pub fn rebuild_dependencies(&mut self) -> Result<()> {
         // Clear existing edges but keep nodes
         self.graph.clear_edges();
+        self.declarations.clear();
 
         let all_crates: Vec<CrateInfo> = self.crates.values().cloned().collect();
 
         // Rebuild all dependency edges
         for crate_info in &all_crates {
             let local_deps =
-                CargoDiscovery::parse_local_dependencies_with_types(crate_info, &all_crates)?;
+                CargoDiscovery::parse_local_dependency_declarations(crate_info, &all_crates)?;
 
-            for (dep_name, dep_type) in local_deps {
+            for (dep_name, declaration) in local_deps {
                 if let Some(target_crate) = all_crates.iter().find(|c| c.id.name == dep_name) {
-                    self.add_dependency(&crate_info.id, &target_crate.id, dep_type);
+                    self.add_declared_dependency(&crate_info.id, &target_crate.id, declaration);
                 }
             }
         }
@@ -69,15 +154,19 @@ impl CrateDependencyGraph {
     ///
     /// returns a vector of cycles, where each cycle is a vector of CrateIds forming a circular dependency
     pub fn find_cycles(&self) -> Vec<Vec<&CrateId>> {
-        self.find_cycles_filtered(&[DependencyType::Normal, DependencyType::Dev, DependencyType::Build])
+        self.find_cycles_filtered(&[
+            DependencyType::Normal,
+            DependencyType::Dev,
+            DependencyType::Build,
+        ])
     }
 
     /// Find dependency cycles considering only specific dependency types
     ///
     /// this is useful for finding only production cycles (excluding dev and build dependencies)
     pub fn find_cycles_filtered(&self, allowed_types: &[DependencyType]) -> Vec<Vec<&CrateId>> {
-        use petgraph::algo::tarjan_scc;
         use petgraph::Graph;
+        use petgraph::algo::tarjan_scc;
 
         // Create a filtered graph with only allowed dependency types
         let mut filtered_graph = Graph::<CrateId, DependencyType, petgraph::Directed>::new();
@@ -129,11 +218,12 @@ impl CrateDependencyGraph {
                 // Check for self-loop
                 let node_idx = scc[0];
                 if filtered_graph.contains_edge(node_idx, node_idx) {
-                    let cycle = vec![self
-                        .crates
-                        .get(&filtered_graph[node_idx])
-                        .map(|info| &info.id)
-                        .unwrap()];
+                    let cycle = vec![
+                        self.crates
+                            .get(&filtered_graph[node_idx])
+                            .map(|info| &info.id)
+                            .unwrap(),
+                    ];
                     cycles.push(cycle);
                 }
             }
@@ -327,28 +417,15 @@ impl CrateDependencyGraph {
             return;
         }
 
-        println!("Found {} production dependency cycle(s):\n", production_cycles.len());
+        println!(
+            "Found {} production dependency cycle(s):\n",
+            production_cycles.len()
+        );
 
         for (i, cycle) in production_cycles.iter().enumerate() {
             println!("Cycle {}:", i + 1);
 
-            // For each cycle, try to reconstruct the actual dependency chain
-            let chain = self.reconstruct_cycle_chain(cycle);
-
-            for (j, crate_id) in chain.iter().enumerate() {
-                if j == 0 {
-                    print!("  {}", crate_id.display_name());
-                } else {
-                    print!(" -> {}", crate_id.display_name());
-                }
-            }
-
-            // Show the cycle completion (back to the first crate)
-            if !chain.is_empty() {
-                println!(" -> {}", chain[0].display_name());
-            } else {
-                println!();
-            }
+            self.display_cycle(cycle, &[DependencyType::Normal]);
             println!();
         }
 
@@ -370,37 +447,141 @@ impl CrateDependencyGraph {
             return;
         }
 
-        println!("Found {} dependency cycle(s) (including dev/build):\n", cycles.len());
+        println!(
+            "Found {} dependency cycle(s) (including dev/build):\n",
+            cycles.len()
+        );
 
         for (i, cycle) in cycles.iter().enumerate() {
             println!("Cycle {}:", i + 1);
 
-            // For each cycle, try to reconstruct the actual dependency chain
-            let chain = self.reconstruct_cycle_chain(cycle);
+            self.display_cycle(
+                cycle,
+                &[
+                    DependencyType::Normal,
+                    DependencyType::Dev,
+                    DependencyType::Build,
+                ],
+            );
+            println!();
+        }
+    }
 
-            for (j, crate_id) in chain.iter().enumerate() {
-                if j == 0 {
-                    print!("  {}", crate_id.display_name());
-                } else {
-                    print!(" -> {}", crate_id.display_name());
-                }
-            }
+    // the chain of a cycle, then each of its edges with where the dependencies
+    // of the `kinds` making it up are declared
+    fn display_cycle(&self, cycle: &[&CrateId], kinds: &[DependencyType]) {
+        // For each cycle, try to reconstruct the actual dependency chain
+        let chain = self.reconstruct_cycle_chain(cycle);
 
-            // Show the cycle completion (back to the first crate)
-            if !chain.is_empty() {
-                println!(" -> {}", chain[0].display_name());
+        for (j, crate_id) in chain.iter().enumerate() {
+            if j == 0 {
+                print!("  {}", crate_id.display_name());
             } else {
-                println!();
+                print!(" -> {}", crate_id.display_name());
             }
+        }
+
+        // Show the cycle completion (back to the first crate)
+        if !chain.is_empty() {
+            println!(" -> {}", chain[0].display_name());
+        } else {
             println!();
         }
+
+        for edge in self.cycle_edges(&chain, kinds) {
+            println!("    {} -> {}:", edge.from, edge.to);
+            for declaration in &edge.declarations {
+                println!("      {}", declaration);
+            }
+        }
+    }
+
+    /// The edges of a cycle chain (as from [`Self::reconstruct_cycle_chain`]),
+    /// the last one closing it, each with where the dependencies of the
+    /// `kinds` making it up are declared
+    pub fn cycle_edges(&self, chain: &[&CrateId], kinds: &[DependencyType]) -> Vec<CycleEdge> {
+        (0..chain.len())
+            .map(|i| {
+                let (from, to) = (chain[i], chain[(i + 1) % chain.len()]);
+                CycleEdge {
+                    from: from.display_name(),
+                    to: to.display_name(),
+                    declarations: self
+                        .dependency_declarations(from, to)
+                        .iter()
+                        .filter(|declaration| kinds.contains(&declaration.kind))
+                        .cloned()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Strongly connected components over every kind of dependency; members
+    /// are sorted by name and components by their first member, so the ids
+    /// stay the same between runs. a crate in no cycle is a trivial
+    /// component of its own
+    pub fn strongly_connected_components(&self) -> Vec<StronglyConnectedComponent> {
+        let mut components: Vec<Vec<&CrateId>> = petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|nodes| {
+                let mut members: Vec<&CrateId> =
+                    nodes.into_iter().map(|node| &self.graph[node]).collect();
+                members.sort_by_key(|id| id.display_name());
+                members
+            })
+            .collect();
+        components.sort_by_key(|members| members[0].display_name());
+
+        components
+            .into_iter()
+            .enumerate()
+            .map(|(id, members)| {
+                let mut edges = Vec::new();
+                for &from in &members {
+                    let mut dependencies: Vec<&CrateId> = self
+                        .get_dependencies(from)
+                        .into_iter()
+                        .filter(|to| members.contains(to))
+                        .collect();
+                    dependencies.sort_by_key(|id| id.display_name());
+                    dependencies.dedup();
+                    for to in dependencies {
+                        edges.push(CycleEdge {
+                            from: from.display_name(),
+                            to: to.display_name(),
+                            declarations: self.dependency_declarations(from, to).to_vec(),
+                        });
+                    }
+                }
+                StronglyConnectedComponent {
+                    id,
+                    members: members.iter().map(|id| id.display_name()).collect(),
+                    edges,
+                }
+            })
+            .collect()
+    }
+
+    /// Production cycles with where each of their edges is declared
+    pub fn production_cycle_details(&self) -> Vec<Vec<CycleEdge>> {
+        self.find_production_cycles()
+            .iter()
+            .map(|cycle| {
+                let chain = self.reconstruct_cycle_chain(cycle);
+                self.cycle_edges(&chain, &[DependencyType::Normal])
+            })
+            .collect()
     }
 
     /// Reconstruct the actual dependency chain within a cycle
     ///
     /// given a set of crates that form a strongly connected component,
     /// this method finds an actual path that demonstrates the cycle
-    fn reconstruct_cycle_chain<'a>(&'a self, cycle_crates: &[&'a CrateId]) -> Vec<&'a CrateId> {
+    pub(crate) fn reconstruct_cycle_chain<'a>(
+        &'a self,
+        cycle_crates: &[&'a CrateId],
+    ) -> Vec<&'a CrateId> {
         if cycle_crates.is_empty() {
             return Vec::new();
         }
@@ -472,7 +653,70 @@ impl CrateDependencyGraph {
             max_dependents: max_in_degree,
             max_dependencies: max_out_degree,
             workspace_count: self.workspaces.len(),
+            crate_metrics: Vec::new(),
+            workspaces: Vec::new(),
+            cross_workspace_matrix: BTreeMap::new(),
+        }
+    }
+
+    /// Get statistics including per-crate source metrics, which scans every crate directory
+    pub fn get_statistics_with_metrics(&self) -> Result<GraphStatistics> {
+        let mut stats = self.get_statistics();
+        stats.crate_metrics = self.crate_metrics()?;
+        Ok(stats)
+    }
+
+    /// Get statistics with a breakdown per workspace and the number of
+    /// dependencies between each pair of workspaces
+    pub fn get_statistics_per_workspace(&self) -> GraphStatistics {
+        let mut stats = self.get_statistics();
+        let mut breakdown: BTreeMap<&str, WorkspaceStatistics> = BTreeMap::new();
+        for crate_id in self.crates.keys() {
+            breakdown
+                .entry(crate_id.workspace.as_str())
+                .or_insert_with(|| WorkspaceStatistics::new(&crate_id.workspace))
+                .crate_count += 1;
+        }
+
+        for edge in self.graph.edge_references() {
+            let from = &self.graph[edge.source()].workspace;
+            let to = &self.graph[edge.target()].workspace;
+            if from == to {
+                if let Some(workspace) = breakdown.get_mut(from.as_str()) {
+                    workspace.internal_dependencies += 1;
+                }
+                continue;
+            }
+            if let Some(workspace) = breakdown.get_mut(from.as_str()) {
+                workspace.outgoing_dependencies += 1;
+            }
+            if let Some(workspace) = breakdown.get_mut(to.as_str()) {
+                workspace.incoming_dependencies += 1;
+            }
+            *stats
+                .cross_workspace_matrix
+                .entry(from.clone())
+                .or_default()
+                .entry(to.clone())
+                .or_default() += 1;
         }
+
+        for cycle in self.find_production_cycles() {
+            let workspaces: BTreeSet<&str> = cycle.iter().map(|id| id.workspace.as_str()).collect();
+            let spanning = workspaces.len() > 1;
+            for name in workspaces {
+                if let Some(workspace) = breakdown.get_mut(name) {
+                    if spanning {
+                        workspace.spanning_cycles += 1;
+                    } else {
+                        workspace.confined_cycles += 1;
+                    }
+                }
+            }
+        }
+
+        stats.workspaces = breakdown.into_values().collect();
+        stats
     }
 
     /// Export graph in DOT format for visualization
@@ -484,8 +728,10 @@ impl CrateDependencyGraph {
         writeln!(&mut dot, "  rankdir=LR;").unwrap();
         writeln!(&mut dot, "  node [shape=box];").unwrap();
 
-        // Add nodes
-        for crate_id in self.crates.keys() {
+        // Add nodes, sorted so the output is stable
+        let mut crate_ids: Vec<&CrateId> = self.crates.keys().collect();
+        crate_ids.sort_by_key(|id| id.display_name());
+        for crate_id in crate_ids {
             let label = format!("{}\\n({})", crate_id.name, crate_id.workspace);
             writeln!(
                 &mut dot,
@@ -497,16 +743,20 @@ impl CrateDependencyGraph {
         }
 
         // Add edges
-        for edge in self.graph.edge_references() {
-            let from = &self.graph[edge.source()];
-            let to = &self.graph[edge.target()];
-            writeln!(
-                &mut dot,
-                "  \"{}\" -> \"{}\";",
-                from.display_name(),
-                to.display_name()
-            )
-            .unwrap();
+        let mut edges: Vec<(String, String)> = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    self.graph[edge.source()].display_name(),
+                    self.graph[edge.target()].display_name(),
+                )
+            })
+            .collect();
+        edges.sort();
+        edges.dedup();
+        for (from, to) in edges {
+            writeln!(&mut dot, "  \"{}\" -> \"{}\";", from, to).unwrap();
         }
 
         writeln!(&mut dot, "}}").unwrap();
@@ -527,6 +777,48 @@ pub struct GraphStatistics {
     pub max_dependents: usize,
     pub max_dependencies: usize,
     pub workspace_count: usize,
+    /// per-crate source metrics, only filled by `get_statistics_with_metrics`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub crate_metrics: Vec<CrateMetrics>,
+    /// per-workspace breakdown by name, only filled by
+    /// `get_statistics_per_workspace`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<WorkspaceStatistics>,
+    /// dependencies of the crates of one workspace (outer key) on the crates
+    /// of another (inner key), only filled by `get_statistics_per_workspace`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub cross_workspace_matrix: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// statistics of the crates of one workspace
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkspaceStatistics {
+    pub workspace: String,
+    pub crate_count: usize,
+    /// dependencies between crates of the workspace
+    pub internal_dependencies: usize,
+    /// dependencies of its crates on crates of other workspaces
+    pub outgoing_dependencies: usize,
+    /// dependencies of crates of other workspaces on its crates
+    pub incoming_dependencies: usize,
+    /// production cycles among its own crates only
+    pub confined_cycles: usize,
+    /// production cycles through its crates and those of other workspaces
+    pub spanning_cycles: usize,
+}
+
+impl WorkspaceStatistics {
+    fn new(workspace: &str) -> Self {
+        Self {
+            workspace: workspace.to_string(),
+            crate_count: 0,
+            internal_dependencies: 0,
+            outgoing_dependencies: 0,
+            incoming_dependencies: 0,
+            confined_cycles: 0,
+            spanning_cycles: 0,
+        }
+    }
 }
 
 impl GraphStatistics {
@@ -1,22 +1,49 @@
-use super::types::{CrateDependencyGraph, CrateId};
-use crate::error::Result;
-use crate::utils::git_ops::{GitOps, GitRef};
+use super::generated::GeneratedFiles;
+use super::types::{CrateDependencyGraph, CrateId, crate_id_map};
+use crate::error::{Error, Result};
+use crate::utils::alt::LogicExpr;
+use crate::utils::changelog::parse_changelog;
+use crate::utils::changelog_checker::ChangelogChecker;
+use crate::utils::config::{DeptrackConfig, ImpactConfig, ImpactPropagation};
+use crate::utils::feature_checker::FeatureChecker;
+use crate::utils::filesystem::{FilePredicate, FsDirectory, FsFile, PredicateContext, ScanOptions};
+use crate::utils::git_ops::{ChangeType, ChangedFiles, FileChange, GitOps, GitRef};
+use crate::utils::manifest_checker::ManifestChecker;
 use crate::utils::severity::Issue;
+use crate::utils::table::{Align, Cell, Color, Table, paint, severity_color};
+use crate::utils::timings;
+use crate::utils::toml_ops::{TomlDiff, TomlDocument, TomlReader, TomlSection, diff as toml_diff};
+use crate::utils::version_policy::{CrateMaturity, RequiredBump};
 use semver::Version;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 const MIN_CRATE_NAME_WIDTH: usize = 10;
 const MIN_VERSION_WIDTH: usize = 12;
 
+/// directories and files below a crate root whose changes affect the crate,
+/// next to its Cargo.toml
+pub(super) const CRATE_SOURCES: [&str; 5] = ["src", "tests", "benches", "examples", "build.rs"];
+
 /// represents version bump status for a crate
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionBumpStatus {
     pub crate_id: CrateId,
     pub base_version: Version,
     pub current_version: Version,
     pub is_bumped: bool,
     pub is_directly_changed: bool,
+    /// no Cargo.toml at the base ref: the crate was added since, and its base
+    /// version is its current one
+    #[serde(default)]
+    pub is_new: bool,
+    /// maturity at the base ref, which selects the policy rule
+    pub maturity: CrateMaturity,
+    /// whether the changelog or the manifest marks the change as breaking
+    pub breaking: bool,
+    /// bump the version policy asks for
+    pub required_bump: RequiredBump,
     pub issues: Vec<Issue>,
 }
 
@@ -48,9 +75,10 @@ impl VersionBumpStatus {
 }
 
 /// represents version bump analysis for all affected crates
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionBumpAnalysis {
     /// all affected crates and their version status
+    #[serde(with = "crate_id_map")]
     pub crate_versions: HashMap<CrateId, VersionBumpStatus>,
     /// crates that need version bumps but don't have them
     pub crates_needing_bump: Vec<CrateId>,
@@ -73,6 +101,18 @@ impl VersionBumpAnalysis {
         self.total_errors > 0
     }
 
+    /// crates added since the base ref, in name order
+    pub fn new_crates(&self) -> Vec<CrateId> {
+        let mut crates: Vec<CrateId> = self
+            .crate_versions
+            .values()
+            .filter(|status| status.is_new)
+            .map(|status| status.crate_id.clone())
+            .collect();
+        crates.sort_by_key(|id| id.display_name());
+        crates
+    }
+
     /// get the percentage of crates that have been bumped
     pub fn bump_percentage(&self) -> f64 {
         let total = self.crate_versions.len();
@@ -96,47 +136,11 @@ impl VersionBumpAnalysis {
         println!("  bump percentage: {:.1}%", self.bump_percentage());
         println!();
 
-        // calculate column widths
-        let name_width = self
-            .crate_versions
-            .keys()
-            .map(|id| id.display_name().len())
-            .max()
-            .unwrap_or(MIN_CRATE_NAME_WIDTH)
-            .max(MIN_CRATE_NAME_WIDTH);
-
-        let version_width = self
-            .crate_versions
-            .values()
-            .map(|s| {
-                std::cmp::max(
-                    s.base_version.to_string().len(),
-                    s.current_version.to_string().len(),
-                )
-            })
-            .max()
-            .unwrap_or(MIN_VERSION_WIDTH)
-            .max(MIN_VERSION_WIDTH);
-
-        // print table header
-        println!(
-            "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {:>6}  {:<8}",
-            "Crate",
-            "Base Version",
-            "Curr Version",
-            "Status",
-            "Changed",
-            name_width = name_width,
-            version_width = version_width
-        );
-
-        // print separator line
-        println!(
-            "  {}  {}  {}  ------  --------",
-            "-".repeat(name_width),
-            "-".repeat(version_width),
-            "-".repeat(version_width)
-        );
+        let mut table = Table::new(["Crate", "Base Version", "Curr Version", "Status", "Changed"])
+            .min_width(0, MIN_CRATE_NAME_WIDTH)
+            .min_width(1, MIN_VERSION_WIDTH)
+            .min_width(2, MIN_VERSION_WIDTH)
+            .align(3, Align::Right);
 
         // collect and sort by status (needs bump first, then bumped)
         let mut entries: Vec<_> = self.crate_versions.values().collect();
@@ -148,9 +152,16 @@ impl VersionBumpAnalysis {
             )
         });
 
-        // print each crate
         for status in entries {
-            let bump_status = if status.is_bumped { "OK" } else { "NEEDED" };
+            let bump_status = if status.is_new && status.is_bumped {
+                Cell::new("NEW").color(Color::Green)
+            } else if status.is_bumped {
+                Cell::new("OK").color(Color::Green)
+            } else if status.has_errors() {
+                Cell::new("NEEDED").color(Color::Red)
+            } else {
+                Cell::new("NEEDED").color(Color::Yellow)
+            };
 
             let change_type = if status.is_directly_changed {
                 "direct"
@@ -158,28 +169,30 @@ impl VersionBumpAnalysis {
                 "transitive"
             };
 
-            println!(
-                "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {:>6}  {:<8}",
-                status.crate_id.display_name(),
-                status.base_version,
-                status.current_version,
+            table.row([
+                Cell::new(status.crate_id.display_name()),
+                Cell::new(status.base_version.to_string()),
+                Cell::new(status.current_version.to_string()),
                 bump_status,
-                change_type,
-                name_width = name_width,
-                version_width = version_width
-            );
+                Cell::new(change_type),
+            ]);
         }
+        table.print();
 
         println!();
 
         // show summary
         if !self.all_bumped() {
-            println!(
+            let message = format!(
                 "warning: {} crate(s) need version bumps",
                 self.crates_needing_bump.len()
             );
+            println!("{}", paint(&message, Color::Yellow));
         } else {
-            println!("all affected crates have been version-bumped");
+            println!(
+                "{}",
+                paint("all affected crates have been version-bumped", Color::Green)
+            );
         }
     }
 
@@ -189,51 +202,19 @@ impl VersionBumpAnalysis {
     ) {
         println!("{}:", title);
 
-        let name_width = issues
-            .iter()
-            .map(|(s, _)| s.crate_id.display_name().len())
-            .max()
-            .unwrap_or(MIN_CRATE_NAME_WIDTH)
-            .max(MIN_CRATE_NAME_WIDTH);
-
-        let version_width = issues
-            .iter()
-            .map(|(s, _)| {
-                s.current_version
-                    .to_string()
-                    .len()
-                    .max(s.base_version.to_string().len())
-            })
-            .max()
-            .unwrap_or(MIN_VERSION_WIDTH)
-            .max(MIN_VERSION_WIDTH);
-
-        println!(
-            "  {:<name_width$}  {:<version_width$}  {:<version_width$}  Issue",
-            "Crate",
-            "Current",
-            "Base",
-            name_width = name_width,
-            version_width = version_width
-        );
-        println!(
-            "  {}  {}  {}  -----",
-            "-".repeat(name_width),
-            "-".repeat(version_width),
-            "-".repeat(version_width)
-        );
-
+        let mut table = Table::new(["Crate", "Current", "Base", "Issue"])
+            .min_width(0, MIN_CRATE_NAME_WIDTH)
+            .min_width(1, MIN_VERSION_WIDTH)
+            .min_width(2, MIN_VERSION_WIDTH);
         for (status, issue) in issues {
-            println!(
-                "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {}",
-                status.crate_id.display_name(),
-                status.current_version,
-                status.base_version,
-                issue.message,
-                name_width = name_width,
-                version_width = version_width
-            );
+            table.row([
+                Cell::new(status.crate_id.display_name()),
+                Cell::new(status.current_version.to_string()),
+                Cell::new(status.base_version.to_string()),
+                Cell::new(issue.message.clone()).color(severity_color(issue.severity)),
+            ]);
         }
+        table.print();
         println!();
     }
 
@@ -270,7 +251,7 @@ impl VersionBumpAnalysis {
 }
 
 /// represents the impact analysis of changes in a repository
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangeImpactAnalysis {
     /// files that were changed between the two git refs
     pub changed_files: Vec<PathBuf>,
@@ -278,10 +259,58 @@ pub struct ChangeImpactAnalysis {
     pub directly_affected_crates: Vec<CrateId>,
     /// all crates affected by changes (including dependents)
     pub all_affected_crates: Vec<CrateId>,
-    /// crates that need to be rebuilt due to changes
+    /// crates that need to be rebuilt due to changes: the changed crates and
+    /// every dependent, whatever the kind of dependency or its depth
     pub needs_rebuild: Vec<CrateId>,
-    /// mapping of files to the crates they belong to
-    pub file_to_crate_mapping: HashMap<PathBuf, CrateId>,
+    /// crates whose next release the changes end up in: affected crates
+    /// reached over normal and build dependencies, without `publish = false`
+    /// ones
+    #[serde(default)]
+    pub needs_release: Vec<CrateId>,
+    /// dependents reached only through dependencies the configured
+    /// propagation does not follow (dev-dependencies, and build-dependencies
+    /// under `normal`); rebuilt but not affected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dev_only_affected: Vec<CrateId>,
+    /// dependents further from every changed crate than the configured
+    /// `max_transitive_depth`; rebuilt but not affected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub beyond_max_depth: Vec<CrateId>,
+    /// affected dependents that keep the change from their own dependents,
+    /// with `stop_at_boundaries`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boundary_crates: Vec<CrateId>,
+    /// changed files of each directly affected crate, paths relative to the
+    /// repository as in `changed_files`
+    #[serde(with = "crate_id_map")]
+    pub crate_changes: HashMap<CrateId, Vec<FileChange>>,
+    /// structural changes to the Cargo.toml of directly affected crates
+    #[serde(with = "crate_id_map")]
+    pub manifest_changes: HashMap<CrateId, TomlDiff>,
+    /// crates whose only change is to Cargo.toml sections in
+    /// `ignore_manifest_sections`; not affected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored_manifest_crates: Vec<CrateId>,
+    /// changed files recognized as generated by `impact.generated`, left
+    /// out of `changed_files`; they affect no crate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated_files: Vec<PathBuf>,
+    /// crates whose Cargo.toml was deleted between the two refs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_crates: Vec<RemovedCrate>,
+}
+
+/// a crate whose Cargo.toml is gone at the newer ref
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemovedCrate {
+    /// package name in the deleted manifest
+    pub name: String,
+    /// version in the deleted manifest, None when it was inherited or missing
+    pub version: Option<String>,
+    /// the crate's directory, relative to the repository
+    pub path: PathBuf,
+    /// crates that still declare a path dependency on it
+    pub referenced_by: Vec<CrateId>,
 }
 
 impl ChangeImpactAnalysis {
@@ -292,7 +321,15 @@ impl ChangeImpactAnalysis {
             directly_affected_crates: Vec::new(),
             all_affected_crates: Vec::new(),
             needs_rebuild: Vec::new(),
-            file_to_crate_mapping: HashMap::new(),
+            needs_release: Vec::new(),
+            dev_only_affected: Vec::new(),
+            beyond_max_depth: Vec::new(),
+            boundary_crates: Vec::new(),
+            crate_changes: HashMap::new(),
+            manifest_changes: HashMap::new(),
+            ignored_manifest_crates: Vec::new(),
+            generated_files: Vec::new(),
+            removed_crates: Vec::new(),
         }
     }
 
@@ -311,21 +348,49 @@ impl ChangeImpactAnalysis {
         self.all_affected_crates.contains(crate_id)
     }
 
-    /// returns the files that belong to a specific crate
-    pub fn get_changed_files_for_crate(&self, crate_id: &CrateId) -> Vec<PathBuf> {
-        self.changed_files
+    /// returns the changed files that belong to a specific crate
+    pub fn get_changed_files_for_crate(&self, crate_id: &CrateId) -> &[FileChange] {
+        self.crate_changes
+            .get(crate_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// changed files of each directly affected crate keyed by display name,
+    /// the form the reports list them in
+    pub fn changed_files_by_crate(&self) -> BTreeMap<String, &[FileChange]> {
+        self.crate_changes
             .iter()
-            .filter(|file| {
-                self.file_to_crate_mapping
-                    .get(*file)
-                    .map(|id| id == crate_id)
-                    .unwrap_or(false)
-            })
-            .cloned()
+            .map(|(id, changes)| (id.display_name(), changes.as_slice()))
             .collect()
     }
 }
 
+// deleted files can still be matched by path, name and extension
+pub(super) fn is_ignored(ignore: &LogicExpr<FilePredicate>, path: &Path) -> bool {
+    let mut file = FsFile::new(path);
+    file.extension = path.extension().map(|e| e.to_string_lossy().to_string());
+    file.name = path.file_stem().map(|n| n.to_string_lossy().to_string());
+    let _ = file.metadata_scan();
+
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let context = PredicateContext::new(FsDirectory::new(directory)).with_file(file);
+    ignore.evaluate(&context).unwrap_or(false)
+}
+
+/// crates a change propagates to, and the dependents it stops at
+#[derive(Debug, Default)]
+pub(super) struct PropagatedCrates {
+    /// the changed crates and the dependents the change reaches
+    pub affected: Vec<CrateId>,
+    /// dependents only reached over dependencies that are not followed
+    pub dev_only: Vec<CrateId>,
+    /// dependents past the maximum depth
+    pub beyond_depth: Vec<CrateId>,
+    /// affected dependents the change stops at
+    pub boundaries: Vec<CrateId>,
+}
+
 impl Default for ChangeImpactAnalysis {
     fn default() -> Self {
         Self::new()
@@ -345,40 +410,439 @@ impl CrateDependencyGraph {
         repo_path: P,
         from_ref: &GitRef,
         to_ref: &GitRef,
+    ) -> Result<ChangeImpactAnalysis> {
+        self.analyze_git_changes_ignoring(repo_path, from_ref, to_ref, None)
+    }
+
+    /// like analyze_git_changes, but changed files matching `ignore` do not affect any crate
+    pub fn analyze_git_changes_ignoring<P: AsRef<Path>>(
+        &self,
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        ignore: Option<&LogicExpr<FilePredicate>>,
+    ) -> Result<ChangeImpactAnalysis> {
+        let impact = ImpactConfig {
+            ignore: ignore.cloned(),
+            ..Default::default()
+        };
+        self.analyze_git_changes_with_config(repo_path, from_ref, to_ref, &impact)
+    }
+
+    /// like analyze_git_changes, with the ignore filter and the propagation of
+    /// `impact`
+    pub fn analyze_git_changes_with_config<P: AsRef<Path>>(
+        &self,
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        impact: &ImpactConfig,
     ) -> Result<ChangeImpactAnalysis> {
         let repo_path = repo_path.as_ref();
 
         // get changed files from git
-        let changed_files = GitOps::list_changed_files(repo_path, from_ref, to_ref)?;
-
-        // create file-to-crate mapping for all crates
-        let file_mapping = self.build_file_to_crate_mapping()?;
+        let changed_files = timings::time("git diff", || {
+            GitOps::list_changed_files_with_cancel(repo_path, from_ref, to_ref, &self.cancel)
+        })?;
+        self.analyze_changed_files(repo_path, changed_files, from_ref, Some(to_ref), impact)
+    }
 
-        // convert relative paths to absolute by joining with repo_path
-        let absolute_changed_files: Vec<PathBuf> = changed_files
-            .changes
-            .iter()
-            .map(|c| repo_path.join(&c.path))
-            .collect();
+    /// analyzes everything changed since `base_ref`: the commits up to HEAD
+    /// plus uncommitted changes in the working directory
+    pub fn analyze_changes_since<P: AsRef<Path>>(
+        &self,
+        repo_path: P,
+        base_ref: &GitRef,
+        impact: &ImpactConfig,
+    ) -> Result<ChangeImpactAnalysis> {
+        let repo_path = repo_path.as_ref();
 
-        // map changed files to affected crates
-        let directly_affected = self.map_files_to_crates(&absolute_changed_files, &file_mapping);
+        let (mut changed_files, working_directory) = timings::time("git diff", || -> Result<_> {
+            Ok((
+                GitOps::list_changed_files_with_cancel(
+                    repo_path,
+                    base_ref,
+                    &GitRef::Head,
+                    &self.cancel,
+                )?,
+                GitOps::list_working_directory_changes_with_cancel(repo_path, &self.cancel)?,
+            ))
+        })?;
+        changed_files.to_ref = "WORKING_DIR".to_string();
+        for change in working_directory.changes {
+            let existing = changed_files
+                .changes
+                .iter()
+                .position(|c| c.path == change.path);
+            match (existing, &change.change_type) {
+                (None, _) => changed_files.add_change(change),
+                // added since the base and deleted again, nothing left to compare
+                (Some(i), ChangeType::Deleted)
+                    if changed_files.changes[i].change_type == ChangeType::Added =>
+                {
+                    changed_files.changes.remove(i);
+                }
+                (Some(i), ChangeType::Deleted) => {
+                    changed_files.changes[i].change_type = ChangeType::Deleted
+                }
+                // deleted in a commit and recreated in the working directory
+                (Some(i), _) if changed_files.changes[i].change_type == ChangeType::Deleted => {
+                    changed_files.changes[i].change_type = ChangeType::Modified
+                }
+                (Some(_), _) => {}
+            }
+        }
 
-        // find all crates that depend on the directly affected crates
-        let all_affected = self.find_all_affected_crates(&directly_affected);
+        self.analyze_changed_files(repo_path, changed_files, base_ref, None, impact)
+    }
 
-        // determine which crates need rebuild (all affected crates)
-        let needs_rebuild = all_affected.clone();
+    // impact of `changed_files` between `from_ref` and `to_ref`, the working
+    // directory when `to_ref` is None
+    fn analyze_changed_files(
+        &self,
+        repo_path: &Path,
+        mut changed_files: ChangedFiles,
+        from_ref: &GitRef,
+        to_ref: Option<&GitRef>,
+        impact: &ImpactConfig,
+    ) -> Result<ChangeImpactAnalysis> {
+        if let Some(ignore) = &impact.ignore {
+            changed_files
+                .changes
+                .retain(|c| !is_ignored(ignore, &repo_path.join(&c.path)));
+        }
+        if let Some(scope) = &self.scope {
+            changed_files.changes.retain(|c| scope.contains(&c.path));
+        }
+        let generated_files = timings::time("git diff", || {
+            self.take_generated_changes(repo_path, &mut changed_files, from_ref, to_ref, impact)
+        })?;
+
+        // reading both sides of the changed manifests is part of the diff
+        let (manifest_diffs, mut removed_crates) = timings::time("git diff", || -> Result<_> {
+            Ok((
+                self.diff_changed_manifests(repo_path, from_ref, to_ref, &changed_files)?,
+                self.find_removed_crates(repo_path, from_ref, &changed_files)?,
+            ))
+        })?;
+        self.find_references_to_removed(&mut removed_crates);
+
+        let (
+            crate_changes,
+            manifest_changes,
+            ignored_manifest_crates,
+            directly_affected,
+            propagated,
+        ) = timings::time("file mapping", || -> Result<_> {
+            // create file-to-crate mapping for all crates
+            let file_mapping = self.build_file_to_crate_mapping()?;
+
+            // map changed files to affected crates
+            let (mut directly_affected, mut crate_changes) =
+                self.map_changes_to_crates(repo_path, &changed_files.changes, &file_mapping);
+
+            // describe manifest changes, and treat crates inheriting a changed
+            // [workspace.dependencies] entry or [workspace.lints] table as
+            // directly affected, unless policy ignores those sections
+            let ignored = &impact.ignore_manifest_sections;
+            let mut manifest_changes = HashMap::new();
+            let mut ignored_manifest_crates = Vec::new();
+            let mut changed_workspace_deps = Vec::new();
+            let mut workspace_lints_changed = false;
+            for (path, diff) in manifest_diffs {
+                if !ignored.contains(&TomlSection::Dependencies) {
+                    changed_workspace_deps.extend(
+                        diff.workspace_dependency_changes()
+                            .into_iter()
+                            .map(str::to_string),
+                    );
+                }
+                workspace_lints_changed |=
+                    !ignored.contains(&TomlSection::Lints) && diff.workspace_lints_changed();
+                let Some(crate_id) = file_mapping.get(&path) else {
+                    continue;
+                };
+                // a crate whose only change is to ignored sections of its
+                // manifest needs no release
+                let manifest_only = crate_changes
+                    .get(crate_id)
+                    .is_some_and(|changes| changes.len() == 1);
+                if manifest_only
+                    && !diff.is_empty()
+                    && diff
+                        .sections()
+                        .iter()
+                        .all(|section| ignored.contains(section))
+                {
+                    directly_affected.retain(|id| id != crate_id);
+                    crate_changes.remove(crate_id);
+                    ignored_manifest_crates.push(crate_id.clone());
+                    continue;
+                }
+                manifest_changes.insert(crate_id.clone(), diff);
+            }
+            let mut inheriting = self.crates_inheriting(&changed_workspace_deps);
+            if workspace_lints_changed {
+                inheriting.extend(self.crates_inheriting_lints());
+            }
+            for crate_id in inheriting {
+                if !directly_affected.contains(&crate_id) {
+                    directly_affected.push(crate_id);
+                }
+            }
+            ignored_manifest_crates.retain(|id| !directly_affected.contains(id));
+            ignored_manifest_crates.sort_by_key(|id| id.display_name());
+
+            // find all crates that depend on the directly affected crates
+            let propagated = self.find_propagated_crates(&directly_affected, impact);
+            Ok((
+                crate_changes,
+                manifest_changes,
+                ignored_manifest_crates,
+                directly_affected,
+                propagated,
+            ))
+        })?;
 
         Ok(ChangeImpactAnalysis {
             changed_files: changed_files.changes.into_iter().map(|c| c.path).collect(),
+            needs_rebuild: self.find_all_affected_crates(&directly_affected),
+            needs_release: self.find_release_crates(&directly_affected, impact),
             directly_affected_crates: directly_affected,
-            all_affected_crates: all_affected,
-            needs_rebuild,
-            file_to_crate_mapping: file_mapping,
+            all_affected_crates: propagated.affected,
+            dev_only_affected: propagated.dev_only,
+            beyond_max_depth: propagated.beyond_depth,
+            boundary_crates: propagated.boundaries,
+            crate_changes,
+            manifest_changes,
+            ignored_manifest_crates,
+            generated_files,
+            removed_crates,
         })
     }
 
+    // removes the generated files from `changed_files` and returns their
+    // paths; markers are looked for in the newer side of each change, the
+    // older one for deletions
+    fn take_generated_changes(
+        &self,
+        repo_path: &Path,
+        changed_files: &mut ChangedFiles,
+        from_ref: &GitRef,
+        to_ref: Option<&GitRef>,
+        impact: &ImpactConfig,
+    ) -> Result<Vec<PathBuf>> {
+        let generated = &impact.generated;
+        if generated.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut generated_files = Vec::new();
+        let mut kept = Vec::new();
+        for change in std::mem::take(&mut changed_files.changes) {
+            let is_generated = generated.is_generated(&change.path, || {
+                let git_ref = match (&change.change_type, to_ref) {
+                    (ChangeType::Deleted, _) => from_ref,
+                    (_, Some(to_ref)) => to_ref,
+                    (_, None) => {
+                        return Ok(std::fs::read_to_string(repo_path.join(&change.path)).ok());
+                    }
+                };
+                GitOps::read_file_at_ref_with_cancel(repo_path, git_ref, &change.path, &self.cancel)
+            })?;
+            if is_generated {
+                generated_files.push(change.path);
+            } else {
+                kept.push(change);
+            }
+        }
+        changed_files.changes = kept;
+        Ok(generated_files)
+    }
+
+    /// crates whose Cargo.toml was deleted between `from_ref` and the newer
+    /// side of `changed_files`; a package that still exists elsewhere in the
+    /// graph was moved, not removed
+    fn find_removed_crates(
+        &self,
+        repo_path: &Path,
+        from_ref: &GitRef,
+        changed_files: &ChangedFiles,
+    ) -> Result<Vec<RemovedCrate>> {
+        let mut removed = Vec::new();
+
+        for change in &changed_files.changes {
+            if change.change_type != ChangeType::Deleted
+                || change
+                    .path
+                    .file_name()
+                    .is_none_or(|name| name != "Cargo.toml")
+            {
+                continue;
+            }
+            let Some(content) = GitOps::read_file_at_ref_with_cancel(
+                repo_path,
+                from_ref,
+                &change.path,
+                &self.cancel,
+            )?
+            else {
+                continue;
+            };
+            // workspace roots and manifests that did not parse were no crate
+            let Ok(old) =
+                TomlReader::parse_string(&content, change.path.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let Some(name) = old.get_path("package.name").and_then(toml::Value::as_str) else {
+                continue;
+            };
+            if self.crates.keys().any(|id| id.name == name) {
+                continue;
+            }
+
+            removed.push(RemovedCrate {
+                name: name.to_string(),
+                version: old
+                    .get_path("package.version")
+                    .and_then(toml::Value::as_str)
+                    .map(str::to_string),
+                path: change
+                    .path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                referenced_by: Vec::new(),
+            });
+        }
+
+        removed.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(removed)
+    }
+
+    /// fill in the crates whose manifest still declares a path dependency on
+    /// a removed crate, directly or through [workspace.dependencies]; cargo
+    /// fails to load them until the entry goes
+    fn find_references_to_removed(&self, removed: &mut [RemovedCrate]) {
+        if removed.is_empty() {
+            return;
+        }
+
+        for info in self.crates.values() {
+            let Ok(doc) = TomlReader::read_cargo_toml_with_workspace(&info.path) else {
+                continue;
+            };
+            for (key, dependency) in doc.all_dependencies() {
+                let Some(dependency) = dependency.as_table() else {
+                    continue;
+                };
+                if !dependency.contains_key("path") {
+                    continue;
+                }
+                let package = dependency
+                    .get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(&key);
+                for crate_removed in removed.iter_mut().filter(|r| r.name == package) {
+                    if !crate_removed.referenced_by.contains(&info.id) {
+                        crate_removed.referenced_by.push(info.id.clone());
+                    }
+                }
+            }
+        }
+
+        for crate_removed in removed.iter_mut() {
+            crate_removed
+                .referenced_by
+                .sort_by_key(|id| id.display_name());
+        }
+    }
+
+    /// structural diffs of every Cargo.toml modified between the two refs, keyed by
+    /// absolute path; a missing `to_ref` compares against the working directory
+    fn diff_changed_manifests(
+        &self,
+        repo_path: &Path,
+        from_ref: &GitRef,
+        to_ref: Option<&GitRef>,
+        changed_files: &ChangedFiles,
+    ) -> Result<Vec<(PathBuf, TomlDiff)>> {
+        let mut diffs = Vec::new();
+
+        for change in &changed_files.changes {
+            if change.change_type != ChangeType::Modified
+                || change
+                    .path
+                    .file_name()
+                    .is_none_or(|name| name != "Cargo.toml")
+            {
+                continue;
+            }
+
+            let read = |git_ref| {
+                GitOps::read_file_at_ref_with_cancel(repo_path, git_ref, &change.path, &self.cancel)
+            };
+            let (Some(old), Some(new)) = (
+                read(from_ref)?,
+                match to_ref {
+                    Some(to_ref) => read(to_ref)?,
+                    None => std::fs::read_to_string(repo_path.join(&change.path)).ok(),
+                },
+            ) else {
+                continue;
+            };
+            let path = change.path.to_string_lossy().to_string();
+            // unparsable manifests are reported elsewhere
+            let (Ok(old), Ok(new)) = (
+                TomlReader::parse_string(&old, path.clone()),
+                TomlReader::parse_string(&new, path),
+            ) else {
+                continue;
+            };
+
+            diffs.push((repo_path.join(&change.path), toml_diff(&old, &new)));
+        }
+
+        Ok(diffs)
+    }
+
+    /// crates whose manifest declares any of `dependencies` with `workspace = true`
+    fn crates_inheriting(&self, dependencies: &[String]) -> Vec<CrateId> {
+        if dependencies.is_empty() {
+            return Vec::new();
+        }
+
+        self.crates_with_manifest(|doc| {
+            doc.workspace_inherited_dependencies()
+                .iter()
+                .any(|name| dependencies.contains(name))
+        })
+    }
+
+    /// crates whose manifest takes its lints from [workspace.lints]
+    fn crates_inheriting_lints(&self) -> Vec<CrateId> {
+        self.crates_with_manifest(|doc| {
+            doc.get_path("lints.workspace")
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(false)
+        })
+    }
+
+    // crates whose readable manifest matches `predicate`, sorted by name
+    fn crates_with_manifest(&self, predicate: impl Fn(&TomlDocument) -> bool) -> Vec<CrateId> {
+        let mut crates: Vec<CrateId> = self
+            .crates
+            .values()
+            .filter(|info| {
+                TomlReader::read_file(&info.cargo_toml_path).is_ok_and(|doc| predicate(&doc))
+            })
+            .map(|info| info.id.clone())
+            .collect();
+        crates.sort_by_key(|id| id.display_name());
+        crates
+    }
+
     /// analyzes changes in the working directory compared to a git ref
     pub fn analyze_working_directory_changes<P: AsRef<Path>>(
         &self,
@@ -387,66 +851,79 @@ impl CrateDependencyGraph {
         let repo_path = repo_path.as_ref();
 
         // get changed files in working directory
-        let changed_files = GitOps::list_working_directory_changes(repo_path)?;
+        let changed_files =
+            GitOps::list_working_directory_changes_with_cancel(repo_path, &self.cancel)?;
 
         // create file-to-crate mapping for all crates
         let file_mapping = self.build_file_to_crate_mapping()?;
 
-        // convert relative paths to absolute by joining with repo_path
-        let absolute_changed_files: Vec<PathBuf> = changed_files
-            .changes
-            .iter()
-            .map(|c| repo_path.join(&c.path))
-            .collect();
-
         // map changed files to affected crates
-        let directly_affected = self.map_files_to_crates(&absolute_changed_files, &file_mapping);
+        let (directly_affected, crate_changes) =
+            self.map_changes_to_crates(repo_path, &changed_files.changes, &file_mapping);
 
         // find all crates that depend on the directly affected crates
         let all_affected = self.find_all_affected_crates(&directly_affected);
 
-        // determine which crates need rebuild (all affected crates)
+        // every dependency is followed, so rebuilding touches the same crates
         let needs_rebuild = all_affected.clone();
+        let needs_release = self.find_release_crates(&directly_affected, &ImpactConfig::default());
 
         Ok(ChangeImpactAnalysis {
             changed_files: changed_files.changes.into_iter().map(|c| c.path).collect(),
             directly_affected_crates: directly_affected,
             all_affected_crates: all_affected,
             needs_rebuild,
-            file_to_crate_mapping: file_mapping,
+            needs_release,
+            dev_only_affected: Vec::new(),
+            beyond_max_depth: Vec::new(),
+            boundary_crates: Vec::new(),
+            crate_changes,
+            manifest_changes: HashMap::new(),
+            ignored_manifest_crates: Vec::new(),
+            generated_files: Vec::new(),
+            removed_crates: Vec::new(),
         })
     }
 
-    /// maps file paths to their owning crates
+    /// maps changed files, relative to `repo_path`, to their owning crates
     ///
-    /// returns a list of crate IDs that contain any of the given files
-    fn map_files_to_crates(
+    /// returns the crates containing any of the files, in the order first
+    /// seen, and the changes of each of them
+    fn map_changes_to_crates(
         &self,
-        file_paths: &[PathBuf],
+        repo_path: &Path,
+        changes: &[FileChange],
         file_mapping: &HashMap<PathBuf, CrateId>,
-    ) -> Vec<CrateId> {
+    ) -> (Vec<CrateId>, HashMap<CrateId, Vec<FileChange>>) {
         let mut affected_crates = Vec::new();
-        let mut seen = std::collections::HashSet::new();
+        let mut crate_changes: HashMap<CrateId, Vec<FileChange>> = HashMap::new();
 
-        for file_path in file_paths {
-            // try to find which crate this file belongs to
-            if let Some(crate_id) = file_mapping.get(file_path)
-                && seen.insert(crate_id.clone())
-            {
+        for change in changes {
+            // the mapping is keyed by absolute path
+            let Some(crate_id) = file_mapping.get(&repo_path.join(&change.path)) else {
+                continue;
+            };
+            if !crate_changes.contains_key(crate_id) {
                 affected_crates.push(crate_id.clone());
             }
+            crate_changes
+                .entry(crate_id.clone())
+                .or_default()
+                .push(change.clone());
         }
 
-        affected_crates
+        (affected_crates, crate_changes)
     }
 
     /// builds a mapping from file paths to crate IDs
     ///
     /// this method scans all crates and creates a map of which files belong to which crate
-    fn build_file_to_crate_mapping(&self) -> Result<HashMap<PathBuf, CrateId>> {
+    pub(super) fn build_file_to_crate_mapping(&self) -> Result<HashMap<PathBuf, CrateId>> {
         let mut mapping = HashMap::new();
 
         for crate_info in self.crates.values() {
+            self.cancel.check()?;
+
             // get the crate's root directory
             let crate_root = &crate_info.path;
 
@@ -462,14 +939,13 @@ impl CrateDependencyGraph {
         Ok(mapping)
     }
 
-    /// scans a crate directory for all relevant files
-    fn scan_crate_files(&self, crate_root: &Path) -> Result<Vec<PathBuf>> {
+    /// scans a crate directory for all relevant files, leaving out build
+    /// output and .git like every other scan
+    pub(super) fn scan_crate_files(&self, crate_root: &Path) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
+        let options = ScanOptions::default().skip_build_dirs_of(crate_root);
 
-        // common source directories to scan
-        let source_dirs = ["src", "tests", "benches", "examples", "build.rs"];
-
-        for source_dir in &source_dirs {
+        for source_dir in &CRATE_SOURCES {
             let dir_path = crate_root.join(source_dir);
 
             if !dir_path.exists() {
@@ -481,7 +957,7 @@ impl CrateDependencyGraph {
                 files.push(dir_path);
             } else if dir_path.is_dir() {
                 // recursively scan directory
-                Self::scan_directory_recursive(&dir_path, &mut files)?;
+                Self::scan_directory_recursive(&dir_path, &options, &mut files)?;
             }
         }
 
@@ -495,14 +971,18 @@ impl CrateDependencyGraph {
     }
 
     /// recursively scans a directory for files
-    fn scan_directory_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    fn scan_directory_recursive(
+        dir: &Path,
+        options: &ScanOptions,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
         if let Ok(entries) = std::fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.is_file() {
                     files.push(path);
-                } else if path.is_dir() {
-                    Self::scan_directory_recursive(&path, files)?;
+                } else if path.is_dir() && !options.is_skipped(&path) {
+                    Self::scan_directory_recursive(&path, options, files)?;
                 }
             }
         }
@@ -513,39 +993,141 @@ impl CrateDependencyGraph {
     ///
     /// this includes the directly affected crates and all crates that depend on them
     /// (transitively following the dependency graph)
-    fn find_all_affected_crates(&self, directly_affected: &[CrateId]) -> Vec<CrateId> {
-        let mut affected = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-
-        for crate_id in directly_affected {
-            self.collect_affected_recursive(crate_id, &mut affected, &mut visited);
-        }
+    pub(super) fn find_all_affected_crates(&self, directly_affected: &[CrateId]) -> Vec<CrateId> {
+        self.find_propagated_crates(directly_affected, &ImpactConfig::default())
+            .affected
+    }
 
-        affected
+    /// crates whose next release picks up changes to the given crates: the
+    /// dependencies of a published crate are normal and build ones, and crates
+    /// with `publish = false` are never released
+    pub(super) fn find_release_crates(
+        &self,
+        directly_affected: &[CrateId],
+        impact: &ImpactConfig,
+    ) -> Vec<CrateId> {
+        let release = ImpactConfig {
+            ignore: None,
+            propagation: match impact.propagation {
+                ImpactPropagation::All => ImpactPropagation::NoDev,
+                stricter => stricter,
+            },
+            max_transitive_depth: impact.max_transitive_depth,
+            stop_at_boundaries: impact.stop_at_boundaries,
+            ignore_manifest_sections: Vec::new(),
+            generated: GeneratedFiles::default(),
+        };
+        let mut crates = self
+            .find_propagated_crates(directly_affected, &release)
+            .affected;
+        crates.retain(|crate_id| {
+            self.crates
+                .get(crate_id)
+                .and_then(|info| TomlReader::read_file(&info.cargo_toml_path).ok())
+                .is_none_or(|document| ManifestChecker::is_publishable(&document))
+        });
+        crates
     }
 
-    /// recursively collects all crates affected by changes to a given crate
-    fn collect_affected_recursive(
+    /// like find_all_affected_crates, following only the dependencies
+    /// `impact.propagation` follows, at most `impact.max_transitive_depth`
+    /// dependents away from a changed crate, and with `impact.stop_at_boundaries`
+    /// not past a dependent that absorbs the change
+    ///
+    /// also returns the dependents left out, which are not affected themselves
+    pub(super) fn find_propagated_crates(
         &self,
-        crate_id: &CrateId,
-        affected: &mut Vec<CrateId>,
-        visited: &mut std::collections::HashSet<CrateId>,
-    ) {
-        // avoid infinite loops in case of cycles
-        if !visited.insert(crate_id.clone()) {
-            return;
+        directly_affected: &[CrateId],
+        impact: &ImpactConfig,
+    ) -> PropagatedCrates {
+        let mut propagated = PropagatedCrates {
+            affected: directly_affected.to_vec(),
+            ..Default::default()
+        };
+        let mut visited: std::collections::HashSet<CrateId> =
+            directly_affected.iter().cloned().collect();
+
+        // breadth first, so each crate is reached at its shortest depth
+        let mut queue: std::collections::VecDeque<(CrateId, usize)> =
+            directly_affected.iter().map(|id| (id.clone(), 0)).collect();
+        while let Some((crate_id, depth)) = queue.pop_front() {
+            for (dependent, dep_type) in self.get_dependent_edges(&crate_id) {
+                if !impact.propagation.follows(dep_type) {
+                    continue;
+                }
+                let boundary =
+                    impact.stop_at_boundaries && self.is_versioned_boundary(dependent, &crate_id);
+                if visited.contains(dependent) {
+                    // absorbed on another path, but this one passes the change on
+                    if !boundary && propagated.boundaries.contains(dependent) {
+                        propagated.boundaries.retain(|id| id != dependent);
+                        queue.push_back((dependent.clone(), depth + 1));
+                    }
+                    continue;
+                }
+                if impact
+                    .max_transitive_depth
+                    .is_some_and(|max| depth + 1 > max)
+                {
+                    if !propagated.beyond_depth.contains(dependent) {
+                        propagated.beyond_depth.push(dependent.clone());
+                    }
+                    continue;
+                }
+                visited.insert(dependent.clone());
+                propagated.affected.push(dependent.clone());
+                if boundary {
+                    propagated.boundaries.push(dependent.clone());
+                } else {
+                    queue.push_back((dependent.clone(), depth + 1));
+                }
+            }
         }
 
-        // add this crate to affected list
-        affected.push(crate_id.clone());
+        for crate_id in &propagated.affected {
+            if propagated.boundaries.contains(crate_id) {
+                continue;
+            }
+            for (dependent, dep_type) in self.get_dependent_edges(crate_id) {
+                if !impact.propagation.follows(dep_type)
+                    && !visited.contains(dependent)
+                    && !propagated.dev_only.contains(dependent)
+                {
+                    propagated.dev_only.push(dependent.clone());
+                }
+            }
+        }
+        // reached at a shorter depth through another path
+        propagated
+            .beyond_depth
+            .retain(|crate_id| !visited.contains(crate_id));
+        propagated
+            .dev_only
+            .retain(|crate_id| !propagated.beyond_depth.contains(crate_id));
+
+        propagated
+    }
 
-        // find all crates that depend on this one
-        let dependents = self.get_dependents(crate_id);
+    /// whether `dependent` keeps changes to `dependency` from its own
+    /// dependents: it pins the dependency with an `=x.y.z` requirement, or
+    /// lists it under `stable-reexports` in [package.metadata.deptrack]
+    fn is_versioned_boundary(&self, dependent: &CrateId, dependency: &CrateId) -> bool {
+        let Some(document) = self
+            .crates
+            .get(dependent)
+            .and_then(|info| TomlReader::read_file(&info.cargo_toml_path).ok())
+        else {
+            return false;
+        };
 
-        // recursively process dependents
-        for dependent in dependents {
-            self.collect_affected_recursive(dependent, affected, visited);
-        }
+        let pinned = document
+            .dependency_requirements(&dependency.name, &["dependencies", "build-dependencies"])
+            .iter()
+            .any(|requirement| requirement.trim_start().starts_with('='));
+        pinned
+            || document
+                .deptrack_metadata()
+                .is_ok_and(|metadata| metadata.stable_reexports.contains(&dependency.name))
     }
 
     /// analyze version bumps for affected crates
@@ -560,6 +1142,34 @@ impl CrateDependencyGraph {
         directly_changed: &[CrateId],
         direct_severity: &crate::utils::severity_config::SeverityConfig,
         transitive_severity: &crate::utils::severity_config::SeverityConfig,
+    ) -> Result<VersionBumpAnalysis> {
+        let config = DeptrackConfig {
+            direct_severity: direct_severity.clone(),
+            transitive_severity: transitive_severity.clone(),
+            ..Default::default()
+        };
+        self.analyze_version_bumps_with_config(
+            repo_path,
+            base_ref,
+            affected_crates,
+            directly_changed,
+            &config,
+        )
+    }
+
+    /// analyze version bumps for affected crates against the version policy
+    ///
+    /// the bump a crate needs depends on its maturity at the base ref and on
+    /// whether the change is breaking, see
+    /// [`crate::utils::version_policy::VersionPolicy`]; severities,
+    /// changelog location and the policy come from `config`
+    pub fn analyze_version_bumps_with_config<P: AsRef<Path>>(
+        &self,
+        repo_path: P,
+        base_ref: &GitRef,
+        affected_crates: &[CrateId],
+        directly_changed: &[CrateId],
+        config: &DeptrackConfig,
     ) -> Result<VersionBumpAnalysis> {
         let repo_path = repo_path.as_ref();
         let mut crate_versions = HashMap::new();
@@ -569,6 +1179,8 @@ impl CrateDependencyGraph {
         let mut total_warnings = 0;
 
         for crate_id in affected_crates {
+            self.cancel.check()?;
+
             // get crate info from current state
             let crate_info = match self.crates.get(crate_id) {
                 Some(info) => info,
@@ -581,29 +1193,46 @@ impl CrateDependencyGraph {
                 Err(_) => continue, // skip if version can't be parsed
             };
 
-            // get base version from git ref
-            let base_version = match Self::read_crate_version_at_ref(
-                repo_path,
-                base_ref,
-                &crate_info.cargo_toml_path,
-            ) {
-                Ok(Some(v)) => v,
-                _ => {
-                    // if we can't read base version, assume it's the same as current
-                    // (might be a new crate)
-                    current_version.clone()
-                }
-            };
+            // get base manifest from git ref; a crate without one is new,
+            // and one that cannot be read keeps its current version as base
+            let base_read =
+                self.read_manifest_at_ref(repo_path, base_ref, &crate_info.cargo_toml_path);
+            // a read killed by cancelling looks like a missing manifest
+            self.cancel.check()?;
+            let is_new = matches!(base_read, Ok(None));
+            let base_manifest = base_read.ok().flatten();
+            let base_version = base_manifest
+                .as_ref()
+                .and_then(|manifest| manifest.get_version())
+                .and_then(|v| Version::parse(&v).ok())
+                .unwrap_or_else(|| current_version.clone());
+
+            let current_manifest = TomlReader::read_file(&crate_info.cargo_toml_path).ok();
+            let publishable = current_manifest
+                .as_ref()
+                .is_none_or(ManifestChecker::is_publishable);
+            let maturity = CrateMaturity::of(&base_version, publishable);
+            let breaking = Self::is_breaking_change(
+                crate_info,
+                &current_version,
+                base_manifest.as_ref(),
+                current_manifest.as_ref(),
+                config,
+            );
+            let required_bump = config.version_policy.required(maturity, breaking);
 
-            // check if version was bumped
-            let is_bumped = current_version > base_version;
+            // check if version was bumped far enough; a new crate has nothing
+            // to bump from
+            let bump = RequiredBump::between(&base_version, &current_version);
+            let is_bumped =
+                bump >= required_bump || (is_new && config.new_crates.skip_version_bump);
             let is_directly_changed = directly_changed.contains(crate_id);
 
             // determine which severity config to use
             let severity_config = if is_directly_changed {
-                direct_severity
+                &config.direct_severity
             } else {
-                transitive_severity
+                &config.transitive_severity
             };
 
             let mut status = VersionBumpStatus {
@@ -612,6 +1241,10 @@ impl CrateDependencyGraph {
                 current_version: current_version.clone(),
                 is_bumped,
                 is_directly_changed,
+                is_new,
+                maturity,
+                breaking,
+                required_bump,
                 issues: Vec::new(),
             };
 
@@ -623,10 +1256,30 @@ impl CrateDependencyGraph {
                 // create issue for missing version bump
                 let severity =
                     severity_config.get_severity(crate::utils::severity::IssueType::NoVersionBump);
-                let message = format!(
-                    "version not bumped (current: {}, base: {})",
-                    current_version, base_version
-                );
+                let message = if is_new {
+                    format!(
+                        "new crate {} has no base version to compare, set new_crates.skip_version_bump to leave it out",
+                        current_version
+                    )
+                } else if bump == RequiredBump::None {
+                    format!(
+                        "version not bumped (current: {}, base: {})",
+                        current_version, base_version
+                    )
+                } else {
+                    format!(
+                        "{} to a {} crate needs a {} bump (current: {}, base: {})",
+                        if breaking {
+                            "breaking change"
+                        } else {
+                            "change"
+                        },
+                        maturity,
+                        required_bump,
+                        current_version,
+                        base_version
+                    )
+                };
                 let issue = Issue::new(
                     severity,
                     crate::utils::severity::IssueType::NoVersionBump,
@@ -653,59 +1306,65 @@ impl CrateDependencyGraph {
         })
     }
 
-    /// read crate version from a specific git ref
-    fn read_crate_version_at_ref<P: AsRef<Path>>(
-        repo_path: P,
-        git_ref: &GitRef,
-        cargo_toml_path: &Path,
-    ) -> Result<Option<Version>> {
-        let repo_path = repo_path.as_ref();
-
-        // get the relative path from repo root
-        let relative_path = match cargo_toml_path.strip_prefix(repo_path) {
-            Ok(p) => p,
-            Err(_) => cargo_toml_path, // already relative
-        };
-
-        // use git show to read file at specific ref
-        let ref_str = match git_ref {
-            GitRef::Head => "HEAD".to_string(),
-            GitRef::Branch(name) => name.clone(),
-            GitRef::Tag(name) => format!("refs/tags/{}", name),
-            GitRef::Hash(hash) => hash.clone(),
-        };
+    // a change is breaking when the changelog section of the current version
+    // has a breaking entry or a feature of the base manifest is gone
+    fn is_breaking_change(
+        crate_info: &super::types::CrateInfo,
+        current_version: &Version,
+        base_manifest: Option<&TomlDocument>,
+        current_manifest: Option<&TomlDocument>,
+        config: &DeptrackConfig,
+    ) -> bool {
+        let changelog_path = ChangelogChecker::changelog_path(&crate_info.path, &config.changelog);
+        let changelog_breaking = changelog_path.exists()
+            && parse_changelog(&changelog_path).is_ok_and(|changelog| {
+                changelog
+                    .get_version(current_version)
+                    .is_some_and(|section| section.has_breaking_changes())
+            });
+        if changelog_breaking {
+            return true;
+        }
 
-        let output = std::process::Command::new("git")
-            .arg("-C")
-            .arg(repo_path)
-            .arg("show")
-            .arg(format!("{}:{}", ref_str, relative_path.display()))
-            .output()
-            .map_err(crate::error::Error::IoError)?;
-
-        if !output.status.success() {
-            // file doesn't exist at this ref (maybe new crate)
-            return Ok(None);
+        match (base_manifest, current_manifest) {
+            (Some(base), Some(current)) => {
+                let current_features = FeatureChecker::available_features(current);
+                FeatureChecker::available_features(base)
+                    .iter()
+                    .any(|feature| !current_features.contains(feature))
+            }
+            _ => false,
         }
+    }
 
-        // parse the Cargo.toml content
-        let content = String::from_utf8_lossy(&output.stdout);
-        let toml_doc: toml::Table = match content.parse() {
-            Ok(doc) => doc,
-            Err(_) => return Ok(None),
+    /// read a crate manifest from a specific git ref, None when it is missing
+    /// there (a new crate), an error when it does not parse
+    fn read_manifest_at_ref<P: AsRef<Path>>(
+        &self,
+        repo_path: P,
+        git_ref: &GitRef,
+        cargo_toml_path: &Path,
+    ) -> Result<Option<TomlDocument>> {
+        let content = match GitOps::read_file_at_ref_with_cancel(
+            repo_path,
+            git_ref,
+            cargo_toml_path,
+            &self.cancel,
+        )? {
+            Some(content) => content,
+            None => return Ok(None),
         };
 
-        // extract version from [package] section
-        let version_str = toml_doc
-            .get("package")
-            .and_then(|p| p.get("version"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("0.1.0");
-
-        match Version::parse(version_str) {
-            Ok(v) => Ok(Some(v)),
-            Err(_) => Ok(None),
-        }
+        let value = content
+            .parse::<toml::Value>()
+            .map_err(|source| Error::TomlParseError {
+                path: cargo_toml_path.to_path_buf(),
+                source,
+            })?;
+        Ok(Some(TomlDocument::new(
+            cargo_toml_path.to_string_lossy().into_owned(),
+            value,
+        )))
     }
 }
 
@@ -0,0 +1,216 @@
+use super::types::{CrateDependencyGraph, CrateId};
+use crate::error::Result;
+use crate::utils::filesystem::{FilesystemExplorer, FsDirectory, FsElement};
+use serde::Serialize;
+use std::fs;
+
+/// size of a single crate's sources, next to its place in the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateMetrics {
+    pub crate_id: CrateId,
+    /// every file in the crate directory, nested crates excluded
+    pub files: usize,
+    /// `.rs` files among `files`
+    pub rust_files: usize,
+    /// non-blank lines across the `.rs` files
+    pub lines_of_code: usize,
+    /// bytes across the `.rs` files
+    pub source_bytes: u64,
+    pub dependents: usize,
+    pub dependencies: usize,
+}
+
+/// ordering of the per-crate metrics table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrateMetricsSort {
+    #[default]
+    Name,
+    Loc,
+    Files,
+    Size,
+    Dependents,
+}
+
+impl std::str::FromStr for CrateMetricsSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(CrateMetricsSort::Name),
+            "loc" => Ok(CrateMetricsSort::Loc),
+            "files" => Ok(CrateMetricsSort::Files),
+            "size" => Ok(CrateMetricsSort::Size),
+            "dependents" => Ok(CrateMetricsSort::Dependents),
+            _ => Err(format!(
+                "invalid sort key: {}, use 'name', 'loc', 'files', 'size' or 'dependents'",
+                s
+            )),
+        }
+    }
+}
+
+impl CrateMetrics {
+    /// sort metrics by `key`, largest first for everything but the name
+    pub fn sort(metrics: &mut [CrateMetrics], key: CrateMetricsSort) {
+        match key {
+            CrateMetricsSort::Name => metrics.sort_by_key(|m| m.crate_id.display_name()),
+            CrateMetricsSort::Loc => metrics.sort_by(|a, b| b.lines_of_code.cmp(&a.lines_of_code)),
+            CrateMetricsSort::Files => metrics.sort_by(|a, b| b.files.cmp(&a.files)),
+            CrateMetricsSort::Size => metrics.sort_by(|a, b| b.source_bytes.cmp(&a.source_bytes)),
+            CrateMetricsSort::Dependents => metrics.sort_by(|a, b| b.dependents.cmp(&a.dependents)),
+        }
+    }
+
+    /// display metrics as a table in their current order
+    pub fn display_table(metrics: &[CrateMetrics]) {
+        let name_width = metrics
+            .iter()
+            .map(|m| m.crate_id.display_name().len())
+            .max()
+            .unwrap_or(10)
+            .max(10);
+
+        println!(
+            "  {:<name_width$}  {:>8}  {:>8}  {:>10}  {:>12}  {:>10}  {:>12}",
+            "Crate",
+            "Files",
+            "Rust",
+            "LOC",
+            "Source size",
+            "Dependents",
+            "Dependencies",
+            name_width = name_width
+        );
+        println!(
+            "  {}  {}  {}  {}  {}  {}  {}",
+            "-".repeat(name_width),
+            "-".repeat(8),
+            "-".repeat(8),
+            "-".repeat(10),
+            "-".repeat(12),
+            "-".repeat(10),
+            "-".repeat(12)
+        );
+        for m in metrics {
+            println!(
+                "  {:<name_width$}  {:>8}  {:>8}  {:>10}  {:>12}  {:>10}  {:>12}",
+                m.crate_id.display_name(),
+                m.files,
+                m.rust_files,
+                m.lines_of_code,
+                m.source_bytes,
+                m.dependents,
+                m.dependencies,
+                name_width = name_width
+            );
+        }
+    }
+}
+
+impl CrateDependencyGraph {
+    /// source metrics of every crate, sorted by name
+    pub fn crate_metrics(&self) -> Result<Vec<CrateMetrics>> {
+        let mut metrics = Vec::new();
+        for (crate_id, crate_info) in &self.crates {
+            let explorer = FilesystemExplorer::new(&crate_info.path);
+            let directory = explorer.scan_from_root()?;
+
+            let mut crate_metrics = CrateMetrics {
+                crate_id: crate_id.clone(),
+                files: 0,
+                rust_files: 0,
+                lines_of_code: 0,
+                source_bytes: 0,
+                dependents: self.get_dependents(crate_id).len(),
+                dependencies: self.get_dependencies(crate_id).len(),
+            };
+            collect_metrics(&directory, &mut crate_metrics)?;
+            metrics.push(crate_metrics);
+        }
+        CrateMetrics::sort(&mut metrics, CrateMetricsSort::Name);
+        Ok(metrics)
+    }
+}
+
+// walk a scanned crate directory, leaving out nested crates; the scan already
+// left out build output
+fn collect_metrics(directory: &FsDirectory, metrics: &mut CrateMetrics) -> Result<()> {
+    for element in &directory.elements {
+        match element {
+            FsElement::File(file) => {
+                metrics.files += 1;
+                if file.path.extension().is_some_and(|e| e == "rs") {
+                    let content = fs::read(&file.path)?;
+                    metrics.rust_files += 1;
+                    metrics.source_bytes += content.len() as u64;
+                    metrics.lines_of_code += String::from_utf8_lossy(&content)
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .count();
+                }
+            }
+            FsElement::Directory(subdir) => {
+                if !subdir.path.join("Cargo.toml").is_file() {
+                    collect_metrics(subdir, metrics)?;
+                }
+            }
+            FsElement::Symlink(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_crate_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"app\"]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("core/src")).unwrap();
+        fs::create_dir_all(root.join("core/target/debug")).unwrap();
+        fs::create_dir_all(root.join("app/src")).unwrap();
+        fs::write(
+            root.join("core/Cargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("core/src/lib.rs"),
+            "pub fn one() -> u32 {\n\n    1\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("core/target/debug/build.rs"), "fn main() {}\n").unwrap();
+        fs::write(
+            root.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ncore = { path = \"../core\" }\n",
+        )
+        .unwrap();
+        fs::write(root.join("app/src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("app/README.md"), "# app\n").unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let mut metrics = graph.crate_metrics().unwrap();
+        assert_eq!(metrics.len(), 2);
+
+        let core = &metrics[1];
+        assert_eq!(core.crate_id.name, "core");
+        assert_eq!((core.files, core.rust_files), (2, 1));
+        assert_eq!(core.lines_of_code, 3);
+        assert_eq!(core.source_bytes, 31);
+        assert_eq!(core.dependents, 1);
+
+        CrateMetrics::sort(&mut metrics, CrateMetricsSort::Loc);
+        assert_eq!(metrics[0].crate_id.name, "core");
+        CrateMetrics::sort(&mut metrics, CrateMetricsSort::Files);
+        assert_eq!(metrics[0].crate_id.name, "app");
+        assert!("bogus".parse::<CrateMetricsSort>().is_err());
+    }
+}
@@ -1,16 +1,29 @@
 pub mod discovery;
+pub mod fingerprint;
+pub mod generated;
 pub mod graph;
 pub mod integration;
+pub mod metrics;
+pub mod scope;
+pub mod tree;
 pub mod types;
 
 pub use discovery::CargoDiscovery;
-pub use graph::GraphStatistics;
-pub use integration::{ChangeImpactAnalysis, VersionBumpAnalysis, VersionBumpStatus};
+pub use fingerprint::{
+    CrateFingerprint, FINGERPRINT_VERSION, FingerprintChange, FingerprintSnapshot,
+};
+pub use generated::GeneratedFiles;
+pub use graph::{GraphStatistics, WorkspaceStatistics};
+pub use integration::{ChangeImpactAnalysis, RemovedCrate, VersionBumpAnalysis, VersionBumpStatus};
+pub use metrics::{CrateMetrics, CrateMetricsSort};
+pub use scope::Scope;
+pub use tree::TreeOptions;
 pub use types::*;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::cancel::CancelToken;
     use std::fs;
     use tempfile::TempDir;
 
@@ -95,6 +108,111 @@ lib2 = { path = "../../crates/lib2" }
         assert!(crate_names.contains(&"cli"));
     }
 
+    #[test]
+    fn test_broken_manifests_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_workspace_structure(temp_dir.path()).unwrap();
+        fs::write(
+            temp_dir.path().join("crates/lib1/Cargo.toml"),
+            "[package]\nname = \"lib1\"\nversion = \n",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("templates")).unwrap();
+        fs::write(
+            temp_dir.path().join("templates/Cargo.toml"),
+            "[dependencies]\nserde = \"1\"\n",
+        )
+        .unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(temp_dir.path()).unwrap();
+        assert_eq!(graph.crates.len(), 2);
+
+        let broken = &graph.broken_manifests;
+        assert_eq!(broken.len(), 2);
+        assert!(broken[0].path.ends_with("crates/lib1/Cargo.toml"));
+        assert_eq!((broken[0].line, broken[0].column), (Some(3), Some(11)));
+        assert!(!broken[0].error.contains('\n'));
+        assert!(broken[1].path.ends_with("templates/Cargo.toml"));
+        assert_eq!(broken[1].line, None);
+    }
+
+    #[test]
+    fn test_cancelled_graph_build() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_workspace_structure(temp_dir.path()).unwrap();
+
+        let cancel = CancelToken::new();
+        let graph =
+            CrateDependencyGraph::build_from_repository_with_cancel(temp_dir.path(), &cancel)
+                .unwrap();
+        assert_eq!(graph.crates.len(), 3);
+
+        // analyses of the graph stop with the token it was built with
+        cancel.cancel();
+        assert!(matches!(
+            graph.build_file_to_crate_mapping(),
+            Err(crate::error::Error::Cancelled { .. })
+        ));
+        assert!(matches!(
+            CrateDependencyGraph::build_from_repository_with_cancel(temp_dir.path(), &cancel),
+            Err(crate::error::Error::Cancelled { .. })
+        ));
+    }
+
+    #[test]
+    fn test_crate_metadata_discovery() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_workspace_structure(temp_dir.path()).unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            r#"
+[workspace]
+members = ["crates/*", "tools/cli"]
+
+[workspace.package]
+license = "MIT"
+edition = "2021"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("crates/lib1/Cargo.toml"),
+            r#"
+[package]
+name = "lib1"
+version = "0.1.0"
+description = "first library"
+license.workspace = true
+edition.workspace = true
+rust-version = "1.70"
+publish = false
+
+[features]
+default = ["std"]
+std = []
+"#,
+        )
+        .unwrap();
+
+        let crates = CargoDiscovery::discover_all_crates(temp_dir.path()).unwrap();
+        let lib1 = crates.iter().find(|c| c.id.name == "lib1").unwrap();
+        assert_eq!(lib1.metadata.description.as_deref(), Some("first library"));
+        assert_eq!(lib1.metadata.license.as_deref(), Some("MIT"));
+        assert_eq!(lib1.metadata.edition.as_deref(), Some("2021"));
+        assert_eq!(lib1.metadata.rust_version.as_deref(), Some("1.70"));
+        assert!(!lib1.metadata.publish);
+        assert_eq!(lib1.metadata.features["default"], ["std"]);
+        assert!(lib1.metadata.features["std"].is_empty());
+
+        let lib2 = crates.iter().find(|c| c.id.name == "lib2").unwrap();
+        assert_eq!(lib2.metadata, CrateMetadata::default());
+
+        // flattened into the crate's json
+        let json = serde_json::to_value(lib1).unwrap();
+        assert_eq!(json["rust_version"], "1.70");
+        assert_eq!(json["publish"], false);
+    }
+
     #[test]
     fn test_dependency_graph_construction() {
         let temp_dir = TempDir::new().unwrap();
@@ -312,6 +430,39 @@ lib_main = { path = "../lib_main" }
             stats.total_cycles_including_dev, 1,
             "total_cycles_including_dev should be 1"
         );
+
+        // dev-dependencies join crates into a component as well
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].edges.len(), 2);
+
+        // the closing edge is declared under [dev-dependencies]
+        let chain = graph.reconstruct_cycle_chain(&all_cycles[0]);
+        let edges = graph.cycle_edges(
+            &chain,
+            &[
+                DependencyType::Normal,
+                DependencyType::Dev,
+                DependencyType::Build,
+            ],
+        );
+        let dev_edge = edges
+            .iter()
+            .find(|edge| edge.from.ends_with("::lib_derive"))
+            .unwrap();
+        assert_eq!(dev_edge.declarations[0].kind, DependencyType::Dev);
+        assert_eq!(dev_edge.declarations[0].section, "dev-dependencies");
+        assert_eq!(dev_edge.declarations[0].line, Some(9));
+        // only the kinds asked for
+        assert!(
+            graph
+                .cycle_edges(&chain, &[DependencyType::Normal])
+                .iter()
+                .all(|edge| edge
+                    .declarations
+                    .iter()
+                    .all(|d| d.kind == DependencyType::Normal))
+        );
     }
 
     #[test]
@@ -385,5 +536,101 @@ crate_a = { path = "../crate_a" }
             stats.total_cycles_including_dev, 1,
             "total_cycles_including_dev should be 1"
         );
+
+        // the component lists both edges, whatever chain the cycle report picks
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 1);
+        let component = &components[0];
+        assert_eq!(component.id, 0);
+        assert!(!component.is_trivial());
+        assert_eq!(component.members.len(), 2);
+        assert!(component.members[0].ends_with("::crate_a"));
+        assert!(component.edges[0].from.ends_with("::crate_a"));
+        assert!(component.edges[1].from.ends_with("::crate_b"));
+
+        // each edge points at the manifest line declaring it
+        let details = graph.production_cycle_details();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].len(), 2);
+        for edge in &details[0] {
+            let declaration = &edge.declarations[0];
+            assert_eq!(declaration.section, "dependencies");
+            assert_eq!(declaration.line, Some(7));
+            assert!(declaration.manifest.ends_with("Cargo.toml"));
+        }
+    }
+
+    #[test]
+    fn test_target_dependency_declarations() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("crates/app")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("crates/sys")).unwrap();
+
+        let app_toml = r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+sys = { path = "../sys" }
+
+[target.'cfg(unix)'.dependencies]
+sys = { path = "../sys", features = ["unix"] }
+
+[target.x86_64-pc-windows-msvc.dev-dependencies]
+sys = { path = "../sys" }
+"#;
+        fs::write(temp_dir.path().join("crates/app/Cargo.toml"), app_toml).unwrap();
+        let sys_toml = r#"[package]
+name = "sys"
+version = "0.1.0"
+
+[target.'cfg(windows)'.build-dependencies.app]
+path = "../app"
+"#;
+        fs::write(temp_dir.path().join("crates/sys/Cargo.toml"), sys_toml).unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(temp_dir.path()).unwrap();
+        let app = graph.find_crate_by_name("app").unwrap().id.clone();
+        let sys = graph.find_crate_by_name("sys").unwrap().id.clone();
+
+        let declared: Vec<(DependencyType, &str, Option<usize>)> = graph
+            .dependency_declarations(&app, &sys)
+            .iter()
+            .map(|d| (d.kind, d.section.as_str(), d.line))
+            .collect();
+        assert_eq!(
+            declared,
+            vec![
+                (DependencyType::Normal, "dependencies", Some(6)),
+                (
+                    DependencyType::Normal,
+                    "target.'cfg(unix)'.dependencies",
+                    Some(9)
+                ),
+                (
+                    DependencyType::Dev,
+                    "target.x86_64-pc-windows-msvc.dev-dependencies",
+                    Some(12)
+                ),
+            ]
+        );
+        // the normal dependency repeated for unix is a single edge
+        assert_eq!(graph.graph.edge_count(), 3);
+
+        // the build-dependency under a target closes a cycle
+        let declarations = graph.dependency_declarations(&sys, &app);
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].kind, DependencyType::Build);
+        assert_eq!(
+            declarations[0].section,
+            "target.'cfg(windows)'.build-dependencies"
+        );
+        assert_eq!(declarations[0].line, Some(5));
+        assert_eq!(graph.find_cycles().len(), 1);
     }
 }
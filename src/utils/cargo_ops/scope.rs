@@ -0,0 +1,86 @@
+use crate::error::{Error, Result};
+use std::fmt;
+use std::path::Path;
+
+/// part of a monorepo an analysis is limited to, as globs relative to the
+/// repository root (`crates/networking/**`)
+///
+/// a path is in scope when it, or one of the directories above it, matches
+/// one of the patterns, so `crates/networking` covers the files below it too
+#[derive(Debug, Clone)]
+pub struct Scope {
+    patterns: Vec<glob::Pattern>,
+}
+
+impl Scope {
+    pub fn new<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                let trimmed = pattern.trim_start_matches("./").trim_matches('/');
+                glob::Pattern::new(trimmed).map_err(|e| Error::WorkspaceError {
+                    reason: format!("invalid scope {}: {}", pattern, e),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Scope { patterns })
+    }
+
+    /// check if `path`, relative to the repository root, is in scope
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.patterns
+            .iter()
+            .any(|pattern| matches_path_or_ancestor(pattern, path))
+    }
+}
+
+// glob options for paths relative to the repository root, `*` stays within
+// one directory
+pub(crate) const PATH_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+// check if `path` or one of the directories above it matches `pattern`, so a
+// pattern naming a directory covers the files below it; shared with
+// GeneratedFiles and CodeOwners
+pub(crate) fn matches_path_or_ancestor(pattern: &glob::Pattern, path: &Path) -> bool {
+    path.ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| pattern.matches_path_with(ancestor, PATH_MATCH_OPTIONS))
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let patterns: Vec<&str> = self.patterns.iter().map(glob::Pattern::as_str).collect();
+        write!(f, "{}", patterns.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_contains() {
+        let scope = Scope::new(["crates/networking/**", "./tools/cli/"]).unwrap();
+        assert!(scope.contains("crates/networking/http/Cargo.toml"));
+        assert!(scope.contains("crates/networking/http/src/lib.rs"));
+        assert!(scope.contains("tools/cli/src/main.rs"));
+        assert!(!scope.contains("crates/storage/src/lib.rs"));
+        assert!(!scope.contains("tools/cli-extra/src/main.rs"));
+        // `*` stays within one directory
+        let scope = Scope::new(["crates/*/src"]).unwrap();
+        assert!(scope.contains("crates/net/src/lib.rs"));
+        assert!(!scope.contains("crates/net/tests/it.rs"));
+
+        assert!(Scope::new(["crates/[net"]).is_err());
+    }
+}
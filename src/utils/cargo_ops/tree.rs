@@ -0,0 +1,226 @@
+use super::types::{CrateDependencyGraph, CrateId, DependencyType};
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// options for rendering a dependency tree
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    /// deepest level to descend to, the root being level 0
+    pub max_depth: Option<usize>,
+    /// show dependents instead of dependencies
+    pub invert: bool,
+    /// leave dev-dependency edges out
+    pub no_dev: bool,
+}
+
+impl TreeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    pub fn no_dev(mut self, no_dev: bool) -> Self {
+        self.no_dev = no_dev;
+        self
+    }
+}
+
+impl CrateDependencyGraph {
+    /// render the workspace-internal dependency tree below `root` the way
+    /// `cargo tree` does; crates already expanded once are folded into `(*)`
+    pub fn render_tree(&self, root: &CrateId, options: &TreeOptions) -> String {
+        let mut out = String::new();
+        let mut expanded = HashSet::new();
+        let _ = writeln!(out, "{}", self.tree_label(root));
+        expanded.insert(root.clone());
+        self.render_children(root, options, "", 1, &mut expanded, &mut out);
+        out
+    }
+
+    /// render one tree per top-level crate, sorted by name: crates nothing
+    /// depends on, or with `invert` crates that depend on nothing
+    pub fn render_forest(&self, options: &TreeOptions) -> String {
+        let upward = options.clone().invert(!options.invert);
+        let mut roots: Vec<&CrateId> = self
+            .crates
+            .keys()
+            .filter(|id| self.tree_children(id, &upward).is_empty())
+            .collect();
+        if roots.is_empty() {
+            // everything is part of a cycle, fall back to every crate
+            roots = self.crates.keys().collect();
+        }
+        roots.sort_by_key(|id| id.display_name());
+
+        roots
+            .into_iter()
+            .map(|root| self.render_tree(root, options))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // neighbours in the tree direction with the edge kind, sorted by name
+    fn tree_children(
+        &self,
+        crate_id: &CrateId,
+        options: &TreeOptions,
+    ) -> Vec<(&CrateId, DependencyType)> {
+        let Some(&index) = self.node_indices.get(crate_id) else {
+            return Vec::new();
+        };
+        let direction = if options.invert {
+            petgraph::Direction::Incoming
+        } else {
+            petgraph::Direction::Outgoing
+        };
+        let mut children: Vec<(&CrateId, DependencyType)> = self
+            .graph
+            .edges_directed(index, direction)
+            .filter(|edge| !(options.no_dev && *edge.weight() == DependencyType::Dev))
+            .map(|edge| {
+                let other = if options.invert {
+                    edge.source()
+                } else {
+                    edge.target()
+                };
+                (&self.graph[other], *edge.weight())
+            })
+            .collect();
+        children
+            .sort_by(|a, b| (a.0.display_name(), a.1 as u8).cmp(&(b.0.display_name(), b.1 as u8)));
+        children.dedup_by(|a, b| a.0 == b.0);
+        children
+    }
+
+    fn render_children(
+        &self,
+        crate_id: &CrateId,
+        options: &TreeOptions,
+        prefix: &str,
+        depth: usize,
+        expanded: &mut HashSet<CrateId>,
+        out: &mut String,
+    ) {
+        if options.max_depth.is_some_and(|max| depth > max) {
+            return;
+        }
+
+        let children = self.tree_children(crate_id, options);
+        let count = children.len();
+        for (position, (child, kind)) in children.into_iter().enumerate() {
+            let last = position + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
+            let kind = match kind {
+                DependencyType::Normal => "",
+                DependencyType::Dev => " [dev]",
+                DependencyType::Build => " [build]",
+            };
+
+            let first_visit = expanded.insert(child.clone());
+            let has_children = !self.tree_children(child, options).is_empty();
+            let folded = if !first_visit && has_children {
+                " (*)"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "{}{}{}{}{}",
+                prefix,
+                branch,
+                self.tree_label(child),
+                kind,
+                folded
+            );
+
+            if first_visit {
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                self.render_children(child, options, &child_prefix, depth + 1, expanded, out);
+            }
+        }
+    }
+
+    fn tree_label(&self, crate_id: &CrateId) -> String {
+        match self.crates.get(crate_id) {
+            Some(info) => format!(
+                "{} v{} ({})",
+                crate_id.name, info.version, crate_id.workspace
+            ),
+            None => crate_id.display_name(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::types::CrateInfo;
+    use super::*;
+    use std::path::PathBuf;
+
+    fn graph() -> CrateDependencyGraph {
+        let mut graph = CrateDependencyGraph::new();
+        let id = |name: &str| CrateId::new("ws".to_string(), name.to_string());
+        for name in ["app", "cli", "core", "util", "testkit"] {
+            graph.add_crate(CrateInfo::new(
+                id(name),
+                "0.1.0".to_string(),
+                PathBuf::from(name),
+            ));
+        }
+        graph.add_dependency(&id("app"), &id("core"), DependencyType::Normal);
+        graph.add_dependency(&id("app"), &id("util"), DependencyType::Normal);
+        graph.add_dependency(&id("util"), &id("core"), DependencyType::Normal);
+        graph.add_dependency(&id("core"), &id("testkit"), DependencyType::Dev);
+        graph.add_dependency(&id("cli"), &id("util"), DependencyType::Build);
+        graph
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let graph = graph();
+        let app = CrateId::new("ws".to_string(), "app".to_string());
+
+        assert_eq!(
+            graph.render_tree(&app, &TreeOptions::new()),
+            "app v0.1.0 (ws)\n\
+             ├── core v0.1.0 (ws)\n\
+             │   └── testkit v0.1.0 (ws) [dev]\n\
+             └── util v0.1.0 (ws)\n    \
+                 └── core v0.1.0 (ws) (*)\n"
+        );
+        assert_eq!(
+            graph.render_tree(&app, &TreeOptions::new().max_depth(Some(1)).no_dev(true)),
+            "app v0.1.0 (ws)\n├── core v0.1.0 (ws)\n└── util v0.1.0 (ws)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_inverted() {
+        let graph = graph();
+        let core = CrateId::new("ws".to_string(), "core".to_string());
+
+        assert_eq!(
+            graph.render_tree(&core, &TreeOptions::new().invert(true)),
+            "core v0.1.0 (ws)\n\
+             ├── app v0.1.0 (ws)\n\
+             └── util v0.1.0 (ws)\n    \
+                 ├── app v0.1.0 (ws)\n    \
+                 └── cli v0.1.0 (ws) [build]\n"
+        );
+
+        let forest = graph.render_forest(&TreeOptions::new());
+        assert!(forest.starts_with("app v0.1.0 (ws)\n"));
+        assert!(forest.contains("\ncli v0.1.0 (ws)\n"));
+        assert!(!forest.contains("\ncore v0.1.0 (ws)\n"));
+    }
+}
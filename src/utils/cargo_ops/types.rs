@@ -1,7 +1,9 @@
+use super::scope::Scope;
+use crate::utils::cancel::CancelToken;
 use petgraph::visit::EdgeRef;
 use petgraph::{Directed, Graph};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -21,12 +23,71 @@ impl CrateId {
     }
 }
 
+/// (de)serializes a map keyed by crate as a list of `[crate, value]` pairs,
+/// for `#[serde(with = "crate_id_map")]`; json only takes string keys
+pub mod crate_id_map {
+    use super::CrateId;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S, V>(map: &HashMap<CrateId, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        let mut entries: Vec<(&CrateId, &V)> = map.iter().collect();
+        entries.sort_by_key(|(id, _)| id.display_name());
+        serializer.collect_seq(entries)
+    }
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<HashMap<CrateId, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        Ok(Vec::<(CrateId, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// [package] metadata and features of a crate, with fields inherited from the
+/// workspace (`license.workspace = true`) resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrateMetadata {
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub rust_version: Option<String>,
+    /// false when the manifest sets `publish = false` (or an empty registry list)
+    pub publish: bool,
+    /// None when the manifest leaves it to cargo's default (2015)
+    pub edition: Option<String>,
+    /// the [features] table, feature name to what it enables
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for CrateMetadata {
+    fn default() -> Self {
+        Self {
+            description: None,
+            license: None,
+            rust_version: None,
+            publish: true,
+            edition: None,
+            features: BTreeMap::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrateInfo {
     pub id: CrateId,
     pub version: String,
     pub path: PathBuf,
     pub cargo_toml_path: PathBuf,
+    #[serde(flatten)]
+    pub metadata: CrateMetadata,
 }
 
 impl CrateInfo {
@@ -37,8 +98,14 @@ impl CrateInfo {
             version,
             path,
             cargo_toml_path,
+            metadata: CrateMetadata::default(),
         }
     }
+
+    pub fn with_metadata(mut self, metadata: CrateMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +138,81 @@ pub enum DependencyType {
     Build,
 }
 
+/// where a dependency edge is declared, to point at when reporting it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyDeclaration {
+    pub kind: DependencyType,
+    /// Cargo.toml of the dependent crate
+    pub manifest: PathBuf,
+    /// table listing the dependency: dependencies, dev-dependencies or
+    /// build-dependencies, or one of them under `target.<cfg>`
+    pub section: String,
+    /// line of the entry, 1-based; None when it could not be located
+    pub line: Option<usize>,
+}
+
+impl std::fmt::Display for DependencyDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] in {}", self.section, self.manifest.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// a Cargo.toml discovery skipped because it does not parse, or is not a
+/// usable package or workspace manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrokenManifest {
+    pub path: PathBuf,
+    /// what is wrong with it
+    pub error: String,
+    /// line of the error, 1-based; None when the parser gave no position
+    pub line: Option<usize>,
+    /// column of the error, 1-based
+    pub column: Option<usize>,
+}
+
+impl std::fmt::Display for BrokenManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+/// one edge of a dependency cycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleEdge {
+    /// display name of the dependent crate
+    pub from: String,
+    /// display name of the dependency
+    pub to: String,
+    pub declarations: Vec<DependencyDeclaration>,
+}
+
+/// a strongly connected component of the dependency graph: crates that all
+/// reach each other over some kind of dependency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StronglyConnectedComponent {
+    /// position in [`CrateDependencyGraph::strongly_connected_components`]
+    pub id: usize,
+    /// display names of the member crates, sorted
+    pub members: Vec<String>,
+    /// the dependencies between members, each with where it is declared
+    pub edges: Vec<CycleEdge>,
+}
+
+impl StronglyConnectedComponent {
+    /// a single crate that does not depend on itself, so in no cycle
+    pub fn is_trivial(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
 /// The main dependency graph structure
 pub struct CrateDependencyGraph {
     /// All discovered crates indexed by their ID
@@ -79,8 +221,18 @@ pub struct CrateDependencyGraph {
     pub graph: Graph<CrateId, DependencyType, Directed>,
     /// Mapping from CrateId to NodeIndex for efficient graph operations
     pub node_indices: HashMap<CrateId, petgraph::graph::NodeIndex>,
+    /// Where each dependency edge is declared, by dependent and dependency
+    pub declarations: HashMap<(CrateId, CrateId), Vec<DependencyDeclaration>>,
     /// Root workspaces in the repository
     pub workspaces: Vec<Workspace>,
+    /// Manifests left out of the graph because they could not be read
+    pub broken_manifests: Vec<BrokenManifest>,
+    /// Part of the repository the graph was restricted to, changed files
+    /// outside of it are left out of change analyses
+    pub scope: Option<Scope>,
+    /// Stops the graph's change, version and fingerprint analyses, and the
+    /// git operations they run, once cancelled
+    pub cancel: CancelToken,
 }
 
 impl CrateDependencyGraph {
@@ -89,7 +241,11 @@ impl CrateDependencyGraph {
             crates: HashMap::new(),
             graph: Graph::new(),
             node_indices: HashMap::new(),
+            declarations: HashMap::new(),
             workspaces: Vec::new(),
+            broken_manifests: Vec::new(),
+            scope: None,
+            cancel: CancelToken::new(),
         }
     }
 
@@ -116,6 +272,45 @@ impl CrateDependencyGraph {
         }
     }
 
+    /// Add a dependency edge between two crates along with where it is declared,
+    /// once per kind of dependency
+    pub fn add_declared_dependency(
+        &mut self,
+        from: &CrateId,
+        to: &CrateId,
+        declaration: DependencyDeclaration,
+    ) {
+        if let (Some(&from_idx), Some(&to_idx)) =
+            (self.node_indices.get(from), self.node_indices.get(to))
+        {
+            // a dependency repeated under target tables is still one edge
+            if !self
+                .graph
+                .edges_connecting(from_idx, to_idx)
+                .any(|edge| *edge.weight() == declaration.kind)
+            {
+                self.graph.add_edge(from_idx, to_idx, declaration.kind);
+            }
+            self.declarations
+                .entry((from.clone(), to.clone()))
+                .or_default()
+                .push(declaration);
+        }
+    }
+
+    /// Where the dependencies of `from` on `to` are declared, empty for edges
+    /// added without a declaration
+    pub fn dependency_declarations(
+        &self,
+        from: &CrateId,
+        to: &CrateId,
+    ) -> &[DependencyDeclaration] {
+        self.declarations
+            .get(&(from.clone(), to.clone()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
     /// Get all crates that depend on the given crate (reverse dependencies)
     pub fn get_dependents(&self, crate_id: &CrateId) -> Vec<&CrateId> {
         if let Some(&node_idx) = self.node_indices.get(crate_id) {
@@ -128,6 +323,18 @@ impl CrateDependencyGraph {
         }
     }
 
+    /// Get all crates that depend on the given crate, with the kind of each dependency
+    pub fn get_dependent_edges(&self, crate_id: &CrateId) -> Vec<(&CrateId, DependencyType)> {
+        if let Some(&node_idx) = self.node_indices.get(crate_id) {
+            self.graph
+                .edges_directed(node_idx, petgraph::Direction::Incoming)
+                .map(|edge| (&self.graph[edge.source()], *edge.weight()))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
     /// Get all crates that this crate depends on (forward dependencies)
     pub fn get_dependencies(&self, crate_id: &CrateId) -> Vec<&CrateId> {
         if let Some(&node_idx) = self.node_indices.get(crate_id) {
@@ -1,5 +1,6 @@
 // changelog configuration
 
+use crate::utils::git_ops::TagPattern;
 use serde::{Deserialize, Serialize};
 
 /// configuration for changelog verification
@@ -25,6 +26,24 @@ pub struct ChangelogConfig {
 
     /// allow missing changelogs for transitive dependencies
     pub allow_missing_for_transitive: bool,
+
+    /// release tag patterns version links (`[1.0.0]: <url>`) have to point at,
+    /// taken from `releases.tag_pattern`
+    #[serde(skip, default = "default_tag_patterns")]
+    pub tag_patterns: Vec<TagPattern>,
+
+    /// require a changelog with an initial version entry from crates added
+    /// since the base ref, taken from `new_crates.require_changelog`
+    #[serde(skip, default = "default_require_for_new_crates")]
+    pub require_for_new_crates: bool,
+}
+
+fn default_require_for_new_crates() -> bool {
+    true
+}
+
+fn default_tag_patterns() -> Vec<TagPattern> {
+    vec![TagPattern::default()]
 }
 
 impl ChangelogConfig {
@@ -68,6 +87,16 @@ impl ChangelogConfig {
         self
     }
 
+    pub fn tag_patterns(mut self, patterns: Vec<TagPattern>) -> Self {
+        self.tag_patterns = patterns;
+        self
+    }
+
+    pub fn require_for_new_crates(mut self, required: bool) -> Self {
+        self.require_for_new_crates = required;
+        self
+    }
+
     /// check if a change type is allowed
     pub fn is_allowed_change_type(&self, change_type: &str) -> bool {
         if self.allowed_change_types.is_empty() {
@@ -96,6 +125,8 @@ impl Default for ChangelogConfig {
             require_scope: false,
             check_changelog_updated: true,
             allow_missing_for_transitive: true,
+            tag_patterns: default_tag_patterns(),
+            require_for_new_crates: default_require_for_new_crates(),
         }
     }
 }
@@ -2,10 +2,14 @@
 
 pub mod config;
 pub mod parser;
+pub mod template;
 pub mod types;
 pub mod validator;
 
 pub use config::ChangelogConfig;
-pub use parser::parse_changelog;
-pub use types::{Changelog, ChangelogAnalysis, ChangelogEntry, ChangelogStatus, ChangelogVersion};
-pub use validator::{has_version_entry, validate_changelog, version_has_content};
+pub use parser::{parse_changelog, parse_changelog_content};
+pub use template::{TemplateConfig, TemplateSet};
+pub use types::{
+    Changelog, ChangelogAnalysis, ChangelogEntry, ChangelogLink, ChangelogStatus, ChangelogVersion,
+};
+pub use validator::{has_version_entry, validate_changelog, validate_links, version_has_content};
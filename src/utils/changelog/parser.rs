@@ -1,6 +1,6 @@
 // changelog parser
 
-use super::types::{Changelog, ChangelogEntry, ChangelogVersion};
+use super::types::{Changelog, ChangelogEntry, ChangelogLink, ChangelogVersion};
 use crate::error::{Error, Result};
 use crate::utils::severity::{Issue, IssueSeverity, IssueType};
 use semver::Version;
@@ -35,8 +35,13 @@ pub fn parse_changelog<P: AsRef<Path>>(path: P) -> Result<Changelog> {
         path: path.to_path_buf(),
         source: e,
     })?;
+    Ok(parse_changelog_content(path, &content))
+}
 
-    let mut changelog = Changelog::new(path.to_path_buf());
+/// parse the `content` of the changelog at `path`, e.g. as read at a git
+/// reference
+pub fn parse_changelog_content<P: AsRef<Path>>(path: P, content: &str) -> Changelog {
+    let mut changelog = Changelog::new(path.as_ref().to_path_buf());
 
     // parse line by line
     let lines: Vec<&str> = content.lines().collect();
@@ -135,6 +140,12 @@ pub fn parse_changelog<P: AsRef<Path>>(path: P) -> Result<Changelog> {
             continue;
         }
 
+        // link reference definition: [1.0.0]: https://...
+        if let Some(link) = parse_link(trimmed, line_number) {
+            changelog.links.push(link);
+            continue;
+        }
+
         // unrecognized line format (might be continuation or description)
         // be lenient and skip
     }
@@ -144,7 +155,21 @@ pub fn parse_changelog<P: AsRef<Path>>(path: P) -> Result<Changelog> {
         changelog.add_version(version_section);
     }
 
-    Ok(changelog)
+    changelog
+}
+
+/// parse a `[label]: url` link reference definition
+fn parse_link(line: &str, line_number: usize) -> Option<ChangelogLink> {
+    let (label, url) = line.strip_prefix('[')?.split_once("]:")?;
+    let url = url.trim();
+    if label.is_empty() || url.is_empty() {
+        return None;
+    }
+    Some(ChangelogLink {
+        label: label.to_string(),
+        url: url.to_string(),
+        line_number,
+    })
 }
 
 fn parse_prefix_and_description(
@@ -186,8 +211,12 @@ fn parse_prefix_and_description(
     ))
 }
 
-/// parse a single changelog entry
-fn parse_entry(text: &str, line_number: usize) -> std::result::Result<ChangelogEntry, String> {
+/// parse a single changelog entry, or a commit subject in the same
+/// `type(scope): description` form
+pub(crate) fn parse_entry(
+    text: &str,
+    line_number: usize,
+) -> std::result::Result<ChangelogEntry, String> {
     // expected format: type(scope): description
     // or: type: description
     // or: simple description (for Keep a Changelog format)
@@ -208,14 +237,21 @@ fn parse_entry(text: &str, line_number: usize) -> std::result::Result<ChangelogE
         }
     };
 
+    // conventional commits mark breaking changes as `type!:` or `type(scope)!:`
     let prefix = &text[..colon_pos];
+    let (prefix, marked_breaking) = match prefix.trim_end().strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
     let description = text[colon_pos + 1..].trim().to_string();
 
     if description.is_empty() {
         return Err("empty description".to_string());
     }
 
+    let breaking = marked_breaking || description.starts_with("BREAKING");
     parse_prefix_and_description(prefix, description, line_number)
+        .map(|entry| entry.breaking(breaking))
 }
 
 #[cfg(test)]
@@ -230,6 +266,22 @@ mod tests {
         assert_eq!(entry.description, "add new endpoint");
     }
 
+    #[test]
+    fn test_parse_entry_breaking() {
+        let entry = parse_entry("feat(api)!: drop v1 endpoints", 1).unwrap();
+        assert_eq!(entry.change_type, "feat");
+        assert_eq!(entry.scope, Some("api".to_string()));
+        assert!(entry.breaking);
+
+        assert!(parse_entry("fix!: stricter parsing", 1).unwrap().breaking);
+        assert!(
+            parse_entry("refactor: BREAKING CHANGE: renamed Config", 1)
+                .unwrap()
+                .breaking
+        );
+        assert!(!parse_entry("feat: add endpoint", 1).unwrap().breaking);
+    }
+
     #[test]
     fn test_parse_entry_without_scope() {
         let entry = parse_entry("fix: resolve bug", 1).unwrap();
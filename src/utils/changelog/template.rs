@@ -0,0 +1,293 @@
+// changelog entry and release notes templates from the [templates] table of
+// deptrack.toml, rendered with minijinja when built with the templates feature
+
+use super::types::{ChangelogEntry, ChangelogVersion};
+use crate::error::{Error, Result};
+use crate::utils::cargo_ops::CrateId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// templates for one crate, workspace or the whole repository; a template
+/// left unset falls back to the next broader one, then to the built-in format
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSet {
+    /// one changelog entry, given `type`, `scope`, `description`, `breaking`,
+    /// `crate` and `workspace`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+
+    /// release notes of one version, given `crate`, `workspace`, `version`,
+    /// `breaking` and `entries` (each with the entry fields and `text`, the
+    /// rendered entry)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<String>,
+}
+
+impl TemplateSet {
+    // fields of `self` that are set, the others from `fallback`
+    fn or(self, fallback: &TemplateSet) -> TemplateSet {
+        TemplateSet {
+            entry: self.entry.or_else(|| fallback.entry.clone()),
+            release_notes: self
+                .release_notes
+                .or_else(|| fallback.release_notes.clone()),
+        }
+    }
+
+    /// a changelog line for `entry` of `crate_id`; `* type(scope): description`
+    /// without an entry template
+    pub fn render_entry(&self, crate_id: &CrateId, entry: &ChangelogEntry) -> Result<String> {
+        let context = EntryContext::new(crate_id, entry, String::new());
+        match &self.entry {
+            Some(template) => render("entry", template, &context),
+            None => Ok(default_entry(entry)),
+        }
+    }
+
+    /// release notes of `version` of `crate_id`; a `crate version` title
+    /// followed by the entries without a release notes template
+    pub fn render_release_notes(
+        &self,
+        crate_id: &CrateId,
+        version: &ChangelogVersion,
+    ) -> Result<String> {
+        let entries = version
+            .entries
+            .iter()
+            .map(|entry| {
+                let text = self.render_entry(crate_id, entry)?;
+                Ok(EntryContext::new(crate_id, entry, text))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        match &self.release_notes {
+            Some(template) => {
+                let context = NotesContext {
+                    crate_name: &crate_id.name,
+                    workspace: &crate_id.workspace,
+                    version: version.version.to_string(),
+                    breaking: version.has_breaking_changes(),
+                    entries,
+                };
+                render("release notes", template, &context)
+            }
+            None => {
+                let mut notes = format!("{} {}\n", crate_id.name, version.version);
+                if !entries.is_empty() {
+                    notes.push('\n');
+                }
+                for entry in &entries {
+                    notes.push_str(&entry.text);
+                    notes.push('\n');
+                }
+                Ok(notes)
+            }
+        }
+    }
+}
+
+/// the [templates] table: repository-wide templates plus overrides under
+/// `[templates.workspaces.<name>]` and `[templates.crates.<name>]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateConfig {
+    #[serde(flatten)]
+    pub defaults: TemplateSet,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, TemplateSet>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub crates: BTreeMap<String, TemplateSet>,
+}
+
+impl TemplateConfig {
+    /// templates of `crate_id`: its own, then its workspace's, then the
+    /// repository-wide ones
+    pub fn for_crate(&self, crate_id: &CrateId) -> TemplateSet {
+        let workspace = self
+            .workspaces
+            .get(&crate_id.workspace)
+            .cloned()
+            .unwrap_or_default()
+            .or(&self.defaults);
+        self.crates
+            .get(&crate_id.name)
+            .cloned()
+            .unwrap_or_default()
+            .or(&workspace)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EntryContext<'a> {
+    #[serde(rename = "type")]
+    change_type: &'a str,
+    scope: Option<&'a str>,
+    description: &'a str,
+    breaking: bool,
+    #[serde(rename = "crate")]
+    crate_name: &'a str,
+    workspace: &'a str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    text: String,
+}
+
+impl<'a> EntryContext<'a> {
+    fn new(crate_id: &'a CrateId, entry: &'a ChangelogEntry, text: String) -> Self {
+        Self {
+            change_type: &entry.change_type,
+            scope: entry.scope.as_deref(),
+            description: &entry.description,
+            breaking: entry.breaking,
+            crate_name: &crate_id.name,
+            workspace: &crate_id.workspace,
+            text,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NotesContext<'a> {
+    #[serde(rename = "crate")]
+    crate_name: &'a str,
+    workspace: &'a str,
+    version: String,
+    breaking: bool,
+    entries: Vec<EntryContext<'a>>,
+}
+
+// `* type(scope)!: description`, the format the changelog parser reads
+fn default_entry(entry: &ChangelogEntry) -> String {
+    let scope = entry
+        .scope
+        .as_ref()
+        .map(|scope| format!("({})", scope))
+        .unwrap_or_default();
+    let marker = if entry.breaking && !entry.description.starts_with("BREAKING") {
+        "!"
+    } else {
+        ""
+    };
+    format!(
+        "* {}{}{}: {}",
+        entry.change_type, scope, marker, entry.description
+    )
+}
+
+#[cfg(feature = "templates")]
+fn render<S: Serialize>(kind: &str, template: &str, context: &S) -> Result<String> {
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.render_str(template, context)
+        .map_err(|e| Error::ChangelogError {
+            reason: format!("{} template: {}", kind, e),
+        })
+}
+
+#[cfg(not(feature = "templates"))]
+fn render<S: Serialize>(kind: &str, _template: &str, _context: &S) -> Result<String> {
+    Err(Error::ChangelogError {
+        reason: format!(
+            "{} template configured, but deptrack was built without the templates feature",
+            kind
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    fn crate_id() -> CrateId {
+        CrateId::new("ws".to_string(), "core".to_string())
+    }
+
+    fn version() -> ChangelogVersion {
+        let mut version = ChangelogVersion::new(Version::new(0, 2, 0), 3);
+        version.add_entry(ChangelogEntry::new(
+            "feat".to_string(),
+            Some("io".to_string()),
+            "async reads".to_string(),
+            5,
+        ));
+        version.add_entry(
+            ChangelogEntry::new("fix".to_string(), None, "drop v1".to_string(), 6).breaking(true),
+        );
+        version
+    }
+
+    #[test]
+    fn test_template_lookup() {
+        let config: TemplateConfig = toml::from_str(
+            "entry = \"- {{ description }}\"\n\
+             release_notes = \"{{ crate }}\"\n\
+             [workspaces.ws]\nentry = \"ws\"\n\
+             [crates.core]\nrelease_notes = \"core\"\n",
+        )
+        .unwrap();
+        let core = config.for_crate(&crate_id());
+        assert_eq!(core.entry.as_deref(), Some("ws"));
+        assert_eq!(core.release_notes.as_deref(), Some("core"));
+        let other = config.for_crate(&CrateId::new("tools".to_string(), "cli".to_string()));
+        assert_eq!(other.entry.as_deref(), Some("- {{ description }}"));
+        assert_eq!(other.release_notes.as_deref(), Some("{{ crate }}"));
+    }
+
+    #[test]
+    fn test_default_rendering() {
+        let notes = TemplateSet::default()
+            .render_release_notes(&crate_id(), &version())
+            .unwrap();
+        assert_eq!(
+            notes,
+            "core 0.2.0\n\n* feat(io): async reads\n* fix!: drop v1\n"
+        );
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn test_template_rendering() {
+        let templates = TemplateSet {
+            entry: Some(
+                "- {% if scope %}**{{ scope }}**: {% endif %}{{ description }}".to_string(),
+            ),
+            release_notes: Some(
+                "# {{ crate }} v{{ version }}{% if breaking %} (breaking){% endif %}\n\n\
+                 {% for entry in entries %}\n{{ entry.text }}\n{% endfor %}"
+                    .to_string(),
+            ),
+        };
+        let notes = templates
+            .render_release_notes(&crate_id(), &version())
+            .unwrap();
+        assert_eq!(
+            notes,
+            "# core v0.2.0 (breaking)\n- **io**: async reads\n- drop v1\n"
+        );
+
+        let broken = TemplateSet {
+            entry: Some("{% if %}".to_string()),
+            release_notes: None,
+        };
+        assert!(
+            broken
+                .render_entry(&crate_id(), &version().entries[0])
+                .is_err()
+        );
+    }
+
+    #[cfg(not(feature = "templates"))]
+    #[test]
+    fn test_template_needs_feature() {
+        let templates = TemplateSet {
+            entry: Some("{{ description }}".to_string()),
+            release_notes: None,
+        };
+        assert!(
+            templates
+                .render_entry(&crate_id(), &version().entries[0])
+                .is_err()
+        );
+    }
+}
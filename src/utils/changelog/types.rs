@@ -14,6 +14,9 @@ pub struct ChangelogEntry {
     pub scope: Option<String>, // crate name or component
     pub description: String,
     pub line_number: usize,
+    /// marked as breaking with `type!:` or a description starting with "BREAKING"
+    #[serde(default)]
+    pub breaking: bool,
 }
 
 impl ChangelogEntry {
@@ -28,8 +31,14 @@ impl ChangelogEntry {
             scope,
             description,
             line_number,
+            breaking: false,
         }
     }
+
+    pub fn breaking(mut self, breaking: bool) -> Self {
+        self.breaking = breaking;
+        self
+    }
 }
 
 /// represents a version section in the changelog
@@ -52,6 +61,19 @@ impl ChangelogVersion {
     pub fn add_entry(&mut self, entry: ChangelogEntry) {
         self.entries.push(entry);
     }
+
+    /// whether any entry is marked as breaking
+    pub fn has_breaking_changes(&self) -> bool {
+        self.entries.iter().any(|entry| entry.breaking)
+    }
+}
+
+/// link reference definition, e.g. `[1.0.0]: https://host/compare/v0.9.0...v1.0.0`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogLink {
+    pub label: String,
+    pub url: String,
+    pub line_number: usize,
 }
 
 /// represents a complete changelog file
@@ -61,6 +83,8 @@ pub struct Changelog {
     pub versions: HashMap<Version, ChangelogVersion>,
     pub has_header: bool,
     pub format_issues: Vec<Issue>,
+    #[serde(default)]
+    pub links: Vec<ChangelogLink>,
 }
 
 impl Changelog {
@@ -70,6 +94,7 @@ impl Changelog {
             versions: HashMap::new(),
             has_header: false,
             format_issues: Vec::new(),
+            links: Vec::new(),
         }
     }
 
@@ -2,6 +2,7 @@
 
 use super::config::ChangelogConfig;
 use super::types::{Changelog, ChangelogEntry};
+use crate::utils::git_ops::{TagPattern, release_tag_name};
 use semver::Version;
 
 /// validate a changelog against configuration rules
@@ -60,6 +61,34 @@ fn validate_entry(entry: &ChangelogEntry, config: &ChangelogConfig) -> Option<St
     None
 }
 
+/// check that version links (`[1.0.0]: <url>`) point at the release tag of
+/// that version of `crate_name` under one of `patterns`; links with other
+/// labels, like `[Unreleased]`, are not checked
+pub fn validate_links(
+    changelog: &Changelog,
+    crate_name: &str,
+    patterns: &[TagPattern],
+) -> Vec<String> {
+    changelog
+        .links
+        .iter()
+        .filter_map(|link| {
+            let version = Version::parse(link.label.trim_start_matches('v')).ok()?;
+            let referenced = patterns
+                .iter()
+                .any(|pattern| pattern.is_referenced_by(&link.url, crate_name, &version));
+            (!referenced).then(|| {
+                format!(
+                    "line {}: link for version {} does not point at tag {}",
+                    link.line_number,
+                    version,
+                    release_tag_name(patterns, crate_name, &version)
+                )
+            })
+        })
+        .collect()
+}
+
 /// check if a version has a changelog entry
 pub fn has_version_entry(changelog: &Changelog, version: &Version) -> bool {
     changelog.has_version(version)
@@ -105,6 +134,35 @@ mod tests {
         assert!(validate_entry(&entry, &config).is_some());
     }
 
+    #[test]
+    fn test_validate_links() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            "# CHANGELOG\n\n## [1.1.0]\n* feat: b\n\n## [1.0.0]\n* feat: a\n\n\
+             [Unreleased]: https://git.example.com/r/compare/core-v1.1.0...HEAD\n\
+             [1.1.0]: https://git.example.com/r/compare/core_1.0.0...core-v1.1.0\n\
+             [1.0.0]: https://git.example.com/r/releases/tag/v1.0.0\n",
+        )
+        .unwrap();
+        let changelog = crate::utils::changelog::parse_changelog(&path).unwrap();
+        assert_eq!(changelog.links.len(), 3);
+
+        let patterns = vec![TagPattern::default()];
+        assert_eq!(
+            validate_links(&changelog, "core", &patterns),
+            vec!["line 11: link for version 1.0.0 does not point at tag core-v1.0.0"]
+        );
+
+        // crates that used to be tagged as plain v<version>
+        let migrating = vec![
+            TagPattern::default(),
+            TagPattern::new("v{version}").unwrap(),
+        ];
+        assert!(validate_links(&changelog, "core", &migrating).is_empty());
+    }
+
     #[test]
     fn test_validate_entry_missing_scope() {
         let config = ChangelogConfig::default().require_scope(true);
@@ -1,14 +1,16 @@
 use super::cargo_ops::integration::{ChangeImpactAnalysis, VersionBumpAnalysis};
-use super::cargo_ops::types::CrateDependencyGraph;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
 use crate::error::Result;
 use crate::utils::changelog::{
     ChangelogAnalysis, ChangelogConfig, ChangelogStatus, has_version_entry, parse_changelog,
-    validate_changelog,
+    validate_changelog, validate_links,
 };
 use crate::utils::severity::{Issue, IssueSeverity, IssueType};
 use crate::utils::severity_config::SeverityConfig;
+use crate::utils::table::{Align, Cell, Color, Table, severity_color};
+use crate::utils::toml_ops::TomlReader;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const MIN_CRATE_NAME_WIDTH: usize = 10;
 
@@ -37,31 +39,12 @@ impl ChangelogAnalysis {
         println!("  compliance: {:.1}%", self.compliance_percentage());
         println!();
 
-        // calculate column widths
-        let name_width = self
-            .statuses
-            .keys()
-            .map(|id| id.display_name().len())
-            .max()
-            .unwrap_or(MIN_CRATE_NAME_WIDTH)
-            .max(MIN_CRATE_NAME_WIDTH);
-
-        // print table header
-        println!(
-            "  {:<name_width$}  {:>6}  {:>6}  {:>7}  {:>13}",
-            "Crate",
-            "Exists",
-            "Format",
-            "Updated",
-            "Version Entry",
-            name_width = name_width
-        );
-
-        // print separator line
-        println!(
-            "  {}  ------  ------  -------  -------------",
-            "-".repeat(name_width)
-        );
+        let mut table = Table::new(["Crate", "Exists", "Format", "Updated", "Version Entry"])
+            .min_width(0, MIN_CRATE_NAME_WIDTH)
+            .align(1, Align::Right)
+            .align(2, Align::Right)
+            .align(3, Align::Right)
+            .align(4, Align::Right);
 
         // collect and sort by status (issues first)
         let mut entries: Vec<_> = self.statuses.values().collect();
@@ -70,20 +53,28 @@ impl ChangelogAnalysis {
             (!has_issues, s.is_complete(), s.crate_id.display_name())
         });
 
-        // print each crate
         for status in entries {
             let (exists, format, updated, version) = status.get_display_status();
+            let failed = if status.has_errors() {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+            let check = |text: &str| match text {
+                "OK" => Cell::new(text).color(Color::Green),
+                "N/A" => Cell::new(text).color(Color::Dim),
+                _ => Cell::new(text).color(failed),
+            };
 
-            println!(
-                "  {:<name_width$}  {:>6}  {:>6}  {:>7}  {:>13}",
-                status.crate_id.display_name(),
-                exists,
-                format,
-                updated,
-                version,
-                name_width = name_width
-            );
+            table.row([
+                Cell::new(status.crate_id.display_name()),
+                check(exists),
+                check(format),
+                check(updated),
+                check(version),
+            ]);
         }
+        table.print();
 
         println!();
     }
@@ -94,24 +85,14 @@ impl ChangelogAnalysis {
     ) {
         println!("{}:", title);
 
-        let name_width = issues
-            .iter()
-            .map(|(s, _)| s.crate_id.display_name().len())
-            .max()
-            .unwrap_or(MIN_CRATE_NAME_WIDTH)
-            .max(MIN_CRATE_NAME_WIDTH);
-
-        println!("  {:<name_width$}  Issue", "Crate", name_width = name_width);
-        println!("  {}  -----", "-".repeat(name_width));
-
+        let mut table = Table::new(["Crate", "Issue"]).min_width(0, MIN_CRATE_NAME_WIDTH);
         for (status, issue) in issues {
-            println!(
-                "  {:<name_width$}  {}",
-                status.crate_id.display_name(),
-                issue.message,
-                name_width = name_width
-            );
+            table.row([
+                Cell::new(status.crate_id.display_name()),
+                Cell::new(issue.message.clone()).color(severity_color(issue.severity)),
+            ]);
         }
+        table.print();
         println!();
     }
 
@@ -195,8 +176,18 @@ struct IssueCounters<'a> {
 pub struct ChangelogChecker;
 
 impl ChangelogChecker {
+    /// changelog location of a crate, `changelog` in [package.metadata.deptrack]
+    /// takes precedence over the configured file name
+    pub fn changelog_path(crate_path: &Path, config: &ChangelogConfig) -> PathBuf {
+        let file_name = TomlReader::read_crate_metadata(crate_path)
+            .ok()
+            .and_then(|metadata| metadata.changelog)
+            .unwrap_or_else(|| config.changelog_file_name.clone());
+        crate_path.join(file_name)
+    }
+
     fn process_changelog(
-        has_changelog: bool,
+        crate_id: &CrateId,
         changelog_path: &Path,
         config: &ChangelogConfig,
         current_version: &semver::Version,
@@ -204,7 +195,7 @@ impl ChangelogChecker {
         counters: &mut IssueCounters,
         check_git_update: Option<(&Path, &[std::path::PathBuf], bool)>,
     ) -> ChangelogProcessResult {
-        if !has_changelog {
+        if !changelog_path.exists() {
             if config.require_changelog {
                 let msg = format!("missing {} file", config.changelog_file_name);
                 Self::add_issue(
@@ -247,7 +238,12 @@ impl ChangelogChecker {
             }
         };
 
-        let validation_messages = validate_changelog(&changelog, config);
+        let mut validation_messages = validate_changelog(&changelog, config);
+        validation_messages.extend(validate_links(
+            &changelog,
+            &crate_id.name,
+            &config.tag_patterns,
+        ));
         let format_valid = validation_messages.is_empty();
 
         if !format_valid {
@@ -377,6 +373,11 @@ impl ChangelogChecker {
         let mut total_issues = 0;
         let mut total_errors = 0;
         let mut total_warnings = 0;
+        // a new crate starts its changelog with the initial version
+        let new_crate_config = ChangelogConfig {
+            require_changelog: config.require_changelog || config.require_for_new_crates,
+            ..config.clone()
+        };
 
         for (crate_id, version_status) in &version_analysis.crate_versions {
             let crate_info = match graph.crates.get(crate_id) {
@@ -384,7 +385,7 @@ impl ChangelogChecker {
                 None => continue,
             };
 
-            let changelog_path = crate_info.path.join(&config.changelog_file_name);
+            let changelog_path = Self::changelog_path(&crate_info.path, config);
             let has_changelog = changelog_path.exists();
             let is_directly_changed = impact_analysis.directly_affected_crates.contains(crate_id);
 
@@ -407,9 +408,13 @@ impl ChangelogChecker {
             };
 
             let result = Self::process_changelog(
-                has_changelog,
+                crate_id,
                 &changelog_path,
-                config,
+                if version_status.is_new {
+                    &new_crate_config
+                } else {
+                    config
+                },
                 &version_status.current_version,
                 severity_config,
                 &mut counters,
@@ -489,7 +494,7 @@ impl ChangelogChecker {
             let crate_id = &crate_info.id;
 
             // construct changelog path (crate_info.path is already the crate root)
-            let changelog_path = crate_info.path.join(&config.changelog_file_name);
+            let changelog_path = Self::changelog_path(&crate_info.path, config);
             let has_changelog = changelog_path.exists();
 
             let mut issues = Vec::new();
@@ -508,7 +513,7 @@ impl ChangelogChecker {
             match semver::Version::parse(&crate_info.version) {
                 Ok(current_version) => {
                     let result = Self::process_changelog(
-                        has_changelog,
+                        crate_id,
                         &changelog_path,
                         config,
                         &current_version,
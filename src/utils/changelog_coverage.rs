@@ -0,0 +1,266 @@
+// how well the changelog entries added between two references describe the
+// commits that landed on each crate in between
+
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::changelog::parser::parse_entry;
+use super::changelog::{Changelog, ChangelogConfig, ChangelogEntry, parse_changelog_content};
+use super::changelog_checker::ChangelogChecker;
+use super::git_ops::{GitOps, GitRef};
+use super::table::{Align, Cell, Color, Table};
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+/// commits on a crate from which a changelog grown by at most one generic
+/// entry is flagged
+pub const DEFAULT_MIN_COMMITS: usize = 5;
+
+// descriptions that say nothing about what changed
+const GENERIC_DESCRIPTIONS: &[&str] = &[
+    "bug fixes",
+    "bugfixes",
+    "changes",
+    "cleanup",
+    "fixes",
+    "improvements",
+    "maintenance",
+    "minor changes",
+    "minor fixes",
+    "minor improvements",
+    "misc",
+    "miscellaneous",
+    "small fixes",
+    "update",
+    "update dependencies",
+    "updates",
+    "various changes",
+    "various fixes",
+    "various improvements",
+    "wip",
+];
+
+/// changelog coverage of one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateCoverage {
+    pub crate_id: CrateId,
+    /// commits touching the crate, those touching only its changelog left out
+    pub commits: usize,
+    /// entries added to the changelog between the two references
+    pub new_entries: usize,
+    /// new entries per commit as a percentage, at most 100
+    pub coverage: f64,
+    /// scopes of `type(scope): ...` commit subjects no new entry mentions
+    pub missing_scopes: Vec<String>,
+    /// at least `min_commits` commits, but the changelog grew by at most one
+    /// generic entry
+    pub flagged: bool,
+}
+
+/// changelog coverage of the crates changed between two references
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogCoverage {
+    pub min_commits: usize,
+    /// crates with commits in the range, sorted by name
+    pub crates: Vec<CrateCoverage>,
+}
+
+impl ChangelogCoverage {
+    /// compare the commits between `from` and `to` touching each of `crates`
+    /// with the entries its changelog gained in the same range
+    pub fn analyze<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        from: &str,
+        to: &str,
+        crates: &[CrateId],
+        config: &ChangelogConfig,
+        min_commits: usize,
+    ) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let (from_ref, to_ref) = (GitRef::from_string(from), GitRef::from_string(to));
+        let mut coverage = Vec::new();
+
+        for crate_id in crates {
+            let Some(info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let changelog_path = ChangelogChecker::changelog_path(&info.path, config);
+            let commits = GitOps::commits_in_range_excluding(
+                repo_path,
+                from,
+                to,
+                &info.path,
+                &changelog_path,
+            )?;
+            if commits.is_empty() {
+                continue;
+            }
+
+            let read = |git_ref: &GitRef| -> Result<Option<Changelog>> {
+                Ok(
+                    GitOps::read_file_at_ref(repo_path, git_ref, &changelog_path)?
+                        .map(|content| parse_changelog_content(&changelog_path, &content)),
+                )
+            };
+            let old = read(&from_ref)?;
+            let new = read(&to_ref)?;
+            let added = new
+                .as_ref()
+                .map(|new| new_entries(old.as_ref(), new))
+                .unwrap_or_default();
+
+            // a scope naming the crate itself is covered by any entry
+            let entry_scopes: HashSet<&str> =
+                added.iter().filter_map(|e| e.scope.as_deref()).collect();
+            let missing_scopes: BTreeSet<String> = commits
+                .iter()
+                .filter_map(|commit| parse_entry(&commit.subject, 0).ok()?.scope)
+                .filter(|scope| {
+                    !entry_scopes.contains(scope.as_str())
+                        && (added.is_empty() || *scope != crate_id.name)
+                })
+                .collect();
+
+            let flagged = commits.len() >= min_commits
+                && match added.as_slice() {
+                    [] => true,
+                    [entry] => is_generic(entry),
+                    _ => false,
+                };
+            coverage.push(CrateCoverage {
+                crate_id: crate_id.clone(),
+                commits: commits.len(),
+                new_entries: added.len(),
+                coverage: percentage(added.len(), commits.len()),
+                missing_scopes: missing_scopes.into_iter().collect(),
+                flagged,
+            });
+        }
+
+        coverage.sort_by_key(|c| c.crate_id.display_name());
+        Ok(Self {
+            min_commits,
+            crates: coverage,
+        })
+    }
+
+    /// new entries per commit over all crates, as a percentage
+    pub fn overall_coverage(&self) -> f64 {
+        let covered = self.crates.iter().map(|c| c.new_entries.min(c.commits));
+        let commits = self.crates.iter().map(|c| c.commits).sum();
+        percentage(covered.sum(), commits)
+    }
+
+    pub fn flagged(&self) -> impl Iterator<Item = &CrateCoverage> {
+        self.crates.iter().filter(|c| c.flagged)
+    }
+
+    pub fn display(&self) {
+        if self.crates.is_empty() {
+            println!("no commits touching workspace crates");
+            return;
+        }
+
+        let mut table = Table::new([
+            "Crate",
+            "Commits",
+            "New Entries",
+            "Coverage",
+            "Missing Scopes",
+        ])
+        .align(1, Align::Right)
+        .align(2, Align::Right)
+        .align(3, Align::Right);
+        for crate_coverage in &self.crates {
+            let mut name = Cell::new(crate_coverage.crate_id.display_name());
+            if crate_coverage.flagged {
+                name = name.color(Color::Yellow);
+            }
+            table.row([
+                name,
+                Cell::new(crate_coverage.commits.to_string()),
+                Cell::new(crate_coverage.new_entries.to_string()),
+                Cell::new(format!("{:.0}%", crate_coverage.coverage)),
+                Cell::new(crate_coverage.missing_scopes.join(", ")),
+            ]);
+        }
+        table.print();
+
+        println!();
+        println!("overall coverage: {:.0}%", self.overall_coverage());
+        let flagged: Vec<String> = self.flagged().map(|c| c.crate_id.display_name()).collect();
+        if !flagged.is_empty() {
+            println!(
+                "{} or more commits but at most one generic changelog entry: {}",
+                self.min_commits,
+                flagged.join(", ")
+            );
+        }
+    }
+}
+
+/// entries of `new` that `old` does not have under the same version, newest
+/// version first
+fn new_entries<'a>(old: Option<&Changelog>, new: &'a Changelog) -> Vec<&'a ChangelogEntry> {
+    let existing: HashSet<_> = old
+        .into_iter()
+        .flat_map(|old| old.versions.values())
+        .flat_map(|version| version.entries.iter().map(move |e| (&version.version, e)))
+        .map(|(version, e)| (version, &e.change_type, &e.scope, &e.description))
+        .collect();
+    let mut versions: Vec<_> = new.versions.values().collect();
+    versions.sort_by(|a, b| b.version.cmp(&a.version));
+    versions
+        .into_iter()
+        .flat_map(|version| version.entries.iter().map(move |e| (&version.version, e)))
+        .filter(|(version, e)| {
+            !existing.contains(&(*version, &e.change_type, &e.scope, &e.description))
+        })
+        .map(|(_, e)| e)
+        .collect()
+}
+
+/// check if an entry is too vague to describe several commits: a stock
+/// phrase like "bug fixes", or a single word
+fn is_generic(entry: &ChangelogEntry) -> bool {
+    let description = entry
+        .description
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_lowercase();
+    GENERIC_DESCRIPTIONS.contains(&description.as_str())
+        || description.split_whitespace().count() <= 1
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    part.min(total) as f64 / total as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changelog(content: &str) -> Changelog {
+        parse_changelog_content("CHANGELOG.md", content)
+    }
+
+    #[test]
+    fn test_new_entries() {
+        let old = changelog("# CHANGELOG\n\n## [0.1.0]\n\n* feat: initial release\n");
+        let new = changelog(
+            "# CHANGELOG\n\n## [0.2.0]\n\n* fix(io): retry reads\n* fix: bug fixes\n\n\
+             ## [0.1.0]\n\n* feat: initial release\n",
+        );
+        let added = new_entries(Some(&old), &new);
+        assert_eq!(added.len(), 2);
+        assert_eq!(added[0].scope.as_deref(), Some("io"));
+        assert!(!is_generic(added[0]));
+        assert!(is_generic(added[1]));
+        assert_eq!(new_entries(None, &new).len(), 3);
+        assert!(new_entries(Some(&new), &new).is_empty());
+    }
+}
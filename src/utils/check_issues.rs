@@ -0,0 +1,106 @@
+// counting and human output shared by the checkers whose analyses keep a
+// list of issues per crate: manifests, membership, features, msrv, lockfiles
+// and external checks
+
+use super::cargo_ops::types::CrateId;
+use crate::utils::severity::{Issue, IssueSeverity, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use crate::utils::table::{Cell, Table, severity_color};
+
+/// narrowest the crate column of an issue table gets
+const MIN_CRATE_NAME_WIDTH: usize = 10;
+
+/// an analysis counting the issues it records in error and warning totals
+pub trait IssueTotals {
+    /// the error and warning totals
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize);
+
+    /// count one issue of `severity`
+    fn count(&mut self, severity: IssueSeverity) {
+        let (errors, warnings) = self.totals_mut();
+        match severity {
+            IssueSeverity::Error => *errors += 1,
+            IssueSeverity::Warning => *warnings += 1,
+        }
+    }
+
+    /// issue of `issue_type` at the severity `severity_config` gives it,
+    /// counted in the totals
+    fn counted_issue(
+        &mut self,
+        severity_config: &SeverityConfig,
+        issue_type: IssueType,
+        message: String,
+    ) -> Issue {
+        let severity = severity_config.get_severity(issue_type);
+        self.count(severity);
+        Issue::new(severity, issue_type, message)
+    }
+}
+
+/// print the issues as a `<check> errors` and a `<check> warnings` table,
+/// sorted by crate; a table without rows is left out
+pub fn display_issue_tables<'a, I>(check: &str, issues: I)
+where
+    I: IntoIterator<Item = (&'a CrateId, &'a Issue)>,
+{
+    let (mut errors, mut warnings): (Vec<_>, Vec<_>) = issues
+        .into_iter()
+        .map(|(crate_id, issue)| (crate_id.display_name(), issue))
+        .partition(|(_, issue)| issue.severity == IssueSeverity::Error);
+
+    for (kind, issues) in [("errors", &mut errors), ("warnings", &mut warnings)] {
+        if issues.is_empty() {
+            continue;
+        }
+        issues.sort_by(|(a, _), (b, _)| a.cmp(b));
+        println!("{} {}:", check, kind);
+
+        let mut table = Table::new(["Crate", "Issue"]).min_width(0, MIN_CRATE_NAME_WIDTH);
+        for (name, issue) in issues.iter() {
+            table.row([
+                Cell::new(name.clone()),
+                Cell::new(issue.message.clone()).color(severity_color(issue.severity)),
+            ]);
+        }
+        table.print();
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Totals {
+        errors: usize,
+        warnings: usize,
+    }
+
+    impl IssueTotals for Totals {
+        fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+            (&mut self.errors, &mut self.warnings)
+        }
+    }
+
+    #[test]
+    fn test_counted_issue() {
+        let mut totals = Totals::default();
+        let config = SeverityConfig::default();
+        let issue = totals.counted_issue(&config, IssueType::AnalysisFailed, "x".to_string());
+        let other = totals.counted_issue(&config, IssueType::BrokenManifest, "y".to_string());
+
+        assert_eq!(
+            issue.severity,
+            config.get_severity(IssueType::AnalysisFailed)
+        );
+        assert_eq!(issue.message, "x");
+        assert_eq!(other.issue_type, IssueType::BrokenManifest);
+        let errors = [issue.severity, other.severity]
+            .iter()
+            .filter(|s| **s == IssueSeverity::Error)
+            .count();
+        assert_eq!((totals.errors, totals.warnings), (errors, 2 - errors));
+    }
+}
@@ -0,0 +1,239 @@
+use super::cargo_ops::types::CrateDependencyGraph;
+use super::test_plan::{TestPlan, relative_to};
+use serde::Serialize;
+use std::fmt;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// CI system the matrix is rendered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiProvider {
+    /// GitHub Actions `strategy.matrix` json, for `fromJSON()`
+    Github,
+    /// GitLab child pipeline yaml
+    Gitlab,
+}
+
+impl std::str::FromStr for CiProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(CiProvider::Github),
+            "gitlab" => Ok(CiProvider::Gitlab),
+            _ => Err(format!("invalid provider: {}, use 'github' or 'gitlab'", s)),
+        }
+    }
+}
+
+/// job suggested for an affected crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiJob {
+    Check,
+    Clippy,
+    Test,
+}
+
+impl fmt::Display for CiJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiJob::Check => write!(f, "check"),
+            CiJob::Clippy => write!(f, "clippy"),
+            CiJob::Test => write!(f, "test"),
+        }
+    }
+}
+
+/// one affected crate in the matrix
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixEntry {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub workspace: String,
+    /// crate directory relative to the repository
+    pub path: PathBuf,
+    /// manifest to pass to `--manifest-path`, None for a workspace at the root
+    pub manifest_path: Option<PathBuf>,
+    pub directly_affected: bool,
+    pub jobs: Vec<CiJob>,
+}
+
+impl MatrixEntry {
+    /// cargo invocation for one of the entry's jobs
+    pub fn command(&self, job: CiJob) -> String {
+        let mut command = format!("cargo {}", job);
+        if let Some(manifest_path) = &self.manifest_path {
+            let _ = write!(command, " --manifest-path {}", manifest_path.display());
+        }
+        let _ = write!(command, " -p {}", self.crate_name);
+        if job == CiJob::Clippy {
+            command.push_str(" -- -D warnings");
+        }
+        command
+    }
+}
+
+/// affected crates with their suggested CI jobs
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CiMatrix {
+    pub entries: Vec<MatrixEntry>,
+}
+
+impl CiMatrix {
+    /// matrix for every crate in a test plan: check and clippy always, test
+    /// when the crate has test targets
+    pub fn from_plan<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        plan: &TestPlan,
+        repo_root: P,
+    ) -> Self {
+        let repo_root = repo_root.as_ref();
+        let entries = plan
+            .crates
+            .iter()
+            .filter_map(|crate_plan| {
+                let crate_info = graph.crates.get(&crate_plan.crate_id)?;
+                let manifest_path = graph
+                    .workspaces
+                    .iter()
+                    .find(|w| w.name == crate_plan.crate_id.workspace)
+                    .map(|w| relative_to(&w.cargo_toml_path, repo_root))
+                    .filter(|path| path != Path::new("Cargo.toml"));
+
+                let mut jobs = vec![CiJob::Check, CiJob::Clippy];
+                if !crate_plan.targets.is_empty() {
+                    jobs.push(CiJob::Test);
+                }
+                Some(MatrixEntry {
+                    crate_name: crate_plan.crate_id.name.clone(),
+                    workspace: crate_plan.crate_id.workspace.clone(),
+                    path: relative_to(&crate_info.path, repo_root),
+                    manifest_path,
+                    directly_affected: crate_plan.directly_affected,
+                    jobs,
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// check if no crate is affected
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the matrix rendered for `provider`
+    pub fn render(&self, provider: CiProvider) -> String {
+        match provider {
+            CiProvider::Github => {
+                serde_json::to_string(&self.to_github()).unwrap_or_else(|_| "{}".to_string())
+            }
+            CiProvider::Gitlab => self.to_gitlab(),
+        }
+    }
+
+    /// `{"include": [...]}`, one entry per crate
+    pub fn to_github(&self) -> serde_json::Value {
+        serde_json::json!({ "include": self.entries })
+    }
+
+    /// child pipeline with one job per crate and suggested job, or a single
+    /// no-op job when nothing is affected (gitlab rejects empty pipelines)
+    pub fn to_gitlab(&self) -> String {
+        let mut yaml = String::from("# generated by deptrack ci-matrix\n");
+        if self.entries.is_empty() {
+            yaml.push_str("\nno-affected-crates:\n  script:\n    - echo \"no affected crates\"\n");
+            return yaml;
+        }
+
+        let mut stages: Vec<CiJob> = self.entries.iter().flat_map(|e| e.jobs.clone()).collect();
+        stages.sort_by_key(|job| *job as u8);
+        stages.dedup();
+        yaml.push_str("\nstages:\n");
+        for stage in &stages {
+            let _ = writeln!(yaml, "  - {}", stage);
+        }
+
+        for entry in &self.entries {
+            for job in &entry.jobs {
+                let _ = writeln!(yaml);
+                let _ = writeln!(yaml, "{}:", quote(&format!("{}:{}", job, entry.crate_name)));
+                let _ = writeln!(yaml, "  stage: {}", job);
+                let _ = writeln!(yaml, "  variables:");
+                let _ = writeln!(yaml, "    CRATE: {}", quote(&entry.crate_name));
+                let _ = writeln!(
+                    yaml,
+                    "    CRATE_PATH: {}",
+                    quote(&entry.path.to_string_lossy())
+                );
+                let _ = writeln!(yaml, "  script:");
+                let _ = writeln!(yaml, "    - {}", quote(&entry.command(*job)));
+            }
+        }
+        yaml
+    }
+}
+
+// yaml double quoted scalar, json string escaping is a subset of it
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> CiMatrix {
+        CiMatrix {
+            entries: vec![
+                MatrixEntry {
+                    crate_name: "core".to_string(),
+                    workspace: "ws".to_string(),
+                    path: PathBuf::from("ws/core"),
+                    manifest_path: Some(PathBuf::from("ws/Cargo.toml")),
+                    directly_affected: true,
+                    jobs: vec![CiJob::Check, CiJob::Clippy, CiJob::Test],
+                },
+                MatrixEntry {
+                    crate_name: "app".to_string(),
+                    workspace: "ws".to_string(),
+                    path: PathBuf::from("ws/app"),
+                    manifest_path: Some(PathBuf::from("ws/Cargo.toml")),
+                    directly_affected: false,
+                    jobs: vec![CiJob::Check, CiJob::Clippy],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_github_matrix() {
+        let github = matrix().to_github();
+        let include = github["include"].as_array().unwrap();
+        assert_eq!(include.len(), 2);
+        assert_eq!(include[0]["crate"], "core");
+        assert_eq!(include[0]["path"], "ws/core");
+        assert_eq!(
+            include[0]["jobs"],
+            serde_json::json!(["check", "clippy", "test"])
+        );
+        assert_eq!(
+            matrix().entries[1].command(CiJob::Clippy),
+            "cargo clippy --manifest-path ws/Cargo.toml -p app -- -D warnings"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_pipeline() {
+        let yaml = matrix().to_gitlab();
+        assert!(yaml.contains("stages:\n  - check\n  - clippy\n  - test\n"));
+        assert!(yaml.contains(
+            "\"test:core\":\n  stage: test\n  variables:\n    CRATE: \"core\"\n    CRATE_PATH: \"ws/core\"\n  script:\n    - \"cargo test --manifest-path ws/Cargo.toml -p core\"\n"
+        ));
+        assert!(!yaml.contains("test:app"));
+
+        let empty = CiMatrix::default().to_gitlab();
+        assert!(empty.contains("no-affected-crates:"));
+    }
+}
@@ -0,0 +1,119 @@
+// CODEOWNERS files as github and gitlab read them: one `pattern owner...`
+// rule per line, the last matching rule wins
+
+use super::cargo_ops::scope::matches_path_or_ancestor;
+use crate::error::{Error, Result};
+use std::path::Path;
+
+/// where CODEOWNERS is looked for, relative to the repository root
+pub const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: glob::Pattern,
+    owners: Vec<String>,
+}
+
+/// the rules of a CODEOWNERS file
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// the first CODEOWNERS file of [`CODEOWNERS_LOCATIONS`] below `root`,
+    /// None when the repository has none
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Option<Self>> {
+        let root = root.as_ref();
+        for location in CODEOWNERS_LOCATIONS {
+            let path = root.join(location);
+            if path.is_file() {
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|source| Error::FileReadError { path, source })?;
+                return Ok(Some(Self::parse(&content)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// parse CODEOWNERS content; comments, section headers and patterns that
+    /// are not valid globs are skipped
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let pattern = to_glob(fields.next()?)?;
+                let owners = fields
+                    .take_while(|field| !field.starts_with('#'))
+                    .map(str::to_string)
+                    .collect();
+                Some(Rule { pattern, owners })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// owners of `path`, relative to the repository root; empty when no rule
+    /// matches or the matching rule lists nobody
+    pub fn owners_of<P: AsRef<Path>>(&self, path: P) -> &[String] {
+        let path = path.as_ref();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| matches_path_or_ancestor(&rule.pattern, path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+// gitignore-style pattern as a glob matched against a path or one of its
+// directories: a slash at the start or in the middle anchors it at the root,
+// otherwise it matches at any depth
+fn to_glob(pattern: &str) -> Option<glob::Pattern> {
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    let glob = match (anchored, trimmed) {
+        (_, "" | "*" | "**") => "**".to_string(),
+        (true, trimmed) => trimmed.to_string(),
+        (false, trimmed) => format!("**/{}", trimmed),
+    };
+    glob::Pattern::new(&glob).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners_of() {
+        let owners = CodeOwners::parse(
+            "# default owners\n\
+             * @org/maintainers\n\
+             \n\
+             /crates/net/ @org/net @alice # networking\n\
+             docs @org/writers\n\
+             *.toml @org/build\n\
+             /crates/net/vendored/\n",
+        );
+        assert_eq!(owners.owners_of("README.md"), ["@org/maintainers"]);
+        assert_eq!(
+            owners.owners_of("crates/net/src/lib.rs"),
+            ["@org/net", "@alice"]
+        );
+        assert_eq!(
+            owners.owners_of("crates/core/docs/intro.md"),
+            ["@org/writers"]
+        );
+        assert_eq!(owners.owners_of("crates/net/Cargo.toml"), ["@org/build"]);
+        assert!(owners.owners_of("crates/net/vendored/lib.rs").is_empty());
+        // anchored patterns only match at the root
+        assert_eq!(
+            owners.owners_of("tools/crates/net/main.rs"),
+            ["@org/maintainers"]
+        );
+    }
+}
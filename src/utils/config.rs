@@ -1,7 +1,15 @@
-use super::changelog::ChangelogConfig;
-use super::severity_config::SeverityConfig;
+use super::cargo_ops::GeneratedFiles;
+use super::cargo_ops::types::DependencyType;
+use super::changelog::{ChangelogConfig, TemplateConfig};
+use super::severity_config::{SeverityConfig, SeverityPreset};
 use crate::error::Result;
-use serde::{Deserialize, Serialize};
+use crate::utils::alt::LogicExpr;
+use crate::utils::filesystem::FilePredicate;
+use crate::utils::git_ops::TagPattern;
+use crate::utils::msrv_checker::MsrvChecker;
+use crate::utils::toml_ops::TomlSection;
+use crate::utils::version_policy::VersionPolicy;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::{Path, PathBuf};
 
 /// main configuration for deptrack
@@ -18,6 +26,362 @@ pub struct DeptrackConfig {
     /// severity configuration for transitive dependencies
     #[serde(default = "SeverityConfig::default_transitive")]
     pub transitive_severity: SeverityConfig,
+
+    /// preset for the severity tables the config file does not set
+    #[serde(default)]
+    pub severity: SeveritySettings,
+
+    /// change impact analysis configuration
+    #[serde(default)]
+    pub impact: ImpactConfig,
+
+    /// minimum supported rust version policy
+    #[serde(default)]
+    pub msrv: MsrvConfig,
+
+    /// release artifacts hashed by `deptrack manifest create`
+    #[serde(default)]
+    pub artifacts: ArtifactConfig,
+
+    /// external commands run against every checked crate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<ExternalCheckConfig>,
+
+    /// webhook notification after check-versions
+    #[serde(default)]
+    pub notify: NotifyConfig,
+
+    /// release tag naming
+    #[serde(default)]
+    pub releases: ReleasesConfig,
+
+    /// bumps required per crate maturity
+    #[serde(default)]
+    pub version_policy: VersionPolicy,
+
+    /// checks on crates added since the base ref
+    #[serde(default)]
+    pub new_crates: NewCratesConfig,
+
+    /// sections included by `deptrack report`
+    #[serde(default)]
+    pub report: ReportConfig,
+
+    /// formats of generated changelog entries and release notes
+    #[serde(default)]
+    pub templates: TemplateConfig,
+}
+
+/// the [severity] table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeveritySettings {
+    /// fills in `direct_severity` and `transitive_severity` when they are not
+    /// in the config file
+    #[serde(default)]
+    pub preset: SeverityPreset,
+
+    /// check-versions reports a warning as an error once each of the last
+    /// this many runs reported it, keeping the runs in .deptrack/history.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalate_after: Option<usize>,
+}
+
+/// configuration for the rust-version consistency check
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MsrvConfig {
+    /// when set, every crate must declare a rust-version of at least this
+    /// (e.g. "1.75") instead of agreeing with the rest of its workspace
+    #[serde(
+        default,
+        deserialize_with = "deserialize_rust_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub minimum: Option<String>,
+}
+
+fn deserialize_rust_version<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let version = String::deserialize(deserializer)?;
+    match MsrvChecker::parse_rust_version(&version) {
+        Some(_) => Ok(Some(version)),
+        None => Err(serde::de::Error::custom(format!(
+            "invalid rust-version '{}'",
+            version
+        ))),
+    }
+}
+
+/// configuration for the release artifact hash manifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactConfig {
+    /// glob patterns relative to the repository root (e.g. "dist/*.tar.gz"),
+    /// used when no artifacts are given on the command line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+/// configuration for release tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleasesConfig {
+    /// tag naming patterns with `{crate}` and `{version}` placeholders, a
+    /// single one or a list; the first names new tags, every one of them is
+    /// recognized so crates can keep their older scheme while migrating
+    #[serde(
+        default = "default_tag_patterns",
+        deserialize_with = "deserialize_tag_patterns"
+    )]
+    pub tag_pattern: Vec<TagPattern>,
+
+    /// which requirements of internal dependents `release` rewrites to the
+    /// released version
+    #[serde(default)]
+    pub update_requirements: RequirementUpdate,
+}
+
+impl Default for ReleasesConfig {
+    fn default() -> Self {
+        Self {
+            tag_pattern: default_tag_patterns(),
+            update_requirements: RequirementUpdate::default(),
+        }
+    }
+}
+
+/// when `release` rewrites requirements on a released crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementUpdate {
+    /// leave every requirement as it is
+    Never,
+    /// requirements that no longer accept the released version
+    #[default]
+    Outdated,
+    /// also requirements still accepting the previous version when the
+    /// major or minor version moves, so dependents pick up the new release
+    Minor,
+}
+
+fn default_tag_patterns() -> Vec<TagPattern> {
+    vec![TagPattern::default()]
+}
+
+fn deserialize_tag_patterns<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<TagPattern>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Patterns {
+        One(TagPattern),
+        Many(Vec<TagPattern>),
+    }
+
+    let patterns = match Patterns::deserialize(deserializer)? {
+        Patterns::One(pattern) => vec![pattern],
+        Patterns::Many(patterns) => patterns,
+    };
+    if patterns.is_empty() {
+        return Err(serde::de::Error::custom(
+            "releases.tag_pattern needs at least one pattern",
+        ));
+    }
+    Ok(patterns)
+}
+
+/// configuration for check-versions webhook notifications
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// slack or teams compatible incoming webhook, overridden by `--notify-webhook`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// message template, see `Notifier::render` for the placeholders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// link to the published report included in the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report_url: Option<String>,
+}
+
+/// an external check registered with `[[checks]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalCheckConfig {
+    /// name shown in front of reported issues, the command when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// shell command run from the repository root; `{crate_path}`,
+    /// `{crate_name}`, `{workspace}`, `{manifest_path}` and `{version}` are
+    /// replaced with the crate's values
+    pub command: String,
+}
+
+impl ExternalCheckConfig {
+    /// the name of the check, falling back to its command
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+}
+
+/// sections of `deptrack report`, every one included unless turned off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    /// workspace statistics and the crates affected by the changes
+    pub analysis: bool,
+    /// the check-versions checks on the affected crates
+    pub versions: bool,
+    /// dependency cycles of the whole workspace
+    pub cycles: bool,
+    /// the license of every workspace crate
+    pub licenses: bool,
+    /// publish-readiness of the affected crates' manifests
+    pub manifests: bool,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            analysis: true,
+            versions: true,
+            cycles: true,
+            licenses: true,
+            manifests: true,
+        }
+    }
+}
+
+/// the [new_crates] table: a crate without a Cargo.toml at the base ref has
+/// no version to have bumped, so it gets its own rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewCratesConfig {
+    /// leave new crates out of the version bump check
+    pub skip_version_bump: bool,
+    /// require a changelog with an entry for the initial version, even when
+    /// `changelog.require_changelog` is off
+    pub require_changelog: bool,
+}
+
+impl Default for NewCratesConfig {
+    fn default() -> Self {
+        Self {
+            skip_version_bump: true,
+            require_changelog: true,
+        }
+    }
+}
+
+/// configuration for change impact analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactConfig {
+    /// changed files matching this filter do not affect their crate
+    ///
+    /// accepts either the filter text form (`ignore = "ext == 'md'"`) or the
+    /// serialized expression tree
+    #[serde(
+        default,
+        deserialize_with = "deserialize_filter",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ignore: Option<LogicExpr<FilePredicate>>,
+
+    /// dependency edges a change travels along to the dependents
+    #[serde(default)]
+    pub propagation: ImpactPropagation,
+
+    /// how many dependents away from a changed crate the change travels, so
+    /// touching a foundational crate does not flag the whole workspace;
+    /// unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transitive_depth: Option<usize>,
+
+    /// stop at dependents that pin the changed crate with `=x.y.z` or list it
+    /// under `stable-reexports` in [package.metadata.deptrack]: they are
+    /// affected, their own dependents are not
+    #[serde(default)]
+    pub stop_at_boundaries: bool,
+
+    /// Cargo.toml sections whose changes leave a crate unaffected when
+    /// nothing else in it changed; lints, profiles and metadata by default
+    #[serde(default = "default_ignore_manifest_sections")]
+    pub ignore_manifest_sections: Vec<TomlSection>,
+
+    /// generated files, by pattern or `@generated` marker, whose changes do
+    /// not affect their crate
+    #[serde(default)]
+    pub generated: GeneratedFiles,
+}
+
+impl Default for ImpactConfig {
+    fn default() -> Self {
+        Self {
+            ignore: None,
+            propagation: ImpactPropagation::default(),
+            max_transitive_depth: None,
+            stop_at_boundaries: false,
+            ignore_manifest_sections: default_ignore_manifest_sections(),
+            generated: GeneratedFiles::default(),
+        }
+    }
+}
+
+fn default_ignore_manifest_sections() -> Vec<TomlSection> {
+    vec![
+        TomlSection::Metadata,
+        TomlSection::Lints,
+        TomlSection::Profile,
+    ]
+}
+
+/// which dependents of a changed crate are affected by it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpactPropagation {
+    /// every dependent, whatever the kind of dependency
+    #[default]
+    All,
+    /// dependents through normal and build dependencies; dev-dependents are
+    /// reported apart and not affected
+    NoDev,
+    /// dependents through normal dependencies only
+    Normal,
+}
+
+impl ImpactPropagation {
+    /// check if a change travels along a dependency of `dep_type`
+    pub fn follows(self, dep_type: DependencyType) -> bool {
+        match self {
+            ImpactPropagation::All => true,
+            ImpactPropagation::NoDev => dep_type != DependencyType::Dev,
+            ImpactPropagation::Normal => dep_type == DependencyType::Normal,
+        }
+    }
+}
+
+fn deserialize_filter<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<LogicExpr<FilePredicate>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Filter {
+        Text(String),
+        Expr(LogicExpr<FilePredicate>),
+    }
+
+    match Filter::deserialize(deserializer)? {
+        Filter::Text(text) => LogicExpr::parse(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        Filter::Expr(expr) => Ok(Some(expr)),
+    }
 }
 
 impl Default for DeptrackConfig {
@@ -26,6 +390,17 @@ impl Default for DeptrackConfig {
             changelog: ChangelogConfig::default(),
             direct_severity: SeverityConfig::default_direct(),
             transitive_severity: SeverityConfig::default_transitive(),
+            severity: SeveritySettings::default(),
+            impact: ImpactConfig::default(),
+            msrv: MsrvConfig::default(),
+            artifacts: ArtifactConfig::default(),
+            checks: Vec::new(),
+            notify: NotifyConfig::default(),
+            releases: ReleasesConfig::default(),
+            version_policy: VersionPolicy::default(),
+            new_crates: NewCratesConfig::default(),
+            report: ReportConfig::default(),
+            templates: TemplateConfig::default(),
         }
     }
 }
@@ -40,15 +415,38 @@ impl DeptrackConfig {
                 source: e,
             })?;
 
-        let config: DeptrackConfig =
-            toml::from_str(&contents).map_err(|e| crate::error::Error::TomlParseError {
-                path: path.to_path_buf(),
-                source: e,
-            })?;
+        let parse_error = |e| crate::error::Error::TomlParseError {
+            path: path.to_path_buf(),
+            source: e,
+        };
+        let table: toml::Table = toml::from_str(&contents).map_err(parse_error)?;
+        let explicit = |key: &str| table.contains_key(key);
+        let (direct_set, transitive_set) =
+            (explicit("direct_severity"), explicit("transitive_severity"));
+        let mut config: DeptrackConfig = table.try_into().map_err(parse_error)?;
 
+        // tables set in the file win over the preset
+        let preset = config.severity.preset;
+        if !direct_set {
+            config.direct_severity = preset.direct();
+        }
+        if !transitive_set {
+            config.transitive_severity = preset.transitive();
+        }
+        // changelog links are checked against the release tag names
+        config.changelog.tag_patterns = config.releases.tag_pattern.clone();
+        config.changelog.require_for_new_crates = config.new_crates.require_changelog;
         Ok(config)
     }
 
+    /// replace both severity tables with those of `preset`, as
+    /// `--severity-preset` does
+    pub fn use_severity_preset(&mut self, preset: SeverityPreset) {
+        self.severity.preset = preset;
+        self.direct_severity = preset.direct();
+        self.transitive_severity = preset.transitive();
+    }
+
     /// find and load configuration file in repository
     ///
     /// looks for `deptrack.toml` in the repository root
@@ -78,3 +476,103 @@ impl DeptrackConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::severity::{IssueSeverity, IssueType};
+
+    #[test]
+    fn test_impact_ignore_filter_forms() {
+        let config: DeptrackConfig =
+            toml::from_str("[impact]\nignore = \"ext == 'md'\"\n").unwrap();
+        let text_form = config.impact.ignore.unwrap();
+
+        // the structured form is what gets echoed back in reports
+        let echoed = toml::to_string(&ImpactConfig {
+            ignore: Some(text_form),
+            ..Default::default()
+        })
+        .unwrap();
+        let config: ImpactConfig = toml::from_str(&echoed).unwrap();
+        assert!(matches!(
+            config.ignore,
+            Some(LogicExpr::Leaf(FilePredicate::Extension(ref pred))) if pred.extension == "md"
+        ));
+
+        assert!(toml::from_str::<DeptrackConfig>("[impact]\nignore = \"ext ==\"\n").is_err());
+        assert!(DeptrackConfig::default().impact.ignore.is_none());
+    }
+
+    #[test]
+    fn test_impact_propagation() {
+        let config: DeptrackConfig =
+            toml::from_str("[impact]\npropagation = \"no_dev\"\n").unwrap();
+        assert_eq!(config.impact.propagation, ImpactPropagation::NoDev);
+        assert!(ImpactPropagation::NoDev.follows(DependencyType::Build));
+        assert!(!ImpactPropagation::NoDev.follows(DependencyType::Dev));
+        assert!(!ImpactPropagation::Normal.follows(DependencyType::Build));
+        assert!(ImpactPropagation::default().follows(DependencyType::Dev));
+    }
+
+    #[test]
+    fn test_severity_preset() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("deptrack.toml");
+        let load = |content: &str| {
+            std::fs::write(&path, content).unwrap();
+            DeptrackConfig::load_from_file(&path).unwrap()
+        };
+
+        let config = load("[severity]\npreset = \"lenient\"\n");
+        assert_eq!(config.severity.preset, SeverityPreset::Lenient);
+        assert_eq!(config.severity.escalate_after, None);
+        assert_eq!(
+            load("[severity]\nescalate_after = 3\n")
+                .severity
+                .escalate_after,
+            Some(3)
+        );
+        assert_eq!(
+            config
+                .direct_severity
+                .get_severity(IssueType::NoVersionBump),
+            IssueSeverity::Warning
+        );
+        assert_eq!(
+            config
+                .direct_severity
+                .get_severity(IssueType::LockfileMismatch),
+            IssueSeverity::Error
+        );
+
+        // a table set in the file is kept
+        let direct = toml::to_string(&SeverityConfig::default_direct()).unwrap();
+        let config = load(&format!(
+            "[severity]\npreset = \"strict\"\n\n[direct_severity]\n{}",
+            direct
+        ));
+        assert_eq!(
+            config
+                .direct_severity
+                .get_severity(IssueType::MissingVersionEntry),
+            IssueSeverity::Warning
+        );
+        assert_eq!(
+            config
+                .transitive_severity
+                .get_severity(IssueType::MissingVersionEntry),
+            IssueSeverity::Error
+        );
+
+        assert!(toml::from_str::<DeptrackConfig>("[severity]\npreset = \"loose\"\n").is_err());
+    }
+
+    #[test]
+    fn test_msrv_minimum() {
+        let config: DeptrackConfig = toml::from_str("[msrv]\nminimum = \"1.75\"\n").unwrap();
+        assert_eq!(config.msrv.minimum.as_deref(), Some("1.75"));
+        assert!(DeptrackConfig::default().msrv.minimum.is_none());
+        assert!(toml::from_str::<DeptrackConfig>("[msrv]\nminimum = \"latest\"\n").is_err());
+    }
+}
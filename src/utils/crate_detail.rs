@@ -0,0 +1,198 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::changelog::ChangelogStatus;
+use super::changelog_checker::ChangelogChecker;
+use super::config::DeptrackConfig;
+use super::git_ops::{CommitSummary, GitOps, latest_release_tag};
+use super::table::{Color, paint, severity_color};
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// newest release tag of a crate
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseTag {
+    pub tag: String,
+    pub version: String,
+}
+
+/// everything `deptrack crate` shows about one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateDetail {
+    pub crate_id: CrateId,
+    pub version: String,
+    pub path: PathBuf,
+    pub dependencies: Vec<CrateId>,
+    /// reached only through other dependencies
+    pub transitive_dependencies: Vec<CrateId>,
+    pub dependents: Vec<CrateId>,
+    /// reaching the crate only through other dependents
+    pub transitive_dependents: Vec<CrateId>,
+    /// None when no tag follows `releases.tag_pattern`
+    pub last_release: Option<ReleaseTag>,
+    pub changelog: Option<ChangelogStatus>,
+    /// newest commits touching the crate's directory, newest first
+    pub recent_changes: Vec<CommitSummary>,
+}
+
+impl CrateDetail {
+    /// gather the detail of `crate_id` with up to `history` recent commits;
+    /// outside a git repository there are no tags or commits to show
+    pub fn collect<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        crate_id: &CrateId,
+        config: &DeptrackConfig,
+        history: usize,
+    ) -> Result<Option<Self>> {
+        let repo_path = repo_path.as_ref();
+        let Some(info) = graph.crates.get(crate_id) else {
+            return Ok(None);
+        };
+
+        let (dependencies, transitive_dependencies) =
+            reachable(crate_id, |id| graph.get_dependencies(id));
+        let (dependents, transitive_dependents) =
+            reachable(crate_id, |id| graph.get_dependents(id));
+
+        let tags = GitOps::list_tag_names(repo_path).unwrap_or_default();
+        let last_release = latest_release_tag(&config.releases.tag_pattern, &tags, &crate_id.name)
+            .map(|(tag, version)| ReleaseTag {
+                tag,
+                version: version.to_string(),
+            });
+
+        let changelog = ChangelogChecker::analyze_all(
+            graph,
+            repo_path,
+            &config.changelog,
+            &config.direct_severity,
+        )?
+        .statuses
+        .remove(crate_id);
+
+        let recent_changes = if history > 0 {
+            GitOps::recent_commits(repo_path, &info.path, history).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Some(Self {
+            crate_id: crate_id.clone(),
+            version: info.version.clone(),
+            path: info.path.clone(),
+            dependencies,
+            transitive_dependencies,
+            dependents,
+            transitive_dependents,
+            last_release,
+            changelog,
+            recent_changes,
+        }))
+    }
+
+    /// print the detail for people
+    pub fn display(&self) {
+        println!("{} v{}", self.crate_id.display_name(), self.version);
+        println!("  workspace:    {}", self.crate_id.workspace);
+        println!("  path:         {}", self.path.display());
+        match &self.last_release {
+            Some(release) => println!("  last release: {} ({})", release.tag, release.version),
+            None => println!("  last release: {}", paint("none", Color::Dim)),
+        }
+        println!("  changelog:    {}", self.changelog_summary());
+        if let Some(changelog) = &self.changelog {
+            for issue in &changelog.issues {
+                println!(
+                    "    {}",
+                    paint(&issue.message, severity_color(issue.severity))
+                );
+            }
+        }
+
+        for (title, direct, transitive) in [
+            (
+                "dependencies",
+                &self.dependencies,
+                &self.transitive_dependencies,
+            ),
+            ("dependents", &self.dependents, &self.transitive_dependents),
+        ] {
+            println!();
+            println!(
+                "{} ({} direct, {} transitive):",
+                title,
+                direct.len(),
+                transitive.len()
+            );
+            for id in direct {
+                println!("  {}", id.display_name());
+            }
+            for id in transitive {
+                println!("  {}", paint(&id.display_name(), Color::Dim));
+            }
+        }
+
+        println!();
+        println!("recent changes:");
+        if self.recent_changes.is_empty() {
+            println!("  {}", paint("none", Color::Dim));
+        }
+        for commit in &self.recent_changes {
+            println!(
+                "  {} {} {} ({})",
+                &commit.hash[..commit.hash.len().min(8)],
+                commit.date,
+                commit.subject,
+                commit.author
+            );
+        }
+    }
+
+    fn changelog_summary(&self) -> String {
+        match &self.changelog {
+            Some(status) if status.has_changelog => {
+                let file = status
+                    .changelog_path
+                    .as_ref()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if status.current_version_has_entry {
+                    format!("{}, has an entry for {}", file, self.version)
+                } else {
+                    paint(
+                        &format!("{}, no entry for {}", file, self.version),
+                        Color::Yellow,
+                    )
+                }
+            }
+            _ => paint("missing", Color::Yellow),
+        }
+    }
+}
+
+// direct and transitive neighbours of `start` following `next`, each sorted by name
+fn reachable<'a>(
+    start: &CrateId,
+    next: impl Fn(&CrateId) -> Vec<&'a CrateId>,
+) -> (Vec<CrateId>, Vec<CrateId>) {
+    let mut direct: Vec<CrateId> = next(start).into_iter().cloned().collect();
+    direct.sort_by_key(|id| id.display_name());
+    direct.dedup();
+
+    let mut seen: HashSet<CrateId> = direct.iter().cloned().collect();
+    seen.insert(start.clone());
+    let mut queue: VecDeque<CrateId> = direct.iter().cloned().collect();
+    let mut transitive = Vec::new();
+    while let Some(id) = queue.pop_front() {
+        for neighbour in next(&id) {
+            if seen.insert(neighbour.clone()) {
+                transitive.push(neighbour.clone());
+                queue.push_back(neighbour.clone());
+            }
+        }
+    }
+    transitive.sort_by_key(|id| id.display_name());
+    (direct, transitive)
+}
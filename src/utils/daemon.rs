@@ -0,0 +1,526 @@
+use super::cancel::CancelToken;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::config::DeptrackConfig;
+use super::external_checker::ExternalChecker;
+use super::feature_checker::FeatureChecker;
+use super::lockfile_checker::LockfileChecker;
+use super::manifest_checker::ManifestChecker;
+use super::msrv_checker::MsrvChecker;
+use super::severity::{Issue, IssueSeverity};
+use crate::error::Result;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// invalid json was received
+pub const PARSE_ERROR: i64 = -32700;
+/// the message is not a valid request object
+pub const INVALID_REQUEST: i64 = -32600;
+/// the method does not exist
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// the params are missing or of the wrong shape
+pub const INVALID_PARAMS: i64 = -32602;
+/// the analysis itself failed
+pub const INTERNAL_ERROR: i64 = -32603;
+/// the analysis ran past the request's `timeout_ms`
+pub const REQUEST_CANCELLED: i64 = -32800;
+
+// error answered to a request, carries the json-rpc error code
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<crate::error::Error> for RpcError {
+    fn from(error: crate::error::Error) -> Self {
+        let code = match error {
+            crate::error::Error::Cancelled { .. } => REQUEST_CANCELLED,
+            _ => INTERNAL_ERROR,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+/// long running json-rpc 2.0 server over newline delimited stdin/stdout
+///
+/// the graph, the canonical crate directories and per-crate check results
+/// stay in memory between requests; they are rebuilt when any Cargo.toml the
+/// graph was built from changes, or on an explicit `reload`
+pub struct Daemon {
+    repo_path: PathBuf,
+    config: DeptrackConfig,
+    graph: CrateDependencyGraph,
+    // canonical crate directories, deepest first so nested crates win
+    crate_dirs: Vec<(PathBuf, CrateId)>,
+    // modification times of the manifests the graph was built from
+    manifests: HashMap<PathBuf, Option<SystemTime>>,
+    checks: HashMap<CrateId, Value>,
+    shutdown: bool,
+}
+
+impl Daemon {
+    /// build the graph for `repo_path` and warm the caches
+    pub fn new<P: AsRef<Path>>(repo_path: P) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let repo_path = repo_path
+            .canonicalize()
+            .unwrap_or_else(|_| repo_path.to_path_buf());
+        let mut daemon = Self {
+            config: DeptrackConfig::load_or_default(&repo_path),
+            graph: CrateDependencyGraph::build_from_repository(&repo_path)?,
+            repo_path,
+            crate_dirs: Vec::new(),
+            manifests: HashMap::new(),
+            checks: HashMap::new(),
+            shutdown: false,
+        };
+        daemon.index();
+        Ok(daemon)
+    }
+
+    /// answer requests from `input` on `output` until it closes or a
+    /// `shutdown` request arrives
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+            if self.shutdown {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// answer a single message, None for notifications (requests without an id)
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ));
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.dispatch(method, &params)
+            }
+            None => Err(RpcError::new(INVALID_REQUEST, "missing method")),
+        };
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> RpcResult {
+        // bounds the graph rebuild a request may trigger, the old graph is
+        // kept when it runs out
+        let cancel = match params.get("timeout_ms").and_then(Value::as_u64) {
+            Some(timeout) => CancelToken::with_timeout(Duration::from_millis(timeout)),
+            None => CancelToken::new(),
+        };
+        if !matches!(method, "reload" | "shutdown") && self.manifests_changed() {
+            self.reload(&cancel)?;
+        }
+
+        match method {
+            "affected-by" => self.affected_by(&param(params, "file")?),
+            "dependents" => {
+                let crate_id = self.crate_param(params)?;
+                let transitive = params
+                    .get("transitive")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                Ok(self.dependents(&crate_id, transitive))
+            }
+            "check" => {
+                let crate_id = self.crate_param(params)?;
+                self.check(&crate_id)
+            }
+            "reload" => {
+                self.reload(&cancel)?;
+                Ok(json!({ "crates": self.graph.crates.len() }))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        }
+    }
+
+    // crate owning `file` and everything depending on it
+    fn affected_by(&self, file: &str) -> RpcResult {
+        let path = self.repo_path.join(file);
+        let path = path.canonicalize().unwrap_or(path);
+        let Some(owner) = self
+            .crate_dirs
+            .iter()
+            .find(|(dir, _)| path.starts_with(dir))
+            .map(|(_, id)| id.clone())
+        else {
+            return Ok(json!({ "file": file, "crate": null, "affected": [] }));
+        };
+
+        let owners = [owner];
+        Ok(json!({
+            "file": file,
+            "crate": owners[0].display_name(),
+            "affected": sorted_names(self.graph.find_affected_crates(&owners)),
+        }))
+    }
+
+    fn dependents(&self, crate_id: &CrateId, transitive: bool) -> Value {
+        let target = [crate_id.clone()];
+        let dependents: Vec<&CrateId> = if transitive {
+            self.graph
+                .find_affected_crates(&target)
+                .into_iter()
+                .filter(|id| *id != crate_id)
+                .collect()
+        } else {
+            self.graph.get_dependents(crate_id)
+        };
+        json!({
+            "crate": crate_id.display_name(),
+            "transitive": transitive,
+            "dependents": sorted_names(dependents),
+        })
+    }
+
+    // manifest, rust-version, feature, lock file and external checks of a single crate, cached
+    fn check(&mut self, crate_id: &CrateId) -> RpcResult {
+        if let Some(result) = self.checks.get(crate_id) {
+            return Ok(result.clone());
+        }
+
+        let crates = [crate_id.clone()];
+        let severity = &self.config.direct_severity;
+        let mut issues: Vec<(&str, Issue)> = Vec::new();
+        if let Some(status) =
+            ManifestChecker::analyze(&self.graph, &crates, &crates, severity, severity)?
+                .statuses
+                .remove(crate_id)
+        {
+            issues.extend(status.issues.into_iter().map(|issue| ("manifest", issue)));
+        }
+        if let Some(status) = MsrvChecker::analyze(
+            &self.graph,
+            &crates,
+            &crates,
+            severity,
+            severity,
+            &self.config.msrv,
+        )?
+        .statuses
+        .remove(crate_id)
+        {
+            issues.extend(status.issues.into_iter().map(|issue| ("msrv", issue)));
+        }
+        if let Some(status) =
+            FeatureChecker::analyze(&self.graph, &crates, &crates, severity, severity)?
+                .statuses
+                .remove(crate_id)
+        {
+            issues.extend(status.issues.into_iter().map(|issue| ("features", issue)));
+        }
+        if let Some(status) = LockfileChecker::analyze(
+            &self.graph,
+            &self.repo_path,
+            &crates,
+            &crates,
+            severity,
+            severity,
+        )?
+        .statuses
+        .remove(crate_id)
+        {
+            issues.extend(status.issues.into_iter().map(|issue| ("lockfile", issue)));
+        }
+        if let Some(status) = ExternalChecker::analyze(
+            &self.graph,
+            &self.repo_path,
+            &self.config.checks,
+            &crates,
+            &crates,
+            severity,
+            severity,
+        )?
+        .statuses
+        .remove(crate_id)
+        {
+            issues.extend(status.issues.into_iter().map(|issue| ("checks", issue)));
+        }
+
+        let count = |severity| {
+            issues
+                .iter()
+                .filter(|(_, i)| i.severity == severity)
+                .count()
+        };
+        let result = json!({
+            "crate": crate_id.display_name(),
+            "errors": count(IssueSeverity::Error),
+            "warnings": count(IssueSeverity::Warning),
+            "issues": issues
+                .iter()
+                .map(|(check, issue)| json!({
+                    "check": check,
+                    "severity": issue.severity,
+                    "type": issue.issue_type.to_string(),
+                    "message": issue.message,
+                }))
+                .collect::<Vec<_>>(),
+        });
+        self.checks.insert(crate_id.clone(), result.clone());
+        Ok(result)
+    }
+
+    fn reload(&mut self, cancel: &CancelToken) -> Result<()> {
+        self.graph =
+            CrateDependencyGraph::build_from_repository_with_cancel(&self.repo_path, cancel)?;
+        self.config = DeptrackConfig::load_or_default(&self.repo_path);
+        self.index();
+        Ok(())
+    }
+
+    // recompute everything derived from the graph
+    fn index(&mut self) {
+        self.checks.clear();
+        self.crate_dirs = self
+            .graph
+            .crates
+            .values()
+            .map(|info| {
+                let dir = info
+                    .path
+                    .canonicalize()
+                    .unwrap_or_else(|_| info.path.clone());
+                (dir, info.id.clone())
+            })
+            .collect();
+        self.crate_dirs
+            .sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+
+        let manifests = self
+            .graph
+            .crates
+            .values()
+            .map(|info| info.cargo_toml_path.clone())
+            .chain(
+                self.graph
+                    .workspaces
+                    .iter()
+                    .map(|w| w.cargo_toml_path.clone()),
+            )
+            .chain([self.repo_path.join("deptrack.toml")]);
+        self.manifests = manifests
+            .map(|path| (path.clone(), modified(&path)))
+            .collect();
+    }
+
+    fn manifests_changed(&self) -> bool {
+        self.manifests
+            .iter()
+            .any(|(path, seen)| modified(path) != *seen)
+    }
+
+    // `crate` param, either a plain name or `workspace::name`
+    fn crate_param(&self, params: &Value) -> std::result::Result<CrateId, RpcError> {
+        let name = param(params, "crate")?;
+        let found = match name.split_once("::") {
+            Some((workspace, crate_name)) => {
+                let id = CrateId::new(workspace.to_string(), crate_name.to_string());
+                self.graph.crates.contains_key(&id).then_some(id)
+            }
+            None => self
+                .graph
+                .find_crate_by_name(&name)
+                .map(|info| info.id.clone()),
+        };
+        found.ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("crate {} not found", name)))
+    }
+}
+
+// a string param given by name, or as the first positional param
+fn param(params: &Value, name: &str) -> std::result::Result<String, RpcError> {
+    params
+        .get(name)
+        .or_else(|| params.get(0))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing string param '{}'", name)))
+}
+
+fn sorted_names(crates: Vec<&CrateId>) -> Vec<String> {
+    let mut names: Vec<String> = crates.iter().map(|id| id.display_name()).collect();
+    names.sort();
+    names
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn error_response(id: Value, error: RpcError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_daemon_requests() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"util\", \"app\"]\n",
+        )
+        .unwrap();
+        for (name, deps) in [
+            ("core", ""),
+            ("util", "core = { path = \"../core\" }\n"),
+            ("app", "util = { path = \"../util\" }\n"),
+        ] {
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+            fs::write(
+                root.join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}",
+                    name, deps
+                ),
+            )
+            .unwrap();
+            fs::write(root.join(name).join("src/lib.rs"), "").unwrap();
+        }
+
+        let mut daemon = Daemon::new(root).unwrap();
+        let mut ask = |request: &str| -> Value {
+            serde_json::from_str(&daemon.handle_line(request).unwrap()).unwrap()
+        };
+
+        let response = ask(
+            r#"{"jsonrpc":"2.0","id":1,"method":"affected-by","params":{"file":"core/src/lib.rs"}}"#,
+        );
+        assert_eq!(response["id"], 1);
+        let affected = response["result"]["affected"].as_array().unwrap();
+        assert_eq!(affected.len(), 3);
+        assert!(
+            response["result"]["crate"]
+                .as_str()
+                .unwrap()
+                .ends_with("::core")
+        );
+
+        let response = ask(r#"{"jsonrpc":"2.0","id":2,"method":"dependents","params":["core"]}"#);
+        assert_eq!(
+            response["result"]["dependents"].as_array().unwrap().len(),
+            1
+        );
+        let response = ask(
+            r#"{"jsonrpc":"2.0","id":3,"method":"dependents","params":{"crate":"core","transitive":true}}"#,
+        );
+        assert_eq!(
+            response["result"]["dependents"].as_array().unwrap().len(),
+            2
+        );
+
+        // bare manifests are not publish-ready
+        let response = ask(r#"{"jsonrpc":"2.0","id":4,"method":"check","params":{"crate":"app"}}"#);
+        assert!(response["result"]["issues"][0]["check"] == "manifest");
+
+        let response =
+            ask(r#"{"jsonrpc":"2.0","id":5,"method":"check","params":{"crate":"nope"}}"#);
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        let response = ask(r#"{"jsonrpc":"2.0","id":6,"method":"frobnicate"}"#);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(ask("not json")["error"]["code"], PARSE_ERROR);
+
+        // a new dependency edge is picked up without an explicit reload
+        fs::write(
+            root.join("core/Cargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n\n[dev-dependencies]\napp = { path = \"../app\" }\n",
+        )
+        .unwrap();
+        let mut manifests = daemon.manifests.clone();
+        for seen in manifests.values_mut() {
+            *seen = None;
+        }
+        daemon.manifests = manifests;
+        let response: Value = serde_json::from_str(
+            &daemon
+                .handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"dependents","params":["app"]}"#)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            response["result"]["dependents"].as_array().unwrap().len(),
+            1
+        );
+
+        // a rebuild running out of time keeps the graph it had
+        let response: Value = serde_json::from_str(
+            &daemon
+                .handle_line(
+                    r#"{"jsonrpc":"2.0","id":8,"method":"reload","params":{"timeout_ms":0}}"#,
+                )
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], REQUEST_CANCELLED);
+        assert_eq!(daemon.graph.crates.len(), 3);
+
+        assert!(
+            daemon
+                .handle_line(r#"{"jsonrpc":"2.0","method":"reload"}"#)
+                .is_none()
+        );
+        let mut output = Vec::new();
+        daemon
+            .run(
+                "{\"jsonrpc\":\"2.0\",\"id\":9,\"method\":\"shutdown\"}\n{\"jsonrpc\":\"2.0\",\"id\":10,\"method\":\"reload\"}\n"
+                    .as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 1);
+    }
+}
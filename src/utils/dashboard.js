@@ -0,0 +1,136 @@
+// force-directed drawing of /api/graph for the dashboard index page; bundled
+// into the binary so the dashboard works offline, no external scripts
+(function () {
+  const SVG = "http://www.w3.org/2000/svg";
+  const WIDTH = 1200, HEIGHT = 800;
+  const PALETTE = ["#4e79a7", "#f28e2c", "#e15759", "#76b7b2", "#59a14f",
+                   "#edc949", "#af7aa1", "#ff9da7", "#9c755f", "#bab0ab"];
+
+  function element(name, attributes, parent) {
+    const node = document.createElementNS(SVG, name);
+    for (const [key, value] of Object.entries(attributes)) {
+      node.setAttribute(key, value);
+    }
+    parent.appendChild(node);
+    return node;
+  }
+
+  function draw(data) {
+    const svg = document.getElementById("graph");
+    const defs = element("defs", {}, svg);
+    const marker = element("marker", {
+      id: "arrow", viewBox: "0 -5 10 10", refX: 18,
+      markerWidth: 6, markerHeight: 6, orient: "auto",
+    }, defs);
+    element("path", { d: "M0,-5L10,0L0,5", fill: "#999" }, marker);
+
+    const workspaces = [];
+    const byId = new Map();
+    data.nodes.forEach((node, i) => {
+      if (!workspaces.includes(node.workspace)) workspaces.push(node.workspace);
+      // start on a circle so the layout is the same on every load
+      const angle = (2 * Math.PI * i) / data.nodes.length;
+      node.x = WIDTH / 2 + 200 * Math.cos(angle);
+      node.y = HEIGHT / 2 + 200 * Math.sin(angle);
+      node.vx = 0;
+      node.vy = 0;
+      byId.set(node.id, node);
+    });
+    const links = data.links
+      .map(link => ({ source: byId.get(link.source), target: byId.get(link.target), kind: link.kind }))
+      .filter(link => link.source && link.target);
+
+    const linkGroup = element("g", {}, svg);
+    const nodeGroup = element("g", {}, svg);
+    links.forEach(link => {
+      link.line = element("line", { stroke: "#999", "marker-end": "url(#arrow)" }, linkGroup);
+      if (link.kind !== "Normal") link.line.setAttribute("stroke-dasharray", "4 3");
+    });
+
+    let dragged = null;
+    let moved = false;
+    data.nodes.forEach(node => {
+      node.anchor = element("a", { href: node.url }, nodeGroup);
+      const colour = PALETTE[workspaces.indexOf(node.workspace) % PALETTE.length];
+      element("circle", { r: 8, fill: colour }, node.anchor);
+      element("text", { x: 12, y: 4 }, node.anchor).textContent = node.name + " v" + node.version;
+      node.anchor.addEventListener("pointerdown", event => {
+        dragged = node;
+        moved = false;
+        node.anchor.setPointerCapture(event.pointerId);
+        restart();
+      });
+      // a drag is not a click on the crate link
+      node.anchor.addEventListener("click", event => { if (moved) event.preventDefault(); });
+    });
+    svg.addEventListener("pointermove", event => {
+      if (!dragged) return;
+      const box = svg.getBoundingClientRect();
+      dragged.x = event.clientX - box.left;
+      dragged.y = event.clientY - box.top;
+      moved = true;
+      restart();
+    });
+    svg.addEventListener("pointerup", () => { dragged = null; });
+
+    let alpha = 1;
+    let running = false;
+    function restart() {
+      alpha = Math.max(alpha, 0.3);
+      if (!running) {
+        running = true;
+        requestAnimationFrame(tick);
+      }
+    }
+
+    function tick() {
+      const nodes = data.nodes;
+      // every pair repels, linked crates pull together, all drift to the centre
+      for (let i = 0; i < nodes.length; i++) {
+        for (let j = i + 1; j < nodes.length; j++) {
+          const a = nodes[i], b = nodes[j];
+          let dx = b.x - a.x, dy = b.y - a.y;
+          const distance2 = Math.max(dx * dx + dy * dy, 1);
+          const force = (300 * alpha) / distance2;
+          dx *= force;
+          dy *= force;
+          a.vx -= dx; a.vy -= dy;
+          b.vx += dx; b.vy += dy;
+        }
+      }
+      links.forEach(({ source, target }) => {
+        const dx = target.x - source.x, dy = target.y - source.y;
+        const distance = Math.max(Math.sqrt(dx * dx + dy * dy), 1);
+        const force = ((distance - 90) / distance) * 0.1 * alpha;
+        source.vx += dx * force; source.vy += dy * force;
+        target.vx -= dx * force; target.vy -= dy * force;
+      });
+      nodes.forEach(node => {
+        node.vx += (WIDTH / 2 - node.x) * 0.01 * alpha;
+        node.vy += (HEIGHT / 2 - node.y) * 0.01 * alpha;
+        if (node !== dragged) {
+          node.x += node.vx;
+          node.y += node.vy;
+        }
+        node.vx *= 0.6;
+        node.vy *= 0.6;
+        node.anchor.setAttribute("transform", `translate(${node.x},${node.y})`);
+      });
+      links.forEach(({ source, target, line }) => {
+        line.setAttribute("x1", source.x); line.setAttribute("y1", source.y);
+        line.setAttribute("x2", target.x); line.setAttribute("y2", target.y);
+      });
+
+      alpha *= dragged ? 1 : 0.98;
+      if (alpha > 0.005) {
+        requestAnimationFrame(tick);
+      } else {
+        running = false;
+      }
+    }
+
+    restart();
+  }
+
+  fetch("/api/graph").then(response => response.json()).then(draw);
+})();
@@ -0,0 +1,475 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, DependencyType};
+use super::config::DeptrackConfig;
+use super::health::HealthScorer;
+use crate::error::Result;
+use petgraph::visit::EdgeRef;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// address the dashboard listens on unless told otherwise
+pub const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8080";
+
+/// how long a connection may take to send its request or accept the response
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// request line and headers read at most, the body of a GET is ignored
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// force layout drawing the index page graph, inlined so the dashboard loads
+/// no external scripts
+const GRAPH_SCRIPT: &str = include_str!("dashboard.js");
+
+/// a rendered page or api document
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn html(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body,
+        }
+    }
+
+    fn json(value: &serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string()),
+        }
+    }
+
+    fn not_found(what: &str) -> Self {
+        Self {
+            status: 404,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{} not found\n", what),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// small http server rendering the dependency graph, the latest
+/// check-versions report and per-crate pages
+///
+/// the graph is rebuilt on every request so the pages follow the working tree
+pub struct DashboardServer {
+    repo_path: PathBuf,
+    report_path: Option<PathBuf>,
+}
+
+impl DashboardServer {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        Self {
+            repo_path: repo_path.into(),
+            report_path: None,
+        }
+    }
+
+    /// json written by `deptrack --format json check-versions`, re-read on
+    /// every request so regenerating it updates the dashboard
+    pub fn report_path(mut self, report_path: Option<PathBuf>) -> Self {
+        self.report_path = report_path;
+        self
+    }
+
+    /// serve requests until the process is stopped
+    pub fn serve(&self, address: &str) -> Result<()> {
+        self.serve_on(TcpListener::bind(address)?);
+        Ok(())
+    }
+
+    // each connection is handled on its own thread, so a slow or idle
+    // client holds up no one else
+    fn serve_on(&self, listener: TcpListener) {
+        std::thread::scope(|scope| {
+            for stream in listener.incoming().flatten() {
+                // a client hanging up early is not a reason to stop serving
+                scope.spawn(move || {
+                    let _ = self.handle_connection(stream);
+                });
+            }
+        });
+    }
+
+    fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+        let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // the headers are not used, but are read up to the blank line ending
+        // them so the client is done sending when the response goes out
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+            header.clear();
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or("/");
+
+        let response = if method == "GET" {
+            let path = target.split('?').next().unwrap_or(target);
+            self.respond(path).unwrap_or_else(|e| Response {
+                status: 500,
+                content_type: "text/plain; charset=utf-8",
+                body: format!("{}\n", e),
+            })
+        } else {
+            Response {
+                status: 405,
+                content_type: "text/plain; charset=utf-8",
+                body: "only GET is supported\n".to_string(),
+            }
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.reason(),
+            response.content_type,
+            response.body.len(),
+            response.body
+        )?;
+        stream.flush()
+    }
+
+    /// render the page or api document for a request path
+    pub fn respond(&self, path: &str) -> Result<Response> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let response = match segments.as_slice() {
+            [""] => Response::html(index_page()),
+            ["api", "graph"] => Response::json(&graph_json(&self.graph()?)),
+            ["api", "health"] => {
+                let graph = self.graph()?;
+                let config = DeptrackConfig::load_or_default(&self.repo_path);
+                let health = HealthScorer::analyze(&graph, &self.repo_path, &config, None)?;
+                Response::json(&serde_json::to_value(health).map_err(std::io::Error::from)?)
+            }
+            ["api", "report"] => match self.report()? {
+                Some(report) => Response::json(&report),
+                None => Response::not_found("check-versions report"),
+            },
+            ["report"] => Response::html(report_page(self.report()?.as_ref())),
+            ["crate", workspace, name] => {
+                let graph = self.graph()?;
+                let crate_id = CrateId::new(workspace.to_string(), name.to_string());
+                if graph.crates.contains_key(&crate_id) {
+                    Response::html(self.crate_page(&graph, &crate_id)?)
+                } else {
+                    Response::not_found(&format!("crate {}", crate_id.display_name()))
+                }
+            }
+            _ => Response::not_found(path),
+        };
+        Ok(response)
+    }
+
+    fn graph(&self) -> Result<CrateDependencyGraph> {
+        CrateDependencyGraph::build_from_repository(&self.repo_path)
+    }
+
+    fn report(&self) -> Result<Option<serde_json::Value>> {
+        match &self.report_path {
+            Some(path) if path.is_file() => {
+                let content = std::fs::read_to_string(path)?;
+                Ok(Some(
+                    serde_json::from_str(&content).map_err(std::io::Error::from)?,
+                ))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn crate_page(&self, graph: &CrateDependencyGraph, crate_id: &CrateId) -> Result<String> {
+        let info = &graph.crates[crate_id];
+        let config = DeptrackConfig::load_or_default(&self.repo_path);
+        let health = HealthScorer::analyze(graph, &self.repo_path, &config, None)?;
+
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "<h1>{} <small>v{}</small></h1>",
+            escape(&crate_id.name),
+            escape(&info.version)
+        );
+        let _ = writeln!(
+            body,
+            "<p>workspace <b>{}</b>, {}</p>",
+            escape(&crate_id.workspace),
+            escape(&info.path.display().to_string())
+        );
+        if let Some(crate_health) = health.crates.iter().find(|c| &c.crate_id == crate_id) {
+            let _ = writeln!(
+                body,
+                "<p>health score <b>{:.1}</b> (changelog {:.0}%, manifest {:.0}%{}{})</p>",
+                crate_health.score,
+                crate_health.changelog * 100.0,
+                crate_health.manifest * 100.0,
+                if crate_health.in_cycle {
+                    ", in a dependency cycle"
+                } else {
+                    ""
+                },
+                if crate_health.orphan {
+                    ", orphaned"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        for (title, direction) in [
+            ("dependencies", petgraph::Direction::Outgoing),
+            ("dependents", petgraph::Direction::Incoming),
+        ] {
+            let mut neighbours: Vec<(&CrateId, DependencyType)> = graph
+                .node_indices
+                .get(crate_id)
+                .map(|&index| {
+                    graph
+                        .graph
+                        .edges_directed(index, direction)
+                        .map(|edge| {
+                            let other = match direction {
+                                petgraph::Direction::Outgoing => edge.target(),
+                                petgraph::Direction::Incoming => edge.source(),
+                            };
+                            (&graph.graph[other], *edge.weight())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            neighbours.sort_by_key(|(id, kind)| (id.display_name(), *kind as u8));
+
+            let _ = writeln!(body, "<h2>{} ({})</h2>", title, neighbours.len());
+            let _ = writeln!(body, "<ul>");
+            for (id, kind) in neighbours {
+                let _ = writeln!(body, "<li>{}{}</li>", crate_link(id), edge_suffix(kind));
+            }
+            let _ = writeln!(body, "</ul>");
+        }
+
+        Ok(page(&crate_id.display_name(), &body))
+    }
+}
+
+/// nodes and links drawn by the index page's force layout
+pub fn graph_json(graph: &CrateDependencyGraph) -> serde_json::Value {
+    let mut nodes: Vec<serde_json::Value> = graph
+        .crates
+        .values()
+        .map(|info| {
+            serde_json::json!({
+                "id": info.id.display_name(),
+                "name": info.id.name,
+                "workspace": info.id.workspace,
+                "version": info.version,
+                "url": crate_url(&info.id),
+            })
+        })
+        .collect();
+    nodes.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+    let mut links: Vec<(String, String, DependencyType)> = graph
+        .graph
+        .edge_references()
+        .map(|edge| {
+            (
+                graph.graph[edge.source()].display_name(),
+                graph.graph[edge.target()].display_name(),
+                *edge.weight(),
+            )
+        })
+        .collect();
+    links.sort_by(|a, b| (&a.0, &a.1, a.2 as u8).cmp(&(&b.0, &b.1, b.2 as u8)));
+
+    serde_json::json!({
+        "nodes": nodes,
+        "links": links
+            .into_iter()
+            .map(|(source, target, kind)| serde_json::json!({
+                "source": source,
+                "target": target,
+                "kind": kind,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn crate_url(crate_id: &CrateId) -> String {
+    format!("/crate/{}/{}", crate_id.workspace, crate_id.name)
+}
+
+fn crate_link(crate_id: &CrateId) -> String {
+    format!(
+        "<a href=\"{}\">{}</a>",
+        escape(&crate_url(crate_id)),
+        escape(&crate_id.display_name())
+    )
+}
+
+fn edge_suffix(kind: DependencyType) -> &'static str {
+    match kind {
+        DependencyType::Normal => "",
+        DependencyType::Dev => " <i>[dev]</i>",
+        DependencyType::Build => " <i>[build]</i>",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} - deptrack</title>\n\
+         <style>body {{ font-family: sans-serif; margin: 2em; }} nav a {{ margin-right: 1em; }}\
+         table {{ border-collapse: collapse; }} td, th {{ padding: 0.2em 0.8em; text-align: left; }}</style>\n\
+         </head>\n<body>\n<nav><a href=\"/\">graph</a><a href=\"/report\">report</a>\
+         <a href=\"/api/graph\">graph json</a><a href=\"/api/health\">health json</a></nav>\n{}</body>\n</html>\n",
+        escape(title),
+        body
+    )
+}
+
+fn index_page() -> String {
+    let body = format!(
+        "<h1>dependency graph</h1>\n<svg id=\"graph\" width=\"1200\" height=\"800\"></svg>\n\
+         <script>\n{}</script>\n",
+        GRAPH_SCRIPT
+    );
+    page("dependency graph", &body)
+}
+
+fn report_page(report: Option<&serde_json::Value>) -> String {
+    let Some(report) = report else {
+        return page(
+            "check-versions report",
+            "<h1>check-versions report</h1>\n<p>no report, start the dashboard with <code>--report</code> \
+             pointing at the output of <code>deptrack --format json check-versions</code></p>\n",
+        );
+    };
+
+    let mut body = String::from("<h1>check-versions report</h1>\n<table>\n");
+    if let Some(fields) = report.as_object() {
+        for (key, value) in fields {
+            // nested sections are easier to read as json than flattened
+            let value = match value {
+                serde_json::Value::String(text) => escape(text),
+                serde_json::Value::Object(_) | serde_json::Value::Array(_) => format!(
+                    "<pre>{}</pre>",
+                    escape(&serde_json::to_string_pretty(value).unwrap_or_default())
+                ),
+                other => escape(&other.to_string()),
+            };
+            let _ = writeln!(body, "<tr><th>{}</th><td>{}</td></tr>", escape(key), value);
+        }
+    }
+    body.push_str("</table>\n");
+    page("check-versions report", &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dashboard_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"app\"]\n",
+        )
+        .unwrap();
+        for (name, deps) in [
+            ("core", ""),
+            ("app", "\n[dependencies]\ncore = { path = \"../core\" }\n"),
+        ] {
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+            fs::write(
+                root.join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n{}",
+                    name, deps
+                ),
+            )
+            .unwrap();
+        }
+        let report = root.join("report.json");
+        fs::write(&report, "{\"all_bumped\": true, \"from\": \"<main>\"}").unwrap();
+
+        let server = DashboardServer::new(root).report_path(Some(report));
+        let graph = server.respond("/api/graph").unwrap();
+        let graph: serde_json::Value = serde_json::from_str(&graph.body).unwrap();
+        assert_eq!(graph["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(graph["links"][0]["kind"], "Normal");
+        let workspace = graph["nodes"][0]["workspace"].as_str().unwrap().to_string();
+
+        // the graph script is served inline, nothing is fetched from elsewhere
+        let index = server.respond("/").unwrap();
+        assert!(index.body.contains("fetch(\"/api/graph\")"));
+        assert!(!index.body.contains("<script src="));
+
+        let page = server
+            .respond(&format!("/crate/{}/core", workspace))
+            .unwrap();
+        assert_eq!(page.status, 200);
+        assert!(page.body.contains("<h2>dependents (1)</h2>"));
+        assert!(
+            page.body
+                .contains(&format!("href=\"/crate/{}/app\"", workspace))
+        );
+
+        let report = server.respond("/report").unwrap();
+        assert!(report.body.contains("<td>&lt;main&gt;</td>"));
+        assert_eq!(server.respond("/crate/nope/nope").unwrap().status, 404);
+        assert_eq!(server.respond("/api/nothing").unwrap().status, 404);
+    }
+
+    #[test]
+    fn test_idle_connection_blocks_no_one() {
+        let temp_dir = TempDir::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = DashboardServer::new(temp_dir.path());
+        std::thread::spawn(move || server.serve_on(listener));
+
+        // a client that connects and never sends anything
+        let _idle = TcpStream::connect(address).unwrap();
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        client
+            .write_all(b"GET /api/nothing HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+    }
+}
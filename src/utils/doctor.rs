@@ -0,0 +1,323 @@
+use super::cargo_ops::discovery::CargoDiscovery;
+use super::config::DeptrackConfig;
+use super::git_ops::{CurrentRef, GitOps, GitRef};
+use super::table::{Color, paint};
+use crate::error::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// branches a base reference is usually taken from
+const BASE_BRANCHES: [&str; 2] = ["main", "master"];
+
+/// outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosisStatus {
+    Ok,
+    /// some commands work, others give incomplete answers
+    Warning,
+    /// commands relying on this fail
+    Error,
+}
+
+/// result of one environment check, with what to do about it
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    pub check: String,
+    pub status: DiagnosisStatus,
+    pub detail: String,
+    /// how to fix it, None when there is nothing to do
+    pub remedy: Option<String>,
+}
+
+impl Diagnosis {
+    fn ok(check: &str, detail: impl Into<String>) -> Self {
+        Diagnosis {
+            check: check.to_string(),
+            status: DiagnosisStatus::Ok,
+            detail: detail.into(),
+            remedy: None,
+        }
+    }
+
+    fn warning(check: &str, detail: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Diagnosis {
+            check: check.to_string(),
+            status: DiagnosisStatus::Warning,
+            detail: detail.into(),
+            remedy: Some(remedy.into()),
+        }
+    }
+
+    fn error(check: &str, detail: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Diagnosis {
+            check: check.to_string(),
+            status: DiagnosisStatus::Error,
+            detail: detail.into(),
+            remedy: Some(remedy.into()),
+        }
+    }
+}
+
+/// every check run by `deptrack doctor`, in the order they ran
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Diagnosis>,
+}
+
+impl DoctorReport {
+    pub fn error_count(&self) -> usize {
+        self.count(DiagnosisStatus::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(DiagnosisStatus::Warning)
+    }
+
+    fn count(&self, status: DiagnosisStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    /// display each check with its remedy, then the totals
+    pub fn display(&self) {
+        for diagnosis in &self.checks {
+            let status = match diagnosis.status {
+                DiagnosisStatus::Ok => paint("ok", Color::Green),
+                DiagnosisStatus::Warning => paint("warning", Color::Yellow),
+                DiagnosisStatus::Error => paint("error", Color::Red),
+            };
+            println!("[{}] {}: {}", status, diagnosis.check, diagnosis.detail);
+            if let Some(remedy) = &diagnosis.remedy {
+                println!("    fix: {}", remedy);
+            }
+        }
+        println!(
+            "{} checks, {} errors, {} warnings",
+            self.checks.len(),
+            self.error_count(),
+            self.warning_count()
+        );
+    }
+}
+
+/// diagnoses the environment deptrack runs in: git, the state of the
+/// checkout, the manifests and the configuration
+pub struct Doctor;
+
+impl Doctor {
+    /// run every check against the repository at `path`; the git state checks
+    /// are skipped when no repository is found there
+    pub fn diagnose<P: AsRef<Path>>(path: P) -> Result<DoctorReport> {
+        let path = path.as_ref();
+        let mut report = DoctorReport::default();
+
+        report.checks.push(Self::check_git_executable());
+
+        let root = match GitOps::detect_repository_root(path) {
+            Ok(root) => {
+                report
+                    .checks
+                    .push(Diagnosis::ok("repository", root.display().to_string()));
+                Self::check_checkout(&root, &mut report);
+                root
+            }
+            Err(e) => {
+                report.checks.push(Diagnosis::error(
+                    "repository",
+                    format!("no git repository at {}: {}", path.display(), e),
+                    "run deptrack inside a git checkout, or use `deptrack fingerprint` \
+                     to compare trees without history",
+                ));
+                path.to_path_buf()
+            }
+        };
+
+        report.checks.extend(Self::check_manifests(&root));
+        report.checks.push(Self::check_config(&root));
+        Ok(report)
+    }
+
+    // refs are resolved in-process, but diffs, file contents at a ref, tags
+    // and history are read through the git executable
+    fn check_git_executable() -> Diagnosis {
+        match std::process::Command::new("git").arg("--version").output() {
+            Ok(output) if output.status.success() => Diagnosis::ok(
+                "git",
+                String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            ),
+            _ => Diagnosis::error(
+                "git",
+                "the git executable was not found; only ref resolution works without it",
+                "install git and make sure it is on PATH, change detection, \
+                 changelogs and releases run it",
+            ),
+        }
+    }
+
+    fn check_checkout(root: &Path, report: &mut DoctorReport) {
+        let repo = match gix::discover(root) {
+            Ok(repo) => repo,
+            Err(e) => {
+                report.checks.push(Diagnosis::error(
+                    "repository",
+                    format!("failed to open {}: {}", root.display(), e),
+                    "check that the .git directory is readable and not corrupted",
+                ));
+                return;
+            }
+        };
+
+        report.checks.push(if repo.is_shallow() {
+            Diagnosis::warning(
+                "history",
+                "shallow clone, refs older than the cut-off cannot be compared",
+                "run `git fetch --unshallow`, or set `fetch-depth: 0` on actions/checkout",
+            )
+        } else {
+            Diagnosis::ok("history", "full clone")
+        });
+
+        report.checks.push(match GitOps::get_current_branch(root) {
+            Ok(_) if repo.head().is_ok_and(|head| head.is_unborn()) => Diagnosis::warning(
+                "HEAD",
+                "no commits yet",
+                "commit the workspace before comparing refs",
+            ),
+            Ok(CurrentRef::Branch(branch)) => {
+                Diagnosis::ok("HEAD", format!("on branch {}", branch))
+            }
+            Ok(detached) => Diagnosis::warning(
+                "HEAD",
+                format!("{}, there is no upstream to detect a base from", detached),
+                "check out a branch, or pass explicit refs such as \
+                 `check-versions origin/main HEAD`",
+            ),
+            Err(e) => Diagnosis::error(
+                "HEAD",
+                format!("unreadable: {}", e),
+                "repair HEAD with `git checkout <branch>`",
+            ),
+        });
+
+        let base = BASE_BRANCHES
+            .iter()
+            .find(|branch| GitOps::resolve_ref(root, &GitRef::Branch(branch.to_string())).is_ok());
+        report.checks.push(match base {
+            Some(branch) => Diagnosis::ok("base branch", format!("{} is available", branch)),
+            None => Diagnosis::warning(
+                "base branch",
+                format!(
+                    "neither {} exists locally or on origin",
+                    BASE_BRANCHES.join(" nor ")
+                ),
+                "fetch the branch you compare against, e.g. `git fetch origin main:main`",
+            ),
+        });
+
+        let tags = repo
+            .references()
+            .ok()
+            .and_then(|refs| refs.tags().ok().map(|tags| tags.count()))
+            .unwrap_or_default();
+        report.checks.push(if tags > 0 {
+            Diagnosis::ok("tags", format!("{} tags", tags))
+        } else {
+            Diagnosis::warning(
+                "tags",
+                "no tags, release tags cannot be used as base references",
+                "run `git fetch --tags`, or `fetch-tags: true` on actions/checkout",
+            )
+        });
+    }
+
+    // workspace discovery skips manifests it cannot parse, leaving their
+    // crates out of every analysis
+    fn check_manifests(root: &Path) -> Vec<Diagnosis> {
+        let manifests: Vec<PathBuf> = match CargoDiscovery::find_manifests(root) {
+            Ok(manifests) => manifests,
+            Err(e) => {
+                return vec![Diagnosis::error(
+                    "manifests",
+                    format!("{}: {}", root.display(), e),
+                    "check the permissions of the repository directories",
+                )];
+            }
+        };
+
+        let mut diagnoses: Vec<Diagnosis> = manifests
+            .iter()
+            .filter_map(|manifest| {
+                CargoDiscovery::check_manifest(manifest).map(|broken| {
+                    Diagnosis::error(
+                        "manifests",
+                        broken.to_string(),
+                        "fix the manifest, the crates it declares are left out until it parses",
+                    )
+                })
+            })
+            .collect();
+
+        if manifests.is_empty() {
+            diagnoses.push(Diagnosis::warning(
+                "manifests",
+                "no Cargo.toml found",
+                "run deptrack from the root of a cargo workspace or pass --path",
+            ));
+        } else if diagnoses.is_empty() {
+            diagnoses.push(Diagnosis::ok(
+                "manifests",
+                format!("{} Cargo.toml files parsed", manifests.len()),
+            ));
+        }
+        diagnoses
+    }
+
+    // a config that fails to parse is replaced by the defaults without a word
+    fn check_config(root: &Path) -> Diagnosis {
+        match DeptrackConfig::find_config_file(root) {
+            None => Diagnosis::ok("config", "no deptrack.toml, using the defaults"),
+            Some(path) => match DeptrackConfig::load_from_file(&path) {
+                Ok(_) => Diagnosis::ok("config", path.display().to_string()),
+                Err(e) => Diagnosis::error(
+                    "config",
+                    format!("{}: {}", path.display(), e),
+                    "fix deptrack.toml, every command falls back to the defaults until it parses",
+                ),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{TestCrate, TestRepoBuilder, TestWorkspace};
+
+    #[test]
+    fn test_doctor_reports_broken_manifest_and_config() {
+        let repo = TestRepoBuilder::new()
+            .workspace(TestWorkspace::new("ws").crate_entry(TestCrate::new("core")))
+            .build()
+            .unwrap();
+        std::fs::write(repo.path().join("deptrack.toml"), "[impact\n").unwrap();
+        std::fs::write(
+            repo.crate_path("core").unwrap().join("Cargo.toml"),
+            "[package\n",
+        )
+        .unwrap();
+
+        let report = Doctor::diagnose(repo.path()).unwrap();
+        let status = |check: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.check == check)
+                .map(|c| c.status)
+        };
+        assert_eq!(status("repository"), Some(DiagnosisStatus::Ok));
+        assert_eq!(status("manifests"), Some(DiagnosisStatus::Error));
+        assert_eq!(status("config"), Some(DiagnosisStatus::Error));
+        assert_eq!(status("tags"), Some(DiagnosisStatus::Warning));
+        assert!(report.error_count() >= 2);
+    }
+}
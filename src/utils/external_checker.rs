@@ -0,0 +1,322 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, CrateInfo};
+use crate::error::Result;
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::config::ExternalCheckConfig;
+use crate::utils::severity::{Issue, IssueSeverity, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// results of the configured external checks for a single crate
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalCheckStatus {
+    /// the crate identifier
+    pub crate_id: CrateId,
+    /// issues reported by the checks, prefixed with the check name
+    pub issues: Vec<Issue>,
+}
+
+/// external check results across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExternalCheckAnalysis {
+    /// status for every analyzed crate
+    pub statuses: HashMap<CrateId, ExternalCheckStatus>,
+    /// crates with at least one reported issue
+    pub crates_with_issues: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+/// an issue as printed by an external check
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportedIssue {
+    pub message: String,
+    /// "error" or "warning", the `external_check` severity when left out
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+// what a check prints on stdout: a list of issues or an object holding one
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CheckOutput {
+    Issues(Vec<ReportedIssue>),
+    Report { issues: Vec<ReportedIssue> },
+}
+
+impl ExternalCheckAnalysis {
+    /// check if no external check reported anything
+    pub fn all_passed(&self) -> bool {
+        self.crates_with_issues.is_empty()
+    }
+
+    /// display detailed external check issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "external check",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+}
+
+impl IssueTotals for ExternalCheckAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// runs the `[[checks]]` commands from deptrack.toml against crates
+pub struct ExternalChecker;
+
+impl ExternalChecker {
+    /// `command` with the crate placeholders replaced by shell-quoted values:
+    /// `{crate_path}`, `{crate_name}`, `{workspace}`, `{manifest_path}` and `{version}`
+    pub fn expand_command(command: &str, crate_info: &CrateInfo) -> String {
+        command
+            .replace(
+                "{crate_path}",
+                &shell_quote(&crate_info.path.to_string_lossy()),
+            )
+            .replace("{crate_name}", &shell_quote(&crate_info.id.name))
+            .replace("{workspace}", &shell_quote(&crate_info.id.workspace))
+            .replace(
+                "{manifest_path}",
+                &shell_quote(&crate_info.cargo_toml_path.to_string_lossy()),
+            )
+            .replace("{version}", &shell_quote(&crate_info.version))
+    }
+
+    /// issues from a check's stdout; empty output means the check passed
+    pub fn parse_output(output: &str) -> std::result::Result<Vec<ReportedIssue>, String> {
+        if output.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        match serde_json::from_str::<CheckOutput>(output) {
+            Ok(CheckOutput::Issues(issues)) | Ok(CheckOutput::Report { issues }) => Ok(issues),
+            Err(e) => Err(format!("unexpected output: {}", e)),
+        }
+    }
+
+    /// run every check against `crates` from `repo_path`, using the direct
+    /// severity for crates in `directly_affected` and the transitive one for
+    /// the rest when a check leaves the severity out
+    pub fn analyze<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        checks: &[ExternalCheckConfig],
+        crates: &[CrateId],
+        directly_affected: &[CrateId],
+        direct_severity: &SeverityConfig,
+        transitive_severity: &SeverityConfig,
+    ) -> Result<ExternalCheckAnalysis> {
+        let repo_path = repo_path.as_ref();
+        let mut analysis = ExternalCheckAnalysis::default();
+        if checks.is_empty() {
+            return Ok(analysis);
+        }
+
+        for crate_id in crates {
+            let Some(crate_info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let severity_config = if directly_affected.contains(crate_id) {
+                direct_severity
+            } else {
+                transitive_severity
+            };
+            let default_severity = severity_config.get_severity(IssueType::ExternalCheck);
+
+            let mut issues = Vec::new();
+            for check in checks {
+                for (severity, message) in Self::run(check, crate_info, repo_path) {
+                    let severity = severity.unwrap_or(default_severity);
+                    analysis.count(severity);
+                    issues.push(Issue::new(
+                        severity,
+                        IssueType::ExternalCheck,
+                        format!("[{}] {}", check.display_name(), message),
+                    ));
+                }
+            }
+
+            if !issues.is_empty() {
+                analysis.crates_with_issues.push(crate_id.clone());
+            }
+            analysis.statuses.insert(
+                crate_id.clone(),
+                ExternalCheckStatus {
+                    crate_id: crate_id.clone(),
+                    issues,
+                },
+            );
+        }
+
+        analysis
+            .crates_with_issues
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    /// run every check against every crate in the graph
+    pub fn analyze_all<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        checks: &[ExternalCheckConfig],
+        severity_config: &SeverityConfig,
+    ) -> Result<ExternalCheckAnalysis> {
+        let crates: Vec<CrateId> = graph.crates.keys().cloned().collect();
+        Self::analyze(
+            graph,
+            repo_path,
+            checks,
+            &crates,
+            &crates,
+            severity_config,
+            severity_config,
+        )
+    }
+
+    // run one check for one crate; a check that cannot be started, fails
+    // without output or prints something unreadable becomes an issue itself
+    fn run(
+        check: &ExternalCheckConfig,
+        crate_info: &CrateInfo,
+        repo_path: &Path,
+    ) -> Vec<(Option<IssueSeverity>, String)> {
+        let command = Self::expand_command(&check.command, crate_info);
+        let output = match shell(&command).current_dir(repo_path).output() {
+            Ok(output) => output,
+            Err(e) => return vec![(None, format!("failed to run '{}': {}", command, e))],
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match Self::parse_output(&stdout) {
+            Ok(reported) if !reported.is_empty() || output.status.success() => reported
+                .into_iter()
+                .map(|issue| {
+                    let severity = issue.severity.and_then(|s| s.parse().ok());
+                    (severity, issue.message)
+                })
+                .collect(),
+            Ok(_) => vec![(
+                None,
+                format!("exited with {}: {}", output.status, stderr.trim()),
+            )],
+            Err(reason) => vec![(None, reason)],
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(unix)]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(windows)]
+fn shell_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_output() {
+        assert!(ExternalChecker::parse_output("  \n").unwrap().is_empty());
+
+        let issues = ExternalChecker::parse_output(
+            r#"[{"message": "public api changed", "severity": "warning"}]"#,
+        )
+        .unwrap();
+        assert_eq!(issues[0].message, "public api changed");
+        assert_eq!(issues[0].severity.as_deref(), Some("warning"));
+
+        let issues =
+            ExternalChecker::parse_output(r#"{"issues": [{"message": "todo left"}]}"#).unwrap();
+        assert!(issues[0].severity.is_none());
+        assert!(ExternalChecker::parse_output("all good").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_checks() {
+        let mut graph = CrateDependencyGraph::new();
+        let core = CrateId::new("ws".to_string(), "core".to_string());
+        graph.add_crate(CrateInfo::new(
+            core.clone(),
+            "0.1.0".to_string(),
+            PathBuf::from("/repo/it's core"),
+        ));
+        assert_eq!(
+            ExternalChecker::expand_command("check {crate_path} {version}", &graph.crates[&core]),
+            "check '/repo/it'\\''s core' '0.1.0'"
+        );
+
+        let checks: Vec<ExternalCheckConfig> = toml::from_str::<crate::utils::config::DeptrackConfig>(
+            r#"
+            [[checks]]
+            name = "api"
+            command = "echo '[{\"message\": \"api of '{crate_name}' changed\", \"severity\": \"warning\"}]'"
+
+            [[checks]]
+            command = "echo '{\"issues\": [{\"message\": \"no docs\"}]}'"
+
+            [[checks]]
+            name = "broken"
+            command = "echo oops >&2; exit 3"
+
+            [[checks]]
+            name = "clean"
+            command = "true"
+            "#,
+        )
+        .unwrap()
+        .checks;
+
+        let analysis = ExternalChecker::analyze_all(
+            &graph,
+            std::env::temp_dir(),
+            &checks,
+            &SeverityConfig::default_direct(),
+        )
+        .unwrap();
+        let messages: Vec<&str> = analysis.statuses[&core]
+            .issues
+            .iter()
+            .map(|i| i.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "[api] api of core changed",
+                "[echo '{\"issues\": [{\"message\": \"no docs\"}]}'] no docs",
+                "[broken] exited with exit status: 3: oops",
+            ]
+        );
+        assert_eq!((analysis.total_errors, analysis.total_warnings), (2, 1));
+        assert!(!analysis.all_passed());
+    }
+}
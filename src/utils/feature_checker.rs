@@ -0,0 +1,392 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use crate::error::Result;
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::severity::{Issue, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use crate::utils::toml_ops::{TomlDocument, TomlReader};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// feature audit result for a single crate
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureStatus {
+    /// the crate identifier
+    pub crate_id: CrateId,
+    /// issues found for this crate, one per problem
+    pub issues: Vec<Issue>,
+}
+
+/// feature audit across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeatureAnalysis {
+    /// status for every analyzed crate
+    pub statuses: HashMap<CrateId, FeatureStatus>,
+    /// crates with at least one feature problem
+    pub crates_with_issues: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+impl FeatureAnalysis {
+    /// check if no analyzed crate has a feature problem
+    pub fn all_valid(&self) -> bool {
+        self.crates_with_issues.is_empty()
+    }
+
+    /// display detailed feature issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "feature",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+
+    // a crate whose features could not be audited, recorded so the other
+    // crates are still checked
+    fn push_failure(
+        &mut self,
+        crate_id: &CrateId,
+        severity_config: &SeverityConfig,
+        error: impl std::fmt::Display,
+    ) {
+        let issue = self.counted_issue(
+            severity_config,
+            IssueType::AnalysisFailed,
+            format!("features could not be audited: {}", error),
+        );
+        self.crates_with_issues.push(crate_id.clone());
+        self.statuses.insert(
+            crate_id.clone(),
+            FeatureStatus {
+                crate_id: crate_id.clone(),
+                issues: vec![issue],
+            },
+        );
+    }
+}
+
+impl IssueTotals for FeatureAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// audits `[features]` tables and the features requested from internal crates
+pub struct FeatureChecker;
+
+impl FeatureChecker {
+    /// features a crate offers: its `[features]` keys and its optional dependencies
+    pub fn available_features(document: &TomlDocument) -> BTreeSet<String> {
+        let mut available: BTreeSet<String> = document
+            .get_features()
+            .unwrap_or_default()
+            .into_keys()
+            .collect();
+        available.extend(document.get_optional_dependencies());
+        available
+    }
+
+    /// feature problems in a manifest
+    ///
+    /// `internal` maps the dependency keys that point at workspace crates to
+    /// the features those crates offer, so `dep/feature` entries and
+    /// `features = [..]` on internal dependencies can be checked
+    pub fn audit(
+        document: &TomlDocument,
+        internal: &HashMap<String, (String, BTreeSet<String>)>,
+    ) -> Vec<String> {
+        let features = document.get_features().unwrap_or_default();
+        let optional = document.get_optional_dependencies();
+        let dependencies: BTreeSet<String> = document
+            .all_dependencies()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut problems = Vec::new();
+        let mut exposed = BTreeSet::new();
+
+        let mut names: Vec<&String> = features.keys().collect();
+        names.sort();
+        for feature in names {
+            for entry in &features[feature] {
+                if let Some(dependency) = entry.strip_prefix("dep:") {
+                    exposed.insert(dependency.to_string());
+                    if !optional.iter().any(|o| o == dependency) {
+                        problems.push(format!(
+                            "feature `{}` enables `{}` but {} is not an optional dependency",
+                            feature, entry, dependency
+                        ));
+                    }
+                } else if let Some((dependency, enabled)) = entry.split_once('/') {
+                    let dependency = dependency.trim_end_matches('?');
+                    exposed.insert(dependency.to_string());
+                    if !dependencies.contains(dependency) {
+                        problems.push(format!(
+                            "feature `{}` enables `{}` but {} is not a dependency",
+                            feature, entry, dependency
+                        ));
+                    } else if let Some((target, offered)) = internal.get(dependency)
+                        && !offered.contains(enabled)
+                    {
+                        problems.push(format!(
+                            "feature `{}` enables `{}` but {} has no feature {}",
+                            feature, entry, target, enabled
+                        ));
+                    }
+                } else {
+                    exposed.insert(entry.clone());
+                    if !features.contains_key(entry) && !optional.contains(entry) {
+                        problems.push(format!(
+                            "feature `{}` enables `{}` which is neither a feature nor an optional dependency",
+                            feature, entry
+                        ));
+                    }
+                }
+            }
+        }
+
+        for dependency in &optional {
+            if !exposed.contains(dependency) {
+                problems.push(format!(
+                    "optional dependency {} is not enabled by any feature",
+                    dependency
+                ));
+            }
+        }
+
+        let mut requested: Vec<(String, String, String)> = Vec::new();
+        for (name, dep) in document.all_dependencies() {
+            let Some((target, offered)) = internal.get(&name) else {
+                continue;
+            };
+            let entries = dep.get("features").and_then(toml::Value::as_array);
+            for feature in entries.into_iter().flatten().filter_map(|f| f.as_str()) {
+                if !offered.contains(feature) {
+                    requested.push((name.clone(), feature.to_string(), target.clone()));
+                }
+            }
+        }
+        requested.sort();
+        requested.dedup();
+        for (name, feature, target) in requested {
+            problems.push(format!(
+                "dependency {} enables feature {} which {} does not define",
+                name, feature, target
+            ));
+        }
+
+        problems
+    }
+
+    /// audit the features of `crates`, using the direct severity for crates
+    /// in `directly_affected` and the transitive one for the rest
+    pub fn analyze(
+        graph: &CrateDependencyGraph,
+        crates: &[CrateId],
+        directly_affected: &[CrateId],
+        direct_severity: &SeverityConfig,
+        transitive_severity: &SeverityConfig,
+    ) -> Result<FeatureAnalysis> {
+        let mut analysis = FeatureAnalysis::default();
+        let mut offered_by: HashMap<CrateId, BTreeSet<String>> = HashMap::new();
+
+        for crate_id in crates {
+            let Some(crate_info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let severity_config = if directly_affected.contains(crate_id) {
+                direct_severity
+            } else {
+                transitive_severity
+            };
+            let document = match TomlReader::read_manifest_with_workspace(&crate_info.path) {
+                Ok(document) => document,
+                Err(e) => {
+                    analysis.push_failure(crate_id, severity_config, e);
+                    continue;
+                }
+            };
+
+            // dependency keys pointing at workspace crates, with what they offer
+            let mut internal = HashMap::new();
+            let mut failure = None;
+            for (name, dep) in document.all_dependencies() {
+                if dep.get("path").is_none() {
+                    continue;
+                }
+                let package = dep
+                    .get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(&name);
+                let Some(target) = find_crate(graph, &crate_id.workspace, package) else {
+                    continue;
+                };
+                if !offered_by.contains_key(target) {
+                    let target_info = &graph.crates[target];
+                    match TomlReader::read_manifest_with_workspace(&target_info.path) {
+                        Ok(target_document) => {
+                            offered_by
+                                .insert(target.clone(), Self::available_features(&target_document));
+                        }
+                        Err(e) => {
+                            failure = Some(format!("dependency {}: {}", target.name, e));
+                            break;
+                        }
+                    }
+                }
+                internal.insert(name, (target.name.clone(), offered_by[target].clone()));
+            }
+            if let Some(failure) = failure {
+                analysis.push_failure(crate_id, severity_config, failure);
+                continue;
+            }
+
+            let issues: Vec<Issue> = Self::audit(&document, &internal)
+                .into_iter()
+                .map(|message| {
+                    analysis.counted_issue(severity_config, IssueType::InvalidFeature, message)
+                })
+                .collect();
+            if !issues.is_empty() {
+                analysis.crates_with_issues.push(crate_id.clone());
+            }
+
+            analysis.statuses.insert(
+                crate_id.clone(),
+                FeatureStatus {
+                    crate_id: crate_id.clone(),
+                    issues,
+                },
+            );
+        }
+
+        analysis
+            .crates_with_issues
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    /// audit the features of every crate in the graph
+    pub fn analyze_all(
+        graph: &CrateDependencyGraph,
+        severity_config: &SeverityConfig,
+    ) -> Result<FeatureAnalysis> {
+        let crates: Vec<CrateId> = graph.crates.keys().cloned().collect();
+        Self::analyze(graph, &crates, &crates, severity_config, severity_config)
+    }
+}
+
+// the crate named `name`, preferring one from `workspace`
+fn find_crate<'a>(
+    graph: &'a CrateDependencyGraph,
+    workspace: &str,
+    name: &str,
+) -> Option<&'a CrateId> {
+    let mut candidates = graph.crates.keys().filter(|id| id.name == name);
+    let first = candidates.next()?;
+    if first.workspace == workspace {
+        return Some(first);
+    }
+    Some(
+        candidates
+            .find(|id| id.workspace == workspace)
+            .unwrap_or(first),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn manifest(content: &str) -> TomlDocument {
+        TomlDocument::new("Cargo.toml".to_string(), toml::from_str(content).unwrap())
+    }
+
+    #[test]
+    fn test_audit() {
+        let document = manifest(
+            r#"
+            [package]
+            name = "app"
+            version = "0.1.0"
+
+            [features]
+            default = ["json", "fast", "turbo"]
+            json = ["dep:serde_json", "core/serde"]
+            fast = ["core/simd", "missing/x"]
+            tls = ["dep:rustls"]
+            logging = ["log"]
+
+            [dependencies]
+            core = { path = "../core", features = ["std", "alloc"] }
+            serde_json = { version = "1", optional = true }
+            log = { version = "0.4", optional = true }
+            unused = { version = "1", optional = true }
+            "#,
+        );
+        let internal = HashMap::from([(
+            "core".to_string(),
+            (
+                "core".to_string(),
+                BTreeSet::from(["serde".to_string(), "std".to_string()]),
+            ),
+        )]);
+
+        assert_eq!(
+            FeatureChecker::audit(&document, &internal),
+            vec![
+                "feature `default` enables `turbo` which is neither a feature nor an optional dependency"
+                    .to_string(),
+                "feature `fast` enables `core/simd` but core has no feature simd".to_string(),
+                "feature `fast` enables `missing/x` but missing is not a dependency".to_string(),
+                "feature `tls` enables `dep:rustls` but rustls is not an optional dependency"
+                    .to_string(),
+                "optional dependency unused is not enabled by any feature".to_string(),
+                "dependency core enables feature alloc which core does not define".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_internal_features() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"app\"]\n\n[workspace.dependencies]\ncore = { path = \"core\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("core/Cargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n\n[features]\nstd = []\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ncore = { workspace = true, features = [\"std\", \"serde\"] }\n",
+        )
+        .unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let analysis =
+            FeatureChecker::analyze_all(&graph, &SeverityConfig::default_direct()).unwrap();
+
+        assert_eq!(analysis.crates_with_issues.len(), 1);
+        assert_eq!(analysis.crates_with_issues[0].name, "app");
+        assert_eq!(analysis.total_errors, 1);
+        let app = &analysis.statuses[&analysis.crates_with_issues[0]];
+        assert_eq!(
+            app.issues[0].message,
+            "dependency core enables feature serde which core does not define"
+        );
+    }
+}
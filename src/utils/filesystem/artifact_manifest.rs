@@ -0,0 +1,265 @@
+// hash manifest of release artifacts, for attesting what was shipped
+// entries are keyed by their path relative to the repository and sorted, and
+// the manifest carries no timestamp, so the same artifacts always produce the
+// same bytes and a detached signature over the file stays meaningful
+
+use crate::utils::filesystem::types::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const ARTIFACT_MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    // relative to the manifest root, always '/' separated
+    pub path: String,
+    pub size: u64,
+    pub crc32: String,
+    pub sha256: String,
+    pub sha512: String,
+    pub blake3: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub version: u32,
+    pub entries: Vec<ArtifactEntry>,
+}
+
+// file whose content no longer matches the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactMismatch {
+    pub path: String,
+    // "size" and/or the names of the hashes that differ
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactVerification {
+    pub verified: Vec<String>,
+    pub mismatched: Vec<ArtifactMismatch>,
+    pub missing: Vec<String>,
+}
+
+impl ArtifactVerification {
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl ArtifactEntry {
+    // hash a single file with every algorithm
+    pub fn from_file(path: &Path, root: &Path) -> Result<Self, std::io::Error> {
+        let mut file = FsFile::new(path);
+        file.hash(HashType::ALL)?;
+        let size = std::fs::metadata(path)?.len();
+        let hash = |hash_type| file.hash_value(hash_type).unwrap_or_default().to_string();
+
+        Ok(ArtifactEntry {
+            path: manifest_path(path, root),
+            size,
+            crc32: hash(HashType::CRC32),
+            sha256: hash(HashType::SHA256),
+            sha512: hash(HashType::SHA512),
+            blake3: hash(HashType::BLAKE3),
+        })
+    }
+
+    // fields of `other` that differ from this entry
+    fn differing_fields(&self, other: &ArtifactEntry) -> Vec<String> {
+        [
+            ("size", self.size != other.size),
+            ("crc32", self.crc32 != other.crc32),
+            ("sha256", self.sha256 != other.sha256),
+            ("sha512", self.sha512 != other.sha512),
+            ("blake3", self.blake3 != other.blake3),
+        ]
+        .into_iter()
+        .filter(|(_, differs)| *differs)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
+
+impl ArtifactManifest {
+    // hash every artifact below `root`: files are taken as they are, directories
+    // are walked (build output and .git left out), relative paths resolve
+    // against `root`
+    pub fn create<P: AsRef<Path>>(root: P, artifacts: &[PathBuf]) -> Result<Self, std::io::Error> {
+        let root = root.as_ref();
+        let mut entries = Vec::new();
+        for artifact in artifacts {
+            let path = root.join(artifact);
+            if path.is_dir() {
+                let mut directory = FsDirectory::new_root(path);
+                directory.scan()?;
+                collect_entries(&directory, root, &mut entries)?;
+            } else {
+                entries.push(ArtifactEntry::from_file(&path, root)?);
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries.dedup_by(|a, b| a.path == b.path);
+
+        Ok(ArtifactManifest {
+            version: ARTIFACT_MANIFEST_VERSION,
+            entries,
+        })
+    }
+
+    // expand glob patterns relative to `root` into the artifacts they match,
+    // a pattern matching nothing is an error so a missing build output is noticed
+    pub fn resolve_patterns<P: AsRef<Path>>(
+        root: P,
+        patterns: &[String],
+    ) -> Result<Vec<PathBuf>, std::io::Error> {
+        let root = root.as_ref();
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, e);
+
+        let mut artifacts = Vec::new();
+        for pattern in patterns {
+            let full = root.join(pattern).to_string_lossy().to_string();
+            let matches = glob::glob(&full)
+                .map_err(|e| invalid(format!("invalid artifact pattern '{}': {}", pattern, e)))?;
+            let before = artifacts.len();
+            for entry in matches {
+                let path = entry.map_err(|e| std::io::Error::other(e.to_string()))?;
+                artifacts.push(path.strip_prefix(root).unwrap_or(&path).to_path_buf());
+            }
+            if artifacts.len() == before {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("artifact pattern '{}' matched nothing", pattern),
+                ));
+            }
+        }
+        Ok(artifacts)
+    }
+
+    // pretty json with a trailing newline, the exact bytes to sign
+    pub fn to_json(&self) -> Result<String, std::io::Error> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        std::fs::write(path, self.to_json()?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let manifest: ArtifactManifest = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if manifest.version != ARTIFACT_MANIFEST_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unsupported artifact manifest version {}", manifest.version),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    // re-hash every listed file below `root` and compare it with its entry,
+    // files not in the manifest are not looked at
+    pub fn verify<P: AsRef<Path>>(&self, root: P) -> Result<ArtifactVerification, std::io::Error> {
+        let root = root.as_ref();
+        let mut verification = ArtifactVerification::default();
+        for entry in &self.entries {
+            let path = root.join(&entry.path);
+            if !path.is_file() {
+                verification.missing.push(entry.path.clone());
+                continue;
+            }
+
+            let fields = entry.differing_fields(&ArtifactEntry::from_file(&path, root)?);
+            if fields.is_empty() {
+                verification.verified.push(entry.path.clone());
+            } else {
+                verification.mismatched.push(ArtifactMismatch {
+                    path: entry.path.clone(),
+                    fields,
+                });
+            }
+        }
+        Ok(verification)
+    }
+}
+
+fn manifest_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn collect_entries(
+    directory: &FsDirectory,
+    root: &Path,
+    entries: &mut Vec<ArtifactEntry>,
+) -> Result<(), std::io::Error> {
+    for element in &directory.elements {
+        match element {
+            FsElement::File(file) => {
+                entries.push(ArtifactEntry::from_file(&file.path, root)?);
+            }
+            FsElement::Directory(subdir) => collect_entries(subdir, root, entries)?,
+            FsElement::Symlink(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_verify() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::create_dir_all(root.join("core/src")).unwrap();
+        fs::create_dir_all(root.join("core/target/debug")).unwrap();
+        fs::write(root.join("dist/app-1.0.tar.gz"), b"release bytes").unwrap();
+        fs::write(root.join("core/Cargo.toml"), "[package]\nname = \"core\"\n").unwrap();
+        fs::write(root.join("core/src/lib.rs"), "pub fn one() {}\n").unwrap();
+        fs::write(root.join("core/target/debug/out"), "build output").unwrap();
+
+        let artifacts =
+            ArtifactManifest::resolve_patterns(root, &["dist/*.tar.gz".to_string()]).unwrap();
+        assert_eq!(artifacts, vec![PathBuf::from("dist/app-1.0.tar.gz")]);
+        assert!(ArtifactManifest::resolve_patterns(root, &["dist/*.zip".to_string()]).is_err());
+
+        let manifest =
+            ArtifactManifest::create(root, &[artifacts[0].clone(), PathBuf::from("core")]).unwrap();
+        let paths: Vec<&str> = manifest.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["core/Cargo.toml", "core/src/lib.rs", "dist/app-1.0.tar.gz"]
+        );
+        assert_eq!(manifest.entries[2].size, 13);
+        assert_eq!(manifest.entries[2].sha256.len(), 64);
+
+        // same artifacts, same bytes
+        let again =
+            ArtifactManifest::create(root, &[PathBuf::from("core"), artifacts[0].clone()]).unwrap();
+        assert_eq!(manifest.to_json().unwrap(), again.to_json().unwrap());
+
+        let manifest_file = root.join("manifest.json");
+        manifest.save(&manifest_file).unwrap();
+        let manifest = ArtifactManifest::load(&manifest_file).unwrap();
+        assert!(manifest.verify(root).unwrap().is_ok());
+
+        fs::write(root.join("dist/app-1.0.tar.gz"), b"tampered bytes").unwrap();
+        fs::remove_file(root.join("core/src/lib.rs")).unwrap();
+        let verification = manifest.verify(root).unwrap();
+        assert!(!verification.is_ok());
+        assert_eq!(verification.missing, vec!["core/src/lib.rs"]);
+        assert_eq!(verification.mismatched[0].path, "dist/app-1.0.tar.gz");
+        assert_eq!(
+            verification.mismatched[0].fields,
+            vec!["size", "crc32", "sha256", "sha512", "blake3"]
+        );
+    }
+}
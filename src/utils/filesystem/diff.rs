@@ -0,0 +1,249 @@
+// comparison of two scanned (and ideally hashed) directory trees
+// files are matched by their path relative to the tree root, so snapshots
+// taken from different locations can still be compared
+
+use crate::utils::filesystem::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FsTreeDiff {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub modified: Vec<FsFileDiff>,
+    pub permission_changes: Vec<FsPermissionChange>,
+}
+
+// content change of a file present in both trees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsFileDiff {
+    pub path: PathBuf,
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsPermissionChange {
+    pub path: PathBuf,
+    pub old: Permissions,
+    pub new: Permissions,
+}
+
+impl FsTreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.permission_changes.is_empty()
+    }
+}
+
+impl FsFileDiff {
+    pub fn size_delta(&self) -> Option<i64> {
+        match (self.old_size, self.new_size) {
+            (Some(old), Some(new)) => Some(new as i64 - old as i64),
+            _ => None,
+        }
+    }
+}
+
+impl FsDirectory {
+    // compare self (old) against other (new)
+    // contents are compared using the strongest hash present on both sides,
+    // falling back to size when neither tree was hashed
+    pub fn diff(&self, other: &FsDirectory) -> FsTreeDiff {
+        let old_files = collect_files(self);
+        let new_files = collect_files(other);
+        let mut diff = FsTreeDiff::default();
+
+        for (path, old) in &old_files {
+            let Some(new) = new_files.get(path) else {
+                diff.removed.push(path.clone());
+                continue;
+            };
+
+            let (old_hash, new_hash) = comparable_hashes(old, new);
+            let content_changed = match (&old_hash, &new_hash) {
+                (Some(a), Some(b)) => a != b,
+                _ => matches!((old.size, new.size), (Some(a), Some(b)) if a != b),
+            };
+            if content_changed {
+                diff.modified.push(FsFileDiff {
+                    path: path.clone(),
+                    old_hash,
+                    new_hash,
+                    old_size: old.size,
+                    new_size: new.size,
+                });
+            }
+
+            if let (Some(old_perms), Some(new_perms)) = (&old.permissions, &new.permissions)
+                && old_perms != new_perms
+            {
+                diff.permission_changes.push(FsPermissionChange {
+                    path: path.clone(),
+                    old: old_perms.clone(),
+                    new: new_perms.clone(),
+                });
+            }
+        }
+
+        diff.added = new_files
+            .keys()
+            .filter(|path| !old_files.contains_key(*path))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.owner_read, 'r'),
+            (self.owner_write, 'w'),
+            (self.owner_execute, 'x'),
+            (self.group_read, 'r'),
+            (self.group_write, 'w'),
+            (self.group_execute, 'x'),
+            (self.others_read, 'r'),
+            (self.others_write, 'w'),
+            (self.others_execute, 'x'),
+        ];
+        for (set, c) in flags {
+            write!(f, "{}", if set { c } else { '-' })?;
+        }
+        Ok(())
+    }
+}
+
+// relative path -> file, sorted so diff output is stable
+fn collect_files(root: &FsDirectory) -> BTreeMap<PathBuf, &FsFile> {
+    let mut files = BTreeMap::new();
+    collect_files_recursive(&root.path, root, &mut files);
+    files
+}
+
+fn collect_files_recursive<'a>(
+    root: &Path,
+    directory: &'a FsDirectory,
+    files: &mut BTreeMap<PathBuf, &'a FsFile>,
+) {
+    for element in &directory.elements {
+        match element {
+            FsElement::File(file) => {
+                let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+                files.insert(relative.to_path_buf(), file);
+            }
+            FsElement::Directory(dir) => collect_files_recursive(root, dir, files),
+            FsElement::Symlink(_) => {}
+        }
+    }
+}
+
+fn comparable_hashes(old: &FsFile, new: &FsFile) -> (Option<String>, Option<String>) {
+    let pairs = [
+        (&old.blake3, &new.blake3),
+        (&old.sha512, &new.sha512),
+        (&old.sha256, &new.sha256),
+        (&old.crc32, &new.crc32),
+    ];
+    pairs
+        .into_iter()
+        .find(|(a, b)| a.is_some() && b.is_some())
+        .map(|(a, b)| (a.clone(), b.clone()))
+        .or_else(|| {
+            old.hashes
+                .iter()
+                .find_map(|(name, a)| Some((Some(a.clone()), Some(new.hashes.get(name)?.clone()))))
+        })
+        .unwrap_or((None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn scan_hashed(path: &Path) -> FsDirectory {
+        let mut directory = FsDirectory::new_root(path);
+        directory.scan().unwrap();
+        directory.metadata_scan().unwrap();
+        directory.hash(HashType::BLAKE3).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_diff_identical_trees() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+
+        let old = scan_hashed(temp_dir.path());
+        let new = scan_hashed(temp_dir.path());
+        assert!(old.diff(&new).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diff_across_roots() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let old_dir = TempDir::new().unwrap();
+        let new_dir = TempDir::new().unwrap();
+
+        for dir in [&old_dir, &new_dir] {
+            fs::create_dir(dir.path().join("sub")).unwrap();
+            fs::write(dir.path().join("sub").join("same.txt"), "same").unwrap();
+        }
+        fs::write(old_dir.path().join("changed.txt"), "old").unwrap();
+        fs::write(new_dir.path().join("changed.txt"), "newer").unwrap();
+        fs::write(old_dir.path().join("removed.txt"), "gone").unwrap();
+        fs::write(new_dir.path().join("added.txt"), "fresh").unwrap();
+        fs::set_permissions(
+            new_dir.path().join("sub").join("same.txt"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let diff = scan_hashed(old_dir.path()).diff(&scan_hashed(new_dir.path()));
+
+        assert_eq!(diff.added, vec![PathBuf::from("added.txt")]);
+        assert_eq!(diff.removed, vec![PathBuf::from("removed.txt")]);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].path, Path::new("changed.txt"));
+        assert_eq!(diff.modified[0].size_delta(), Some(2));
+        assert_ne!(diff.modified[0].old_hash, diff.modified[0].new_hash);
+        assert_eq!(diff.permission_changes.len(), 1);
+        assert_eq!(diff.permission_changes[0].path, Path::new("sub/same.txt"));
+        assert_eq!(diff.permission_changes[0].new.to_string(), "rwxr-xr-x");
+    }
+
+    #[test]
+    fn test_diff_without_hashes_uses_size() {
+        let old_dir = TempDir::new().unwrap();
+        let new_dir = TempDir::new().unwrap();
+        fs::write(old_dir.path().join("f.txt"), "abc").unwrap();
+        fs::write(new_dir.path().join("f.txt"), "xyz").unwrap();
+        fs::write(old_dir.path().join("g.txt"), "abc").unwrap();
+        fs::write(new_dir.path().join("g.txt"), "abcd").unwrap();
+
+        let mut old = FsDirectory::new_root(old_dir.path());
+        old.scan().unwrap();
+        old.metadata_scan().unwrap();
+        let mut new = FsDirectory::new_root(new_dir.path());
+        new.scan().unwrap();
+        new.metadata_scan().unwrap();
+
+        // same size, no hashes: indistinguishable
+        let diff = old.diff(&new);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].path, Path::new("g.txt"));
+        assert!(diff.modified[0].old_hash.is_none());
+    }
+}
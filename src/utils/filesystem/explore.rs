@@ -1,52 +1,53 @@
 use crate::utils::alt::{Evaluable, LogicExpr};
+use crate::utils::filesystem::platform;
 use crate::utils::filesystem::predicates::{PredicateContext, PredicateError};
 use crate::utils::filesystem::types::*;
+use crate::utils::filesystem::walk::Walk;
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct FilesystemExplorer {
-    pub root_path: String,
+    pub root_path: PathBuf,
+    pub options: ScanOptions,
 }
 
 impl FilesystemExplorer {
-    pub fn new(root_path: String) -> Self {
-        FilesystemExplorer { root_path }
+    // scans skip build output and .git, the target directory configured
+    // below `root_path` included
+    pub fn new<P: Into<PathBuf>>(root_path: P) -> Self {
+        let root_path = root_path.into();
+        let options = ScanOptions::default().skip_build_dirs_of(&root_path);
+        FilesystemExplorer { root_path, options }
     }
 
-    pub fn scan_directory(&self, path: &str) -> Result<FsDirectory, std::io::Error> {
-        let mut directory = FsDirectory::new(path.to_string());
-        Self::scan_directory_recursive(&mut directory)?;
-        Ok(directory)
+    pub fn with_options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
     }
 
-    fn scan_directory_recursive(directory: &mut FsDirectory) -> Result<(), std::io::Error> {
-        let entries = fs::read_dir(&directory.path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            let path_str = path.to_string_lossy().to_string();
-
-            if path.is_file() {
-                let file = FsFile::new(path_str);
-                directory.elements.push(FsElement::File(file));
-            } else if path.is_dir() {
-                let mut subdir = FsDirectory::new(path_str);
-                Self::scan_directory_recursive(&mut subdir)?;
-                directory.elements.push(FsElement::Directory(subdir));
-            }
-        }
-
-        Ok(())
+    pub fn scan_directory<P: AsRef<Path>>(&self, path: P) -> Result<FsDirectory, std::io::Error> {
+        let mut directory = FsDirectory::new(path.as_ref());
+        directory.scan_with_options(&self.options)?;
+        Ok(directory)
     }
 
     pub fn scan_from_root(&self) -> Result<FsDirectory, std::io::Error> {
         let mut directory = FsDirectory::new_root(self.root_path.clone());
-        Self::scan_directory_recursive(&mut directory)?;
+        directory.scan_with_options(&self.options)?;
         Ok(directory)
     }
 
-    pub fn find_files_by_extension(&self, directory: &FsDirectory, extension: &str) -> Vec<String> {
+    // the tree below `root_path` as it is read, without building it in memory
+    pub fn walk(&self) -> Walk {
+        Walk::new(self.root_path.clone(), self.options.clone())
+    }
+
+    pub fn find_files_by_extension(
+        &self,
+        directory: &FsDirectory,
+        extension: &str,
+    ) -> Vec<PathBuf> {
         let mut files = Vec::new();
         Self::find_files_by_extension_recursive(directory, extension, &mut files);
         files
@@ -55,20 +56,19 @@ impl FilesystemExplorer {
     fn find_files_by_extension_recursive(
         directory: &FsDirectory,
         extension: &str,
-        files: &mut Vec<String>,
+        files: &mut Vec<PathBuf>,
     ) {
         for element in &directory.elements {
             match element {
                 FsElement::File(file) => {
-                    if let Some(path) = Path::new(&file.path).extension()
-                        && path.to_string_lossy() == extension
-                    {
+                    if file.path.extension().is_some_and(|e| e == extension) {
                         files.push(file.path.clone());
                     }
                 }
                 FsElement::Directory(dir) => {
                     Self::find_files_by_extension_recursive(dir, extension, files);
                 }
+                FsElement::Symlink(_) => {}
             }
         }
     }
@@ -92,6 +92,7 @@ impl FilesystemExplorer {
                     *dir_count += 1;
                     Self::count_elements_recursive(dir, file_count, dir_count);
                 }
+                FsElement::Symlink(_) => {}
             }
         }
     }
@@ -104,7 +105,14 @@ impl FilesystemExplorer {
         T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
     {
         let mut directory = FsDirectory::new_root(self.root_path.clone());
-        Self::scan_directory_with_predicate(&mut directory, &predicate)?;
+        let mut ancestors = HashSet::new();
+        ancestors.insert(platform::directory_identity(&directory.path)?);
+        Self::scan_directory_with_predicate(
+            &mut directory,
+            &predicate,
+            &self.options,
+            &mut ancestors,
+        )?;
         Ok(directory)
     }
 
@@ -155,15 +163,20 @@ impl FilesystemExplorer {
                         }
                     }
                 }
+                // predicates only match files and directories
+                FsElement::Symlink(_) => {}
             }
         }
 
         Ok(filtered)
     }
 
+    // symlinks are only looked at with SymlinkPolicy::Follow, predicates never see the link itself
     fn scan_directory_with_predicate<T>(
         directory: &mut FsDirectory,
         predicate: &LogicExpr<T>,
+        options: &ScanOptions,
+        ancestors: &mut HashSet<(u64, u64)>,
     ) -> Result<(), std::io::Error>
     where
         T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
@@ -173,35 +186,72 @@ impl FilesystemExplorer {
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
-            let path_str = path.to_string_lossy().to_string();
+            let is_symlink = entry.file_type()?.is_symlink();
+
+            // links are kept like FsDirectory::scan_entries keeps them
+            if is_symlink {
+                match options.symlink_policy {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Record => {
+                        directory
+                            .elements
+                            .push(FsElement::Symlink(FsSymlink::read(path)?));
+                        continue;
+                    }
+                    SymlinkPolicy::Follow => {}
+                }
+            }
 
             if path.is_file() {
-                let mut file = FsFile::new(path_str);
+                let mut file = FsFile::new(path);
                 let _ = file.metadata_scan(); // populate file metadata like extension and name
                 let context = PredicateContext::new(directory.clone()).with_file(file.clone());
 
                 if let Ok(matches) = predicate.evaluate(&context)
                     && matches
                 {
+                    if let Some(hash_type) = options.hash {
+                        file.hash(hash_type)?;
+                    }
                     directory.elements.push(FsElement::File(file));
                 }
             } else if path.is_dir() {
-                let mut subdir_for_eval = FsDirectory::new(path_str.clone());
-                // scan just the top level to check if directory contains required files
-                let entries = fs::read_dir(&path_str)?;
-                for entry in entries {
-                    let entry = entry?;
-                    let entry_path = entry.path();
-                    let entry_path_str = entry_path.to_string_lossy().to_string();
+                if options.is_skipped(&path) {
+                    continue;
+                }
+                let identity = platform::directory_identity(&path)?;
+                if !ancestors.insert(identity) {
+                    // a link back into an ancestor
+                    if is_symlink {
+                        directory
+                            .elements
+                            .push(FsElement::Symlink(FsSymlink::read(path)?));
+                    }
+                    continue;
+                }
 
+                let mut subdir_for_eval = FsDirectory::new(path.clone());
+                // scan just the top level to check if directory contains required
+                // files, directories below are listed without their contents
+                let entries = fs::read_dir(&path)?;
+                for entry in entries {
+                    let entry_path = entry?.path();
                     if entry_path.is_file() {
-                        let file = FsFile::new(entry_path_str);
-                        subdir_for_eval.elements.push(FsElement::File(file));
+                        subdir_for_eval
+                            .elements
+                            .push(FsElement::File(FsFile::new(entry_path)));
+                    } else if entry_path.is_dir() && !options.is_skipped(&entry_path) {
+                        subdir_for_eval
+                            .elements
+                            .push(FsElement::Directory(FsDirectory::new(entry_path)));
                     }
                 }
 
-                let mut subdir = FsDirectory::new(path_str);
-                Self::scan_directory_with_predicate(&mut subdir, predicate)?;
+                let mut subdir = FsDirectory::new(path);
+                let result =
+                    Self::scan_directory_with_predicate(&mut subdir, predicate, options, ancestors);
+                ancestors.remove(&identity);
+                result?;
 
                 let context = PredicateContext::new(subdir_for_eval);
                 if let Ok(matches) = predicate.evaluate(&context)
@@ -209,9 +259,17 @@ impl FilesystemExplorer {
                 {
                     directory.elements.push(FsElement::Directory(subdir));
                 }
+            } else if is_symlink {
+                // dangling link
+                directory
+                    .elements
+                    .push(FsElement::Symlink(FsSymlink::read(path)?));
             }
         }
 
+        if let Some(hash_type) = options.hash {
+            directory.update_aggregate_hashes(hash_type);
+        }
         Ok(())
     }
 
@@ -219,7 +277,7 @@ impl FilesystemExplorer {
         &self,
         directory: &FsDirectory,
         predicate: &LogicExpr<T>,
-    ) -> Result<Vec<String>, PredicateError>
+    ) -> Result<Vec<PathBuf>, PredicateError>
     where
         T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
     {
@@ -231,7 +289,7 @@ impl FilesystemExplorer {
     fn find_files_matching_recursive<T>(
         directory: &FsDirectory,
         predicate: &LogicExpr<T>,
-        files: &mut Vec<String>,
+        files: &mut Vec<PathBuf>,
     ) -> Result<(), PredicateError>
     where
         T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
@@ -251,10 +309,61 @@ impl FilesystemExplorer {
                 FsElement::Directory(dir) => {
                     Self::find_files_matching_recursive(dir, predicate, files)?;
                 }
+                FsElement::Symlink(_) => {}
             }
         }
         Ok(())
     }
+
+    // keeps only the files the predicate accepts, directories left empty are dropped;
+    // unlike filter_directory_with_predicate the predicate never sees a directory context
+    pub fn retain_files_matching<T>(
+        &self,
+        directory: &FsDirectory,
+        predicate: &LogicExpr<T>,
+    ) -> Result<FsDirectory, PredicateError>
+    where
+        T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
+    {
+        Self::retain_files_recursive(directory, predicate)
+    }
+
+    fn retain_files_recursive<T>(
+        directory: &FsDirectory,
+        predicate: &LogicExpr<T>,
+    ) -> Result<FsDirectory, PredicateError>
+    where
+        T: Evaluable<Context = PredicateContext, Error = PredicateError> + Clone,
+    {
+        let mut filtered = directory.clone();
+        filtered.elements.clear();
+
+        for element in &directory.elements {
+            match element {
+                FsElement::File(file) => {
+                    let mut file_with_metadata = file.clone();
+                    let _ = file_with_metadata.metadata_scan(); // ensure metadata is populated
+                    let context =
+                        PredicateContext::new(directory.clone()).with_file(file_with_metadata);
+
+                    if predicate.evaluate(&context)? {
+                        filtered.elements.push(element.clone());
+                    }
+                }
+                FsElement::Directory(dir) => {
+                    let filtered_subdir = Self::retain_files_recursive(dir, predicate)?;
+                    if !filtered_subdir.elements.is_empty() {
+                        filtered
+                            .elements
+                            .push(FsElement::Directory(filtered_subdir));
+                    }
+                }
+                FsElement::Symlink(_) => {}
+            }
+        }
+
+        Ok(filtered)
+    }
 }
 
 #[cfg(test)]
@@ -289,7 +398,7 @@ mod tests {
     #[test]
     fn test_filesystem_explorer_new() {
         let explorer = FilesystemExplorer::new("/test/path".to_string());
-        assert_eq!(explorer.root_path, "/test/path");
+        assert_eq!(explorer.root_path, Path::new("/test/path"));
     }
 
     #[test]
@@ -359,7 +468,7 @@ mod tests {
         // check all expected files are found
         let paths: Vec<String> = rs_files
             .iter()
-            .map(|p| p.split('/').next_back().unwrap().to_string())
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
             .collect();
         assert!(paths.contains(&"top.rs".to_string()));
         assert!(paths.contains(&"mid.rs".to_string()));
@@ -481,6 +590,318 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_with_owner_predicate() {
+        use crate::utils::alt::LogicExpr;
+        use crate::utils::filesystem::ownership::user_name;
+        use crate::utils::filesystem::predicates::{FilePredicate, OwnerPredicate};
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        fs::write(temp_dir.path().join("sub").join("b.txt"), "b").unwrap();
+        let me = user_name(fs::metadata(temp_dir.path()).unwrap().uid());
+
+        let explorer = FilesystemExplorer::new(temp_path);
+        let owned = explorer
+            .scan_with_predicate(LogicExpr::Leaf(FilePredicate::Owner(OwnerPredicate::user(
+                me.clone(),
+            ))))
+            .unwrap();
+        assert_eq!(explorer.count_elements(&owned), (2, 1));
+
+        // audits look for the opposite: anything not owned by the expected user
+        let unexpected = explorer
+            .scan_with_predicate(LogicExpr::Not(Box::new(LogicExpr::Leaf(
+                FilePredicate::Owner(OwnerPredicate::user(me)),
+            ))))
+            .unwrap();
+        assert_eq!(explorer.count_elements(&unexpected).0, 0);
+    }
+
+    #[test]
+    fn test_scan_with_file_type_predicate() {
+        use crate::utils::alt::LogicExpr;
+        use crate::utils::filesystem::predicates::{FilePredicate, FileTypePredicate};
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        // extension says text, content says png
+        fs::write(
+            temp_dir.path().join("logo.txt"),
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0],
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "plain notes").unwrap();
+
+        let explorer = FilesystemExplorer::new(temp_path);
+        let images = explorer
+            .scan_with_predicate(LogicExpr::Leaf(FilePredicate::FileType(
+                FileTypePredicate::category("image"),
+            )))
+            .unwrap();
+        let files = explorer.find_files_by_extension(&images, "txt");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("logo.txt"));
+
+        let mut scanned = explorer.scan_from_root().unwrap();
+        scanned.type_scan();
+        let text = explorer
+            .filter_directory_with_predicate(
+                &scanned,
+                &LogicExpr::Leaf(FilePredicate::FileType(FileTypePredicate::mime(
+                    "text/plain",
+                ))),
+            )
+            .unwrap();
+        let files = explorer.find_files_by_extension(&text, "txt");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("notes.txt"));
+    }
+
+    #[test]
+    fn test_scan_with_content_matches_predicate() {
+        use crate::utils::alt::LogicExpr;
+        use crate::utils::filesystem::predicates::{
+            FileContentMatchesPredicate, FileExtensionPredicate, FileNamePredicate, FilePredicate,
+            PredicateError,
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        fs::write(
+            temp_dir.path().join("raw.rs"),
+            "fn f() { unsafe { core::hint::unreachable_unchecked() } }",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("safe.rs"), "fn f() {}").unwrap();
+        fs::write(temp_dir.path().join("unsafe.md"), "unsafe notes").unwrap();
+        fs::create_dir(temp_dir.path().join("member")).unwrap();
+        fs::write(
+            temp_dir.path().join("member").join("Cargo.toml"),
+            "[dependencies]\ncore = { path = \"../core\" }\n",
+        )
+        .unwrap();
+
+        let explorer = FilesystemExplorer::new(temp_path);
+
+        // rust files containing unsafe
+        let unsafe_rust = LogicExpr::and(
+            LogicExpr::Leaf(FilePredicate::Extension(FileExtensionPredicate::new("rs"))),
+            LogicExpr::Leaf(FilePredicate::FileContentMatches(
+                FileContentMatchesPredicate::new(r"\bunsafe\b").unwrap(),
+            )),
+        );
+        let result = explorer.scan_with_predicate(unsafe_rust).unwrap();
+        let files = explorer.find_files_by_extension(&result, "rs");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("raw.rs"));
+        assert!(explorer.find_files_by_extension(&result, "md").is_empty());
+
+        // manifests with relative path dependencies
+        let path_deps = LogicExpr::and(
+            LogicExpr::Leaf(FilePredicate::FileName(FileNamePredicate::exact("Cargo"))),
+            LogicExpr::Leaf(FilePredicate::FileContentMatches(
+                FileContentMatchesPredicate::new(r#"path\s*=\s*"\.\."#).unwrap(),
+            )),
+        );
+        let result = explorer.scan_with_predicate(path_deps).unwrap();
+        assert_eq!(explorer.find_files_by_extension(&result, "toml").len(), 1);
+
+        // files above the size limit are never read
+        let limited = LogicExpr::Leaf(FilePredicate::FileContentMatches(
+            FileContentMatchesPredicate::new("unsafe")
+                .unwrap()
+                .max_size(4),
+        ));
+        let result = explorer.scan_with_predicate(limited).unwrap();
+        assert_eq!(explorer.count_elements(&result).0, 0);
+
+        assert!(matches!(
+            FileContentMatchesPredicate::new("(unclosed"),
+            Err(PredicateError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_scan_with_path_glob_predicate() {
+        use crate::utils::alt::LogicExpr;
+        use crate::utils::filesystem::predicates::{FilePredicate, PathGlobPredicate};
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(src.join("nested").join("generated")).unwrap();
+        fs::write(src.join("lib.rs"), "").unwrap();
+        fs::write(src.join("nested").join("mod.rs"), "").unwrap();
+        fs::write(src.join("nested").join("generated").join("out.rs"), "").unwrap();
+        fs::write(src.join("notes.md"), "").unwrap();
+        fs::write(temp_dir.path().join("build.rs"), "").unwrap();
+
+        let explorer = FilesystemExplorer::new(temp_path.clone());
+        let glob = PathGlobPredicate::new(["src/**/*.rs", "!**/generated/**"]).unwrap();
+        let result = explorer
+            .scan_with_predicate(LogicExpr::Leaf(FilePredicate::PathGlob(glob.clone())))
+            .unwrap();
+
+        let mut names: Vec<String> = explorer
+            .find_files_by_extension(&result, "rs")
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["lib.rs", "mod.rs"]);
+
+        // `*` does not cross directories
+        let top_level = PathGlobPredicate::new(["*.rs"])
+            .unwrap()
+            .relative_to(&temp_path);
+        assert!(top_level.matches_path(format!("{}/build.rs", temp_path)));
+        assert!(!top_level.matches_path(format!("{}/src/lib.rs", temp_path)));
+
+        // exclude-only keeps everything else
+        let not_docs = PathGlobPredicate::new(["!*.md"]).unwrap();
+        assert!(not_docs.matches_path("/repo/src/lib.rs"));
+        assert!(!not_docs.matches_path("/repo/src/notes.md"));
+
+        assert!(PathGlobPredicate::new(["src/[lib.rs"]).is_err());
+    }
+
+    #[test]
+    fn test_scan_excluding_with_and_not() {
+        use crate::utils::alt::ExprBuilder;
+        use crate::utils::filesystem::predicates::{
+            FileExtensionPredicate, FilePredicate, PathGlobPredicate,
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::create_dir(temp_dir.path().join("tests")).unwrap();
+        fs::write(temp_dir.path().join("src").join("lib.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("tests").join("it.rs"), "").unwrap();
+
+        // all .rs files except those under tests/
+        let expr = ExprBuilder::new(FilePredicate::Extension(FileExtensionPredicate::new("rs")))
+            .and_not(FilePredicate::PathGlob(
+                PathGlobPredicate::new(["tests/**"])
+                    .unwrap()
+                    .relative_to(&temp_path),
+            ))
+            .build();
+
+        let explorer = FilesystemExplorer::new(temp_path);
+        let result = explorer.scan_with_predicate(expr).unwrap();
+        let files = explorer.find_files_by_extension(&result, "rs");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_retain_files_matching_parsed_expression() {
+        use crate::utils::alt::LogicExpr;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        fs::create_dir_all(temp_dir.path().join("src").join("nested")).unwrap();
+        fs::create_dir(temp_dir.path().join("tests")).unwrap();
+        fs::write(temp_dir.path().join("src").join("lib.rs"), "x".repeat(2048)).unwrap();
+        fs::write(temp_dir.path().join("src").join("tiny.rs"), "").unwrap();
+        fs::write(
+            temp_dir.path().join("src/nested/notes.md"),
+            "x".repeat(2048),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("tests").join("it.rs"),
+            "x".repeat(2048),
+        )
+        .unwrap();
+
+        let expr = LogicExpr::parse("ext == 'rs' && size > 1k && !path ~ 'tests/**'").unwrap();
+        let explorer = FilesystemExplorer::new(temp_path);
+        let tree = explorer.scan_from_root().unwrap();
+        let filtered = explorer.retain_files_matching(&tree, &expr).unwrap();
+
+        // src/nested and tests end up empty and are dropped
+        assert_eq!(explorer.count_elements(&filtered), (1, 1));
+        let files = explorer.find_files_by_extension(&filtered, "rs");
+        assert!(files[0].ends_with("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_filter_directories_by_manifest_and_files() {
+        use crate::utils::alt::ExprBuilder;
+        use crate::utils::filesystem::predicates::{
+            DirectoryAnyFilePredicate, DirectoryTomlPredicate, FileAgePredicate,
+            FileExtensionPredicate, FilePredicate,
+        };
+        use crate::utils::toml_ops::{TomlPredicate, VersionMatchesPredicate};
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().to_string_lossy().to_string();
+        // rust sources below the crate root count, build output does not
+        for (name, version, file) in [
+            ("unstable", "0.3.0", "src/lib.rs"),
+            ("stable", "1.0.0", "src/lib.rs"),
+            ("no_rust", "0.1.0", "target/debug/build/out.rs"),
+        ] {
+            let dir = temp_dir.path().join(name);
+            fs::create_dir(&dir).unwrap();
+            fs::write(
+                dir.join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"{}\"\n",
+                    name, version
+                ),
+            )
+            .unwrap();
+            fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        // pre-1.0 crates with recently touched rust files, in a single pass
+        let expr = ExprBuilder::new(FilePredicate::from(DirectoryTomlPredicate::cargo_manifest(
+            TomlPredicate::VersionMatches(VersionMatchesPredicate::starts_with("0.")),
+        )))
+        .and(FilePredicate::from(DirectoryAnyFilePredicate::new(
+            ExprBuilder::new(FilePredicate::from(FileExtensionPredicate::new("rs")))
+                .and(FilePredicate::from(FileAgePredicate::newer_than(
+                    Duration::from_secs(3600),
+                )))
+                .build(),
+        )))
+        .build();
+
+        let explorer = FilesystemExplorer::new(temp_path);
+        let tree = explorer.scan_from_root().unwrap();
+        let filtered = explorer
+            .filter_directory_with_predicate(&tree, &expr)
+            .unwrap();
+
+        let dirs = |tree: &FsDirectory| -> Vec<_> {
+            tree.elements
+                .iter()
+                .filter_map(|e| match e {
+                    FsElement::Directory(d) => Some(d.path.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let filtered = dirs(&filtered);
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered[0].ends_with("unstable"));
+
+        // a scan lists directories without their contents, those are read too
+        let scanned = dirs(&explorer.scan_with_predicate(expr).unwrap());
+        assert_eq!(scanned.len(), 1);
+        assert!(scanned[0].ends_with("unstable"));
+    }
+
     #[test]
     fn test_scan_with_or_predicate() {
         use crate::utils::alt::LogicExpr;
@@ -503,7 +924,7 @@ mod tests {
 
         // should contain .rs and .md files
         assert_eq!(result.elements.len(), 2);
-        let paths: Vec<String> = result
+        let paths: Vec<PathBuf> = result
             .elements
             .iter()
             .filter_map(|e| match e {
@@ -565,7 +986,7 @@ mod tests {
         for element in &result.elements {
             match element {
                 FsElement::File(f) => {
-                    if f.path.ends_with(".rs") {
+                    if f.path.extension().is_some_and(|e| e == "rs") {
                         found_rs_files += 1;
                     }
                 }
@@ -580,12 +1001,13 @@ mod tests {
                     // count nested .rs files
                     for nested in &dir.elements {
                         if let FsElement::File(f) = nested
-                            && f.path.ends_with(".rs")
+                            && f.path.extension().is_some_and(|e| e == "rs")
                         {
                             found_rs_files += 1;
                         }
                     }
                 }
+                FsElement::Symlink(_) => {}
             }
         }
 
@@ -790,4 +1212,178 @@ version = "2.1.0"
         let nomatch_result = explorer.scan_with_predicate(nomatch_predicate).unwrap();
         assert_eq!(nomatch_result.elements.len(), 0);
     }
+
+    #[cfg(unix)]
+    fn symlink_fixture() -> TempDir {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("real")).unwrap();
+        fs::write(temp_dir.path().join("real").join("file.txt"), "content").unwrap();
+        symlink(
+            temp_dir.path().join("real").join("file.txt"),
+            temp_dir.path().join("link.txt"),
+        )
+        .unwrap();
+        // points back at the root, following it blindly never terminates
+        symlink(temp_dir.path(), temp_dir.path().join("real").join("loop")).unwrap();
+        temp_dir
+    }
+
+    #[cfg(unix)]
+    fn collect_symlinks(directory: &FsDirectory, links: &mut Vec<FsSymlink>) {
+        for element in &directory.elements {
+            match element {
+                FsElement::Symlink(link) => links.push(link.clone()),
+                FsElement::Directory(dir) => collect_symlinks(dir, links),
+                FsElement::File(_) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_skips_build_output() {
+        use crate::utils::filesystem::predicates::{FileExtensionPredicate, FilePredicate};
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in [
+            "src/target",
+            "target/debug",
+            ".git",
+            "out/release",
+            "crates/core/target",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "src/lib.rs",
+            "src/target/mod.rs",
+            "target/debug/build.rs",
+            ".git/hook.rs",
+            "out/release/gen.rs",
+            "crates/core/target/gen.rs",
+        ] {
+            fs::write(root.join(file), "fn main() {}").unwrap();
+        }
+        // `target` is build output only beside a manifest, a module of that
+        // name under src/ is scanned
+        for manifest in ["Cargo.toml", "crates/core/Cargo.toml"] {
+            fs::write(root.join(manifest), "").unwrap();
+        }
+        fs::create_dir(root.join(".cargo")).unwrap();
+        fs::write(
+            root.join(".cargo/config.toml"),
+            "[build]\ntarget-dir = \"out\"\n",
+        )
+        .unwrap();
+
+        let explorer = FilesystemExplorer::new(root);
+        let scanned = explorer.scan_from_root().unwrap();
+        let mut rs_files = explorer.find_files_by_extension(&scanned, "rs");
+        rs_files.sort();
+        assert_eq!(rs_files.len(), 2);
+        assert!(rs_files[0].ends_with("src/lib.rs"));
+        assert!(rs_files[1].ends_with("src/target/mod.rs"));
+
+        let rs = LogicExpr::Leaf(FilePredicate::Extension(FileExtensionPredicate::new("rs")));
+        let matched = explorer.scan_with_predicate(rs).unwrap();
+        assert_eq!(explorer.count_elements(&matched).0, 2);
+
+        let everything = FilesystemExplorer::new(root)
+            .with_options(ScanOptions::new().skip_nothing())
+            .scan_from_root()
+            .unwrap();
+        assert_eq!(explorer.find_files_by_extension(&everything, "rs").len(), 6);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy_skip() {
+        let temp_dir = symlink_fixture();
+        let explorer = FilesystemExplorer::new(temp_dir.path())
+            .with_options(ScanOptions::new().symlink_policy(SymlinkPolicy::Skip));
+        let result = explorer.scan_from_root().unwrap();
+
+        let (file_count, dir_count) = explorer.count_elements(&result);
+        assert_eq!(file_count, 1); // real/file.txt
+        assert_eq!(dir_count, 1); // real
+
+        let mut links = Vec::new();
+        collect_symlinks(&result, &mut links);
+        assert!(links.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy_record() {
+        let temp_dir = symlink_fixture();
+        let explorer = FilesystemExplorer::new(temp_dir.path())
+            .with_options(ScanOptions::new().symlink_policy(SymlinkPolicy::Record));
+        let result = explorer.scan_from_root().unwrap();
+
+        let (file_count, dir_count) = explorer.count_elements(&result);
+        assert_eq!(file_count, 1);
+        assert_eq!(dir_count, 1);
+
+        let mut links = Vec::new();
+        collect_symlinks(&result, &mut links);
+        assert_eq!(links.len(), 2);
+
+        let file_link = links.iter().find(|l| l.path.ends_with("link.txt")).unwrap();
+        assert!(file_link.target.ends_with("real/file.txt"));
+        let loop_link = links.iter().find(|l| l.path.ends_with("loop")).unwrap();
+        assert_eq!(loop_link.target, temp_dir.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy_follow_stops_at_cycle() {
+        let temp_dir = symlink_fixture();
+        let explorer = FilesystemExplorer::new(temp_dir.path());
+        assert_eq!(explorer.options.symlink_policy, SymlinkPolicy::Follow);
+        let result = explorer.scan_from_root().unwrap();
+
+        // link.txt is followed as a file, the loop is kept as a link
+        let (file_count, dir_count) = explorer.count_elements(&result);
+        assert_eq!(file_count, 2);
+        assert_eq!(dir_count, 1);
+
+        let mut links = Vec::new();
+        collect_symlinks(&result, &mut links);
+        assert_eq!(links.len(), 1);
+        assert!(links[0].path.ends_with("loop"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_predicate_scan_keeps_links_like_full_scan() {
+        use crate::utils::filesystem::predicates::{FileExtensionPredicate, FilePredicate};
+
+        let temp_dir = symlink_fixture();
+        // matches every file
+        let everything = LogicExpr::Not(Box::new(LogicExpr::Leaf(FilePredicate::Extension(
+            FileExtensionPredicate::new("none"),
+        ))));
+        for policy in [SymlinkPolicy::Follow, SymlinkPolicy::Record] {
+            let explorer = FilesystemExplorer::new(temp_dir.path())
+                .with_options(ScanOptions::new().symlink_policy(policy));
+            let full = explorer.scan_from_root().unwrap();
+            let filtered = explorer.scan_with_predicate(everything.clone()).unwrap();
+            assert_eq!(
+                explorer.count_elements(&full),
+                explorer.count_elements(&filtered)
+            );
+
+            let paths = |directory: &FsDirectory| {
+                let mut links = Vec::new();
+                collect_symlinks(directory, &mut links);
+                let mut paths: Vec<PathBuf> = links.into_iter().map(|link| link.path).collect();
+                paths.sort();
+                paths
+            };
+            assert_eq!(paths(&full), paths(&filtered));
+            assert!(paths(&filtered).iter().any(|path| path.ends_with("loop")));
+        }
+    }
 }
@@ -0,0 +1,389 @@
+// incremental hashing, files whose size and mtime are unchanged reuse
+// previously computed hashes instead of being read again
+
+use crate::utils::filesystem::platform::xattrs;
+use crate::utils::filesystem::types::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+// extended attribute prefix used by HashCacheSource::ExtAttributes
+pub const HASH_XATTR_PREFIX: &str = "user.deptrack.";
+
+#[derive(Debug, Clone)]
+pub struct CachedHash {
+    pub size: u64,
+    pub modified: String,
+    pub crc32: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    pub blake3: Option<String>,
+    pub hashes: BTreeMap<String, String>, // as FsFile::hashes
+}
+
+// previously computed hashes keyed by file path
+#[derive(Debug, Clone, Default)]
+pub struct HashCache {
+    pub entries: HashMap<PathBuf, CachedHash>,
+}
+
+// where cached hashes come from
+#[derive(Debug, Clone)]
+pub enum HashCacheSource<'a> {
+    Snapshot(&'a HashCache),
+    ExtAttributes, // stored on the files themselves, written back after hashing
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HashCacheStats {
+    pub reused: usize,
+    pub computed: usize,
+}
+
+impl CachedHash {
+    fn hash_value(&self, hash_type: HashType) -> Option<&str> {
+        match hash_type {
+            HashType::CRC32 => self.crc32.as_deref(),
+            HashType::SHA256 => self.sha256.as_deref(),
+            HashType::SHA512 => self.sha512.as_deref(),
+            HashType::BLAKE3 => self.blake3.as_deref(),
+            HashType::ALL | HashType::FAST => None,
+            other => self.hashes.get(other.name()).map(String::as_str),
+        }
+    }
+
+    fn covers(&self, hash_type: HashType) -> bool {
+        hash_type
+            .components()
+            .iter()
+            .all(|single| self.hash_value(*single).is_some())
+    }
+}
+
+impl HashCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // collect every file of a previously hashed tree that has size and mtime recorded
+    pub fn from_snapshot(directory: &FsDirectory) -> Self {
+        let mut cache = HashCache::new();
+        cache.collect(directory);
+        cache
+    }
+
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Ok(Self::from_snapshot(&FsDirectory::load_snapshot(path)?))
+    }
+
+    fn collect(&mut self, directory: &FsDirectory) {
+        for element in &directory.elements {
+            match element {
+                FsElement::File(file) => {
+                    if let (Some(size), Some(modified)) = (file.size, &file.modified) {
+                        self.entries.insert(
+                            file.path.clone(),
+                            CachedHash {
+                                size,
+                                modified: modified.clone(),
+                                crc32: file.crc32.clone(),
+                                sha256: file.sha256.clone(),
+                                sha512: file.sha512.clone(),
+                                blake3: file.blake3.clone(),
+                                hashes: file.hashes.clone(),
+                            },
+                        );
+                    }
+                }
+                FsElement::Directory(dir) => self.collect(dir),
+                FsElement::Symlink(_) => {}
+            }
+        }
+    }
+}
+
+impl FsDirectory {
+    // like hash(), but skips reading files whose size and mtime match the cache
+    pub fn hash_cached(
+        &mut self,
+        hash_type: HashType,
+        source: &HashCacheSource,
+    ) -> Result<HashCacheStats, std::io::Error> {
+        let mut stats = HashCacheStats::default();
+        self.hash_cached_recursive(hash_type, source, &mut stats)?;
+        Ok(stats)
+    }
+
+    fn hash_cached_recursive(
+        &mut self,
+        hash_type: HashType,
+        source: &HashCacheSource,
+        stats: &mut HashCacheStats,
+    ) -> Result<(), std::io::Error> {
+        for element in &mut self.elements {
+            match element {
+                FsElement::File(file) => {
+                    if file.hash_cached(hash_type, source)? {
+                        stats.reused += 1;
+                    } else {
+                        stats.computed += 1;
+                    }
+                }
+                FsElement::Directory(dir) => {
+                    dir.hash_cached_recursive(hash_type, source, stats)?;
+                }
+                FsElement::Symlink(_) => {}
+            }
+        }
+
+        self.update_aggregate_hashes(hash_type);
+        Ok(())
+    }
+}
+
+impl FsFile {
+    // returns true when the hashes were taken from the cache
+    pub fn hash_cached(
+        &mut self,
+        hash_type: HashType,
+        source: &HashCacheSource,
+    ) -> Result<bool, std::io::Error> {
+        use chrono::{DateTime, Utc};
+
+        let metadata = std::fs::metadata(&self.path)?;
+        let modified: DateTime<Utc> = metadata.modified()?.into();
+        let size = metadata.len();
+        let modified = modified.to_rfc3339();
+        self.size = Some(size);
+        self.modified = Some(modified.clone());
+
+        let cached = match source {
+            HashCacheSource::Snapshot(cache) => cache.entries.get(&self.path).cloned(),
+            HashCacheSource::ExtAttributes => self.read_cached_xattrs(hash_type),
+        };
+
+        if let Some(cached) = cached
+            && cached.size == size
+            && cached.modified == modified
+            && cached.covers(hash_type)
+        {
+            for single in hash_type.components() {
+                self.set_hash_value(*single, cached.hash_value(*single).map(str::to_string));
+            }
+            return Ok(true);
+        }
+
+        self.hash(hash_type)?;
+        if let HashCacheSource::ExtAttributes = source {
+            // best effort, filesystems without xattr support just never hit the cache
+            let _ = self.write_cached_xattrs(size, &modified);
+        }
+        Ok(false)
+    }
+
+    // the four fixed algorithms plus whichever others `hash_type` asks for
+    fn read_cached_xattrs(&self, hash_type: HashType) -> Option<CachedHash> {
+        let read = |name: &str| -> Option<String> {
+            let value = self
+                .get_ext_attribute(&format!("{}{}", HASH_XATTR_PREFIX, name))
+                .ok()??;
+            String::from_utf8(value).ok()
+        };
+
+        Some(CachedHash {
+            size: read("size")?.parse().ok()?,
+            modified: read("mtime")?,
+            crc32: read("crc32"),
+            sha256: read("sha256"),
+            sha512: read("sha512"),
+            blake3: read("blake3"),
+            hashes: hash_type
+                .components()
+                .iter()
+                .filter_map(|single| Some((single.name().to_string(), read(single.name())?)))
+                .collect(),
+        })
+    }
+
+    // replaces every cached hash: one left from an earlier algorithm would be
+    // taken as current once size and mtime are rewritten
+    fn write_cached_xattrs(&self, size: u64, modified: &str) -> Result<(), std::io::Error> {
+        let write = |name: &str, value: &str| {
+            self.set_ext_attribute(&format!("{}{}", HASH_XATTR_PREFIX, name), value.as_bytes())
+        };
+
+        // tags and anything else namespaced under the prefix are kept
+        for name in xattrs::list(&self.path)? {
+            if let Some(key) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(HASH_XATTR_PREFIX))
+                && !key.contains('.')
+            {
+                self.remove_ext_attribute(&format!("{}{}", HASH_XATTR_PREFIX, key))?;
+            }
+        }
+
+        for (name, value) in [
+            ("crc32", &self.crc32),
+            ("sha256", &self.sha256),
+            ("sha512", &self.sha512),
+            ("blake3", &self.blake3),
+        ] {
+            if let Some(value) = value {
+                write(name, value)?;
+            }
+        }
+        for (name, value) in &self.hashes {
+            write(name, value)?;
+        }
+        write("size", &size.to_string())?;
+        write("mtime", modified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn scanned(path: &Path) -> FsDirectory {
+        let mut directory = FsDirectory::new_root(path);
+        directory.scan().unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_hash_cached_snapshot_reuses_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("keep.txt"), "keep").unwrap();
+        fs::write(temp_dir.path().join("change.txt"), "before").unwrap();
+
+        let mut first = scanned(temp_dir.path());
+        let stats = first
+            .hash_cached(
+                HashType::BLAKE3,
+                &HashCacheSource::Snapshot(&HashCache::new()),
+            )
+            .unwrap();
+        assert_eq!(
+            stats,
+            HashCacheStats {
+                reused: 0,
+                computed: 2
+            }
+        );
+
+        fs::write(temp_dir.path().join("change.txt"), "after, longer").unwrap();
+
+        let cache = HashCache::from_snapshot(&first);
+        let mut second = scanned(temp_dir.path());
+        let stats = second
+            .hash_cached(HashType::BLAKE3, &HashCacheSource::Snapshot(&cache))
+            .unwrap();
+        assert_eq!(
+            stats,
+            HashCacheStats {
+                reused: 1,
+                computed: 1
+            }
+        );
+
+        // cached results must match a full rehash
+        let mut full = scanned(temp_dir.path());
+        full.hash(HashType::BLAKE3).unwrap();
+        assert_eq!(second.content_equals(&full), Some(true));
+    }
+
+    #[test]
+    fn test_hash_cached_missing_algorithm_recomputes() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let mut first = scanned(temp_dir.path());
+        first
+            .hash_cached(
+                HashType::CRC32,
+                &HashCacheSource::Snapshot(&HashCache::new()),
+            )
+            .unwrap();
+
+        let cache = HashCache::from_snapshot(&first);
+        let mut second = scanned(temp_dir.path());
+        let stats = second
+            .hash_cached(HashType::FAST, &HashCacheSource::Snapshot(&cache))
+            .unwrap();
+        assert_eq!(stats.computed, 1);
+        assert!(second.hash_value(HashType::BLAKE3).is_some());
+    }
+
+    #[test]
+    fn test_hash_cached_xattrs() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("file.txt");
+        fs::write(&file_path, "content").unwrap();
+
+        let mut file = FsFile::new(&file_path);
+        assert!(
+            !file
+                .hash_cached(HashType::SHA256, &HashCacheSource::ExtAttributes)
+                .unwrap()
+        );
+
+        let mut again = FsFile::new(file_path);
+        let reused = again
+            .hash_cached(HashType::SHA256, &HashCacheSource::ExtAttributes)
+            .unwrap();
+        // tmpfs and some other filesystems do not support user xattrs
+        if file
+            .get_ext_attribute("user.deptrack.sha256")
+            .is_ok_and(|v| v.is_some())
+        {
+            assert!(reused);
+        }
+        assert_eq!(again.sha256, file.sha256);
+    }
+
+    #[test]
+    fn test_hash_cached_xattrs_switching_algorithms() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("file.txt");
+        fs::write(&file_path, "before").unwrap();
+        let mut file = FsFile::new(&file_path);
+        file.hash_cached(HashType::SHA256, &HashCacheSource::ExtAttributes)
+            .unwrap();
+        if file
+            .set_ext_attribute("user.deptrack.tag.keep", b"1")
+            .is_err()
+        {
+            // tmpfs and some other filesystems do not support user xattrs
+            return;
+        }
+
+        // the content changes and another algorithm is cached
+        fs::write(&file_path, "after, longer").unwrap();
+        FsFile::new(&file_path)
+            .hash_cached(HashType::BLAKE3, &HashCacheSource::ExtAttributes)
+            .unwrap();
+        assert!(
+            file.get_ext_attribute("user.deptrack.sha256")
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            file.get_ext_attribute("user.deptrack.tag.keep")
+                .unwrap()
+                .is_some()
+        );
+
+        // the first algorithm is computed again, not taken from before
+        let mut again = FsFile::new(&file_path);
+        assert!(
+            !again
+                .hash_cached(HashType::SHA256, &HashCacheSource::ExtAttributes)
+                .unwrap()
+        );
+        let mut full = FsFile::new(&file_path);
+        full.hash(HashType::SHA256).unwrap();
+        assert_eq!(again.sha256, full.sha256);
+    }
+}
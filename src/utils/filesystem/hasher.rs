@@ -0,0 +1,204 @@
+// hash algorithms behind one trait, looked up by name in a process wide
+// registry; the built-in ones are always present and more can be registered
+// at runtime, then selected through HashType::Custom or HashType::from_str
+
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait Hasher: Send + Sync {
+    // lowercase name the algorithm is selected by and its values stored under
+    fn name(&self) -> &'static str;
+
+    // hex digest of a buffer
+    fn digest(&self, data: &[u8]) -> String;
+}
+
+pub struct Crc32;
+pub struct Sha1;
+pub struct Sha256;
+pub struct Sha512;
+pub struct Blake3;
+pub struct Xxh64;
+pub struct Xxh128; // xxh3 128 bit variant
+pub struct GitBlob; // sha1 of the git blob object, equal to `git hash-object`
+
+impl Hasher for Crc32 {
+    fn name(&self) -> &'static str {
+        "crc32"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(data);
+        format!("{:08x}", hasher.finalize())
+    }
+}
+
+impl Hasher for Sha1 {
+    fn name(&self) -> &'static str {
+        "sha1"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        use sha1::Digest;
+        format!("{:x}", sha1::Sha1::digest(data))
+    }
+}
+
+impl Hasher for Sha256 {
+    fn name(&self) -> &'static str {
+        "sha256"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha256::digest(data))
+    }
+}
+
+impl Hasher for Sha512 {
+    fn name(&self) -> &'static str {
+        "sha512"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha512::digest(data))
+    }
+}
+
+impl Hasher for Blake3 {
+    fn name(&self) -> &'static str {
+        "blake3"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        blake3::hash(data).to_hex().to_string()
+    }
+}
+
+impl Hasher for Xxh64 {
+    fn name(&self) -> &'static str {
+        "xxh64"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        format!("{:016x}", xxhash_rust::xxh64::xxh64(data, 0))
+    }
+}
+
+impl Hasher for Xxh128 {
+    fn name(&self) -> &'static str {
+        "xxh128"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        format!("{:032x}", xxhash_rust::xxh3::xxh3_128(data))
+    }
+}
+
+impl Hasher for GitBlob {
+    fn name(&self) -> &'static str {
+        "git-blob"
+    }
+
+    fn digest(&self, data: &[u8]) -> String {
+        use sha1::Digest;
+        let mut hasher = sha1::Sha1::new();
+        hasher.update(format!("blob {}\0", data.len()).as_bytes());
+        hasher.update(data);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn Hasher>>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn Hasher>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        RwLock::new(vec![
+            Arc::new(Crc32),
+            Arc::new(Sha1),
+            Arc::new(Sha256),
+            Arc::new(Sha512),
+            Arc::new(Blake3),
+            Arc::new(Xxh64),
+            Arc::new(Xxh128),
+            Arc::new(GitBlob),
+        ])
+    })
+}
+
+// make an algorithm available by its name, replacing one registered under the
+// same name; names of the ALL and FAST selections are refused
+pub fn register_hasher<H: Hasher + 'static>(hasher: H) -> Result<(), String> {
+    let name = hasher.name();
+    if matches!(name, "all" | "fast") || name.is_empty() || name != name.to_lowercase() {
+        return Err(format!("invalid hasher name: {}", name));
+    }
+
+    let mut hashers = registry().write().unwrap_or_else(|e| e.into_inner());
+    hashers.retain(|h| h.name() != name);
+    hashers.push(Arc::new(hasher));
+    Ok(())
+}
+
+pub fn find_hasher(name: &str) -> Option<Arc<dyn Hasher>> {
+    let hashers = registry().read().unwrap_or_else(|e| e.into_inner());
+    hashers.iter().find(|h| h.name() == name).cloned()
+}
+
+// every registered name, built-in ones first
+pub fn hasher_names() -> Vec<&'static str> {
+    let hashers = registry().read().unwrap_or_else(|e| e.into_inner());
+    hashers.iter().map(|h| h.name()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_digests() {
+        let digest = |name: &str| find_hasher(name).unwrap().digest(b"test content");
+        assert_eq!(digest("crc32"), "57f4675d");
+        assert_eq!(digest("sha1"), "1eebdf4fdc9fc7bf283031b93f9aef3338de9052");
+        assert_eq!(digest("xxh64").len(), 16);
+        assert_eq!(digest("xxh128").len(), 32);
+        // git hash-object of a file holding "test content"
+        assert_eq!(
+            digest("git-blob"),
+            "08cf6101416f0ce0dda3c80e627f333854c4085c"
+        );
+    }
+
+    struct Length;
+
+    impl Hasher for Length {
+        fn name(&self) -> &'static str {
+            "test-length"
+        }
+
+        fn digest(&self, data: &[u8]) -> String {
+            data.len().to_string()
+        }
+    }
+
+    #[test]
+    fn test_register_hasher() {
+        assert!(find_hasher("test-length").is_none());
+        register_hasher(Length).unwrap();
+        assert_eq!(find_hasher("test-length").unwrap().digest(b"abc"), "3");
+        assert!(hasher_names().contains(&"test-length"));
+        assert!(register_hasher(NamedAll).is_err());
+    }
+
+    struct NamedAll;
+
+    impl Hasher for NamedAll {
+        fn name(&self) -> &'static str {
+            "all"
+        }
+
+        fn digest(&self, _data: &[u8]) -> String {
+            String::new()
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// content based file type detection
+// known signatures are recognised via the infer crate, anything else is
+// classified as plain text or generic binary by looking at the bytes
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// enough for every signature infer knows about
+const SNIFF_LEN: usize = 8192;
+
+pub const MIME_TEXT: &str = "text/plain";
+pub const MIME_BINARY: &str = "application/octet-stream";
+
+// mime type of a file, based on its leading bytes
+pub fn detect_file_type<P: AsRef<Path>>(path: P) -> Result<String, std::io::Error> {
+    let mut buffer = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut buffer)?;
+    Ok(detect_bytes_type(&buffer).to_string())
+}
+
+pub fn detect_bytes_type(bytes: &[u8]) -> &'static str {
+    if let Some(kind) = infer::get(bytes) {
+        return kind.mime_type();
+    }
+
+    if bytes.contains(&0) {
+        return MIME_BINARY;
+    }
+    // a multi-byte character may be cut off at the end of the sample
+    match std::str::from_utf8(bytes) {
+        Ok(_) => MIME_TEXT,
+        Err(e) if e.error_len().is_none() => MIME_TEXT,
+        Err(_) => MIME_BINARY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_bytes_type() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+        assert_eq!(detect_bytes_type(&png), "image/png");
+        assert_eq!(detect_bytes_type(b"fn main() {}\n"), MIME_TEXT);
+        assert_eq!(detect_bytes_type("zażółć".as_bytes()), MIME_TEXT);
+        assert_eq!(detect_bytes_type(&"zażółć".as_bytes()[..3]), MIME_TEXT);
+        assert_eq!(detect_bytes_type(&[0x01, 0x00, 0xff]), MIME_BINARY);
+        assert_eq!(detect_bytes_type(&[]), MIME_TEXT);
+    }
+
+    #[test]
+    fn test_detect_file_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let gz = temp_dir.path().join("archive.bin");
+        fs::write(&gz, [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00]).unwrap();
+
+        assert_eq!(detect_file_type(&gz).unwrap(), "application/gzip");
+        assert!(detect_file_type(temp_dir.path().join("missing")).is_err());
+    }
+}
@@ -1,7 +1,25 @@
+pub mod artifact_manifest;
+pub mod diff;
 pub mod explore;
+pub mod hash_cache;
+pub mod hasher;
+pub mod magic;
+pub mod ownership;
+pub(crate) mod platform;
 pub mod predicates;
+pub mod tagging;
 pub mod types;
+pub mod walk;
 
+pub use artifact_manifest::{
+    ARTIFACT_MANIFEST_VERSION, ArtifactEntry, ArtifactManifest, ArtifactMismatch,
+    ArtifactVerification,
+};
+pub use diff::{FsFileDiff, FsPermissionChange, FsTreeDiff};
 pub use explore::FilesystemExplorer;
+pub use hash_cache::{CachedHash, HashCache, HashCacheSource, HashCacheStats};
+pub use hasher::{Hasher, find_hasher, hasher_names, register_hasher};
 pub use predicates::*;
+pub use tagging::{FileTag, TAG_XATTR_PREFIX, TagMismatch, TagVerdict, TagVerification};
 pub use types::*;
+pub use walk::{FsEntry, Walk};
@@ -0,0 +1,82 @@
+// uid/gid to name resolution through getpwuid_r/getgrgid_r, so accounts from
+// ldap, sssd or systemd-homed resolve like they do for `ls -l`
+// names are kept per process once looked up, so scanning large trees does
+// not ask nss again for every file
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+static USERS: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+static GROUPS: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+
+// user name for a uid, falls back to the numeric id when unknown
+pub fn user_name(uid: u32) -> String {
+    cached(&USERS, uid, lookup_user)
+}
+
+// group name for a gid, falls back to the numeric id when unknown
+pub fn group_name(gid: u32) -> String {
+    cached(&GROUPS, gid, lookup_group)
+}
+
+fn cached(
+    names: &OnceLock<Mutex<HashMap<u32, String>>>,
+    id: u32,
+    lookup: fn(u32) -> Option<String>,
+) -> String {
+    let mut names = names
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    names
+        .entry(id)
+        .or_insert_with(|| lookup(id).unwrap_or_else(|| id.to_string()))
+        .clone()
+}
+
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> Option<String> {
+    use nix::unistd::{Uid, User};
+    User::from_uid(Uid::from_raw(uid))
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+}
+
+#[cfg(unix)]
+fn lookup_group(gid: u32) -> Option<String> {
+    use nix::unistd::{Gid, Group};
+    Group::from_gid(Gid::from_raw(gid))
+        .ok()
+        .flatten()
+        .map(|group| group.name)
+}
+
+#[cfg(not(unix))]
+fn lookup_user(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+fn lookup_group(_gid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_known_ids_resolve() {
+        // uid and gid 0 are root on every unix deptrack runs on
+        assert_eq!(user_name(0), "root");
+        assert!(matches!(group_name(0).as_str(), "root" | "wheel"));
+    }
+
+    #[test]
+    fn test_unknown_ids_fall_back_to_number() {
+        assert_eq!(user_name(u32::MAX - 7), (u32::MAX - 7).to_string());
+        assert_eq!(group_name(u32::MAX - 7), (u32::MAX - 7).to_string());
+    }
+}
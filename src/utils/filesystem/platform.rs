@@ -0,0 +1,153 @@
+// the parts of file metadata that differ between operating systems
+// unix has mode bits, uid/gid owners and extended attributes; windows only
+// gets a readonly summary, no owner (that lives in ACLs) and no xattrs
+
+use crate::utils::filesystem::types::Permissions;
+use std::fs::Metadata;
+use std::path::Path;
+
+#[cfg(unix)]
+pub(crate) fn permissions(_path: &Path, metadata: &Metadata) -> Permissions {
+    use std::os::unix::fs::PermissionsExt;
+
+    Permissions::from_mode(metadata.permissions().mode())
+}
+
+// directories can always be entered, files run when windows would run them
+#[cfg(not(unix))]
+pub(crate) fn permissions(path: &Path, metadata: &Metadata) -> Permissions {
+    const EXECUTABLE: [&str; 5] = ["exe", "com", "bat", "cmd", "ps1"];
+
+    let executable = metadata.is_dir()
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXECUTABLE.contains(&e.to_ascii_lowercase().as_str()));
+    Permissions::from_readonly(metadata.permissions().readonly(), executable)
+}
+
+// (owner, group) names
+#[cfg(unix)]
+pub(crate) fn ownership(metadata: &Metadata) -> (Option<String>, Option<String>) {
+    use crate::utils::filesystem::ownership::{group_name, user_name};
+    use std::os::unix::fs::MetadataExt;
+
+    (
+        Some(user_name(metadata.uid())),
+        Some(group_name(metadata.gid())),
+    )
+}
+
+#[cfg(not(unix))]
+pub(crate) fn ownership(_metadata: &Metadata) -> (Option<String>, Option<String>) {
+    (None, None)
+}
+
+// (device, inode) pair of the directory a path resolves to
+#[cfg(unix)]
+pub(crate) fn directory_identity(path: &Path) -> Result<(u64, u64), std::io::Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+// file indexes are not available on stable, the resolved path stands in for
+// them since it already has junctions and symlinks followed
+#[cfg(not(unix))]
+pub(crate) fn directory_identity(path: &Path) -> Result<(u64, u64), std::io::Error> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    std::fs::canonicalize(path)?.hash(&mut hasher);
+    Ok((0, hasher.finish()))
+}
+
+#[cfg(unix)]
+pub(crate) mod xattrs {
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    pub(crate) fn list(path: &Path) -> Result<Vec<OsString>, std::io::Error> {
+        Ok(xattr::list(path)?.collect())
+    }
+
+    pub(crate) fn get(path: &Path, name: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
+        xattr::get(path, name)
+    }
+
+    pub(crate) fn set(path: &Path, name: &str, value: &[u8]) -> Result<(), std::io::Error> {
+        xattr::set(path, name, value)
+    }
+
+    pub(crate) fn remove(path: &Path, name: &str) -> Result<(), std::io::Error> {
+        xattr::remove(path, name)
+    }
+}
+
+// no extended attributes: nothing is listed and writes are refused, so the
+// xattr hash cache simply never hits
+#[cfg(not(unix))]
+pub(crate) mod xattrs {
+    use std::ffi::OsString;
+    use std::io::{Error, ErrorKind};
+    use std::path::Path;
+
+    fn unsupported() -> Error {
+        Error::new(
+            ErrorKind::Unsupported,
+            "extended attributes are not supported on this platform",
+        )
+    }
+
+    pub(crate) fn list(_path: &Path) -> Result<Vec<OsString>, Error> {
+        Ok(Vec::new())
+    }
+
+    pub(crate) fn get(_path: &Path, _name: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(None)
+    }
+
+    pub(crate) fn set(_path: &Path, _name: &str, _value: &[u8]) -> Result<(), Error> {
+        Err(unsupported())
+    }
+
+    pub(crate) fn remove(_path: &Path, _name: &str) -> Result<(), Error> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_permissions_summary() {
+        assert_eq!(Permissions::from_mode(0o754).to_string(), "rwxr-xr--");
+        assert_eq!(
+            Permissions::from_readonly(true, false).to_string(),
+            "r--r--r--"
+        );
+        assert_eq!(
+            Permissions::from_readonly(false, true).to_string(),
+            "rwxrwxrwx"
+        );
+    }
+
+    #[test]
+    fn test_readonly_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "content").unwrap();
+        let mut perms = fs::metadata(&path).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&path, perms).unwrap();
+
+        let permissions = permissions(&path, &fs::metadata(&path).unwrap());
+        assert!(permissions.owner_read);
+        assert!(!permissions.owner_write);
+        assert!(directory_identity(temp_dir.path()).is_ok());
+    }
+}
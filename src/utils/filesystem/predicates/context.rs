@@ -17,6 +17,31 @@ impl PredicateContext {
         }
     }
 
+    // context for a single file on disk, its directory only lists direct children
+    pub fn for_file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let path = path.as_ref();
+        let mut file = FsFile::new(path);
+        file.metadata_scan()?;
+
+        let parent = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut directory = FsDirectory::new(parent);
+        for entry in std::fs::read_dir(parent)? {
+            let entry = entry?;
+            let entry_path = entry.path();
+            if entry.file_type()?.is_dir() {
+                directory
+                    .elements
+                    .push(FsElement::Directory(FsDirectory::new(entry_path)));
+            } else {
+                directory
+                    .elements
+                    .push(FsElement::File(FsFile::new(entry_path)));
+            }
+        }
+
+        Ok(Self::new(directory).with_file(file))
+    }
+
     pub fn with_file(mut self, file: FsFile) -> Self {
         self.current_file = Some(file);
         self
@@ -27,8 +52,8 @@ impl PredicateContext {
         self
     }
 
-    pub fn file_path(&self) -> Option<&str> {
-        self.current_file.as_ref().map(|f| f.path.as_str())
+    pub fn file_path(&self) -> Option<&Path> {
+        self.current_file.as_ref().map(|f| f.path.as_path())
     }
 
     pub fn file_extension(&self) -> Option<&str> {
@@ -39,7 +64,12 @@ impl PredicateContext {
         self.current_file.as_ref()?.name.as_deref()
     }
 
-    pub fn directory_path(&self) -> &str {
+    /// the file name with its extension
+    pub fn file_full_name(&self) -> Option<&str> {
+        self.current_file.as_ref()?.path.file_name()?.to_str()
+    }
+
+    pub fn directory_path(&self) -> &Path {
         &self.current_directory.path
     }
 
@@ -49,8 +79,8 @@ impl PredicateContext {
             .iter()
             .any(|element| match element {
                 FsElement::File(file) => {
-                    if let Some(name) = Path::new(&file.path).file_name() {
-                        name.to_string_lossy() == filename
+                    if let Some(name) = file.path.file_name() {
+                        name == filename
                     } else {
                         false
                     }
@@ -65,8 +95,8 @@ impl PredicateContext {
             .iter()
             .any(|element| match element {
                 FsElement::Directory(dir) => {
-                    if let Some(name) = Path::new(&dir.path).file_name() {
-                        name.to_string_lossy() == dirname
+                    if let Some(name) = dir.path.file_name() {
+                        name == dirname
                     } else {
                         false
                     }
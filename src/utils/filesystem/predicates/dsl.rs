@@ -0,0 +1,455 @@
+// text form of file predicate expressions, e.g.
+//   ext == 'rs' && size > 10k && !path ~ 'tests/**'
+//
+// grammar (loosest binding first):
+//   expr       := xor ( '||' xor )*
+//   xor        := and ( '^' and )*
+//   and        := unary ( '&&' unary )*
+//   unary      := '!' unary | '(' expr ')' | comparison
+//   comparison := field op value
+//
+// fields and operators:
+//   ext == / != value          file extension
+//   name == / != / ~ value     file name without extension (~ is substring)
+//   filename == / != / ~ value file name with extension
+//   path ~ 'glob'              PathGlobPredicate, ! prefixed globs exclude
+//   size > >= < <= == n        bytes, optional k/m/g suffix (powers of 1024)
+//   content ~ 'regex'          FileContentMatchesPredicate
+//   modified < > age           time since modification, s/h/d/w suffix
+//   type == / != value         mime type, or category when there is no '/'
+//   owner, group == / != value
+//   dir_contains == value      directory holds a file with this name
+//
+// values are 'single' or "double" quoted strings, or bare words
+
+use super::file_predicates::*;
+use crate::utils::alt::LogicExpr;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Op(&'static str),
+    And,
+    Or,
+    Xor,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl LogicExpr<FilePredicate> {
+    pub fn parse(input: &str) -> Result<Self, PredicateError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(parse_error(format!("unexpected {:?}", token))),
+        }
+    }
+}
+
+fn parse_error(msg: impl Into<String>) -> PredicateError {
+    PredicateError::ParseError(msg.into())
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, PredicateError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '^' => {
+                tokens.push(Token::Xor);
+                i += 1;
+            }
+            '~' => {
+                tokens.push(Token::Op("~"));
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op("=="));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op("!="));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '>' | '<' => {
+                let op = match (c, next == Some('=')) {
+                    ('>', true) => ">=",
+                    ('>', false) => ">",
+                    ('<', true) => "<=",
+                    _ => "<",
+                };
+                tokens.push(Token::Op(op));
+                i += op.len();
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(parse_error("unterminated string")),
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            c if is_word_char(c) => {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            other => {
+                return Err(parse_error(format!(
+                    "unexpected character '{}' at {}",
+                    other, i
+                )));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '/')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<LogicExpr<FilePredicate>, PredicateError> {
+        let mut left = self.parse_xor()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = LogicExpr::or(left, self.parse_xor()?);
+        }
+        Ok(left)
+    }
+
+    fn parse_xor(&mut self) -> Result<LogicExpr<FilePredicate>, PredicateError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Xor) {
+            self.pos += 1;
+            left = LogicExpr::xor(left, self.parse_and()?);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<LogicExpr<FilePredicate>, PredicateError> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = LogicExpr::and(left, self.parse_unary()?);
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<LogicExpr<FilePredicate>, PredicateError> {
+        match self.next() {
+            Some(Token::Not) => Ok(LogicExpr::negate(self.parse_unary()?)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(parse_error("expected ')'")),
+                }
+            }
+            Some(Token::Word(field)) => self.parse_comparison(&field),
+            Some(token) => Err(parse_error(format!("expected a field, got {:?}", token))),
+            None => Err(parse_error("unexpected end of expression")),
+        }
+    }
+
+    fn parse_comparison(
+        &mut self,
+        field: &str,
+    ) -> Result<LogicExpr<FilePredicate>, PredicateError> {
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => {
+                return Err(parse_error(format!(
+                    "expected an operator after '{}'",
+                    field
+                )));
+            }
+        };
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Str(value)) => value,
+            _ => {
+                return Err(parse_error(format!(
+                    "expected a value after '{} {}'",
+                    field, op
+                )));
+            }
+        };
+
+        let unsupported = || {
+            parse_error(format!(
+                "operator '{}' is not supported for '{}'",
+                op, field
+            ))
+        };
+        let leaf = |predicate: FilePredicate| LogicExpr::Leaf(predicate);
+        let equality = |predicate: FilePredicate| match op {
+            "==" => Ok(LogicExpr::Leaf(predicate)),
+            "!=" => Ok(LogicExpr::negate(LogicExpr::Leaf(predicate))),
+            _ => Err(unsupported()),
+        };
+
+        match field {
+            "ext" => equality(FileExtensionPredicate::new(value).into()),
+            "modified" => {
+                let age = std::time::Duration::from_secs(parse_age(&value)?);
+                match op {
+                    "<" => Ok(leaf(FileAgePredicate::newer_than(age).into())),
+                    ">" => Ok(leaf(FileAgePredicate::older_than(age).into())),
+                    _ => Err(unsupported()),
+                }
+            }
+            "name" if op == "~" => Ok(leaf(FileNamePredicate::contains(value).into())),
+            "name" => equality(FileNamePredicate::exact(value).into()),
+            "filename" if op == "~" => Ok(leaf(
+                FileNamePredicate::contains(value).with_extension().into(),
+            )),
+            "filename" => equality(FileNamePredicate::exact(value).with_extension().into()),
+            "path" if op == "~" => Ok(leaf(PathGlobPredicate::new([value])?.into())),
+            "content" if op == "~" => Ok(leaf(FileContentMatchesPredicate::new(value)?.into())),
+            "type" if value.contains('/') => equality(FileTypePredicate::mime(value).into()),
+            "type" => equality(FileTypePredicate::category(value).into()),
+            "owner" => equality(OwnerPredicate::user(value).into()),
+            "group" => equality(OwnerPredicate::group(value).into()),
+            "dir_contains" => equality(DirectoryContainsPredicate::new(value).into()),
+            "size" => {
+                let size = parse_size(&value)?;
+                let predicate = match op {
+                    ">" => FileSizePredicate::new().min_size(size.saturating_add(1)),
+                    ">=" => FileSizePredicate::new().min_size(size),
+                    "<" if size == 0 => return Err(parse_error("size < 0 never matches")),
+                    "<" => FileSizePredicate::new().max_size(size - 1),
+                    "<=" => FileSizePredicate::new().max_size(size),
+                    "==" => FileSizePredicate::range(size, size),
+                    "!=" => {
+                        return Ok(LogicExpr::negate(leaf(
+                            FileSizePredicate::range(size, size).into(),
+                        )));
+                    }
+                    _ => return Err(unsupported()),
+                };
+                Ok(leaf(predicate.into()))
+            }
+            "path" | "content" => Err(unsupported()),
+            other => Err(parse_error(format!("unknown field '{}'", other))),
+        }
+    }
+}
+
+// "10", "10k", "1.5m", "2G"
+fn parse_size(value: &str) -> Result<u64, PredicateError> {
+    let lower = value.to_lowercase();
+    let (number, multiplier) = match lower.trim_end_matches('b').chars().last() {
+        Some('k') => (&lower[..lower.find('k').unwrap()], 1024u64),
+        Some('m') => (&lower[..lower.find('m').unwrap()], 1024 * 1024),
+        Some('g') => (&lower[..lower.find('g').unwrap()], 1024 * 1024 * 1024),
+        _ => (lower.trim_end_matches('b'), 1),
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map(|n| (n * multiplier as f64) as u64)
+        .ok_or_else(|| parse_error(format!("invalid size '{}'", value)))
+}
+
+// "90", "90s", "12h", "7d", "2w"
+fn parse_age(value: &str) -> Result<u64, PredicateError> {
+    let (number, multiplier) = match value.chars().last() {
+        Some('s') => (&value[..value.len() - 1], 1),
+        Some('h') => (&value[..value.len() - 1], 60 * 60),
+        Some('d') => (&value[..value.len() - 1], 24 * 60 * 60),
+        Some('w') => (&value[..value.len() - 1], 7 * 24 * 60 * 60),
+        _ => (value, 1),
+    };
+
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| parse_error(format!("invalid age '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::filesystem::predicates::PredicateContext;
+    use crate::utils::filesystem::types::{FsDirectory, FsFile};
+
+    fn file_context(path: &str, size: u64) -> PredicateContext {
+        let mut file = FsFile::new(path.to_string());
+        let p = std::path::Path::new(path);
+        file.size = Some(size);
+        file.extension = p.extension().map(|e| e.to_string_lossy().to_string());
+        file.name = p.file_stem().map(|n| n.to_string_lossy().to_string());
+        PredicateContext::new(FsDirectory::new("/repo".to_string())).with_file(file)
+    }
+
+    fn eval(expr: &str, path: &str, size: u64) -> bool {
+        LogicExpr::parse(expr)
+            .unwrap()
+            .evaluate(&file_context(path, size))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_example_expression() {
+        let expr = "ext == 'rs' && size > 10k && !path ~ 'tests/**'";
+        assert!(eval(expr, "/repo/src/big.rs", 20 * 1024));
+        assert!(!eval(expr, "/repo/src/small.rs", 100));
+        assert!(!eval(expr, "/repo/tests/big.rs", 20 * 1024));
+        assert!(!eval(expr, "/repo/src/big.md", 20 * 1024));
+    }
+
+    #[test]
+    fn test_parse_precedence_and_grouping() {
+        // && binds tighter than ||
+        assert!(eval("ext == md || ext == rs && size > 1m", "/r/a.md", 0));
+        assert!(!eval("(ext == md || ext == rs) && size > 1m", "/r/a.md", 0));
+        assert!(eval("ext == rs ^ name == \"main\"", "/r/lib.rs", 0));
+        assert!(!eval("ext == rs ^ name == \"main\"", "/r/main.rs", 0));
+        assert!(eval("!!(ext != toml)", "/r/main.rs", 0));
+    }
+
+    #[test]
+    fn test_parse_sizes_and_names() {
+        assert!(eval("size >= 1.5k", "/r/a", 1536));
+        assert!(!eval("size < 1k", "/r/a", 1024));
+        assert!(eval("size <= 1kb", "/r/a", 1024));
+        assert!(eval("size == 42", "/r/a", 42));
+        assert!(eval("size != 42", "/r/a", 43));
+        assert!(eval("name ~ 'ar'", "/r/Cargo.toml", 0));
+        assert!(eval("name == Cargo && ext == toml", "/r/Cargo.toml", 0));
+        // name leaves the extension out, filename keeps it
+        assert!(!eval("name == 'Cargo.toml'", "/r/Cargo.toml", 0));
+        assert!(eval("filename == 'Cargo.toml'", "/r/Cargo.toml", 0));
+        assert!(!eval("filename == Cargo", "/r/Cargo.toml", 0));
+        assert!(eval("filename != 'Cargo.lock'", "/r/Cargo.toml", 0));
+        assert!(eval("filename ~ 'go.to'", "/r/Cargo.toml", 0));
+        let LogicExpr::Leaf(filename) = LogicExpr::parse("filename == 'Cargo.toml'").unwrap()
+        else {
+            panic!("expected a single comparison");
+        };
+        assert_eq!(filename.to_string(), "filename == 'Cargo.toml'");
+    }
+
+    #[test]
+    fn test_parse_modified_age() {
+        let mut context = file_context("/r/a.rs", 0);
+        let hour_ago = chrono::Utc::now() - chrono::Duration::hours(1);
+        context.current_file.as_mut().unwrap().modified = Some(hour_ago.to_rfc3339());
+
+        let check = |expr: &str| LogicExpr::parse(expr).unwrap().evaluate(&context).unwrap();
+        assert!(check("modified < 2h"));
+        assert!(!check("modified < 30"));
+        assert!(check("modified > 1800"));
+        assert!(!check("modified > 1d"));
+        assert!(LogicExpr::parse("modified == 1d").is_err());
+        assert!(LogicExpr::parse("modified < 1y").is_err());
+    }
+
+    #[test]
+    fn test_parsed_expression_serde_round_trip() {
+        let expr = LogicExpr::parse(
+            "(ext == rs || type == image) && size <= 1m && !path ~ 'target/**' \
+             && content ~ 'fn main' && owner != nobody",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&expr).unwrap();
+        let restored: LogicExpr<FilePredicate> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+
+        // compiled patterns are rebuilt and validated on load
+        assert!(json.contains(r#"{"path_glob":{"patterns":["target/**"]}}"#));
+        let bad = json.replace("fn main", "fn (main");
+        assert!(serde_json::from_str::<LogicExpr<FilePredicate>>(&bad).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for bad in [
+            "",
+            "ext",
+            "ext ==",
+            "ext == 'rs' &&",
+            "(ext == rs",
+            "ext == rs)",
+            "colour == red",
+            "ext > rs",
+            "size > lots",
+            "path == 'a'",
+            "content ~ '(unclosed'",
+            "name == 'unterminated",
+            "ext == rs & size > 1",
+        ] {
+            assert!(
+                LogicExpr::parse(bad).is_err(),
+                "expected error for {:?}",
+                bad
+            );
+        }
+    }
+}
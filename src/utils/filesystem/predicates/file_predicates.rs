@@ -1,10 +1,16 @@
 use super::context::PredicateContext;
-use crate::utils::alt::Evaluable;
+use crate::utils::alt::{Evaluable, LogicExpr};
+use crate::utils::filesystem::magic::detect_file_type;
+use crate::utils::filesystem::types::{FsDirectory, FsElement};
 use crate::utils::toml_ops::{TomlContext, TomlPredicate as TomlOp, TomlReader};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
+use std::path::Path;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FilePredicate {
     Extension(FileExtensionPredicate),
     DirectoryContains(DirectoryContainsPredicate),
@@ -12,6 +18,13 @@ pub enum FilePredicate {
     FileSize(FileSizePredicate),
     FileName(FileNamePredicate),
     TomlContent(TomlContentPredicate),
+    Owner(OwnerPredicate),
+    FileType(FileTypePredicate),
+    FileContentMatches(FileContentMatchesPredicate),
+    PathGlob(PathGlobPredicate),
+    FileAge(FileAgePredicate),
+    DirectoryToml(DirectoryTomlPredicate),
+    DirectoryAnyFile(DirectoryAnyFilePredicate),
 }
 
 impl Evaluable for FilePredicate {
@@ -26,10 +39,105 @@ impl Evaluable for FilePredicate {
             FilePredicate::FileSize(pred) => pred.evaluate(context),
             FilePredicate::FileName(pred) => pred.evaluate(context),
             FilePredicate::TomlContent(pred) => pred.evaluate(context),
+            FilePredicate::Owner(pred) => pred.evaluate(context),
+            FilePredicate::FileType(pred) => pred.evaluate(context),
+            FilePredicate::FileContentMatches(pred) => pred.evaluate(context),
+            FilePredicate::PathGlob(pred) => pred.evaluate(context),
+            FilePredicate::FileAge(pred) => pred.evaluate(context),
+            FilePredicate::DirectoryToml(pred) => pred.evaluate(context),
+            FilePredicate::DirectoryAnyFile(pred) => pred.evaluate(context),
         }
     }
 }
 
+// roughly the text form accepted by LogicExpr::parse, used in explanations
+impl fmt::Display for FilePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilePredicate::Extension(pred) => write!(f, "ext == '{}'", pred.extension),
+            FilePredicate::DirectoryContains(pred) => {
+                write!(f, "dir_contains == '{}'", pred.filename)
+            }
+            FilePredicate::FilePath(pred) => write!(f, "path contains '{}'", pred.pattern),
+            FilePredicate::FileSize(pred) => match (pred.min_size, pred.max_size) {
+                (Some(min), Some(max)) => write!(f, "size >= {} && size <= {}", min, max),
+                (Some(min), None) => write!(f, "size >= {}", min),
+                (None, Some(max)) => write!(f, "size <= {}", max),
+                (None, None) => write!(f, "size any"),
+            },
+            FilePredicate::FileName(pred) => {
+                let field = if pred.with_extension {
+                    "filename"
+                } else {
+                    "name"
+                };
+                let op = if pred.exact_match { "==" } else { "~" };
+                write!(f, "{} {} '{}'", field, op, pred.name_pattern)
+            }
+            FilePredicate::TomlContent(pred) => write!(f, "toml {:?}", pred.toml_predicate),
+            FilePredicate::Owner(pred) => match (&pred.user, &pred.group) {
+                (Some(user), Some(group)) => {
+                    write!(f, "owner == '{}' && group == '{}'", user, group)
+                }
+                (Some(user), None) => write!(f, "owner == '{}'", user),
+                (None, Some(group)) => write!(f, "group == '{}'", group),
+                (None, None) => write!(f, "owner any"),
+            },
+            FilePredicate::FileType(pred) => write!(f, "type == '{}'", pred.mime),
+            FilePredicate::FileContentMatches(pred) => write!(f, "content ~ '{}'", pred.pattern),
+            FilePredicate::PathGlob(pred) => {
+                let patterns: Vec<String> =
+                    pred.patterns.iter().map(|p| format!("'{}'", p)).collect();
+                write!(f, "path ~ {}", patterns.join(", "))
+            }
+            FilePredicate::FileAge(pred) => match (pred.max_age_secs, pred.min_age_secs) {
+                (Some(max), Some(min)) => write!(f, "modified > {}s && modified < {}s", min, max),
+                (Some(max), None) => write!(f, "modified < {}s", max),
+                (None, Some(min)) => write!(f, "modified > {}s", min),
+                (None, None) => write!(f, "modified any"),
+            },
+            FilePredicate::DirectoryToml(pred) => {
+                write!(f, "{} {:?}", pred.file_name, pred.toml_predicate)
+            }
+            FilePredicate::DirectoryAnyFile(pred) => {
+                write!(f, "any file in directory where (")?;
+                write_expr(f, &pred.expr)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn write_expr(f: &mut fmt::Formatter<'_>, expr: &LogicExpr<FilePredicate>) -> fmt::Result {
+    match expr {
+        LogicExpr::And(l, r) => write_binary(f, "&&", l, r),
+        LogicExpr::Or(l, r) => write_binary(f, "||", l, r),
+        LogicExpr::Xor(l, r) => write_binary(f, "^", l, r),
+        LogicExpr::Nand(l, r) => {
+            write!(f, "!")?;
+            write_binary(f, "&&", l, r)
+        }
+        LogicExpr::Not(e) => {
+            write!(f, "!")?;
+            write_expr(f, e)
+        }
+        LogicExpr::Leaf(pred) => write!(f, "{}", pred),
+    }
+}
+
+fn write_binary(
+    f: &mut fmt::Formatter<'_>,
+    op: &str,
+    left: &LogicExpr<FilePredicate>,
+    right: &LogicExpr<FilePredicate>,
+) -> fmt::Result {
+    write!(f, "(")?;
+    write_expr(f, left)?;
+    write!(f, " {} ", op)?;
+    write_expr(f, right)?;
+    write!(f, ")")
+}
+
 impl From<FileExtensionPredicate> for FilePredicate {
     fn from(pred: FileExtensionPredicate) -> Self {
         FilePredicate::Extension(pred)
@@ -66,10 +174,54 @@ impl From<TomlContentPredicate> for FilePredicate {
     }
 }
 
+impl From<OwnerPredicate> for FilePredicate {
+    fn from(pred: OwnerPredicate) -> Self {
+        FilePredicate::Owner(pred)
+    }
+}
+
+impl From<FileTypePredicate> for FilePredicate {
+    fn from(pred: FileTypePredicate) -> Self {
+        FilePredicate::FileType(pred)
+    }
+}
+
+impl From<FileContentMatchesPredicate> for FilePredicate {
+    fn from(pred: FileContentMatchesPredicate) -> Self {
+        FilePredicate::FileContentMatches(pred)
+    }
+}
+
+impl From<PathGlobPredicate> for FilePredicate {
+    fn from(pred: PathGlobPredicate) -> Self {
+        FilePredicate::PathGlob(pred)
+    }
+}
+
+impl From<FileAgePredicate> for FilePredicate {
+    fn from(pred: FileAgePredicate) -> Self {
+        FilePredicate::FileAge(pred)
+    }
+}
+
+impl From<DirectoryTomlPredicate> for FilePredicate {
+    fn from(pred: DirectoryTomlPredicate) -> Self {
+        FilePredicate::DirectoryToml(pred)
+    }
+}
+
+impl From<DirectoryAnyFilePredicate> for FilePredicate {
+    fn from(pred: DirectoryAnyFilePredicate) -> Self {
+        FilePredicate::DirectoryAnyFile(pred)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PredicateError {
     NoFileInContext,
     IoError(String),
+    InvalidPattern(String),
+    ParseError(String),
 }
 
 impl fmt::Display for PredicateError {
@@ -77,13 +229,15 @@ impl fmt::Display for PredicateError {
         match self {
             PredicateError::NoFileInContext => write!(f, "No file in predicate context"),
             PredicateError::IoError(msg) => write!(f, "IO error: {}", msg),
+            PredicateError::InvalidPattern(msg) => write!(f, "Invalid pattern: {}", msg),
+            PredicateError::ParseError(msg) => write!(f, "Parse error: {}", msg),
         }
     }
 }
 
 impl Error for PredicateError {}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileExtensionPredicate {
     pub extension: String,
 }
@@ -108,7 +262,7 @@ impl Evaluable for FileExtensionPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectoryContainsPredicate {
     pub filename: String,
 }
@@ -130,7 +284,7 @@ impl Evaluable for DirectoryContainsPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilePathPredicate {
     pub pattern: String,
     pub is_regex: bool,
@@ -161,14 +315,138 @@ impl Evaluable for FilePathPredicate {
             Some(path) => {
                 // for now, both regex and literal matching use contains
                 // in the future, we can implement proper regex support
-                Ok(path.contains(&self.pattern))
+                Ok(path.to_string_lossy().contains(&self.pattern))
             }
             None => Ok(false),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+// gitignore-like path globs, e.g. ["src/**/*.rs", "!**/generated/**"]
+// a file matches when it hits any include pattern (or there are none) and no
+// `!` exclude pattern; `*` never crosses `/`, `**` spans directories
+// without a root, a pattern may match any trailing part of the path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "PathGlobSpec", into = "PathGlobSpec")]
+pub struct PathGlobPredicate {
+    pub patterns: Vec<String>,
+    pub root: Option<String>,
+    includes: GlobSet,
+    excludes: GlobSet,
+}
+
+impl PathGlobPredicate {
+    pub fn new<I, S>(patterns: I) -> Result<Self, PredicateError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let patterns: Vec<String> = patterns.into_iter().map(Into::into).collect();
+        let mut includes = GlobSetBuilder::new();
+        let mut excludes = GlobSetBuilder::new();
+
+        for pattern in &patterns {
+            let (target, raw) = match pattern.strip_prefix('!') {
+                Some(rest) => (&mut excludes, rest),
+                None => (&mut includes, pattern.as_str()),
+            };
+            let compiled = GlobBuilder::new(raw.trim_start_matches('/'))
+                .literal_separator(true)
+                .build()
+                .map_err(|e| PredicateError::InvalidPattern(format!("{}: {}", pattern, e)))?;
+            target.add(compiled);
+        }
+
+        let build = |set: GlobSetBuilder| {
+            set.build()
+                .map_err(|e| PredicateError::InvalidPattern(e.to_string()))
+        };
+        Ok(Self {
+            includes: build(includes)?,
+            excludes: build(excludes)?,
+            patterns,
+            root: None,
+        })
+    }
+
+    // anchor patterns to this directory instead of matching trailing path parts
+    pub fn relative_to(mut self, root: impl Into<String>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    // patterns are text, a path that is not utf-8 is matched in its lossy form
+    pub fn matches_path<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref().to_string_lossy();
+        let candidates = self.candidates(&path);
+        let hit = |set: &GlobSet| candidates.iter().any(|candidate| set.is_match(candidate));
+
+        (self.includes.is_empty() || hit(&self.includes)) && !hit(&self.excludes)
+    }
+
+    fn candidates<'a>(&self, path: &'a str) -> Vec<&'a str> {
+        if let Some(root) = &self.root {
+            let relative = std::path::Path::new(path)
+                .strip_prefix(root)
+                .ok()
+                .and_then(|p| p.to_str())
+                .unwrap_or(path);
+            return vec![relative];
+        }
+
+        let trimmed = path.trim_start_matches('/');
+        let mut candidates = vec![trimmed];
+        candidates.extend(
+            trimmed
+                .match_indices('/')
+                .map(|(index, _)| &trimmed[index + 1..]),
+        );
+        candidates
+    }
+}
+
+// serialized form, the compiled patterns are rebuilt on load
+#[derive(Serialize, Deserialize)]
+struct PathGlobSpec {
+    patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    root: Option<String>,
+}
+
+impl TryFrom<PathGlobSpec> for PathGlobPredicate {
+    type Error = PredicateError;
+
+    fn try_from(spec: PathGlobSpec) -> Result<Self, Self::Error> {
+        let predicate = Self::new(spec.patterns)?;
+        Ok(match spec.root {
+            Some(root) => predicate.relative_to(root),
+            None => predicate,
+        })
+    }
+}
+
+impl From<PathGlobPredicate> for PathGlobSpec {
+    fn from(predicate: PathGlobPredicate) -> Self {
+        Self {
+            patterns: predicate.patterns,
+            root: predicate.root,
+        }
+    }
+}
+
+impl Evaluable for PathGlobPredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        match context.file_path() {
+            Some(path) => Ok(self.matches_path(path)),
+            None => Ok(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileSizePredicate {
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
@@ -234,10 +512,14 @@ impl Evaluable for FileSizePredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+// matches the file name without its extension, or the whole name with
+// `with_extension`
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileNamePredicate {
     pub name_pattern: String,
     pub exact_match: bool,
+    #[serde(default)]
+    pub with_extension: bool,
 }
 
 impl FileNamePredicate {
@@ -245,6 +527,7 @@ impl FileNamePredicate {
         Self {
             name_pattern: name.into(),
             exact_match: true,
+            with_extension: false,
         }
     }
 
@@ -252,8 +535,15 @@ impl FileNamePredicate {
         Self {
             name_pattern: pattern.into(),
             exact_match: false,
+            with_extension: false,
         }
     }
+
+    /// compare the whole file name, `Cargo.toml` rather than `Cargo`
+    pub fn with_extension(mut self) -> Self {
+        self.with_extension = true;
+        self
+    }
 }
 
 impl Evaluable for FileNamePredicate {
@@ -261,7 +551,12 @@ impl Evaluable for FileNamePredicate {
     type Error = PredicateError;
 
     fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
-        match context.file_name() {
+        let name = if self.with_extension {
+            context.file_full_name()
+        } else {
+            context.file_name()
+        };
+        match name {
             Some(name) => {
                 if self.exact_match {
                     Ok(name == self.name_pattern)
@@ -274,7 +569,199 @@ impl Evaluable for FileNamePredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+// matches files by owning user and/or group name (numeric id when the name is unknown)
+// requires metadata_scan, files without owner information never match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerPredicate {
+    pub user: Option<String>,
+    pub group: Option<String>,
+}
+
+impl OwnerPredicate {
+    pub fn user(name: impl Into<String>) -> Self {
+        Self {
+            user: Some(name.into()),
+            group: None,
+        }
+    }
+
+    pub fn group(name: impl Into<String>) -> Self {
+        Self {
+            user: None,
+            group: Some(name.into()),
+        }
+    }
+
+    pub fn user_and_group(user: impl Into<String>, group: impl Into<String>) -> Self {
+        Self {
+            user: Some(user.into()),
+            group: Some(group.into()),
+        }
+    }
+}
+
+impl Evaluable for OwnerPredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        let Some(file) = &context.current_file else {
+            return Ok(false);
+        };
+
+        let user_matches = match &self.user {
+            Some(expected) => file.owner.as_ref() == Some(expected),
+            None => true,
+        };
+        let group_matches = match &self.group {
+            Some(expected) => file.group.as_ref() == Some(expected),
+            None => true,
+        };
+
+        Ok(user_matches && group_matches)
+    }
+}
+
+// matches files by detected mime type, either exactly ("image/png") or by
+// top level category ("image"); uses magic when populated, otherwise sniffs the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTypePredicate {
+    pub mime: String,
+    pub category_only: bool,
+}
+
+impl FileTypePredicate {
+    pub fn mime(mime: impl Into<String>) -> Self {
+        Self {
+            mime: mime.into(),
+            category_only: false,
+        }
+    }
+
+    pub fn category(category: impl Into<String>) -> Self {
+        Self {
+            mime: category.into(),
+            category_only: true,
+        }
+    }
+}
+
+impl Evaluable for FileTypePredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        let Some(file) = &context.current_file else {
+            return Ok(false);
+        };
+
+        let detected = match &file.magic {
+            Some(magic) => magic.clone(),
+            None => {
+                detect_file_type(&file.path).map_err(|e| PredicateError::IoError(e.to_string()))?
+            }
+        };
+
+        if self.category_only {
+            Ok(detected.split('/').next() == Some(self.mime.as_str()))
+        } else {
+            Ok(detected == self.mime)
+        }
+    }
+}
+
+// files larger than this are not read by FileContentMatchesPredicate unless raised
+pub const DEFAULT_MAX_CONTENT_SIZE: u64 = 1024 * 1024;
+
+// matches file contents against a regex, the file is only read when the
+// predicate is actually evaluated (so put cheaper predicates first in an AND)
+// oversized files never match; binary content is matched bytewise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "FileContentMatchesSpec", into = "FileContentMatchesSpec")]
+pub struct FileContentMatchesPredicate {
+    pub pattern: String,
+    pub max_size: u64,
+    regex: regex::bytes::Regex,
+}
+
+impl FileContentMatchesPredicate {
+    pub fn new(pattern: impl Into<String>) -> Result<Self, PredicateError> {
+        let pattern = pattern.into();
+        let regex = regex::bytes::Regex::new(&pattern)
+            .map_err(|e| PredicateError::InvalidPattern(e.to_string()))?;
+        Ok(Self {
+            pattern,
+            max_size: DEFAULT_MAX_CONTENT_SIZE,
+            regex,
+        })
+    }
+
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = size;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileContentMatchesSpec {
+    pattern: String,
+    #[serde(default = "default_max_content_size")]
+    max_size: u64,
+}
+
+fn default_max_content_size() -> u64 {
+    DEFAULT_MAX_CONTENT_SIZE
+}
+
+impl TryFrom<FileContentMatchesSpec> for FileContentMatchesPredicate {
+    type Error = PredicateError;
+
+    fn try_from(spec: FileContentMatchesSpec) -> Result<Self, Self::Error> {
+        Ok(Self::new(spec.pattern)?.max_size(spec.max_size))
+    }
+}
+
+impl From<FileContentMatchesPredicate> for FileContentMatchesSpec {
+    fn from(predicate: FileContentMatchesPredicate) -> Self {
+        Self {
+            pattern: predicate.pattern,
+            max_size: predicate.max_size,
+        }
+    }
+}
+
+impl Evaluable for FileContentMatchesPredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        use std::io::Read;
+
+        let Some(file) = &context.current_file else {
+            return Ok(false);
+        };
+
+        let size = match file.size {
+            Some(size) => size,
+            None => std::fs::metadata(&file.path)
+                .map_err(|e| PredicateError::IoError(e.to_string()))?
+                .len(),
+        };
+        if size > self.max_size {
+            return Ok(false);
+        }
+
+        // the file may have grown since it was scanned, never read past the limit
+        let mut content = Vec::new();
+        std::fs::File::open(&file.path)
+            .and_then(|f| f.take(self.max_size).read_to_end(&mut content))
+            .map_err(|e| PredicateError::IoError(e.to_string()))?;
+
+        Ok(self.regex.is_match(&content))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TomlContentPredicate {
     pub toml_predicate: TomlOp,
 }
@@ -328,7 +815,7 @@ impl Evaluable for TomlContentPredicate {
         // Only apply to .toml files
         match context.file_path() {
             Some(path) => {
-                if !path.ends_with(".toml") {
+                if path.extension().is_none_or(|extension| extension != "toml") {
                     return Ok(false);
                 }
 
@@ -350,3 +837,143 @@ impl Evaluable for TomlContentPredicate {
         }
     }
 }
+
+// time since last modification, based on the mtime recorded by metadata_scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAgePredicate {
+    pub max_age_secs: Option<u64>,
+    pub min_age_secs: Option<u64>,
+}
+
+impl FileAgePredicate {
+    // modified less than `age` ago
+    pub fn newer_than(age: std::time::Duration) -> Self {
+        Self {
+            max_age_secs: Some(age.as_secs()),
+            min_age_secs: None,
+        }
+    }
+
+    // modified more than `age` ago
+    pub fn older_than(age: std::time::Duration) -> Self {
+        Self {
+            max_age_secs: None,
+            min_age_secs: Some(age.as_secs()),
+        }
+    }
+}
+
+impl Evaluable for FileAgePredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        let Some(modified) = context
+            .current_file
+            .as_ref()
+            .and_then(|f| f.modified.as_ref())
+        else {
+            return Ok(false);
+        };
+        let modified = chrono::DateTime::parse_from_rfc3339(modified)
+            .map_err(|e| PredicateError::IoError(format!("invalid mtime {}: {}", modified, e)))?;
+        let age = (chrono::Utc::now() - modified.to_utc())
+            .num_seconds()
+            .max(0) as u64;
+
+        Ok(self.max_age_secs.is_none_or(|max| age < max)
+            && self.min_age_secs.is_none_or(|min| age > min))
+    }
+}
+
+// evaluates a toml predicate against a manifest inside the context directory,
+// so a directory can be selected by what its Cargo.toml says
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryTomlPredicate {
+    pub file_name: String,
+    pub toml_predicate: TomlOp,
+}
+
+impl DirectoryTomlPredicate {
+    pub fn new(file_name: impl Into<String>, toml_predicate: TomlOp) -> Self {
+        Self {
+            file_name: file_name.into(),
+            toml_predicate,
+        }
+    }
+
+    pub fn cargo_manifest(toml_predicate: TomlOp) -> Self {
+        Self::new("Cargo.toml", toml_predicate)
+    }
+}
+
+impl Evaluable for DirectoryTomlPredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        if !context.directory_contains_file(&self.file_name) {
+            return Ok(false);
+        }
+
+        let path = context.directory_path().join(&self.file_name);
+        match TomlReader::read_file(&path) {
+            Ok(toml_doc) => Ok(self
+                .toml_predicate
+                .evaluate(&TomlContext::new(toml_doc))
+                .unwrap_or(false)),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+// true when any file in the context directory, or in a directory below it,
+// matches `expr`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryAnyFilePredicate {
+    pub expr: Box<LogicExpr<FilePredicate>>,
+}
+
+impl DirectoryAnyFilePredicate {
+    pub fn new(expr: impl Into<LogicExpr<FilePredicate>>) -> Self {
+        Self {
+            expr: Box::new(expr.into()),
+        }
+    }
+
+    // each file is evaluated in a context holding only the path of its
+    // directory, not a copy of the tree being searched
+    fn any_file_in(&self, directory: &FsDirectory) -> Result<bool, PredicateError> {
+        let mut file_context = PredicateContext::new(FsDirectory::new(&directory.path));
+        for element in &directory.elements {
+            let matched = match element {
+                FsElement::File(file) => {
+                    let mut file = file.clone();
+                    let _ = file.metadata_scan(); // directory contexts usually carry bare paths
+                    file_context.current_file = Some(file);
+                    self.expr.evaluate(&file_context)?
+                }
+                // listed without its contents, read it with the default skips
+                FsElement::Directory(dir) if dir.elements.is_empty() => {
+                    let mut scanned = FsDirectory::new(&dir.path);
+                    scanned.scan().is_ok() && self.any_file_in(&scanned)?
+                }
+                FsElement::Directory(dir) => self.any_file_in(dir)?,
+                FsElement::Symlink(_) => false,
+            };
+            if matched {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Evaluable for DirectoryAnyFilePredicate {
+    type Context = PredicateContext;
+    type Error = PredicateError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        self.any_file_in(&context.current_directory)
+    }
+}
@@ -1,4 +1,5 @@
 pub mod context;
+pub mod dsl;
 pub mod file_predicates;
 
 pub use context::*;
@@ -0,0 +1,365 @@
+// verdict tags: hashes recorded on the files themselves as extended
+// attributes, so a tree can later be checked for files changed since tagging
+// unlike the hash cache these are never rewritten by hashing, only by tagging
+
+use crate::utils::filesystem::platform::xattrs;
+use crate::utils::filesystem::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// extended attribute prefix of the recorded tag
+pub const TAG_XATTR_PREFIX: &str = "user.deptrack.tag.";
+
+// attributes of a tag that do not hold a hash
+const TAG_FIELDS: [&str; 7] = [
+    "size",
+    "mtime",
+    "tagged_at",
+    "crc32",
+    "sha256",
+    "sha512",
+    "blake3",
+];
+
+// what was recorded when a file was tagged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileTag {
+    pub size: u64,
+    pub modified: String, // mtime at tagging, rfc3339
+    pub tagged_at: String,
+    pub crc32: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    pub blake3: Option<String>,
+    pub hashes: BTreeMap<String, String>, // as FsFile::hashes
+}
+
+// outcome of checking one file against its tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagVerdict {
+    Verified,
+    Untagged,
+    Drifted(Vec<String>),  // changed, and its mtime moved on since tagging
+    Tampered(Vec<String>), // changed while its mtime claims it was not touched
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagMismatch {
+    pub path: PathBuf,
+    // "size" and/or the names of the hashes that differ
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagVerification {
+    pub verified: Vec<PathBuf>,
+    pub drifted: Vec<TagMismatch>,
+    pub tampered: Vec<TagMismatch>,
+    pub untagged: Vec<PathBuf>,
+}
+
+impl TagVerification {
+    // untagged files alone do not fail a verification
+    pub fn is_ok(&self) -> bool {
+        self.drifted.is_empty() && self.tampered.is_empty()
+    }
+}
+
+impl FileTag {
+    fn hash_value(&self, hash_type: HashType) -> Option<&str> {
+        match hash_type {
+            HashType::CRC32 => self.crc32.as_deref(),
+            HashType::SHA256 => self.sha256.as_deref(),
+            HashType::SHA512 => self.sha512.as_deref(),
+            HashType::BLAKE3 => self.blake3.as_deref(),
+            HashType::ALL | HashType::FAST => None,
+            other => self.hashes.get(other.name()).map(String::as_str),
+        }
+    }
+}
+
+impl FsFile {
+    // hash the file and record the result on it, replacing any earlier tag
+    pub fn tag(&mut self, hash_type: HashType) -> Result<FileTag, std::io::Error> {
+        use chrono::{DateTime, Utc};
+
+        let metadata = std::fs::metadata(&self.path)?;
+        let modified: DateTime<Utc> = metadata.modified()?.into();
+        self.hash(hash_type)?;
+
+        let tag = FileTag {
+            size: metadata.len(),
+            modified: modified.to_rfc3339(),
+            tagged_at: Utc::now().to_rfc3339(),
+            crc32: self.crc32.clone(),
+            sha256: self.sha256.clone(),
+            sha512: self.sha512.clone(),
+            blake3: self.blake3.clone(),
+            hashes: self.hashes.clone(),
+        };
+
+        let write = |name: &str, value: &str| {
+            self.set_ext_attribute(&format!("{}{}", TAG_XATTR_PREFIX, name), value.as_bytes())
+        };
+        for (name, value) in [
+            ("crc32", &tag.crc32),
+            ("sha256", &tag.sha256),
+            ("sha512", &tag.sha512),
+            ("blake3", &tag.blake3),
+        ] {
+            match value {
+                Some(value) => write(name, value)?,
+                // hashes of an earlier tag would no longer match the content
+                None => {
+                    let _ = self.remove_ext_attribute(&format!("{}{}", TAG_XATTR_PREFIX, name));
+                }
+            }
+        }
+        for name in self.tagged_hash_names()? {
+            if !tag.hashes.contains_key(&name) {
+                let _ = self.remove_ext_attribute(&format!("{}{}", TAG_XATTR_PREFIX, name));
+            }
+        }
+        for (name, value) in &tag.hashes {
+            write(name, value)?;
+        }
+        write("size", &tag.size.to_string())?;
+        write("mtime", &tag.modified)?;
+        write("tagged_at", &tag.tagged_at)?;
+        Ok(tag)
+    }
+
+    // tag attributes holding hashes of algorithms without a field of their own
+    fn tagged_hash_names(&self) -> Result<Vec<String>, std::io::Error> {
+        Ok(xattrs::list(&self.path)?
+            .iter()
+            .filter_map(|name| name.to_str()?.strip_prefix(TAG_XATTR_PREFIX))
+            .filter(|name| !TAG_FIELDS.contains(name))
+            .map(str::to_string)
+            .collect())
+    }
+
+    // the recorded tag, None when the file was never tagged
+    pub fn read_tag(&self) -> Result<Option<FileTag>, std::io::Error> {
+        let read = |name: &str| -> Result<Option<String>, std::io::Error> {
+            let value = self.get_ext_attribute(&format!("{}{}", TAG_XATTR_PREFIX, name))?;
+            Ok(value.map(|v| String::from_utf8_lossy(&v).to_string()))
+        };
+
+        let (Some(size), Some(modified), Some(tagged_at)) =
+            (read("size")?, read("mtime")?, read("tagged_at")?)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(FileTag {
+            size: size.parse().unwrap_or_default(),
+            modified,
+            tagged_at,
+            crc32: read("crc32")?,
+            sha256: read("sha256")?,
+            sha512: read("sha512")?,
+            blake3: read("blake3")?,
+            hashes: self
+                .tagged_hash_names()?
+                .into_iter()
+                .filter_map(|name| match read(&name) {
+                    Ok(Some(value)) => Some(Ok((name, value))),
+                    Ok(None) => None,
+                    Err(e) => Some(Err(e)),
+                })
+                .collect::<Result<_, _>>()?,
+        }))
+    }
+
+    // re-hash with the algorithms the tag recorded and compare
+    pub fn verify_tag(&mut self) -> Result<TagVerdict, std::io::Error> {
+        use chrono::{DateTime, Utc};
+
+        let Some(tag) = self.read_tag()? else {
+            return Ok(TagVerdict::Untagged);
+        };
+
+        let metadata = std::fs::metadata(&self.path)?;
+        let mut fields = Vec::new();
+        if metadata.len() != tag.size {
+            fields.push("size".to_string());
+        }
+        // algorithms no longer registered under a recorded name cannot be
+        // checked and are passed over
+        let recorded_types = [
+            HashType::CRC32,
+            HashType::SHA256,
+            HashType::SHA512,
+            HashType::BLAKE3,
+        ]
+        .into_iter()
+        .chain(tag.hashes.keys().filter_map(|name| name.parse().ok()));
+        for hash_type in recorded_types {
+            let Some(recorded) = tag.hash_value(hash_type) else {
+                continue;
+            };
+            self.hash(hash_type)?;
+            if self.hash_value(hash_type) != Some(recorded) {
+                fields.push(hash_type.name().to_string());
+            }
+        }
+
+        if fields.is_empty() {
+            return Ok(TagVerdict::Verified);
+        }
+        let modified: DateTime<Utc> = metadata.modified()?.into();
+        let untouched =
+            DateTime::parse_from_rfc3339(&tag.modified).is_ok_and(|recorded| modified <= recorded);
+        Ok(if untouched {
+            TagVerdict::Tampered(fields)
+        } else {
+            TagVerdict::Drifted(fields)
+        })
+    }
+}
+
+impl FsDirectory {
+    // tag every file of a scanned tree, returns how many were tagged
+    pub fn tag(&mut self, hash_type: HashType) -> Result<usize, std::io::Error> {
+        let mut tagged = 0;
+        for element in &mut self.elements {
+            match element {
+                FsElement::File(file) => {
+                    file.tag(hash_type)?;
+                    tagged += 1;
+                }
+                FsElement::Directory(dir) => tagged += dir.tag(hash_type)?,
+                FsElement::Symlink(_) => {}
+            }
+        }
+        self.update_aggregate_hashes(hash_type);
+        Ok(tagged)
+    }
+
+    // check every file of a scanned tree against its tag
+    pub fn verify_tags(&mut self) -> Result<TagVerification, std::io::Error> {
+        let mut verification = TagVerification::default();
+        self.verify_tags_recursive(&mut verification)?;
+        Ok(verification)
+    }
+
+    fn verify_tags_recursive(
+        &mut self,
+        verification: &mut TagVerification,
+    ) -> Result<(), std::io::Error> {
+        for element in &mut self.elements {
+            match element {
+                FsElement::File(file) => {
+                    let path = file.path.clone();
+                    match file.verify_tag()? {
+                        TagVerdict::Verified => verification.verified.push(path),
+                        TagVerdict::Untagged => verification.untagged.push(path),
+                        TagVerdict::Drifted(fields) => {
+                            verification.drifted.push(TagMismatch { path, fields })
+                        }
+                        TagVerdict::Tampered(fields) => {
+                            verification.tampered.push(TagMismatch { path, fields })
+                        }
+                    }
+                }
+                FsElement::Directory(dir) => dir.verify_tags_recursive(verification)?,
+                FsElement::Symlink(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    // tmpfs, some other filesystems and windows have no user xattrs
+    fn supports_xattrs(path: &std::path::Path) -> bool {
+        FsFile::new(path)
+            .set_ext_attribute("user.deptrack.probe", b"1")
+            .is_ok()
+    }
+
+    #[test]
+    fn test_tag_and_verify() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        fs::write(temp_dir.path().join("kept.txt"), "kept").unwrap();
+        fs::write(temp_dir.path().join("sub").join("edited.txt"), "before").unwrap();
+        if !supports_xattrs(&temp_dir.path().join("kept.txt")) {
+            return;
+        }
+
+        let mut directory = FsDirectory::new_root(temp_dir.path());
+        directory.scan().unwrap();
+        assert_eq!(directory.tag(HashType::BLAKE3).unwrap(), 2);
+
+        let tag = FsFile::new(temp_dir.path().join("kept.txt"))
+            .read_tag()
+            .unwrap()
+            .unwrap();
+        assert_eq!(tag.size, 4);
+        assert!(tag.blake3.is_some());
+        assert!(tag.sha256.is_none());
+
+        let edited = temp_dir.path().join("sub").join("edited.txt");
+        fs::write(&edited, "after").unwrap();
+        let future = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&edited)
+            .unwrap()
+            .set_modified(future)
+            .unwrap();
+        fs::write(temp_dir.path().join("new.txt"), "new").unwrap();
+
+        let mut directory = FsDirectory::new_root(temp_dir.path());
+        directory.scan().unwrap();
+        let verification = directory.verify_tags().unwrap();
+        assert_eq!(
+            verification.verified,
+            vec![temp_dir.path().join("kept.txt")]
+        );
+        assert_eq!(verification.untagged, vec![temp_dir.path().join("new.txt")]);
+        assert_eq!(verification.drifted.len(), 1);
+        assert_eq!(verification.drifted[0].fields, vec!["size", "blake3"]);
+        assert!(!verification.is_ok());
+    }
+
+    #[test]
+    fn test_tampered_keeps_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "original").unwrap();
+        if !supports_xattrs(&path) {
+            return;
+        }
+
+        FsFile::new(&path).tag(HashType::SHA256).unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, "replaced").unwrap();
+        // put the old mtime back, as a careful edit would
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        assert_eq!(
+            FsFile::new(&path).verify_tag().unwrap(),
+            TagVerdict::Tampered(vec!["sha256".to_string()])
+        );
+
+        // tagging again accepts the new content
+        FsFile::new(&path).tag(HashType::SHA256).unwrap();
+        assert_eq!(
+            FsFile::new(&path).verify_tag().unwrap(),
+            TagVerdict::Verified
+        );
+    }
+}
@@ -1,24 +1,318 @@
-#[derive(Debug, Clone, Copy)]
+use crate::utils::filesystem::hasher::{Hasher, find_hasher, hasher_names};
+use crate::utils::filesystem::magic::detect_file_type;
+use crate::utils::filesystem::platform::{self, xattrs};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashType {
-    CRC32,  // general file sum
-    SHA256, // gfeneral security file sum
-    SHA512, // if you want to be extra sure
-    BLAKE3, // advised for general usage, speed + security
-    ALL,    // all of the above
-    FAST,   // crc32 + blake3 // fast but still usefull
+    CRC32,                // general file sum
+    SHA1,                 // plain sha1, only for matching existing sums
+    SHA256,               // gfeneral security file sum
+    SHA512,               // if you want to be extra sure
+    BLAKE3,               // advised for general usage, speed + security
+    XXH64,                // very fast, not cryptographic
+    XXH128,               // xxh3 128 bit, very fast with fewer collisions
+    GITBLOB,              // sha1 of the git blob object, compares against git's ids
+    ALL,                  // crc32, sha256, sha512 and blake3
+    FAST,                 // crc32 + blake3 // fast but still usefull
+    Custom(&'static str), // an algorithm added with register_hasher
 }
 
-#[derive(Debug, Clone)]
+impl HashType {
+    // single algorithms making up this selection
+    pub fn components(&self) -> &[HashType] {
+        match self {
+            HashType::ALL => &[
+                HashType::CRC32,
+                HashType::SHA256,
+                HashType::SHA512,
+                HashType::BLAKE3,
+            ],
+            HashType::FAST => &[HashType::CRC32, HashType::BLAKE3],
+            single => std::slice::from_ref(single),
+        }
+    }
+
+    // registry name, also the key values are stored under
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashType::CRC32 => "crc32",
+            HashType::SHA1 => "sha1",
+            HashType::SHA256 => "sha256",
+            HashType::SHA512 => "sha512",
+            HashType::BLAKE3 => "blake3",
+            HashType::XXH64 => "xxh64",
+            HashType::XXH128 => "xxh128",
+            HashType::GITBLOB => "git-blob",
+            HashType::ALL => "all",
+            HashType::FAST => "fast",
+            HashType::Custom(name) => name,
+        }
+    }
+
+    // the implementation of a single algorithm, None for the selections and
+    // for custom names nothing was registered under
+    pub fn hasher(&self) -> Option<Arc<dyn Hasher>> {
+        match self {
+            HashType::ALL | HashType::FAST => None,
+            single => find_hasher(single.name()),
+        }
+    }
+}
+
+impl std::str::FromStr for HashType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        match name.as_str() {
+            "crc32" => Ok(HashType::CRC32),
+            "sha1" => Ok(HashType::SHA1),
+            "sha256" => Ok(HashType::SHA256),
+            "sha512" => Ok(HashType::SHA512),
+            "blake3" => Ok(HashType::BLAKE3),
+            "xxh64" => Ok(HashType::XXH64),
+            "xxh128" => Ok(HashType::XXH128),
+            "git-blob" => Ok(HashType::GITBLOB),
+            "all" => Ok(HashType::ALL),
+            "fast" => Ok(HashType::FAST),
+            _ => match find_hasher(&name) {
+                Some(hasher) => Ok(HashType::Custom(hasher.name())),
+                None => Err(format!(
+                    "invalid hash: {}, use {}, all or fast",
+                    s,
+                    hasher_names().join(", ")
+                )),
+            },
+        }
+    }
+}
+
+// digest of a buffer for a single algorithm, None for the ALL/FAST selections
+pub(crate) fn hash_bytes(hash_type: HashType, data: &[u8]) -> Option<String> {
+    hash_type.hasher().map(|hasher| hasher.digest(data))
+}
+
+fn set_named_hash(
+    hashes: &mut BTreeMap<String, String>,
+    hash_type: HashType,
+    value: Option<String>,
+) {
+    match value {
+        Some(value) => hashes.insert(hash_type.name().to_string(), value),
+        None => hashes.remove(hash_type.name()),
+    };
+}
+
+// raw bytes of the last path component, so names that are not utf-8 still
+// hash apart
+fn element_name(path: &Path) -> Vec<u8> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .as_encoded_bytes()
+        .to_vec()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FsElement {
     File(FsFile),
     Directory(FsDirectory),
+    Symlink(FsSymlink),
 }
 
-// represents file, abastract form the FS and quite dumbed down
+impl FsElement {
+    pub fn path(&self) -> &Path {
+        match self {
+            FsElement::File(file) => &file.path,
+            FsElement::Directory(dir) => &dir.path,
+            FsElement::Symlink(link) => &link.path,
+        }
+    }
+
+    // the path for messages, lossy only when it is not utf-8
+    pub fn display(&self) -> std::path::Display<'_> {
+        self.path().display()
+    }
+}
+
+// how scans treat symbolic links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    Skip,   // ignore links entirely
+    Record, // keep the link as FsElement::Symlink, never descend into it
+    #[default]
+    Follow, // treat the link as its target, links back into an ancestor are recorded instead
+}
 
+// directories no scan descends into unless told otherwise: git's object
+// database
+pub const DEFAULT_SKIPPED_DIRECTORIES: [&str; 1] = [".git"];
+
+// cargo's build output, skipped only beside a Cargo.toml so a `target`
+// module under a crate's sources is still scanned
+pub const DEFAULT_BUILD_DIRECTORIES: [&str; 1] = ["target"];
+
+// options shared by directory scans
 #[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub symlink_policy: SymlinkPolicy,
+    pub skipped_names: Vec<String>, // directories with these names are left out wherever they are
+    pub build_dir_names: Vec<String>, // left out where a Cargo.toml sits beside them
+    pub skipped_paths: Vec<PathBuf>, // absolute, left out as a whole
+    pub hash: Option<HashType>,     // computed as each file is visited
+}
+
+// skips the default directories and CARGO_TARGET_DIR
+impl Default for ScanOptions {
+    fn default() -> Self {
+        let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+            .filter(|dir| !dir.is_empty())
+            .and_then(|dir| std::path::absolute(dir).ok());
+        ScanOptions {
+            symlink_policy: SymlinkPolicy::default(),
+            skipped_names: DEFAULT_SKIPPED_DIRECTORIES.map(String::from).to_vec(),
+            build_dir_names: DEFAULT_BUILD_DIRECTORIES.map(String::from).to_vec(),
+            skipped_paths: target_dir.into_iter().collect(),
+            hash: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
+    pub fn skip_name(mut self, name: impl Into<String>) -> Self {
+        self.skipped_names.push(name.into());
+        self
+    }
+
+    pub fn skip_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        if let Ok(path) = std::path::absolute(path) {
+            self.skipped_paths.push(path);
+        }
+        self
+    }
+
+    // hash every file while the scan visits it and fill in the directory
+    // aggregates on the way back up, the same result as a later hash() call
+    // without a second traversal
+    pub fn hash(mut self, hash_type: HashType) -> Self {
+        self.hash = Some(hash_type);
+        self
+    }
+
+    // descend into every directory, build output included
+    pub fn skip_nothing(mut self) -> Self {
+        self.skipped_names.clear();
+        self.build_dir_names.clear();
+        self.skipped_paths.clear();
+        self
+    }
+
+    // also skip the `build.target-dir` set in `root`'s .cargo/config.toml
+    // (or the older .cargo/config), relative to `root` like cargo does
+    pub fn skip_build_dirs_of<P: AsRef<Path>>(self, root: P) -> Self {
+        let root = root.as_ref();
+        let target_dir = ["config.toml", "config"].iter().find_map(|name| {
+            let config: toml::Table = std::fs::read_to_string(root.join(".cargo").join(name))
+                .ok()?
+                .parse()
+                .ok()?;
+            Some(
+                config
+                    .get("build")?
+                    .get("target-dir")?
+                    .as_str()?
+                    .to_string(),
+            )
+        });
+        match target_dir {
+            Some(dir) => self.skip_path(root.join(dir)),
+            None => self,
+        }
+    }
+
+    // whether a scan leaves the directory at `path` out
+    pub fn is_skipped(&self, path: &Path) -> bool {
+        if let Some(name) = path.file_name() {
+            if self.skipped_names.iter().any(|s| name == s.as_str()) {
+                return true;
+            }
+            // build output of the workspace or crate whose root holds it
+            if self.build_dir_names.iter().any(|s| name == s.as_str())
+                && path
+                    .parent()
+                    .is_some_and(|parent| parent.join("Cargo.toml").is_file())
+            {
+                return true;
+            }
+        }
+        !self.skipped_paths.is_empty()
+            && std::path::absolute(path).is_ok_and(|path| {
+                self.skipped_paths
+                    .iter()
+                    .any(|skipped| normalize(skipped) == normalize(&path))
+            })
+    }
+}
+
+// `path` without `.` and `..` components, without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+// symbolic link, target is the raw link content (may be relative or dangling)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsSymlink {
+    pub path: PathBuf,
+    pub target: PathBuf,
+}
+
+impl FsSymlink {
+    pub fn new<P: Into<PathBuf>, T: Into<PathBuf>>(path: P, target: T) -> Self {
+        FsSymlink {
+            path: path.into(),
+            target: target.into(),
+        }
+    }
+
+    pub fn read<P: Into<PathBuf>>(path: P) -> Result<Self, std::io::Error> {
+        let path = path.into();
+        let target = std::fs::read_link(&path)?;
+        Ok(FsSymlink { path, target })
+    }
+
+    pub fn display(&self) -> std::path::Display<'_> {
+        self.path.display()
+    }
+}
+
+// represents file, abastract form the FS and quite dumbed down
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsFile {
-    pub path: String,
+    pub path: PathBuf,
 
     // optional after scan
     pub size: Option<u64>,
@@ -34,16 +328,19 @@ pub struct FsFile {
     // optional after scan (extra)
     pub extension: Option<String>, // part after last dot if any
     pub name: Option<String>,      // part before last dot if any
-    pub magic: Option<String>, // output of `file` command if available, warning, this is unreliable
+    pub magic: Option<String>, // mime type detected from content by type_scan, text/binary fallback for unknown formats
 
     // hashes
     pub crc32: Option<String>,
     pub sha256: Option<String>,
     pub sha512: Option<String>,
     pub blake3: Option<String>,
+    // algorithms without a field of their own, keyed by HashType::name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hashes: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Permissions {
     pub owner_read: bool,
     pub owner_write: bool,
@@ -56,7 +353,32 @@ pub struct Permissions {
     pub others_execute: bool,
 }
 
-#[derive(Debug, Clone)]
+impl Permissions {
+    // from unix mode bits, only the lower nine are looked at
+    pub fn from_mode(mode: u32) -> Self {
+        Permissions {
+            owner_read: mode & 0o400 != 0,
+            owner_write: mode & 0o200 != 0,
+            owner_execute: mode & 0o100 != 0,
+            group_read: mode & 0o040 != 0,
+            group_write: mode & 0o020 != 0,
+            group_execute: mode & 0o010 != 0,
+            others_read: mode & 0o004 != 0,
+            others_write: mode & 0o002 != 0,
+            others_execute: mode & 0o001 != 0,
+        }
+    }
+
+    // summary for systems without mode bits: everyone may read, write unless
+    // readonly, and execute when `executable`
+    pub fn from_readonly(readonly: bool, executable: bool) -> Self {
+        let write = if readonly { 0 } else { 0o222 };
+        let execute = if executable { 0o111 } else { 0 };
+        Self::from_mode(0o444 | write | execute)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtAttributes {
     pub name: String,
     pub value: String,
@@ -64,9 +386,9 @@ pub struct ExtAttributes {
 
 // defines a generic directory type
 // very basic and abstract from the filesystem
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsDirectory {
-    pub path: String,
+    pub path: PathBuf,
     pub elements: Vec<FsElement>,
     pub is_root: bool,
 
@@ -78,12 +400,20 @@ pub struct FsDirectory {
     pub modified: Option<String>,
     pub accessed: Option<String>,
     pub permissions: Option<Permissions>,
+
+    // aggregate hashes over children names and hashes, filled by hash()
+    pub crc32: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    pub blake3: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hashes: BTreeMap<String, String>,
 }
 
 impl FsDirectory {
-    pub fn new(path: String) -> Self {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
         FsDirectory {
-            path,
+            path: path.into(),
             elements: Vec::new(),
             is_root: false,
             is_read: false,
@@ -94,12 +424,17 @@ impl FsDirectory {
             modified: None,
             accessed: None,
             permissions: None,
+            crc32: None,
+            sha256: None,
+            sha512: None,
+            blake3: None,
+            hashes: BTreeMap::new(),
         }
     }
 
-    pub fn new_root(path: String) -> Self {
+    pub fn new_root<P: Into<PathBuf>>(path: P) -> Self {
         FsDirectory {
-            path,
+            path: path.into(),
             elements: Vec::new(),
             is_root: true,
             is_read: false,
@@ -110,10 +445,36 @@ impl FsDirectory {
             modified: None,
             accessed: None,
             permissions: None,
+            crc32: None,
+            sha256: None,
+            sha512: None,
+            blake3: None,
+            hashes: BTreeMap::new(),
         }
     }
 
+    // the path for messages, lossy only when it is not utf-8
+    pub fn display(&self) -> std::path::Display<'_> {
+        self.path.display()
+    }
+
     pub fn scan(&mut self) -> Result<(), std::io::Error> {
+        self.scan_with_options(&ScanOptions::default())
+    }
+
+    pub fn scan_with_options(&mut self, options: &ScanOptions) -> Result<(), std::io::Error> {
+        let mut ancestors = HashSet::new();
+        ancestors.insert(platform::directory_identity(&self.path)?);
+        self.scan_entries(options, &mut ancestors)
+    }
+
+    // ancestors holds (device, inode) of every directory on the current path,
+    // so following a link back up the tree cannot recurse forever
+    fn scan_entries(
+        &mut self,
+        options: &ScanOptions,
+        ancestors: &mut HashSet<(u64, u64)>,
+    ) -> Result<(), std::io::Error> {
         use std::fs;
 
         let entries = fs::read_dir(&self.path)?;
@@ -122,20 +483,86 @@ impl FsDirectory {
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
-            let path_str = path.to_string_lossy().to_string();
+            let is_symlink = entry.file_type()?.is_symlink();
+
+            if is_symlink {
+                match options.symlink_policy {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Record => {
+                        self.elements
+                            .push(FsElement::Symlink(FsSymlink::read(path)?));
+                        continue;
+                    }
+                    SymlinkPolicy::Follow => {}
+                }
+            }
 
             if path.is_file() {
-                let file = FsFile::new(path_str);
+                let mut file = FsFile::new(path);
+                if let Some(hash_type) = options.hash {
+                    file.hash(hash_type)?;
+                }
                 self.elements.push(FsElement::File(file));
-            } else if path.is_dir() {
-                let mut subdir = FsDirectory::new(path_str);
-                subdir.scan()?;
-                self.elements.push(FsElement::Directory(subdir));
+                continue;
+            }
+
+            if path.is_dir() {
+                if options.is_skipped(&path) {
+                    continue;
+                }
+                let identity = platform::directory_identity(&path)?;
+                if ancestors.insert(identity) {
+                    let mut subdir = FsDirectory::new(path.clone());
+                    let result = subdir.scan_entries(options, ancestors);
+                    ancestors.remove(&identity);
+                    result?;
+                    self.elements.push(FsElement::Directory(subdir));
+                    continue;
+                }
             }
+
+            // dangling link or a link back into an ancestor
+            if is_symlink {
+                self.elements
+                    .push(FsElement::Symlink(FsSymlink::read(path)?));
+            }
+        }
+
+        if let Some(hash_type) = options.hash {
+            self.update_aggregate_hashes(hash_type);
+        }
+        Ok(())
+    }
+
+    // persist the tree (including any hashes and metadata already collected) as json
+    // written next to `path` and renamed over it, so a failed write leaves any
+    // previous snapshot in place
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        use std::io::Write;
+
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        let written = (|| {
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&temp)?);
+            serde_json::to_writer_pretty(&mut writer, self)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()
+        })();
+        if let Err(e) = written.and_then(|()| std::fs::rename(&temp, path)) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e);
         }
         Ok(())
     }
 
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
     pub fn hash(&mut self, hash_type: HashType) -> Result<(), std::io::Error> {
         // hash the directory contents (follows files and directories recursively)
         self.hash_recursive(hash_type)
@@ -150,30 +577,105 @@ impl FsDirectory {
                 FsElement::Directory(dir) => {
                     dir.hash_recursive(hash_type)?;
                 }
+                FsElement::Symlink(_) => {}
             }
         }
+
+        self.update_aggregate_hashes(hash_type);
         Ok(())
     }
 
+    // recompute this directory's aggregate from already hashed children
+    pub(crate) fn update_aggregate_hashes(&mut self, hash_type: HashType) {
+        for single in hash_type.components() {
+            let value = self.aggregate_hash(*single);
+            self.set_hash_value(*single, value);
+        }
+    }
+
+    fn set_hash_value(&mut self, hash_type: HashType, value: Option<String>) {
+        match hash_type {
+            HashType::CRC32 => self.crc32 = value,
+            HashType::SHA256 => self.sha256 = value,
+            HashType::SHA512 => self.sha512 = value,
+            HashType::BLAKE3 => self.blake3 = value,
+            HashType::ALL | HashType::FAST => {}
+            other => set_named_hash(&mut self.hashes, other, value),
+        }
+    }
+
+    // merkle-style: entries sorted by name, each contributing kind, name and hash
+    // (or link target), so the result does not depend on where the tree lives
+    fn aggregate_hash(&self, hash_type: HashType) -> Option<String> {
+        let mut entries: Vec<(Vec<u8>, char, Vec<u8>)> = Vec::new();
+        for element in &self.elements {
+            let entry = match element {
+                FsElement::File(file) => (
+                    element_name(&file.path),
+                    'f',
+                    file.hash_value(hash_type)?.as_bytes().to_vec(),
+                ),
+                FsElement::Directory(dir) => (
+                    element_name(&dir.path),
+                    'd',
+                    dir.hash_value(hash_type)?.as_bytes().to_vec(),
+                ),
+                FsElement::Symlink(link) => (
+                    element_name(&link.path),
+                    'l',
+                    link.target.as_os_str().as_encoded_bytes().to_vec(),
+                ),
+            };
+            entries.push(entry);
+        }
+        entries.sort();
+
+        let mut manifest = Vec::new();
+        for (name, kind, value) in entries {
+            manifest.extend_from_slice(format!("{} ", kind).as_bytes());
+            manifest.extend_from_slice(&name);
+            manifest.push(0);
+            manifest.extend_from_slice(&value);
+            manifest.push(b'\n');
+        }
+        hash_bytes(hash_type, &manifest)
+    }
+
+    // ALL and FAST both include blake3, so that is what they resolve to
+    pub fn hash_value(&self, hash_type: HashType) -> Option<&str> {
+        match hash_type {
+            HashType::CRC32 => self.crc32.as_deref(),
+            HashType::SHA256 => self.sha256.as_deref(),
+            HashType::SHA512 => self.sha512.as_deref(),
+            HashType::BLAKE3 | HashType::ALL | HashType::FAST => self.blake3.as_deref(),
+            other => self.hashes.get(other.name()).map(String::as_str),
+        }
+    }
+
+    // compares aggregate hashes, None when both sides were not hashed with a common algorithm
+    pub fn content_equals(&self, other: &FsDirectory) -> Option<bool> {
+        [
+            HashType::BLAKE3,
+            HashType::SHA512,
+            HashType::SHA256,
+            HashType::CRC32,
+        ]
+        .into_iter()
+        .find_map(
+            |kind| match (self.hash_value(kind), other.hash_value(kind)) {
+                (Some(a), Some(b)) => Some(a == b),
+                _ => None,
+            },
+        )
+    }
+
     pub fn metadata_scan(&mut self) -> Result<(), std::io::Error> {
         use chrono::{DateTime, Utc};
         use std::fs;
-        use std::os::unix::fs::PermissionsExt;
 
         let metadata = fs::metadata(&self.path)?;
-        let permissions = metadata.permissions();
-
-        self.permissions = Some(Permissions {
-            owner_read: permissions.mode() & 0o400 != 0,
-            owner_write: permissions.mode() & 0o200 != 0,
-            owner_execute: permissions.mode() & 0o100 != 0,
-            group_read: permissions.mode() & 0o040 != 0,
-            group_write: permissions.mode() & 0o020 != 0,
-            group_execute: permissions.mode() & 0o010 != 0,
-            others_read: permissions.mode() & 0o004 != 0,
-            others_write: permissions.mode() & 0o002 != 0,
-            others_execute: permissions.mode() & 0o001 != 0,
-        });
+        self.permissions = Some(platform::permissions(&self.path, &metadata));
+        (self.owner, self.group) = platform::ownership(&metadata);
 
         if let Ok(created) = metadata.created() {
             let datetime: DateTime<Utc> = created.into();
@@ -197,22 +699,34 @@ impl FsDirectory {
                 FsElement::Directory(dir) => {
                     let _ = dir.metadata_scan();
                 }
+                FsElement::Symlink(_) => {}
             }
         }
 
         Ok(())
     }
 
-    pub fn ext_attributes_scan(&mut self) -> Result<Vec<ExtAttributes>, std::io::Error> {
-        use xattr;
+    // detect content type of every file in the tree, unreadable files are left as-is
+    pub fn type_scan(&mut self) {
+        for element in &mut self.elements {
+            match element {
+                FsElement::File(file) => {
+                    let _ = file.type_scan();
+                }
+                FsElement::Directory(dir) => dir.type_scan(),
+                FsElement::Symlink(_) => {}
+            }
+        }
+    }
 
+    pub fn ext_attributes_scan(&mut self) -> Result<Vec<ExtAttributes>, std::io::Error> {
         let mut attributes = Vec::new();
 
-        match xattr::list(&self.path) {
+        match xattrs::list(&self.path) {
             Ok(names) => {
                 for name in names {
                     if let Some(name_str) = name.to_str()
-                        && let Ok(Some(value)) = xattr::get(&self.path, &name)
+                        && let Ok(Some(value)) = xattrs::get(&self.path, name_str)
                     {
                         let value_str = match String::from_utf8(value) {
                             Ok(s) => s,
@@ -233,24 +747,18 @@ impl FsDirectory {
     }
 
     pub fn set_ext_attribute(&self, name: &str, value: &[u8]) -> Result<(), std::io::Error> {
-        use xattr;
-
-        xattr::set(&self.path, name, value).map_err(std::io::Error::other)
+        xattrs::set(&self.path, name, value)
     }
 
     pub fn remove_ext_attribute(&self, name: &str) -> Result<(), std::io::Error> {
-        use xattr;
-
-        xattr::remove(&self.path, name).map_err(std::io::Error::other)
+        xattrs::remove(&self.path, name)
     }
 
     pub fn get_ext_attribute(&self, name: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
-        use xattr;
-
-        xattr::get(&self.path, name).map_err(std::io::Error::other)
+        xattrs::get(&self.path, name)
     }
 
-    pub fn search_ext_attributes_recursive(&self, attribute_name: &str) -> Vec<(String, String)> {
+    pub fn search_ext_attributes_recursive(&self, attribute_name: &str) -> Vec<(PathBuf, String)> {
         let mut results = Vec::new();
         self.search_ext_attributes_recursive_impl(attribute_name, &mut results);
         results
@@ -259,7 +767,7 @@ impl FsDirectory {
     fn search_ext_attributes_recursive_impl(
         &self,
         attribute_name: &str,
-        results: &mut Vec<(String, String)>,
+        results: &mut Vec<(PathBuf, String)>,
     ) {
         // check this directory
         if let Ok(Some(value)) = self.get_ext_attribute(attribute_name) {
@@ -285,11 +793,12 @@ impl FsDirectory {
                 FsElement::Directory(dir) => {
                     dir.search_ext_attributes_recursive_impl(attribute_name, results);
                 }
+                FsElement::Symlink(_) => {}
             }
         }
     }
 
-    pub fn list_all_ext_attributes_recursive(&self) -> Vec<(String, Vec<ExtAttributes>)> {
+    pub fn list_all_ext_attributes_recursive(&self) -> Vec<(PathBuf, Vec<ExtAttributes>)> {
         let mut results = Vec::new();
         self.list_all_ext_attributes_recursive_impl(&mut results);
         results
@@ -297,7 +806,7 @@ impl FsDirectory {
 
     fn list_all_ext_attributes_recursive_impl(
         &self,
-        results: &mut Vec<(String, Vec<ExtAttributes>)>,
+        results: &mut Vec<(PathBuf, Vec<ExtAttributes>)>,
     ) {
         // get attributes for this directory
         if let Ok(attrs) = FsDirectory::ext_attributes_scan(&mut self.clone())
@@ -319,15 +828,16 @@ impl FsDirectory {
                 FsElement::Directory(dir) => {
                     dir.list_all_ext_attributes_recursive_impl(results);
                 }
+                FsElement::Symlink(_) => {}
             }
         }
     }
 }
 
 impl FsFile {
-    pub fn new(path: String) -> Self {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
         FsFile {
-            path,
+            path: path.into(),
             size: None,
             is_read: false,
             is_write: false,
@@ -344,13 +854,16 @@ impl FsFile {
             sha256: None,
             sha512: None,
             blake3: None,
+            hashes: BTreeMap::new(),
         }
     }
 
+    // the path for messages, lossy only when it is not utf-8
+    pub fn display(&self) -> std::path::Display<'_> {
+        self.path.display()
+    }
+
     pub fn hash(&mut self, hash_type: HashType) -> Result<(), std::io::Error> {
-        use blake3::Hasher as Blake3Hasher;
-        use crc32fast::Hasher as Crc32Hasher;
-        use sha2::{Digest, Sha256, Sha512};
         use std::fs::File;
         use std::io::Read;
 
@@ -358,63 +871,44 @@ impl FsFile {
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
 
-        match hash_type {
-            HashType::CRC32 => {
-                let mut hasher = Crc32Hasher::new();
-                hasher.update(&buffer);
-                self.crc32 = Some(format!("{:08x}", hasher.finalize()));
-            }
-            HashType::SHA256 => {
-                let mut hasher = Sha256::new();
-                hasher.update(&buffer);
-                self.sha256 = Some(format!("{:x}", hasher.finalize()));
-            }
-            HashType::SHA512 => {
-                let mut hasher = Sha512::new();
-                hasher.update(&buffer);
-                self.sha512 = Some(format!("{:x}", hasher.finalize()));
-            }
-            HashType::BLAKE3 => {
-                let mut hasher = Blake3Hasher::new();
-                hasher.update(&buffer);
-                self.blake3 = Some(format!("{}", hasher.finalize().to_hex()));
-            }
-            HashType::ALL => {
-                self.hash(HashType::CRC32)?;
-                self.hash(HashType::SHA256)?;
-                self.hash(HashType::SHA512)?;
-                self.hash(HashType::BLAKE3)?;
-            }
-            HashType::FAST => {
-                self.hash(HashType::CRC32)?;
-                self.hash(HashType::BLAKE3)?;
-            }
+        for single in hash_type.components() {
+            self.set_hash_value(*single, hash_bytes(*single, &buffer));
         }
 
         Ok(())
     }
 
+    pub(crate) fn set_hash_value(&mut self, hash_type: HashType, value: Option<String>) {
+        match hash_type {
+            HashType::CRC32 => self.crc32 = value,
+            HashType::SHA256 => self.sha256 = value,
+            HashType::SHA512 => self.sha512 = value,
+            HashType::BLAKE3 => self.blake3 = value,
+            // selections, not single algorithms
+            HashType::ALL | HashType::FAST => {}
+            other => set_named_hash(&mut self.hashes, other, value),
+        }
+    }
+
+    // ALL and FAST both include blake3, so that is what they resolve to
+    pub fn hash_value(&self, hash_type: HashType) -> Option<&str> {
+        match hash_type {
+            HashType::CRC32 => self.crc32.as_deref(),
+            HashType::SHA256 => self.sha256.as_deref(),
+            HashType::SHA512 => self.sha512.as_deref(),
+            HashType::BLAKE3 | HashType::ALL | HashType::FAST => self.blake3.as_deref(),
+            other => self.hashes.get(other.name()).map(String::as_str),
+        }
+    }
+
     pub fn metadata_scan(&mut self) -> Result<(), std::io::Error> {
         use chrono::{DateTime, Utc};
         use std::fs;
-        use std::os::unix::fs::PermissionsExt;
-        use std::path::Path;
 
         let metadata = fs::metadata(&self.path)?;
-        let permissions = metadata.permissions();
-
         self.size = Some(metadata.len());
-        self.permissions = Some(Permissions {
-            owner_read: permissions.mode() & 0o400 != 0,
-            owner_write: permissions.mode() & 0o200 != 0,
-            owner_execute: permissions.mode() & 0o100 != 0,
-            group_read: permissions.mode() & 0o040 != 0,
-            group_write: permissions.mode() & 0o020 != 0,
-            group_execute: permissions.mode() & 0o010 != 0,
-            others_read: permissions.mode() & 0o004 != 0,
-            others_write: permissions.mode() & 0o002 != 0,
-            others_execute: permissions.mode() & 0o001 != 0,
-        });
+        self.permissions = Some(platform::permissions(&self.path, &metadata));
+        (self.owner, self.group) = platform::ownership(&metadata);
 
         if let Ok(created) = metadata.created() {
             let datetime: DateTime<Utc> = created.into();
@@ -430,7 +924,7 @@ impl FsFile {
         }
 
         // extract file name and extension
-        let path = Path::new(&self.path);
+        let path = self.path.as_path();
         if let Some(file_name) = path.file_name() {
             let file_name_str = file_name.to_string_lossy();
             if let Some(extension) = path.extension() {
@@ -446,16 +940,20 @@ impl FsFile {
         Ok(())
     }
 
-    pub fn ext_attributes_scan(&mut self) -> Result<Vec<ExtAttributes>, std::io::Error> {
-        use xattr;
+    // sniff the leading bytes and store the detected mime type in magic
+    pub fn type_scan(&mut self) -> Result<(), std::io::Error> {
+        self.magic = Some(detect_file_type(&self.path)?);
+        Ok(())
+    }
 
+    pub fn ext_attributes_scan(&mut self) -> Result<Vec<ExtAttributes>, std::io::Error> {
         let mut attributes = Vec::new();
 
-        match xattr::list(&self.path) {
+        match xattrs::list(&self.path) {
             Ok(names) => {
                 for name in names {
                     if let Some(name_str) = name.to_str()
-                        && let Ok(Some(value)) = xattr::get(&self.path, &name)
+                        && let Ok(Some(value)) = xattrs::get(&self.path, name_str)
                     {
                         // convert bytes to string, handling non-UTF8 values
                         let value_str = match String::from_utf8(value) {
@@ -483,21 +981,15 @@ impl FsFile {
     }
 
     pub fn set_ext_attribute(&self, name: &str, value: &[u8]) -> Result<(), std::io::Error> {
-        use xattr;
-
-        xattr::set(&self.path, name, value).map_err(std::io::Error::other)
+        xattrs::set(&self.path, name, value)
     }
 
     pub fn remove_ext_attribute(&self, name: &str) -> Result<(), std::io::Error> {
-        use xattr;
-
-        xattr::remove(&self.path, name).map_err(std::io::Error::other)
+        xattrs::remove(&self.path, name)
     }
 
     pub fn get_ext_attribute(&self, name: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
-        use xattr;
-
-        xattr::get(&self.path, name).map_err(std::io::Error::other)
+        xattrs::get(&self.path, name)
     }
 
     pub fn open(&mut self) -> Result<std::fs::File, std::io::Error> {
@@ -510,13 +1002,12 @@ impl FsFile {
 mod tests {
     use super::*;
     use std::fs;
-    use std::os::unix::fs::PermissionsExt;
     use tempfile::TempDir;
 
     #[test]
     fn test_fsfile_new() {
         let file = FsFile::new("test.txt".to_string());
-        assert_eq!(file.path, "test.txt");
+        assert_eq!(file.path, Path::new("test.txt"));
         assert_eq!(file.size, None);
         assert_eq!(file.extension, None);
         assert_eq!(file.name, None);
@@ -530,7 +1021,7 @@ mod tests {
         let file_path = temp_dir.path().join("test_file.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         assert!(file.size.is_some());
@@ -549,26 +1040,51 @@ mod tests {
         let file_path = temp_dir.path().join("README");
         fs::write(&file_path, "readme content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         assert_eq!(file.extension, None);
         assert_eq!(file.name, Some("README".to_string()));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_fsfile_metadata_scan_owner_and_group() {
+        use crate::utils::filesystem::ownership::{group_name, user_name};
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("owned.txt");
+        fs::write(&file_path, "content").unwrap();
+        let metadata = fs::metadata(&file_path).unwrap();
+
+        let mut file = FsFile::new(file_path);
+        file.metadata_scan().unwrap();
+        assert_eq!(file.owner, Some(user_name(metadata.uid())));
+        assert_eq!(file.group, Some(group_name(metadata.gid())));
+
+        let mut directory = FsDirectory::new(temp_dir.path());
+        directory.metadata_scan().unwrap();
+        assert!(directory.owner.is_some());
+        assert!(directory.group.is_some());
+    }
+
     #[test]
     fn test_fsfile_open() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         let opened_file = file.open().unwrap();
         assert!(opened_file.metadata().unwrap().is_file());
     }
 
+    #[cfg(unix)]
     #[test]
     fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test").unwrap();
@@ -578,7 +1094,7 @@ mod tests {
         perms.set_mode(0o644); // rw-r--r--
         fs::set_permissions(&file_path, perms).unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         let permissions = file.permissions.unwrap();
@@ -596,7 +1112,7 @@ mod tests {
     #[test]
     fn test_fsdirectory_new() {
         let dir = FsDirectory::new("/test/path".to_string());
-        assert_eq!(dir.path, "/test/path");
+        assert_eq!(dir.path, Path::new("/test/path"));
         assert!(dir.elements.is_empty());
         assert!(!dir.is_read);
         assert!(!dir.is_write);
@@ -607,7 +1123,7 @@ mod tests {
     #[test]
     fn test_fsdirectory_new_root() {
         let dir = FsDirectory::new_root("/test/path".to_string());
-        assert_eq!(dir.path, "/test/path");
+        assert_eq!(dir.path, Path::new("/test/path"));
         assert!(dir.elements.is_empty());
         assert!(!dir.is_read);
         assert!(!dir.is_write);
@@ -640,6 +1156,7 @@ mod tests {
                     dirs += 1;
                     assert_eq!(dir.elements.len(), 1); // nested.txt
                 }
+                FsElement::Symlink(_) => panic!("no symlinks were created"),
             }
         }
         assert_eq!(files, 2);
@@ -692,13 +1209,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fsdirectory_snapshot_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let tree_dir = temp_dir.path().join("tree");
+        fs::create_dir_all(tree_dir.join("subdir")).unwrap();
+        fs::write(tree_dir.join("file.txt"), "content").unwrap();
+        fs::write(tree_dir.join("subdir").join("nested.rs"), "fn main() {}").unwrap();
+
+        let mut directory = FsDirectory::new_root(tree_dir);
+        directory.scan().unwrap();
+        directory.metadata_scan().unwrap();
+        directory.hash(HashType::FAST).unwrap();
+
+        let snapshot_path = temp_dir.path().join("snapshot.json");
+        directory.save_snapshot(&snapshot_path).unwrap();
+        let loaded = FsDirectory::load_snapshot(&snapshot_path).unwrap();
+
+        assert_eq!(loaded.path, directory.path);
+        assert!(loaded.is_root);
+        assert!(loaded.permissions.is_some());
+        assert_eq!(loaded.elements.len(), 2);
+
+        let original = serde_json::to_value(&directory).unwrap();
+        let reloaded = serde_json::to_value(&loaded).unwrap();
+        assert_eq!(original, reloaded);
+        assert!(!temp_dir.path().join("snapshot.json.tmp").exists());
+
+        // a snapshot that cannot be put in place is an error, not a partial file
+        let occupied = temp_dir.path().join("occupied");
+        fs::create_dir_all(occupied.join("inside")).unwrap();
+        assert!(directory.save_snapshot(&occupied).is_err());
+        assert!(!temp_dir.path().join("occupied.tmp").exists());
+        assert!(
+            directory
+                .save_snapshot(temp_dir.path().join("missing/snapshot.json"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fsdirectory_load_snapshot_invalid() {
+        let temp_dir = TempDir::new().unwrap();
+        let snapshot_path = temp_dir.path().join("snapshot.json");
+        fs::write(&snapshot_path, "not json").unwrap();
+
+        assert!(FsDirectory::load_snapshot(&snapshot_path).is_err());
+        assert!(FsDirectory::load_snapshot(temp_dir.path().join("missing.json")).is_err());
+    }
+
     #[test]
     fn test_fsfile_hash_crc32() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::CRC32).unwrap();
 
         assert!(file.crc32.is_some());
@@ -714,7 +1280,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::SHA256).unwrap();
 
         assert!(file.sha256.is_some());
@@ -731,7 +1297,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::SHA512).unwrap();
 
         assert!(file.sha512.is_some());
@@ -747,7 +1313,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::BLAKE3).unwrap();
 
         assert!(file.blake3.is_some());
@@ -757,13 +1323,39 @@ mod tests {
         assert!(file.sha512.is_none());
     }
 
+    #[test]
+    fn test_fsfile_hash_named_algorithms() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "test content").unwrap();
+
+        let mut file = FsFile::new(file_path);
+        file.hash(HashType::GITBLOB).unwrap();
+        file.hash("xxh64".parse().unwrap()).unwrap();
+
+        assert_eq!(
+            file.hash_value(HashType::GITBLOB),
+            Some("08cf6101416f0ce0dda3c80e627f333854c4085c")
+        );
+        assert_eq!(file.hashes["xxh64"].len(), 16);
+        assert!(file.blake3.is_none());
+        assert!("md5".parse::<HashType>().is_err());
+
+        // aggregates work the same for algorithms without a field
+        let mut directory = FsDirectory::new_root(temp_dir.path());
+        directory.scan().unwrap();
+        directory.hash(HashType::XXH128).unwrap();
+        assert_eq!(directory.hash_value(HashType::XXH128).unwrap().len(), 32);
+        assert!(directory.hash_value(HashType::XXH64).is_none());
+    }
+
     #[test]
     fn test_fsfile_hash_all() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::ALL).unwrap();
 
         assert!(file.crc32.is_some());
@@ -778,7 +1370,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::FAST).unwrap();
 
         assert!(file.crc32.is_some());
@@ -796,8 +1388,8 @@ mod tests {
         fs::write(&file1_path, "content A").unwrap();
         fs::write(&file2_path, "content B").unwrap();
 
-        let mut file1 = FsFile::new(file1_path.to_string_lossy().to_string());
-        let mut file2 = FsFile::new(file2_path.to_string_lossy().to_string());
+        let mut file1 = FsFile::new(file1_path);
+        let mut file2 = FsFile::new(file2_path);
 
         file1.hash(HashType::SHA256).unwrap();
         file2.hash(HashType::SHA256).unwrap();
@@ -811,7 +1403,7 @@ mod tests {
         let file_path = temp_dir.path().join("empty.txt");
         fs::write(&file_path, "").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.hash(HashType::SHA256).unwrap();
 
         assert!(file.sha256.is_some());
@@ -854,10 +1446,137 @@ mod tests {
                         }
                     }
                 }
+                FsElement::Symlink(_) => {}
             }
         }
     }
 
+    fn build_tree(root: &std::path::Path) {
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        fs::write(root.join("a").join("one.txt"), "one").unwrap();
+        fs::write(root.join("b").join("two.txt"), "two").unwrap();
+    }
+
+    fn hashed_tree(root: &std::path::Path, hash_type: HashType) -> FsDirectory {
+        let mut directory = FsDirectory::new_root(root);
+        directory.scan().unwrap();
+        directory.hash(hash_type).unwrap();
+        directory
+    }
+
+    fn subdir<'a>(directory: &'a FsDirectory, name: &str) -> &'a FsDirectory {
+        directory
+            .elements
+            .iter()
+            .find_map(|e| match e {
+                FsElement::Directory(d) if d.path.ends_with(name) => Some(d),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fsdirectory_aggregate_hash_location_independent() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        build_tree(first.path());
+        build_tree(second.path());
+
+        let a = hashed_tree(first.path(), HashType::BLAKE3);
+        let b = hashed_tree(second.path(), HashType::BLAKE3);
+
+        assert!(a.blake3.is_some());
+        assert_eq!(a.blake3, b.blake3);
+        assert_eq!(a.content_equals(&b), Some(true));
+    }
+
+    #[test]
+    fn test_scan_hashes_in_one_pass() {
+        let temp_dir = TempDir::new().unwrap();
+        build_tree(temp_dir.path());
+
+        let mut directory = FsDirectory::new_root(temp_dir.path());
+        directory
+            .scan_with_options(&ScanOptions::new().hash(HashType::FAST))
+            .unwrap();
+        let two_pass = hashed_tree(temp_dir.path(), HashType::FAST);
+
+        assert!(directory.blake3.is_some());
+        assert_eq!(directory.blake3, two_pass.blake3);
+        assert_eq!(directory.crc32, two_pass.crc32);
+        assert_eq!(
+            subdir(&directory, "a").blake3,
+            subdir(&two_pass, "a").blake3
+        );
+        assert!(directory.sha256.is_none());
+    }
+
+    #[test]
+    fn test_fsdirectory_aggregate_hash_propagates_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        build_tree(temp_dir.path());
+        let before = hashed_tree(temp_dir.path(), HashType::SHA256);
+
+        fs::write(temp_dir.path().join("a").join("one.txt"), "changed").unwrap();
+        let after = hashed_tree(temp_dir.path(), HashType::SHA256);
+
+        assert_eq!(before.content_equals(&after), Some(false));
+        assert_ne!(subdir(&before, "a").sha256, subdir(&after, "a").sha256);
+        // untouched sibling subtree keeps its hash
+        assert_eq!(subdir(&before, "b").sha256, subdir(&after, "b").sha256);
+
+        // renaming a file changes the parent hash even with identical content
+        fs::rename(
+            temp_dir.path().join("b").join("two.txt"),
+            temp_dir.path().join("b").join("renamed.txt"),
+        )
+        .unwrap();
+        let renamed = hashed_tree(temp_dir.path(), HashType::SHA256);
+        assert_ne!(subdir(&after, "b").sha256, subdir(&renamed, "b").sha256);
+    }
+
+    #[test]
+    fn test_fsdirectory_aggregate_hash_all() {
+        let temp_dir = TempDir::new().unwrap();
+        build_tree(temp_dir.path());
+        let directory = hashed_tree(temp_dir.path(), HashType::ALL);
+
+        assert!(directory.crc32.is_some());
+        assert!(directory.sha256.is_some());
+        assert!(directory.sha512.is_some());
+        assert!(directory.blake3.is_some());
+
+        // unhashed tree has nothing to compare against
+        let unhashed = FsDirectory::new(temp_dir.path());
+        assert_eq!(directory.content_equals(&unhashed), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fsdirectory_non_utf8_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // both names are "caf\u{fffd}.txt" once made lossy
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let first_name = OsStr::from_bytes(b"caf\xe9.txt");
+        let second_name = OsStr::from_bytes(b"caf\xe8.txt");
+        fs::write(first.path().join(first_name), "same").unwrap();
+        fs::write(second.path().join(second_name), "same").unwrap();
+
+        let a = hashed_tree(first.path(), HashType::BLAKE3);
+        let b = hashed_tree(second.path(), HashType::BLAKE3);
+
+        let FsElement::File(file) = &a.elements[0] else {
+            panic!("expected a file");
+        };
+        assert_eq!(file.path, first.path().join(first_name));
+        assert!(file.blake3.is_some());
+        assert_eq!(a.content_equals(&b), Some(false));
+    }
+
     #[test]
     fn test_hashtype_debug() {
         assert_eq!(format!("{:?}", HashType::CRC32), "CRC32");
@@ -882,7 +1601,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         // verify all time fields are RFC 3339 compliant
@@ -1006,7 +1725,7 @@ mod tests {
         fs::write(&file_path, "consistency test").unwrap();
         let after = SystemTime::now() + std::time::Duration::from_secs(1);
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         // verify the RFC 3339 timestamp falls within our time window
@@ -1033,7 +1752,7 @@ mod tests {
         let file_path = temp_dir.path().join("format_test.txt");
         fs::write(&file_path, "format test").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
         file.metadata_scan().unwrap();
 
         if let Some(created) = &file.created {
@@ -1077,7 +1796,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
 
         // test setting extended attribute
         let result = file.set_ext_attribute("user.test_attr", b"test_value");
@@ -1250,7 +1969,7 @@ mod tests {
         let file_path = temp_dir.path().join("binary_test.txt");
         fs::write(&file_path, "binary test").unwrap();
 
-        let mut file = FsFile::new(file_path.to_string_lossy().to_string());
+        let mut file = FsFile::new(file_path);
 
         // test binary data
         let binary_data = vec![0x00, 0x01, 0x02, 0xFF, 0xFE, 0xFD];
@@ -0,0 +1,359 @@
+// lazy depth-first traversal: entries come out as they are read, so only the
+// directories still being listed are held in memory, never the whole tree
+// follows the same ScanOptions rules as FsDirectory::scan_with_options, files
+// are hashed when the options ask for it but directories get no aggregates
+
+use crate::utils::alt::{Evaluable, LogicExpr};
+use crate::utils::filesystem::platform;
+use crate::utils::filesystem::predicates::{PredicateContext, PredicateError};
+use crate::utils::filesystem::types::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// one element met during a walk, directories come without their elements
+#[derive(Debug, Clone)]
+pub struct FsEntry {
+    pub element: FsElement,
+    pub depth: usize, // 1 for the direct children of the walk root
+}
+
+impl FsEntry {
+    pub fn path(&self) -> &Path {
+        self.element.path()
+    }
+
+    pub fn is_file(&self) -> bool {
+        matches!(self.element, FsElement::File(_))
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self.element, FsElement::Directory(_))
+    }
+}
+
+type PruneFn = Box<dyn Fn(&PredicateContext) -> bool>;
+
+// directory being listed, with the identity it holds in `ancestors`
+struct Level {
+    entries: fs::ReadDir,
+    depth: usize,
+    identity: (u64, u64),
+}
+
+pub struct Walk {
+    options: ScanOptions,
+    prune: Option<PruneFn>,
+    metadata: bool,
+    stack: Vec<Level>,
+    ancestors: HashSet<(u64, u64)>,
+    pending: Option<std::io::Error>, // failure to open the root, reported first
+}
+
+impl Walk {
+    pub fn new<P: Into<PathBuf>>(root: P, options: ScanOptions) -> Self {
+        let root = root.into();
+        let mut walk = Walk {
+            options,
+            prune: None,
+            metadata: false,
+            stack: Vec::new(),
+            ancestors: HashSet::new(),
+            pending: None,
+        };
+        let identity = platform::directory_identity(&root);
+        match (fs::read_dir(&root), identity) {
+            (Ok(entries), Ok(identity)) => {
+                walk.ancestors.insert(identity);
+                walk.stack.push(Level {
+                    entries,
+                    depth: 1,
+                    identity,
+                });
+            }
+            (Err(e), _) | (_, Err(e)) => walk.pending = Some(e),
+        }
+        walk
+    }
+
+    // leave out every directory the predicate matches, along with everything
+    // below it; the directory context lists its direct children, and a
+    // predicate that fails to evaluate prunes nothing
+    pub fn prune<T>(mut self, predicate: LogicExpr<T>) -> Self
+    where
+        T: Evaluable<Context = PredicateContext, Error = PredicateError> + 'static,
+    {
+        self.prune = Some(Box::new(move |context| {
+            predicate.evaluate(context).unwrap_or(false)
+        }));
+        self
+    }
+
+    // run metadata_scan on every file and directory before it is returned
+    pub fn with_metadata(mut self) -> Self {
+        self.metadata = true;
+        self
+    }
+
+    // the directory at `path` as a predicate sees it
+    fn context(&self, path: &Path) -> Result<PredicateContext, std::io::Error> {
+        let mut directory = FsDirectory::new(path);
+        for entry in fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            directory.elements.push(if entry_path.is_dir() {
+                FsElement::Directory(FsDirectory::new(entry_path))
+            } else {
+                FsElement::File(FsFile::new(entry_path))
+            });
+        }
+        if self.metadata {
+            let _ = directory.metadata_scan();
+        }
+        Ok(PredicateContext::new(directory))
+    }
+
+    fn file(&self, path: PathBuf, depth: usize) -> Result<FsEntry, std::io::Error> {
+        let mut file = FsFile::new(path);
+        if self.metadata {
+            let _ = file.metadata_scan();
+        }
+        if let Some(hash_type) = self.options.hash {
+            file.hash(hash_type)?;
+        }
+        Ok(FsEntry {
+            element: FsElement::File(file),
+            depth,
+        })
+    }
+
+    fn symlink(path: PathBuf, depth: usize) -> Result<FsEntry, std::io::Error> {
+        Ok(FsEntry {
+            element: FsElement::Symlink(FsSymlink::read(path)?),
+            depth,
+        })
+    }
+
+    // None when the directory is skipped or pruned; a link back into an
+    // ancestor is returned as the link itself
+    fn enter(
+        &mut self,
+        path: PathBuf,
+        depth: usize,
+        is_symlink: bool,
+    ) -> Result<Option<FsEntry>, std::io::Error> {
+        if self.options.is_skipped(&path) {
+            return Ok(None);
+        }
+        let identity = platform::directory_identity(&path)?;
+        if self.ancestors.contains(&identity) {
+            return if is_symlink {
+                Self::symlink(path, depth).map(Some)
+            } else {
+                Ok(None)
+            };
+        }
+
+        let directory = match &self.prune {
+            Some(prune) => {
+                let context = self.context(&path)?;
+                if prune(&context) {
+                    return Ok(None);
+                }
+                FsDirectory {
+                    elements: Vec::new(),
+                    ..context.current_directory
+                }
+            }
+            None => {
+                let mut directory = FsDirectory::new(&path);
+                if self.metadata {
+                    let _ = directory.metadata_scan();
+                }
+                directory
+            }
+        };
+
+        self.stack.push(Level {
+            entries: fs::read_dir(&path)?,
+            depth: depth + 1,
+            identity,
+        });
+        self.ancestors.insert(identity);
+        Ok(Some(FsEntry {
+            element: FsElement::Directory(directory),
+            depth,
+        }))
+    }
+
+    fn visit(
+        &mut self,
+        entry: Result<fs::DirEntry, std::io::Error>,
+        depth: usize,
+    ) -> Result<Option<FsEntry>, std::io::Error> {
+        let entry = entry?;
+        let path = entry.path();
+        let is_symlink = entry.file_type()?.is_symlink();
+
+        if is_symlink {
+            match self.options.symlink_policy {
+                SymlinkPolicy::Skip => return Ok(None),
+                SymlinkPolicy::Record => return Self::symlink(path, depth).map(Some),
+                SymlinkPolicy::Follow => {}
+            }
+        }
+
+        if path.is_file() {
+            self.file(path, depth).map(Some)
+        } else if path.is_dir() {
+            self.enter(path, depth, is_symlink)
+        } else if is_symlink {
+            // dangling
+            Self::symlink(path, depth).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<FsEntry, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.pending.take() {
+            return Some(Err(e));
+        }
+
+        loop {
+            let level = self.stack.last_mut()?;
+            let depth = level.depth;
+            let Some(entry) = level.entries.next() else {
+                if let Some(level) = self.stack.pop() {
+                    self.ancestors.remove(&level.identity);
+                }
+                continue;
+            };
+
+            match self.visit(entry, depth) {
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::filesystem::predicates::{DirectoryContainsPredicate, FilePredicate};
+    use tempfile::TempDir;
+
+    fn fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src").join("nested")).unwrap();
+        fs::create_dir_all(root.join("vendor").join("dep")).unwrap();
+        fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::write(root.join("src").join("lib.rs"), "").unwrap();
+        fs::write(root.join("src").join("nested").join("mod.rs"), "").unwrap();
+        fs::write(root.join("vendor").join("CACHEDIR.TAG"), "").unwrap();
+        fs::write(root.join("vendor").join("dep").join("lib.rs"), "").unwrap();
+        fs::write(root.join("target").join("debug").join("out"), "").unwrap();
+        temp_dir
+    }
+
+    fn relative(walk: Walk, root: &Path) -> Vec<(String, usize)> {
+        let mut entries: Vec<_> = walk
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let path = entry.path().strip_prefix(root).unwrap();
+                (path.to_string_lossy().replace('\\', "/"), entry.depth)
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn test_walk() {
+        let temp_dir = fixture();
+        let entries = relative(
+            Walk::new(temp_dir.path(), ScanOptions::default()),
+            temp_dir.path(),
+        );
+        assert_eq!(
+            entries,
+            vec![
+                ("Cargo.toml".to_string(), 1),
+                ("src".to_string(), 1),
+                ("src/lib.rs".to_string(), 2),
+                ("src/nested".to_string(), 2),
+                ("src/nested/mod.rs".to_string(), 3),
+                ("vendor".to_string(), 1),
+                ("vendor/CACHEDIR.TAG".to_string(), 2),
+                ("vendor/dep".to_string(), 2),
+                ("vendor/dep/lib.rs".to_string(), 3),
+            ]
+        );
+
+        // a directory comes out before anything inside it
+        let order: Vec<PathBuf> = Walk::new(temp_dir.path(), ScanOptions::default())
+            .map(|entry| entry.unwrap().path().to_path_buf())
+            .collect();
+        let position = |p: &Path| order.iter().position(|o| o == p).unwrap();
+        let src = temp_dir.path().join("src");
+        assert!(position(&src) < position(&src.join("lib.rs")));
+    }
+
+    #[test]
+    fn test_walk_prune() {
+        let temp_dir = fixture();
+        let walk = Walk::new(temp_dir.path(), ScanOptions::default()).prune(LogicExpr::Leaf(
+            FilePredicate::DirectoryContains(DirectoryContainsPredicate::new("CACHEDIR.TAG")),
+        ));
+        let entries = relative(walk, temp_dir.path());
+        assert!(entries.iter().all(|(path, _)| !path.starts_with("vendor")));
+        assert!(entries.contains(&("src/nested/mod.rs".to_string(), 3)));
+    }
+
+    #[test]
+    fn test_walk_metadata_and_errors() {
+        let temp_dir = fixture();
+        let files: Vec<FsFile> = Walk::new(temp_dir.path(), ScanOptions::new().skip_nothing())
+            .with_metadata()
+            .filter_map(|entry| match entry.unwrap().element {
+                FsElement::File(file) => Some(file),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(files.len(), 6);
+        assert!(files.iter().all(|file| file.size == Some(0)));
+
+        let mut hashed = Walk::new(temp_dir.path(), ScanOptions::new().hash(HashType::CRC32))
+            .filter_map(|entry| match entry.unwrap().element {
+                FsElement::File(file) => Some(file),
+                _ => None,
+            });
+        assert!(hashed.all(|file| file.crc32.is_some()));
+
+        let mut missing = Walk::new(temp_dir.path().join("missing"), ScanOptions::default());
+        assert!(missing.next().unwrap().is_err());
+        assert!(missing.next().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_follow_stops_at_cycle() {
+        let temp_dir = fixture();
+        std::os::unix::fs::symlink(temp_dir.path(), temp_dir.path().join("src").join("up"))
+            .unwrap();
+
+        let entries = relative(
+            Walk::new(temp_dir.path(), ScanOptions::default()),
+            temp_dir.path(),
+        );
+        assert!(entries.contains(&("src/up".to_string(), 2)));
+        assert!(entries.iter().all(|(path, _)| !path.starts_with("src/up/")));
+    }
+}
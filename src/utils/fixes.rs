@@ -0,0 +1,503 @@
+// fix-it patches: the mechanical fixes for missing version bumps, changelog
+// sections and stale requirements as unified diffs, for a developer to review
+// and `git apply` instead of deptrack editing the tree
+
+use super::cargo_ops::VersionBumpAnalysis;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::changelog::{ChangelogAnalysis, ChangelogConfig, parse_changelog_content};
+use super::changelog_checker::ChangelogChecker;
+use super::release::{BumpLevel, add_version_section};
+use super::requirement_sync::RequirementSyncReport;
+use super::severity::IssueType;
+use super::version_policy::RequiredBump;
+use crate::error::{Error, Result};
+use crate::utils::toml_ops::TomlWriter;
+use semver::Version;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// where `check-versions --write-patches` puts the patches, relative to the
+/// repository root
+pub const FIXES_DIR: &str = ".deptrack/fixes";
+
+// unchanged lines around each hunk
+const CONTEXT: usize = 3;
+
+// largest changed region compared line by line, bigger ones are replaced whole
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// the fixes for one crate, or for the [workspace.dependencies] of a workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct FixPatch {
+    /// crate the fixes are for, None for [workspace.dependencies] entries
+    pub crate_id: Option<CrateId>,
+    /// file name of the patch under [`FIXES_DIR`]
+    pub file_name: String,
+    /// what the patch does, one line per fix
+    pub fixes: Vec<String>,
+    /// files the patch changes, relative to the repository
+    pub files: Vec<PathBuf>,
+    /// unified diff to `git apply` from the repository root
+    pub diff: String,
+}
+
+impl FixPatch {
+    /// the patch file: the fixes as a preamble `git apply` skips, then the diff
+    pub fn contents(&self) -> String {
+        let mut contents = String::new();
+        for fix in &self.fixes {
+            contents.push_str(&format!("# {}\n", fix));
+        }
+        contents.push('\n');
+        contents.push_str(&self.diff);
+        contents
+    }
+}
+
+// fixes collected for one patch, every file edited in memory
+struct PatchBuilder {
+    crate_id: Option<CrateId>,
+    name: String,
+    fixes: Vec<String>,
+    // original content (None for a new file) and edited content by path
+    files: BTreeMap<PathBuf, (Option<String>, String)>,
+}
+
+impl PatchBuilder {
+    // apply `edit` to the current content of `path`, an empty string when the
+    // file does not exist; an edit returning None is dropped
+    fn edit(&mut self, path: &Path, edit: impl FnOnce(&str) -> Option<String>) -> Result<bool> {
+        if !self.files.contains_key(path) {
+            let original = match fs::read_to_string(path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(Error::FileReadError {
+                        path: path.to_path_buf(),
+                        source: e,
+                    });
+                }
+            };
+            let current = original.clone().unwrap_or_default();
+            self.files.insert(path.to_path_buf(), (original, current));
+        }
+
+        let (_, current) = self.files.get_mut(path).expect("inserted above");
+        match edit(current) {
+            Some(edited) => {
+                *current = edited;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn build(self, repo_path: &Path) -> Option<FixPatch> {
+        let mut files = Vec::new();
+        let mut diff = String::new();
+        for (path, (original, edited)) in &self.files {
+            let relative = path.strip_prefix(repo_path).unwrap_or(path);
+            let file_diff = unified_diff(relative, original.as_deref(), edited);
+            if !file_diff.is_empty() {
+                files.push(relative.to_path_buf());
+                diff.push_str(&file_diff);
+            }
+        }
+        if diff.is_empty() {
+            return None;
+        }
+
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        Some(FixPatch {
+            crate_id: self.crate_id,
+            file_name: format!("{}.patch", file_name),
+            fixes: self.fixes,
+            files,
+            diff,
+        })
+    }
+}
+
+/// turns the mechanical issues of a check into patches
+pub struct FixPlanner;
+
+impl FixPlanner {
+    /// a patch per crate with the version bumps `versions` asks for, a
+    /// changelog section for each version a crate lacks one for, and the
+    /// requirement updates of `requirements`; files are read but not written
+    pub fn plan<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        versions: &VersionBumpAnalysis,
+        changelogs: Option<&ChangelogAnalysis>,
+        changelog_config: &ChangelogConfig,
+        requirements: &RequirementSyncReport,
+    ) -> Result<Vec<FixPatch>> {
+        let repo_path = repo_path.as_ref();
+        let mut builders: BTreeMap<String, PatchBuilder> = BTreeMap::new();
+
+        // version bumps, from the base version by the required level; new
+        // crates have no base and inherited versions are left alone
+        let mut bumped: HashMap<&CrateId, Version> = HashMap::new();
+        for status in versions.crate_versions.values() {
+            let level = match status.required_bump {
+                _ if status.is_new => continue,
+                RequiredBump::None => continue,
+                RequiredBump::Patch => BumpLevel::Patch,
+                RequiredBump::Minor => BumpLevel::Minor,
+                RequiredBump::Major => BumpLevel::Major,
+            };
+            let Some(info) = graph.crates.get(&status.crate_id) else {
+                continue;
+            };
+            if !status
+                .issues
+                .iter()
+                .any(|issue| issue.issue_type == IssueType::NoVersionBump)
+            {
+                continue;
+            }
+
+            let version = level.apply(&status.base_version);
+            let patch = crate_patch(&mut builders, graph, &status.crate_id);
+            let edited = patch.edit(&info.cargo_toml_path, |content| {
+                let mut writer =
+                    TomlWriter::parse_string(content, info.cargo_toml_path.display().to_string())
+                        .ok()?;
+                writer.set_package_version(&version.to_string()).ok()?;
+                Some(writer.document().to_string())
+            })?;
+            if edited {
+                patch.fixes.push(format!(
+                    "bump version {} -> {} ({} change since {})",
+                    status.current_version, version, level, status.base_version
+                ));
+                bumped.insert(&status.crate_id, version);
+            }
+        }
+
+        // a section for the version each crate is released at, when its
+        // changelog was flagged or the version was just bumped
+        for status in changelogs
+            .iter()
+            .flat_map(|analysis| analysis.statuses.values())
+        {
+            let Some(info) = graph.crates.get(&status.crate_id) else {
+                continue;
+            };
+            let flagged = status.issues.iter().any(|issue| {
+                matches!(
+                    issue.issue_type,
+                    IssueType::MissingChangelog | IssueType::MissingVersionEntry
+                )
+            });
+            let version = match bumped.get(&status.crate_id) {
+                Some(version) if status.has_changelog || flagged => version.clone(),
+                Some(_) => continue,
+                None if flagged => match Version::parse(&info.version) {
+                    Ok(version) => version,
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+
+            let path = ChangelogChecker::changelog_path(&info.path, changelog_config);
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let patch = crate_patch(&mut builders, graph, &status.crate_id);
+            let edited = patch.edit(&path, |content| {
+                if parse_changelog_content(&path, content).has_version(&version) {
+                    return None;
+                }
+                Some(add_version_section(content, &version))
+            })?;
+            if edited {
+                patch.fixes.push(format!(
+                    "add a [{}] section to {}, its entries are up to you",
+                    version, file_name
+                ));
+            }
+        }
+
+        // requirements, in the manifest that declares them
+        for stale in &requirements.stale {
+            let patch = requirement_patch(
+                &mut builders,
+                graph,
+                stale.dependent.as_ref(),
+                &stale.manifest,
+            );
+            let edited = patch.edit(&stale.manifest, |content| {
+                let mut writer =
+                    TomlWriter::parse_string(content, stale.manifest.display().to_string()).ok()?;
+                (writer.set_dependency_version(&stale.dependency.name, &stale.synced) > 0)
+                    .then(|| writer.document().to_string())
+            })?;
+            if edited {
+                patch.fixes.push(format!(
+                    "update the requirement on {} from \"{}\" to \"{}\"",
+                    stale.dependency.name, stale.requirement, stale.synced
+                ));
+            }
+        }
+        for missing in &requirements.missing {
+            let patch = requirement_patch(
+                &mut builders,
+                graph,
+                missing.dependent.as_ref(),
+                &missing.manifest,
+            );
+            let edited = patch.edit(&missing.manifest, |content| {
+                let mut writer =
+                    TomlWriter::parse_string(content, missing.manifest.display().to_string())
+                        .ok()?;
+                (writer.set_path_dependency_version(&missing.dependency.name, &missing.synced) > 0)
+                    .then(|| writer.document().to_string())
+            })?;
+            if edited {
+                patch.fixes.push(format!(
+                    "add the requirement \"{}\" to the path dependency on {}",
+                    missing.synced, missing.dependency.name
+                ));
+            }
+        }
+
+        Ok(builders
+            .into_values()
+            .filter_map(|builder| builder.build(repo_path))
+            .collect())
+    }
+
+    /// write each patch to `dir`, replacing the patches of an earlier run;
+    /// returns the paths written
+    pub fn write_patches(patches: &[FixPatch], dir: &Path) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "patch") {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        let mut written = Vec::new();
+        for patch in patches {
+            let path = dir.join(&patch.file_name);
+            fs::write(&path, patch.contents())?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+// the patch collecting the fixes of `crate_id`, named after the crate and
+// its workspace too when another workspace has a crate of that name
+fn crate_patch<'a>(
+    builders: &'a mut BTreeMap<String, PatchBuilder>,
+    graph: &CrateDependencyGraph,
+    crate_id: &CrateId,
+) -> &'a mut PatchBuilder {
+    let name = if graph
+        .crates
+        .keys()
+        .filter(|id| id.name == crate_id.name)
+        .count()
+        > 1
+    {
+        crate_id.display_name()
+    } else {
+        crate_id.name.clone()
+    };
+    patch(builders, Some(crate_id), name)
+}
+
+// the patch of the crate declaring a requirement, or of the workspace for
+// [workspace.dependencies]
+fn requirement_patch<'a>(
+    builders: &'a mut BTreeMap<String, PatchBuilder>,
+    graph: &CrateDependencyGraph,
+    dependent: Option<&CrateId>,
+    manifest: &Path,
+) -> &'a mut PatchBuilder {
+    match dependent {
+        Some(crate_id) => crate_patch(builders, graph, crate_id),
+        None => {
+            let workspace = graph
+                .workspaces
+                .iter()
+                .find(|workspace| workspace.cargo_toml_path == manifest)
+                .map_or("workspace", |workspace| workspace.name.as_str());
+            patch(
+                builders,
+                None,
+                format!("{}-workspace-dependencies", workspace),
+            )
+        }
+    }
+}
+
+fn patch<'a>(
+    builders: &'a mut BTreeMap<String, PatchBuilder>,
+    crate_id: Option<&CrateId>,
+    name: String,
+) -> &'a mut PatchBuilder {
+    builders
+        .entry(name.clone())
+        .or_insert_with(|| PatchBuilder {
+            crate_id: crate_id.cloned(),
+            name,
+            fixes: Vec::new(),
+            files: BTreeMap::new(),
+        })
+}
+
+/// unified diff turning `old` into `new` at `path`, a new file when `old` is
+/// None; empty when nothing changed
+pub fn unified_diff(path: &Path, old: Option<&str>, new: &str) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let old_lines: Vec<&str> = old.unwrap_or_default().split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = edit_script(&old_lines, &new_lines);
+    let changed: Vec<usize> = (0..ops.len()).filter(|&i| ops[i].0 != ' ').collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("diff --git a/{0} b/{0}\n", path);
+    match old {
+        Some(_) => diff.push_str(&format!("--- a/{}\n", path)),
+        None => diff.push_str("new file mode 100644\n--- /dev/null\n"),
+    }
+    diff.push_str(&format!("+++ b/{}\n", path));
+
+    // lines of each side before every operation
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for (kind, _) in &ops {
+        positions.push((old_line, new_line));
+        match kind {
+            '-' => old_line += 1,
+            '+' => new_line += 1,
+            _ => {
+                old_line += 1;
+                new_line += 1;
+            }
+        }
+    }
+
+    let mut i = 0;
+    while i < changed.len() {
+        let start = changed[i].saturating_sub(CONTEXT);
+        // changes with at most twice the context between them share a hunk
+        while i + 1 < changed.len() && changed[i + 1] - changed[i] <= 2 * CONTEXT + 1 {
+            i += 1;
+        }
+        let end = (changed[i] + CONTEXT).min(ops.len() - 1);
+        i += 1;
+
+        let hunk = &ops[start..=end];
+        let old_count = hunk.iter().filter(|(kind, _)| *kind != '+').count();
+        let new_count = hunk.iter().filter(|(kind, _)| *kind != '-').count();
+        let (old_start, new_start) = positions[start];
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + usize::from(old_count > 0),
+            old_count,
+            new_start + usize::from(new_count > 0),
+            new_count
+        ));
+        for (kind, line) in hunk {
+            diff.push(*kind);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    diff
+}
+
+// (' ' | '-' | '+', line) operations turning `old` into `new`, from the
+// longest common subsequence of the lines between their common ends
+fn edit_script<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(char, &str)> = old[..prefix].iter().map(|line| (' ', *line)).collect();
+    if a.len() * b.len() <= MAX_LCS_CELLS {
+        // lcs[i][j]: longest common subsequence of a[i..] and b[j..]
+        let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push((' ', a[i]));
+                i += 1;
+                j += 1;
+            } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(('-', a[i]));
+                i += 1;
+            } else {
+                ops.push(('+', b[j]));
+                j += 1;
+            }
+        }
+    } else {
+        ops.extend(a.iter().map(|line| ('-', *line)));
+        ops.extend(b.iter().map(|line| ('+', *line)));
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|line| (' ', *line)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn";
+        assert_eq!(
+            unified_diff(Path::new("x.txt"), Some(old), new),
+            "diff --git a/x.txt b/x.txt\n--- a/x.txt\n+++ b/x.txt\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -11,3 +11,4 @@\n k\n l\n m\n+n\n\\ No newline at end of file\n"
+        );
+
+        assert_eq!(
+            unified_diff(Path::new("new.md"), None, "# CHANGELOG\n"),
+            "diff --git a/new.md b/new.md\nnew file mode 100644\n--- /dev/null\n+++ b/new.md\n\
+             @@ -0,0 +1,1 @@\n+# CHANGELOG\n"
+        );
+        assert!(unified_diff(Path::new("x.txt"), Some(old), old).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+// base reference picked when check-versions is given none: where the branch
+// forked from its upstream, otherwise the last release
+
+use super::repository::GitOps;
+use super::tag_pattern::TagPattern;
+use super::types::GitRef;
+use crate::error::Result;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// where a detected base reference comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseRefSource {
+    /// merge-base of HEAD and the branch it tracks
+    UpstreamMergeBase,
+    /// newest release tag reachable from HEAD
+    ReleaseTag,
+}
+
+/// base reference detected for a comparison
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BaseRef {
+    pub source: BaseRefSource,
+    /// the upstream branch or the tag
+    pub name: String,
+    /// reference to compare from, a commit hash or `refs/tags/<tag>`
+    pub git_ref: String,
+}
+
+impl BaseRef {
+    /// the upstream merge-base when HEAD has commits of its own on top of it,
+    /// otherwise the newest tag reachable from HEAD that names a release of
+    /// one of `crate_names`; None when neither exists
+    pub fn detect<P: AsRef<Path>>(
+        repo_path: P,
+        patterns: &[TagPattern],
+        crate_names: &[String],
+    ) -> Result<Option<Self>> {
+        let repo_path = repo_path.as_ref();
+
+        if let Some(upstream) = GitOps::upstream_branch(repo_path)? {
+            let base = GitOps::merge_base(repo_path, "HEAD", &upstream)?;
+            let head = GitOps::resolve_ref(repo_path, &GitRef::Head)?;
+            // a branch level with its upstream has nothing to compare
+            if base != head {
+                return Ok(Some(BaseRef {
+                    source: BaseRefSource::UpstreamMergeBase,
+                    name: upstream,
+                    git_ref: base,
+                }));
+            }
+        }
+
+        let release = GitOps::tags_merged_into_head(repo_path)?
+            .into_iter()
+            .find(|tag| {
+                crate_names.iter().any(|name| {
+                    patterns
+                        .iter()
+                        .any(|pattern| pattern.parse(tag, name).is_some())
+                })
+            });
+        Ok(release.map(|tag| BaseRef {
+            source: BaseRefSource::ReleaseTag,
+            git_ref: format!("refs/tags/{}", tag),
+            name: tag,
+        }))
+    }
+}
+
+impl fmt::Display for BaseRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            BaseRefSource::UpstreamMergeBase => {
+                let short = self.git_ref.get(..12).unwrap_or(&self.git_ref);
+                write!(f, "merge-base with {} ({})", self.name, short)
+            }
+            BaseRefSource::ReleaseTag => write!(f, "last release tag {}", self.name),
+        }
+    }
+}
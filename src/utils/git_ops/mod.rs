@@ -1,7 +1,11 @@
+pub mod base_ref;
 pub mod repository;
+pub mod tag_pattern;
 pub mod types;
 
+pub use base_ref::{BaseRef, BaseRefSource};
 pub use repository::GitOps;
+pub use tag_pattern::{DEFAULT_TAG_PATTERN, TagPattern, latest_release_tag, release_tag_name};
 pub use types::*;
 
 #[cfg(test)]
@@ -179,6 +183,13 @@ mod tests {
         // should detect the untracked test.txt file created by create_git_repo
         assert_eq!(changes.changes.len(), 1);
         assert_eq!(changes.changes[0].path, PathBuf::from("test.txt"));
+
+        // what deptrack writes itself leaves the tree clean
+        std::fs::create_dir_all(temp_dir.path().join(".deptrack/fixes")).unwrap();
+        std::fs::write(temp_dir.path().join(".deptrack/history.json"), "{}").unwrap();
+        std::fs::write(temp_dir.path().join(".deptrack/fixes/core.patch"), "").unwrap();
+        let changes = GitOps::list_working_directory_changes(temp_dir.path()).unwrap();
+        assert_eq!(changes.changes.len(), 1);
     }
 
     #[test]
@@ -253,4 +264,21 @@ mod tests {
         assert_eq!(changes.get_modified_files().len(), 1);
         assert_eq!(changes.get_deleted_files().len(), 1);
     }
+
+    #[test]
+    fn test_author_summary() {
+        use super::types::*;
+
+        let commit = |author: &str| CommitSummary {
+            hash: "abc123".to_string(),
+            author: author.to_string(),
+            date: "2024-01-01".to_string(),
+            subject: "change".to_string(),
+        };
+        let authors = AuthorSummary::of(&[commit("bob"), commit("alice"), commit("bob")]);
+
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors[0].to_string(), "bob (2)");
+        assert_eq!(authors[1].to_string(), "alice (1)");
+    }
 }
@@ -1,9 +1,17 @@
-use super::types::{ChangeType, ChangedFiles, FileChange, GitRef, GitRepository};
+use super::types::{
+    ChangeType, ChangedFiles, CommitSummary, CurrentRef, DiffMode, FileChange, GitRef,
+    GitRepository, Pathspec, TagInfo,
+};
 use crate::error::{Error, Result};
+use crate::utils::cancel::CancelToken;
 use gix;
 use gix::bstr::ByteSlice;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 
+// directory deptrack keeps its own state in, see CACHE_DIR and HISTORY_FILE
+const DEPTRACK_DIR: &str = ".deptrack";
+
 pub struct GitOps;
 
 impl GitOps {
@@ -177,11 +185,145 @@ impl GitOps {
         Ok(commit_id)
     }
 
+    /// read a file as it exists at a git reference
+    ///
+    /// `file_path` may be absolute (inside `repo_path`) or relative to the repository root;
+    /// returns None when the file does not exist at that reference
+    pub fn read_file_at_ref<P: AsRef<Path>>(
+        repo_path: P,
+        git_ref: &GitRef,
+        file_path: &Path,
+    ) -> Result<Option<String>> {
+        Self::read_file_at_ref_with_cancel(repo_path, git_ref, file_path, &CancelToken::new())
+    }
+
+    /// like read_file_at_ref, killing git once `cancel` is cancelled
+    pub fn read_file_at_ref_with_cancel<P: AsRef<Path>>(
+        repo_path: P,
+        git_ref: &GitRef,
+        file_path: &Path,
+        cancel: &CancelToken,
+    ) -> Result<Option<String>> {
+        let repo_path = repo_path.as_ref();
+        let relative_path = file_path.strip_prefix(repo_path).unwrap_or(file_path);
+
+        let ref_str = match git_ref {
+            GitRef::Head => "HEAD".to_string(),
+            GitRef::Branch(name) => name.clone(),
+            GitRef::Tag(name) => format!("refs/tags/{}", name),
+            GitRef::Hash(hash) => hash.clone(),
+        };
+
+        let output = cancel.output(
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo_path)
+                .arg("show")
+                .arg(format!("{}:{}", ref_str, relative_path.display())),
+        )?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
     /// list changed files between two git references
     pub fn list_changed_files<P: AsRef<Path>>(
         repo_path: P,
         from_ref: &GitRef,
         to_ref: &GitRef,
+    ) -> Result<ChangedFiles> {
+        Self::list_changed_files_with_cancel(repo_path, from_ref, to_ref, &CancelToken::new())
+    }
+
+    /// like list_changed_files, killing git once `cancel` is cancelled
+    pub fn list_changed_files_with_cancel<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        cancel: &CancelToken,
+    ) -> Result<ChangedFiles> {
+        Self::list_changed_files_matching(repo_path, from_ref, to_ref, &Pathspec::default(), cancel)
+    }
+
+    /// like list_changed_files_with_cancel, leaving git to diff only the
+    /// paths `pathspec` matches
+    pub fn list_changed_files_matching<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        pathspec: &Pathspec,
+        cancel: &CancelToken,
+    ) -> Result<ChangedFiles> {
+        Self::list_changes(
+            repo_path,
+            from_ref,
+            to_ref,
+            DiffMode::TwoDot,
+            pathspec,
+            cancel,
+        )
+    }
+
+    /// list files changed uniquely in the 'to' ref compared to 'from' ref
+    /// uses three-dot syntax (from...to) to find changes since the merge base
+    /// this excludes changes from 'from' that were merged into 'to'
+    pub fn list_unique_changes<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+    ) -> Result<ChangedFiles> {
+        Self::list_unique_changes_with_cancel(repo_path, from_ref, to_ref, &CancelToken::new())
+    }
+
+    /// like list_unique_changes, killing git once `cancel` is cancelled
+    pub fn list_unique_changes_with_cancel<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        cancel: &CancelToken,
+    ) -> Result<ChangedFiles> {
+        Self::list_unique_changes_matching(
+            repo_path,
+            from_ref,
+            to_ref,
+            &Pathspec::default(),
+            cancel,
+        )
+    }
+
+    /// like list_unique_changes_with_cancel, leaving git to diff only the
+    /// paths `pathspec` matches
+    pub fn list_unique_changes_matching<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        pathspec: &Pathspec,
+        cancel: &CancelToken,
+    ) -> Result<ChangedFiles> {
+        Self::list_changes(
+            repo_path,
+            from_ref,
+            to_ref,
+            DiffMode::ThreeDot,
+            pathspec,
+            cancel,
+        )
+    }
+
+    /// files changed between two git references, the whole difference of
+    /// their trees or only the changes of `to_ref` since its merge base with
+    /// `from_ref` depending on `mode`; git diffs only the paths `pathspec`
+    /// matches and is killed once `cancel` is cancelled
+    pub fn list_changes<P: AsRef<Path>>(
+        repo_path: P,
+        from_ref: &GitRef,
+        to_ref: &GitRef,
+        mode: DiffMode,
+        pathspec: &Pathspec,
+        cancel: &CancelToken,
     ) -> Result<ChangedFiles> {
         let repo_path = repo_path.as_ref();
         let repo = gix::discover(repo_path)?;
@@ -189,68 +331,49 @@ impl GitOps {
         let from_hash = Self::resolve_ref(repo_path, from_ref)?;
         let to_hash = Self::resolve_ref(repo_path, to_ref)?;
 
-        let mut changed_files = ChangedFiles::new(from_hash.clone(), to_hash.clone());
+        let mut changed_files = match mode {
+            DiffMode::TwoDot => ChangedFiles::new(from_hash.clone(), to_hash.clone()),
+            DiffMode::ThreeDot => {
+                ChangedFiles::new(format!("{}...{}", from_hash, to_hash), to_hash.clone())
+            }
+        };
 
-        // for now, implement a simple version that compares commit hashes
-        // if they're the same, no changes
+        // if refs are the same, no changes
         if from_hash == to_hash {
             return Ok(changed_files);
         }
 
-        // get commits
-        let from_commit_id =
-            gix::ObjectId::from_hex(from_hash.as_bytes()).map_err(|_| Error::InvalidRef {
-                ref_name: from_hash.clone(),
-            })?;
-        let to_commit_id =
-            gix::ObjectId::from_hex(to_hash.as_bytes()).map_err(|_| Error::InvalidRef {
-                ref_name: to_hash.clone(),
-            })?;
-
-        let from_commit = repo
-            .find_object(from_commit_id)
-            .map_err(|e| Error::GitError(Box::new(e)))?
-            .try_into_commit()
-            .map_err(|_| {
-                Error::GitError(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Not a commit",
-                )))
-            })?;
-        let to_commit = repo
-            .find_object(to_commit_id)
-            .map_err(|e| Error::GitError(Box::new(e)))?
-            .try_into_commit()
-            .map_err(|_| {
-                Error::GitError(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Not a commit",
-                )))
-            })?;
-
-        // get tree ids
-        let from_tree_id = from_commit
-            .tree_id()
-            .map_err(|e| Error::GitError(Box::new(e)))?;
-        let to_tree_id = to_commit
-            .tree_id()
-            .map_err(|e| Error::GitError(Box::new(e)))?;
+        let from_commit_id = Self::object_id(&from_hash)?;
+        let to_commit_id = Self::object_id(&to_hash)?;
+        let base_id = match mode {
+            DiffMode::TwoDot => from_commit_id,
+            DiffMode::ThreeDot => Self::merge_base_id(&repo, from_commit_id, to_commit_id)?,
+        };
 
         // if tree ids are the same, no changes
-        if from_tree_id == to_tree_id {
+        let tree_id = |id: gix::ObjectId| -> Result<gix::ObjectId> {
+            let commit = repo
+                .find_commit(id)
+                .map_err(|e| Error::GitError(Box::new(e)))?;
+            let tree_id = commit.tree_id().map_err(|e| Error::GitError(Box::new(e)))?;
+            Ok(tree_id.detach())
+        };
+        if tree_id(base_id)? == tree_id(to_commit_id)? {
             return Ok(changed_files);
         }
 
         // use git command to get file changes (simpler than gix diff API for now)
-        let output = std::process::Command::new("git")
-            .arg("-C")
-            .arg(repo_path)
-            .arg("diff")
-            .arg("--name-status")
-            .arg(&from_hash)
-            .arg(&to_hash)
-            .output()
-            .map_err(Error::IoError)?;
+        let output = cancel.output(
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo_path)
+                .arg("diff")
+                .arg("--name-status")
+                .arg(base_id.to_string())
+                .arg(&to_hash)
+                .arg("--")
+                .args(pathspec.to_args()),
+        )?;
 
         if !output.status.success() {
             return Err(Error::GitError(Box::new(std::io::Error::other(
@@ -285,73 +408,124 @@ impl GitOps {
         Ok(changed_files)
     }
 
-    /// list files changed uniquely in the 'to' ref compared to 'from' ref
-    /// uses three-dot syntax (from...to) to find changes since the merge base
-    /// this excludes changes from 'from' that were merged into 'to'
-    pub fn list_unique_changes<P: AsRef<Path>>(
-        repo_path: P,
-        from_ref: &GitRef,
-        to_ref: &GitRef,
-    ) -> Result<ChangedFiles> {
-        let repo_path = repo_path.as_ref();
-        let _repo = gix::discover(repo_path)?;
-
-        let from_hash = Self::resolve_ref(repo_path, from_ref)?;
-        let to_hash = Self::resolve_ref(repo_path, to_ref)?;
-
-        let mut changed_files =
-            ChangedFiles::new(format!("{}...{}", from_hash, to_hash), to_hash.clone());
+    fn object_id(hash: &str) -> Result<gix::ObjectId> {
+        gix::ObjectId::from_hex(hash.as_bytes()).map_err(|_| Error::InvalidRef {
+            ref_name: hash.to_string(),
+        })
+    }
 
-        // if refs are the same, no changes
-        if from_hash == to_hash {
-            return Ok(changed_files);
+    // best common ancestor of two commits, found as git merge-base does:
+    // both histories are walked newest commit first, marking which side
+    // reaches each commit, and the walk stops once every commit left is
+    // below a common one, so only the history since the fork is read
+    fn merge_base_id(
+        repo: &gix::Repository,
+        a: gix::ObjectId,
+        b: gix::ObjectId,
+    ) -> Result<gix::ObjectId> {
+        const FROM_A: u8 = 1;
+        const FROM_B: u8 = 2;
+        const STALE: u8 = 4;
+
+        if a == b {
+            return Ok(a);
         }
+        let commit_time = |id: gix::ObjectId| -> Result<i64> {
+            let commit = repo
+                .find_commit(id)
+                .map_err(|e| Error::GitError(Box::new(e)))?;
+            let time = commit.time().map_err(|e| Error::GitError(Box::new(e)))?;
+            Ok(time.seconds)
+        };
 
-        // use git diff with three-dot syntax to compare from merge-base
-        let output = std::process::Command::new("git")
-            .arg("-C")
-            .arg(repo_path)
-            .arg("diff")
-            .arg("--name-status")
-            .arg(format!("{}...{}", from_hash, to_hash))
-            .output()
-            .map_err(Error::IoError)?;
+        let mut flags = HashMap::from([(a, FROM_A), (b, FROM_B)]);
+        let mut queue = BinaryHeap::from([(commit_time(a)?, a), (commit_time(b)?, b)]);
+        let mut candidates = Vec::new();
+        while queue.iter().any(|(_, id)| flags[id] & STALE == 0) {
+            let Some((_, id)) = queue.pop() else {
+                break;
+            };
+            let mut flag = flags[&id];
+            if flag & (FROM_A | FROM_B) == FROM_A | FROM_B && flag & STALE == 0 {
+                // everything below a common commit is a worse candidate
+                candidates.push(id);
+                flag |= STALE;
+                flags.insert(id, flag);
+            }
+            let commit = repo
+                .find_commit(id)
+                .map_err(|e| Error::GitError(Box::new(e)))?;
+            for parent in commit.parent_ids() {
+                let parent = parent.detach();
+                let parent_flag = flags.entry(parent).or_default();
+                if *parent_flag & flag == flag {
+                    continue;
+                }
+                *parent_flag |= flag;
+                queue.push((commit_time(parent)?, parent));
+            }
+        }
 
-        if !output.status.success() {
-            return Err(Error::GitError(Box::new(std::io::Error::other(
-                "git diff failed",
-            ))));
+        // with criss-cross merges a candidate may still be below another, or
+        // several may be equally good; the newest one not below another wins
+        for (i, candidate) in candidates.iter().enumerate() {
+            let others: Vec<gix::ObjectId> = candidates
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, id)| *id)
+                .collect();
+            if !Self::reaches(repo, &others, *candidate, commit_time(*candidate)?)? {
+                return Ok(*candidate);
+            }
         }
+        Err(Error::from_git_error(std::io::Error::other(format!(
+            "{} and {} have no common ancestor",
+            a, b
+        ))))
+    }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        for line in output_str.lines() {
-            if line.trim().is_empty() {
+    // check if `target` is an ancestor of one of `tips`, not looking past
+    // commits older than `target_time`
+    fn reaches(
+        repo: &gix::Repository,
+        tips: &[gix::ObjectId],
+        target: gix::ObjectId,
+        target_time: i64,
+    ) -> Result<bool> {
+        let mut seen: HashSet<gix::ObjectId> = tips.iter().copied().collect();
+        let mut queue: VecDeque<gix::ObjectId> = tips.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            if id == target {
+                return Ok(true);
+            }
+            let commit = repo
+                .find_commit(id)
+                .map_err(|e| Error::GitError(Box::new(e)))?;
+            let time = commit.time().map_err(|e| Error::GitError(Box::new(e)))?;
+            if time.seconds < target_time {
                 continue;
             }
-
-            // parse git diff output format: "M\tpath/to/file"
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 2 {
-                let status = parts[0];
-                let path = PathBuf::from(parts[1]);
-
-                let change_type = match status.chars().next() {
-                    Some('A') => ChangeType::Added,
-                    Some('D') => ChangeType::Deleted,
-                    Some('M') => ChangeType::Modified,
-                    _ => ChangeType::Modified, // default to modified for unknown types
-                };
-
-                let is_binary = Self::_is_binary_file_path(&path).unwrap_or(false);
-                changed_files.add_change(FileChange::new(path, change_type, is_binary));
+            for parent in commit.parent_ids() {
+                if seen.insert(parent.detach()) {
+                    queue.push_back(parent.detach());
+                }
             }
         }
-
-        Ok(changed_files)
+        Ok(false)
     }
 
     /// list files changed in working directory (staged and unstaged)
     pub fn list_working_directory_changes<P: AsRef<Path>>(repo_path: P) -> Result<ChangedFiles> {
+        Self::list_working_directory_changes_with_cancel(repo_path, &CancelToken::new())
+    }
+
+    /// like list_working_directory_changes, killing git once `cancel` is
+    /// cancelled
+    pub fn list_working_directory_changes_with_cancel<P: AsRef<Path>>(
+        repo_path: P,
+        cancel: &CancelToken,
+    ) -> Result<ChangedFiles> {
         let repo_path = repo_path.as_ref();
         let repo = gix::discover(repo_path)?;
 
@@ -364,13 +538,15 @@ impl GitOps {
         let mut changed_files = ChangedFiles::new(head_commit, "WORKING_DIR".to_string());
 
         // use git status --porcelain to get working directory changes
-        let output = std::process::Command::new("git")
-            .arg("-C")
-            .arg(repo_path)
-            .arg("status")
-            .arg("--porcelain")
-            .output()
-            .map_err(Error::IoError)?;
+        let output = cancel.output(
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo_path)
+                .arg("status")
+                .arg("--porcelain")
+                // list the files in new directories, not just the directories
+                .arg("--untracked-files=all"),
+        )?;
 
         if !output.status.success() {
             return Err(Error::GitError(Box::new(std::io::Error::other(
@@ -388,6 +564,11 @@ impl GitOps {
             // X = staged status, Y = unstaged status
             let status_chars = &line[0..2];
             let path = PathBuf::from(line[3..].trim());
+            // the cache, issue history and patches deptrack writes itself
+            // are not changes to the tree
+            if path.components().any(|c| c.as_os_str() == DEPTRACK_DIR) {
+                continue;
+            }
 
             // determine change type based on status
             // we look at both staged (X) and unstaged (Y) status
@@ -490,27 +671,263 @@ impl GitOps {
         Ok(branches)
     }
 
-    /// get the current branch name
-    pub fn get_current_branch<P: AsRef<Path>>(repo_path: P) -> Result<String> {
+    /// remote-tracking branches as `<remote>/<branch>`, without the
+    /// `<remote>/HEAD` symbolic refs
+    pub fn list_remote_branches<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>> {
+        let repo = gix::discover(repo_path.as_ref())?;
+        let references = repo.references().map_err(Error::from_git_error)?;
+
+        let mut branches = Vec::new();
+        for reference in references
+            .prefixed("refs/remotes/")
+            .map_err(Error::from_git_error)?
+            .flatten()
+        {
+            if let Ok(name) = reference.name().as_bstr().to_str()
+                && let Some(branch) = name.strip_prefix("refs/remotes/")
+                && !branch.ends_with("/HEAD")
+            {
+                branches.push(branch.to_string());
+            }
+        }
+        Ok(branches)
+    }
+
+    /// stage `paths` and commit only those, leaving anything else in the index alone
+    pub fn commit_paths<P: AsRef<Path>>(
+        repo_path: P,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<String> {
+        let repo_path = repo_path.as_ref();
+        Self::run_git(repo_path, |cmd| {
+            cmd.arg("add").arg("--").args(paths);
+        })?;
+        Self::run_git(repo_path, |cmd| {
+            cmd.args(["commit", "-m", message, "--"]).args(paths);
+        })?;
+        Self::run_git(repo_path, |cmd| {
+            cmd.args(["rev-parse", "HEAD"]);
+        })
+    }
+
+    /// create an annotated tag on HEAD
+    pub fn create_tag<P: AsRef<Path>>(repo_path: P, name: &str, message: &str) -> Result<()> {
+        Self::run_git(repo_path.as_ref(), |cmd| {
+            cmd.args(["tag", "-a", name, "-m", message]);
+        })?;
+        Ok(())
+    }
+
+    /// check if a tag exists
+    pub fn tag_exists<P: AsRef<Path>>(repo_path: P, name: &str) -> Result<bool> {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo_path.as_ref())
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/tags/{}", name))
+            .output()
+            .map_err(Error::IoError)?;
+        Ok(output.status.success())
+    }
+
+    /// every tag in the repository, sorted by name
+    pub fn list_tags<P: AsRef<Path>>(repo_path: P) -> Result<Vec<TagInfo>> {
+        // fields split by the ascii unit separator and tags by the record
+        // separator, so multi-line messages survive
+        let output = Self::run_git(repo_path.as_ref(), |cmd| {
+            cmd.args([
+                "for-each-ref",
+                "refs/tags",
+                "--format=%(refname:short)%1f%(objecttype)%1f%(objectname)%1f%(*objectname)\
+                 %1f%(creatordate:short)%1f%(contents)%1e",
+            ]);
+        })?;
+
+        Ok(output
+            .split('\x1e')
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').splitn(6, '\x1f');
+                let name = fields.next().filter(|name| !name.is_empty())?;
+                let annotated = fields.next()? == "tag";
+                let object = fields.next()?;
+                let peeled = fields.next()?;
+                let date = fields.next()?;
+                let contents = fields.next().unwrap_or_default().trim();
+                Some(TagInfo {
+                    name: name.to_string(),
+                    target: if peeled.is_empty() { object } else { peeled }.to_string(),
+                    message: annotated.then(|| contents.to_string()),
+                    date: date.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// names of every tag in the repository
+    pub fn list_tag_names<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>> {
+        Ok(Self::list_tags(repo_path)?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect())
+    }
+
+    /// remote branch HEAD's branch tracks, e.g. `origin/main`, None without one
+    pub fn upstream_branch<P: AsRef<Path>>(repo_path: P) -> Result<Option<String>> {
+        // fails on a detached HEAD or a branch without upstream alike
+        Ok(Self::run_git(repo_path.as_ref(), |cmd| {
+            cmd.args([
+                "rev-parse",
+                "--abbrev-ref",
+                "--symbolic-full-name",
+                "@{upstream}",
+            ]);
+        })
+        .ok())
+    }
+
+    /// best common ancestor of two revisions
+    pub fn merge_base<P: AsRef<Path>>(repo_path: P, a: &str, b: &str) -> Result<String> {
+        let repo = gix::discover(repo_path.as_ref())?;
+        let resolve = |rev: &str| -> Result<gix::ObjectId> {
+            let id = repo.rev_parse_single(rev).map_err(|_| Error::RefNotFound {
+                ref_name: rev.to_string(),
+            })?;
+            Ok(id.detach())
+        };
+        let base = Self::merge_base_id(&repo, resolve(a)?, resolve(b)?)?;
+        Ok(base.to_string())
+    }
+
+    /// tags reachable from HEAD, the most recently created first
+    pub fn tags_merged_into_head<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>> {
+        let output = Self::run_git(repo_path.as_ref(), |cmd| {
+            cmd.args(["tag", "--merged", "HEAD", "--sort=-creatordate"]);
+        })?;
+        Ok(output.lines().map(str::to_string).collect())
+    }
+
+    /// the last `limit` commits touching `path`, newest first
+    pub fn recent_commits<P: AsRef<Path>>(
+        repo_path: P,
+        path: &Path,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        Self::log(repo_path.as_ref(), &[format!("-n{}", limit)], path, None)
+    }
+
+    /// commits reachable from `to` but not from `from` touching `path`,
+    /// newest first
+    pub fn commits_in_range<P: AsRef<Path>>(
+        repo_path: P,
+        from: &str,
+        to: &str,
+        path: &Path,
+    ) -> Result<Vec<CommitSummary>> {
+        Self::log(
+            repo_path.as_ref(),
+            &[format!("{}..{}", from, to)],
+            path,
+            None,
+        )
+    }
+
+    /// commits reachable from `to` but not from `from` touching `path`,
+    /// leaving out those that only touch `excluded` (a file below `path`),
+    /// newest first
+    pub fn commits_in_range_excluding<P: AsRef<Path>>(
+        repo_path: P,
+        from: &str,
+        to: &str,
+        path: &Path,
+        excluded: &Path,
+    ) -> Result<Vec<CommitSummary>> {
+        let range = [format!("{}..{}", from, to)];
+        Self::log(repo_path.as_ref(), &range, path, Some(excluded))
+    }
+
+    // `git log <args> -- <path> [:(exclude)<excluded>]`, with the paths made
+    // relative to the repository
+    fn log(
+        repo_path: &Path,
+        args: &[String],
+        path: &Path,
+        excluded: Option<&Path>,
+    ) -> Result<Vec<CommitSummary>> {
+        let relative = |path: &Path| -> PathBuf {
+            match path.strip_prefix(repo_path) {
+                Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => path.to_path_buf(),
+            }
+        };
+        let pathspec = relative(path);
+        let exclude =
+            excluded.map(|excluded| format!(":(exclude){}", relative(excluded).display()));
+        // fields split by the ascii unit separator, which subjects never contain
+        let output = Self::run_git(repo_path, |cmd| {
+            cmd.arg("log")
+                .args(args)
+                .args(["--date=short", "--format=%H%x1f%an%x1f%ad%x1f%s", "--"])
+                .arg(pathspec)
+                .args(exclude);
+        })?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\x1f');
+                Some(CommitSummary {
+                    hash: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                    subject: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    // run git in `repo_path`, failing with its stderr when it exits non-zero
+    fn run_git(
+        repo_path: &Path,
+        configure: impl FnOnce(&mut std::process::Command),
+    ) -> Result<String> {
+        let mut command = std::process::Command::new("git");
+        command.arg("-C").arg(repo_path);
+        configure(&mut command);
+
+        let output = command.output().map_err(Error::IoError)?;
+        if !output.status.success() {
+            return Err(Error::from_git_error(std::io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// what HEAD points at: a branch, also one without commits yet, or a
+    /// commit when detached, as in most CI checkouts
+    pub fn get_current_branch<P: AsRef<Path>>(repo_path: P) -> Result<CurrentRef> {
         let repo_path = repo_path.as_ref();
         let repo = gix::discover(repo_path)?;
 
         let head = repo.head().map_err(Error::from_git_error)?;
+        let name = match head.kind {
+            gix::head::Kind::Detached { target, peeled } => {
+                return Ok(CurrentRef::Detached(peeled.unwrap_or(target).to_string()));
+            }
+            gix::head::Kind::Symbolic(reference) => reference.name,
+            gix::head::Kind::Unborn(name) => name,
+        };
 
-        let head_ref = head
-            .try_into_referent()
-            .ok_or_else(|| Error::from_git_error(std::io::Error::other("HEAD is detached")))?;
-
-        let name = head_ref.name().as_bstr().to_str().map_err(|_| {
+        let name = name.as_bstr().to_str().map_err(|_| {
             Error::from_git_error(std::io::Error::other("invalid branch name encoding"))
         })?;
 
         // strip refs/heads/ prefix if present
-        if let Some(branch_name) = name.strip_prefix("refs/heads/") {
-            Ok(branch_name.to_string())
-        } else {
-            Ok(name.to_string())
-        }
+        Ok(CurrentRef::Branch(
+            name.strip_prefix("refs/heads/").unwrap_or(name).to_string(),
+        ))
     }
 }
 
@@ -0,0 +1,220 @@
+// release tag naming, e.g. "{crate}-v{version}" -> "serde-v1.0.0"
+
+use regex::Regex;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// pattern used when none is configured
+pub const DEFAULT_TAG_PATTERN: &str = "{crate}-v{version}";
+
+// semver as it appears in a tag: core version plus optional pre-release and build
+const VERSION_REGEX: &str = r"(\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)";
+
+/// release tag naming pattern with `{crate}` and `{version}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TagPattern(String);
+
+impl TagPattern {
+    /// pattern from text, which has to contain `{version}`
+    pub fn new(pattern: impl Into<String>) -> Result<Self, String> {
+        let pattern = pattern.into();
+        if !pattern.contains("{version}") {
+            return Err(format!(
+                "invalid tag pattern: {}, it has to contain {{version}}",
+                pattern
+            ));
+        }
+        Ok(Self(pattern))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// tag of `version` of `crate_name`
+    pub fn format(&self, crate_name: &str, version: &Version) -> String {
+        self.0
+            .replace("{crate}", crate_name)
+            .replace("{version}", &version.to_string())
+    }
+
+    /// version of `crate_name` that `tag` names, None when it does not follow the pattern
+    pub fn parse(&self, tag: &str, crate_name: &str) -> Option<Version> {
+        let mut regex = String::from("^");
+        for (i, part) in self.0.split("{version}").enumerate() {
+            if i > 0 {
+                regex.push_str(VERSION_REGEX);
+            }
+            regex.push_str(&regex::escape(part).replace(r"\{crate\}", &regex::escape(crate_name)));
+        }
+        regex.push('$');
+
+        let captures = Regex::new(&regex).ok()?.captures(tag)?;
+        let versions: Vec<Version> = captures
+            .iter()
+            .skip(1)
+            .flatten()
+            .filter_map(|m| Version::parse(m.as_str()).ok())
+            .collect();
+        // a pattern repeating {version} must repeat the same one
+        match versions.split_first() {
+            Some((first, rest)) if rest.iter().all(|v| v == first) => Some(first.clone()),
+            _ => None,
+        }
+    }
+
+    /// whether `url` refers to the tag of `version` of `crate_name`, as in
+    /// `.../releases/tag/<tag>` or `.../compare/<old>...<tag>`
+    pub fn is_referenced_by(&self, url: &str, crate_name: &str, version: &Version) -> bool {
+        let tag = self.format(crate_name, version);
+        url.match_indices(&tag).any(|(start, _)| {
+            let before = url[..start].chars().next_back();
+            let after = url[start + tag.len()..].chars().next();
+            matches!(before, Some('/' | '.' | '='))
+                && matches!(after, None | Some('/' | '.' | '#' | '?' | '&'))
+        })
+    }
+}
+
+impl Default for TagPattern {
+    fn default() -> Self {
+        Self(DEFAULT_TAG_PATTERN.to_string())
+    }
+}
+
+impl fmt::Display for TagPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for TagPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for TagPattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(pattern)
+    }
+}
+
+impl From<TagPattern> for String {
+    fn from(pattern: TagPattern) -> Self {
+        pattern.0
+    }
+}
+
+/// tag of `version` of `crate_name` under the first pattern, the one new tags use
+pub fn release_tag_name(patterns: &[TagPattern], crate_name: &str, version: &Version) -> String {
+    patterns
+        .first()
+        .cloned()
+        .unwrap_or_default()
+        .format(crate_name, version)
+}
+
+/// newest release of `crate_name` among `tags`, under any of the patterns
+pub fn latest_release_tag(
+    patterns: &[TagPattern],
+    tags: &[String],
+    crate_name: &str,
+) -> Option<(String, Version)> {
+    tags.iter()
+        .filter_map(|tag| {
+            patterns
+                .iter()
+                .find_map(|pattern| pattern.parse(tag, crate_name))
+                .map(|version| (tag.clone(), version))
+        })
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(v: &str) -> Version {
+        Version::parse(v).unwrap()
+    }
+
+    #[test]
+    fn test_format_and_parse() {
+        let pattern = TagPattern::default();
+        assert_eq!(pattern.format("core", &version("1.2.3")), "core-v1.2.3");
+        assert_eq!(pattern.parse("core-v1.2.3", "core"), Some(version("1.2.3")));
+        assert_eq!(
+            pattern.parse("core-v2.0.0-rc.1", "core"),
+            Some(version("2.0.0-rc.1"))
+        );
+        assert_eq!(pattern.parse("core-extra-v1.2.3", "core"), None);
+        assert_eq!(pattern.parse("v1.2.3", "core"), None);
+
+        let nested = TagPattern::new("{crate}/v{version}").unwrap();
+        assert_eq!(
+            nested.parse("my.crate/v0.1.0", "my.crate"),
+            Some(version("0.1.0"))
+        );
+        assert_eq!(nested.parse("myxcrate/v0.1.0", "my.crate"), None);
+
+        assert!(TagPattern::new("{crate}-latest").is_err());
+        assert!(
+            toml::from_str::<std::collections::HashMap<String, TagPattern>>("p = \"v\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_latest_release_tag() {
+        let patterns = vec![
+            TagPattern::default(),
+            TagPattern::new("{crate}_{version}").unwrap(),
+        ];
+        let tags: Vec<String> = ["core_0.9.0", "core-v0.10.0", "core-v0.2.0", "app-v3.0.0"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        assert_eq!(
+            latest_release_tag(&patterns, &tags, "core"),
+            Some(("core-v0.10.0".to_string(), version("0.10.0")))
+        );
+        assert_eq!(latest_release_tag(&patterns, &tags, "cli"), None);
+        assert_eq!(
+            release_tag_name(&patterns[1..], "core", &version("1.0.0")),
+            "core_1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_is_referenced_by() {
+        let pattern = TagPattern::default();
+        let v = version("1.0.0");
+        assert!(pattern.is_referenced_by(
+            "https://github.com/o/r/compare/core-v0.9.0...core-v1.0.0",
+            "core",
+            &v
+        ));
+        assert!(pattern.is_referenced_by(
+            "https://github.com/o/r/releases/tag/core-v1.0.0",
+            "core",
+            &v
+        ));
+        assert!(!pattern.is_referenced_by(
+            "https://github.com/o/r/releases/tag/core-v1.0.01",
+            "core",
+            &v
+        ));
+        assert!(!pattern.is_referenced_by(
+            "https://github.com/o/r/releases/tag/mycore-v1.0.0",
+            "core",
+            &v
+        ));
+    }
+}
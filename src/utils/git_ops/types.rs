@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +19,112 @@ impl GitRepository {
     }
 }
 
+/// one commit of `git log`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub author: String,
+    /// author date as YYYY-MM-DD
+    pub date: String,
+    pub subject: String,
+}
+
+/// an author and how many commits of a range they wrote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorSummary {
+    pub author: String,
+    pub commits: usize,
+}
+
+impl AuthorSummary {
+    /// authors of `commits`, most commits first
+    pub fn of(commits: &[CommitSummary]) -> Vec<Self> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for commit in commits {
+            *counts.entry(&commit.author).or_default() += 1;
+        }
+        let mut authors: Vec<_> = counts
+            .into_iter()
+            .map(|(author, commits)| AuthorSummary {
+                author: author.to_string(),
+                commits,
+            })
+            .collect();
+        authors.sort_by(|a, b| b.commits.cmp(&a.commits));
+        authors
+    }
+}
+
+impl std::fmt::Display for AuthorSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.author, self.commits)
+    }
+}
+
+/// paths a diff is limited to, as globs relative to the repository root
+/// (`crates/foo`, `**/*.md`); a directory matches everything below it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pathspec {
+    /// only paths matching one of these, every path when empty
+    pub include: Vec<String>,
+    /// none of the paths matching one of these
+    pub exclude: Vec<String>,
+}
+
+impl Pathspec {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// the pathspec arguments following `--` on a git command line
+    pub fn to_args(&self) -> Vec<String> {
+        let include = self
+            .include
+            .iter()
+            .map(|pattern| format!(":(top,glob){}", pattern));
+        let exclude = self
+            .exclude
+            .iter()
+            .map(|pattern| format!(":(top,glob,exclude){}", pattern));
+        include.chain(exclude).collect()
+    }
+}
+
+/// which changes a diff between two refs reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffMode {
+    /// `from..to`: every difference between the two trees, including
+    /// changes made on `from` that `to` does not have
+    #[default]
+    TwoDot,
+    /// `from...to`: only what `to` changed since it forked off `from`,
+    /// i.e. the diff from their merge base
+    ThreeDot,
+}
+
+/// one tag of the repository
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagInfo {
+    pub name: String,
+    /// hash of the commit the tag points at, annotated tags peeled
+    pub target: String,
+    /// message of an annotated tag, None for a lightweight one
+    pub message: Option<String>,
+    /// tagger date of an annotated tag, commit date of a lightweight one,
+    /// as YYYY-MM-DD
+    pub date: String,
+}
+
+impl TagInfo {
+    pub fn is_annotated(&self) -> bool {
+        self.message.is_some()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ChangeType {
     Added,
@@ -66,6 +173,49 @@ impl GitRef {
     }
 }
 
+impl std::fmt::Display for GitRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitRef::Hash(name) | GitRef::Branch(name) | GitRef::Tag(name) => write!(f, "{}", name),
+            GitRef::Head => write!(f, "HEAD"),
+        }
+    }
+}
+
+/// what HEAD points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurrentRef {
+    /// short name of the checked out branch
+    Branch(String),
+    /// hash of the commit a detached HEAD points at
+    Detached(String),
+}
+
+impl CurrentRef {
+    /// the branch name, None when detached
+    pub fn branch(&self) -> Option<&str> {
+        match self {
+            CurrentRef::Branch(name) => Some(name),
+            CurrentRef::Detached(_) => None,
+        }
+    }
+
+    pub fn is_detached(&self) -> bool {
+        matches!(self, CurrentRef::Detached(_))
+    }
+}
+
+impl std::fmt::Display for CurrentRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurrentRef::Branch(name) => write!(f, "{}", name),
+            CurrentRef::Detached(hash) => {
+                write!(f, "detached at {}", hash.get(..12).unwrap_or(hash))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChangedFiles {
     pub changes: Vec<FileChange>,
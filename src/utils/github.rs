@@ -0,0 +1,356 @@
+use crate::error::{Error, Result};
+use serde_json::{Value, json};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// api of github.com, GITHUB_API_URL points elsewhere on enterprise servers
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// hidden marker identifying the sticky comment so reruns update it
+pub const COMMENT_MARKER: &str = "<!-- deptrack-report -->";
+
+/// context of the commit status
+pub const STATUS_CONTEXT: &str = "deptrack";
+
+/// comments fetched per page while looking for the sticky comment
+const COMMENTS_PER_PAGE: usize = 100;
+
+/// sections of a check-versions json report: key, title and the crate list
+const REPORT_SECTIONS: [(&str, &str, &str); 7] = [
+    ("manifest", "manifests", "incomplete_crates"),
+    ("msrv", "rust-version", "mismatched_crates"),
+    ("features", "features", "crates_with_issues"),
+    ("lockfile", "Cargo.lock", "mismatched_crates"),
+    ("membership", "workspace membership", "crates_with_issues"),
+    ("checks", "external checks", "crates_with_issues"),
+    ("changelog", "changelogs", ""),
+];
+
+/// error and warning totals of a `check-versions --format json` report
+pub fn report_totals(report: &Value) -> (u64, u64) {
+    let count = |value: Option<&Value>| value.and_then(Value::as_u64).unwrap_or(0);
+    let mut errors = count(report.get("version_bump_errors"));
+    let mut warnings = count(report.get("version_bump_warnings"));
+    for (key, _, _) in REPORT_SECTIONS {
+        if let Some(section) = report.get(key) {
+            errors += count(section.get("total_errors"));
+            warnings += count(section.get("total_warnings"));
+        }
+    }
+    (errors, warnings)
+}
+
+/// markdown summary of a `check-versions --format json` report, starting with
+/// [`COMMENT_MARKER`]
+pub fn render_markdown(report: &Value) -> String {
+    let text = |key: &str| match report.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => "?".to_string(),
+    };
+    let (errors, warnings) = report_totals(report);
+    let status = if errors == 0 { "passed" } else { "failed" };
+
+    let mut out = format!(
+        "{}\n### deptrack check-versions: {}\n\n",
+        COMMENT_MARKER, status
+    );
+    out.push_str(&format!(
+        "`{}`..`{}`: {} changed file(s), {} directly affected crate(s), {} affected in total\n\n",
+        text("from"),
+        text("to"),
+        text("changed_files"),
+        text("directly_affected"),
+        text("total_affected")
+    ));
+
+    out.push_str("| check | errors | warnings | crates |\n|---|---:|---:|---|\n");
+    out.push_str(&format!(
+        "| version bumps | {} | {} | {} of {} bumped |\n",
+        report.get("version_bump_errors").unwrap_or(&json!(0)),
+        report.get("version_bump_warnings").unwrap_or(&json!(0)),
+        text("bumped"),
+        report.get("bumped").and_then(Value::as_u64).unwrap_or(0)
+            + report
+                .get("needing_bump")
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+    ));
+    for (key, title, list) in REPORT_SECTIONS {
+        let Some(section) = report.get(key) else {
+            continue;
+        };
+        let crates = section
+            .get(list)
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            title,
+            section.get("total_errors").unwrap_or(&json!(0)),
+            section.get("total_warnings").unwrap_or(&json!(0)),
+            crates
+        ));
+    }
+    if report.get("changelog_skipped") == Some(&json!(true)) {
+        out.push_str("\nchangelog validation was skipped\n");
+    }
+
+    out.push_str(&format!(
+        "\n**{} error(s), {} warning(s)**\n",
+        errors, warnings
+    ));
+    out
+}
+
+/// pull request number and head commit from a github actions event payload
+/// (GITHUB_EVENT_PATH), None for events that are not about a pull request
+pub fn pull_request_from_event<P: AsRef<Path>>(event_path: P) -> Option<(u64, String)> {
+    let event: Value = serde_json::from_str(&std::fs::read_to_string(event_path).ok()?).ok()?;
+    let pull_request = event.get("pull_request")?;
+    Some((
+        pull_request.get("number")?.as_u64()?,
+        pull_request.get("head")?.get("sha")?.as_str()?.to_string(),
+    ))
+}
+
+/// minimal github rest client for the pull request comment and commit status
+///
+/// requests go through curl like the registry index does; the token is handed
+/// to curl on stdin so it never shows up in the process list
+#[derive(Debug, Clone)]
+pub struct GithubClient {
+    /// api base url, without a trailing slash
+    pub api_url: String,
+    /// "owner/name"
+    pub repository: String,
+    token: String,
+    /// timeout for a single request in seconds
+    pub timeout_secs: u64,
+}
+
+impl GithubClient {
+    pub fn new(repository: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            api_url: GITHUB_API_URL.to_string(),
+            repository: repository.into(),
+            token: token.into(),
+            timeout_secs: 30,
+        }
+    }
+
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// create the sticky comment on pull request `number`, or update it when
+    /// a comment with [`COMMENT_MARKER`] exists; returns the comment's url
+    pub fn upsert_comment(&self, number: u64, body: &str) -> Result<String> {
+        let payload = json!({ "body": body });
+        let comment = match self.find_comment(number)? {
+            Some(id) => self.request(
+                "PATCH",
+                &format!("/repos/{}/issues/comments/{}", self.repository, id),
+                Some(&payload),
+            )?,
+            None => self.request(
+                "POST",
+                &format!("/repos/{}/issues/{}/comments", self.repository, number),
+                Some(&payload),
+            )?,
+        };
+        Ok(comment
+            .get("html_url")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// set the [`STATUS_CONTEXT`] commit status of `sha`
+    pub fn set_status(
+        &self,
+        sha: &str,
+        success: bool,
+        description: &str,
+        target_url: Option<&str>,
+    ) -> Result<()> {
+        let mut payload = json!({
+            "state": if success { "success" } else { "failure" },
+            "context": STATUS_CONTEXT,
+            "description": description,
+        });
+        if let Some(url) = target_url.filter(|url| !url.is_empty()) {
+            payload["target_url"] = json!(url);
+        }
+        self.request(
+            "POST",
+            &format!("/repos/{}/statuses/{}", self.repository, sha),
+            Some(&payload),
+        )?;
+        Ok(())
+    }
+
+    fn find_comment(&self, number: u64) -> Result<Option<u64>> {
+        for page in 1.. {
+            let comments = self.request(
+                "GET",
+                &format!(
+                    "/repos/{}/issues/{}/comments?per_page={}&page={}",
+                    self.repository, number, COMMENTS_PER_PAGE, page
+                ),
+                None,
+            )?;
+            let comments = comments.as_array().cloned().unwrap_or_default();
+            let sticky = comments.iter().find(|comment| {
+                comment
+                    .get("body")
+                    .and_then(Value::as_str)
+                    .is_some_and(|body| body.starts_with(COMMENT_MARKER))
+            });
+            if let Some(id) = sticky.and_then(|comment| comment.get("id")?.as_u64()) {
+                return Ok(Some(id));
+            }
+            if comments.len() < COMMENTS_PER_PAGE {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<&Value>) -> Result<Value> {
+        let url = format!("{}{}", self.api_url, path);
+        let body_file = match body {
+            Some(body) => {
+                let mut file = tempfile::NamedTempFile::new()?;
+                file.write_all(body.to_string().as_bytes())?;
+                Some(file)
+            }
+            None => None,
+        };
+
+        let mut command = Command::new("curl");
+        command
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .arg("--max-time")
+            .arg(self.timeout_secs.to_string())
+            .args(["--request", method])
+            .args(["--header", "Accept: application/vnd.github+json"])
+            .args(["--header", "X-GitHub-Api-Version: 2022-11-28"])
+            .args(["--config", "-"]);
+        if let Some(file) = &body_file {
+            command
+                .args(["--header", "Content-Type: application/json"])
+                .arg("--data-binary")
+                .arg(format!("@{}", file.path().display()));
+        }
+        let mut child = command
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::GithubError {
+                reason: format!("failed to run curl: {}", e),
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            writeln!(stdin, "header = \"Authorization: Bearer {}\"", self.token)?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::GithubError {
+                reason: format!(
+                    "{} {} failed: {}",
+                    method,
+                    path,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Value::Null);
+        }
+        serde_json::from_slice(&output.stdout).map_err(|e| Error::GithubError {
+            reason: format!("unexpected response to {} {}: {}", method, path, e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_markdown() {
+        let report = json!({
+            "from": "main",
+            "to": "HEAD",
+            "changed_files": 3,
+            "directly_affected": 1,
+            "total_affected": 2,
+            "bumped": 1,
+            "needing_bump": 1,
+            "version_bump_errors": 1,
+            "version_bump_warnings": 0,
+            "manifest": {
+                "incomplete_crates": ["ws::core"],
+                "total_errors": 1,
+                "total_warnings": 0,
+            },
+            "msrv": { "mismatched_crates": [], "total_errors": 0, "total_warnings": 2 },
+            "changelog_skipped": true,
+        });
+        assert_eq!(report_totals(&report), (2, 2));
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.starts_with(COMMENT_MARKER));
+        assert!(markdown.contains("### deptrack check-versions: failed"));
+        assert!(markdown.contains("`main`..`HEAD`: 3 changed file(s)"));
+        assert!(markdown.contains("| version bumps | 1 | 0 | 1 of 2 bumped |"));
+        assert!(markdown.contains("| manifests | 1 | 0 | `ws::core` |"));
+        assert!(markdown.contains("| rust-version | 0 | 2 |  |"));
+        assert!(!markdown.contains("| features |"));
+        assert!(markdown.contains("changelog validation was skipped"));
+        assert!(markdown.contains("**2 error(s), 2 warning(s)**"));
+    }
+
+    #[test]
+    fn test_pull_request_from_event() {
+        let temp_dir = TempDir::new().unwrap();
+        let event = temp_dir.path().join("event.json");
+        fs::write(
+            &event,
+            r#"{"action": "synchronize", "pull_request": {"number": 42, "head": {"sha": "abc123"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pull_request_from_event(&event),
+            Some((42, "abc123".to_string()))
+        );
+
+        fs::write(&event, r#"{"ref": "refs/heads/main"}"#).unwrap();
+        assert_eq!(pull_request_from_event(&event), None);
+        assert_eq!(
+            pull_request_from_event(temp_dir.path().join("missing")),
+            None
+        );
+    }
+}
@@ -0,0 +1,149 @@
+// dependency graph pictures: graphviz `dot` when it is installed, the
+// embedded layout-rs engine (svg only) when it is not
+
+use super::cargo_ops::types::CrateDependencyGraph;
+use crate::error::{Error, Result};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// image file format of a rendered graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    /// file extension, also the graphviz output type
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(format!("invalid image format: {}, use 'svg' or 'png'", s)),
+        }
+    }
+}
+
+/// render the graph as an image, with graphviz when `dot` is on PATH and
+/// with the embedded layout otherwise; png needs graphviz
+pub fn render_image(graph: &CrateDependencyGraph, format: ImageFormat) -> Result<Vec<u8>> {
+    let dot = graph.to_dot();
+    if let Some(image) = run_graphviz(&dot, format)? {
+        return Ok(image);
+    }
+    match format {
+        ImageFormat::Svg => Ok(layout_svg(&dot)?.into_bytes()),
+        ImageFormat::Png => Err(Error::RenderError {
+            reason: "png output needs graphviz `dot` on PATH, use svg instead".to_string(),
+        }),
+    }
+}
+
+/// lay out a dot document with the embedded engine and draw it as svg
+pub fn layout_svg(dot: &str) -> Result<String> {
+    let ast = layout::gv::DotParser::new(dot)
+        .process()
+        .map_err(|reason| Error::RenderError { reason })?;
+    let mut builder = layout::gv::GraphBuilder::new();
+    builder.visit_graph(&ast);
+    let mut visual = builder.get();
+    if visual.num_nodes() == 0 {
+        return Err(Error::RenderError {
+            reason: "the graph has no crates to draw".to_string(),
+        });
+    }
+
+    let mut svg = layout::backends::svg::SVGWriter::new();
+    visual.do_it(false, false, false, &mut svg);
+    Ok(svg.finalize())
+}
+
+// None when graphviz is not installed
+fn run_graphviz(dot: &str, format: ImageFormat) -> Result<Option<Vec<u8>>> {
+    let child = Command::new("dot")
+        .arg(format!("-T{}", format.extension()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(dot.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::RenderError {
+            reason: format!(
+                "dot failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(Some(output.stdout))
+}
+
+/// open `path` with the desktop's default application
+pub fn open_file(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(path).status()?;
+    if !status.success() {
+        return Err(Error::RenderError {
+            reason: format!("could not open {}", path.display()),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cargo_ops::types::{CrateId, CrateInfo, DependencyType};
+    use std::path::PathBuf;
+
+    fn graph() -> CrateDependencyGraph {
+        let mut graph = CrateDependencyGraph::new();
+        let id = |name: &str| CrateId::new("ws".to_string(), name.to_string());
+        for name in ["core", "app", "lonely"] {
+            graph.add_crate(CrateInfo::new(
+                id(name),
+                "0.1.0".to_string(),
+                PathBuf::from(name),
+            ));
+        }
+        graph.add_dependency(&id("app"), &id("core"), DependencyType::Normal);
+        graph
+    }
+
+    #[test]
+    fn test_layout_svg() {
+        let svg = layout_svg(&graph().to_dot()).unwrap();
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("lonely"));
+        assert!(layout_svg("digraph {").is_err());
+    }
+}
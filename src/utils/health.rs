@@ -0,0 +1,342 @@
+use super::cargo_ops::integration::VersionBumpAnalysis;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::changelog_checker::ChangelogChecker;
+use super::config::DeptrackConfig;
+use super::manifest_checker::ManifestChecker;
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+const MIN_CRATE_NAME_WIDTH: usize = 10;
+
+/// weight of not being part of a production dependency cycle
+pub const CYCLE_WEIGHT: f64 = 30.0;
+/// weight of a valid changelog with an entry for the current version
+pub const CHANGELOG_WEIGHT: f64 = 25.0;
+/// weight of a version bump, only counted for crates changed since a base ref
+pub const BUMP_WEIGHT: f64 = 20.0;
+/// weight of the publish-readiness fields of the manifest
+pub const MANIFEST_WEIGHT: f64 = 15.0;
+/// weight of being connected to the rest of a multi-crate workspace
+pub const ORPHAN_WEIGHT: f64 = 10.0;
+
+/// health signals and score of a single crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateHealth {
+    pub crate_id: CrateId,
+    /// weighted score between 0 and 100
+    pub score: f64,
+    /// part of a production dependency cycle
+    pub in_cycle: bool,
+    /// 1.0 for a valid changelog with a current version entry, 0.5 for one
+    /// that exists but falls short, 0.0 without one
+    pub changelog: f64,
+    /// share of the required manifest fields present, 1.0 when not publishable
+    pub manifest: f64,
+    /// no workspace crate depends on it and it depends on none, in a
+    /// workspace with other crates
+    pub orphan: bool,
+    /// None when no base ref was given or the crate was not affected
+    pub bumped: Option<bool>,
+}
+
+/// aggregated health of a workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceHealth {
+    pub workspace: String,
+    /// mean score of the workspace crates
+    pub score: f64,
+    pub crates: usize,
+    pub crates_in_cycles: usize,
+    /// percentage of crates with a valid, up to date changelog
+    pub changelog_compliance: f64,
+    /// percentage of affected crates that were bumped, None without a base ref
+    /// or when nothing was affected
+    pub bump_compliance: Option<f64>,
+    /// mean manifest completeness as a percentage
+    pub manifest_completeness: f64,
+    pub orphan_crates: usize,
+}
+
+/// health of the whole repository
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    /// mean score of every crate
+    pub score: f64,
+    /// sorted by workspace name
+    pub workspaces: Vec<WorkspaceHealth>,
+    /// sorted by crate name
+    pub crates: Vec<CrateHealth>,
+}
+
+impl CrateHealth {
+    // weighted mean of the signals that apply to this crate
+    fn compute_score(&mut self, orphan_applies: bool) {
+        let mut signals = vec![
+            (CYCLE_WEIGHT, if self.in_cycle { 0.0 } else { 1.0 }),
+            (CHANGELOG_WEIGHT, self.changelog),
+            (MANIFEST_WEIGHT, self.manifest),
+        ];
+        if orphan_applies {
+            signals.push((ORPHAN_WEIGHT, if self.orphan { 0.0 } else { 1.0 }));
+        }
+        if let Some(bumped) = self.bumped {
+            signals.push((BUMP_WEIGHT, if bumped { 1.0 } else { 0.0 }));
+        }
+
+        let total_weight: f64 = signals.iter().map(|(weight, _)| weight).sum();
+        let weighted: f64 = signals.iter().map(|(weight, value)| weight * value).sum();
+        self.score = round(100.0 * weighted / total_weight);
+    }
+}
+
+impl HealthReport {
+    /// display the workspace summary and, when verbose, every crate
+    pub fn display(&self, verbose: bool) {
+        println!("health score: {:.1}/100", self.score);
+        for workspace in &self.workspaces {
+            let bumps = match workspace.bump_compliance {
+                Some(percentage) => format!(", bumps {:.1}%", percentage),
+                None => String::new(),
+            };
+            println!(
+                "  {}: {:.1} ({} crates, changelogs {:.1}%{}, manifests {:.1}%, {} in cycles, {} orphaned)",
+                workspace.workspace,
+                workspace.score,
+                workspace.crates,
+                workspace.changelog_compliance,
+                bumps,
+                workspace.manifest_completeness,
+                workspace.crates_in_cycles,
+                workspace.orphan_crates
+            );
+        }
+
+        if !verbose || self.crates.is_empty() {
+            return;
+        }
+
+        let name_width = self
+            .crates
+            .iter()
+            .map(|c| c.crate_id.display_name().len())
+            .max()
+            .unwrap_or(MIN_CRATE_NAME_WIDTH)
+            .max(MIN_CRATE_NAME_WIDTH);
+
+        println!();
+        println!(
+            "  {:<name_width$}  {:>6}  {:>5}  {:>9}  {:>8}  {:>6}  {:>6}",
+            "Crate",
+            "Score",
+            "Cycle",
+            "Changelog",
+            "Manifest",
+            "Orphan",
+            "Bumped",
+            name_width = name_width
+        );
+        println!(
+            "  {}  {}  {}  {}  {}  {}  {}",
+            "-".repeat(name_width),
+            "-".repeat(6),
+            "-".repeat(5),
+            "-".repeat(9),
+            "-".repeat(8),
+            "-".repeat(6),
+            "-".repeat(6)
+        );
+        for crate_health in &self.crates {
+            let flag = |value: bool| if value { "yes" } else { "no" };
+            println!(
+                "  {:<name_width$}  {:>6.1}  {:>5}  {:>8.0}%  {:>7.0}%  {:>6}  {:>6}",
+                crate_health.crate_id.display_name(),
+                crate_health.score,
+                flag(crate_health.in_cycle),
+                crate_health.changelog * 100.0,
+                crate_health.manifest * 100.0,
+                flag(crate_health.orphan),
+                crate_health.bumped.map(flag).unwrap_or("-"),
+                name_width = name_width
+            );
+        }
+    }
+}
+
+/// composite health score from the existing checks
+pub struct HealthScorer;
+
+impl HealthScorer {
+    /// score every crate and workspace; `bumps` adds bump compliance for the
+    /// crates it covers
+    pub fn analyze<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        config: &DeptrackConfig,
+        bumps: Option<&VersionBumpAnalysis>,
+    ) -> Result<HealthReport> {
+        let changelogs = ChangelogChecker::analyze_all(
+            graph,
+            repo_path,
+            &config.changelog,
+            &config.direct_severity,
+        )?;
+        let manifests = ManifestChecker::analyze_all(graph, &config.direct_severity)?;
+        let in_cycles: HashSet<&CrateId> = graph
+            .find_production_cycles()
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut by_workspace: BTreeMap<&str, Vec<CrateHealth>> = BTreeMap::new();
+        for crate_id in graph.crates.keys() {
+            let changelog = match changelogs.statuses.get(crate_id) {
+                Some(s) if s.has_changelog && s.format_valid && s.current_version_has_entry => 1.0,
+                Some(s) if s.has_changelog => 0.5,
+                _ => 0.0,
+            };
+            let manifest = match manifests.statuses.get(crate_id) {
+                Some(s) if s.publishable => {
+                    1.0 - s.missing_fields.len() as f64 / ManifestChecker::REQUIRED_FIELDS as f64
+                }
+                _ => 1.0,
+            };
+            let bumped = bumps
+                .and_then(|b| b.crate_versions.get(crate_id))
+                .map(|status| status.is_bumped);
+
+            by_workspace
+                .entry(crate_id.workspace.as_str())
+                .or_default()
+                .push(CrateHealth {
+                    crate_id: crate_id.clone(),
+                    score: 0.0,
+                    in_cycle: in_cycles.contains(crate_id),
+                    changelog,
+                    manifest,
+                    orphan: graph.get_dependents(crate_id).is_empty()
+                        && graph.get_dependencies(crate_id).is_empty(),
+                    bumped,
+                });
+        }
+
+        let mut report = HealthReport::default();
+        for (workspace, mut crates) in by_workspace {
+            let orphan_applies = crates.len() > 1;
+            for crate_health in &mut crates {
+                if !orphan_applies {
+                    crate_health.orphan = false;
+                }
+                crate_health.compute_score(orphan_applies);
+            }
+
+            let count = crates.len() as f64;
+            let bumped: Vec<bool> = crates.iter().filter_map(|c| c.bumped).collect();
+            report.workspaces.push(WorkspaceHealth {
+                workspace: workspace.to_string(),
+                score: round(crates.iter().map(|c| c.score).sum::<f64>() / count),
+                crates: crates.len(),
+                crates_in_cycles: crates.iter().filter(|c| c.in_cycle).count(),
+                changelog_compliance: round(
+                    100.0 * crates.iter().filter(|c| c.changelog == 1.0).count() as f64 / count,
+                ),
+                bump_compliance: (!bumped.is_empty()).then(|| {
+                    round(
+                        100.0 * bumped.iter().filter(|b| **b).count() as f64 / bumped.len() as f64,
+                    )
+                }),
+                manifest_completeness: round(
+                    100.0 * crates.iter().map(|c| c.manifest).sum::<f64>() / count,
+                ),
+                orphan_crates: crates.iter().filter(|c| c.orphan).count(),
+            });
+            report.crates.extend(crates);
+        }
+
+        if !report.crates.is_empty() {
+            report.score = round(
+                report.crates.iter().map(|c| c.score).sum::<f64>() / report.crates.len() as f64,
+            );
+        }
+        report.crates.sort_by_key(|c| c.crate_id.display_name());
+        Ok(report)
+    }
+}
+
+// one decimal place, enough for a dashboard and stable in json
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_health_scores() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"app\", \"lonely\"]\n",
+        )
+        .unwrap();
+        for (name, extra) in [
+            ("core", "publish = false\n"),
+            (
+                "app",
+                "publish = false\n\n[dependencies]\ncore = { path = \"../core\" }\n",
+            ),
+            ("lonely", ""),
+        ] {
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+            fs::write(
+                root.join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n{}",
+                    name, extra
+                ),
+            )
+            .unwrap();
+        }
+        fs::write(
+            root.join("core/CHANGELOG.md"),
+            "# Changelog\n\n## [0.1.0]\n\n- feat: first release\n",
+        )
+        .unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let report = HealthScorer::analyze(&graph, root, &DeptrackConfig::default(), None).unwrap();
+
+        let names: Vec<&str> = report
+            .crates
+            .iter()
+            .map(|c| c.crate_id.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["app", "core", "lonely"]);
+
+        let core = &report.crates[1];
+        assert_eq!(core.changelog, 1.0);
+        assert_eq!(core.score, 100.0);
+
+        // no changelog: 30 + 15 + 10 of 80
+        assert_eq!(report.crates[0].score, 68.8);
+
+        // no changelog, no manifest fields, not connected: 30 of 80
+        let lonely = &report.crates[2];
+        assert!(lonely.orphan);
+        assert_eq!(lonely.manifest, 0.0);
+        assert_eq!(lonely.score, 37.5);
+
+        let workspace = &report.workspaces[0];
+        assert_eq!(workspace.crates, 3);
+        assert_eq!(workspace.orphan_crates, 1);
+        assert_eq!(workspace.changelog_compliance, 33.3);
+        assert_eq!(workspace.manifest_completeness, 66.7);
+        assert!(workspace.bump_compliance.is_none());
+        assert_eq!(report.score, 68.8);
+    }
+}
@@ -0,0 +1,186 @@
+// issues of the last check-versions runs, so warnings nobody fixes can be
+// escalated to errors after `[severity] escalate_after` runs
+
+use super::cargo_ops::types::CrateId;
+use super::git_ops::GitOps;
+use super::report_compare::ReportIssue;
+use super::severity::{Issue, IssueSeverity};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// history file, relative to the repository root
+pub const HISTORY_FILE: &str = ".deptrack/history.json";
+
+/// what makes two runs report the same issue; messages are left out as they
+/// carry versions and paths that change from run to run
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct IssueKey {
+    pub check: String,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    #[serde(rename = "type")]
+    pub issue_type: String,
+}
+
+impl IssueKey {
+    fn new(check: &str, crate_id: &CrateId, issue: &Issue) -> Self {
+        IssueKey {
+            check: check.to_string(),
+            crate_name: crate_id.display_name(),
+            issue_type: issue.issue_type.to_string(),
+        }
+    }
+}
+
+/// contents of the history file, oldest run first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFile {
+    pub runs: Vec<BTreeSet<IssueKey>>,
+}
+
+/// the issues of the last `escalate_after` runs of one repository
+#[derive(Debug, Clone)]
+pub struct IssueHistory {
+    path: PathBuf,
+    escalate_after: usize,
+    runs: Vec<BTreeSet<IssueKey>>,
+}
+
+impl IssueHistory {
+    /// history of the repository containing `repo_path`; a missing or
+    /// unreadable file starts a new one
+    pub fn load<P: AsRef<Path>>(repo_path: P, escalate_after: usize) -> Result<Self> {
+        let root = GitOps::detect_repository_root(repo_path.as_ref())?;
+        let path = root.join(HISTORY_FILE);
+        let runs = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<HistoryFile>(&content).ok())
+            .map(|file| file.runs)
+            .unwrap_or_default();
+        Ok(Self {
+            path,
+            escalate_after,
+            runs,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// check if `key` was reported by each of the last `escalate_after` runs
+    pub fn is_chronic(&self, key: &IssueKey) -> bool {
+        self.escalate_after > 0
+            && self.runs.len() >= self.escalate_after
+            && self.runs[self.runs.len() - self.escalate_after..]
+                .iter()
+                .all(|run| run.contains(key))
+    }
+
+    /// turn the chronic warnings of one check into errors; returns how many
+    /// were escalated so the caller can move them between its totals
+    pub fn escalate<S>(
+        &self,
+        check: &str,
+        statuses: &mut HashMap<CrateId, S>,
+        issues: impl Fn(&mut S) -> &mut Vec<Issue>,
+    ) -> usize {
+        let mut escalated = 0;
+        for (crate_id, status) in statuses.iter_mut() {
+            for issue in issues(status).iter_mut() {
+                if issue.severity == IssueSeverity::Warning
+                    && self.is_chronic(&IssueKey::new(check, crate_id, issue))
+                {
+                    issue.severity = IssueSeverity::Error;
+                    issue.message = format!(
+                        "{} (escalated: reported by each of the last {} runs)",
+                        issue.message, self.escalate_after
+                    );
+                    escalated += 1;
+                }
+            }
+        }
+        escalated
+    }
+
+    /// add the issues of this run, forgetting runs too old to matter
+    pub fn record(&mut self, issues: &[ReportIssue]) {
+        self.runs.push(
+            issues
+                .iter()
+                .map(|issue| IssueKey {
+                    check: issue.check.clone(),
+                    crate_name: issue.crate_name.clone(),
+                    issue_type: issue.issue_type.clone(),
+                })
+                .collect(),
+        );
+        let keep = self.escalate_after.max(1);
+        if self.runs.len() > keep {
+            self.runs.drain(..self.runs.len() - keep);
+        }
+    }
+
+    /// write the history, replacing the file atomically
+    pub fn save(&self) -> Result<()> {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        let file = HistoryFile {
+            runs: self.runs.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(std::io::Error::from)?;
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(json.as_bytes())?;
+        tmp.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::severity::IssueType;
+
+    fn history(escalate_after: usize) -> IssueHistory {
+        IssueHistory {
+            path: PathBuf::from(HISTORY_FILE),
+            escalate_after,
+            runs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_escalate_chronic_warnings() {
+        let crate_id = CrateId::new("ws".to_string(), "core".to_string());
+        let warning = Issue::new(
+            IssueSeverity::Warning,
+            IssueType::MissingChangelog,
+            "no changelog".to_string(),
+        );
+        let reported = [ReportIssue::new("changelog", &crate_id, &warning)];
+        let statuses = || HashMap::from([(crate_id.clone(), vec![warning.clone()])]);
+
+        let mut history = history(2);
+        history.record(&reported);
+        let mut once = statuses();
+        assert_eq!(history.escalate("changelog", &mut once, |s| s), 0);
+
+        history.record(&reported);
+        // another check reporting the same type is a different issue
+        let mut other = statuses();
+        assert_eq!(history.escalate("manifest", &mut other, |s| s), 0);
+        let mut twice = statuses();
+        assert_eq!(history.escalate("changelog", &mut twice, |s| s), 1);
+        assert_eq!(twice[&crate_id][0].severity, IssueSeverity::Error);
+        assert!(twice[&crate_id][0].message.contains("escalated"));
+
+        // a clean run resets the count, and only the last two runs are kept
+        history.record(&[]);
+        assert_eq!(history.runs.len(), 2);
+        let mut fixed = statuses();
+        assert_eq!(history.escalate("changelog", &mut fixed, |s| s), 0);
+    }
+}
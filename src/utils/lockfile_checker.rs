@@ -0,0 +1,314 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use crate::error::{Error, Result};
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::severity::{Issue, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Cargo.lock agreement of a single crate
+#[derive(Debug, Clone, Serialize)]
+pub struct LockfileStatus {
+    /// the crate identifier
+    pub crate_id: CrateId,
+    /// version in the crate's Cargo.toml
+    pub manifest_version: String,
+    /// lock file covering the crate, None when there is none up to the repo root
+    pub lockfile: Option<PathBuf>,
+    /// versions the lock file records for the crate as a local package
+    pub locked_versions: Vec<String>,
+    /// issues found for this crate
+    pub issues: Vec<Issue>,
+}
+
+/// Cargo.lock consistency analysis across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LockfileAnalysis {
+    /// status for every analyzed crate
+    pub statuses: HashMap<CrateId, LockfileStatus>,
+    /// crates whose locked version differs from their Cargo.toml
+    pub mismatched_crates: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+impl LockfileAnalysis {
+    /// check if every lock file agrees with the manifests
+    pub fn all_consistent(&self) -> bool {
+        self.mismatched_crates.is_empty()
+    }
+
+    /// display detailed lock file issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "lock file",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+}
+
+impl IssueTotals for LockfileAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// compares workspace member versions recorded in Cargo.lock with their manifests
+pub struct LockfileChecker;
+
+impl LockfileChecker {
+    /// the Cargo.lock covering `crate_path`: the nearest one in it or a parent
+    /// directory, not looking above `repo_path`
+    pub fn find_lockfile(crate_path: &Path, repo_path: &Path) -> Option<PathBuf> {
+        crate_path
+            .ancestors()
+            .take_while(|dir| dir.starts_with(repo_path))
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|lockfile| lockfile.is_file())
+    }
+
+    /// versions of the local packages in a Cargo.lock by name; registry and
+    /// git packages carry a `source` and are left out
+    pub fn local_packages(
+        content: &str,
+    ) -> std::result::Result<HashMap<String, Vec<String>>, String> {
+        let lock: toml::Table = content.parse().map_err(|e: toml::de::Error| {
+            let message: Vec<&str> = e
+                .message()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect();
+            format!("invalid Cargo.lock: {}", message.join("; "))
+        })?;
+        let mut packages: HashMap<String, Vec<String>> = HashMap::new();
+
+        let entries = lock
+            .get("package")
+            .and_then(toml::Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for package in entries {
+            if package.get("source").is_some() {
+                continue;
+            }
+            let name = package.get("name").and_then(toml::Value::as_str);
+            let version = package.get("version").and_then(toml::Value::as_str);
+            if let (Some(name), Some(version)) = (name, version) {
+                packages
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(version.to_string());
+            }
+        }
+        Ok(packages)
+    }
+
+    /// compare the locked versions of `crates` with their manifests, using the
+    /// direct severity for crates in `directly_affected` and the transitive
+    /// one for the rest; crates without a lock file are not checked
+    pub fn analyze<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        crates: &[CrateId],
+        directly_affected: &[CrateId],
+        direct_severity: &SeverityConfig,
+        transitive_severity: &SeverityConfig,
+    ) -> Result<LockfileAnalysis> {
+        let repo_path = repo_path.as_ref();
+        let mut analysis = LockfileAnalysis::default();
+        // a lock file that cannot be read fails every crate using it, the
+        // others are still checked
+        let mut lockfiles: HashMap<
+            PathBuf,
+            std::result::Result<HashMap<String, Vec<String>>, Error>,
+        > = HashMap::new();
+
+        for crate_id in crates {
+            let Some(crate_info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let lockfile = Self::find_lockfile(&crate_info.path, repo_path);
+            let severity_config = if directly_affected.contains(crate_id) {
+                direct_severity
+            } else {
+                transitive_severity
+            };
+
+            let mut issues = Vec::new();
+            let locked_versions = match &lockfile {
+                Some(lockfile) => {
+                    let packages = lockfiles
+                        .entry(lockfile.clone())
+                        .or_insert_with(|| Self::read_lockfile(lockfile));
+                    match packages {
+                        Ok(packages) => packages.get(&crate_id.name).cloned().unwrap_or_default(),
+                        Err(e) => {
+                            issues.push(analysis.counted_issue(
+                                severity_config,
+                                IssueType::AnalysisFailed,
+                                format!("Cargo.lock could not be checked: {}", e),
+                            ));
+                            Vec::new()
+                        }
+                    }
+                }
+                None => Vec::new(),
+            };
+
+            // a member missing from the lock file has not been locked yet;
+            // cargo adds it without touching other entries
+            if !locked_versions.is_empty() && !locked_versions.contains(&crate_info.version) {
+                let message = format!(
+                    "Cargo.lock records {} but Cargo.toml has {}, run `cargo update --workspace` to regenerate it",
+                    locked_versions.join(", "),
+                    crate_info.version
+                );
+                issues.push(analysis.counted_issue(
+                    severity_config,
+                    IssueType::LockfileMismatch,
+                    message,
+                ));
+                analysis.mismatched_crates.push(crate_id.clone());
+            }
+
+            analysis.statuses.insert(
+                crate_id.clone(),
+                LockfileStatus {
+                    crate_id: crate_id.clone(),
+                    manifest_version: crate_info.version.clone(),
+                    lockfile,
+                    locked_versions,
+                    issues,
+                },
+            );
+        }
+
+        analysis
+            .mismatched_crates
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    // local packages of the Cargo.lock at `lockfile`
+    fn read_lockfile(lockfile: &Path) -> Result<HashMap<String, Vec<String>>> {
+        let content = std::fs::read_to_string(lockfile).map_err(|e| Error::FileReadError {
+            path: lockfile.to_path_buf(),
+            source: e,
+        })?;
+        Self::local_packages(&content).map_err(|reason| Error::FileReadError {
+            path: lockfile.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, reason),
+        })
+    }
+
+    /// compare the locked versions of every crate in the graph
+    pub fn analyze_all<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        severity_config: &SeverityConfig,
+    ) -> Result<LockfileAnalysis> {
+        let crates: Vec<CrateId> = graph.crates.keys().cloned().collect();
+        Self::analyze(
+            graph,
+            repo_path,
+            &crates,
+            &crates,
+            severity_config,
+            severity_config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cargo_ops::types::CrateInfo;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const LOCKFILE: &str = r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["core", "serde"]
+
+[[package]]
+name = "core"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[test]
+    fn test_local_packages() {
+        let packages = LockfileChecker::local_packages(LOCKFILE).unwrap();
+        assert_eq!(packages["core"], vec!["0.1.0"]);
+        assert!(!packages.contains_key("serde"));
+        assert!(LockfileChecker::local_packages("[[package]").is_err());
+    }
+
+    #[test]
+    fn test_analyze() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("Cargo.lock"), LOCKFILE).unwrap();
+
+        let mut graph = CrateDependencyGraph::new();
+        let mut add = |name: &str, version: &str| {
+            let id = CrateId::new("ws".to_string(), name.to_string());
+            graph.add_crate(CrateInfo::new(
+                id.clone(),
+                version.to_string(),
+                root.join(name),
+            ));
+            id
+        };
+        let core = add("core", "0.2.0");
+        let app = add("app", "0.1.0");
+        let fresh = add("fresh", "0.1.0");
+        let tool = add("tool", "0.1.0");
+        fs::create_dir_all(root.join("tool")).unwrap();
+        fs::write(root.join("tool/Cargo.lock"), "[[package]").unwrap();
+
+        let analysis =
+            LockfileChecker::analyze_all(&graph, root, &SeverityConfig::default_direct()).unwrap();
+        assert_eq!(analysis.mismatched_crates, vec![core.clone()]);
+        assert_eq!(analysis.total_errors, 2);
+        // an unreadable lock file fails only the crates using it
+        let failure = &analysis.statuses[&tool].issues[0];
+        assert_eq!(failure.issue_type, IssueType::AnalysisFailed);
+        assert!(
+            failure
+                .message
+                .starts_with("Cargo.lock could not be checked")
+        );
+        assert!(
+            analysis.statuses[&core].issues[0]
+                .message
+                .starts_with("Cargo.lock records 0.1.0 but Cargo.toml has 0.2.0")
+        );
+        assert!(analysis.statuses[&app].issues.is_empty());
+        assert!(analysis.statuses[&fresh].locked_versions.is_empty());
+        assert_eq!(
+            analysis.statuses[&app].lockfile,
+            Some(root.join("Cargo.lock"))
+        );
+
+        // nothing to compare without a lock file
+        fs::remove_file(root.join("Cargo.lock")).unwrap();
+        let analysis =
+            LockfileChecker::analyze_all(&graph, root, &SeverityConfig::default_direct()).unwrap();
+        assert!(analysis.all_consistent());
+    }
+}
@@ -0,0 +1,450 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use crate::error::Result;
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::severity::{Issue, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use crate::utils::toml_ops::{TomlDocument, TomlReader};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// files cargo picks up as the readme when the `readme` field is not set
+const DEFAULT_README_FILES: [&str; 3] = ["README.md", "README.txt", "README"];
+
+/// dependency tables `cargo publish` keeps, path dev-dependencies without a
+/// version are stripped instead
+const PUBLISHED_DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "build-dependencies"];
+
+/// a path dependency without a version requirement, which `cargo publish`
+/// rejects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnversionedDependency {
+    /// name of the crate depended on
+    pub name: String,
+    /// declared with `workspace = true`, the path is set in [workspace.dependencies]
+    pub inherited: bool,
+}
+
+/// publish-readiness of a single crate manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestStatus {
+    /// the crate identifier
+    pub crate_id: CrateId,
+    /// false when the manifest sets `publish = false` (or an empty registry list)
+    pub publishable: bool,
+    /// required fields missing from [package]
+    pub missing_fields: Vec<String>,
+    /// path dependencies without a version requirement
+    pub unversioned_dependencies: Vec<UnversionedDependency>,
+    /// issues found for this crate
+    pub issues: Vec<Issue>,
+}
+
+/// publish-readiness analysis across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ManifestAnalysis {
+    /// status for every analyzed crate
+    pub statuses: HashMap<CrateId, ManifestStatus>,
+    /// publishable crates with at least one missing field
+    pub incomplete_crates: Vec<CrateId>,
+    /// publishable crates with a path dependency lacking a version requirement
+    pub unversioned_crates: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+impl ManifestAnalysis {
+    /// check if every publishable crate has a complete manifest and
+    /// versioned path dependencies
+    pub fn all_complete(&self) -> bool {
+        self.incomplete_crates.is_empty() && self.unversioned_crates.is_empty()
+    }
+
+    /// display detailed manifest issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "manifest",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+
+    // a crate whose manifest could not be read, recorded so the other
+    // crates are still checked
+    fn push_failure(
+        &mut self,
+        crate_id: &CrateId,
+        severity_config: &SeverityConfig,
+        error: impl std::fmt::Display,
+    ) {
+        let issue = self.counted_issue(
+            severity_config,
+            IssueType::AnalysisFailed,
+            format!("manifest could not be checked: {}", error),
+        );
+        self.statuses.insert(
+            crate_id.clone(),
+            ManifestStatus {
+                crate_id: crate_id.clone(),
+                publishable: true,
+                missing_fields: Vec::new(),
+                unversioned_dependencies: Vec::new(),
+                issues: vec![issue],
+            },
+        );
+    }
+}
+
+impl IssueTotals for ManifestAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// checks crate manifests for the metadata `cargo publish` requires
+pub struct ManifestChecker;
+
+impl ManifestChecker {
+    /// number of fields `missing_fields` checks for
+    pub const REQUIRED_FIELDS: usize = 4;
+
+    /// whether the manifest allows publishing to any registry
+    pub fn is_publishable(document: &TomlDocument) -> bool {
+        match document.get_path("package.publish") {
+            Some(toml::Value::Boolean(publish)) => *publish,
+            Some(toml::Value::Array(registries)) => !registries.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// required [package] fields missing from the manifest in `crate_path`
+    ///
+    /// fields inherited from the workspace (`description.workspace = true`)
+    /// count as present, and a readme file cargo would pick up by default
+    /// satisfies `readme`
+    pub fn missing_fields(document: &TomlDocument, crate_path: &Path) -> Vec<String> {
+        let has = |field: &str| document.get_path(&format!("package.{}", field)).is_some();
+        let mut missing = Vec::new();
+
+        if !has("description") {
+            missing.push("description".to_string());
+        }
+        if !has("license") && !has("license-file") {
+            missing.push("license".to_string());
+        }
+        if !has("repository") {
+            missing.push("repository".to_string());
+        }
+
+        let readme_disabled = matches!(
+            document.get_path("package.readme"),
+            Some(toml::Value::Boolean(false))
+        );
+        let default_readme = DEFAULT_README_FILES
+            .iter()
+            .any(|name| crate_path.join(name).is_file());
+        if readme_disabled || (!has("readme") && !default_readme) {
+            missing.push("readme".to_string());
+        }
+
+        missing
+    }
+
+    /// path dependencies in [dependencies] and [build-dependencies] (target
+    /// specific ones included) without a `version`; an entry with
+    /// `workspace = true` is looked up in the [workspace.dependencies] of
+    /// `workspace_document`
+    pub fn unversioned_path_dependencies(
+        document: &TomlDocument,
+        workspace_document: Option<&TomlDocument>,
+    ) -> Vec<UnversionedDependency> {
+        let workspace_dependencies =
+            workspace_document.and_then(|ws| ws.get_path("workspace.dependencies"));
+        let mut unversioned = Vec::new();
+
+        for table in document.dependency_tables(&PUBLISHED_DEPENDENCY_TABLES) {
+            for (key, dependency) in table {
+                let Some(dependency) = dependency.as_table() else {
+                    continue;
+                };
+                let inherited =
+                    dependency.get("workspace").and_then(toml::Value::as_bool) == Some(true);
+                let declaration = if inherited {
+                    match workspace_dependencies
+                        .and_then(|deps| deps.get(key))
+                        .and_then(toml::Value::as_table)
+                    {
+                        Some(declaration) => declaration,
+                        None => continue,
+                    }
+                } else {
+                    dependency
+                };
+                if !declaration.contains_key("path") || declaration.contains_key("version") {
+                    continue;
+                }
+
+                let name = declaration
+                    .get("package")
+                    .or_else(|| dependency.get("package"))
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(key)
+                    .to_string();
+                let entry = UnversionedDependency { name, inherited };
+                if !unversioned.contains(&entry) {
+                    unversioned.push(entry);
+                }
+            }
+        }
+
+        unversioned.sort_by(|a, b| a.name.cmp(&b.name));
+        unversioned
+    }
+
+    /// check publish-readiness of `crates`, using the direct severity for
+    /// crates in `directly_affected` and the transitive one for the rest
+    pub fn analyze(
+        graph: &CrateDependencyGraph,
+        crates: &[CrateId],
+        directly_affected: &[CrateId],
+        direct_severity: &SeverityConfig,
+        transitive_severity: &SeverityConfig,
+    ) -> Result<ManifestAnalysis> {
+        let mut analysis = ManifestAnalysis::default();
+        let mut workspace_documents: HashMap<&str, Option<TomlDocument>> = HashMap::new();
+
+        for crate_id in crates {
+            let Some(crate_info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let severity_config = if directly_affected.contains(crate_id) {
+                direct_severity
+            } else {
+                transitive_severity
+            };
+            let document = match TomlReader::read_manifest(&crate_info.cargo_toml_path) {
+                Ok(document) => document,
+                Err(e) => {
+                    analysis.push_failure(crate_id, severity_config, e);
+                    continue;
+                }
+            };
+
+            let publishable = Self::is_publishable(&document);
+            let (missing_fields, unversioned_dependencies) = if publishable {
+                if !workspace_documents.contains_key(crate_id.workspace.as_str()) {
+                    let workspace_document = graph
+                        .workspaces
+                        .iter()
+                        .find(|workspace| workspace.name == crate_id.workspace)
+                        .map(|workspace| TomlReader::read_manifest(&workspace.cargo_toml_path))
+                        .transpose();
+                    match workspace_document {
+                        Ok(workspace_document) => {
+                            workspace_documents.insert(&crate_id.workspace, workspace_document);
+                        }
+                        Err(e) => {
+                            analysis.push_failure(crate_id, severity_config, e);
+                            continue;
+                        }
+                    }
+                }
+                (
+                    Self::missing_fields(&document, &crate_info.path),
+                    Self::unversioned_path_dependencies(
+                        &document,
+                        workspace_documents[crate_id.workspace.as_str()].as_ref(),
+                    ),
+                )
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+            let mut issues = Vec::new();
+            if !missing_fields.is_empty() {
+                let message = format!(
+                    "Cargo.toml is missing {} required for publishing",
+                    missing_fields.join(", ")
+                );
+                issues.push(analysis.counted_issue(
+                    severity_config,
+                    IssueType::IncompleteManifest,
+                    message,
+                ));
+                analysis.incomplete_crates.push(crate_id.clone());
+            }
+            if !unversioned_dependencies.is_empty() {
+                let names: Vec<&str> = unversioned_dependencies
+                    .iter()
+                    .map(|dependency| dependency.name.as_str())
+                    .collect();
+                let message = format!(
+                    "path dependencies without a version requirement block publishing: {} (`deptrack deps sync` adds them)",
+                    names.join(", ")
+                );
+                issues.push(analysis.counted_issue(
+                    severity_config,
+                    IssueType::MissingPathDepVersion,
+                    message,
+                ));
+                analysis.unversioned_crates.push(crate_id.clone());
+            }
+
+            analysis.statuses.insert(
+                crate_id.clone(),
+                ManifestStatus {
+                    crate_id: crate_id.clone(),
+                    publishable,
+                    missing_fields,
+                    unversioned_dependencies,
+                    issues,
+                },
+            );
+        }
+
+        analysis
+            .incomplete_crates
+            .sort_by_key(|id| id.display_name());
+        analysis
+            .unversioned_crates
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    /// check publish-readiness of every crate in the graph
+    pub fn analyze_all(
+        graph: &CrateDependencyGraph,
+        severity_config: &SeverityConfig,
+    ) -> Result<ManifestAnalysis> {
+        let crates: Vec<CrateId> = graph.crates.keys().cloned().collect();
+        Self::analyze(graph, &crates, &crates, severity_config, severity_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn manifest(content: &str) -> TomlDocument {
+        TomlReader::parse_string(content, "Cargo.toml".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let bare = manifest("[package]\nname = \"bare\"\nversion = \"0.1.0\"\n");
+        assert_eq!(
+            ManifestChecker::missing_fields(&bare, temp_dir.path()),
+            vec!["description", "license", "repository", "readme"]
+        );
+
+        let complete = manifest(
+            r#"
+            [package]
+            name = "complete"
+            version = "0.1.0"
+            description.workspace = true
+            license-file = "LICENSE"
+            repository = "https://example.com/complete"
+            "#,
+        );
+        assert_eq!(
+            ManifestChecker::missing_fields(&complete, temp_dir.path()),
+            vec!["readme"]
+        );
+
+        // cargo falls back to README.md unless readme = false
+        fs::write(temp_dir.path().join("README.md"), "# complete\n").unwrap();
+        assert!(ManifestChecker::missing_fields(&complete, temp_dir.path()).is_empty());
+
+        let disabled = manifest(
+            r#"
+            [package]
+            name = "disabled"
+            description = "d"
+            license = "MIT"
+            repository = "https://example.com/disabled"
+            readme = false
+            "#,
+        );
+        assert_eq!(
+            ManifestChecker::missing_fields(&disabled, temp_dir.path()),
+            vec!["readme"]
+        );
+    }
+
+    #[test]
+    fn test_unversioned_path_dependencies() {
+        let workspace = manifest(
+            r#"
+            [workspace.dependencies]
+            shared = { path = "crates/shared" }
+            versioned = { path = "crates/versioned", version = "0.2" }
+            "#,
+        );
+        let document = manifest(
+            r#"
+            [package]
+            name = "app"
+
+            [dependencies]
+            core = { path = "../core" }
+            pinned = { path = "../pinned", version = "0.1" }
+            serde = "1.0"
+            shared = { workspace = true }
+            versioned = { workspace = true }
+            alias = { package = "helpers", path = "../helpers" }
+
+            [build-dependencies]
+            core = { path = "../core" }
+
+            [dev-dependencies]
+            testing = { path = "../testing" }
+
+            [target.'cfg(unix)'.dependencies]
+            sys = { path = "../sys" }
+            "#,
+        );
+
+        let unversioned = |name: &str, inherited| UnversionedDependency {
+            name: name.to_string(),
+            inherited,
+        };
+        assert_eq!(
+            ManifestChecker::unversioned_path_dependencies(&document, Some(&workspace)),
+            vec![
+                unversioned("core", false),
+                unversioned("helpers", false),
+                unversioned("shared", true),
+                unversioned("sys", false),
+            ]
+        );
+        // without the workspace manifest inherited entries cannot be resolved
+        assert_eq!(
+            ManifestChecker::unversioned_path_dependencies(&document, None).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_is_publishable() {
+        assert!(ManifestChecker::is_publishable(&manifest(
+            "[package]\nname = \"a\"\n"
+        )));
+        assert!(!ManifestChecker::is_publishable(&manifest(
+            "[package]\nname = \"a\"\npublish = false\n"
+        )));
+        assert!(!ManifestChecker::is_publishable(&manifest(
+            "[package]\nname = \"a\"\npublish = []\n"
+        )));
+        assert!(ManifestChecker::is_publishable(&manifest(
+            "[package]\nname = \"a\"\npublish = [\"internal\"]\n"
+        )));
+    }
+}
@@ -0,0 +1,421 @@
+use super::cargo_ops::RemovedCrate;
+use super::cargo_ops::discovery::CargoDiscovery;
+use super::cargo_ops::fingerprint::relative;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, Workspace};
+use crate::error::Result;
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::severity::{Issue, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use crate::utils::toml_ops::TomlReader;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// workspace name of crates outside of every workspace
+pub const NO_WORKSPACE: &str = "none";
+
+/// a crate left out of its workspace, a member entry pointing nowhere, a
+/// manifest discovery skipped, or a crate depending on a removed one
+#[derive(Debug, Clone, Serialize)]
+pub struct MembershipStatus {
+    /// the crate, the workspace and member entry of a missing member, or the
+    /// enclosing workspace and path of a broken manifest
+    pub crate_id: CrateId,
+    /// the crate's Cargo.toml, or where the member entry points, relative to
+    /// the repository root
+    pub path: PathBuf,
+    /// issues found for this crate or entry
+    pub issues: Vec<Issue>,
+}
+
+/// workspace membership analysis of a repository
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MembershipAnalysis {
+    /// status of every unlisted crate and missing member
+    pub statuses: HashMap<CrateId, MembershipStatus>,
+    /// crates on disk that are not a member of any workspace
+    pub unlisted_crates: Vec<CrateId>,
+    /// member entries without a crate behind them
+    pub missing_members: Vec<CrateId>,
+    /// Cargo.toml files that do not parse or are not a package or workspace
+    pub broken_manifests: Vec<CrateId>,
+    /// crates still depending on a crate removed between the refs
+    pub stale_references: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+impl MembershipAnalysis {
+    /// check if every crate is a member and every member exists
+    pub fn all_listed(&self) -> bool {
+        self.unlisted_crates.is_empty() && self.missing_members.is_empty()
+    }
+
+    /// check if every manifest in the repository could be read
+    pub fn all_parsed(&self) -> bool {
+        self.broken_manifests.is_empty()
+    }
+
+    /// check if no crate depends on a removed one
+    pub fn no_stale_references(&self) -> bool {
+        self.stale_references.is_empty()
+    }
+
+    /// display detailed membership issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "membership",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+}
+
+impl IssueTotals for MembershipAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// finds crates and workspace members that discovery drops without a word:
+/// a Cargo.toml with a [package] no workspace lists (nor excludes), a
+/// `members` entry matching no crate, and manifests discovery had to skip
+pub struct MembershipChecker;
+
+impl MembershipChecker {
+    /// check the repository at `repo_path` against the crates of `graph`;
+    /// with a scoped graph only manifests in scope are looked at
+    pub fn analyze<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        severity_config: &SeverityConfig,
+    ) -> Result<MembershipAnalysis> {
+        let repo_path = repo_path.as_ref();
+        let mut analysis = MembershipAnalysis::default();
+        let members: HashSet<PathBuf> = graph
+            .crates
+            .values()
+            .map(|info| canonical(&info.cargo_toml_path))
+            .collect();
+        let excluded: Vec<PathBuf> = graph
+            .workspaces
+            .iter()
+            .flat_map(|workspace| {
+                Self::excluded_paths(workspace)
+                    .into_iter()
+                    .map(|path| canonical(&path))
+            })
+            .collect();
+
+        for manifest in CargoDiscovery::find_manifests(repo_path)? {
+            let manifest_relative = relative(&manifest, repo_path);
+            if graph
+                .scope
+                .as_ref()
+                .is_some_and(|scope| !scope.contains(&manifest_relative))
+            {
+                continue;
+            }
+            let canonical_manifest = canonical(&manifest);
+            if members.contains(&canonical_manifest)
+                || excluded
+                    .iter()
+                    .any(|path| canonical_manifest.starts_with(path))
+            {
+                continue;
+            }
+            // workspace roots and manifests that do not parse are not crates
+            // anyone forgot to list
+            let Ok(toml_doc) = TomlReader::read_file(&manifest) else {
+                continue;
+            };
+            if toml_doc.has_table("workspace") {
+                continue;
+            }
+            let Some(name) = toml_doc
+                .get_table("package")
+                .and_then(|package| package.get("name"))
+                .and_then(|name| name.as_str())
+            else {
+                continue;
+            };
+
+            let workspace = Self::enclosing_workspace(graph, &canonical_manifest)
+                .map(|workspace| workspace.name.clone())
+                .unwrap_or_else(|| NO_WORKSPACE.to_string());
+            let crate_id = CrateId::new(workspace, name.to_string());
+            let message = format!(
+                "{} is not a member of any workspace, so no check sees it; list it in `members` or `exclude`",
+                manifest_relative.display()
+            );
+            Self::push(
+                &mut analysis,
+                severity_config,
+                crate_id,
+                manifest_relative,
+                IssueType::UnlistedCrate,
+                message,
+            );
+        }
+
+        for workspace in &graph.workspaces {
+            for member in &workspace.members {
+                if Self::member_exists(workspace, member) {
+                    continue;
+                }
+                let crate_id = CrateId::new(workspace.name.clone(), member.clone());
+                let message = format!(
+                    "member \"{}\" of {} matches no crate, cargo refuses to load the workspace",
+                    member,
+                    relative(&workspace.cargo_toml_path, repo_path).display()
+                );
+                Self::push(
+                    &mut analysis,
+                    severity_config,
+                    crate_id,
+                    relative(&workspace.root_path.join(member), repo_path),
+                    IssueType::MissingMember,
+                    message,
+                );
+            }
+        }
+
+        for broken in &graph.broken_manifests {
+            let manifest_relative = relative(&broken.path, repo_path);
+            let workspace = Self::enclosing_workspace(graph, &canonical(&broken.path))
+                .map(|workspace| workspace.name.clone())
+                .unwrap_or_else(|| NO_WORKSPACE.to_string());
+            let crate_id = CrateId::new(workspace, manifest_relative.display().to_string());
+            let position = match (broken.line, broken.column) {
+                (Some(line), Some(column)) => format!(":{}:{}", line, column),
+                _ => String::new(),
+            };
+            let message = format!(
+                "{}{} was skipped: {}",
+                manifest_relative.display(),
+                position,
+                broken.error
+            );
+            Self::push(
+                &mut analysis,
+                severity_config,
+                crate_id,
+                manifest_relative,
+                IssueType::BrokenManifest,
+                message,
+            );
+        }
+
+        analysis.unlisted_crates.sort_by_key(|id| id.display_name());
+        analysis.missing_members.sort_by_key(|id| id.display_name());
+        analysis
+            .broken_manifests
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    /// flag the crates of `graph` that still depend on one of `removed`,
+    /// the crates deleted between the refs a change analysis compared
+    pub fn check_removed_crates<P: AsRef<Path>>(
+        analysis: &mut MembershipAnalysis,
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        removed: &[RemovedCrate],
+        severity_config: &SeverityConfig,
+    ) {
+        let repo_path = repo_path.as_ref();
+
+        for crate_removed in removed {
+            for crate_id in &crate_removed.referenced_by {
+                let Some(info) = graph.crates.get(crate_id) else {
+                    continue;
+                };
+                let message = format!(
+                    "depends on {}, which was removed from {}; cargo cannot load the crate until the dependency goes",
+                    crate_removed.name,
+                    crate_removed.path.display()
+                );
+                Self::push(
+                    analysis,
+                    severity_config,
+                    crate_id.clone(),
+                    relative(&info.cargo_toml_path, repo_path),
+                    IssueType::RemovedCrateReferenced,
+                    message,
+                );
+            }
+        }
+
+        analysis
+            .stale_references
+            .sort_by_key(|id| id.display_name());
+    }
+
+    fn push(
+        analysis: &mut MembershipAnalysis,
+        severity_config: &SeverityConfig,
+        crate_id: CrateId,
+        path: PathBuf,
+        issue_type: IssueType,
+        message: String,
+    ) {
+        let issue = analysis.counted_issue(severity_config, issue_type, message);
+        let listed = match issue_type {
+            IssueType::MissingMember => &mut analysis.missing_members,
+            IssueType::BrokenManifest => &mut analysis.broken_manifests,
+            IssueType::RemovedCrateReferenced => &mut analysis.stale_references,
+            _ => &mut analysis.unlisted_crates,
+        };
+        if !listed.contains(&crate_id) {
+            listed.push(crate_id.clone());
+        }
+        // a crate may depend on more than one removed crate
+        analysis
+            .statuses
+            .entry(crate_id.clone())
+            .or_insert_with(|| MembershipStatus {
+                crate_id,
+                path,
+                issues: Vec::new(),
+            })
+            .issues
+            .push(issue);
+    }
+
+    // whether a `members` entry names a directory with a Cargo.toml, or as a
+    // glob matches at least one
+    fn member_exists(workspace: &Workspace, member: &str) -> bool {
+        let pattern = workspace.root_path.join(member).join("Cargo.toml");
+        if !member.contains(['*', '?', '[']) {
+            return pattern.is_file();
+        }
+        glob::glob(&pattern.to_string_lossy())
+            .map(|mut paths| paths.any(|path| path.is_ok()))
+            .unwrap_or(false)
+    }
+
+    // directories under `workspace.exclude` of a workspace
+    fn excluded_paths(workspace: &Workspace) -> Vec<PathBuf> {
+        let Ok(toml_doc) = TomlReader::read_file(&workspace.cargo_toml_path) else {
+            return Vec::new();
+        };
+        toml_doc
+            .get_table("workspace")
+            .and_then(|table| table.get("exclude"))
+            .and_then(|exclude| exclude.as_array())
+            .map(|exclude| {
+                exclude
+                    .iter()
+                    .filter_map(|path| path.as_str())
+                    .map(|path| workspace.root_path.join(path))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // the innermost workspace whose root contains `manifest`
+    fn enclosing_workspace<'a>(
+        graph: &'a CrateDependencyGraph,
+        manifest: &Path,
+    ) -> Option<&'a Workspace> {
+        graph
+            .workspaces
+            .iter()
+            .filter(|workspace| manifest.starts_with(canonical(&workspace.root_path)))
+            .max_by_key(|workspace| workspace.root_path.components().count())
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_crate(root: &Path, dir: &str, name: &str) {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(
+            root.join(dir).join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_analyze() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/core\", \"crates/gone\", \"plugins/*\"]\nexclude = [\"fixtures\"]\n",
+        )
+        .unwrap();
+        write_crate(root, "crates/core", "core");
+        write_crate(root, "crates/stray", "stray");
+        write_crate(root, "fixtures/sample", "sample");
+        fs::create_dir_all(root.join("plugins")).unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let analysis =
+            MembershipChecker::analyze(&graph, root, &SeverityConfig::default_direct()).unwrap();
+
+        let workspace = graph.workspaces[0].name.clone();
+        let names: Vec<_> = analysis
+            .unlisted_crates
+            .iter()
+            .map(|id| id.name.as_str())
+            .collect();
+        assert_eq!(names, ["stray"]);
+        assert_eq!(analysis.unlisted_crates[0].workspace, workspace);
+        let members: Vec<_> = analysis
+            .missing_members
+            .iter()
+            .map(|id| id.name.as_str())
+            .collect();
+        assert_eq!(members, ["crates/gone", "plugins/*"]);
+        assert_eq!(analysis.total_errors, 2);
+        assert_eq!(analysis.total_warnings, 1);
+        assert_eq!(
+            analysis.statuses[&analysis.unlisted_crates[0]].path,
+            Path::new("crates/stray/Cargo.toml")
+        );
+
+        fs::create_dir_all(root.join("crates/broken")).unwrap();
+        fs::write(
+            root.join("crates/broken/Cargo.toml"),
+            "[package]\nname = \"broken\"\nversion = \n",
+        )
+        .unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let analysis =
+            MembershipChecker::analyze(&graph, root, &SeverityConfig::default_direct()).unwrap();
+        let broken = &analysis.statuses[&analysis.broken_manifests[0]];
+        assert_eq!(broken.path, Path::new("crates/broken/Cargo.toml"));
+        assert!(
+            broken.issues[0]
+                .message
+                .starts_with("crates/broken/Cargo.toml:3:11 was skipped")
+        );
+        assert_eq!(analysis.total_warnings, 2);
+        fs::remove_dir_all(root.join("crates/broken")).unwrap();
+
+        write_crate(root, "plugins/auth", "auth");
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"plugins/*\"]\nexclude = [\"fixtures\"]\n",
+        )
+        .unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let analysis =
+            MembershipChecker::analyze(&graph, root, &SeverityConfig::default_direct()).unwrap();
+        assert!(analysis.all_listed());
+        assert!(analysis.all_parsed());
+    }
+}
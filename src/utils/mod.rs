@@ -1,12 +1,49 @@
 pub mod alt;
+pub mod analysis_cache;
+pub mod bazel_export;
+pub mod cancel;
 pub mod cargo_ops;
 pub mod changelog;
 pub mod changelog_checker;
+pub mod changelog_coverage;
+pub mod check_issues;
+pub mod ci_matrix;
+pub mod codeowners;
 pub mod config;
+pub mod crate_detail;
+pub mod daemon;
+#[cfg(feature = "serve")]
+pub mod dashboard;
+pub mod doctor;
+pub mod external_checker;
+pub mod feature_checker;
 pub mod filesystem;
+pub mod fixes;
 pub mod git_ops;
+pub mod github;
+pub mod graph_image;
+pub mod health;
+pub mod issue_history;
+pub mod lockfile_checker;
+pub mod manifest_checker;
+pub mod membership_checker;
+pub mod msrv_checker;
+pub mod notify;
+pub mod output;
+pub mod registry;
+pub mod release;
+pub mod report;
+pub mod report_compare;
+pub mod requirement_sync;
 pub mod severity;
 pub mod severity_config;
+pub mod table;
+pub mod tasks;
+pub mod test_plan;
+pub mod timings;
 pub mod toml_ops;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod version_policy;
 
 pub mod testing;
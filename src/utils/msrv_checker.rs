@@ -0,0 +1,392 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, DependencyType};
+use crate::error::{Error, Result};
+use crate::utils::check_issues::{IssueTotals, display_issue_tables};
+use crate::utils::config::MsrvConfig;
+use crate::utils::severity::{Issue, IssueType};
+use crate::utils::severity_config::SeverityConfig;
+use crate::utils::toml_ops::TomlReader;
+use petgraph::visit::EdgeRef;
+use semver::Version;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// declared rust-version of each crate, as written and parsed
+type DeclaredVersions = HashMap<CrateId, Option<(String, Version)>>;
+
+/// rust-version of a single crate
+#[derive(Debug, Clone, Serialize)]
+pub struct MsrvStatus {
+    /// the crate identifier
+    pub crate_id: CrateId,
+    /// `package.rust-version` as written (or inherited from the workspace)
+    pub rust_version: Option<String>,
+    /// issues found for this crate
+    pub issues: Vec<Issue>,
+}
+
+/// rust-version consistency analysis across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MsrvAnalysis {
+    /// status for every analyzed crate
+    pub statuses: HashMap<CrateId, MsrvStatus>,
+    /// crates whose rust-version disagrees with the policy
+    pub mismatched_crates: Vec<CrateId>,
+    /// total number of error-level issues
+    pub total_errors: usize,
+    /// total number of warning-level issues
+    pub total_warnings: usize,
+}
+
+impl MsrvAnalysis {
+    /// check if every analyzed crate follows the rust-version policy
+    pub fn all_consistent(&self) -> bool {
+        self.mismatched_crates.is_empty()
+    }
+
+    /// display detailed msrv issues split by severity
+    pub fn display_issues(&self) {
+        display_issue_tables(
+            "msrv",
+            self.statuses
+                .values()
+                .flat_map(|status| status.issues.iter().map(|issue| (&status.crate_id, issue))),
+        );
+    }
+}
+
+impl IssueTotals for MsrvAnalysis {
+    fn totals_mut(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.total_errors, &mut self.total_warnings)
+    }
+}
+
+/// checks that `package.rust-version` is consistent across crates
+pub struct MsrvChecker;
+
+impl MsrvChecker {
+    /// parse a rust-version ("1.70" or "1.70.0") into a comparable version
+    pub fn parse_rust_version(rust_version: &str) -> Option<Version> {
+        let parts: Vec<&str> = rust_version.trim().split('.').collect();
+        let [major, minor, patch @ ..] = parts.as_slice() else {
+            return None;
+        };
+        let patch = match patch {
+            [] => 0,
+            [patch] => patch.parse().ok()?,
+            _ => return None,
+        };
+        Some(Version::new(
+            major.parse().ok()?,
+            minor.parse().ok()?,
+            patch,
+        ))
+    }
+
+    /// check the rust-version of `crates`, using the direct severity for
+    /// crates in `directly_affected` and the transitive one for the rest
+    ///
+    /// a crate is reported when its rust-version differs from the one most
+    /// members of its workspace declare (or is below `config.minimum` when
+    /// set), or is lower than that of a workspace crate it depends on
+    pub fn analyze(
+        graph: &CrateDependencyGraph,
+        crates: &[CrateId],
+        directly_affected: &[CrateId],
+        direct_severity: &SeverityConfig,
+        transitive_severity: &SeverityConfig,
+        config: &MsrvConfig,
+    ) -> Result<MsrvAnalysis> {
+        let (declared, failures) = Self::declared_versions(graph);
+        let minimum = config
+            .minimum
+            .as_deref()
+            .and_then(|m| Some((m, Self::parse_rust_version(m)?)));
+
+        let mut analysis = MsrvAnalysis::default();
+
+        for crate_id in crates {
+            if !graph.crates.contains_key(crate_id) {
+                continue;
+            }
+            let severity_config = if directly_affected.contains(crate_id) {
+                direct_severity
+            } else {
+                transitive_severity
+            };
+            if let Some(failure) = failures.get(crate_id) {
+                let issue = analysis.counted_issue(
+                    severity_config,
+                    IssueType::AnalysisFailed,
+                    format!("rust-version could not be checked: {}", failure),
+                );
+                analysis.statuses.insert(
+                    crate_id.clone(),
+                    MsrvStatus {
+                        crate_id: crate_id.clone(),
+                        rust_version: None,
+                        issues: vec![issue],
+                    },
+                );
+                continue;
+            }
+            let own = declared.get(crate_id).cloned().flatten();
+            let mut problems = Vec::new();
+
+            match (&minimum, &own) {
+                (Some((min, _)), None) => problems.push(format!(
+                    "no rust-version declared, at least {} is required",
+                    min
+                )),
+                (Some((min, min_version)), Some((own, own_version))) => {
+                    if own_version < min_version {
+                        problems.push(format!(
+                            "rust-version {} is below the configured minimum {}",
+                            own, min
+                        ));
+                    }
+                }
+                (None, _) => {
+                    if let Some((reference, users)) =
+                        Self::workspace_reference(&declared, &crate_id.workspace)
+                    {
+                        let differs = own.as_ref().is_none_or(|(_, v)| *v != reference.1);
+                        if differs {
+                            let own = own
+                                .as_ref()
+                                .map(|(s, _)| format!("rust-version {}", s))
+                                .unwrap_or_else(|| "no rust-version".to_string());
+                            problems.push(format!(
+                                "{} differs from {} used by {}",
+                                own,
+                                reference.0,
+                                users.join(", ")
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if let Some((own, own_version)) = &own {
+                for dependency in Self::production_dependencies(graph, crate_id) {
+                    if let Some(Some((dep, dep_version))) = declared.get(dependency)
+                        && dep_version > own_version
+                    {
+                        problems.push(format!(
+                            "rust-version {} is lower than {} required by dependency {}",
+                            own, dep, dependency.name
+                        ));
+                    }
+                }
+            }
+
+            let mut issues = Vec::new();
+            if !problems.is_empty() {
+                issues.push(analysis.counted_issue(
+                    severity_config,
+                    IssueType::MsrvMismatch,
+                    problems.join("; "),
+                ));
+                analysis.mismatched_crates.push(crate_id.clone());
+            }
+
+            analysis.statuses.insert(
+                crate_id.clone(),
+                MsrvStatus {
+                    crate_id: crate_id.clone(),
+                    rust_version: own.map(|(s, _)| s),
+                    issues,
+                },
+            );
+        }
+
+        analysis
+            .mismatched_crates
+            .sort_by_key(|id| id.display_name());
+        Ok(analysis)
+    }
+
+    /// check the rust-version of every crate in the graph
+    pub fn analyze_all(
+        graph: &CrateDependencyGraph,
+        severity_config: &SeverityConfig,
+        config: &MsrvConfig,
+    ) -> Result<MsrvAnalysis> {
+        let crates: Vec<CrateId> = graph.crates.keys().cloned().collect();
+        Self::analyze(
+            graph,
+            &crates,
+            &crates,
+            severity_config,
+            severity_config,
+            config,
+        )
+    }
+
+    // rust-version of every crate in the graph, inherited values resolved,
+    // and why it could not be read for the crates whose manifest failed
+    fn declared_versions(
+        graph: &CrateDependencyGraph,
+    ) -> (DeclaredVersions, HashMap<CrateId, Error>) {
+        let mut declared = HashMap::new();
+        let mut failures = HashMap::new();
+        for (crate_id, crate_info) in &graph.crates {
+            let document = match TomlReader::read_manifest_with_workspace(&crate_info.path) {
+                Ok(document) => document,
+                Err(e) => {
+                    failures.insert(crate_id.clone(), e);
+                    continue;
+                }
+            };
+            let rust_version = document
+                .get_package_field("rust-version")
+                .and_then(|v| v.as_str().map(str::to_string))
+                .and_then(|s| Some((s.clone(), Self::parse_rust_version(&s)?)));
+            declared.insert(crate_id.clone(), rust_version);
+        }
+        (declared, failures)
+    }
+
+    // most common rust-version in a workspace (the highest on ties) and the
+    // crates declaring it
+    fn workspace_reference(
+        declared: &DeclaredVersions,
+        workspace: &str,
+    ) -> Option<((String, Version), Vec<String>)> {
+        let mut users: HashMap<&Version, (&String, Vec<String>)> = HashMap::new();
+        for (crate_id, version) in declared {
+            if crate_id.workspace != workspace {
+                continue;
+            }
+            if let Some((text, version)) = version {
+                let entry = users.entry(version).or_insert_with(|| (text, Vec::new()));
+                // "1.70" and "1.70.0" count as one, show the shorter spelling
+                if text < entry.0 {
+                    entry.0 = text;
+                }
+                entry.1.push(crate_id.name.clone());
+            }
+        }
+
+        let (version, (text, mut names)) = users
+            .into_iter()
+            .max_by(|a, b| (a.1.1.len(), a.0).cmp(&(b.1.1.len(), b.0)))?;
+        names.sort();
+        Some(((text.clone(), version.clone()), names))
+    }
+
+    // workspace crates a crate needs to build, dev-dependencies excluded
+    fn production_dependencies<'a>(
+        graph: &'a CrateDependencyGraph,
+        crate_id: &CrateId,
+    ) -> Vec<&'a CrateId> {
+        let Some(&index) = graph.node_indices.get(crate_id) else {
+            return Vec::new();
+        };
+        graph
+            .graph
+            .edges_directed(index, petgraph::Direction::Outgoing)
+            .filter(|edge| *edge.weight() != DependencyType::Dev)
+            .map(|edge| &graph.graph[edge.target()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_crate(root: &std::path::Path, name: &str, rust_version: Option<&str>, deps: &[&str]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        let mut manifest = format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name);
+        if let Some(rust_version) = rust_version {
+            manifest.push_str(&format!("rust-version = \"{}\"\n", rust_version));
+        }
+        manifest.push_str("\n[dependencies]\n");
+        for dep in deps {
+            manifest.push_str(&format!("{} = {{ path = \"../{}\" }}\n", dep, dep));
+        }
+        fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+    }
+
+    fn messages(analysis: &MsrvAnalysis, name: &str) -> Vec<String> {
+        analysis
+            .statuses
+            .values()
+            .find(|s| s.crate_id.name == name)
+            .map(|s| s.issues.iter().map(|i| i.message.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_parse_rust_version() {
+        assert_eq!(
+            MsrvChecker::parse_rust_version("1.70"),
+            Some(Version::new(1, 70, 0))
+        );
+        assert_eq!(
+            MsrvChecker::parse_rust_version("1.70.1"),
+            Some(Version::new(1, 70, 1))
+        );
+        assert_eq!(MsrvChecker::parse_rust_version("1"), None);
+        assert_eq!(MsrvChecker::parse_rust_version("1.70-beta"), None);
+    }
+
+    #[test]
+    fn test_workspace_agreement_and_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"util\", \"app\", \"cli\"]\n",
+        )
+        .unwrap();
+        write_crate(root, "core", Some("1.80"), &[]);
+        write_crate(root, "util", Some("1.70"), &[]);
+        write_crate(root, "app", Some("1.70.0"), &["core", "util"]);
+        write_crate(root, "cli", None, &["util"]);
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let analysis = MsrvChecker::analyze_all(
+            &graph,
+            &SeverityConfig::default_direct(),
+            &MsrvConfig::default(),
+        )
+        .unwrap();
+
+        assert!(messages(&analysis, "util").is_empty());
+        assert_eq!(
+            messages(&analysis, "core"),
+            vec!["rust-version 1.80 differs from 1.70 used by app, util"]
+        );
+        assert_eq!(
+            messages(&analysis, "app"),
+            vec!["rust-version 1.70.0 is lower than 1.80 required by dependency core"]
+        );
+        assert_eq!(
+            messages(&analysis, "cli"),
+            vec!["no rust-version differs from 1.70 used by app, util"]
+        );
+        assert_eq!(analysis.total_errors, 3);
+        assert!(!analysis.all_consistent());
+
+        let minimum = MsrvConfig {
+            minimum: Some("1.75".to_string()),
+        };
+        let analysis =
+            MsrvChecker::analyze_all(&graph, &SeverityConfig::default_transitive(), &minimum)
+                .unwrap();
+        assert!(messages(&analysis, "core").is_empty());
+        assert_eq!(
+            messages(&analysis, "util"),
+            vec!["rust-version 1.70 is below the configured minimum 1.75"]
+        );
+        assert_eq!(
+            messages(&analysis, "cli"),
+            vec!["no rust-version declared, at least 1.75 is required"]
+        );
+        assert_eq!(analysis.total_warnings, 3);
+    }
+}
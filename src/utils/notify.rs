@@ -0,0 +1,256 @@
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// message posted when no template is configured
+pub const DEFAULT_TEMPLATE: &str = "deptrack {from}..{to}: {status}, {errors} error(s), {warnings} warning(s), {affected_count} affected crate(s){report_line}";
+
+/// affected crates listed by name before the rest is summarized as a count
+const MAX_LISTED_CRATES: usize = 10;
+
+/// outcome of a check-versions run, as sent to a webhook
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotifySummary {
+    pub from: String,
+    pub to: String,
+    pub errors: usize,
+    pub warnings: usize,
+    /// display names of the affected crates
+    pub affected_crates: Vec<String>,
+    /// link to the uploaded report, e.g. a ci artifact
+    pub report_url: Option<String>,
+}
+
+impl NotifySummary {
+    pub fn passed(&self) -> bool {
+        self.errors == 0
+    }
+}
+
+/// posts check summaries to slack or teams compatible incoming webhooks
+///
+/// the body is `{"text": "..."}`, which both accept; requests go through
+/// curl like the registry index does, with the url passed on stdin so it
+/// stays out of the process list and out of error messages
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    /// webhook url
+    pub url: String,
+    /// message template, see [`Notifier::render`]
+    pub template: String,
+    /// timeout for the request in seconds
+    pub timeout_secs: u64,
+}
+
+impl Notifier {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            timeout_secs: 30,
+        }
+    }
+
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// the message for `summary`, with `{from}`, `{to}`, `{status}` ("passed"
+    /// or "failed"), `{errors}`, `{warnings}`, `{affected}` (comma separated
+    /// names), `{affected_count}`, `{report_url}` and `{report_line}` (the
+    /// url on a new line, empty without one) replaced
+    pub fn render(&self, summary: &NotifySummary) -> String {
+        let mut affected = summary
+            .affected_crates
+            .iter()
+            .take(MAX_LISTED_CRATES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if summary.affected_crates.len() > MAX_LISTED_CRATES {
+            affected.push_str(&format!(
+                " and {} more",
+                summary.affected_crates.len() - MAX_LISTED_CRATES
+            ));
+        }
+        let report_url = summary.report_url.as_deref().unwrap_or("");
+        let report_line = if report_url.is_empty() {
+            String::new()
+        } else {
+            format!("\nreport: {}", report_url)
+        };
+        let status = if summary.passed() { "passed" } else { "failed" };
+
+        self.template
+            .replace("{from}", &summary.from)
+            .replace("{to}", &summary.to)
+            .replace("{status}", status)
+            .replace("{errors}", &summary.errors.to_string())
+            .replace("{warnings}", &summary.warnings.to_string())
+            .replace(
+                "{affected_count}",
+                &summary.affected_crates.len().to_string(),
+            )
+            .replace("{affected}", &affected)
+            .replace("{report_url}", report_url)
+            .replace("{report_line}", &report_line)
+    }
+
+    /// json body posted to the webhook
+    pub fn payload(&self, summary: &NotifySummary) -> serde_json::Value {
+        serde_json::json!({ "text": self.render(summary) })
+    }
+
+    // the url of an incoming webhook is its secret: drop it, and the path
+    // that holds the token, from curl's messages
+    fn redact(&self, message: &str) -> String {
+        let mut redacted = message.replace(&self.url, "<webhook>");
+        let after_scheme = self
+            .url
+            .split_once("://")
+            .map_or(self.url.as_str(), |(_, rest)| rest);
+        if let Some(index) = after_scheme.find('/') {
+            let path = &after_scheme[index..];
+            if path.len() > 1 {
+                redacted = redacted.replace(path, "/<redacted>");
+            }
+        }
+        redacted
+    }
+
+    /// post `summary` to the webhook
+    pub fn send(&self, summary: &NotifySummary) -> Result<()> {
+        let body = self.payload(summary).to_string();
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .arg("--max-time")
+            .arg(self.timeout_secs.to_string())
+            .args(["--header", "Content-Type: application/json"])
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::NotifyError {
+                reason: format!("failed to run curl: {}", e),
+            })?;
+
+        // the body goes into the config too, stdin is already taken by it
+        if let Some(mut stdin) = child.stdin.take() {
+            writeln!(stdin, "url = \"{}\"", config_escape(&self.url))?;
+            writeln!(stdin, "data-binary = \"{}\"", config_escape(&body))?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::NotifyError {
+                reason: format!(
+                    "failed to post to the webhook: {}",
+                    self.redact(stderr.trim())
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+// quote `value` for a double quoted curl config entry
+fn config_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> NotifySummary {
+        NotifySummary {
+            from: "main".to_string(),
+            to: "HEAD".to_string(),
+            errors: 2,
+            warnings: 1,
+            affected_crates: vec!["ws::core".to_string(), "ws::app".to_string()],
+            report_url: None,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let notifier = Notifier::new("https://hooks.example.com/x");
+        assert_eq!(
+            notifier.render(&summary()),
+            "deptrack main..HEAD: failed, 2 error(s), 1 warning(s), 2 affected crate(s)"
+        );
+
+        let mut linked = summary();
+        linked.errors = 0;
+        linked.report_url = Some("https://ci.example.com/42".to_string());
+        assert_eq!(
+            notifier.render(&linked),
+            "deptrack main..HEAD: passed, 0 error(s), 1 warning(s), 2 affected crate(s)\nreport: https://ci.example.com/42"
+        );
+
+        let custom = notifier.template(":rotating_light: {status} ({affected}) <{report_url}>");
+        assert_eq!(
+            custom.payload(&summary()),
+            serde_json::json!({ "text": ":rotating_light: failed (ws::core, ws::app) <>" })
+        );
+    }
+
+    #[test]
+    fn test_render_truncates_affected() {
+        let mut many = summary();
+        many.affected_crates = (0..12).map(|i| format!("c{}", i)).collect();
+        let rendered = Notifier::new("x").template("{affected}").render(&many);
+        assert!(rendered.starts_with("c0, c1,"));
+        assert!(rendered.ends_with("c9 and 2 more"));
+    }
+
+    #[test]
+    fn test_send_to_file_url() {
+        // a target curl cannot reach surfaces as an error, without the url
+        let result = Notifier::new("file:///nonexistent/deptrack/T0123/secret")
+            .timeout_secs(5)
+            .send(&summary());
+        let Err(Error::NotifyError { reason }) = result else {
+            panic!("expected a notify error, got {:?}", result);
+        };
+        assert!(reason.starts_with("failed to post to the webhook"));
+        assert!(!reason.contains("secret"), "{}", reason);
+    }
+
+    #[test]
+    fn test_redact() {
+        let notifier = Notifier::new("https://hooks.example.com/services/T0/B1/secret");
+        assert_eq!(
+            notifier.redact("curl: (22) https://hooks.example.com/services/T0/B1/secret: 404"),
+            "curl: (22) <webhook>: 404"
+        );
+        assert_eq!(
+            notifier.redact("POST /services/T0/B1/secret failed"),
+            "POST /<redacted> failed"
+        );
+        assert_eq!(
+            notifier.redact("Could not resolve host: hooks.example.com"),
+            "Could not resolve host: hooks.example.com"
+        );
+    }
+}
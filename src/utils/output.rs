@@ -0,0 +1,249 @@
+// yaml, toml and json lines renderings of the json reports printed by the cli
+
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+
+/// key holding a report that is not a table at the top, toml documents have to be one
+pub const TOML_ROOT_KEY: &str = "items";
+
+/// `value` as a block style yaml document, without a trailing newline
+pub fn to_yaml(value: &Value) -> String {
+    yaml_lines(value).join("\n")
+}
+
+/// `value` as a toml document, without a trailing newline
+///
+/// toml has no null, so null values are left out; a report that is not an
+/// object is put under [`TOML_ROOT_KEY`]
+pub fn to_toml(value: &Value) -> Result<String, toml::ser::Error> {
+    let root = match toml_value(value) {
+        Some(toml::Value::Table(table)) => table,
+        Some(other) => toml::Table::from_iter([(TOML_ROOT_KEY.to_string(), other)]),
+        None => toml::Table::new(),
+    };
+    Ok(toml::to_string_pretty(&root)?.trim_end().to_string())
+}
+
+/// `value` as json lines: one compact line per item of a list, a single line
+/// for anything else, without a trailing newline
+pub fn to_jsonl(value: &Value) -> String {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+/// writes json lines records as a report is produced, flushing after each one
+/// so consumers can start before the report is complete
+///
+/// every record is an object whose `record` key names its kind; values that
+/// are not objects are put under `value`
+pub struct JsonlWriter<W: Write> {
+    out: W,
+}
+
+impl JsonlWriter<std::io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(std::io::stdout())
+    }
+}
+
+impl<W: Write> JsonlWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// write `value` as a record of `kind`
+    pub fn record<T: Serialize>(&mut self, kind: &str, value: &T) -> std::io::Result<()> {
+        let mut record = serde_json::Map::new();
+        record.insert("record".to_string(), Value::from(kind));
+        match serde_json::to_value(value)? {
+            Value::Object(fields) => record.extend(fields),
+            other => {
+                record.insert("value".to_string(), other);
+            }
+        }
+        writeln!(self.out, "{}", Value::Object(record))?;
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+fn toml_value(value: &Value) -> Option<toml::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None => toml::Value::Float(n.as_f64()?),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(items) => toml::Value::Array(items.iter().filter_map(toml_value).collect()),
+        Value::Object(map) => toml::Value::Table(
+            map.iter()
+                .filter_map(|(k, v)| Some((k.clone(), toml_value(v)?)))
+                .collect(),
+        ),
+    })
+}
+
+// non-empty objects and arrays become blocks, everything else fits on the line
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_lines(value: &Value) -> Vec<String> {
+    let mut lines = Vec::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                if is_block(value) {
+                    lines.push(format!("{}:", yaml_string(key)));
+                    lines.extend(yaml_lines(value).into_iter().map(|l| format!("  {}", l)));
+                } else {
+                    lines.push(format!("{}: {}", yaml_string(key), yaml_inline(value)));
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                for (i, line) in yaml_lines(item).into_iter().enumerate() {
+                    let prefix = if i == 0 { "- " } else { "  " };
+                    lines.push(format!("{}{}", prefix, line));
+                }
+            }
+        }
+        _ => lines.push(yaml_inline(value)),
+    }
+    lines
+}
+
+fn yaml_inline(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => yaml_string(s),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+// plain when it cannot be read as anything but this string, double quoted
+// (json escaping is valid yaml) otherwise
+fn yaml_string(s: &str) -> String {
+    const RESERVED: [&str; 11] = [
+        "true", "false", "yes", "no", "on", "off", "null", "y", "n", "~", "",
+    ];
+    let plain = s
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '/')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || " _./:-@+".contains(c))
+        && !s.contains(": ")
+        && !s.ends_with([':', ' '])
+        && !RESERVED.contains(&s.to_ascii_lowercase().as_str());
+    if plain {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report() -> Value {
+        json!({
+            "from": "main",
+            "changed_files": 3,
+            "all_bumped": false,
+            "ignore_filter": null,
+            "manifest": {
+                "incomplete_crates": ["ws::core", "ws::app"],
+                "total_errors": 1,
+            },
+            "bumps": [
+                { "crate": "core", "next_version": "0.2.0", "level": null },
+                { "crate": "yes", "next_version": "1.0.0", "level": "major" },
+            ],
+            "empty": [],
+            "message": "version not bumped (current: 0.1.0, base: 0.1.0)",
+        })
+    }
+
+    #[test]
+    fn test_to_yaml() {
+        assert_eq!(
+            to_yaml(&report()),
+            "all_bumped: false\n\
+             bumps:\n\
+             \x20 - crate: core\n\
+             \x20   level: null\n\
+             \x20   next_version: \"0.2.0\"\n\
+             \x20 - crate: \"yes\"\n\
+             \x20   level: major\n\
+             \x20   next_version: \"1.0.0\"\n\
+             changed_files: 3\n\
+             empty: []\n\
+             from: main\n\
+             ignore_filter: null\n\
+             manifest:\n\
+             \x20 incomplete_crates:\n\
+             \x20   - ws::core\n\
+             \x20   - ws::app\n\
+             \x20 total_errors: 1\n\
+             message: \"version not bumped (current: 0.1.0, base: 0.1.0)\""
+        );
+        assert_eq!(to_yaml(&json!(["a", 1])), "- a\n- 1");
+        assert_eq!(to_yaml(&json!({})), "{}");
+    }
+
+    #[test]
+    fn test_to_toml() {
+        let rendered = to_toml(&report()).unwrap();
+        let parsed: toml::Table = rendered.parse().unwrap();
+        assert!(!parsed.contains_key("ignore_filter"));
+        assert_eq!(parsed["manifest"]["total_errors"].as_integer(), Some(1));
+        assert_eq!(parsed["bumps"][1]["level"].as_str(), Some("major"));
+        assert!(parsed["bumps"][0].get("level").is_none());
+
+        let list: toml::Table = to_toml(&json!(["main", "feature"]))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(list[TOML_ROOT_KEY].as_array().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn test_jsonl() {
+        assert_eq!(
+            to_jsonl(&json!([{ "crate": "core" }, 2])),
+            "{\"crate\":\"core\"}\n2"
+        );
+        assert_eq!(to_jsonl(&json!({ "a": [1, 2] })), "{\"a\":[1,2]}");
+
+        let mut writer = JsonlWriter::new(Vec::new());
+        writer.record("crate", &json!({ "crate": "core" })).unwrap();
+        writer.record("count", &3).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "{\"crate\":\"core\",\"record\":\"crate\"}\n{\"record\":\"count\",\"value\":3}\n"
+        );
+    }
+}
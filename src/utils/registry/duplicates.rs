@@ -0,0 +1,301 @@
+use super::lockfile::LockedPackage;
+use crate::error::Result;
+use crate::utils::cargo_ops::CrateDependencyGraph;
+use semver::Version;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+const MIN_VERSION_WIDTH: usize = 12;
+
+/// one resolved version of a duplicated dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateVersion {
+    pub version: Version,
+    /// source from Cargo.lock, e.g. the crates.io index or a git url
+    pub source: Option<String>,
+    /// workspace members depending on this version, directly or transitively
+    pub pulled_by: Vec<String>,
+}
+
+/// an external crate resolved to more than one version in a workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateDependency {
+    pub workspace: String,
+    pub name: String,
+    /// versions sorted from oldest to newest
+    pub versions: Vec<DuplicateVersion>,
+}
+
+/// duplicated external dependencies across workspaces
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuplicateReport {
+    /// duplicates sorted by workspace and name
+    pub duplicates: Vec<DuplicateDependency>,
+    /// workspaces without a Cargo.lock, which could not be checked
+    pub missing_lockfiles: Vec<String>,
+}
+
+impl DuplicateReport {
+    /// check if no external crate is resolved to several versions
+    pub fn is_empty(&self) -> bool {
+        self.duplicates.is_empty()
+    }
+
+    /// display every duplicated crate with the members pulling each version
+    pub fn display(&self) {
+        for duplicate in &self.duplicates {
+            println!("{} ({}):", duplicate.name, duplicate.workspace);
+            for version in &duplicate.versions {
+                let pulled_by = if version.pulled_by.is_empty() {
+                    "no workspace member".to_string()
+                } else {
+                    version.pulled_by.join(", ")
+                };
+                println!(
+                    "  {:<version_width$}  pulled by {}",
+                    version.version.to_string(),
+                    pulled_by,
+                    version_width = MIN_VERSION_WIDTH
+                );
+            }
+            println!();
+        }
+
+        println!(
+            "{} external dependencies resolved to more than one version",
+            self.duplicates.len()
+        );
+        if !self.missing_lockfiles.is_empty() {
+            println!("no Cargo.lock in: {}", self.missing_lockfiles.join(", "));
+        }
+    }
+}
+
+/// finds external crates resolved to several versions in Cargo.lock
+pub struct DuplicateChecker;
+
+impl DuplicateChecker {
+    /// check the Cargo.lock at the root of every workspace in the graph
+    pub fn analyze(graph: &CrateDependencyGraph) -> Result<DuplicateReport> {
+        let mut report = DuplicateReport::default();
+
+        let mut workspaces: Vec<_> = graph.workspaces.iter().collect();
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+        for workspace in workspaces {
+            let lockfile = workspace.root_path.join("Cargo.lock");
+            if !lockfile.is_file() {
+                report.missing_lockfiles.push(workspace.name.clone());
+                continue;
+            }
+
+            let members: Vec<String> = graph
+                .crates
+                .keys()
+                .filter(|id| id.workspace == workspace.name)
+                .map(|id| id.name.clone())
+                .collect();
+            report
+                .duplicates
+                .extend(Self::from_lockfile(&lockfile, &workspace.name, &members)?);
+        }
+
+        Ok(report)
+    }
+
+    /// duplicated crates in a single Cargo.lock, `members` are the names of
+    /// the workspace crates whose dependencies are followed
+    pub fn from_lockfile<P: AsRef<Path>>(
+        path: P,
+        workspace: &str,
+        members: &[String],
+    ) -> Result<Vec<DuplicateDependency>> {
+        let packages = LockedPackage::read_all(path)?;
+
+        // external packages grouped by name, keeping only the ambiguous ones
+        let mut by_name: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (index, package) in packages.iter().enumerate() {
+            if package.source.is_some() {
+                by_name
+                    .entry(package.name.as_str())
+                    .or_default()
+                    .push(index);
+            }
+        }
+        by_name.retain(|_, indices| indices.len() > 1);
+        if by_name.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut member_names: Vec<&String> = members.iter().collect();
+        member_names.sort();
+        let reachable: Vec<(&String, HashSet<usize>)> = member_names
+            .into_iter()
+            .filter_map(|member| {
+                let start = packages
+                    .iter()
+                    .position(|p| p.source.is_none() && &p.name == member)?;
+                Some((member, Self::reachable_from(&packages, start)))
+            })
+            .collect();
+
+        Ok(by_name
+            .into_iter()
+            .map(|(name, mut indices)| {
+                indices.sort_by(|a, b| packages[*a].version.cmp(&packages[*b].version));
+                let versions = indices
+                    .into_iter()
+                    .map(|index| DuplicateVersion {
+                        version: packages[index].version.clone(),
+                        source: packages[index].source.clone(),
+                        pulled_by: reachable
+                            .iter()
+                            .filter(|(_, reached)| reached.contains(&index))
+                            .map(|(member, _)| (*member).clone())
+                            .collect(),
+                    })
+                    .collect();
+                DuplicateDependency {
+                    workspace: workspace.to_string(),
+                    name: name.to_string(),
+                    versions,
+                }
+            })
+            .collect())
+    }
+
+    // indices of every package `start` depends on, directly or transitively
+    fn reachable_from(packages: &[LockedPackage], start: usize) -> HashSet<usize> {
+        let mut reached = HashSet::new();
+        let mut pending = vec![start];
+        while let Some(current) = pending.pop() {
+            for reference in &packages[current].dependencies {
+                let target = packages.iter().position(|p| p.matches_reference(reference));
+                if let Some(target) = target
+                    && reached.insert(target)
+                {
+                    pending.push(target);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const LOCKFILE: &str = r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "core",
+ "syn 2.0.50",
+]
+
+[[package]]
+name = "core"
+version = "0.1.0"
+dependencies = [
+ "derive",
+ "log",
+]
+
+[[package]]
+name = "cli"
+version = "0.1.0"
+dependencies = [
+ "syn 2.0.50",
+]
+
+[[package]]
+name = "derive"
+version = "0.3.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "syn 1.0.109",
+]
+
+[[package]]
+name = "log"
+version = "0.4.22"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.50"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[test]
+    fn test_from_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Cargo.lock");
+        fs::write(&path, LOCKFILE).unwrap();
+
+        let members = vec!["app".to_string(), "cli".to_string(), "core".to_string()];
+        let duplicates = DuplicateChecker::from_lockfile(&path, "ws", &members).unwrap();
+
+        assert_eq!(duplicates.len(), 1);
+        let syn = &duplicates[0];
+        assert_eq!(syn.name, "syn");
+        assert_eq!(syn.workspace, "ws");
+        let versions: Vec<(String, Vec<String>)> = syn
+            .versions
+            .iter()
+            .map(|v| (v.version.to_string(), v.pulled_by.clone()))
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                (
+                    "1.0.109".to_string(),
+                    vec!["app".to_string(), "core".to_string()]
+                ),
+                (
+                    "2.0.50".to_string(),
+                    vec!["app".to_string(), "cli".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_reports_missing_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let report = DuplicateChecker::analyze(&graph).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(report.missing_lockfiles.len(), 1);
+
+        fs::write(root.join("Cargo.lock"), LOCKFILE).unwrap();
+        let report = DuplicateChecker::analyze(&graph).unwrap();
+        assert!(report.missing_lockfiles.is_empty());
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].versions[1].pulled_by, vec!["app"]);
+    }
+}
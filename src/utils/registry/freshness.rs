@@ -0,0 +1,458 @@
+use super::index::VersionSource;
+use super::lockfile::LockedPackage;
+use crate::error::Result;
+use crate::utils::cargo_ops::{CrateDependencyGraph, CrateId, DependencyType};
+use crate::utils::release::BumpLevel;
+use crate::utils::toml_ops::{TomlDocument, TomlReader, lowest_accepted};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+const MIN_NAME_WIDTH: usize = 10;
+const MIN_VERSION_WIDTH: usize = 12;
+
+/// how an external dependency compares to the latest published release
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyFreshness {
+    /// crate name on the registry (the `package` key for renamed dependencies)
+    pub name: String,
+    /// version requirement from Cargo.toml
+    pub requirement: String,
+    pub kind: DependencyType,
+    /// locked version, or the lowest version the requirement accepts
+    pub current: Option<Version>,
+    /// newest published version, stable releases preferred
+    pub latest: Option<Version>,
+    /// how far `current` trails `latest`, None when up to date or unknown
+    pub behind: Option<BumpLevel>,
+    /// the requirement already accepts `latest`, a `cargo update` is enough
+    pub compatible: bool,
+}
+
+impl DependencyFreshness {
+    /// check if the dependency trails the latest release by at least `level`
+    pub fn is_behind(&self, level: BumpLevel) -> bool {
+        self.behind.is_some_and(|behind| behind >= level)
+    }
+}
+
+/// external dependencies of one workspace crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateFreshness {
+    pub crate_id: CrateId,
+    pub dependencies: Vec<DependencyFreshness>,
+}
+
+/// freshness of external dependencies across crates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FreshnessReport {
+    /// crates sorted by name, with their external dependencies
+    pub crates: Vec<CrateFreshness>,
+    /// dependencies the index does not know about
+    pub unknown: Vec<String>,
+}
+
+impl FreshnessReport {
+    /// number of dependency entries trailing by at least `level`
+    pub fn outdated_count(&self, level: BumpLevel) -> usize {
+        self.crates
+            .iter()
+            .flat_map(|c| &c.dependencies)
+            .filter(|d| d.is_behind(level))
+            .count()
+    }
+
+    /// number of external dependency entries checked
+    pub fn dependency_count(&self) -> usize {
+        self.crates.iter().map(|c| c.dependencies.len()).sum()
+    }
+
+    /// display dependencies trailing by at least `level`, grouped per crate
+    pub fn display(&self, level: BumpLevel) {
+        for crate_freshness in &self.crates {
+            let outdated: Vec<&DependencyFreshness> = crate_freshness
+                .dependencies
+                .iter()
+                .filter(|d| d.is_behind(level))
+                .collect();
+            if outdated.is_empty() {
+                continue;
+            }
+
+            let name_width = outdated
+                .iter()
+                .map(|d| d.name.len())
+                .max()
+                .unwrap_or(MIN_NAME_WIDTH)
+                .max(MIN_NAME_WIDTH);
+
+            println!("{}:", crate_freshness.crate_id.display_name());
+            println!(
+                "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {:<version_width$}  Behind",
+                "Dependency",
+                "Requirement",
+                "Current",
+                "Latest",
+                name_width = name_width,
+                version_width = MIN_VERSION_WIDTH
+            );
+            println!(
+                "  {}  {}  {}  {}  ------",
+                "-".repeat(name_width),
+                "-".repeat(MIN_VERSION_WIDTH),
+                "-".repeat(MIN_VERSION_WIDTH),
+                "-".repeat(MIN_VERSION_WIDTH)
+            );
+            for dependency in outdated {
+                let show = |v: &Option<Version>| v.as_ref().map(Version::to_string);
+                let behind = match dependency.behind {
+                    Some(behind) if dependency.compatible => format!("{} (compatible)", behind),
+                    Some(behind) => behind.to_string(),
+                    None => String::new(),
+                };
+                println!(
+                    "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {:<version_width$}  {}",
+                    dependency.name,
+                    dependency.requirement,
+                    show(&dependency.current).unwrap_or_else(|| "?".to_string()),
+                    show(&dependency.latest).unwrap_or_else(|| "?".to_string()),
+                    behind,
+                    name_width = name_width,
+                    version_width = MIN_VERSION_WIDTH
+                );
+            }
+            println!();
+        }
+
+        println!(
+            "{} of {} external dependencies are at least a {} release behind",
+            self.outdated_count(level),
+            self.dependency_count(),
+            level
+        );
+        if !self.unknown.is_empty() {
+            println!("not found in the index: {}", self.unknown.join(", "));
+        }
+    }
+}
+
+/// compares external dependencies of workspace crates with a registry index
+pub struct FreshnessChecker;
+
+impl FreshnessChecker {
+    /// registry dependencies declared in a manifest as (name, requirement, kind);
+    /// path, git and alternate registry dependencies are left out
+    pub fn external_dependencies(document: &TomlDocument) -> Vec<(String, String, DependencyType)> {
+        let tables = [
+            (document.get_dependencies(), DependencyType::Normal),
+            (document.get_dev_dependencies(), DependencyType::Dev),
+            (document.get_build_dependencies(), DependencyType::Build),
+        ];
+
+        let mut dependencies = Vec::new();
+        for (table, kind) in tables {
+            for (key, value) in table.into_iter().flatten() {
+                let (name, requirement) = match &value {
+                    serde_json::Value::String(requirement) => (key, requirement.clone()),
+                    serde_json::Value::Object(dep) => {
+                        let local = ["path", "git", "registry", "workspace"]
+                            .iter()
+                            .any(|k| dep.contains_key(*k));
+                        if local {
+                            continue;
+                        }
+                        let name = dep
+                            .get("package")
+                            .and_then(|p| p.as_str())
+                            .map(str::to_string)
+                            .unwrap_or(key);
+                        let requirement = dep
+                            .get("version")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("*")
+                            .to_string();
+                        (name, requirement)
+                    }
+                    _ => continue,
+                };
+                dependencies.push((name, requirement, kind));
+            }
+        }
+
+        dependencies.sort_by(|a, b| (&a.0, kind_order(a.2)).cmp(&(&b.0, kind_order(b.2))));
+        dependencies
+    }
+
+    /// crates.io packages and their versions from a Cargo.lock
+    pub fn read_lockfile<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Vec<Version>>> {
+        let mut locked: HashMap<String, Vec<Version>> = HashMap::new();
+        for package in LockedPackage::read_all(path)? {
+            if package.is_from_crates_io() {
+                locked
+                    .entry(package.name)
+                    .or_default()
+                    .push(package.version);
+            }
+        }
+        Ok(locked)
+    }
+
+    /// check the external dependencies of every crate in the graph against
+    /// `source`, each dependency is looked up once
+    ///
+    /// with `use_lockfiles` the versions in each workspace's Cargo.lock are
+    /// compared, otherwise the lowest version a requirement accepts
+    pub fn analyze(
+        graph: &CrateDependencyGraph,
+        source: &dyn VersionSource,
+        use_lockfiles: bool,
+    ) -> Result<FreshnessReport> {
+        let mut report = FreshnessReport::default();
+        let mut latest_versions: HashMap<String, Option<Version>> = HashMap::new();
+        let mut lockfiles: HashMap<&str, HashMap<String, Vec<Version>>> = HashMap::new();
+
+        let mut crate_ids: Vec<&CrateId> = graph.crates.keys().collect();
+        crate_ids.sort_by_key(|id| id.display_name());
+
+        for crate_id in crate_ids {
+            let crate_info = &graph.crates[crate_id];
+            let document = TomlReader::read_manifest_with_workspace(&crate_info.path)?;
+
+            if use_lockfiles && !lockfiles.contains_key(crate_id.workspace.as_str()) {
+                let lockfile = graph
+                    .workspaces
+                    .iter()
+                    .find(|w| w.name == crate_id.workspace)
+                    .map(|w| w.root_path.join("Cargo.lock"))
+                    .filter(|path| path.is_file());
+                let locked = match lockfile {
+                    Some(path) => Self::read_lockfile(path)?,
+                    None => HashMap::new(),
+                };
+                lockfiles.insert(crate_id.workspace.as_str(), locked);
+            }
+            let locked = lockfiles.get(crate_id.workspace.as_str());
+
+            let mut dependencies = Vec::new();
+            for (name, requirement, kind) in Self::external_dependencies(&document) {
+                let latest = match latest_versions.get(&name) {
+                    Some(latest) => latest.clone(),
+                    None => {
+                        let latest = newest(source.published_versions(&name)?);
+                        if latest.is_none() {
+                            report.unknown.push(name.clone());
+                        }
+                        latest_versions.insert(name.clone(), latest.clone());
+                        latest
+                    }
+                };
+
+                let req = VersionReq::parse(&requirement).ok();
+                let current = locked
+                    .and_then(|locked| locked.get(&name))
+                    .and_then(|versions| {
+                        versions
+                            .iter()
+                            .filter(|v| req.as_ref().is_none_or(|req| req.matches(v)))
+                            .max()
+                            .cloned()
+                    })
+                    .or_else(|| lowest_accepted(&requirement));
+
+                let behind = match (&current, &latest) {
+                    (Some(current), Some(latest)) => behind(current, latest),
+                    _ => None,
+                };
+                let compatible = match (&req, &latest) {
+                    (Some(req), Some(latest)) => req.matches(latest),
+                    _ => false,
+                };
+
+                dependencies.push(DependencyFreshness {
+                    name,
+                    requirement,
+                    kind,
+                    current,
+                    latest,
+                    behind,
+                    compatible,
+                });
+            }
+
+            if !dependencies.is_empty() {
+                report.crates.push(CrateFreshness {
+                    crate_id: crate_id.clone(),
+                    dependencies,
+                });
+            }
+        }
+
+        report.unknown.sort();
+        Ok(report)
+    }
+}
+
+// newest stable release, or the newest pre-release when nothing is stable
+fn newest(versions: Vec<Version>) -> Option<Version> {
+    let stable = versions.iter().filter(|v| v.pre.is_empty()).max().cloned();
+    stable.or_else(|| versions.into_iter().max())
+}
+
+// how far `current` trails `latest`, by the first differing component
+fn behind(current: &Version, latest: &Version) -> Option<BumpLevel> {
+    if latest <= current {
+        None
+    } else if latest.major != current.major {
+        Some(BumpLevel::Major)
+    } else if latest.minor != current.minor {
+        Some(BumpLevel::Minor)
+    } else {
+        Some(BumpLevel::Patch)
+    }
+}
+
+fn kind_order(kind: DependencyType) -> u8 {
+    match kind {
+        DependencyType::Normal => 0,
+        DependencyType::Build => 1,
+        DependencyType::Dev => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    struct StaticSource(HashMap<&'static str, Vec<&'static str>>);
+
+    impl VersionSource for StaticSource {
+        fn published_versions(&self, name: &str) -> Result<Vec<Version>> {
+            Ok(self
+                .0
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|v| Version::parse(v).unwrap())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_behind() {
+        let v = |s| Version::parse(s).unwrap();
+        assert_eq!(behind(&v("1.0.0"), &v("2.1.0")), Some(BumpLevel::Major));
+        assert_eq!(behind(&v("0.3.9"), &v("0.4.0")), Some(BumpLevel::Minor));
+        assert_eq!(behind(&v("1.2.3"), &v("1.2.4")), Some(BumpLevel::Patch));
+        assert_eq!(behind(&v("1.2.3"), &v("1.2.3")), None);
+        assert_eq!(
+            newest(vec![v("1.0.0"), v("2.0.0-rc.1"), v("1.5.0")]),
+            Some(v("1.5.0"))
+        );
+    }
+
+    #[test]
+    fn test_analyze_with_lockfile() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\"]\n\n[workspace.dependencies]\nlog = \"0.4\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("core/Cargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/Cargo.toml"),
+            r#"
+            [package]
+            name = "app"
+            version = "0.1.0"
+
+            [dependencies]
+            core = { path = "../core" }
+            json = { package = "serde_json", version = "1.0" }
+            log.workspace = true
+            private = { version = "1", registry = "internal" }
+            ghost = "0.1"
+
+            [dev-dependencies]
+            tempfile = "2"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.lock"),
+            r#"
+            version = 3
+
+            [[package]]
+            name = "serde_json"
+            version = "1.0.90"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "core"
+            version = "0.1.0"
+            "#,
+        )
+        .unwrap();
+
+        let graph = CrateDependencyGraph::build_from_repository(root).unwrap();
+        let source = StaticSource(HashMap::from([
+            ("serde_json", vec!["1.0.90", "1.0.120"]),
+            ("log", vec!["0.4.0", "0.4.22"]),
+            ("tempfile", vec!["2.0.0", "3.10.1", "4.0.0-beta.1"]),
+        ]));
+
+        let report = FreshnessChecker::analyze(&graph, &source, true).unwrap();
+        assert_eq!(report.crates.len(), 1);
+        assert_eq!(report.unknown, vec!["ghost"]);
+
+        let deps: Vec<(&str, Option<String>, Option<BumpLevel>, bool)> = report.crates[0]
+            .dependencies
+            .iter()
+            .map(|d| {
+                (
+                    d.name.as_str(),
+                    d.current.as_ref().map(Version::to_string),
+                    d.behind,
+                    d.compatible,
+                )
+            })
+            .collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("ghost", Some("0.1.0".to_string()), None, false),
+                (
+                    "log",
+                    Some("0.4.0".to_string()),
+                    Some(BumpLevel::Patch),
+                    true
+                ),
+                (
+                    "serde_json",
+                    Some("1.0.90".to_string()),
+                    Some(BumpLevel::Patch),
+                    true
+                ),
+                (
+                    "tempfile",
+                    Some("2.0.0".to_string()),
+                    Some(BumpLevel::Major),
+                    false
+                ),
+            ]
+        );
+        assert_eq!(report.outdated_count(BumpLevel::Patch), 3);
+        assert_eq!(report.outdated_count(BumpLevel::Minor), 1);
+    }
+}
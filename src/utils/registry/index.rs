@@ -0,0 +1,208 @@
+use crate::error::{Error, Result};
+use semver::Version;
+use std::process::Command;
+
+/// sparse index of crates.io
+pub const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+/// http statuses meaning the index has no file for the crate; any other
+/// failure, say a 403 or 429, is an error rather than an unpublished crate
+const NOT_FOUND_STATUSES: [&str; 2] = ["404", "410"];
+
+/// curl exit code for a file:// path that does not exist
+const FILE_NOT_FOUND_EXIT_CODE: i32 = 37;
+
+/// source of the versions published for a crate
+pub trait VersionSource {
+    /// published, non-yanked versions of `name`, empty when the crate is unknown
+    fn published_versions(&self, name: &str) -> Result<Vec<Version>>;
+}
+
+/// client for a cargo sparse registry index
+///
+/// entries are fetched with curl, so `file://` urls work for mirrors on disk
+#[derive(Debug, Clone)]
+pub struct SparseIndex {
+    /// base url of the index, without a trailing slash
+    pub url: String,
+    /// timeout for a single request in seconds
+    pub timeout_secs: u64,
+}
+
+impl SparseIndex {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            timeout_secs: 30,
+        }
+    }
+
+    /// index of crates.io
+    pub fn crates_io() -> Self {
+        Self::new(CRATES_IO_INDEX)
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// path of a crate's file inside the index, e.g. "se/rd/serde" or "3/s/syn"
+    pub fn index_path(name: &str) -> String {
+        let name = name.to_lowercase();
+        match name.len() {
+            1 => format!("1/{}", name),
+            2 => format!("2/{}", name),
+            3 => format!("3/{}/{}", &name[..1], name),
+            _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+        }
+    }
+
+    /// versions listed in an index file, one json object per line;
+    /// yanked releases and lines that do not parse are skipped
+    pub fn parse_entries(content: &str) -> Vec<Version> {
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|entry| !entry["yanked"].as_bool().unwrap_or(false))
+            .filter_map(|entry| Version::parse(entry["vers"].as_str()?).ok())
+            .collect()
+    }
+}
+
+impl VersionSource for SparseIndex {
+    fn published_versions(&self, name: &str) -> Result<Vec<Version>> {
+        let url = format!("{}/{}", self.url, Self::index_path(name));
+        // the status follows the body on a line of its own
+        let output = Command::new("curl")
+            .args(["--silent", "--show-error", "--location"])
+            .args(["--write-out", "\n%{http_code}"])
+            .arg("--max-time")
+            .arg(self.timeout_secs.to_string())
+            .arg(&url)
+            .output()
+            .map_err(|e| Error::RegistryError {
+                reason: format!("failed to run curl: {}", e),
+            })?;
+
+        if !output.status.success() {
+            if output.status.code() == Some(FILE_NOT_FOUND_EXIT_CODE) {
+                return Ok(Vec::new());
+            }
+            return Err(Error::RegistryError {
+                reason: format!(
+                    "failed to fetch {}: {}",
+                    url,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+        // file:// urls have no status and report 000
+        match status.trim() {
+            "000" => {}
+            status if status.starts_with('2') => {}
+            status if NOT_FOUND_STATUSES.contains(&status) => return Ok(Vec::new()),
+            status => {
+                return Err(Error::RegistryError {
+                    reason: format!("failed to fetch {}: http status {}", url, status),
+                });
+            }
+        }
+
+        Ok(Self::parse_entries(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SERDE_ENTRIES: &str = concat!(
+        r#"{"name":"serde","vers":"1.0.100","deps":[],"yanked":false}"#,
+        "\n",
+        r#"{"name":"serde","vers":"1.0.101","deps":[],"yanked":true}"#,
+        "\n",
+        "not json\n",
+        r#"{"name":"serde","vers":"1.1.0-rc.1","deps":[],"yanked":false}"#,
+        "\n",
+    );
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(SparseIndex::index_path("a"), "1/a");
+        assert_eq!(SparseIndex::index_path("cc"), "2/cc");
+        assert_eq!(SparseIndex::index_path("syn"), "3/s/syn");
+        assert_eq!(SparseIndex::index_path("Serde_JSON"), "se/rd/serde_json");
+    }
+
+    #[test]
+    fn test_published_versions_from_file_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let entry_dir = temp_dir.path().join("se/rd");
+        fs::create_dir_all(&entry_dir).unwrap();
+        fs::write(entry_dir.join("serde"), SERDE_ENTRIES).unwrap();
+
+        let index = SparseIndex::new(format!("file://{}/", temp_dir.path().display()));
+        assert_eq!(
+            index.published_versions("serde").unwrap(),
+            vec![
+                Version::parse("1.0.100").unwrap(),
+                Version::parse("1.1.0-rc.1").unwrap()
+            ]
+        );
+        assert!(index.published_versions("missing").unwrap().is_empty());
+    }
+
+    // answers each request on a local port with the next of `responses`
+    fn serve(responses: Vec<(u16, &'static str)>) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_published_versions_http_statuses() {
+        let url = serve(vec![
+            (200, SERDE_ENTRIES),
+            (404, "not found"),
+            (410, "gone"),
+            (403, "forbidden"),
+            (429, "slow down"),
+            (500, "oops"),
+        ]);
+        let index = SparseIndex::new(url);
+        assert_eq!(index.published_versions("serde").unwrap().len(), 2);
+        assert!(index.published_versions("missing").unwrap().is_empty());
+        assert!(index.published_versions("removed").unwrap().is_empty());
+        // only a missing file means the crate is unknown
+        for status in ["403", "429", "500"] {
+            let error = index.published_versions("serde").unwrap_err().to_string();
+            assert!(
+                error.contains(&format!("http status {}", status)),
+                "{}",
+                error
+            );
+        }
+    }
+}
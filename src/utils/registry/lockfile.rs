@@ -0,0 +1,111 @@
+use crate::error::{Error, Result};
+use crate::utils::toml_ops::TomlReader;
+use semver::Version;
+use serde::Serialize;
+use std::path::Path;
+
+/// a `[[package]]` entry of a Cargo.lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+    /// where the package comes from, None for path crates
+    pub source: Option<String>,
+    /// dependency references as written: "name", "name version" or
+    /// "name version (source)"
+    pub dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// every package in a Cargo.lock, entries without a valid version are skipped
+    pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<LockedPackage>> {
+        let path = path.as_ref();
+        let document = TomlReader::read_file(path).map_err(|e| Error::FileReadError {
+            path: path.to_path_buf(),
+            source: std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to read Cargo.lock: {}", e),
+            ),
+        })?;
+
+        let packages = document
+            .get_path("package")
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default();
+        Ok(packages
+            .iter()
+            .filter_map(|package| {
+                let as_str = |key: &str| package.get(key).and_then(|v| v.as_str());
+                Some(LockedPackage {
+                    name: as_str("name")?.to_string(),
+                    version: Version::parse(as_str("version")?).ok()?,
+                    source: as_str("source").map(str::to_string),
+                    dependencies: package
+                        .get("dependencies")
+                        .and_then(|d| d.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|d| d.as_str().map(str::to_string))
+                        .collect(),
+                })
+            })
+            .collect())
+    }
+
+    /// check if the package was resolved from crates.io
+    pub fn is_from_crates_io(&self) -> bool {
+        self.source
+            .as_ref()
+            .is_some_and(|s| s.contains("crates.io"))
+    }
+
+    /// check if a dependency reference of another package points at this one
+    pub fn matches_reference(&self, reference: &str) -> bool {
+        let mut parts = reference.splitn(3, ' ');
+        if parts.next() != Some(self.name.as_str()) {
+            return false;
+        }
+        if let Some(version) = parts.next()
+            && Version::parse(version).ok().as_ref() != Some(&self.version)
+        {
+            return false;
+        }
+        match parts.next() {
+            Some(source) => {
+                let source = source.trim_start_matches('(').trim_end_matches(')');
+                self.source.as_deref() == Some(source)
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, source: Option<&str>) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: Version::parse(version).unwrap(),
+            source: source.map(str::to_string),
+            dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let registry = "registry+https://github.com/rust-lang/crates.io-index";
+        let syn = package("syn", "2.0.50", Some(registry));
+
+        assert!(syn.matches_reference("syn"));
+        assert!(syn.matches_reference("syn 2.0.50"));
+        assert!(syn.matches_reference(&format!("syn 2.0.50 ({})", registry)));
+        assert!(!syn.matches_reference("syn 1.0.109"));
+        assert!(!syn.matches_reference("syn 2.0.50 (git+https://example.com/syn)"));
+        assert!(!syn.matches_reference("synstructure"));
+        assert!(syn.is_from_crates_io());
+        assert!(!package("app", "0.1.0", None).is_from_crates_io());
+    }
+}
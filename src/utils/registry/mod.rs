@@ -0,0 +1,12 @@
+// package registry lookups and lockfile analysis: the sparse index client,
+// the external dependency freshness report and duplicate version detection
+
+pub mod duplicates;
+pub mod freshness;
+pub mod index;
+pub mod lockfile;
+
+pub use duplicates::{DuplicateChecker, DuplicateDependency, DuplicateReport, DuplicateVersion};
+pub use freshness::{CrateFreshness, DependencyFreshness, FreshnessChecker, FreshnessReport};
+pub use index::{CRATES_IO_INDEX, SparseIndex, VersionSource};
+pub use lockfile::LockedPackage;
@@ -0,0 +1,177 @@
+use super::find_unreleased_header;
+use crate::error::{Error, Result};
+use crate::utils::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use crate::utils::changelog::parser::parse_entry;
+use crate::utils::changelog_checker::ChangelogChecker;
+use crate::utils::config::DeptrackConfig;
+use crate::utils::git_ops::GitOps;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// entries drafted for one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateDraft {
+    pub crate_id: CrateId,
+    pub changelog: PathBuf,
+    /// one rendered entry per commit, oldest first
+    pub entries: Vec<String>,
+}
+
+/// changelog entries drafted from the `type(scope): description` subjects of
+/// the commits touching each crate, in the format of [templates]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChangelogDraft {
+    /// crates with commits, sorted by name
+    pub crates: Vec<CrateDraft>,
+}
+
+impl ChangelogDraft {
+    /// draft entries for `crates` from the commits between `from` and `to`;
+    /// merge commits and commits touching only the changelog are left out
+    pub fn from_commits<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        from: &str,
+        to: &str,
+        crates: &[CrateId],
+        config: &DeptrackConfig,
+    ) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let mut drafts = Vec::new();
+
+        for crate_id in crates {
+            let Some(info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let changelog = ChangelogChecker::changelog_path(&info.path, &config.changelog);
+            let templates = config.templates.for_crate(crate_id);
+
+            let mut entries: Vec<String> = Vec::new();
+            let commits =
+                GitOps::commits_in_range_excluding(repo_path, from, to, &info.path, &changelog)?;
+            for commit in commits.iter().rev() {
+                if commit.subject.starts_with("Merge ") {
+                    continue;
+                }
+                let Ok(entry) = parse_entry(&commit.subject, 0) else {
+                    continue;
+                };
+                let rendered = templates.render_entry(crate_id, &entry)?;
+                if !entries.contains(&rendered) {
+                    entries.push(rendered);
+                }
+            }
+
+            if !entries.is_empty() {
+                drafts.push(CrateDraft {
+                    crate_id: crate_id.clone(),
+                    changelog,
+                    entries,
+                });
+            }
+        }
+
+        drafts.sort_by_key(|draft| draft.crate_id.display_name());
+        Ok(Self { crates: drafts })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.crates.is_empty()
+    }
+
+    /// add the entries to the top of each changelog's `## [Unreleased]`
+    /// section, creating the section and the file when missing
+    pub fn write(&self) -> Result<()> {
+        for draft in &self.crates {
+            let content = match fs::read_to_string(&draft.changelog) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => {
+                    return Err(Error::FileReadError {
+                        path: draft.changelog.clone(),
+                        source: e,
+                    });
+                }
+            };
+            fs::write(&draft.changelog, add_unreleased(&content, &draft.entries))?;
+        }
+        Ok(())
+    }
+
+    pub fn display(&self) {
+        if self.is_empty() {
+            println!("no commits to draft entries from");
+            return;
+        }
+        for (i, draft) in self.crates.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!(
+                "{} ({}):",
+                draft.crate_id.display_name(),
+                draft.changelog.display()
+            );
+            for entry in &draft.entries {
+                println!("  {}", entry);
+            }
+        }
+    }
+}
+
+// changelog content with `entries` at the top of the unreleased section, the
+// section added above the first version when there is none
+fn add_unreleased(content: &str, entries: &[String]) -> String {
+    let lines = entries.join("\n");
+    if let Some(header) = find_unreleased_header(content) {
+        let rest = content[header.end..].trim_start_matches(['\n', '\r']);
+        let separator = if rest.starts_with('#') { "\n\n" } else { "\n" };
+        return format!(
+            "{}\n\n{}{}{}",
+            &content[..header.end],
+            lines,
+            separator,
+            rest
+        );
+    }
+
+    let section = format!("## [Unreleased]\n\n{}\n\n", lines);
+    if content.starts_with("## ") {
+        return format!("{}{}", section, content);
+    }
+    match content.find("\n## ") {
+        Some(pos) => format!("{}{}{}", &content[..=pos], section, &content[pos + 1..]),
+        None if content.trim().is_empty() => format!("# CHANGELOG\n\n{}", section),
+        None => format!("{}\n\n{}", content.trim_end(), section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_unreleased() {
+        let entries = ["* fix(io): retry reads".to_string()];
+        assert_eq!(
+            add_unreleased(
+                "# CHANGELOG\n\n## [Unreleased]\n\n* feat: streams\n\n## [0.1.0]\n",
+                &entries
+            ),
+            "# CHANGELOG\n\n## [Unreleased]\n\n* fix(io): retry reads\n* feat: streams\n\n## [0.1.0]\n"
+        );
+        assert_eq!(
+            add_unreleased("# CHANGELOG\n\n## [Unreleased]\n\n## [0.1.0]\n", &entries),
+            "# CHANGELOG\n\n## [Unreleased]\n\n* fix(io): retry reads\n\n## [0.1.0]\n"
+        );
+        assert_eq!(
+            add_unreleased("# CHANGELOG\n\n## [0.1.0]\n\n* feat: initial\n", &entries),
+            "# CHANGELOG\n\n## [Unreleased]\n\n* fix(io): retry reads\n\n## [0.1.0]\n\n* feat: initial\n"
+        );
+        assert_eq!(
+            add_unreleased("", &entries),
+            "# CHANGELOG\n\n## [Unreleased]\n\n* fix(io): retry reads\n\n"
+        );
+    }
+}
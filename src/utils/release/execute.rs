@@ -0,0 +1,80 @@
+use super::plan::{ReleaseAction, ReleasePlan};
+use super::promote_unreleased;
+use crate::error::{Error, Result};
+use crate::utils::git_ops::GitOps;
+use crate::utils::toml_ops::TomlWriter;
+use std::fs;
+use std::path::Path;
+
+/// applies release plans to the working tree and repository
+pub struct ReleaseExecutor;
+
+impl ReleaseExecutor {
+    /// apply every action of `plan` in order, stopping at the first failure
+    pub fn execute<P: AsRef<Path>>(plan: &ReleasePlan, repo_path: P) -> Result<()> {
+        let repo_path = repo_path.as_ref();
+        for action in &plan.actions {
+            Self::apply(action, repo_path)?;
+        }
+        Ok(())
+    }
+
+    /// apply a single release action
+    pub fn apply<P: AsRef<Path>>(action: &ReleaseAction, repo_path: P) -> Result<()> {
+        match action {
+            ReleaseAction::SetVersion { manifest, to, .. } => {
+                let mut writer = open_manifest(manifest)?;
+                writer
+                    .set_package_version(&to.to_string())
+                    .map_err(|e| release_error(manifest, e))?;
+                writer.write().map_err(|e| release_error(manifest, e))
+            }
+            ReleaseAction::UpdateRequirement {
+                manifest,
+                dependency,
+                to,
+                ..
+            } => {
+                let mut writer = open_manifest(manifest)?;
+                if writer.set_dependency_version(dependency, to) == 0 {
+                    return Err(release_error(
+                        manifest,
+                        format!("no requirement on {} to update", dependency),
+                    ));
+                }
+                writer.write().map_err(|e| release_error(manifest, e))
+            }
+            ReleaseAction::PromoteChangelog {
+                changelog, version, ..
+            } => {
+                let content = fs::read_to_string(changelog).map_err(|e| Error::FileReadError {
+                    path: changelog.clone(),
+                    source: e,
+                })?;
+                let promoted = promote_unreleased(&content, version)
+                    .ok_or_else(|| release_error(changelog, "no [Unreleased] section"))?;
+                fs::write(changelog, promoted)?;
+                Ok(())
+            }
+            ReleaseAction::Commit { message, paths } => {
+                GitOps::commit_paths(repo_path, paths, message)?;
+                Ok(())
+            }
+            ReleaseAction::CreateTag {
+                crate_id,
+                name,
+                version,
+            } => GitOps::create_tag(repo_path, name, &format!("{} {}", crate_id.name, version)),
+        }
+    }
+}
+
+fn open_manifest(path: &Path) -> Result<TomlWriter> {
+    TomlWriter::read_file(path).map_err(|e| release_error(path, e))
+}
+
+fn release_error(path: &Path, reason: impl std::fmt::Display) -> Error {
+    Error::ReleaseError {
+        reason: format!("{}: {}", path.display(), reason),
+    }
+}
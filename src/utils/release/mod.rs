@@ -0,0 +1,121 @@
+// release orchestration: turn change and version analysis into an ordered
+// list of actions (version bumps, requirement updates, changelog promotion,
+// commit and tags) and apply them, and draft the changelog entries released
+
+pub mod draft;
+pub mod execute;
+pub mod plan;
+
+pub use draft::{ChangelogDraft, CrateDraft};
+pub use execute::ReleaseExecutor;
+pub use plan::{
+    BumpLevel, PlannedBump, ReleaseAction, ReleaseOptions, ReleasePlan, ReleasePlanner,
+};
+
+// byte range of the first `## [Unreleased]` header line in a changelog,
+// brackets optional and case ignored
+fn find_unreleased_header(content: &str) -> Option<std::ops::Range<usize>> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let Some(title) = line.trim().strip_prefix("##") else {
+            continue;
+        };
+        if title.starts_with('#') {
+            continue;
+        }
+        let title = title.trim();
+        let title = title
+            .strip_prefix('[')
+            .and_then(|t| t.strip_suffix(']'))
+            .unwrap_or(title);
+        if title.eq_ignore_ascii_case("unreleased") {
+            let end = start + line.trim_end_matches(['\n', '\r']).len();
+            return Some(start..end);
+        }
+    }
+    None
+}
+
+// changelog content with the unreleased section renamed to `version`
+fn promote_unreleased(content: &str, version: &semver::Version) -> Option<String> {
+    let range = find_unreleased_header(content)?;
+    let mut promoted = String::with_capacity(content.len());
+    promoted.push_str(&content[..range.start]);
+    promoted.push_str(&format!("## [{}]", version));
+    promoted.push_str(&content[range.end..]);
+    Some(promoted)
+}
+
+// changelog content with a section for `version`: the unreleased section
+// renamed, or an empty one added above the first version
+pub(crate) fn add_version_section(content: &str, version: &semver::Version) -> String {
+    if let Some(promoted) = promote_unreleased(content, version) {
+        return promoted;
+    }
+
+    let header = format!("## [{}]\n", version);
+    if content.starts_with("## ") {
+        return format!("{}\n{}", header, content);
+    }
+    match content.find("\n## ") {
+        Some(pos) => format!("{}{}\n{}", &content[..=pos], header, &content[pos + 1..]),
+        None if content.trim().is_empty() => format!("# CHANGELOG\n\n{}", header),
+        None => format!("{}\n\n{}", content.trim_end(), header),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    #[test]
+    fn test_bump_level_apply() {
+        let version = Version::parse("1.4.2-beta.1").unwrap();
+        assert_eq!(BumpLevel::Patch.apply(&version), Version::new(1, 4, 3));
+        assert_eq!(BumpLevel::Minor.apply(&version), Version::new(1, 5, 0));
+        assert_eq!(BumpLevel::Major.apply(&version), Version::new(2, 0, 0));
+
+        assert_eq!("minor".parse::<BumpLevel>().unwrap(), BumpLevel::Minor);
+        assert_eq!(BumpLevel::Major.to_string(), "major");
+        assert!("huge".parse::<BumpLevel>().is_err());
+    }
+
+    #[test]
+    fn test_promote_unreleased() {
+        let content = "# CHANGELOG\n\n## [Unreleased]\n\n* fix(core): typo\n\n## [0.1.0]\n\n* feat(core): initial\n";
+        let promoted = promote_unreleased(content, &Version::new(0, 2, 0)).unwrap();
+        assert_eq!(
+            promoted,
+            "# CHANGELOG\n\n## [0.2.0]\n\n* fix(core): typo\n\n## [0.1.0]\n\n* feat(core): initial\n"
+        );
+
+        assert!(find_unreleased_header("## unreleased\r\n* fix(a): b\r\n").is_some());
+        assert!(find_unreleased_header("### Unreleased\n").is_none());
+        assert!(promote_unreleased(&promoted, &Version::new(0, 3, 0)).is_none());
+    }
+
+    #[test]
+    fn test_add_version_section() {
+        let version = Version::new(0, 2, 0);
+        assert_eq!(
+            add_version_section("# CHANGELOG\n\n## [Unreleased]\n\n* fix: typo\n", &version),
+            "# CHANGELOG\n\n## [0.2.0]\n\n* fix: typo\n"
+        );
+        assert_eq!(
+            add_version_section("# CHANGELOG\n\n## [0.1.0]\n\n* feat: initial\n", &version),
+            "# CHANGELOG\n\n## [0.2.0]\n\n## [0.1.0]\n\n* feat: initial\n"
+        );
+        assert_eq!(
+            add_version_section("", &version),
+            "# CHANGELOG\n\n## [0.2.0]\n"
+        );
+        assert_eq!(
+            add_version_section("# CHANGELOG\n", &version),
+            "# CHANGELOG\n\n## [0.2.0]\n"
+        );
+    }
+}
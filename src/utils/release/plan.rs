@@ -0,0 +1,587 @@
+use super::find_unreleased_header;
+use crate::error::{Error, Result};
+use crate::utils::cargo_ops::{CrateDependencyGraph, CrateId, DependencyType};
+use crate::utils::changelog_checker::ChangelogChecker;
+use crate::utils::config::{DeptrackConfig, RequirementUpdate};
+use crate::utils::git_ops::{GitOps, GitRef, latest_release_tag, release_tag_name};
+use crate::utils::manifest_checker::ManifestChecker;
+use crate::utils::toml_ops::{TomlDocument, TomlReader};
+use petgraph::visit::EdgeRef;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MIN_CRATE_NAME_WIDTH: usize = 10;
+const MIN_VERSION_WIDTH: usize = 12;
+
+/// dependency tables whose requirements follow a released crate
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// how far a release moves a crate version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BumpLevel {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl BumpLevel {
+    /// next version at this level, pre-release and build metadata are dropped
+    pub fn apply(&self, version: &Version) -> Version {
+        match self {
+            BumpLevel::Patch => Version::new(version.major, version.minor, version.patch + 1),
+            BumpLevel::Minor => Version::new(version.major, version.minor + 1, 0),
+            BumpLevel::Major => Version::new(version.major + 1, 0, 0),
+        }
+    }
+}
+
+impl std::str::FromStr for BumpLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "patch" => Ok(BumpLevel::Patch),
+            "minor" => Ok(BumpLevel::Minor),
+            "major" => Ok(BumpLevel::Major),
+            _ => Err(format!(
+                "invalid bump level: {}, use 'patch', 'minor' or 'major'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BumpLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BumpLevel::Patch => write!(f, "patch"),
+            BumpLevel::Minor => write!(f, "minor"),
+            BumpLevel::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// options controlling which actions a release plan contains
+#[derive(Debug, Clone)]
+pub struct ReleaseOptions {
+    /// bump level for crates that contain changed files
+    pub level: BumpLevel,
+    /// bump level for crates affected only through their dependencies
+    pub transitive_level: BumpLevel,
+    /// commit the updated manifests and changelogs
+    pub commit: bool,
+    /// tag every released crate, named by `releases.tag_pattern`; skipped when
+    /// files change but are not committed, the tags would point at the old versions
+    pub tag: bool,
+}
+
+impl Default for ReleaseOptions {
+    fn default() -> Self {
+        Self {
+            level: BumpLevel::Patch,
+            transitive_level: BumpLevel::Patch,
+            commit: true,
+            tag: true,
+        }
+    }
+}
+
+impl ReleaseOptions {
+    /// create options with defaults (patch bumps, commit and tag)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(mut self, level: BumpLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn transitive_level(mut self, level: BumpLevel) -> Self {
+        self.transitive_level = level;
+        self
+    }
+
+    pub fn commit(mut self, commit: bool) -> Self {
+        self.commit = commit;
+        self
+    }
+
+    pub fn tag(mut self, tag: bool) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+/// version change planned for one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedBump {
+    pub crate_id: CrateId,
+    /// version at the base reference
+    pub base_version: Version,
+    /// version in the working tree
+    pub current_version: Version,
+    /// version the crate is released as
+    pub next_version: Version,
+    /// bump applied, None when the crate was already bumped since the base reference
+    pub level: Option<BumpLevel>,
+    pub is_directly_changed: bool,
+    /// newest existing release tag of the crate under any configured pattern
+    pub previous_tag: Option<String>,
+}
+
+/// a single step of a release, applied in order by the executor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ReleaseAction {
+    /// set [package] version in a crate manifest
+    SetVersion {
+        crate_id: CrateId,
+        manifest: PathBuf,
+        from: Version,
+        to: Version,
+    },
+    /// rewrite the requirement on a released crate in a dependent manifest
+    UpdateRequirement {
+        manifest: PathBuf,
+        dependency: String,
+        from: String,
+        to: String,
+    },
+    /// rename the `## [Unreleased]` changelog section to the released version
+    PromoteChangelog {
+        crate_id: CrateId,
+        changelog: PathBuf,
+        version: Version,
+    },
+    /// commit the files touched by the previous actions
+    Commit {
+        message: String,
+        paths: Vec<PathBuf>,
+    },
+    /// create an annotated tag on HEAD
+    CreateTag {
+        crate_id: CrateId,
+        name: String,
+        version: Version,
+    },
+}
+
+impl fmt::Display for ReleaseAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReleaseAction::SetVersion {
+                crate_id,
+                manifest,
+                from,
+                to,
+            } => write!(
+                f,
+                "set version of {} {} -> {} in {}",
+                crate_id.display_name(),
+                from,
+                to,
+                manifest.display()
+            ),
+            ReleaseAction::UpdateRequirement {
+                manifest,
+                dependency,
+                from,
+                to,
+            } => write!(
+                f,
+                "update requirement on {} {} -> {} in {}",
+                dependency,
+                from,
+                to,
+                manifest.display()
+            ),
+            ReleaseAction::PromoteChangelog {
+                changelog, version, ..
+            } => write!(
+                f,
+                "promote [Unreleased] to [{}] in {}",
+                version,
+                changelog.display()
+            ),
+            ReleaseAction::Commit { message, paths } => {
+                write!(f, "commit {} file(s): {}", paths.len(), message)
+            }
+            ReleaseAction::CreateTag { name, .. } => write!(f, "create tag {}", name),
+        }
+    }
+}
+
+/// everything a release will do, in order
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleasePlan {
+    /// reference the changes were measured against
+    pub base_ref: String,
+    /// released crates, sorted by name
+    pub bumps: Vec<PlannedBump>,
+    /// actions in the order they are applied
+    pub actions: Vec<ReleaseAction>,
+    /// publishable released crates, dependencies before dependents
+    pub publish_order: Vec<CrateId>,
+}
+
+impl ReleasePlan {
+    /// check if there is nothing to release
+    pub fn is_empty(&self) -> bool {
+        self.bumps.is_empty()
+    }
+
+    /// display planned versions, actions and publish order
+    pub fn display(&self) {
+        if self.bumps.is_empty() {
+            println!("nothing to release since {}", self.base_ref);
+            return;
+        }
+
+        let name_width = self
+            .bumps
+            .iter()
+            .map(|b| b.crate_id.display_name().len())
+            .max()
+            .unwrap_or(MIN_CRATE_NAME_WIDTH)
+            .max(MIN_CRATE_NAME_WIDTH);
+
+        println!("release since {}:", self.base_ref);
+        println!(
+            "  {:<name_width$}  {:<version_width$}  {:<version_width$}  Bump",
+            "Crate",
+            "Current",
+            "Next",
+            name_width = name_width,
+            version_width = MIN_VERSION_WIDTH
+        );
+        println!(
+            "  {}  {}  {}  ----",
+            "-".repeat(name_width),
+            "-".repeat(MIN_VERSION_WIDTH),
+            "-".repeat(MIN_VERSION_WIDTH)
+        );
+        for bump in &self.bumps {
+            let level = match bump.level {
+                Some(level) => level.to_string(),
+                None => "already bumped".to_string(),
+            };
+            println!(
+                "  {:<name_width$}  {:<version_width$}  {:<version_width$}  {}",
+                bump.crate_id.display_name(),
+                bump.current_version.to_string(),
+                bump.next_version.to_string(),
+                level,
+                name_width = name_width,
+                version_width = MIN_VERSION_WIDTH
+            );
+        }
+        println!();
+
+        println!("actions:");
+        for (i, action) in self.actions.iter().enumerate() {
+            println!("  {:>2}. {}", i + 1, action);
+        }
+        println!();
+
+        println!("publish order:");
+        for (i, crate_id) in self.publish_order.iter().enumerate() {
+            println!("  {:>2}. {}", i + 1, crate_id.name);
+        }
+    }
+}
+
+/// builds release plans from the change and version bump analysis
+pub struct ReleasePlanner;
+
+impl ReleasePlanner {
+    /// plan a release of every crate the changes between `base_ref` and HEAD
+    /// end up in, see `needs_release`; dependents reached only through
+    /// dev-dependencies are rebuilt but not released
+    ///
+    /// crates already bumped since `base_ref` keep their version, the rest are
+    /// bumped at the configured level; nothing is written to disk
+    pub fn plan<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        base_ref: &GitRef,
+        config: &DeptrackConfig,
+        options: &ReleaseOptions,
+    ) -> Result<ReleasePlan> {
+        let repo_path = repo_path.as_ref();
+
+        let impact = graph.analyze_git_changes_with_config(
+            repo_path,
+            base_ref,
+            &GitRef::Head,
+            &config.impact,
+        )?;
+        let versions = graph.analyze_version_bumps_with_config(
+            repo_path,
+            base_ref,
+            &impact.needs_release,
+            &impact.directly_affected_crates,
+            config,
+        )?;
+
+        let patterns = &config.releases.tag_pattern;
+        let tags = GitOps::list_tag_names(repo_path)?;
+
+        let mut bumps: Vec<PlannedBump> = versions
+            .crate_versions
+            .values()
+            .map(|status| {
+                let level = if status.is_bumped {
+                    None
+                } else if status.is_directly_changed {
+                    Some(options.level)
+                } else {
+                    Some(options.transitive_level)
+                };
+                PlannedBump {
+                    crate_id: status.crate_id.clone(),
+                    base_version: status.base_version.clone(),
+                    current_version: status.current_version.clone(),
+                    next_version: level
+                        .map(|l| l.apply(&status.current_version))
+                        .unwrap_or_else(|| status.current_version.clone()),
+                    level,
+                    is_directly_changed: status.is_directly_changed,
+                    previous_tag: latest_release_tag(patterns, &tags, &status.crate_id.name)
+                        .map(|(tag, _)| tag),
+                }
+            })
+            .collect();
+        bumps.sort_by_key(|b| b.crate_id.display_name());
+
+        let mut actions = Vec::new();
+        for bump in bumps.iter().filter(|b| b.level.is_some()) {
+            let manifest = graph.crates[&bump.crate_id].cargo_toml_path.clone();
+            let document = TomlReader::read_manifest(&manifest)?;
+            if !matches!(
+                document.get_path("package.version"),
+                Some(toml::Value::String(_)) | None
+            ) {
+                return Err(Error::ReleaseError {
+                    reason: format!(
+                        "version of {} is inherited from [workspace.package], bump it there",
+                        bump.crate_id.display_name()
+                    ),
+                });
+            }
+            actions.push(ReleaseAction::SetVersion {
+                crate_id: bump.crate_id.clone(),
+                manifest,
+                from: bump.current_version.clone(),
+                to: bump.next_version.clone(),
+            });
+        }
+
+        actions.extend(Self::requirement_updates(
+            graph,
+            &bumps,
+            config.releases.update_requirements,
+        )?);
+
+        for bump in &bumps {
+            let crate_path = &graph.crates[&bump.crate_id].path;
+            let changelog = ChangelogChecker::changelog_path(crate_path, &config.changelog);
+            let has_unreleased = fs::read_to_string(&changelog)
+                .map(|content| find_unreleased_header(&content).is_some())
+                .unwrap_or(false);
+            if has_unreleased {
+                actions.push(ReleaseAction::PromoteChangelog {
+                    crate_id: bump.crate_id.clone(),
+                    changelog,
+                    version: bump.next_version.clone(),
+                });
+            }
+        }
+
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for action in &actions {
+            let path = match action {
+                ReleaseAction::SetVersion { manifest, .. }
+                | ReleaseAction::UpdateRequirement { manifest, .. } => manifest,
+                ReleaseAction::PromoteChangelog { changelog, .. } => changelog,
+                _ => continue,
+            };
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+
+        if options.commit && !paths.is_empty() {
+            let released: Vec<String> = bumps
+                .iter()
+                .map(|b| format!("{} {}", b.crate_id.name, b.next_version))
+                .collect();
+            actions.push(ReleaseAction::Commit {
+                message: format!("release {}", released.join(", ")),
+                paths: paths.clone(),
+            });
+        }
+
+        if options.tag && (options.commit || paths.is_empty()) {
+            for bump in &bumps {
+                let name = release_tag_name(patterns, &bump.crate_id.name, &bump.next_version);
+                // the version may already be released under an older naming scheme
+                for pattern in patterns {
+                    let existing = pattern.format(&bump.crate_id.name, &bump.next_version);
+                    if tags.contains(&existing) {
+                        return Err(Error::ReleaseError {
+                            reason: format!("tag {} already exists", existing),
+                        });
+                    }
+                }
+                actions.push(ReleaseAction::CreateTag {
+                    crate_id: bump.crate_id.clone(),
+                    name,
+                    version: bump.next_version.clone(),
+                });
+            }
+        }
+
+        let publish_order = Self::publish_order(graph, &bumps)?;
+
+        Ok(ReleasePlan {
+            base_ref: base_ref.to_string(),
+            bumps,
+            actions,
+            publish_order,
+        })
+    }
+
+    /// requirements on released crates to rewrite to the new version, in
+    /// member manifests and [workspace.dependencies], as selected by `mode`
+    fn requirement_updates(
+        graph: &CrateDependencyGraph,
+        bumps: &[PlannedBump],
+        mode: RequirementUpdate,
+    ) -> Result<Vec<ReleaseAction>> {
+        let mut updates = Vec::new();
+        if mode == RequirementUpdate::Never {
+            return Ok(updates);
+        }
+
+        let needs_update = |req: &VersionReq, bump: &PlannedBump| {
+            let (base, next) = (&bump.base_version, &bump.next_version);
+            !req.matches(next)
+                || (mode == RequirementUpdate::Minor
+                    && (base.major, base.minor) != (next.major, next.minor)
+                    && req.matches(base))
+        };
+        let mut push_updates =
+            |document: &TomlDocument, manifest: &Path, tables: &[&str], bump: &PlannedBump| {
+                let outdated = document
+                    .dependency_requirements(&bump.crate_id.name, tables)
+                    .into_iter()
+                    .find(|req| VersionReq::parse(req).is_ok_and(|req| needs_update(&req, bump)));
+                if let Some(from) = outdated {
+                    updates.push(ReleaseAction::UpdateRequirement {
+                        manifest: manifest.to_path_buf(),
+                        dependency: bump.crate_id.name.clone(),
+                        from: from.to_string(),
+                        to: bump.next_version.to_string(),
+                    });
+                }
+            };
+
+        let mut dependents: Vec<&CrateId> = bumps
+            .iter()
+            .flat_map(|b| graph.get_dependents(&b.crate_id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        dependents.sort_by_key(|id| id.display_name());
+
+        for dependent in dependents {
+            let manifest = &graph.crates[dependent].cargo_toml_path;
+            let document = TomlReader::read_manifest(manifest)?;
+            let dependencies = graph.get_dependencies(dependent);
+            for bump in bumps.iter().filter(|b| dependencies.contains(&&b.crate_id)) {
+                push_updates(&document, manifest, &DEPENDENCY_TABLES, bump);
+            }
+        }
+
+        for workspace in &graph.workspaces {
+            let root = TomlReader::read_manifest(&workspace.cargo_toml_path)?;
+            let Some(workspace_table) = root.get_path("workspace") else {
+                continue;
+            };
+            let document = TomlDocument::new(
+                root.file_path.clone(),
+                toml::Value::Table(workspace_table.as_table().cloned().unwrap_or_default()),
+            );
+            for bump in bumps
+                .iter()
+                .filter(|b| b.crate_id.workspace == workspace.name)
+            {
+                push_updates(
+                    &document,
+                    &workspace.cargo_toml_path,
+                    &["dependencies"],
+                    bump,
+                );
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// publishable released crates ordered so each comes after the released
+    /// crates it depends on (dev-dependencies do not count), ties by name
+    fn publish_order(graph: &CrateDependencyGraph, bumps: &[PlannedBump]) -> Result<Vec<CrateId>> {
+        let mut pending: Vec<&CrateId> = Vec::new();
+        for bump in bumps {
+            let document =
+                TomlReader::read_manifest(&graph.crates[&bump.crate_id].cargo_toml_path)?;
+            if ManifestChecker::is_publishable(&document) {
+                pending.push(&bump.crate_id);
+            }
+        }
+
+        let mut dependencies: HashMap<&CrateId, Vec<&CrateId>> = HashMap::new();
+        for crate_id in &pending {
+            let Some(&index) = graph.node_indices.get(*crate_id) else {
+                continue;
+            };
+            let released: Vec<&CrateId> = graph
+                .graph
+                .edges_directed(index, petgraph::Direction::Outgoing)
+                .filter(|edge| *edge.weight() != DependencyType::Dev)
+                .map(|edge| &graph.graph[edge.target()])
+                .filter(|dep| pending.contains(dep))
+                .collect();
+            dependencies.insert(*crate_id, released);
+        }
+
+        let mut order: Vec<CrateId> = Vec::new();
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<&CrateId>, Vec<&CrateId>) =
+                std::mem::take(&mut pending).into_iter().partition(|id| {
+                    dependencies
+                        .get(*id)
+                        .is_none_or(|deps| deps.iter().all(|dep| order.contains(dep)))
+                });
+            if ready.is_empty() {
+                let mut names: Vec<String> = blocked.iter().map(|id| id.display_name()).collect();
+                names.sort();
+                return Err(Error::CyclicDependency {
+                    cycle: names.join(", "),
+                });
+            }
+            let mut ready: Vec<CrateId> = ready.into_iter().cloned().collect();
+            ready.sort_by_key(|id| id.display_name());
+            order.extend(ready);
+            pending = blocked;
+        }
+
+        Ok(order)
+    }
+}
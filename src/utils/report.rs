@@ -0,0 +1,621 @@
+// everything a CI run asks of deptrack in one pass: the change analysis, the
+// check-versions checks, cycles, licenses and manifests
+
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::cargo_ops::{ChangeImpactAnalysis, GraphStatistics};
+use super::changelog_checker::ChangelogChecker;
+use super::config::DeptrackConfig;
+use super::external_checker::ExternalChecker;
+use super::feature_checker::FeatureChecker;
+use super::git_ops::GitRef;
+use super::lockfile_checker::LockfileChecker;
+use super::manifest_checker::ManifestChecker;
+use super::membership_checker::MembershipChecker;
+use super::msrv_checker::MsrvChecker;
+use super::report_compare::ReportIssue;
+use super::severity::IssueSeverity;
+use super::severity_config::{SeverityMetadata, SeverityRule};
+use super::table::{paint, severity_color};
+use super::toml_ops::TomlReader;
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// key of the crates that declare no license in [`LicenseSection::licenses`]
+const UNLICENSED: &str = "unlicensed";
+
+/// the changes between the two references and the workspace they land in
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisSection {
+    pub statistics: GraphStatistics,
+    pub changed_files: usize,
+    pub directly_affected: Vec<String>,
+    pub all_affected: Vec<String>,
+    pub needs_release: Vec<String>,
+}
+
+/// error and warning totals of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckTotals {
+    pub check: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// the checks of check-versions on the affected crates, but the manifests
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionSection {
+    pub bumped: Vec<String>,
+    pub needing_bump: Vec<String>,
+    /// version, changelog, msrv, features, lockfile, membership and checks, in
+    /// that order
+    pub checks: Vec<CheckTotals>,
+}
+
+/// dependency cycles, each listed in dependency order
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleSection {
+    /// cycles through normal dependencies, which cargo refuses to build
+    pub production: Vec<Vec<String>>,
+    /// how many more cycles go through dev or build dependencies
+    pub dev_and_build: usize,
+}
+
+/// the license of every workspace crate
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseSection {
+    /// crates by license expression, `license-file <path>` for crates
+    /// pointing to a file and `unlicensed` for those declaring neither
+    pub licenses: BTreeMap<String, Vec<String>>,
+}
+
+/// publish-readiness of the affected crates
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestSection {
+    pub checked: usize,
+    /// publishable crates missing required fields
+    pub incomplete: Vec<String>,
+    /// crates with `publish = false`
+    pub unpublished: Vec<String>,
+}
+
+/// the document written by `deptrack report`; a section disabled in
+/// [report] is None and left out of the output
+#[derive(Debug, Clone, Serialize)]
+pub struct UnifiedReport {
+    pub from: String,
+    pub to: String,
+    /// the rules of the included sections and the severities they got
+    pub severity: SeverityMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analysis: Option<AnalysisSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versions: Option<VersionSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycles: Option<CycleSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<LicenseSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifests: Option<ManifestSection>,
+    /// issues of every included section, in the check-versions format so
+    /// reports can be compared with `compare-reports`
+    pub issues: Vec<ReportIssue>,
+}
+
+impl UnifiedReport {
+    /// run the sections enabled in `config.report` on the changes between
+    /// `from` and `to`; versions and manifests only look at affected crates,
+    /// cycles and licenses at the whole workspace
+    pub fn build<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        from: &str,
+        to: &str,
+        config: &DeptrackConfig,
+    ) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let sections = &config.report;
+        let from_ref = GitRef::from_string(from);
+        let to_ref = GitRef::from_string(to);
+
+        let impact =
+            graph.analyze_git_changes_with_config(repo_path, &from_ref, &to_ref, &config.impact)?;
+        let affected = &impact.all_affected_crates;
+        let direct = &impact.directly_affected_crates;
+        let mut issues = Vec::new();
+
+        let analysis = sections.analysis.then(|| AnalysisSection {
+            statistics: graph.get_statistics(),
+            changed_files: impact.changed_files.len(),
+            directly_affected: names(direct),
+            all_affected: names(affected),
+            needs_release: names(&impact.needs_release),
+        });
+
+        let versions = if sections.versions {
+            Some(Self::versions(
+                graph,
+                repo_path,
+                &from_ref,
+                &impact,
+                config,
+                &mut issues,
+            )?)
+        } else {
+            None
+        };
+
+        let cycles = sections.cycles.then(|| {
+            let section = Self::cycles(graph);
+            issues.extend(section.production.iter().map(|cycle| ReportIssue {
+                check: "cycles".to_string(),
+                crate_name: cycle[0].clone(),
+                severity: IssueSeverity::Error,
+                issue_type: "dependency_cycle".to_string(),
+                message: format!("dependency cycle {} -> {}", cycle.join(" -> "), cycle[0]),
+            }));
+            section
+        });
+
+        let licenses = if sections.licenses {
+            Some(Self::licenses(graph)?)
+        } else {
+            None
+        };
+
+        let manifests = if sections.manifests {
+            let manifest_analysis = ManifestChecker::analyze(
+                graph,
+                affected,
+                direct,
+                &config.direct_severity,
+                &config.transitive_severity,
+            )?;
+            issues.extend(ReportIssue::collect(
+                "manifest",
+                &manifest_analysis.statuses,
+                |s| &s.issues,
+            ));
+            let mut unpublished: Vec<String> = manifest_analysis
+                .statuses
+                .values()
+                .filter(|status| !status.publishable)
+                .map(|status| status.crate_id.display_name())
+                .collect();
+            unpublished.sort();
+            Some(ManifestSection {
+                checked: manifest_analysis.statuses.len(),
+                incomplete: names(&manifest_analysis.incomplete_crates),
+                unpublished,
+            })
+        } else {
+            None
+        };
+
+        let mut checks = Vec::new();
+        if sections.versions {
+            checks.extend([
+                "version",
+                "changelog",
+                "msrv",
+                "features",
+                "lockfile",
+                "membership",
+            ]);
+            if !config.checks.is_empty() {
+                checks.push("checks");
+            }
+        }
+        if sections.manifests {
+            checks.push("manifest");
+        }
+        let mut severity = SeverityMetadata::new(config, &checks, affected, direct);
+        if sections.cycles {
+            severity.rules.push(SeverityRule {
+                rule: "dependency_cycle".to_string(),
+                checks: vec!["cycles".to_string()],
+                direct: IssueSeverity::Error,
+                transitive: IssueSeverity::Error,
+            });
+        }
+
+        Ok(UnifiedReport {
+            from: from.to_string(),
+            to: to.to_string(),
+            severity,
+            analysis,
+            versions,
+            cycles,
+            licenses,
+            manifests,
+            issues,
+        })
+    }
+
+    fn versions(
+        graph: &CrateDependencyGraph,
+        repo_path: &Path,
+        from_ref: &GitRef,
+        impact: &ChangeImpactAnalysis,
+        config: &DeptrackConfig,
+        issues: &mut Vec<ReportIssue>,
+    ) -> Result<VersionSection> {
+        let affected = &impact.all_affected_crates;
+        let direct = &impact.directly_affected_crates;
+        let (direct_severity, transitive_severity) =
+            (&config.direct_severity, &config.transitive_severity);
+        let mut checks = Vec::new();
+        let mut record = |check: &str, found: Vec<ReportIssue>| {
+            let count = |severity| found.iter().filter(|i| i.severity == severity).count();
+            checks.push(CheckTotals {
+                check: check.to_string(),
+                errors: count(IssueSeverity::Error),
+                warnings: count(IssueSeverity::Warning),
+            });
+            issues.extend(found);
+        };
+
+        let version_analysis = graph
+            .analyze_version_bumps_with_config(repo_path, from_ref, affected, direct, config)?;
+        record(
+            "version",
+            ReportIssue::collect("version", &version_analysis.crate_versions, |s| &s.issues),
+        );
+
+        let changelog = ChangelogChecker::analyze_for_changes(
+            graph,
+            repo_path,
+            &config.changelog,
+            direct_severity,
+            transitive_severity,
+            &version_analysis,
+            impact,
+        )?;
+        record(
+            "changelog",
+            ReportIssue::collect("changelog", &changelog.statuses, |s| &s.issues),
+        );
+
+        let msrv = MsrvChecker::analyze(
+            graph,
+            affected,
+            direct,
+            direct_severity,
+            transitive_severity,
+            &config.msrv,
+        )?;
+        record(
+            "msrv",
+            ReportIssue::collect("msrv", &msrv.statuses, |s| &s.issues),
+        );
+
+        let features = FeatureChecker::analyze(
+            graph,
+            affected,
+            direct,
+            direct_severity,
+            transitive_severity,
+        )?;
+        record(
+            "features",
+            ReportIssue::collect("features", &features.statuses, |s| &s.issues),
+        );
+
+        let lockfile = LockfileChecker::analyze(
+            graph,
+            repo_path,
+            affected,
+            direct,
+            direct_severity,
+            transitive_severity,
+        )?;
+        record(
+            "lockfile",
+            ReportIssue::collect("lockfile", &lockfile.statuses, |s| &s.issues),
+        );
+
+        let mut membership = MembershipChecker::analyze(graph, repo_path, direct_severity)?;
+        MembershipChecker::check_removed_crates(
+            &mut membership,
+            graph,
+            repo_path,
+            &impact.removed_crates,
+            direct_severity,
+        );
+        record(
+            "membership",
+            ReportIssue::collect("membership", &membership.statuses, |s| &s.issues),
+        );
+
+        let external = ExternalChecker::analyze(
+            graph,
+            repo_path,
+            &config.checks,
+            affected,
+            direct,
+            direct_severity,
+            transitive_severity,
+        )?;
+        record(
+            "checks",
+            ReportIssue::collect("checks", &external.statuses, |s| &s.issues),
+        );
+
+        Ok(VersionSection {
+            bumped: names(&version_analysis.crates_bumped),
+            needing_bump: names(&version_analysis.crates_needing_bump),
+            checks,
+        })
+    }
+
+    fn cycles(graph: &CrateDependencyGraph) -> CycleSection {
+        let production = graph.find_production_cycles();
+        let total = graph.find_cycles().len();
+        let mut production: Vec<Vec<String>> = production
+            .iter()
+            .map(|cycle| {
+                graph
+                    .reconstruct_cycle_chain(cycle)
+                    .iter()
+                    .map(|id| id.display_name())
+                    .collect()
+            })
+            .filter(|chain: &Vec<String>| !chain.is_empty())
+            .collect();
+        production.sort();
+        CycleSection {
+            dev_and_build: total.saturating_sub(production.len()),
+            production,
+        }
+    }
+
+    // `license.workspace = true` is resolved through [workspace.package]
+    fn licenses(graph: &CrateDependencyGraph) -> Result<LicenseSection> {
+        let mut licenses: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (crate_id, info) in &graph.crates {
+            let document = TomlReader::read_manifest_with_workspace(&info.path)?;
+            let field = |name: &str| {
+                document
+                    .get_package_field(name)
+                    .and_then(|value| value.as_str().map(str::to_string))
+            };
+            let license = field("license")
+                .or_else(|| field("license-file").map(|file| format!("license-file {}", file)))
+                .unwrap_or_else(|| UNLICENSED.to_string());
+            licenses
+                .entry(license)
+                .or_default()
+                .push(crate_id.display_name());
+        }
+        for crates in licenses.values_mut() {
+            crates.sort();
+        }
+        Ok(LicenseSection { licenses })
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.count(IssueSeverity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(IssueSeverity::Warning)
+    }
+
+    fn count(&self, severity: IssueSeverity) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+
+    /// display every included section, then the issues and their totals
+    pub fn display(&self) {
+        println!("report for {}..{}", self.from, self.to);
+        println!();
+
+        if let Some(analysis) = &self.analysis {
+            let stats = &analysis.statistics;
+            println!("analysis:");
+            println!(
+                "  {} crates in {} workspaces, {} internal dependencies",
+                stats.crate_count, stats.workspace_count, stats.dependency_count
+            );
+            println!(
+                "  {} changed files, {} crates directly affected, {} in total, {} to release",
+                analysis.changed_files,
+                analysis.directly_affected.len(),
+                analysis.all_affected.len(),
+                analysis.needs_release.len()
+            );
+            println!();
+        }
+
+        if let Some(versions) = &self.versions {
+            println!("versions:");
+            println!(
+                "  {} bumped, {} needing a bump",
+                versions.bumped.len(),
+                versions.needing_bump.len()
+            );
+            for check in &versions.checks {
+                println!(
+                    "  {:<10} {} errors, {} warnings",
+                    check.check, check.errors, check.warnings
+                );
+            }
+            println!();
+        }
+
+        if let Some(cycles) = &self.cycles {
+            println!("cycles:");
+            if cycles.production.is_empty() {
+                println!("  no production dependency cycles");
+            }
+            for cycle in &cycles.production {
+                println!("  {} -> {}", cycle.join(" -> "), cycle[0]);
+            }
+            if cycles.dev_and_build > 0 {
+                println!(
+                    "  {} more through dev or build dependencies",
+                    cycles.dev_and_build
+                );
+            }
+            println!();
+        }
+
+        if let Some(licenses) = &self.licenses {
+            println!("licenses:");
+            for (license, crates) in &licenses.licenses {
+                println!("  {}: {}", license, crates.join(", "));
+            }
+            println!();
+        }
+
+        if let Some(manifests) = &self.manifests {
+            println!("manifests:");
+            println!(
+                "  {} checked, {} incomplete, {} not published",
+                manifests.checked,
+                manifests.incomplete.len(),
+                manifests.unpublished.len()
+            );
+            println!();
+        }
+
+        if !self.issues.is_empty() {
+            println!("issues:");
+            for issue in &self.issues {
+                println!(
+                    "  {} [{}] {}: {}",
+                    paint(&issue.severity.to_string(), severity_color(issue.severity)),
+                    issue.check,
+                    issue.crate_name,
+                    issue.message
+                );
+            }
+            println!();
+        }
+
+        println!(
+            "{} errors, {} warnings",
+            self.error_count(),
+            self.warning_count()
+        );
+    }
+
+    /// the report as markdown, for a job summary or a pull request comment
+    pub fn to_markdown(&self) -> String {
+        let status = if self.error_count() == 0 {
+            "passed"
+        } else {
+            "failed"
+        };
+        let code = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut out = format!(
+            "### deptrack report: {}\n\n`{}`..`{}`\n\n",
+            status, self.from, self.to
+        );
+
+        if let Some(analysis) = &self.analysis {
+            let stats = &analysis.statistics;
+            out.push_str("#### analysis\n\n");
+            out.push_str(&format!(
+                "{} crate(s) in {} workspace(s), {} changed file(s), {} directly affected crate(s), {} affected in total\n\n",
+                stats.crate_count,
+                stats.workspace_count,
+                analysis.changed_files,
+                analysis.directly_affected.len(),
+                analysis.all_affected.len()
+            ));
+            if !analysis.needs_release.is_empty() {
+                out.push_str(&format!(
+                    "to release: {}\n\n",
+                    code(&analysis.needs_release)
+                ));
+            }
+        }
+
+        if let Some(versions) = &self.versions {
+            out.push_str("#### versions\n\n");
+            out.push_str(&format!(
+                "{} bumped, {} needing a bump\n\n",
+                versions.bumped.len(),
+                versions.needing_bump.len()
+            ));
+            out.push_str("| check | errors | warnings |\n|---|---:|---:|\n");
+            for check in &versions.checks {
+                out.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    check.check, check.errors, check.warnings
+                ));
+            }
+            out.push('\n');
+        }
+
+        if let Some(cycles) = &self.cycles {
+            out.push_str("#### cycles\n\n");
+            if cycles.production.is_empty() {
+                out.push_str("no production dependency cycles\n");
+            }
+            for cycle in &cycles.production {
+                out.push_str(&format!("- `{} -> {}`\n", cycle.join(" -> "), cycle[0]));
+            }
+            if cycles.dev_and_build > 0 {
+                out.push_str(&format!(
+                    "\n{} more through dev or build dependencies\n",
+                    cycles.dev_and_build
+                ));
+            }
+            out.push('\n');
+        }
+
+        if let Some(licenses) = &self.licenses {
+            out.push_str("#### licenses\n\n| license | crates |\n|---|---|\n");
+            for (license, crates) in &licenses.licenses {
+                out.push_str(&format!("| {} | {} |\n", license, code(crates)));
+            }
+            out.push('\n');
+        }
+
+        if let Some(manifests) = &self.manifests {
+            out.push_str("#### manifests\n\n");
+            out.push_str(&format!(
+                "{} checked, {} incomplete, {} not published\n\n",
+                manifests.checked,
+                manifests.incomplete.len(),
+                manifests.unpublished.len()
+            ));
+        }
+
+        if !self.issues.is_empty() {
+            out.push_str(
+                "#### issues\n\n| severity | check | crate | message |\n|---|---|---|---|\n",
+            );
+            for issue in &self.issues {
+                out.push_str(&format!(
+                    "| {} | {} | `{}` | {} |\n",
+                    issue.severity, issue.check, issue.crate_name, issue.message
+                ));
+            }
+            out.push('\n');
+        }
+
+        out.push_str(&format!(
+            "**{} error(s), {} warning(s)**\n",
+            self.error_count(),
+            self.warning_count()
+        ));
+        out
+    }
+}
+
+fn names(ids: &[CrateId]) -> Vec<String> {
+    ids.iter().map(|id| id.display_name()).collect()
+}
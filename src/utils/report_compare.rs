@@ -0,0 +1,306 @@
+use super::cargo_ops::types::CrateId;
+use super::severity::{Issue, IssueSeverity};
+use super::table::{Color, paint, severity_color};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// one issue of a check-versions report, as listed under `issues`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportIssue {
+    /// check that raised it: version, changelog, manifest, msrv, features,
+    /// lockfile, membership or checks
+    pub check: String,
+    /// display name of the crate
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub severity: IssueSeverity,
+    #[serde(rename = "type")]
+    pub issue_type: String,
+    pub message: String,
+}
+
+impl ReportIssue {
+    pub fn new(check: &str, crate_id: &CrateId, issue: &Issue) -> Self {
+        ReportIssue {
+            check: check.to_string(),
+            crate_name: crate_id.display_name(),
+            severity: issue.severity,
+            issue_type: issue.issue_type.to_string(),
+            message: issue.message.clone(),
+        }
+    }
+
+    /// every issue of one check, crates in name order
+    pub fn collect<S>(
+        check: &str,
+        statuses: &HashMap<CrateId, S>,
+        issues: impl Fn(&S) -> &Vec<Issue>,
+    ) -> Vec<Self> {
+        let mut statuses: Vec<_> = statuses.iter().collect();
+        statuses.sort_by_key(|(id, _)| id.display_name());
+        statuses
+            .into_iter()
+            .flat_map(|(id, status)| issues(status).iter().map(|i| Self::new(check, id, i)))
+            .collect()
+    }
+
+    // issues that only differ in their message are the same issue reworded,
+    // say a version that moved on
+    fn kind(&self) -> (&str, &str, &str, IssueSeverity) {
+        (
+            &self.check,
+            &self.crate_name,
+            &self.issue_type,
+            self.severity,
+        )
+    }
+}
+
+/// how a crate fares in one report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrateCompliance {
+    Passing,
+    Warnings,
+    Failing,
+}
+
+impl CrateCompliance {
+    fn of<'a>(issues: impl Iterator<Item = &'a ReportIssue>) -> Self {
+        issues.fold(CrateCompliance::Passing, |compliance, issue| {
+            compliance.max(match issue.severity {
+                IssueSeverity::Error => CrateCompliance::Failing,
+                IssueSeverity::Warning => CrateCompliance::Warnings,
+            })
+        })
+    }
+}
+
+impl std::fmt::Display for CrateCompliance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrateCompliance::Passing => write!(f, "passing"),
+            CrateCompliance::Warnings => write!(f, "warnings"),
+            CrateCompliance::Failing => write!(f, "failing"),
+        }
+    }
+}
+
+/// a crate whose compliance differs between the two reports
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplianceChange {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub before: CrateCompliance,
+    pub after: CrateCompliance,
+}
+
+impl ComplianceChange {
+    pub fn is_regression(&self) -> bool {
+        self.after > self.before
+    }
+}
+
+/// differences between an older and a newer check-versions json report
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportComparison {
+    /// issues of the newer report the older one did not have
+    pub introduced: Vec<ReportIssue>,
+    /// issues of the older report gone from the newer one
+    pub resolved: Vec<ReportIssue>,
+    /// sorted by crate name
+    pub compliance: Vec<ComplianceChange>,
+}
+
+impl ReportComparison {
+    /// compare two `check-versions --format json` reports; both need the
+    /// `issues` list, which older reports do not have
+    pub fn compare(old: &Value, new: &Value) -> Result<Self> {
+        let old = report_issues(old, "old")?;
+        let new = report_issues(new, "new")?;
+
+        let mut introduced = new.clone();
+        let mut resolved = old.clone();
+        pair_off(&mut introduced, &mut resolved, |a, b| a == b);
+        // what is left on both sides under the same kind was reworded
+        pair_off(&mut introduced, &mut resolved, |a, b| a.kind() == b.kind());
+
+        let crates: BTreeSet<&str> = old
+            .iter()
+            .chain(&new)
+            .map(|issue| issue.crate_name.as_str())
+            .collect();
+        let compliance = crates
+            .into_iter()
+            .filter_map(|name| {
+                let before = CrateCompliance::of(old.iter().filter(|i| i.crate_name == name));
+                let after = CrateCompliance::of(new.iter().filter(|i| i.crate_name == name));
+                (before != after).then(|| ComplianceChange {
+                    crate_name: name.to_string(),
+                    before,
+                    after,
+                })
+            })
+            .collect();
+
+        Ok(ReportComparison {
+            introduced,
+            resolved,
+            compliance,
+        })
+    }
+
+    /// introduced issues of `severity`
+    pub fn introduced_count(&self, severity: IssueSeverity) -> usize {
+        self.introduced
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+
+    /// check if nothing got worse
+    pub fn is_clean(&self) -> bool {
+        self.introduced.is_empty() && !self.compliance.iter().any(|c| c.is_regression())
+    }
+
+    /// display introduced and resolved issues, then the crates whose
+    /// compliance changed
+    pub fn display(&self) {
+        let section = |title: &str, issues: &[ReportIssue]| {
+            if issues.is_empty() {
+                return;
+            }
+            println!("{}:", title);
+            for issue in issues {
+                println!(
+                    "  {} [{}] {} {}: {}",
+                    paint(&issue.severity.to_string(), severity_color(issue.severity)),
+                    issue.check,
+                    issue.crate_name,
+                    issue.issue_type,
+                    issue.message
+                );
+            }
+            println!();
+        };
+        section("introduced", &self.introduced);
+        section("resolved", &self.resolved);
+
+        if !self.compliance.is_empty() {
+            println!("compliance:");
+            for change in &self.compliance {
+                let color = if change.is_regression() {
+                    Color::Red
+                } else {
+                    Color::Green
+                };
+                println!(
+                    "  {}: {} -> {}",
+                    change.crate_name,
+                    change.before,
+                    paint(&change.after.to_string(), color)
+                );
+            }
+            println!();
+        }
+
+        println!(
+            "{} introduced ({} errors, {} warnings), {} resolved",
+            self.introduced.len(),
+            self.introduced_count(IssueSeverity::Error),
+            self.introduced_count(IssueSeverity::Warning),
+            self.resolved.len()
+        );
+    }
+}
+
+fn report_issues(report: &Value, which: &str) -> Result<Vec<ReportIssue>> {
+    let issues = report.get("issues").ok_or_else(|| Error::ReportError {
+        reason: format!(
+            "the {} report has no issue list, write it again with `deptrack --format json check-versions`",
+            which
+        ),
+    })?;
+    serde_json::from_value(issues.clone()).map_err(|e| Error::ReportError {
+        reason: format!("invalid issue list in the {} report: {}", which, e),
+    })
+}
+
+// drop the issues `same` pairs up between the two sides, each one paired once
+fn pair_off(
+    new: &mut Vec<ReportIssue>,
+    old: &mut Vec<ReportIssue>,
+    same: impl Fn(&ReportIssue, &ReportIssue) -> bool,
+) {
+    old.retain(
+        |old_issue| match new.iter().position(|new_issue| same(new_issue, old_issue)) {
+            Some(index) => {
+                new.remove(index);
+                false
+            }
+            None => true,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn issue(crate_name: &str, severity: &str, issue_type: &str, message: &str) -> Value {
+        json!({
+            "check": "version",
+            "crate": crate_name,
+            "severity": severity,
+            "type": issue_type,
+            "message": message,
+        })
+    }
+
+    #[test]
+    fn test_compare_reports() {
+        let old = json!({ "issues": [
+            issue("ws::core", "error", "no_version_bump", "version not bumped (current: 0.1.0)"),
+            issue("ws::app", "warning", "missing_changelog", "missing CHANGELOG.md file"),
+        ]});
+        let new = json!({ "issues": [
+            issue("ws::core", "error", "no_version_bump", "version not bumped (current: 0.2.0)"),
+            issue("ws::cli", "error", "incomplete_manifest", "missing license"),
+        ]});
+
+        let comparison = ReportComparison::compare(&old, &new).unwrap();
+        // the reworded core issue is neither introduced nor resolved
+        assert_eq!(comparison.introduced.len(), 1);
+        assert_eq!(comparison.introduced[0].crate_name, "ws::cli");
+        assert_eq!(comparison.resolved.len(), 1);
+        assert_eq!(comparison.resolved[0].crate_name, "ws::app");
+        assert_eq!(comparison.introduced_count(IssueSeverity::Error), 1);
+        assert!(!comparison.is_clean());
+
+        let changes: Vec<_> = comparison
+            .compliance
+            .iter()
+            .map(|c| (c.crate_name.as_str(), c.before, c.after))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "ws::app",
+                    CrateCompliance::Warnings,
+                    CrateCompliance::Passing
+                ),
+                (
+                    "ws::cli",
+                    CrateCompliance::Passing,
+                    CrateCompliance::Failing
+                ),
+            ]
+        );
+
+        assert!(ReportComparison::compare(&json!({}), &new).is_err());
+    }
+}
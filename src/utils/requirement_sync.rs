@@ -0,0 +1,312 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::manifest_checker::ManifestChecker;
+use crate::error::{Error, Result};
+use crate::utils::toml_ops::{TomlDocument, TomlReader, TomlWriter};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// an internal dependency requirement that no longer accepts the current
+/// version of the crate it names
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleRequirement {
+    /// manifest declaring the requirement
+    pub manifest: PathBuf,
+    /// crate declaring it, None for [workspace.dependencies]
+    pub dependent: Option<CrateId>,
+    pub dependency: CrateId,
+    pub requirement: String,
+    /// current version of the dependency
+    pub version: Version,
+    /// what `deps sync` rewrites the requirement to
+    pub synced: String,
+}
+
+/// a path dependency of a publishable crate on a workspace crate that has no
+/// version requirement at all
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingRequirement {
+    /// manifest declaring the path, the workspace root for inherited ones
+    pub manifest: PathBuf,
+    /// crate declaring it, None for [workspace.dependencies]
+    pub dependent: Option<CrateId>,
+    pub dependency: CrateId,
+    /// what `deps sync` adds as the requirement
+    pub synced: String,
+}
+
+/// internal requirements checked against the workspace crates they name
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequirementSyncReport {
+    /// stale requirements sorted by manifest and dependency
+    pub stale: Vec<StaleRequirement>,
+    /// missing requirements sorted by manifest and dependency
+    pub missing: Vec<MissingRequirement>,
+    /// how many requirements were looked at
+    pub checked: usize,
+}
+
+impl RequirementSyncReport {
+    /// check if every internal requirement accepts the current version and
+    /// no publishable crate lacks one
+    pub fn is_consistent(&self) -> bool {
+        self.stale.is_empty() && self.missing.is_empty()
+    }
+
+    /// display every stale requirement with the one it is synced to
+    pub fn display(&self) {
+        for stale in &self.stale {
+            let declared_by = stale.dependent.as_ref().map_or_else(
+                || "[workspace.dependencies]".to_string(),
+                |id| id.display_name(),
+            );
+            println!(
+                "  {} -> {}: \"{}\" does not accept {}, sync to \"{}\"",
+                declared_by, stale.dependency.name, stale.requirement, stale.version, stale.synced
+            );
+            println!("    in {}", stale.manifest.display());
+        }
+        for missing in &self.missing {
+            let declared_by = missing.dependent.as_ref().map_or_else(
+                || "[workspace.dependencies]".to_string(),
+                |id| id.display_name(),
+            );
+            println!(
+                "  {} -> {}: path dependency without a version, add \"{}\"",
+                declared_by, missing.dependency.name, missing.synced
+            );
+            println!("    in {}", missing.manifest.display());
+        }
+        if !self.missing.is_empty() {
+            println!(
+                "{} path dependencies of publishable crates have no requirement",
+                self.missing.len()
+            );
+        }
+        println!(
+            "{} of {} internal requirements are stale",
+            self.stale.len(),
+            self.checked
+        );
+    }
+}
+
+/// compares internal dependency requirements with the versions of the
+/// crates they point to and rewrites the stale ones
+pub struct RequirementSync;
+
+impl RequirementSync {
+    /// check the requirements of every member on the crates it depends on,
+    /// and of every [workspace.dependencies] entry naming a member; requirements
+    /// or versions that do not parse are left out. path dependencies of
+    /// publishable members on other members without a requirement are
+    /// reported as missing
+    pub fn analyze(graph: &CrateDependencyGraph) -> Result<RequirementSyncReport> {
+        let mut report = RequirementSyncReport::default();
+        let mut workspace_documents: HashMap<&str, TomlDocument> = HashMap::new();
+        for workspace in &graph.workspaces {
+            workspace_documents.insert(
+                &workspace.name,
+                TomlReader::read_manifest(&workspace.cargo_toml_path)?,
+            );
+        }
+
+        for (crate_id, info) in &graph.crates {
+            let document = TomlReader::read_manifest(&info.cargo_toml_path)?;
+            if ManifestChecker::is_publishable(&document) {
+                Self::check_missing(
+                    graph,
+                    &document,
+                    workspace_documents.get(crate_id.workspace.as_str()),
+                    crate_id,
+                    &mut report,
+                );
+            }
+            for dependency in graph.get_dependencies(crate_id) {
+                Self::check(
+                    graph,
+                    &document,
+                    &info.cargo_toml_path,
+                    Some(crate_id),
+                    dependency,
+                    &DEPENDENCY_TABLES,
+                    &mut report,
+                );
+            }
+        }
+
+        for workspace in &graph.workspaces {
+            let root = &workspace_documents[workspace.name.as_str()];
+            let Some(workspace_table) = root.get_path("workspace") else {
+                continue;
+            };
+            let document = TomlDocument::new(
+                root.file_path.clone(),
+                toml::Value::Table(workspace_table.as_table().cloned().unwrap_or_default()),
+            );
+            for member in graph.get_workspace_crates(&workspace.name) {
+                Self::check(
+                    graph,
+                    &document,
+                    &workspace.cargo_toml_path,
+                    None,
+                    &member.id,
+                    &["dependencies"],
+                    &mut report,
+                );
+            }
+        }
+
+        report.missing.sort_by(|a, b| {
+            (&a.manifest, &a.dependency.name).cmp(&(&b.manifest, &b.dependency.name))
+        });
+        report.missing.dedup();
+        report.stale.sort_by(|a, b| {
+            (&a.manifest, &a.dependency.name, &a.requirement).cmp(&(
+                &b.manifest,
+                &b.dependency.name,
+                &b.requirement,
+            ))
+        });
+        Ok(report)
+    }
+
+    fn check_missing(
+        graph: &CrateDependencyGraph,
+        document: &TomlDocument,
+        workspace_document: Option<&TomlDocument>,
+        dependent: &CrateId,
+        report: &mut RequirementSyncReport,
+    ) {
+        for unversioned in
+            ManifestChecker::unversioned_path_dependencies(document, workspace_document)
+        {
+            let Some(dependency) = graph
+                .get_dependencies(dependent)
+                .into_iter()
+                .find(|dependency| dependency.name == unversioned.name)
+            else {
+                continue;
+            };
+            let (manifest, dependent) = match workspace_document {
+                Some(workspace) if unversioned.inherited => {
+                    (PathBuf::from(&workspace.file_path), None)
+                }
+                _ => (
+                    graph.crates[dependent].cargo_toml_path.clone(),
+                    Some(dependent.clone()),
+                ),
+            };
+            report.missing.push(MissingRequirement {
+                manifest,
+                dependent,
+                synced: graph.crates[dependency].version.clone(),
+                dependency: dependency.clone(),
+            });
+        }
+    }
+
+    fn check(
+        graph: &CrateDependencyGraph,
+        document: &TomlDocument,
+        manifest: &Path,
+        dependent: Option<&CrateId>,
+        dependency: &CrateId,
+        tables: &[&str],
+        report: &mut RequirementSyncReport,
+    ) {
+        let Ok(version) = Version::parse(&graph.crates[dependency].version) else {
+            return;
+        };
+
+        let mut requirements = document.dependency_requirements(&dependency.name, tables);
+        requirements.sort();
+        requirements.dedup();
+        for requirement in requirements {
+            let Ok(req) = VersionReq::parse(requirement) else {
+                continue;
+            };
+            report.checked += 1;
+            if !req.matches(&version) {
+                report.stale.push(StaleRequirement {
+                    manifest: manifest.to_path_buf(),
+                    dependent: dependent.cloned(),
+                    dependency: dependency.clone(),
+                    requirement: requirement.to_string(),
+                    synced: synced_requirement(requirement, &version),
+                    version: version.clone(),
+                });
+            }
+        }
+    }
+
+    /// rewrite the stale requirements of `report` and add the missing ones,
+    /// keeping the formatting of each manifest; returns how many entries were
+    /// changed
+    pub fn apply(report: &RequirementSyncReport) -> Result<usize> {
+        let mut by_manifest: BTreeMap<&Path, (Vec<&StaleRequirement>, Vec<&MissingRequirement>)> =
+            BTreeMap::new();
+        for stale in &report.stale {
+            by_manifest
+                .entry(&stale.manifest)
+                .or_default()
+                .0
+                .push(stale);
+        }
+        for missing in &report.missing {
+            by_manifest
+                .entry(&missing.manifest)
+                .or_default()
+                .1
+                .push(missing);
+        }
+
+        let mut updated = 0;
+        for (manifest, (stale, missing)) in by_manifest {
+            let mut writer =
+                TomlWriter::read_file(manifest).map_err(|e| sync_error(manifest, e))?;
+            for stale in stale {
+                updated += writer.set_dependency_version(&stale.dependency.name, &stale.synced);
+            }
+            for missing in missing {
+                updated +=
+                    writer.set_path_dependency_version(&missing.dependency.name, &missing.synced);
+            }
+            writer.write().map_err(|e| sync_error(manifest, e))?;
+        }
+        Ok(updated)
+    }
+}
+
+/// the current version, keeping a leading `=`, `~` or `^` of a single
+/// comparator requirement
+fn synced_requirement(requirement: &str, version: &Version) -> String {
+    let requirement = requirement.trim();
+    match requirement.chars().next() {
+        Some(op @ ('=' | '~' | '^')) if !requirement.contains(',') => format!("{}{}", op, version),
+        _ => version.to_string(),
+    }
+}
+
+fn sync_error(path: &Path, reason: impl std::fmt::Display) -> Error {
+    Error::WorkspaceError {
+        reason: format!("{}: {}", path.display(), reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synced_requirement() {
+        let version = Version::new(1, 4, 0);
+        assert_eq!(synced_requirement("0.3", &version), "1.4.0");
+        assert_eq!(synced_requirement("=1.3.2", &version), "=1.4.0");
+        assert_eq!(synced_requirement(" ~1.3 ", &version), "~1.4.0");
+        assert_eq!(synced_requirement(">=1.0, <1.4", &version), "1.4.0");
+    }
+}
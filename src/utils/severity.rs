@@ -45,6 +45,72 @@ pub enum IssueType {
     BadFormat,
     /// version was not bumped when crate was modified
     NoVersionBump,
+    /// publishable crate is missing metadata required by `cargo publish`
+    IncompleteManifest,
+    /// crate's rust-version disagrees with its workspace, dependencies or configured minimum
+    MsrvMismatch,
+    /// feature references a missing dependency or feature, or an optional dependency is never enabled
+    InvalidFeature,
+    /// issue reported by a `[[checks]]` command from the config file
+    ExternalCheck,
+    /// Cargo.lock records a different version of a workspace member than its Cargo.toml
+    LockfileMismatch,
+    /// crate on disk that no workspace lists as a member or excludes
+    UnlistedCrate,
+    /// workspace `members` entry that matches no crate
+    MissingMember,
+    /// publishable crate depends on a path dependency without a version requirement
+    MissingPathDepVersion,
+    /// Cargo.toml that does not parse or is neither a package nor a workspace manifest
+    BrokenManifest,
+    /// a check could not be run for a crate, say its manifest did not read
+    AnalysisFailed,
+    /// crate still depends on a crate whose Cargo.toml was deleted
+    RemovedCrateReferenced,
+}
+
+impl IssueType {
+    /// every issue type, in declaration order
+    pub const ALL: [IssueType; 16] = [
+        IssueType::MissingChangelog,
+        IssueType::MissingVersionEntry,
+        IssueType::ChangelogNotUpdated,
+        IssueType::BadFormat,
+        IssueType::NoVersionBump,
+        IssueType::IncompleteManifest,
+        IssueType::MsrvMismatch,
+        IssueType::InvalidFeature,
+        IssueType::ExternalCheck,
+        IssueType::LockfileMismatch,
+        IssueType::UnlistedCrate,
+        IssueType::MissingMember,
+        IssueType::MissingPathDepVersion,
+        IssueType::BrokenManifest,
+        IssueType::AnalysisFailed,
+        IssueType::RemovedCrateReferenced,
+    ];
+
+    /// the checks reporting this issue type, named as in the `check` of
+    /// report issues
+    pub fn checks(self) -> &'static [&'static str] {
+        match self {
+            IssueType::MissingChangelog
+            | IssueType::MissingVersionEntry
+            | IssueType::ChangelogNotUpdated
+            | IssueType::BadFormat => &["changelog"],
+            IssueType::NoVersionBump => &["version"],
+            IssueType::IncompleteManifest | IssueType::MissingPathDepVersion => &["manifest"],
+            IssueType::MsrvMismatch => &["msrv"],
+            IssueType::InvalidFeature => &["features"],
+            IssueType::ExternalCheck => &["checks"],
+            IssueType::LockfileMismatch => &["lockfile"],
+            IssueType::UnlistedCrate
+            | IssueType::MissingMember
+            | IssueType::BrokenManifest
+            | IssueType::RemovedCrateReferenced => &["membership"],
+            IssueType::AnalysisFailed => &["manifest", "msrv", "features", "lockfile"],
+        }
+    }
 }
 
 impl fmt::Display for IssueType {
@@ -55,6 +121,17 @@ impl fmt::Display for IssueType {
             IssueType::ChangelogNotUpdated => write!(f, "changelog_not_updated"),
             IssueType::BadFormat => write!(f, "bad_format"),
             IssueType::NoVersionBump => write!(f, "no_version_bump"),
+            IssueType::IncompleteManifest => write!(f, "incomplete_manifest"),
+            IssueType::MsrvMismatch => write!(f, "msrv_mismatch"),
+            IssueType::InvalidFeature => write!(f, "invalid_feature"),
+            IssueType::ExternalCheck => write!(f, "external_check"),
+            IssueType::LockfileMismatch => write!(f, "lockfile_mismatch"),
+            IssueType::UnlistedCrate => write!(f, "unlisted_crate"),
+            IssueType::MissingMember => write!(f, "missing_member"),
+            IssueType::MissingPathDepVersion => write!(f, "missing_path_dep_version"),
+            IssueType::BrokenManifest => write!(f, "broken_manifest"),
+            IssueType::AnalysisFailed => write!(f, "analysis_failed"),
+            IssueType::RemovedCrateReferenced => write!(f, "removed_crate_referenced"),
         }
     }
 }
@@ -1,5 +1,8 @@
+use super::cargo_ops::types::CrateId;
+use super::config::DeptrackConfig;
 use super::severity::{IssueSeverity, IssueType};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// configuration for issue severity levels
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +17,99 @@ pub struct SeverityConfig {
     pub bad_format: IssueSeverity,
     /// severity for missing version bump when crate was modified
     pub no_version_bump: IssueSeverity,
+    /// severity for publishable crates missing description, license, repository or readme
+    #[serde(default = "default_incomplete_manifest")]
+    pub incomplete_manifest: IssueSeverity,
+    /// severity for rust-version disagreeing with the workspace, dependencies or configured minimum
+    #[serde(default = "default_msrv_mismatch")]
+    pub msrv_mismatch: IssueSeverity,
+    /// severity for features referencing missing dependencies or features, and unexposed optional dependencies
+    #[serde(default = "default_invalid_feature")]
+    pub invalid_feature: IssueSeverity,
+    /// severity for issues reported by `[[checks]]` commands that do not set one
+    #[serde(default = "default_external_check")]
+    pub external_check: IssueSeverity,
+    /// severity for workspace members whose Cargo.lock version differs from Cargo.toml
+    #[serde(default = "default_lockfile_mismatch")]
+    pub lockfile_mismatch: IssueSeverity,
+    /// severity for crates on disk that are not a member of any workspace
+    #[serde(default = "default_unlisted_crate")]
+    pub unlisted_crate: IssueSeverity,
+    /// severity for workspace members entries that match no crate
+    #[serde(default = "default_missing_member")]
+    pub missing_member: IssueSeverity,
+    /// severity for path dependencies of publishable crates without a version requirement
+    #[serde(default = "default_missing_path_dep_version")]
+    pub missing_path_dep_version: IssueSeverity,
+    /// severity for Cargo.toml files discovery skips because they do not parse
+    #[serde(default = "default_broken_manifest")]
+    pub broken_manifest: IssueSeverity,
+    /// severity for crates a check could not be run for
+    #[serde(default = "default_analysis_failed")]
+    pub analysis_failed: IssueSeverity,
+    /// severity for crates still depending on a crate removed between the refs
+    #[serde(default = "default_removed_crate_referenced")]
+    pub removed_crate_referenced: IssueSeverity,
+}
+
+/// used when a severity table in the config file predates incomplete_manifest
+fn default_incomplete_manifest() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates msrv_mismatch
+fn default_msrv_mismatch() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates invalid_feature
+fn default_invalid_feature() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates external_check
+fn default_external_check() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates lockfile_mismatch;
+/// a stale lock file breaks `--locked` builds and publishing either way
+fn default_lockfile_mismatch() -> IssueSeverity {
+    IssueSeverity::Error
+}
+
+/// used when a severity table in the config file predates unlisted_crate
+fn default_unlisted_crate() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates missing_member;
+/// cargo cannot load a workspace with a missing member
+fn default_missing_member() -> IssueSeverity {
+    IssueSeverity::Error
+}
+
+/// used when a severity table in the config file predates missing_path_dep_version
+fn default_missing_path_dep_version() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates broken_manifest
+fn default_broken_manifest() -> IssueSeverity {
+    IssueSeverity::Warning
+}
+
+/// used when a severity table in the config file predates analysis_failed;
+/// a crate that was not checked may hide any other issue
+fn default_analysis_failed() -> IssueSeverity {
+    IssueSeverity::Error
+}
+
+/// used when a severity table in the config file predates
+/// removed_crate_referenced; cargo cannot load a crate whose path dependency
+/// is gone
+fn default_removed_crate_referenced() -> IssueSeverity {
+    IssueSeverity::Error
 }
 
 impl SeverityConfig {
@@ -25,14 +121,29 @@ impl SeverityConfig {
             IssueType::ChangelogNotUpdated => self.changelog_not_updated,
             IssueType::BadFormat => self.bad_format,
             IssueType::NoVersionBump => self.no_version_bump,
+            IssueType::IncompleteManifest => self.incomplete_manifest,
+            IssueType::MsrvMismatch => self.msrv_mismatch,
+            IssueType::InvalidFeature => self.invalid_feature,
+            IssueType::ExternalCheck => self.external_check,
+            IssueType::LockfileMismatch => self.lockfile_mismatch,
+            IssueType::UnlistedCrate => self.unlisted_crate,
+            IssueType::MissingMember => self.missing_member,
+            IssueType::MissingPathDepVersion => self.missing_path_dep_version,
+            IssueType::BrokenManifest => self.broken_manifest,
+            IssueType::AnalysisFailed => self.analysis_failed,
+            IssueType::RemovedCrateReferenced => self.removed_crate_referenced,
         }
     }
 
     /// create default severity config for direct dependencies
     ///
     /// defaults:
-    /// - error: missing changelog, bad format, no version bump
-    /// - warning: missing version entry, changelog not updated
+    /// - error: missing changelog, bad format, no version bump, incomplete manifest,
+    ///   msrv mismatch, invalid feature, external check, lockfile mismatch,
+    ///   missing member, missing path dependency version, analysis failed,
+    ///   removed crate referenced
+    /// - warning: missing version entry, changelog not updated, unlisted crate,
+    ///   broken manifest
     pub fn default_direct() -> Self {
         Self {
             missing_changelog: IssueSeverity::Error,
@@ -40,13 +151,26 @@ impl SeverityConfig {
             changelog_not_updated: IssueSeverity::Warning,
             bad_format: IssueSeverity::Error,
             no_version_bump: IssueSeverity::Error,
+            incomplete_manifest: IssueSeverity::Error,
+            msrv_mismatch: IssueSeverity::Error,
+            invalid_feature: IssueSeverity::Error,
+            external_check: IssueSeverity::Error,
+            lockfile_mismatch: IssueSeverity::Error,
+            unlisted_crate: IssueSeverity::Warning,
+            missing_member: IssueSeverity::Error,
+            missing_path_dep_version: IssueSeverity::Error,
+            broken_manifest: IssueSeverity::Warning,
+            analysis_failed: IssueSeverity::Error,
+            removed_crate_referenced: IssueSeverity::Error,
         }
     }
 
     /// create default severity config for transitive dependencies
     ///
     /// defaults:
-    /// - warning: all issue types
+    /// - error: lockfile mismatch, which breaks builds regardless of what
+    ///   changed, and analysis failed
+    /// - warning: all other issue types
     pub fn default_transitive() -> Self {
         Self {
             missing_changelog: IssueSeverity::Warning,
@@ -54,6 +178,109 @@ impl SeverityConfig {
             changelog_not_updated: IssueSeverity::Warning,
             bad_format: IssueSeverity::Warning,
             no_version_bump: IssueSeverity::Warning,
+            incomplete_manifest: IssueSeverity::Warning,
+            msrv_mismatch: IssueSeverity::Warning,
+            invalid_feature: IssueSeverity::Warning,
+            external_check: IssueSeverity::Warning,
+            lockfile_mismatch: IssueSeverity::Error,
+            unlisted_crate: IssueSeverity::Warning,
+            missing_member: IssueSeverity::Warning,
+            missing_path_dep_version: IssueSeverity::Warning,
+            broken_manifest: IssueSeverity::Warning,
+            analysis_failed: IssueSeverity::Error,
+            removed_crate_referenced: IssueSeverity::Warning,
+        }
+    }
+
+    fn uniform(severity: IssueSeverity) -> Self {
+        Self {
+            missing_changelog: severity,
+            missing_version_entry: severity,
+            changelog_not_updated: severity,
+            bad_format: severity,
+            no_version_bump: severity,
+            incomplete_manifest: severity,
+            msrv_mismatch: severity,
+            invalid_feature: severity,
+            external_check: severity,
+            lockfile_mismatch: severity,
+            unlisted_crate: severity,
+            missing_member: severity,
+            missing_path_dep_version: severity,
+            broken_manifest: severity,
+            analysis_failed: severity,
+            removed_crate_referenced: severity,
+        }
+    }
+
+    // a stale lock file breaks `--locked` builds and a crate that was not
+    // checked may hide anything, so both stay errors
+    fn lenient() -> Self {
+        Self {
+            lockfile_mismatch: IssueSeverity::Error,
+            analysis_failed: IssueSeverity::Error,
+            ..Self::uniform(IssueSeverity::Warning)
+        }
+    }
+}
+
+/// named pairs of direct and transitive severity tables, for configs that do
+/// not want to list every issue type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityPreset {
+    /// every issue is an error, in transitive crates too
+    Strict,
+    /// the defaults, see [`SeverityConfig::default_direct`] and
+    /// [`SeverityConfig::default_transitive`]
+    #[default]
+    Standard,
+    /// every issue is a warning except a lockfile mismatch and a failed analysis
+    Lenient,
+}
+
+impl SeverityPreset {
+    /// severity table for crates that changed themselves
+    pub fn direct(self) -> SeverityConfig {
+        match self {
+            SeverityPreset::Strict => SeverityConfig::uniform(IssueSeverity::Error),
+            SeverityPreset::Standard => SeverityConfig::default_direct(),
+            SeverityPreset::Lenient => SeverityConfig::lenient(),
+        }
+    }
+
+    /// severity table for crates affected through their dependencies
+    pub fn transitive(self) -> SeverityConfig {
+        match self {
+            SeverityPreset::Strict => SeverityConfig::uniform(IssueSeverity::Error),
+            SeverityPreset::Standard => SeverityConfig::default_transitive(),
+            SeverityPreset::Lenient => SeverityConfig::lenient(),
+        }
+    }
+}
+
+impl std::fmt::Display for SeverityPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeverityPreset::Strict => write!(f, "strict"),
+            SeverityPreset::Standard => write!(f, "standard"),
+            SeverityPreset::Lenient => write!(f, "lenient"),
+        }
+    }
+}
+
+impl std::str::FromStr for SeverityPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(SeverityPreset::Strict),
+            "standard" => Ok(SeverityPreset::Standard),
+            "lenient" => Ok(SeverityPreset::Lenient),
+            _ => Err(format!(
+                "invalid severity preset: {}, use 'strict', 'standard' or 'lenient'",
+                s
+            )),
         }
     }
 }
@@ -63,3 +290,108 @@ impl Default for SeverityConfig {
         Self::default_direct()
     }
 }
+
+/// the severity table a crate's issues are graded with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityScope {
+    /// `direct_severity`, for crates that changed themselves
+    Direct,
+    /// `transitive_severity`, for crates affected through their dependencies
+    Transitive,
+}
+
+/// an issue type a report can contain and the severity it gets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityRule {
+    /// the issue type, as in the `issue_type` of report issues
+    pub rule: String,
+    /// the checks that ran and report it
+    pub checks: Vec<String>,
+    pub direct: IssueSeverity,
+    pub transitive: IssueSeverity,
+}
+
+/// the severities a report was graded with, so its readers can tell why an
+/// issue is an error in one repository and a warning in another without the
+/// config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeverityMetadata {
+    pub preset: SeverityPreset,
+    /// runs a warning has to keep coming back for to become an error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalate_after: Option<usize>,
+    /// the rules of the checks that ran
+    pub rules: Vec<SeverityRule>,
+    /// the table each checked crate is graded with, by display name
+    pub crates: BTreeMap<String, SeverityScope>,
+}
+
+impl SeverityMetadata {
+    /// the rules of `checks` with the severities of `config`; crates in
+    /// `direct` are graded with `direct_severity`, the rest of `affected`
+    /// with `transitive_severity`
+    pub fn new(
+        config: &DeptrackConfig,
+        checks: &[&str],
+        affected: &[CrateId],
+        direct: &[CrateId],
+    ) -> Self {
+        let rules = IssueType::ALL
+            .into_iter()
+            .filter_map(|issue_type| {
+                let ran: Vec<String> = issue_type
+                    .checks()
+                    .iter()
+                    .filter(|check| checks.contains(check))
+                    .map(|check| check.to_string())
+                    .collect();
+                if ran.is_empty() {
+                    return None;
+                }
+                let direct = config.direct_severity.get_severity(issue_type);
+                // membership grades every crate as a direct one
+                let transitive = if ran == ["membership"] {
+                    direct
+                } else {
+                    config.transitive_severity.get_severity(issue_type)
+                };
+                Some(SeverityRule {
+                    rule: issue_type.to_string(),
+                    checks: ran,
+                    direct,
+                    transitive,
+                })
+            })
+            .collect();
+
+        let crates = affected
+            .iter()
+            .map(|id| {
+                let scope = if direct.contains(id) {
+                    SeverityScope::Direct
+                } else {
+                    SeverityScope::Transitive
+                };
+                (id.display_name(), scope)
+            })
+            .collect();
+
+        SeverityMetadata {
+            preset: config.severity.preset,
+            escalate_after: config.severity.escalate_after,
+            rules,
+            crates,
+        }
+    }
+
+    /// the severity `issue_type` gets in `crate_name`, None for a rule or
+    /// crate the report did not check
+    pub fn resolve(&self, crate_name: &str, issue_type: &str) -> Option<IssueSeverity> {
+        let rule = self.rules.iter().find(|rule| rule.rule == issue_type)?;
+        match self.crates.get(crate_name)? {
+            SeverityScope::Direct => Some(rule.direct),
+            SeverityScope::Transitive => Some(rule.transitive),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+// column tables for human output, with ansi colors and wrapping to the terminal
+
+use crate::utils::severity::IssueSeverity;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// spaces in front of every table line
+const INDENT: usize = 2;
+
+/// spaces between columns
+const GAP: usize = 2;
+
+/// narrowest a column gets when the table is squeezed to fit the terminal
+const MIN_WRAP_WIDTH: usize = 8;
+
+static COLOR_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// turn colors off for the rest of the process, for `--no-color`
+pub fn disable_color() {
+    COLOR_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// whether human output is colored: not disabled, NO_COLOR unset (or empty)
+/// and stdout a terminal
+pub fn color_enabled() -> bool {
+    !COLOR_DISABLED.load(Ordering::Relaxed)
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+/// columns of the terminal stdout is attached to, from COLUMNS or `stty size`;
+/// None when stdout is not a terminal or the width is unknown
+pub fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.trim().parse().ok())
+    {
+        return Some(columns);
+    }
+    stty_width()
+}
+
+#[cfg(unix)]
+fn stty_width() -> Option<usize> {
+    let tty = std::fs::File::open("/dev/tty").ok()?;
+    let output = std::process::Command::new("stty")
+        .arg("size")
+        .stdin(tty)
+        .output()
+        .ok()?;
+    // "<rows> <columns>"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn stty_width() -> Option<usize> {
+    None
+}
+
+/// colors used for status cells and messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Dim,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Dim => "2",
+        }
+    }
+
+    /// `text` wrapped in this color's escape codes
+    pub fn apply(self, text: &str) -> String {
+        format!("\x1b[{}m{}\x1b[0m", self.code(), text)
+    }
+}
+
+/// color of an issue of `severity`
+pub fn severity_color(severity: IssueSeverity) -> Color {
+    match severity {
+        IssueSeverity::Error => Color::Red,
+        IssueSeverity::Warning => Color::Yellow,
+    }
+}
+
+/// `text` in `color` when colors are enabled, unchanged otherwise
+pub fn paint(text: &str, color: Color) -> String {
+    if color_enabled() {
+        color.apply(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// alignment of a column's cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+/// a table cell, optionally colored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    header: String,
+    align: Align,
+    min_width: usize,
+}
+
+/// table with a header and a dashed separator, columns sized to their widest
+/// cell; wider than the terminal, the widest columns are narrowed and their
+/// cells wrapped
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            columns: headers
+                .into_iter()
+                .map(|header| Column {
+                    header: header.into(),
+                    align: Align::Left,
+                    min_width: 0,
+                })
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        if let Some(column) = self.columns.get_mut(column) {
+            column.align = align;
+        }
+        self
+    }
+
+    /// keep `column` at least `width` wide, even when its cells are narrower
+    pub fn min_width(mut self, column: usize, width: usize) -> Self {
+        if let Some(column) = self.columns.get_mut(column) {
+            column.min_width = width;
+        }
+        self
+    }
+
+    /// append a row; missing cells are left empty, extra ones dropped
+    pub fn row<I, C>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Cell>,
+    {
+        let mut row: Vec<Cell> = cells
+            .into_iter()
+            .map(Into::into)
+            .take(self.columns.len())
+            .collect();
+        row.resize(self.columns.len(), Cell::new(""));
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// print to stdout, fitted to the terminal and colored when enabled
+    pub fn print(&self) {
+        print!("{}", self.render(terminal_width(), color_enabled()));
+    }
+
+    /// the table as text, fitted into `max_width` columns when given
+    pub fn render(&self, max_width: Option<usize>, color: bool) -> String {
+        let widths = self.widths(max_width);
+        let mut out = String::new();
+
+        let header: Vec<Cell> = self
+            .columns
+            .iter()
+            .map(|c| Cell::new(c.header.clone()))
+            .collect();
+        self.render_row(&mut out, &header, &widths, false);
+        let separator: Vec<Cell> = widths.iter().map(|w| Cell::new("-".repeat(*w))).collect();
+        self.render_row(&mut out, &separator, &widths, false);
+
+        for row in &self.rows {
+            self.render_row(&mut out, row, &widths, color);
+        }
+        out
+    }
+
+    // natural column widths, narrowed widest first until the table fits
+    fn widths(&self, max_width: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                self.rows
+                    .iter()
+                    .map(|row| text_width(&row[i].text))
+                    .chain([text_width(&column.header), column.min_width])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let Some(max_width) = max_width else {
+            return widths;
+        };
+        let available =
+            max_width.saturating_sub(INDENT + GAP * self.columns.len().saturating_sub(1));
+        let floor = |i: usize| text_width(&self.columns[i].header).max(MIN_WRAP_WIDTH);
+        while widths.iter().sum::<usize>() > available {
+            let widest = (0..widths.len())
+                .filter(|&i| widths[i] > floor(i))
+                .max_by_key(|&i| widths[i]);
+            match widest {
+                Some(i) => widths[i] -= 1,
+                None => break,
+            }
+        }
+        widths
+    }
+
+    fn render_row(&self, out: &mut String, cells: &[Cell], widths: &[usize], color: bool) {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| wrap(&cell.text, *width))
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+
+        for line in 0..height {
+            let mut text = " ".repeat(INDENT);
+            for (i, column) in self.columns.iter().enumerate() {
+                if i > 0 {
+                    text.push_str(&" ".repeat(GAP));
+                }
+                let piece = wrapped[i].get(line).map(String::as_str).unwrap_or("");
+                let padding = " ".repeat(widths[i].saturating_sub(text_width(piece)));
+                let piece = match cells[i].color {
+                    Some(c) if color && !piece.is_empty() => c.apply(piece),
+                    _ => piece.to_string(),
+                };
+                match column.align {
+                    Align::Left => {
+                        text.push_str(&piece);
+                        text.push_str(&padding);
+                    }
+                    Align::Right => {
+                        text.push_str(&padding);
+                        text.push_str(&piece);
+                    }
+                }
+            }
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+// break `text` into lines of at most `width` characters at spaces, splitting
+// words longer than a line
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if width == 0 || text_width(text) <= width {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !line.is_empty() && text_width(&line) + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        if !word.is_empty() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new(["Crate", "Status", "Issue"]).align(1, Align::Right);
+        table.row([
+            Cell::new("ws::core"),
+            Cell::new("OK").color(Color::Green),
+            Cell::new("all fine"),
+        ]);
+        table.row([
+            Cell::new("ws::app"),
+            Cell::new("NEEDED").color(Color::Red),
+            Cell::new("version not bumped (current: 0.1.0, base: 0.1.0)"),
+        ]);
+        table
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            table().render(None, false),
+            "  Crate     Status  Issue\n  --------  ------  ------------------------------------------------\n  ws::core      OK  all fine\n  ws::app   NEEDED  version not bumped (current: 0.1.0, base: 0.1.0)\n"
+        );
+
+        let colored = table().render(None, true);
+        assert!(colored.contains("    \x1b[32mOK\x1b[0m  all fine"));
+        assert!(colored.contains("\x1b[31mNEEDED\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_wraps_to_width() {
+        let rendered = table().min_width(0, 10).render(Some(40), false);
+        assert_eq!(
+            rendered,
+            "  Crate       Status  Issue\n  ----------  ------  ------------------\n  ws::core        OK  all fine\n  ws::app     NEEDED  version not bumped\n                      (current: 0.1.0,\n                      base: 0.1.0)\n"
+        );
+        assert!(rendered.lines().all(|line| line.len() <= 40));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("short", 10), vec!["short"]);
+        assert_eq!(wrap("a bb ccc", 4), vec!["a bb", "ccc"]);
+        assert_eq!(wrap("abcdefghij k", 4), vec!["abcd", "efgh", "ij k"]);
+        assert_eq!(wrap("", 4), vec![""]);
+    }
+}
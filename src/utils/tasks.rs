@@ -0,0 +1,171 @@
+// outstanding issues of a report as a checklist per owner, for pasting into
+// a release tracking issue
+
+use super::cargo_ops::fingerprint::relative;
+use super::cargo_ops::types::CrateDependencyGraph;
+use super::codeowners::CodeOwners;
+use super::git_ops::{AuthorSummary, GitOps};
+use super::report::UnifiedReport;
+use super::report_compare::ReportIssue;
+use super::severity::IssueSeverity;
+use super::table::{paint, severity_color};
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// owner of crates nobody could be found for
+pub const UNOWNED: &str = "unowned";
+
+/// how the owner of a group of tasks was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerSource {
+    /// a CODEOWNERS rule covering the crate directory
+    Codeowners,
+    /// the author of most commits on the crate in the range
+    Commits,
+    /// neither
+    None,
+}
+
+/// the tasks of one owner
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerTasks {
+    pub owner: String,
+    pub source: OwnerSource,
+    /// errors first, then by crate
+    pub tasks: Vec<ReportIssue>,
+}
+
+/// issues of a [`UnifiedReport`] grouped by the owner of their crate
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskList {
+    pub from: String,
+    pub to: String,
+    /// owners by name, [`UNOWNED`] last
+    pub owners: Vec<OwnerTasks>,
+}
+
+impl TaskList {
+    /// the issues of `report`, each assigned to the first CODEOWNERS owner of
+    /// its crate, or else to the author of most commits on the crate between
+    /// the report's references; warnings are left out with `errors_only`
+    pub fn from_report<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        report: &UnifiedReport,
+        errors_only: bool,
+    ) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let root = GitOps::detect_repository_root(repo_path)?;
+        let codeowners = CodeOwners::load(&root)?.unwrap_or_default();
+        let crates: HashMap<String, &Path> = graph
+            .crates
+            .iter()
+            .map(|(id, info)| (id.display_name(), info.path.as_path()))
+            .collect();
+
+        let mut crate_owners: HashMap<&str, (String, OwnerSource)> = HashMap::new();
+        let mut groups: BTreeMap<(bool, String), OwnerTasks> = BTreeMap::new();
+        for issue in &report.issues {
+            if errors_only && issue.severity != IssueSeverity::Error {
+                continue;
+            }
+            if !crate_owners.contains_key(issue.crate_name.as_str()) {
+                let owner = match crates.get(&issue.crate_name) {
+                    Some(path) => owner_of(&codeowners, &root, repo_path, report, path)?,
+                    None => (UNOWNED.to_string(), OwnerSource::None),
+                };
+                crate_owners.insert(&issue.crate_name, owner);
+            }
+            let (owner, source) = &crate_owners[issue.crate_name.as_str()];
+            groups
+                .entry((*source == OwnerSource::None, owner.clone()))
+                .or_insert_with(|| OwnerTasks {
+                    owner: owner.clone(),
+                    source: *source,
+                    tasks: Vec::new(),
+                })
+                .tasks
+                .push(issue.clone());
+        }
+
+        let mut owners: Vec<OwnerTasks> = groups.into_values().collect();
+        for owner in &mut owners {
+            owner.tasks.sort_by(|a, b| {
+                (a.severity != IssueSeverity::Error, &a.crate_name)
+                    .cmp(&(b.severity != IssueSeverity::Error, &b.crate_name))
+            });
+        }
+        Ok(Self {
+            from: report.from.clone(),
+            to: report.to.clone(),
+            owners,
+        })
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.owners.iter().map(|owner| owner.tasks.len()).sum()
+    }
+
+    /// a `### owner` heading and a `- [ ]` item per task for every owner
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## Release tasks for {}..{}\n", self.from, self.to);
+        if self.owners.is_empty() {
+            out.push_str("\nNo outstanding issues.\n");
+            return out;
+        }
+        for owner in &self.owners {
+            out.push_str(&format!("\n### {}\n\n", owner.owner));
+            for task in &owner.tasks {
+                out.push_str(&format!(
+                    "- [ ] `{}` ({}, {}): {}\n",
+                    task.crate_name, task.severity, task.check, task.message
+                ));
+            }
+        }
+        out
+    }
+
+    pub fn display(&self) {
+        if self.owners.is_empty() {
+            println!("no outstanding issues for {}..{}", self.from, self.to);
+            return;
+        }
+        for (i, owner) in self.owners.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("{} ({} tasks):", owner.owner, owner.tasks.len());
+            for task in &owner.tasks {
+                println!(
+                    "  {} [{}] {}: {}",
+                    paint(&task.severity.to_string(), severity_color(task.severity)),
+                    task.check,
+                    task.crate_name,
+                    task.message
+                );
+            }
+        }
+    }
+}
+
+// first CODEOWNERS owner of the crate directory, else its most frequent
+// committer in the report's range
+fn owner_of(
+    codeowners: &CodeOwners,
+    root: &Path,
+    repo_path: &Path,
+    report: &UnifiedReport,
+    crate_path: &Path,
+) -> Result<(String, OwnerSource)> {
+    if let Some(owner) = codeowners.owners_of(relative(crate_path, root)).first() {
+        return Ok((owner.clone(), OwnerSource::Codeowners));
+    }
+    let commits = GitOps::commits_in_range(repo_path, &report.from, &report.to, crate_path)?;
+    Ok(match AuthorSummary::of(&commits).into_iter().next() {
+        Some(author) => (author.author, OwnerSource::Commits),
+        None => (UNOWNED.to_string(), OwnerSource::None),
+    })
+}
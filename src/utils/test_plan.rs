@@ -0,0 +1,401 @@
+use super::cargo_ops::ChangeImpactAnalysis;
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId, CrateInfo};
+use crate::error::Result;
+use crate::utils::toml_ops::{TomlDocument, TomlReader};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// kind of test target cargo builds for a crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestTargetKind {
+    /// unit tests of the library
+    Lib,
+    /// unit tests of a binary
+    Bin,
+    /// a file or directory under tests/ (or a `[[test]]` entry)
+    Integration,
+    /// doc tests of the library
+    Doc,
+}
+
+impl fmt::Display for TestTargetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestTargetKind::Lib => write!(f, "lib"),
+            TestTargetKind::Bin => write!(f, "bin"),
+            TestTargetKind::Integration => write!(f, "integration"),
+            TestTargetKind::Doc => write!(f, "doc"),
+        }
+    }
+}
+
+/// a single test target of a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestTarget {
+    pub kind: TestTargetKind,
+    /// target name, the crate name for lib, unit and doc tests
+    pub name: String,
+    /// root source file relative to the crate directory
+    pub path: PathBuf,
+}
+
+/// test targets of one affected crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateTestPlan {
+    pub crate_id: CrateId,
+    /// the crate itself changed, rather than one of its dependencies
+    pub directly_affected: bool,
+    pub targets: Vec<TestTarget>,
+}
+
+/// cargo test invocation covering the affected crates of one workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct TestCommand {
+    pub workspace: String,
+    /// workspace manifest relative to the repository, None at the root
+    pub manifest_path: Option<PathBuf>,
+    pub packages: Vec<String>,
+}
+
+impl fmt::Display for TestCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cargo test")?;
+        if let Some(manifest_path) = &self.manifest_path {
+            write!(f, " --manifest-path {}", manifest_path.display())?;
+        }
+        for package in &self.packages {
+            write!(f, " -p {}", package)?;
+        }
+        Ok(())
+    }
+}
+
+/// tests to run for a set of changes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TestPlan {
+    /// affected crates sorted by name
+    pub crates: Vec<CrateTestPlan>,
+    /// one command per workspace, crates without test targets left out
+    pub commands: Vec<TestCommand>,
+}
+
+impl TestPlan {
+    /// check if no affected crate has anything to test
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// number of test targets across affected crates
+    pub fn target_count(&self) -> usize {
+        self.crates.iter().map(|c| c.targets.len()).sum()
+    }
+
+    /// display the affected crates with their targets, then the commands
+    pub fn display(&self) {
+        for crate_plan in &self.crates {
+            let reason = if crate_plan.directly_affected {
+                "changed"
+            } else {
+                "dependency changed"
+            };
+            println!("{} ({}):", crate_plan.crate_id.display_name(), reason);
+            if crate_plan.targets.is_empty() {
+                println!("  no test targets");
+            }
+            for target in &crate_plan.targets {
+                println!(
+                    "  {:<12} {} ({})",
+                    target.kind.to_string(),
+                    target.name,
+                    target.path.display()
+                );
+            }
+        }
+
+        if self.is_empty() {
+            println!("nothing to test");
+            return;
+        }
+        println!();
+        for command in &self.commands {
+            println!("{}", command);
+        }
+    }
+}
+
+/// maps affected crates to their test targets and cargo test commands
+pub struct TestPlanner;
+
+impl TestPlanner {
+    /// test targets of a crate, following cargo's target auto-discovery and
+    /// the `[lib]`, `[[bin]]` and `[[test]]` tables
+    pub fn test_targets(crate_info: &CrateInfo) -> Result<Vec<TestTarget>> {
+        let document = TomlReader::read_manifest(&crate_info.cargo_toml_path)?;
+        let root = &crate_info.path;
+        let name = &crate_info.id.name;
+        let flag = |path: &str, default: bool| {
+            document
+                .get_path(path)
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(default)
+        };
+
+        let mut targets = Vec::new();
+
+        // library: unit tests and doc tests
+        let lib_path = document
+            .get_path("lib.path")
+            .and_then(toml::Value::as_str)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("src/lib.rs"));
+        if root.join(&lib_path).is_file() {
+            if flag("lib.test", true) {
+                targets.push(TestTarget {
+                    kind: TestTargetKind::Lib,
+                    name: name.clone(),
+                    path: lib_path.clone(),
+                });
+            }
+            if flag("lib.doctest", true) {
+                targets.push(TestTarget {
+                    kind: TestTargetKind::Doc,
+                    name: name.clone(),
+                    path: lib_path,
+                });
+            }
+        }
+
+        // binaries: explicit entries, then src/main.rs and src/bin/
+        let mut bins = Self::explicit_targets(&document, "bin");
+        if flag("package.autobins", true) {
+            if root.join("src/main.rs").is_file() {
+                bins.push((name.clone(), PathBuf::from("src/main.rs"), true));
+            }
+            bins.extend(Self::discover(root, "src/bin"));
+        }
+        targets.extend(Self::merge(bins, TestTargetKind::Bin));
+
+        // integration tests: explicit entries, then tests/
+        let mut tests = Self::explicit_targets(&document, "test");
+        if flag("package.autotests", true) {
+            tests.extend(Self::discover(root, "tests"));
+        }
+        targets.extend(Self::merge(tests, TestTargetKind::Integration));
+
+        Ok(targets)
+    }
+
+    /// test plan for the crates affected by `impact`
+    ///
+    /// `repo_root` is used to make workspace manifest paths relative
+    pub fn plan<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        impact: &ChangeImpactAnalysis,
+        repo_root: P,
+    ) -> Result<TestPlan> {
+        let repo_root = repo_root.as_ref();
+        let mut plan = TestPlan::default();
+
+        let mut affected: Vec<&CrateId> = impact.all_affected_crates.iter().collect();
+        affected.sort_by_key(|id| id.display_name());
+        affected.dedup();
+
+        let mut packages: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for crate_id in affected {
+            let Some(crate_info) = graph.crates.get(crate_id) else {
+                continue;
+            };
+            let targets = Self::test_targets(crate_info)?;
+            if !targets.is_empty() {
+                packages
+                    .entry(crate_id.workspace.as_str())
+                    .or_default()
+                    .push(crate_id.name.clone());
+            }
+            plan.crates.push(CrateTestPlan {
+                crate_id: crate_id.clone(),
+                directly_affected: impact.directly_affected_crates.contains(crate_id),
+                targets,
+            });
+        }
+
+        for (workspace, packages) in packages {
+            let manifest_path = graph
+                .workspaces
+                .iter()
+                .find(|w| w.name == workspace)
+                .map(|w| relative_to(&w.cargo_toml_path, repo_root))
+                .filter(|path| path != Path::new("Cargo.toml"));
+            plan.commands.push(TestCommand {
+                workspace: workspace.to_string(),
+                manifest_path,
+                packages,
+            });
+        }
+
+        Ok(plan)
+    }
+
+    // `[[bin]]` / `[[test]]` entries as (name, path, test enabled)
+    fn explicit_targets(document: &TomlDocument, table: &str) -> Vec<(String, PathBuf, bool)> {
+        let entries = document.get_path(table).and_then(toml::Value::as_array);
+        entries
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.get("name")?.as_str()?.to_string();
+                let path = entry
+                    .get("path")
+                    .and_then(toml::Value::as_str)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| {
+                        let dir = if table == "bin" { "src/bin" } else { "tests" };
+                        PathBuf::from(format!("{}/{}.rs", dir, name))
+                    });
+                let test = entry
+                    .get("test")
+                    .and_then(toml::Value::as_bool)
+                    .unwrap_or(true);
+                Some((name, path, test))
+            })
+            .collect()
+    }
+
+    // `<dir>/<name>.rs` and `<dir>/<name>/main.rs` targets, sorted by name
+    fn discover(root: &Path, dir: &str) -> Vec<(String, PathBuf, bool)> {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            return Vec::new();
+        };
+        let mut found: Vec<(String, PathBuf, bool)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if path.is_file() {
+                    let name = file_name.strip_suffix(".rs")?;
+                    Some((name.to_string(), Path::new(dir).join(&file_name), true))
+                } else if path.join("main.rs").is_file() {
+                    Some((
+                        file_name.clone(),
+                        Path::new(dir).join(file_name).join("main.rs"),
+                        true,
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    // explicit entries win over discovered ones with the same name
+    fn merge(candidates: Vec<(String, PathBuf, bool)>, kind: TestTargetKind) -> Vec<TestTarget> {
+        let mut seen = std::collections::HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|(name, _, _)| seen.insert(name.clone()))
+            .filter(|(_, _, test)| *test)
+            .map(|(name, path, _)| TestTarget { kind, name, path })
+            .collect()
+    }
+}
+
+// `path` relative to `root` when it lives below it, unchanged otherwise
+pub(crate) fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    canonical(path)
+        .strip_prefix(canonical(root))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_test_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "tool"
+            version = "0.1.0"
+
+            [lib]
+            doctest = false
+
+            [[bin]]
+            name = "helper"
+            path = "src/helper.rs"
+            test = false
+
+            [[test]]
+            name = "slow"
+            path = "checks/slow.rs"
+            "#,
+        );
+        write(root, "src/lib.rs", "");
+        write(root, "src/main.rs", "fn main() {}");
+        write(root, "src/bin/extra.rs", "fn main() {}");
+        write(root, "tests/api.rs", "");
+        write(root, "tests/suite/main.rs", "");
+        write(root, "tests/common/mod.rs", "");
+
+        let crate_info = CrateInfo::new(
+            CrateId::new("ws".to_string(), "tool".to_string()),
+            "0.1.0".to_string(),
+            root.to_path_buf(),
+        );
+        let targets: Vec<(TestTargetKind, String)> = TestPlanner::test_targets(&crate_info)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, t.name))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                (TestTargetKind::Lib, "tool".to_string()),
+                (TestTargetKind::Bin, "tool".to_string()),
+                (TestTargetKind::Bin, "extra".to_string()),
+                (TestTargetKind::Integration, "slow".to_string()),
+                (TestTargetKind::Integration, "api".to_string()),
+                (TestTargetKind::Integration, "suite".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let command = TestCommand {
+            workspace: "ws".to_string(),
+            manifest_path: None,
+            packages: vec!["a".to_string(), "b".to_string()],
+        };
+        assert_eq!(command.to_string(), "cargo test -p a -p b");
+
+        let command = TestCommand {
+            manifest_path: Some(PathBuf::from("tools/Cargo.toml")),
+            ..command
+        };
+        assert_eq!(
+            command.to_string(),
+            "cargo test --manifest-path tools/Cargo.toml -p a -p b"
+        );
+    }
+}
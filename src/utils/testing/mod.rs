@@ -3,5 +3,7 @@
 pub mod repo_builder;
 pub mod scenarios;
 
-pub use repo_builder::{TestCrate, TestRepoBuilder, TestRepository, TestWorkspace};
+pub use repo_builder::{
+    ChangelogFixture, TestCrate, TestRepoBuilder, TestRepository, TestWorkspace,
+};
 pub use scenarios::TestScenario;
@@ -1,17 +1,56 @@
 // builder for creating test repositories
 
+use crate::utils::toml_ops::TomlWriter;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+/// CHANGELOG.md content written for a test crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangelogFixture {
+    /// well-formed changelog with an entry for the crate's version
+    Valid,
+    /// no header, an unknown change type and an empty description
+    Malformed,
+    /// well-formed, but only lists 0.0.1 so the current version has no entry
+    MissingVersionEntry,
+    /// verbatim content
+    Custom(String),
+}
+
+impl ChangelogFixture {
+    /// changelog text for a crate at `version`
+    pub fn render(&self, version: &str) -> String {
+        match self {
+            ChangelogFixture::Valid => format!(
+                "# CHANGELOG\n\n## [{}]\n\n* feat(core): initial release\n",
+                version
+            ),
+            ChangelogFixture::Malformed => {
+                format!(
+                    "## [{}]\n\n* banana(core): unknown type\n* fix(core):\n",
+                    version
+                )
+            }
+            ChangelogFixture::MissingVersionEntry => {
+                "# CHANGELOG\n\n## [0.0.1]\n\n* feat(core): prototype\n".to_string()
+            }
+            ChangelogFixture::Custom(content) => content.clone(),
+        }
+    }
+}
+
 /// represents a test crate to be created
 #[derive(Debug, Clone)]
 pub struct TestCrate {
     pub name: String,
     pub version: String,
     pub dependencies: Vec<String>,
+    pub dev_dependencies: Vec<String>,
     pub files: HashMap<String, String>, // relative path -> content
+    pub inherit_version: bool,
+    pub changelog: Option<ChangelogFixture>,
 }
 
 impl TestCrate {
@@ -20,7 +59,10 @@ impl TestCrate {
             name: name.into(),
             version: "0.1.0".to_string(),
             dependencies: Vec::new(),
+            dev_dependencies: Vec::new(),
             files: HashMap::new(),
+            inherit_version: false,
+            changelog: None,
         }
     }
 
@@ -29,11 +71,28 @@ impl TestCrate {
         self
     }
 
+    /// write `version.workspace = true` instead of an explicit version
+    pub fn workspace_version(mut self) -> Self {
+        self.inherit_version = true;
+        self
+    }
+
     pub fn dependency(mut self, dep: impl Into<String>) -> Self {
         self.dependencies.push(dep.into());
         self
     }
 
+    pub fn dev_dependency(mut self, dep: impl Into<String>) -> Self {
+        self.dev_dependencies.push(dep.into());
+        self
+    }
+
+    /// write a CHANGELOG.md for the crate
+    pub fn changelog(mut self, fixture: ChangelogFixture) -> Self {
+        self.changelog = Some(fixture);
+        self
+    }
+
     pub fn file(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
         self.files.insert(path.into(), content.into());
         self
@@ -45,6 +104,7 @@ impl TestCrate {
 pub struct TestWorkspace {
     pub name: String,
     pub crates: Vec<TestCrate>,
+    pub package_version: Option<String>,
 }
 
 impl TestWorkspace {
@@ -52,21 +112,53 @@ impl TestWorkspace {
         Self {
             name: name.into(),
             crates: Vec::new(),
+            package_version: None,
         }
     }
 
+    /// version shared through [workspace.package], see `TestCrate::workspace_version`
+    pub fn package_version(mut self, version: impl Into<String>) -> Self {
+        self.package_version = Some(version.into());
+        self
+    }
+
     pub fn crate_entry(mut self, crate_def: TestCrate) -> Self {
         self.crates.push(crate_def);
         self
     }
 }
 
+/// history step replayed on top of the initial commit
+#[derive(Debug, Clone)]
+enum RepoStep {
+    Commit(String),
+    Branch(String),
+    Checkout(String),
+    Tag(String),
+    ModifyCrate {
+        crate_name: String,
+        rel_path: String,
+        content: String,
+    },
+    BumpVersion {
+        crate_name: String,
+        version: String,
+    },
+    TouchCode(String),
+    AddChangelogEntry {
+        crate_name: String,
+        version: String,
+        entry: String,
+    },
+}
+
 /// builder for test repositories
 pub struct TestRepoBuilder {
     workspaces: Vec<TestWorkspace>,
     temp_dir: Option<TempDir>,
     use_temp: bool,
     git_init: bool,
+    steps: Vec<RepoStep>,
 }
 
 impl TestRepoBuilder {
@@ -77,6 +169,7 @@ impl TestRepoBuilder {
             temp_dir: None,
             use_temp: true,
             git_init: true,
+            steps: Vec::new(),
         }
     }
 
@@ -87,6 +180,7 @@ impl TestRepoBuilder {
             temp_dir: None,
             use_temp: false,
             git_init: true,
+            steps: Vec::new(),
         }
     }
 
@@ -102,6 +196,79 @@ impl TestRepoBuilder {
         self
     }
 
+    /// commit all pending changes after the initial commit
+    pub fn commit(mut self, message: impl Into<String>) -> Self {
+        self.steps.push(RepoStep::Commit(message.into()));
+        self
+    }
+
+    /// create a branch at the current commit and switch to it
+    pub fn branch(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(RepoStep::Branch(name.into()));
+        self
+    }
+
+    /// switch to an existing branch
+    pub fn checkout(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(RepoStep::Checkout(name.into()));
+        self
+    }
+
+    /// tag the current commit
+    pub fn tag(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(RepoStep::Tag(name.into()));
+        self
+    }
+
+    /// overwrite (or create) a file in a crate, left uncommitted until `commit`
+    pub fn modify_crate(
+        mut self,
+        crate_name: impl Into<String>,
+        rel_path: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.steps.push(RepoStep::ModifyCrate {
+            crate_name: crate_name.into(),
+            rel_path: rel_path.into(),
+            content: content.into(),
+        });
+        self
+    }
+
+    /// set a crate's package version, left uncommitted until `commit`
+    pub fn bump_version(
+        mut self,
+        crate_name: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        self.steps.push(RepoStep::BumpVersion {
+            crate_name: crate_name.into(),
+            version: version.into(),
+        });
+        self
+    }
+
+    /// change a crate's source without touching its changelog, left uncommitted until `commit`
+    pub fn touch_code(mut self, crate_name: impl Into<String>) -> Self {
+        self.steps.push(RepoStep::TouchCode(crate_name.into()));
+        self
+    }
+
+    /// add an entry to a crate's changelog, left uncommitted until `commit`
+    pub fn add_changelog_entry(
+        mut self,
+        crate_name: impl Into<String>,
+        version: impl Into<String>,
+        entry: impl Into<String>,
+    ) -> Self {
+        self.steps.push(RepoStep::AddChangelogEntry {
+            crate_name: crate_name.into(),
+            version: version.into(),
+            entry: entry.into(),
+        });
+        self
+    }
+
     /// build the repository and return the path
     pub fn build(mut self) -> Result<TestRepository, Box<dyn std::error::Error>> {
         let repo_path = if self.use_temp {
@@ -133,11 +300,37 @@ impl TestRepoBuilder {
             Self::create_commit(&repo_path, "Initial commit")?;
         }
 
-        Ok(TestRepository {
+        let repository = TestRepository {
             path: repo_path,
             _temp_dir: self.temp_dir,
             workspaces: self.workspaces,
-        })
+        };
+
+        for step in &self.steps {
+            match step {
+                RepoStep::Commit(message) => repository.commit(message)?,
+                RepoStep::Branch(name) => repository.branch(name)?,
+                RepoStep::Checkout(name) => repository.checkout(name)?,
+                RepoStep::Tag(name) => repository.tag(name)?,
+                RepoStep::ModifyCrate {
+                    crate_name,
+                    rel_path,
+                    content,
+                } => repository.modify_crate(crate_name, rel_path, content)?,
+                RepoStep::BumpVersion {
+                    crate_name,
+                    version,
+                } => repository.bump_version(crate_name, version)?,
+                RepoStep::TouchCode(crate_name) => repository.touch_code(crate_name)?,
+                RepoStep::AddChangelogEntry {
+                    crate_name,
+                    version,
+                    entry,
+                } => repository.add_changelog_entry(crate_name, version, entry)?,
+            }
+        }
+
+        Ok(repository)
     }
 
     fn init_git(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
@@ -195,7 +388,7 @@ impl TestRepoBuilder {
             .map(|c| c.name.to_string())
             .collect();
 
-        let workspace_toml = format!(
+        let mut workspace_toml = format!(
             "[workspace]\nmembers = [\n{}\n]\nresolver = \"2\"\n",
             member_paths
                 .iter()
@@ -204,6 +397,13 @@ impl TestRepoBuilder {
                 .join(",\n")
         );
 
+        if let Some(version) = &workspace.package_version {
+            workspace_toml.push_str(&format!(
+                "\n[workspace.package]\nversion = \"{}\"\n",
+                version
+            ));
+        }
+
         fs::write(workspace_dir.join("Cargo.toml"), workspace_toml)?;
 
         // create each crate
@@ -228,14 +428,25 @@ impl TestRepoBuilder {
         fs::create_dir_all(&src_dir)?;
 
         // create Cargo.toml
+        let version_line = if crate_def.inherit_version {
+            "version.workspace = true".to_string()
+        } else {
+            format!("version = \"{}\"", crate_def.version)
+        };
         let mut cargo_toml = format!(
-            "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n\n",
-            crate_def.name, crate_def.version
+            "[package]\nname = \"{}\"\n{}\nedition = \"2021\"\n\n",
+            crate_def.name, version_line
         );
 
-        if !crate_def.dependencies.is_empty() {
-            cargo_toml.push_str("[dependencies]\n");
-            for dep in &crate_def.dependencies {
+        for (table, deps) in [
+            ("dependencies", &crate_def.dependencies),
+            ("dev-dependencies", &crate_def.dev_dependencies),
+        ] {
+            if deps.is_empty() {
+                continue;
+            }
+            cargo_toml.push_str(&format!("[{}]\n", table));
+            for dep in deps {
                 // determine if this is a local dependency
                 let dep_path = self.find_dependency_path(workspace, dep);
                 if let Some(path) = dep_path {
@@ -258,6 +469,18 @@ impl TestRepoBuilder {
             fs::write(src_dir.join("lib.rs"), default_lib)?;
         }
 
+        if let Some(fixture) = &crate_def.changelog {
+            let version = if crate_def.inherit_version {
+                workspace
+                    .package_version
+                    .as_deref()
+                    .unwrap_or(&crate_def.version)
+            } else {
+                &crate_def.version
+            };
+            fs::write(crate_dir.join("CHANGELOG.md"), fixture.render(version))?;
+        }
+
         // create custom files
         for (rel_path, content) in &crate_def.files {
             let file_path = crate_dir.join(rel_path);
@@ -314,21 +537,82 @@ impl TestRepository {
         &self.path
     }
 
-    /// create a new branch
-    pub fn create_branch(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        std::process::Command::new("git")
-            .args(["checkout", "-b", name])
+    /// run git in the repository, failing when git exits with an error
+    fn git(&self, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("git")
+            .args(args)
             .current_dir(&self.path)
             .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// directory of a crate, looked up by name across all workspaces
+    pub fn crate_path(&self, crate_name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        self.workspaces
+            .iter()
+            .find(|w| w.crates.iter().any(|c| c.name == crate_name))
+            .map(|w| self.path.join(&w.name).join(crate_name))
+            .ok_or_else(|| format!("no crate named '{}' in the test repository", crate_name).into())
+    }
+
+    /// create a new branch
+    pub fn create_branch(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.branch(name)
+    }
+
+    /// create a branch at the current commit and switch to it
+    pub fn branch(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.git(&["checkout", "-b", name])?;
         Ok(())
     }
 
     /// checkout a branch
     pub fn checkout(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        std::process::Command::new("git")
-            .args(["checkout", name])
-            .current_dir(&self.path)
-            .output()?;
+        self.git(&["checkout", name])?;
+        Ok(())
+    }
+
+    /// tag the current commit
+    pub fn tag(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.git(&["tag", name])?;
+        Ok(())
+    }
+
+    /// overwrite (or create) a file in a crate, looked up by name
+    pub fn modify_crate(
+        &self,
+        crate_name: &str,
+        rel_path: &str,
+        content: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file_path = self.crate_path(crate_name)?.join(rel_path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file_path, content)?;
+        Ok(())
+    }
+
+    /// set a crate's package version, keeping the rest of its Cargo.toml as is
+    pub fn bump_version(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let cargo_toml_path = self.crate_path(crate_name)?.join("Cargo.toml");
+        let mut writer = TomlWriter::read_file(&cargo_toml_path)?;
+        writer.set_package_version(version)?;
+        writer.write()?;
         Ok(())
     }
 
@@ -375,6 +659,36 @@ impl TestRepository {
         Ok(())
     }
 
+    /// append to a crate's src/lib.rs, a code change that leaves the changelog untouched
+    pub fn touch_code(&self, crate_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let lib_path = self.crate_path(crate_name)?.join("src/lib.rs");
+        let mut content = fs::read_to_string(&lib_path).unwrap_or_default();
+        content.push_str("\npub fn touched() {}\n");
+        fs::write(lib_path, content)?;
+        Ok(())
+    }
+
+    /// add `* <entry>` under a new `## [version]` section at the top of the
+    /// crate's changelog, creating the file when missing
+    pub fn add_changelog_entry(
+        &self,
+        crate_name: &str,
+        version: &str,
+        entry: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let changelog_path = self.crate_path(crate_name)?.join("CHANGELOG.md");
+        let existing = fs::read_to_string(&changelog_path).unwrap_or_default();
+        let section = format!("## [{}]\n\n* {}\n\n", version, entry);
+
+        let content = match existing.find("\n## ") {
+            Some(pos) => format!("{}{}{}", &existing[..=pos], section, &existing[pos + 1..]),
+            None if existing.is_empty() => format!("# CHANGELOG\n\n{}", section),
+            None => format!("{}\n\n{}", existing.trim_end(), section),
+        };
+        fs::write(changelog_path, content)?;
+        Ok(())
+    }
+
     /// stage changes
     pub fn stage_all(&self) -> Result<(), Box<dyn std::error::Error>> {
         std::process::Command::new("git")
@@ -384,22 +698,20 @@ impl TestRepository {
         Ok(())
     }
 
-    /// create a commit
+    /// stage everything and create a commit
     pub fn commit(&self, message: &str) -> Result<(), Box<dyn std::error::Error>> {
-        std::process::Command::new("git")
-            .args(["commit", "-m", message, "--no-gpg-sign"])
-            .current_dir(&self.path)
-            .output()?;
+        self.git(&["add", "-A"])?;
+        self.git(&["commit", "-m", message, "--no-gpg-sign"])?;
         Ok(())
     }
 
     /// get current branch name
     pub fn current_branch(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let output = std::process::Command::new("git")
-            .args(["branch", "--show-current"])
-            .current_dir(&self.path)
-            .output()?;
+        self.git(&["branch", "--show-current"])
+    }
 
-        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    /// full hash of the current commit
+    pub fn head_commit(&self) -> Result<String, Box<dyn std::error::Error>> {
+        self.git(&["rev-parse", "HEAD"])
     }
 }
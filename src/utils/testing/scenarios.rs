@@ -1,8 +1,19 @@
 // pre-defined test scenarios
+//
+// each scenario builds a fresh git repository in a temporary directory with
+// everything committed on `master`; crates are named `crate_<letter>` and
+// start at version 0.1.0 unless noted otherwise. the repositories are meant
+// to be reused by crates embedding deptrack in their own tests:
+//
+//     let repo = TestScenario::Diamond.build()?;
+//     let graph = CrateDependencyGraph::build_from_repository(repo.path())?;
 
-use super::repo_builder::{TestCrate, TestRepoBuilder, TestRepository, TestWorkspace};
+use super::repo_builder::{
+    ChangelogFixture, TestCrate, TestRepoBuilder, TestRepository, TestWorkspace,
+};
 
 /// pre-defined test scenarios
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestScenario {
     /// simple single workspace with 3 crates in a chain: A -> B -> C
     SimpleChain,
@@ -10,32 +21,81 @@ pub enum TestScenario {
     CrossWorkspace,
     /// complex scenario with multiple workspaces and cross-dependencies
     Complex,
+    /// diamond in one workspace: B and C depend on A, D depends on B and C
+    Diamond,
+    /// A dev-depends on B while B depends on A, a cycle cargo accepts;
+    /// C is unrelated
+    DevDependencyCycle,
+    /// three workspaces `core`, `services` and `apps` chained through
+    /// cross-workspace dependencies: A <- B (services) <- C (apps), plus D in
+    /// apps depending on A directly
+    MultiWorkspace,
+    /// crates released in lockstep, all inherit version 1.2.0 from
+    /// [workspace.package]; B and C depend on A
+    LockstepVersions,
+    /// chain A -> B -> C where only A has a (valid) CHANGELOG.md
+    MissingChangelogs,
 }
 
 impl TestScenario {
+    /// every available scenario
+    pub const ALL: [TestScenario; 8] = [
+        TestScenario::SimpleChain,
+        TestScenario::CrossWorkspace,
+        TestScenario::Complex,
+        TestScenario::Diamond,
+        TestScenario::DevDependencyCycle,
+        TestScenario::MultiWorkspace,
+        TestScenario::LockstepVersions,
+        TestScenario::MissingChangelogs,
+    ];
+
     /// build a repository from a predefined scenario
     pub fn build(self) -> Result<TestRepository, Box<dyn std::error::Error>> {
+        self.builder().build()
+    }
+
+    /// builder preloaded with the scenario, for adding history on top
+    /// (e.g. `.branch("feature").bump_version("crate_A", "0.2.0").commit("bump")`)
+    pub fn builder(self) -> TestRepoBuilder {
+        match self {
+            TestScenario::SimpleChain => Self::simple_chain(),
+            TestScenario::CrossWorkspace => Self::cross_workspace(),
+            TestScenario::Complex => Self::complex(),
+            TestScenario::Diamond => Self::diamond(),
+            TestScenario::DevDependencyCycle => Self::dev_dependency_cycle(),
+            TestScenario::MultiWorkspace => Self::multi_workspace(),
+            TestScenario::LockstepVersions => Self::lockstep_versions(),
+            TestScenario::MissingChangelogs => Self::missing_changelogs(),
+        }
+    }
+
+    /// number of crates the scenario creates
+    pub fn crate_count(self) -> usize {
         match self {
-            TestScenario::SimpleChain => Self::build_simple_chain(),
-            TestScenario::CrossWorkspace => Self::build_cross_workspace(),
-            TestScenario::Complex => Self::build_complex(),
+            TestScenario::SimpleChain => 3,
+            TestScenario::CrossWorkspace => 4,
+            TestScenario::Complex => 10,
+            TestScenario::Diamond => 4,
+            TestScenario::DevDependencyCycle => 3,
+            TestScenario::MultiWorkspace => 4,
+            TestScenario::LockstepVersions => 3,
+            TestScenario::MissingChangelogs => 3,
         }
     }
 
     /// simple chain: crate_A -> crate_B -> crate_C
-    fn build_simple_chain() -> Result<TestRepository, Box<dyn std::error::Error>> {
-        TestRepoBuilder::new()
-            .workspace(
-                TestWorkspace::new("workspace")
-                    .crate_entry(TestCrate::new("crate_A"))
-                    .crate_entry(TestCrate::new("crate_B").dependency("crate_A"))
-                    .crate_entry(TestCrate::new("crate_C").dependency("crate_B")),
-            )
-            .build()
+    fn simple_chain() -> TestRepoBuilder {
+        TestRepoBuilder::new().workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("crate_A"))
+                .crate_entry(TestCrate::new("crate_B").dependency("crate_A"))
+                .crate_entry(TestCrate::new("crate_C").dependency("crate_B")),
+        )
     }
 
     /// cross-workspace dependencies
-    fn build_cross_workspace() -> Result<TestRepository, Box<dyn std::error::Error>> {
+    fn cross_workspace() -> TestRepoBuilder {
         TestRepoBuilder::new()
             .workspace(
                 TestWorkspace::new("workspace_1")
@@ -49,11 +109,10 @@ impl TestScenario {
                     )
                     .crate_entry(TestCrate::new("crate_D").dependency("crate_C")),
             )
-            .build()
     }
 
     /// complex scenario similar to our test_repo
-    fn build_complex() -> Result<TestRepository, Box<dyn std::error::Error>> {
+    fn complex() -> TestRepoBuilder {
         TestRepoBuilder::new()
             .workspace(
                 TestWorkspace::new("workspace_1")
@@ -83,7 +142,75 @@ impl TestScenario {
                         TestCrate::new("crate_M").dependency("crate_G"), // cross-workspace
                     ),
             )
-            .build()
+    }
+
+    /// diamond: crate_D -> {crate_B, crate_C} -> crate_A
+    fn diamond() -> TestRepoBuilder {
+        TestRepoBuilder::new().workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("crate_A"))
+                .crate_entry(TestCrate::new("crate_B").dependency("crate_A"))
+                .crate_entry(TestCrate::new("crate_C").dependency("crate_A"))
+                .crate_entry(
+                    TestCrate::new("crate_D")
+                        .dependency("crate_B")
+                        .dependency("crate_C"),
+                ),
+        )
+    }
+
+    /// crate_A dev-depends on crate_B, crate_B depends on crate_A
+    fn dev_dependency_cycle() -> TestRepoBuilder {
+        TestRepoBuilder::new().workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("crate_A").dev_dependency("crate_B"))
+                .crate_entry(TestCrate::new("crate_B").dependency("crate_A"))
+                .crate_entry(TestCrate::new("crate_C")),
+        )
+    }
+
+    /// core/crate_A <- services/crate_B <- apps/crate_C, apps/crate_D -> core/crate_A
+    fn multi_workspace() -> TestRepoBuilder {
+        TestRepoBuilder::new()
+            .workspace(TestWorkspace::new("core").crate_entry(TestCrate::new("crate_A")))
+            .workspace(
+                TestWorkspace::new("services")
+                    .crate_entry(TestCrate::new("crate_B").dependency("crate_A")),
+            )
+            .workspace(
+                TestWorkspace::new("apps")
+                    .crate_entry(TestCrate::new("crate_C").dependency("crate_B"))
+                    .crate_entry(TestCrate::new("crate_D").dependency("crate_A")),
+            )
+    }
+
+    /// all crates share version 1.2.0 through [workspace.package]
+    fn lockstep_versions() -> TestRepoBuilder {
+        TestRepoBuilder::new().workspace(
+            TestWorkspace::new("workspace")
+                .package_version("1.2.0")
+                .crate_entry(TestCrate::new("crate_A").workspace_version())
+                .crate_entry(
+                    TestCrate::new("crate_B")
+                        .workspace_version()
+                        .dependency("crate_A"),
+                )
+                .crate_entry(
+                    TestCrate::new("crate_C")
+                        .workspace_version()
+                        .dependency("crate_A"),
+                ),
+        )
+    }
+
+    /// chain crate_A <- crate_B <- crate_C, only crate_A has a changelog
+    fn missing_changelogs() -> TestRepoBuilder {
+        TestRepoBuilder::new().workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("crate_A").changelog(ChangelogFixture::Valid))
+                .crate_entry(TestCrate::new("crate_B").dependency("crate_A"))
+                .crate_entry(TestCrate::new("crate_C").dependency("crate_B")),
+        )
     }
 }
 
@@ -151,6 +278,56 @@ mod tests {
         assert!(e_deps.contains(&&crate_h.id));
     }
 
+    #[test]
+    fn test_all_scenarios_build() {
+        for scenario in TestScenario::ALL {
+            let repo = scenario.build().unwrap();
+            let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+            assert_eq!(graph.crates.len(), scenario.crate_count(), "{:?}", scenario);
+        }
+    }
+
+    #[test]
+    fn test_diamond_and_dev_cycle_scenarios() {
+        use crate::utils::cargo_ops::DependencyType;
+
+        let repo = TestScenario::Diamond.build().unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+        let crate_a = graph.find_crate_by_name("crate_A").unwrap();
+        let crate_d = graph.find_crate_by_name("crate_D").unwrap();
+        assert_eq!(graph.get_dependencies(&crate_d.id).len(), 2);
+        for name in ["crate_B", "crate_C"] {
+            let crate_info = graph.find_crate_by_name(name).unwrap();
+            assert_eq!(graph.get_dependencies(&crate_info.id), vec![&crate_a.id]);
+        }
+
+        let repo = TestScenario::DevDependencyCycle.build().unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+        assert_eq!(graph.find_cycles().len(), 1);
+        assert!(
+            graph
+                .find_cycles_filtered(&[DependencyType::Normal])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_lockstep_and_changelog_scenarios() {
+        let repo = TestScenario::LockstepVersions.build().unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+        assert!(graph.crates.values().all(|c| c.version == "1.2.0"));
+
+        let repo = TestScenario::MissingChangelogs.build().unwrap();
+        let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+        let with_changelog: Vec<_> = graph
+            .crates
+            .values()
+            .filter(|c| c.path.join("CHANGELOG.md").exists())
+            .map(|c| c.id.name.as_str())
+            .collect();
+        assert_eq!(with_changelog, vec!["crate_A"]);
+    }
+
     #[test]
     fn test_repo_modification() {
         let repo = TestScenario::SimpleChain.build().unwrap();
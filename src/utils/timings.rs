@@ -0,0 +1,121 @@
+// wall clock time spent in each phase of a run, kept only once `enable` was
+// called, for `--timings`
+
+use super::table::{Align, Table};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static RECORDED: Mutex<Vec<PhaseTiming>> = Mutex::new(Vec::new());
+
+/// total time of one phase, over every time it ran
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub duration_ms: f64,
+    pub runs: usize,
+}
+
+/// start recording for the rest of the process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// run `f`, adding its duration to `phase` when recording
+pub fn time<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record(phase, start.elapsed());
+    result
+}
+
+/// add `elapsed` to `phase` when recording
+pub fn record(phase: &'static str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    let mut recorded = RECORDED.lock().unwrap_or_else(PoisonError::into_inner);
+    let duration_ms = elapsed.as_secs_f64() * 1000.0;
+    match recorded.iter_mut().find(|t| t.phase == phase) {
+        Some(timing) => {
+            timing.duration_ms += duration_ms;
+            timing.runs += 1;
+        }
+        None => recorded.push(PhaseTiming {
+            phase,
+            duration_ms,
+            runs: 1,
+        }),
+    }
+}
+
+/// the phases recorded so far, in the order they first ran and to the
+/// microsecond, leaving none behind
+pub fn take() -> Vec<PhaseTiming> {
+    let mut timings = std::mem::take(&mut *RECORDED.lock().unwrap_or_else(PoisonError::into_inner));
+    for timing in &mut timings {
+        timing.duration_ms = (timing.duration_ms * 1000.0).round() / 1000.0;
+    }
+    timings
+}
+
+/// print the phases as a table with their total
+pub fn display(timings: &[PhaseTiming]) {
+    let mut table = Table::new(["Phase", "Time", "Runs"])
+        .align(1, Align::Right)
+        .align(2, Align::Right);
+    for timing in timings {
+        table.row([
+            timing.phase.to_string(),
+            format!("{:.1} ms", timing.duration_ms),
+            timing.runs.to_string(),
+        ]);
+    }
+    let total: f64 = timings.iter().map(|t| t.duration_ms).sum();
+    table.row([
+        "total".to_string(),
+        format!("{:.1} ms", total),
+        String::new(),
+    ]);
+
+    println!("timings:");
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time() {
+        // other tests may record phases of their own meanwhile
+        let ours = |timings: Vec<PhaseTiming>| -> Vec<PhaseTiming> {
+            timings
+                .into_iter()
+                .filter(|t| t.phase.starts_with("test "))
+                .collect()
+        };
+
+        assert_eq!(time("test off", || 1), 1);
+        enable();
+        assert_eq!(time("test first", || 2), 2);
+        record("test second", Duration::from_millis(3));
+        record("test first", Duration::from_millis(5));
+
+        let timings = ours(take());
+        let phases: Vec<_> = timings.iter().map(|t| (t.phase, t.runs)).collect();
+        assert_eq!(phases, vec![("test first", 2), ("test second", 1)]);
+        assert!(timings[0].duration_ms >= 5.0);
+        assert!(ours(take()).is_empty());
+    }
+}
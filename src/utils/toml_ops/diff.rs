@@ -0,0 +1,412 @@
+use super::types::TomlDocument;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TomlChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// part of a Cargo.toml a change falls in, so policy can tell which changes
+/// matter to a release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TomlSection {
+    /// [package] and [workspace.package] fields other than metadata
+    Package,
+    /// [package.metadata], [workspace.metadata] and [badges]
+    Metadata,
+    /// dependency tables, target-specific ones and [workspace.dependencies]
+    Dependencies,
+    /// [features]
+    Features,
+    /// [lib], [[bin]], [[test]], [[bench]] and [[example]]
+    Targets,
+    /// [lints] and [workspace.lints]
+    Lints,
+    /// [profile.*]
+    Profile,
+    /// the rest: other [workspace] settings, [patch], [replace]
+    Other,
+}
+
+// one added, removed or changed value; tables are descended into, so keys
+// points at the most specific value that differs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TomlChange {
+    pub keys: Vec<String>,
+    pub kind: TomlChangeKind,
+    pub old: Option<toml::Value>,
+    pub new: Option<toml::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TomlDiff {
+    pub changes: Vec<TomlChange>,
+}
+
+// structural difference between two documents, keys are compared in order
+pub fn diff(old: &TomlDocument, new: &TomlDocument) -> TomlDiff {
+    let mut changes = Vec::new();
+    diff_values(&mut Vec::new(), &old.content, &new.content, &mut changes);
+    TomlDiff { changes }
+}
+
+fn diff_values(
+    keys: &mut Vec<String>,
+    old: &toml::Value,
+    new: &toml::Value,
+    changes: &mut Vec<TomlChange>,
+) {
+    match (old, new) {
+        (toml::Value::Table(old_table), toml::Value::Table(new_table)) => {
+            for (key, old_value) in old_table {
+                keys.push(key.clone());
+                match new_table.get(key) {
+                    Some(new_value) => diff_values(keys, old_value, new_value, changes),
+                    None => changes.push(TomlChange {
+                        keys: keys.clone(),
+                        kind: TomlChangeKind::Removed,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+                keys.pop();
+            }
+            for (key, new_value) in new_table {
+                if !old_table.contains_key(key) {
+                    let mut added = keys.clone();
+                    added.push(key.clone());
+                    changes.push(TomlChange {
+                        keys: added,
+                        kind: TomlChangeKind::Added,
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        _ if old != new => changes.push(TomlChange {
+            keys: keys.clone(),
+            kind: TomlChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+impl TomlDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // (old, new) package version, when it changed
+    pub fn version_change(&self) -> Option<(String, String)> {
+        self.changes
+            .iter()
+            .find(|c| c.keys == ["package", "version"] && c.kind == TomlChangeKind::Changed)
+            .and_then(|c| {
+                Some((
+                    c.old.as_ref()?.as_str()?.to_string(),
+                    c.new.as_ref()?.as_str()?.to_string(),
+                ))
+            })
+    }
+
+    // names of [workspace.dependencies] entries that were added, removed or changed
+    pub fn workspace_dependency_changes(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .changes
+            .iter()
+            .filter(|c| {
+                c.keys.len() >= 3 && c.keys[0] == "workspace" && c.keys[1] == "dependencies"
+            })
+            .map(|c| c.keys[2].as_str())
+            .collect();
+        names.dedup();
+        names
+    }
+
+    // sections touched by any change, each once, in the order TomlSection
+    // lists its variants (package first) rather than the manifest's
+    pub fn sections(&self) -> Vec<TomlSection> {
+        let mut sections: Vec<TomlSection> = self.changes.iter().map(TomlChange::section).collect();
+        sections.sort();
+        sections.dedup();
+        sections
+    }
+
+    // whether [workspace.lints] was added, removed or changed
+    pub fn workspace_lints_changed(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| c.keys.len() >= 2 && c.keys[0] == "workspace" && c.keys[1] == "lints")
+    }
+
+    // one readable line per change
+    pub fn summary(&self) -> Vec<String> {
+        self.changes.iter().map(|c| c.to_string()).collect()
+    }
+}
+
+impl TomlChange {
+    pub fn path(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| {
+                let bare = !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                if bare {
+                    key.clone()
+                } else {
+                    format!("'{}'", key)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    pub fn section(&self) -> TomlSection {
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        match keys.as_slice() {
+            ["package" | "workspace", "metadata", ..] | ["badges", ..] => TomlSection::Metadata,
+            ["package", ..] | ["workspace", "package", ..] => TomlSection::Package,
+            [table, ..] if DEPENDENCY_TABLES.contains(table) => TomlSection::Dependencies,
+            ["workspace", "dependencies", ..] | ["target", ..] => TomlSection::Dependencies,
+            ["features", ..] => TomlSection::Features,
+            ["lib" | "bin" | "test" | "bench" | "example", ..] => TomlSection::Targets,
+            ["lints", ..] | ["workspace", "lints", ..] => TomlSection::Lints,
+            ["profile", ..] => TomlSection::Profile,
+            _ => TomlSection::Other,
+        }
+    }
+
+    // position of the dependency name in keys, when this change is inside a dependency table
+    fn dependency_index(&self) -> Option<usize> {
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        let index = match keys.as_slice() {
+            [table, ..] if DEPENDENCY_TABLES.contains(table) => 1,
+            ["workspace", "dependencies", ..] => 2,
+            ["target", _, table, ..] if DEPENDENCY_TABLES.contains(table) => 3,
+            _ => return None,
+        };
+        (keys.len() > index).then_some(index)
+    }
+}
+
+fn short(value: &Option<toml::Value>) -> String {
+    match value {
+        Some(toml::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+impl fmt::Display for TomlChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            TomlChangeKind::Added => "added",
+            TomlChangeKind::Removed => "removed",
+            TomlChangeKind::Changed => "changed",
+        };
+        let values = match self.kind {
+            TomlChangeKind::Changed => format!(" ({} -> {})", short(&self.old), short(&self.new)),
+            _ => String::new(),
+        };
+
+        if self.keys == ["package", "version"] {
+            return write!(f, "version {}{}", kind, values);
+        }
+
+        if let Some(index) = self.dependency_index() {
+            let table = self.keys[..index].join(".");
+            let name = &self.keys[index];
+            return match &self.keys[index + 1..] {
+                [] => write!(f, "dependency {} {} in [{}]{}", name, kind, table, values),
+                rest => write!(
+                    f,
+                    "dependency {} {} {} in [{}]{}",
+                    name,
+                    rest.join("."),
+                    kind,
+                    table,
+                    values
+                ),
+            };
+        }
+
+        if self.keys.len() == 2 && self.keys[0] == "features" {
+            return write!(f, "feature {} {}{}", self.keys[1], kind, values);
+        }
+
+        write!(f, "{} {}{}", self.path(), kind, values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::toml_ops::TomlReader;
+
+    fn doc(content: &str) -> TomlDocument {
+        TomlReader::parse_string(content, "Cargo.toml".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_reports_changes() {
+        let old = doc(r#"
+            [package]
+            name = "demo"
+            version = "0.1.0"
+
+            [dependencies]
+            serde = "1.0"
+            tokio = { version = "1.0", features = ["rt"] }
+            old-dep = "0.3"
+
+            [features]
+            default = []
+            legacy = []
+        "#);
+        let new = doc(r#"
+            [package]
+            name = "demo"
+            version = "0.2.0"
+
+            [dependencies]
+            serde = "1.0"
+            tokio = { version = "1.2", features = ["rt"] }
+            anyhow = "1.0"
+
+            [target.'cfg(unix)'.dependencies]
+            libc = "0.2"
+
+            [features]
+            default = ["std"]
+            std = []
+        "#);
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.version_change(),
+            Some(("0.1.0".to_string(), "0.2.0".to_string()))
+        );
+
+        let summary = diff.summary();
+        for expected in [
+            "version changed (0.1.0 -> 0.2.0)",
+            "dependency tokio version changed in [dependencies] (1.0 -> 1.2)",
+            "dependency old-dep removed in [dependencies]",
+            "dependency anyhow added in [dependencies]",
+            "feature legacy removed",
+            "feature std added",
+            "feature default changed ([] -> [\"std\"])",
+            "target added",
+        ] {
+            assert!(
+                summary.iter().any(|line| line == expected),
+                "missing {:?} in {:?}",
+                expected,
+                summary
+            );
+        }
+        assert_eq!(summary.len(), 8);
+    }
+
+    #[test]
+    fn test_sections() {
+        let old = doc(r#"
+            [package]
+            name = "demo"
+            version = "0.1.0"
+
+            [package.metadata.docs.rs]
+            all-features = true
+
+            [lints.rust]
+            unsafe_code = "forbid"
+
+            [workspace.lints.clippy]
+            pedantic = "warn"
+        "#);
+        let new = doc(r#"
+            [package]
+            name = "demo"
+            version = "0.1.0"
+
+            [package.metadata.docs.rs]
+            all-features = false
+
+            [lints.rust]
+            unsafe_code = "deny"
+
+            [workspace.lints.clippy]
+            pedantic = "allow"
+
+            [profile.release]
+            lto = true
+        "#);
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.sections(),
+            vec![
+                TomlSection::Metadata,
+                TomlSection::Lints,
+                TomlSection::Profile
+            ]
+        );
+        assert!(diff.workspace_lints_changed());
+
+        let bumped = doc(r#"
+            [package]
+            name = "demo"
+            version = "0.2.0"
+
+            [target.'cfg(unix)'.dependencies]
+            libc = "0.2"
+
+            [[bin]]
+            name = "demo"
+        "#);
+        let diff = super::diff(&old, &bumped);
+        assert_eq!(
+            diff.sections(),
+            vec![
+                TomlSection::Package,
+                TomlSection::Metadata,
+                TomlSection::Dependencies,
+                TomlSection::Targets,
+                TomlSection::Lints,
+                TomlSection::Other
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_and_workspace_dependencies() {
+        let content = "[package]\nname = \"a\"\nversion = \"1.0.0\"\n";
+        assert!(diff(&doc(content), &doc(content)).is_empty());
+
+        let old = doc("[workspace.dependencies]\nserde = \"1.0\"\nlog = \"0.4\"\n");
+        let new = doc(
+            "[workspace.dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] }\nlog = \"0.4\"\n",
+        );
+        let diff = diff(&old, &new);
+        assert_eq!(diff.workspace_dependency_changes(), vec!["serde"]);
+        assert_eq!(
+            diff.summary(),
+            vec![
+                "dependency serde changed in [workspace.dependencies] (1.0 -> { features = [\"derive\"], version = \"1.0\" })"
+            ]
+        );
+    }
+}
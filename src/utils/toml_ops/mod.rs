@@ -1,7 +1,11 @@
+pub mod diff;
 pub mod predicates;
 pub mod reader;
 pub mod types;
+pub mod writer;
 
+pub use diff::*;
 pub use predicates::*;
 pub use reader::*;
 pub use types::*;
+pub use writer::*;
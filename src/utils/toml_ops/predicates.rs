@@ -1,9 +1,11 @@
 use super::types::{TomlContext, TomlError};
 use crate::utils::alt::Evaluable;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TomlPredicate {
     HasField(HasFieldPredicate),
     HasTable(HasTablePredicate),
@@ -13,6 +15,10 @@ pub enum TomlPredicate {
     ExtractBuildDependencies(ExtractBuildDependenciesPredicate),
     FieldEquals(FieldEqualsPredicate),
     VersionMatches(VersionMatchesPredicate),
+    HasFeature(HasFeaturePredicate),
+    FeatureEnables(FeatureEnablesPredicate),
+    DependencyVersionMatches(DependencyVersionMatchesPredicate),
+    ArrayContains(ArrayContainsPredicate),
 }
 
 impl Evaluable for TomlPredicate {
@@ -29,11 +35,15 @@ impl Evaluable for TomlPredicate {
             TomlPredicate::ExtractBuildDependencies(pred) => pred.evaluate(context),
             TomlPredicate::FieldEquals(pred) => pred.evaluate(context),
             TomlPredicate::VersionMatches(pred) => pred.evaluate(context),
+            TomlPredicate::HasFeature(pred) => pred.evaluate(context),
+            TomlPredicate::FeatureEnables(pred) => pred.evaluate(context),
+            TomlPredicate::DependencyVersionMatches(pred) => pred.evaluate(context),
+            TomlPredicate::ArrayContains(pred) => pred.evaluate(context),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HasFieldPredicate {
     pub field_name: String,
 }
@@ -55,7 +65,7 @@ impl Evaluable for HasFieldPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HasTablePredicate {
     pub table_name: String,
 }
@@ -77,7 +87,7 @@ impl Evaluable for HasTablePredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractVersionPredicate {
     pub extracted_version: Option<String>,
 }
@@ -116,12 +126,13 @@ impl Evaluable for ExtractVersionPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractDependenciesPredicate {
     pub dependency_type: DependencyType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DependencyType {
     Regular,
     Dev,
@@ -163,7 +174,7 @@ impl Evaluable for ExtractDependenciesPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractDevDependenciesPredicate;
 
 impl ExtractDevDependenciesPredicate {
@@ -187,7 +198,7 @@ impl Evaluable for ExtractDevDependenciesPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractBuildDependenciesPredicate;
 
 impl ExtractBuildDependenciesPredicate {
@@ -211,7 +222,7 @@ impl Evaluable for ExtractBuildDependenciesPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldEqualsPredicate {
     pub field_name: String,
     pub expected_value: String,
@@ -238,13 +249,14 @@ impl Evaluable for FieldEqualsPredicate {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionMatchesPredicate {
     pub version_pattern: String,
     pub match_type: VersionMatchType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum VersionMatchType {
     Exact,
     StartsWith,
@@ -299,6 +311,215 @@ impl Evaluable for VersionMatchesPredicate {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HasFeaturePredicate {
+    pub feature_name: String,
+}
+
+impl HasFeaturePredicate {
+    pub fn new(feature_name: impl Into<String>) -> Self {
+        Self {
+            feature_name: feature_name.into(),
+        }
+    }
+}
+
+impl Evaluable for HasFeaturePredicate {
+    type Context = TomlContext;
+    type Error = TomlError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        Ok(context
+            .document
+            .get_features()
+            .is_some_and(|features| features.contains_key(&self.feature_name)))
+    }
+}
+
+// true when `feature_name` turns on `target`, either directly or through the
+// features it enables; `target` may be a feature or a dependency name, the
+// latter also matches "dep:name", "name/feature" and "name?/feature" entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureEnablesPredicate {
+    pub feature_name: String,
+    pub target: String,
+}
+
+impl FeatureEnablesPredicate {
+    pub fn new(feature_name: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            feature_name: feature_name.into(),
+            target: target.into(),
+        }
+    }
+
+    fn entry_matches(&self, entry: &str) -> bool {
+        let dependency = entry.strip_prefix("dep:").or_else(|| {
+            entry
+                .split_once('/')
+                .map(|(dep, _)| dep.trim_end_matches('?'))
+        });
+        entry == self.target || dependency == Some(self.target.as_str())
+    }
+}
+
+impl Evaluable for FeatureEnablesPredicate {
+    type Context = TomlContext;
+    type Error = TomlError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        let Some(features) = context.document.get_features() else {
+            return Ok(false);
+        };
+
+        // walk the features reachable from feature_name, each one visited once
+        let mut pending = vec![self.feature_name.as_str()];
+        let mut visited = std::collections::HashSet::new();
+        while let Some(feature) = pending.pop() {
+            if !visited.insert(feature) {
+                continue;
+            }
+            for entry in features.get(feature).into_iter().flatten() {
+                if self.entry_matches(entry) {
+                    return Ok(true);
+                }
+                if features.contains_key(entry.as_str()) {
+                    pending.push(entry);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+// true when the version requirement of `dependency` falls inside `range`,
+// i.e. the lowest version the requirement accepts also matches the range
+// ("1.2" against ">=1.0, <2" matches, "0.9" does not); renamed dependencies
+// are matched by their `package` key, inherited ones (`workspace = true`)
+// carry no requirement and never match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyVersionMatchesPredicate {
+    pub dependency: String,
+    pub range: semver::VersionReq,
+    // restrict the lookup to one dependency table, all of them when None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependency_type: Option<DependencyType>,
+}
+
+impl DependencyVersionMatchesPredicate {
+    pub fn new(dependency: impl Into<String>, range: semver::VersionReq) -> Self {
+        Self {
+            dependency: dependency.into(),
+            range,
+            dependency_type: None,
+        }
+    }
+
+    pub fn parse(dependency: impl Into<String>, range: &str) -> Result<Self, TomlError> {
+        let range = semver::VersionReq::parse(range).map_err(|e| {
+            TomlError::ParseError(format!("invalid version range '{}': {}", range, e))
+        })?;
+        Ok(Self::new(dependency, range))
+    }
+
+    pub fn with_dependency_type(mut self, dependency_type: DependencyType) -> Self {
+        self.dependency_type = Some(dependency_type);
+        self
+    }
+
+    fn table_names(&self) -> &'static [&'static str] {
+        match self.dependency_type {
+            Some(DependencyType::Regular) => &["dependencies"],
+            Some(DependencyType::Dev) => &["dev-dependencies"],
+            Some(DependencyType::Build) => &["build-dependencies"],
+            None => &["dependencies", "dev-dependencies", "build-dependencies"],
+        }
+    }
+}
+
+// smallest version a requirement accepts, None for requirements without a
+// lower bound (e.g. "<2.0") or ones that cannot be parsed
+pub fn lowest_accepted(requirement: &str) -> Option<semver::Version> {
+    use semver::Op;
+
+    let req = semver::VersionReq::parse(requirement).ok()?;
+    let lowest = req
+        .comparators
+        .iter()
+        .filter_map(|c| {
+            let mut version =
+                semver::Version::new(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0));
+            version.pre = c.pre.clone();
+            match c.op {
+                Op::Exact | Op::GreaterEq | Op::Tilde | Op::Caret | Op::Wildcard => Some(version),
+                Op::Greater => {
+                    // ">1.2" accepts 1.3.0 at the earliest, ">1.2.3" 1.2.4
+                    match (c.minor, c.patch) {
+                        (None, _) => Some(semver::Version::new(c.major + 1, 0, 0)),
+                        (Some(minor), None) => Some(semver::Version::new(c.major, minor + 1, 0)),
+                        (Some(minor), Some(patch)) => {
+                            Some(semver::Version::new(c.major, minor, patch + 1))
+                        }
+                    }
+                }
+                _ => None,
+            }
+        })
+        .max()?;
+
+    req.matches(&lowest).then_some(lowest)
+}
+
+impl Evaluable for DependencyVersionMatchesPredicate {
+    type Context = TomlContext;
+    type Error = TomlError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        Ok(context
+            .document
+            .dependency_requirements(&self.dependency, self.table_names())
+            .into_iter()
+            .filter_map(lowest_accepted)
+            .any(|version| self.range.matches(&version)))
+    }
+}
+
+// true when the array at the dotted `path` (e.g. "package.keywords",
+// "workspace.members") contains `value`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArrayContainsPredicate {
+    pub path: String,
+    pub value: String,
+}
+
+impl ArrayContainsPredicate {
+    pub fn new(path: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl Evaluable for ArrayContainsPredicate {
+    type Context = TomlContext;
+    type Error = TomlError;
+
+    fn evaluate(&self, context: &Self::Context) -> Result<bool, Self::Error> {
+        match context.document.get_path(&self.path) {
+            Some(toml::Value::Array(items)) => Ok(items
+                .iter()
+                .any(|item| item.as_str() == Some(self.value.as_str()))),
+            Some(_) => Err(TomlError::InvalidType(format!(
+                "'{}' is not an array",
+                self.path
+            ))),
+            None => Ok(false),
+        }
+    }
+}
+
 // Utility functions for extracting data (not predicates, but useful for the caller)
 pub struct TomlExtractor;
 
@@ -325,6 +546,14 @@ impl TomlExtractor {
         context.document.get_build_dependencies()
     }
 
+    pub fn extract_features(context: &TomlContext) -> Option<HashMap<String, Vec<String>>> {
+        context.document.get_features()
+    }
+
+    pub fn extract_optional_dependencies(context: &TomlContext) -> Vec<String> {
+        context.document.get_optional_dependencies()
+    }
+
     pub fn extract_field_as_string(context: &TomlContext, field_name: &str) -> Option<String> {
         context.document.get_string(field_name)
     }
@@ -442,6 +671,118 @@ mod tests {
         assert!(!no_match.evaluate(&context).unwrap());
     }
 
+    #[test]
+    fn test_feature_predicates() {
+        let doc = TomlReader::parse_string(
+            r#"
+            [package]
+            name = "featured"
+            version = "0.1.0"
+
+            [dependencies]
+            serde = { version = "1.0", optional = true }
+            tokio = { version = "1.0", optional = true }
+            log = "0.4"
+
+            [target.'cfg(unix)'.dependencies]
+            libc = { version = "0.2", optional = true }
+
+            [features]
+            default = ["std"]
+            std = ["serde?/std", "full"]
+            full = ["dep:tokio", "tokio/rt"]
+            unix = ["libc"]
+            cyclic = ["cyclic"]
+            "#,
+            "Cargo.toml".to_string(),
+        )
+        .unwrap();
+        let context = TomlContext::new(doc);
+
+        let features = TomlExtractor::extract_features(&context).unwrap();
+        assert_eq!(features.len(), 5);
+        assert_eq!(features["std"], vec!["serde?/std", "full"]);
+        assert_eq!(
+            TomlExtractor::extract_optional_dependencies(&context),
+            vec!["libc", "serde", "tokio"]
+        );
+
+        assert!(HasFeaturePredicate::new("full").evaluate(&context).unwrap());
+        assert!(
+            !HasFeaturePredicate::new("missing")
+                .evaluate(&context)
+                .unwrap()
+        );
+
+        let enables = |feature: &str, target: &str| {
+            TomlPredicate::FeatureEnables(FeatureEnablesPredicate::new(feature, target))
+                .evaluate(&context)
+                .unwrap()
+        };
+        assert!(enables("std", "full"));
+        assert!(enables("std", "serde"));
+        assert!(enables("full", "tokio"));
+        assert!(enables("default", "tokio"));
+        assert!(enables("unix", "libc"));
+        assert!(!enables("full", "serde"));
+        assert!(!enables("cyclic", "serde"));
+        assert!(!enables("missing", "serde"));
+    }
+
+    #[test]
+    fn test_dependency_version_matches_predicate() {
+        let doc = TomlReader::parse_string(
+            r#"
+            [package]
+            name = "policy"
+            keywords = ["cli", "graph"]
+
+            [dependencies]
+            serde = "1.0.150"
+            tokio = { version = "~1.28", features = ["rt"] }
+            json = { package = "serde_json", version = ">=1.0.100, <2" }
+            shared = { workspace = true }
+
+            [dev-dependencies]
+            rand = "0.8"
+            "#,
+            "Cargo.toml".to_string(),
+        )
+        .unwrap();
+        let context = TomlContext::new(doc);
+
+        let matches = |dependency: &str, range: &str| {
+            DependencyVersionMatchesPredicate::parse(dependency, range)
+                .unwrap()
+                .evaluate(&context)
+                .unwrap()
+        };
+        assert!(matches("serde", "^1.0.100"));
+        assert!(!matches("serde", ">=1.0.200"));
+        assert!(matches("tokio", ">=1.28, <1.29"));
+        assert!(matches("serde_json", "1"));
+        assert!(!matches("json", "1"));
+        assert!(!matches("shared", "*"));
+        assert!(matches("rand", "0.8"));
+        assert!(!matches("missing", "*"));
+
+        let regular_only = DependencyVersionMatchesPredicate::parse("rand", "0.8")
+            .unwrap()
+            .with_dependency_type(DependencyType::Regular);
+        assert!(!regular_only.evaluate(&context).unwrap());
+        assert!(DependencyVersionMatchesPredicate::parse("serde", "not a range").is_err());
+
+        assert_eq!(lowest_accepted(">1.2"), Some(semver::Version::new(1, 3, 0)));
+        assert_eq!(lowest_accepted("<2"), None);
+
+        let keywords = ArrayContainsPredicate::new("package.keywords", "cli");
+        assert!(keywords.evaluate(&context).unwrap());
+        let missing = ArrayContainsPredicate::new("package.categories", "cli");
+        assert!(!missing.evaluate(&context).unwrap());
+        let not_array = ArrayContainsPredicate::new("package.name", "policy");
+        assert!(not_array.evaluate(&context).is_err());
+    }
+
     #[test]
     fn test_toml_predicate_enum() {
         let context = create_test_context();
@@ -1,4 +1,4 @@
-use super::types::{TomlDocument, TomlError};
+use super::types::{DeptrackMetadata, TomlDocument, TomlError};
 use std::fs;
 use std::path::Path;
 
@@ -20,6 +20,23 @@ impl TomlReader {
         Ok(TomlDocument::new(path_str, toml_value))
     }
 
+    /// [`Self::read_file`] for a Cargo.toml, failing with the
+    /// `Error::FileReadError` the checkers report
+    pub fn read_manifest<P: AsRef<Path>>(path: P) -> crate::error::Result<TomlDocument> {
+        let path = path.as_ref();
+        Self::read_file(path).map_err(|e| manifest_read_error(path, e))
+    }
+
+    /// [`Self::read_cargo_toml_with_workspace`] for the crate in `directory`,
+    /// failing like [`Self::read_manifest`]
+    pub fn read_manifest_with_workspace<P: AsRef<Path>>(
+        directory: P,
+    ) -> crate::error::Result<TomlDocument> {
+        let directory = directory.as_ref();
+        Self::read_cargo_toml_with_workspace(directory)
+            .map_err(|e| manifest_read_error(&directory.join("Cargo.toml"), e))
+    }
+
     pub fn parse_string(content: &str, file_path: String) -> Result<TomlDocument, TomlError> {
         let toml_value = content
             .parse::<toml::Value>()
@@ -33,6 +50,47 @@ impl TomlReader {
         Self::read_file(cargo_path)
     }
 
+    // Cargo.toml of `directory` with its workspace root attached, so inherited
+    // fields and dependencies resolve; the root is taken from `package.workspace`
+    // when set, otherwise from the closest parent declaring [workspace]
+    pub fn read_cargo_toml_with_workspace<P: AsRef<Path>>(
+        directory: P,
+    ) -> Result<TomlDocument, TomlError> {
+        let directory = directory.as_ref();
+        let document = Self::read_cargo_toml(directory)?;
+
+        if document.is_workspace_root() {
+            let root = document.clone();
+            return Ok(document.with_workspace(root));
+        }
+
+        let explicit_root = document
+            .get_path("package.workspace")
+            .and_then(|v| v.as_str())
+            .map(|root| directory.join(root));
+        let workspace_root = match explicit_root {
+            Some(root) => Some(Self::read_cargo_toml(root)?),
+            None => directory
+                .ancestors()
+                .skip(1)
+                .filter(|dir| dir.join("Cargo.toml").is_file())
+                .filter_map(|dir| Self::read_cargo_toml(dir).ok())
+                .find(TomlDocument::is_workspace_root),
+        };
+
+        Ok(match workspace_root {
+            Some(root) => document.with_workspace(root),
+            None => document,
+        })
+    }
+
+    // [package.metadata.deptrack] of the crate in `directory`
+    pub fn read_crate_metadata<P: AsRef<Path>>(
+        directory: P,
+    ) -> Result<DeptrackMetadata, TomlError> {
+        Self::read_cargo_toml(directory)?.deptrack_metadata()
+    }
+
     pub fn find_and_read_cargo_toml<P: AsRef<Path>>(
         start_path: P,
     ) -> Result<TomlDocument, TomlError> {
@@ -58,6 +116,17 @@ impl TomlReader {
     }
 }
 
+// the error a Cargo.toml that could not be read or parsed is reported with
+pub(crate) fn manifest_read_error(path: &Path, e: impl std::fmt::Display) -> crate::error::Error {
+    crate::error::Error::FileReadError {
+        path: path.to_path_buf(),
+        source: std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to read Cargo.toml: {}", e),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +221,125 @@ mod tests {
         assert!(doc.has_table("package"));
     }
 
+    #[test]
+    fn test_get_path() {
+        let doc = TomlReader::parse_string(
+            r#"
+            [package]
+            name = "meta"
+
+            [package.metadata.deptrack]
+            changelog = "docs/CHANGES.md"
+
+            [target.'cfg(unix)'.dependencies]
+            libc = "0.2"
+
+            [workspace]
+            members = ["a", "b"]
+            "#,
+            "Cargo.toml".to_string(),
+        )
+        .unwrap();
+
+        let get = |path| doc.get_path(path).and_then(|v| v.as_str());
+        assert_eq!(get("package.name"), Some("meta"));
+        assert_eq!(get("target.'cfg(unix)'.dependencies.libc"), Some("0.2"));
+        assert_eq!(get("target.\"cfg(unix)\".dependencies.libc"), Some("0.2"));
+        assert_eq!(get("workspace.members.1"), Some("b"));
+        assert_eq!(get("workspace.members.2"), None);
+        assert_eq!(get("package.name.inner"), None);
+        assert_eq!(get("package..name"), None);
+        assert_eq!(get("target.'cfg(unix)"), None);
+
+        let members: Option<Vec<String>> = doc.get_path_as("workspace.members").unwrap();
+        assert_eq!(members.unwrap(), vec!["a", "b"]);
+        assert!(doc.get_path_as::<u32>("package.name").is_err());
+        assert_eq!(doc.get_path_as::<String>("package.missing").unwrap(), None);
+
+        assert_eq!(
+            doc.deptrack_metadata().unwrap().changelog.as_deref(),
+            Some("docs/CHANGES.md")
+        );
+    }
+
+    #[test]
+    fn test_read_crate_metadata_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"plain\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        let metadata = TomlReader::read_crate_metadata(temp_dir.path()).unwrap();
+        assert_eq!(metadata, DeptrackMetadata::default());
+    }
+
+    #[test]
+    fn test_read_cargo_toml_with_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let member_dir = temp_dir.path().join("crates/member");
+        fs::create_dir_all(&member_dir).unwrap();
+
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            r#"
+            [workspace]
+            members = ["crates/member"]
+
+            [workspace.package]
+            version = "1.4.0"
+            license = "MIT"
+
+            [workspace.dependencies]
+            serde = { version = "1.0", features = ["derive"] }
+            log = "0.4"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            member_dir.join("Cargo.toml"),
+            r#"
+            [package]
+            name = "member"
+            version.workspace = true
+            license = { workspace = true }
+
+            [dependencies]
+            serde = { workspace = true, features = ["rc"], optional = true }
+            log.workspace = true
+            missing = { workspace = true }
+            regex = "1"
+            "#,
+        )
+        .unwrap();
+
+        // without the workspace, inherited values are unknown
+        let standalone = TomlReader::read_cargo_toml(&member_dir).unwrap();
+        assert_eq!(standalone.get_version(), None);
+
+        let doc = TomlReader::read_cargo_toml_with_workspace(&member_dir).unwrap();
+        assert_eq!(doc.get_version(), Some("1.4.0".to_string()));
+        assert_eq!(
+            doc.get_package_field("license")
+                .and_then(|v| v.as_str().map(String::from)),
+            Some("MIT".to_string())
+        );
+
+        let deps = doc.get_dependencies().unwrap();
+        assert_eq!(
+            deps["serde"],
+            serde_json::json!({"version": "1.0", "features": ["derive", "rc"], "optional": true})
+        );
+        assert_eq!(deps["log"], serde_json::json!({"version": "0.4"}));
+        assert_eq!(deps["missing"], serde_json::json!({"workspace": true}));
+        assert_eq!(deps["regex"], serde_json::json!("1"));
+
+        // the root itself resolves against its own [workspace] tables
+        let root = TomlReader::read_cargo_toml_with_workspace(temp_dir.path()).unwrap();
+        assert!(root.workspace.is_some());
+    }
+
     #[test]
     fn test_toml_error_handling() {
         let temp_dir = TempDir::new().unwrap();
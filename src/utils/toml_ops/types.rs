@@ -28,11 +28,44 @@ impl Error for TomlError {}
 pub struct TomlDocument {
     pub file_path: String,
     pub content: toml::Value,
+    // root manifest of the workspace this document belongs to, used to
+    // resolve `workspace = true` markers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Box<TomlDocument>>,
 }
 
 impl TomlDocument {
     pub fn new(file_path: String, content: toml::Value) -> Self {
-        Self { file_path, content }
+        Self {
+            file_path,
+            content,
+            workspace: None,
+        }
+    }
+
+    pub fn with_workspace(mut self, workspace: TomlDocument) -> Self {
+        self.workspace = Some(Box::new(workspace));
+        self
+    }
+
+    pub fn is_workspace_root(&self) -> bool {
+        self.has_table("workspace")
+    }
+
+    // value of a [package] field, `field.workspace = true` is resolved against
+    // [workspace.package] of the parent workspace (None when there is none)
+    pub fn get_package_field(&self, field_name: &str) -> Option<toml::Value> {
+        let value = self.get_table("package")?.get(field_name)?;
+        if !is_inherited(value) {
+            return Some(value.clone());
+        }
+
+        self.workspace
+            .as_ref()?
+            .get_table("workspace")?
+            .get("package")?
+            .get(field_name)
+            .cloned()
     }
 
     pub fn get_field(&self, field_name: &str) -> Option<&toml::Value> {
@@ -56,50 +89,81 @@ impl TomlDocument {
             return Some(version);
         }
 
-        // Try package.version for Cargo.toml files
-        if let Some(package_table) = self.get_table("package")
-            && let Some(version_str) = package_table.get("version")?.as_str()
-        {
-            return Some(version_str.to_string());
-        }
-
-        None
+        // Try package.version for Cargo.toml files, version.workspace = true
+        // resolves through [workspace.package]
+        self.get_package_field("version")?
+            .as_str()
+            .map(|s| s.to_string())
     }
 
     pub fn get_dependencies(&self) -> Option<HashMap<String, serde_json::Value>> {
-        let deps_table = self.get_table("dependencies")?;
-        let mut deps = HashMap::new();
+        self.get_dependency_table("dependencies")
+    }
 
-        for (name, value) in deps_table {
-            let json_value = toml_value_to_json(value);
-            deps.insert(name.clone(), json_value);
-        }
+    pub fn get_dev_dependencies(&self) -> Option<HashMap<String, serde_json::Value>> {
+        self.get_dependency_table("dev-dependencies")
+    }
 
-        Some(deps)
+    pub fn get_build_dependencies(&self) -> Option<HashMap<String, serde_json::Value>> {
+        self.get_dependency_table("build-dependencies")
     }
 
-    pub fn get_dev_dependencies(&self) -> Option<HashMap<String, serde_json::Value>> {
-        let deps_table = self.get_table("dev-dependencies")?;
+    // entries of a dependency table with inherited ones replaced by their
+    // effective [workspace.dependencies] definition
+    fn get_dependency_table(&self, table_name: &str) -> Option<HashMap<String, serde_json::Value>> {
+        let deps_table = self.get_table(table_name)?;
         let mut deps = HashMap::new();
 
         for (name, value) in deps_table {
-            let json_value = toml_value_to_json(value);
+            let effective = self.resolve_dependency(name, value);
+            let json_value = toml_value_to_json(effective.as_ref().unwrap_or(value));
             deps.insert(name.clone(), json_value);
         }
 
         Some(deps)
     }
 
-    pub fn get_build_dependencies(&self) -> Option<HashMap<String, serde_json::Value>> {
-        let deps_table = self.get_table("build-dependencies")?;
-        let mut deps = HashMap::new();
+    // effective definition of an inherited dependency entry: the workspace
+    // entry with the local `features` added and local `optional` applied
+    // None when the entry is not inherited or the workspace does not define it
+    pub fn resolve_dependency(&self, name: &str, value: &toml::Value) -> Option<toml::Value> {
+        if !is_inherited(value) {
+            return None;
+        }
 
-        for (name, value) in deps_table {
-            let json_value = toml_value_to_json(value);
-            deps.insert(name.clone(), json_value);
+        let inherited = self
+            .workspace
+            .as_ref()?
+            .get_table("workspace")?
+            .get("dependencies")?
+            .get(name)?;
+        let mut effective = match inherited {
+            toml::Value::String(version) => {
+                let mut table = toml::value::Table::new();
+                table.insert("version".to_string(), toml::Value::String(version.clone()));
+                table
+            }
+            toml::Value::Table(table) => table.clone(),
+            _ => return None,
+        };
+
+        for (key, local) in value.as_table()? {
+            match (key.as_str(), local, effective.get_mut(key)) {
+                ("workspace", _, _) => {}
+                ("features", toml::Value::Array(extra), Some(toml::Value::Array(features))) => {
+                    for feature in extra {
+                        if !features.contains(feature) {
+                            features.push(feature.clone());
+                        }
+                    }
+                }
+                _ => {
+                    effective.insert(key.clone(), local.clone());
+                }
+            }
         }
 
-        Some(deps)
+        Some(toml::Value::Table(effective))
     }
 
     pub fn has_field(&self, field_name: &str) -> bool {
@@ -109,6 +173,197 @@ impl TomlDocument {
     pub fn has_table(&self, table_name: &str) -> bool {
         self.get_table(table_name).is_some()
     }
+
+    // dotted path lookup, e.g. "package.metadata.deptrack.changelog"
+    // segments may be quoted ("target.'cfg(unix)'.dependencies") and
+    // numeric segments index into arrays ("workspace.members.0")
+    pub fn get_path(&self, path: &str) -> Option<&toml::Value> {
+        split_path(path)?
+            .iter()
+            .try_fold(&self.content, |value, segment| match value {
+                toml::Value::Table(table) => table.get(segment.as_str()),
+                toml::Value::Array(array) => array.get(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    // typed extraction of the value at `path`, Ok(None) when it is not present
+    pub fn get_path_as<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<T>, TomlError> {
+        match self.get_path(path) {
+            Some(value) => value
+                .clone()
+                .try_into()
+                .map(Some)
+                .map_err(|e| TomlError::InvalidType(format!("{}: {}", path, e))),
+            None => Ok(None),
+        }
+    }
+
+    // dependencies declared with `workspace = true`, from every dependency table
+    pub fn workspace_inherited_dependencies(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .dependency_tables(&DEPENDENCY_TABLES)
+            .into_iter()
+            .flat_map(|table| table.iter())
+            .filter(|(_, dep)| is_inherited(dep))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // the [features] table, feature name -> features and dependencies it enables
+    pub fn get_features(&self) -> Option<HashMap<String, Vec<String>>> {
+        let features_table = self.get_table("features")?;
+        let mut features = HashMap::new();
+
+        for (name, value) in features_table {
+            let enables = value
+                .as_array()
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| entry.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            features.insert(name.clone(), enables);
+        }
+
+        Some(features)
+    }
+
+    // dependencies declared with `optional = true`, including target specific ones
+    pub fn get_optional_dependencies(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .dependency_tables(&["dependencies", "build-dependencies"])
+            .into_iter()
+            .flat_map(|table| table.iter())
+            .filter(|(_, dep)| dep.get("optional").and_then(toml::Value::as_bool) == Some(true))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // every dependency entry from every dependency table, target specific ones
+    // included, with inherited entries replaced by their effective definition
+    pub fn all_dependencies(&self) -> Vec<(String, toml::Value)> {
+        self.dependency_tables(&DEPENDENCY_TABLES)
+            .into_iter()
+            .flat_map(|table| table.iter())
+            .map(|(name, dep)| {
+                let effective = self.resolve_dependency(name, dep);
+                (name.clone(), effective.unwrap_or_else(|| dep.clone()))
+            })
+            .collect()
+    }
+
+    // version requirements declared for `dependency` in the given tables,
+    // target specific variants included; renamed entries match on `package`
+    pub fn dependency_requirements(&self, dependency: &str, tables: &[&str]) -> Vec<&str> {
+        self.dependency_tables(tables)
+            .into_iter()
+            .flat_map(|table| table.iter())
+            .filter_map(|(name, dep)| match dep {
+                toml::Value::String(req) if name == dependency => Some(req.as_str()),
+                toml::Value::Table(dep) => {
+                    let package = dep.get("package").and_then(toml::Value::as_str);
+                    (package.unwrap_or(name) == dependency)
+                        .then(|| dep.get("version").and_then(toml::Value::as_str))
+                        .flatten()
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // the named tables at the root and under every target.<cfg>
+    pub(crate) fn dependency_tables(&self, tables: &[&str]) -> Vec<&toml::value::Table> {
+        let mut dependency_tables: Vec<&toml::value::Table> =
+            tables.iter().filter_map(|t| self.get_table(t)).collect();
+        if let Some(targets) = self.get_table("target") {
+            for target in targets.values().filter_map(toml::Value::as_table) {
+                dependency_tables.extend(
+                    tables
+                        .iter()
+                        .filter_map(|t| target.get(*t).and_then(toml::Value::as_table)),
+                );
+            }
+        }
+        dependency_tables
+    }
+
+    // the [package.metadata.deptrack] table, defaults when absent
+    pub fn deptrack_metadata(&self) -> Result<DeptrackMetadata, TomlError> {
+        Ok(self
+            .get_path_as(DEPTRACK_METADATA_PATH)?
+            .unwrap_or_default())
+    }
+}
+
+pub const DEPTRACK_METADATA_PATH: &str = "package.metadata.deptrack";
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+// per-crate options read from [package.metadata.deptrack]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DeptrackMetadata {
+    // changelog file relative to the crate root, overrides changelog_file_name
+    pub changelog: Option<String>,
+    // workspace dependencies re-exported behind a stable api, whose changes
+    // stop at this crate with impact.stop_at_boundaries
+    pub stable_reexports: Vec<String>,
+}
+
+// `key.workspace = true`, in either dotted or inline table form
+fn is_inherited(value: &toml::Value) -> bool {
+    value.get("workspace").and_then(toml::Value::as_bool) == Some(true)
+}
+
+fn split_path(path: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    loop {
+        let mut segment = String::new();
+        match chars.peek() {
+            Some(&quote) if quote == '"' || quote == '\'' => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        c if c == quote => break,
+                        c => segment.push(c),
+                    }
+                }
+            }
+            _ => {
+                while let Some(&c) = chars.peek() {
+                    if c == '.' {
+                        break;
+                    }
+                    segment.push(c);
+                    chars.next();
+                }
+                if segment.is_empty() {
+                    return None;
+                }
+            }
+        }
+        segments.push(segment);
+
+        match chars.next() {
+            None => return Some(segments),
+            Some('.') => continue,
+            Some(_) => return None,
+        }
+    }
 }
 
 fn toml_value_to_json(value: &toml::Value) -> serde_json::Value {
@@ -0,0 +1,460 @@
+use super::types::TomlError;
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+// dependency tables cargo knows about, target.<cfg>.* variants are handled separately
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+// dependency tables kept by `cargo publish`, dev-dependencies without a
+// version are stripped instead
+const PUBLISHED_DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "build-dependencies"];
+
+// format preserving editor for toml files, comments, ordering and
+// whitespace of untouched parts are kept as they are
+#[derive(Debug, Clone)]
+pub struct TomlWriter {
+    pub file_path: String,
+    document: DocumentMut,
+}
+
+impl TomlWriter {
+    pub fn read_file<P: AsRef<Path>>(file_path: P) -> Result<Self, TomlError> {
+        let path = file_path.as_ref();
+        let path_str = path.to_string_lossy().to_string();
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            TomlError::IoError(format!("Failed to read file '{}': {}", path_str, e))
+        })?;
+
+        let document = content.parse::<DocumentMut>().map_err(|e| {
+            TomlError::ParseError(format!("Failed to parse TOML in '{}': {}", path_str, e))
+        })?;
+
+        Ok(Self {
+            file_path: path_str,
+            document,
+        })
+    }
+
+    pub fn parse_string(content: &str, file_path: String) -> Result<Self, TomlError> {
+        let document = content
+            .parse::<DocumentMut>()
+            .map_err(|e| TomlError::ParseError(format!("Failed to parse TOML: {}", e)))?;
+
+        Ok(Self {
+            file_path,
+            document,
+        })
+    }
+
+    // write back to the file the document was read from
+    pub fn write(&self) -> Result<(), TomlError> {
+        self.write_to(&self.file_path)
+    }
+
+    pub fn write_to<P: AsRef<Path>>(&self, file_path: P) -> Result<(), TomlError> {
+        let path = file_path.as_ref();
+        fs::write(path, self.document.to_string()).map_err(|e| {
+            TomlError::IoError(format!(
+                "Failed to write file '{}': {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })
+    }
+
+    pub fn document(&self) -> &DocumentMut {
+        &self.document
+    }
+
+    pub fn set_package_version(&mut self, version: &str) -> Result<(), TomlError> {
+        let package = self
+            .document
+            .get_mut("package")
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| TomlError::FieldNotFound("package".to_string()))?;
+
+        match package.get_mut("version") {
+            Some(item) if item.is_str() => {
+                replace_string(item, version);
+                Ok(())
+            }
+            Some(_) => Err(TomlError::InvalidType(
+                "package.version is not a string (inherited from the workspace?)".to_string(),
+            )),
+            None => {
+                package.insert("version", toml_edit::value(version));
+                Ok(())
+            }
+        }
+    }
+
+    // updates the version requirement of `name` in every dependency table,
+    // including target specific and [workspace.dependencies] ones; renamed
+    // dependencies are matched by their `package` key
+    // returns how many entries were changed, path-only entries are left alone
+    pub fn set_dependency_version(&mut self, name: &str, requirement: &str) -> usize {
+        let mut updated = 0;
+        let root = self.document.as_table_mut();
+
+        for table_name in DEPENDENCY_TABLES {
+            if let Some(deps) = root.get_mut(table_name).and_then(Item::as_table_like_mut) {
+                updated += update_dependency_table(deps, name, requirement);
+            }
+        }
+
+        if let Some(targets) = root.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (_, target) in targets.iter_mut() {
+                let Some(target) = target.as_table_like_mut() else {
+                    continue;
+                };
+                for table_name in DEPENDENCY_TABLES {
+                    if let Some(deps) = target.get_mut(table_name).and_then(Item::as_table_like_mut)
+                    {
+                        updated += update_dependency_table(deps, name, requirement);
+                    }
+                }
+            }
+        }
+
+        if let Some(deps) = root
+            .get_mut("workspace")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|ws| ws.get_mut("dependencies"))
+            .and_then(Item::as_table_like_mut)
+        {
+            updated += update_dependency_table(deps, name, requirement);
+        }
+
+        updated
+    }
+
+    // adds `version = requirement` to the path dependencies on `name` that
+    // have none, in [dependencies], [build-dependencies], their target
+    // specific variants and [workspace.dependencies]; entries inheriting from
+    // the workspace are left to the workspace manifest
+    // returns how many entries were changed
+    pub fn set_path_dependency_version(&mut self, name: &str, requirement: &str) -> usize {
+        let mut updated = 0;
+        let root = self.document.as_table_mut();
+
+        for table_name in PUBLISHED_DEPENDENCY_TABLES {
+            if let Some(deps) = root.get_mut(table_name).and_then(Item::as_table_like_mut) {
+                updated += add_path_dependency_version(deps, name, requirement);
+            }
+        }
+
+        if let Some(targets) = root.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (_, target) in targets.iter_mut() {
+                let Some(target) = target.as_table_like_mut() else {
+                    continue;
+                };
+                for table_name in PUBLISHED_DEPENDENCY_TABLES {
+                    if let Some(deps) = target.get_mut(table_name).and_then(Item::as_table_like_mut)
+                    {
+                        updated += add_path_dependency_version(deps, name, requirement);
+                    }
+                }
+            }
+        }
+
+        if let Some(deps) = root
+            .get_mut("workspace")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|ws| ws.get_mut("dependencies"))
+            .and_then(Item::as_table_like_mut)
+        {
+            updated += add_path_dependency_version(deps, name, requirement);
+        }
+
+        updated
+    }
+
+    // sets `key` in the table at the dotted `table_path` (e.g. "package.metadata.deptrack"),
+    // missing tables are created; an existing value keeps its surrounding formatting
+    pub fn insert(
+        &mut self,
+        table_path: &str,
+        key: &str,
+        value: impl Into<Value>,
+    ) -> Result<(), TomlError> {
+        let mut table = self.document.as_table_mut();
+        for segment in table_path.split('.').filter(|s| !s.is_empty()) {
+            let item = table.entry(segment).or_insert_with(|| {
+                let mut new_table = Table::new();
+                new_table.set_implicit(true);
+                Item::Table(new_table)
+            });
+            table = item.as_table_mut().ok_or_else(|| {
+                TomlError::InvalidType(format!("'{}' in '{}' is not a table", segment, table_path))
+            })?;
+        }
+
+        let mut value = value.into();
+        match table.get_mut(key) {
+            Some(Item::Value(existing)) => {
+                *value.decor_mut() = existing.decor().clone();
+                *existing = value;
+            }
+            _ => {
+                table.insert(key, Item::Value(value));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for TomlWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+fn update_dependency_table(
+    deps: &mut dyn toml_edit::TableLike,
+    name: &str,
+    requirement: &str,
+) -> usize {
+    let mut updated = 0;
+
+    for (key, item) in deps.iter_mut() {
+        if item.is_str() {
+            if key.get() == name {
+                replace_string(item, requirement);
+                updated += 1;
+            }
+            continue;
+        }
+
+        let Some(dep) = item.as_table_like_mut() else {
+            continue;
+        };
+        let package = dep
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or(key.get());
+        if package != name {
+            continue;
+        }
+
+        // inherited and path-only dependencies have no requirement to update
+        if let Some(version) = dep.get_mut("version")
+            && version.is_str()
+        {
+            replace_string(version, requirement);
+            updated += 1;
+        }
+    }
+
+    updated
+}
+
+fn add_path_dependency_version(
+    deps: &mut dyn toml_edit::TableLike,
+    name: &str,
+    requirement: &str,
+) -> usize {
+    let mut updated = 0;
+
+    for (key, item) in deps.iter_mut() {
+        let inline = item.is_inline_table();
+        let Some(dep) = item.as_table_like_mut() else {
+            continue;
+        };
+        let package = dep
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or(key.get());
+        if package != name
+            || !dep.contains_key("path")
+            || dep.contains_key("version")
+            || dep.contains_key("workspace")
+        {
+            continue;
+        }
+
+        let mut version = Value::from(requirement);
+        // the space before the closing brace belongs to the last value, keep it last
+        if inline && let Some((_, Item::Value(last))) = dep.iter_mut().last() {
+            let suffix = last.decor().suffix().cloned();
+            if let Some(suffix) = suffix {
+                last.decor_mut().set_suffix("");
+                version.decor_mut().set_suffix(suffix);
+            }
+        }
+        dep.insert("version", Item::Value(version));
+        updated += 1;
+    }
+
+    updated
+}
+
+// swap a string value while keeping the whitespace and comments around it
+fn replace_string(item: &mut Item, new: &str) {
+    if let Some(value) = item.as_value_mut() {
+        let decor = value.decor().clone();
+        *value = Value::from(new);
+        *value.decor_mut() = decor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const MANIFEST: &str = r#"# crate manifest
+[package]
+name = "demo"
+version = "0.1.0"   # bumped by release tooling
+edition = "2021"
+
+[dependencies]
+serde = "1.0"   # pinned major
+tokio = { version = "1.0", features = ["full"] }
+local = { path = "../local" }
+renamed = { package = "serde", version = "1.0" }
+
+[target.'cfg(unix)'.dependencies]
+serde = { version = "1.0" }
+
+[dev-dependencies.serde]
+version = "1.0"
+"#;
+
+    #[test]
+    fn test_set_package_version_preserves_formatting() {
+        let mut writer = TomlWriter::parse_string(MANIFEST, "Cargo.toml".to_string()).unwrap();
+        writer.set_package_version("0.2.0").unwrap();
+
+        let expected = MANIFEST.replace(
+            "version = \"0.1.0\"   # bumped",
+            "version = \"0.2.0\"   # bumped",
+        );
+        assert_eq!(writer.to_string(), expected);
+    }
+
+    #[test]
+    fn test_set_package_version_errors() {
+        let mut writer =
+            TomlWriter::parse_string("[workspace]\nmembers = []\n", String::new()).unwrap();
+        assert!(matches!(
+            writer.set_package_version("1.0.0"),
+            Err(TomlError::FieldNotFound(_))
+        ));
+
+        let mut writer = TomlWriter::parse_string(
+            "[package]\nname = \"x\"\nversion.workspace = true\n",
+            String::new(),
+        )
+        .unwrap();
+        assert!(matches!(
+            writer.set_package_version("1.0.0"),
+            Err(TomlError::InvalidType(_))
+        ));
+    }
+
+    #[test]
+    fn test_set_dependency_version() {
+        let mut writer = TomlWriter::parse_string(MANIFEST, "Cargo.toml".to_string()).unwrap();
+
+        // plain, renamed, target specific and dev-dependencies table
+        assert_eq!(writer.set_dependency_version("serde", "1.0.200"), 4);
+        assert_eq!(writer.set_dependency_version("local", "2.0"), 0);
+        assert_eq!(writer.set_dependency_version("missing", "2.0"), 0);
+
+        let output = writer.to_string();
+        assert!(output.contains("serde = \"1.0.200\"   # pinned major"));
+        assert!(output.contains("renamed = { package = \"serde\", version = \"1.0.200\" }"));
+        assert!(output.contains("tokio = { version = \"1.0\", features = [\"full\"] }"));
+        assert!(output.contains("[dev-dependencies.serde]\nversion = \"1.0.200\""));
+        assert!(output.contains("local = { path = \"../local\" }"));
+    }
+
+    #[test]
+    fn test_set_path_dependency_version() {
+        let manifest = r#"[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+local = { path = "../local" }   # sibling crate
+pinned = { path = "../pinned", version = "0.3" }
+inherited = { workspace = true }
+aliased = { package = "local", path = "../local", features = ["std"] }
+
+[dependencies.dotted]
+path = "../dotted"
+
+[dev-dependencies]
+local = { path = "../local" }
+
+[workspace.dependencies]
+inherited = { path = "crates/inherited" }
+"#;
+        let mut writer = TomlWriter::parse_string(manifest, "Cargo.toml".to_string()).unwrap();
+
+        assert_eq!(writer.set_path_dependency_version("local", "0.2.0"), 2);
+        assert_eq!(writer.set_path_dependency_version("pinned", "0.3.1"), 0);
+        assert_eq!(writer.set_path_dependency_version("inherited", "1.0.0"), 1);
+        assert_eq!(writer.set_path_dependency_version("dotted", "0.1.0"), 1);
+
+        let output = writer.to_string();
+        assert!(
+            output
+                .contains("local = { path = \"../local\", version = \"0.2.0\" }   # sibling crate")
+        );
+        assert!(output.contains("pinned = { path = \"../pinned\", version = \"0.3\" }"));
+        assert!(output.contains("inherited = { workspace = true }"));
+        assert!(output.contains(
+            "aliased = { package = \"local\", path = \"../local\", features = [\"std\"], version = \"0.2.0\" }"
+        ));
+        assert!(
+            output.contains("[dependencies.dotted]\npath = \"../dotted\"\nversion = \"0.1.0\"")
+        );
+        assert!(output.contains("[dev-dependencies]\nlocal = { path = \"../local\" }"));
+        assert!(
+            output.contains("inherited = { path = \"crates/inherited\", version = \"1.0.0\" }")
+        );
+    }
+
+    #[test]
+    fn test_insert_creates_tables() {
+        let mut writer = TomlWriter::parse_string(MANIFEST, "Cargo.toml".to_string()).unwrap();
+        writer.insert("dependencies", "anyhow", "1.0").unwrap();
+        writer
+            .insert("package.metadata.deptrack", "changelog", "CHANGES.md")
+            .unwrap();
+        writer.insert("package", "edition", "2024").unwrap();
+        assert!(writer.insert("package.name", "x", "y").is_err());
+
+        let output = writer.to_string();
+        assert!(output.starts_with("# crate manifest\n"));
+        assert!(output.contains("anyhow = \"1.0\""));
+        assert!(output.contains("[package.metadata.deptrack]\nchangelog = \"CHANGES.md\""));
+        assert!(output.contains("edition = \"2024\""));
+
+        let parsed: toml::Value = output.parse().unwrap();
+        assert_eq!(
+            parsed["package"]["metadata"]["deptrack"]["changelog"].as_str(),
+            Some("CHANGES.md")
+        );
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Cargo.toml");
+        fs::write(&path, MANIFEST).unwrap();
+
+        let mut writer = TomlWriter::read_file(&path).unwrap();
+        writer.write().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), MANIFEST);
+
+        writer.set_package_version("1.0.0").unwrap();
+        writer.write().unwrap();
+        let doc = super::super::TomlReader::read_file(&path).unwrap();
+        assert_eq!(doc.get_version(), Some("1.0.0".to_string()));
+    }
+}
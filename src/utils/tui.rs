@@ -0,0 +1,570 @@
+use super::cargo_ops::types::{CrateDependencyGraph, CrateId};
+use super::config::DeptrackConfig;
+use super::feature_checker::FeatureChecker;
+use super::lockfile_checker::LockfileChecker;
+use super::manifest_checker::ManifestChecker;
+use super::msrv_checker::MsrvChecker;
+use super::severity::{Issue, IssueSeverity};
+use crate::error::Result;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// a crate as the browser shows it
+#[derive(Debug, Clone)]
+pub struct CrateEntry {
+    pub id: CrateId,
+    pub version: String,
+    pub path: PathBuf,
+    pub dependencies: Vec<CrateId>,
+    pub dependents: Vec<CrateId>,
+    /// issues with the checker that reported them
+    pub issues: Vec<(&'static str, Issue)>,
+}
+
+/// pane the arrow keys move in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Crates,
+    Related,
+}
+
+/// state of `deptrack tui`, kept apart from the terminal so it can be driven by tests
+#[derive(Debug, Clone)]
+pub struct TuiApp {
+    crates: Vec<CrateEntry>,
+    workspaces: Vec<String>,
+    /// index into `workspaces`, None shows every workspace
+    workspace: Option<usize>,
+    query: String,
+    searching: bool,
+    focus: Focus,
+    selected: usize,
+    related: usize,
+    quit: bool,
+}
+
+impl TuiApp {
+    /// browser over every crate of the graph, with the issues the checkers
+    /// that need no git range report for them
+    pub fn new<P: AsRef<Path>>(
+        graph: &CrateDependencyGraph,
+        repo_path: P,
+        config: &DeptrackConfig,
+    ) -> Result<Self> {
+        let severity = &config.direct_severity;
+        let mut issues: HashMap<CrateId, Vec<(&'static str, Issue)>> = HashMap::new();
+        let mut collect = |checker: &'static str, statuses: Vec<(CrateId, Vec<Issue>)>| {
+            for (crate_id, found) in statuses {
+                let entry = issues.entry(crate_id).or_default();
+                entry.extend(found.into_iter().map(|issue| (checker, issue)));
+            }
+        };
+        collect(
+            "manifest",
+            ManifestChecker::analyze_all(graph, severity)?
+                .statuses
+                .into_iter()
+                .map(|(id, status)| (id, status.issues))
+                .collect(),
+        );
+        collect(
+            "msrv",
+            MsrvChecker::analyze_all(graph, severity, &config.msrv)?
+                .statuses
+                .into_iter()
+                .map(|(id, status)| (id, status.issues))
+                .collect(),
+        );
+        collect(
+            "features",
+            FeatureChecker::analyze_all(graph, severity)?
+                .statuses
+                .into_iter()
+                .map(|(id, status)| (id, status.issues))
+                .collect(),
+        );
+        collect(
+            "lockfile",
+            LockfileChecker::analyze_all(graph, repo_path, severity)?
+                .statuses
+                .into_iter()
+                .map(|(id, status)| (id, status.issues))
+                .collect(),
+        );
+
+        let sorted = |ids: Vec<&CrateId>| {
+            let mut ids: Vec<CrateId> = ids.into_iter().cloned().collect();
+            ids.sort_by_key(|id| id.display_name());
+            ids.dedup();
+            ids
+        };
+        let mut crates: Vec<CrateEntry> = graph
+            .crates
+            .values()
+            .map(|info| {
+                let mut crate_issues = issues.remove(&info.id).unwrap_or_default();
+                // errors first
+                crate_issues.sort_by_key(|(_, issue)| issue.severity != IssueSeverity::Error);
+                CrateEntry {
+                    id: info.id.clone(),
+                    version: info.version.clone(),
+                    path: info.path.clone(),
+                    dependencies: sorted(graph.get_dependencies(&info.id)),
+                    dependents: sorted(graph.get_dependents(&info.id)),
+                    issues: crate_issues,
+                }
+            })
+            .collect();
+        crates.sort_by_key(|entry| entry.id.display_name());
+        Ok(Self::from_entries(crates))
+    }
+
+    pub fn from_entries(crates: Vec<CrateEntry>) -> Self {
+        let mut workspaces: Vec<String> = crates.iter().map(|c| c.id.workspace.clone()).collect();
+        workspaces.sort();
+        workspaces.dedup();
+        Self {
+            crates,
+            workspaces,
+            workspace: None,
+            query: String::new(),
+            searching: false,
+            focus: Focus::Crates,
+            selected: 0,
+            related: 0,
+            quit: false,
+        }
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// workspace the list is narrowed to, None for all of them
+    pub fn workspace(&self) -> Option<&str> {
+        self.workspace.map(|i| self.workspaces[i].as_str())
+    }
+
+    /// crates in the list, best search matches first
+    pub fn visible(&self) -> Vec<&CrateEntry> {
+        let workspace = self.workspace();
+        let mut matches: Vec<(usize, &CrateEntry)> = self
+            .crates
+            .iter()
+            .filter(|entry| workspace.is_none_or(|ws| entry.id.workspace == ws))
+            .filter_map(|entry| Some((fuzzy_score(&self.query, &entry.id.name)?, entry)))
+            .collect();
+        // stable, so equal scores keep the name order
+        matches.sort_by_key(|(score, _)| *score);
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// the highlighted crate
+    pub fn selected(&self) -> Option<&CrateEntry> {
+        self.visible().get(self.selected).copied()
+    }
+
+    // dependencies then dependents of the highlighted crate
+    fn related_crates(&self) -> Vec<&CrateId> {
+        self.selected()
+            .map(|entry| entry.dependencies.iter().chain(&entry.dependents).collect())
+            .unwrap_or_default()
+    }
+
+    /// apply a key press
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if self.searching {
+            match key {
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.selected = 0;
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.selected = 0;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.focus == Focus::Related => self.focus = Focus::Crates,
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.selected = 0;
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.focus = Focus::Crates;
+            }
+            KeyCode::Char('w') => {
+                self.workspace = match self.workspace {
+                    None if !self.workspaces.is_empty() => Some(0),
+                    Some(i) if i + 1 < self.workspaces.len() => Some(i + 1),
+                    _ => None,
+                };
+                self.selected = 0;
+            }
+            KeyCode::Tab | KeyCode::Right | KeyCode::Left | KeyCode::Char('l' | 'h') => {
+                self.focus = match self.focus {
+                    Focus::Crates if !self.related_crates().is_empty() => {
+                        self.related = 0;
+                        Focus::Related
+                    }
+                    _ => Focus::Crates,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.step(1),
+            KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+            KeyCode::Enter if self.focus == Focus::Related => {
+                if let Some(target) = self
+                    .related_crates()
+                    .get(self.related)
+                    .map(|id| (*id).clone())
+                {
+                    self.jump_to(&target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        let len = match self.focus {
+            Focus::Crates => self.visible().len(),
+            Focus::Related => self.related_crates().len(),
+        };
+        let position = match self.focus {
+            Focus::Crates => &mut self.selected,
+            Focus::Related => &mut self.related,
+        };
+        if len > 0 {
+            *position = position.saturating_add_signed(delta).min(len - 1);
+        }
+    }
+
+    // highlight `target`, dropping filters that hide it
+    fn jump_to(&mut self, target: &CrateId) {
+        self.query.clear();
+        self.workspace = None;
+        self.focus = Focus::Crates;
+        if let Some(index) = self.visible().iter().position(|entry| &entry.id == target) {
+            self.selected = index;
+        }
+    }
+
+    /// draw the search bar, crate list, details and key help
+    pub fn draw(&self, frame: &mut Frame) {
+        let [search_area, main_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main_area);
+
+        let scope = match self.workspace() {
+            Some(workspace) => format!(" search in {} ", workspace),
+            None => " search ".to_string(),
+        };
+        let cursor = if self.searching { "_" } else { "" };
+        frame.render_widget(
+            Paragraph::new(format!("{}{}", self.query, cursor)).block(
+                Block::bordered()
+                    .title(scope)
+                    .border_style(focused(self.searching)),
+            ),
+            search_area,
+        );
+
+        let visible = self.visible();
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|entry| {
+                let mut spans = vec![
+                    Span::raw(entry.id.display_name()),
+                    Span::styled(
+                        format!(" v{}", entry.version),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                ];
+                if let Some(marker) = issue_marker(&entry.issues) {
+                    spans.push(marker);
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let mut list_state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(
+                    Block::bordered()
+                        .title(format!(" crates ({}) ", visible.len()))
+                        .border_style(focused(!self.searching && self.focus == Focus::Crates)),
+                )
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list_area,
+            &mut list_state,
+        );
+
+        self.draw_details(frame, detail_area);
+
+        frame.render_widget(
+            Paragraph::new("/ search  w workspace  tab dependencies  enter open  esc back  q quit")
+                .style(Style::new().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+        let block = Block::bordered()
+            .title(" details ")
+            .border_style(focused(!self.searching && self.focus == Focus::Related));
+        let Some(entry) = self.selected() else {
+            frame.render_widget(Paragraph::new("no crate matches").block(block), area);
+            return;
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("{} v{}", entry.id.name, entry.version),
+                Style::new().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(format!("workspace {}", entry.id.workspace)),
+            Line::from(entry.path.display().to_string()),
+        ];
+
+        let mut index = 0;
+        for (title, ids) in [
+            ("dependencies", &entry.dependencies),
+            ("dependents", &entry.dependents),
+        ] {
+            lines.push(Line::default());
+            lines.push(Line::from(format!("{} ({})", title, ids.len())));
+            for id in ids {
+                let style = if self.focus == Focus::Related && index == self.related {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", id.display_name()),
+                    style,
+                )));
+                index += 1;
+            }
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from(format!("issues ({})", entry.issues.len())));
+        for (checker, issue) in &entry.issues {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {} ", issue.severity),
+                    Style::new().fg(severity_color(issue.severity)),
+                ),
+                Span::raw(format!("[{}] {}", checker, issue.message)),
+            ]));
+        }
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+fn focused(is_focused: bool) -> Style {
+    if is_focused {
+        Style::new().fg(Color::Cyan)
+    } else {
+        Style::new()
+    }
+}
+
+fn severity_color(severity: IssueSeverity) -> Color {
+    match severity {
+        IssueSeverity::Error => Color::Red,
+        IssueSeverity::Warning => Color::Yellow,
+    }
+}
+
+// issue count after a crate name, colored by the worst severity
+fn issue_marker(issues: &[(&'static str, Issue)]) -> Option<Span<'static>> {
+    let worst = issues
+        .iter()
+        .map(|(_, issue)| issue.severity)
+        .min_by_key(|severity| *severity != IssueSeverity::Error)?;
+    Some(Span::styled(
+        format!(" ({})", issues.len()),
+        Style::new().fg(severity_color(worst)),
+    ))
+}
+
+/// how well `query` fuzzily matches `candidate`, lower is better; None when
+/// the query letters do not all appear in order (case insensitive)
+///
+/// the score counts the letters skipped before and between matches, so
+/// prefixes and runs of consecutive letters come first
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut letters = candidate.chars().flat_map(char::to_lowercase);
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            match letters.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => score += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(score)
+}
+
+/// run the browser on the terminal until it is quit
+pub fn run<P: AsRef<Path>>(repo_path: P) -> Result<()> {
+    let repo_path = repo_path.as_ref();
+    let graph = CrateDependencyGraph::build_from_repository(repo_path)?;
+    let config = DeptrackConfig::load_or_default(repo_path);
+    let mut app = TuiApp::new(&graph, repo_path, &config)?;
+
+    let mut terminal = ratatui::init();
+    let result = (|| -> std::io::Result<()> {
+        while !app.should_quit() {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                app.handle_key(key.code);
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::severity::IssueType;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn entry(workspace: &str, name: &str, dependencies: &[&CrateId]) -> CrateEntry {
+        CrateEntry {
+            id: CrateId::new(workspace.to_string(), name.to_string()),
+            version: "0.1.0".to_string(),
+            path: PathBuf::from(name),
+            dependencies: dependencies.iter().map(|id| (*id).clone()).collect(),
+            dependents: Vec::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    fn app() -> TuiApp {
+        let core = entry("base", "core", &[]);
+        let mut core_utils = entry("base", "core_utils", &[]);
+        core_utils.issues.push((
+            "manifest",
+            Issue::new(
+                IssueSeverity::Error,
+                IssueType::IncompleteManifest,
+                "no description".to_string(),
+            ),
+        ));
+        let cli = entry("tools", "cli", &[&core.id]);
+        TuiApp::from_entries(vec![core, core_utils, cli])
+    }
+
+    fn names(app: &TuiApp) -> Vec<String> {
+        app.visible().iter().map(|e| e.id.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "core"), Some(0));
+        assert_eq!(fuzzy_score("core", "core_utils"), Some(0));
+        assert_eq!(fuzzy_score("cu", "core_utils"), Some(4));
+        assert_eq!(fuzzy_score("CLI", "cli"), Some(0));
+        assert_eq!(fuzzy_score("uc", "core_utils"), None);
+    }
+
+    #[test]
+    fn test_search_and_workspaces() {
+        let mut app = app();
+        assert_eq!(names(&app), vec!["core", "core_utils", "cli"]);
+
+        for key in [KeyCode::Char('/'), KeyCode::Char('c'), KeyCode::Char('l')] {
+            app.handle_key(key);
+        }
+        assert_eq!(app.query(), "cl");
+        assert_eq!(names(&app), vec!["cli", "core_utils"]);
+        app.handle_key(KeyCode::Enter);
+        // out of search, letters are commands again
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.selected().unwrap().id.name, "core_utils");
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.query(), "");
+
+        app.handle_key(KeyCode::Char('w'));
+        assert_eq!(app.workspace(), Some("base"));
+        assert_eq!(names(&app), vec!["core", "core_utils"]);
+        app.handle_key(KeyCode::Char('w'));
+        app.handle_key(KeyCode::Char('w'));
+        assert_eq!(app.workspace(), None);
+
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_follow_dependency() {
+        let mut app = app();
+        app.handle_key(KeyCode::Char('w'));
+        app.handle_key(KeyCode::Char('w'));
+        assert_eq!(app.selected().unwrap().id.name, "cli");
+
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.workspace(), None);
+        assert_eq!(app.selected().unwrap().id.name, "core");
+        // core has nothing to move into
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected().unwrap().id.name, "core_utils");
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = app();
+        app.handle_key(KeyCode::Down);
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("crates (3)"));
+        assert!(screen.contains("base::core_utils v0.1.0 (1)"));
+        assert!(screen.contains("[manifest] no description"));
+        assert!(screen.contains("workspace base"));
+    }
+}
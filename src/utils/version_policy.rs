@@ -0,0 +1,202 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// how far a version has to move, ordered from none to major
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequiredBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl RequiredBump {
+    /// how far `current` moved from `base`, None when it did not move forward
+    pub fn between(base: &Version, current: &Version) -> Self {
+        if current <= base {
+            RequiredBump::None
+        } else if current.major != base.major {
+            RequiredBump::Major
+        } else if current.minor != base.minor {
+            RequiredBump::Minor
+        } else {
+            RequiredBump::Patch
+        }
+    }
+}
+
+impl fmt::Display for RequiredBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequiredBump::None => write!(f, "none"),
+            RequiredBump::Patch => write!(f, "patch"),
+            RequiredBump::Minor => write!(f, "minor"),
+            RequiredBump::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// maturity of a crate, which selects the rule of the version policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrateMaturity {
+    /// publishable crate below 1.0.0
+    PreRelease,
+    /// publishable crate at 1.0.0 or above
+    Stable,
+    /// crate with `publish = false`
+    Internal,
+}
+
+impl CrateMaturity {
+    /// maturity of a crate at `version`, before the change being checked
+    pub fn of(version: &Version, publishable: bool) -> Self {
+        if !publishable {
+            CrateMaturity::Internal
+        } else if version.major == 0 {
+            CrateMaturity::PreRelease
+        } else {
+            CrateMaturity::Stable
+        }
+    }
+}
+
+impl fmt::Display for CrateMaturity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrateMaturity::PreRelease => write!(f, "pre_release"),
+            CrateMaturity::Stable => write!(f, "stable"),
+            CrateMaturity::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// bumps a changed crate needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// for any change
+    pub change: RequiredBump,
+    /// for a breaking change
+    pub breaking: RequiredBump,
+}
+
+impl PolicyRule {
+    pub fn new(change: RequiredBump, breaking: RequiredBump) -> Self {
+        Self { change, breaking }
+    }
+}
+
+/// required version bumps per crate maturity, `[version_policy]` in deptrack.toml
+///
+/// a change is breaking when the changelog section of the new version has a
+/// breaking entry (`feat!: ...`, `fix(api)!: ...` or a description starting
+/// with "BREAKING") or a feature was removed from the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionPolicy {
+    /// defaults to a minor bump for breaking changes, semver for 0.x
+    #[serde(default = "default_pre_release")]
+    pub pre_release: PolicyRule,
+    /// defaults to a major bump for breaking changes
+    #[serde(default = "default_stable")]
+    pub stable: PolicyRule,
+    /// defaults to a patch bump for any change
+    #[serde(default = "default_internal")]
+    pub internal: PolicyRule,
+}
+
+fn default_pre_release() -> PolicyRule {
+    PolicyRule::new(RequiredBump::Patch, RequiredBump::Minor)
+}
+
+fn default_stable() -> PolicyRule {
+    PolicyRule::new(RequiredBump::Patch, RequiredBump::Major)
+}
+
+fn default_internal() -> PolicyRule {
+    PolicyRule::new(RequiredBump::Patch, RequiredBump::Patch)
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        Self {
+            pre_release: default_pre_release(),
+            stable: default_stable(),
+            internal: default_internal(),
+        }
+    }
+}
+
+impl VersionPolicy {
+    pub fn rule(&self, maturity: CrateMaturity) -> &PolicyRule {
+        match maturity {
+            CrateMaturity::PreRelease => &self.pre_release,
+            CrateMaturity::Stable => &self.stable,
+            CrateMaturity::Internal => &self.internal,
+        }
+    }
+
+    /// bump a changed crate of `maturity` needs
+    pub fn required(&self, maturity: CrateMaturity, breaking: bool) -> RequiredBump {
+        let rule = self.rule(maturity);
+        if breaking {
+            rule.breaking.max(rule.change)
+        } else {
+            rule.change
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(v: &str) -> Version {
+        Version::parse(v).unwrap()
+    }
+
+    #[test]
+    fn test_between() {
+        let base = version("1.2.3");
+        assert_eq!(RequiredBump::between(&base, &base), RequiredBump::None);
+        assert_eq!(
+            RequiredBump::between(&base, &version("1.2.2")),
+            RequiredBump::None
+        );
+        assert_eq!(
+            RequiredBump::between(&base, &version("1.2.4-rc.1")),
+            RequiredBump::Patch
+        );
+        assert_eq!(
+            RequiredBump::between(&base, &version("1.3.0")),
+            RequiredBump::Minor
+        );
+        assert_eq!(
+            RequiredBump::between(&base, &version("2.0.0")),
+            RequiredBump::Major
+        );
+    }
+
+    #[test]
+    fn test_required() {
+        let policy = VersionPolicy::default();
+        let pre_release = CrateMaturity::of(&version("0.4.0"), true);
+        let stable = CrateMaturity::of(&version("1.0.0"), true);
+        let internal = CrateMaturity::of(&version("3.0.0"), false);
+
+        assert_eq!(policy.required(pre_release, false), RequiredBump::Patch);
+        assert_eq!(policy.required(pre_release, true), RequiredBump::Minor);
+        assert_eq!(policy.required(stable, true), RequiredBump::Major);
+        assert_eq!(policy.required(internal, true), RequiredBump::Patch);
+
+        let relaxed: VersionPolicy = toml::from_str(
+            "internal = { change = \"none\", breaking = \"none\" }\n\
+             stable = { change = \"patch\", breaking = \"minor\" }\n",
+        )
+        .unwrap();
+        assert_eq!(relaxed.required(internal, true), RequiredBump::None);
+        assert_eq!(relaxed.required(stable, true), RequiredBump::Minor);
+        assert_eq!(relaxed.required(pre_release, true), RequiredBump::Minor);
+    }
+}
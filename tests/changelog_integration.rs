@@ -0,0 +1,212 @@
+// end-to-end changelog checks against generated repositories
+
+use deptrack::utils::cargo_ops::CrateDependencyGraph;
+use deptrack::utils::git_ops::GitRef;
+use deptrack::utils::testing::{ChangelogFixture, TestCrate, TestRepoBuilder, TestWorkspace};
+use deptrack::{
+    ChangelogAnalysis, ChangelogChecker, ChangelogConfig, DeptrackConfig, IssueType, RequiredBump,
+    SeverityConfig, VersionBumpAnalysis,
+};
+
+// run impact, version and changelog analysis for master..feature
+fn analyze(repo_path: &std::path::Path) -> (CrateDependencyGraph, ChangelogAnalysis) {
+    let graph = CrateDependencyGraph::build_from_repository(repo_path).unwrap();
+    let base_ref = GitRef::Branch("master".to_string());
+    let current_ref = GitRef::Branch("feature".to_string());
+    let direct = SeverityConfig::default_direct();
+    let transitive = SeverityConfig::default_transitive();
+
+    let impact = graph
+        .analyze_git_changes(repo_path, &base_ref, &current_ref)
+        .unwrap();
+    let versions = graph
+        .analyze_version_bumps(
+            repo_path,
+            &base_ref,
+            &impact.all_affected_crates,
+            &impact.directly_affected_crates,
+            &direct,
+            &transitive,
+        )
+        .unwrap();
+    let analysis = ChangelogChecker::analyze_for_changes(
+        &graph,
+        repo_path,
+        &ChangelogConfig::default(),
+        &direct,
+        &transitive,
+        &versions,
+        &impact,
+    )
+    .unwrap();
+
+    (graph, analysis)
+}
+
+fn issue_types(
+    graph: &CrateDependencyGraph,
+    analysis: &ChangelogAnalysis,
+    name: &str,
+) -> Vec<IssueType> {
+    let crate_info = graph.find_crate_by_name(name).unwrap();
+    analysis.statuses[&crate_info.id]
+        .issues
+        .iter()
+        .map(|issue| issue.issue_type)
+        .collect()
+}
+
+#[test]
+fn test_code_changed_but_changelog_untouched() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core").changelog(ChangelogFixture::Valid))
+                .crate_entry(
+                    TestCrate::new("app")
+                        .dependency("core")
+                        .changelog(ChangelogFixture::Valid),
+                ),
+        )
+        .branch("feature")
+        .touch_code("core")
+        .bump_version("core", "0.2.0")
+        .commit("Change core without a changelog entry")
+        .build()
+        .unwrap();
+
+    let (graph, analysis) = analyze(repo.path());
+
+    let core_issues = issue_types(&graph, &analysis, "core");
+    assert!(core_issues.contains(&IssueType::ChangelogNotUpdated));
+    assert!(core_issues.contains(&IssueType::MissingVersionEntry));
+    assert_eq!(analysis.crates_needing_changelog_update.len(), 1);
+
+    // the dependent is only transitively affected, its changelog is not required to change
+    assert!(!issue_types(&graph, &analysis, "app").contains(&IssueType::ChangelogNotUpdated));
+}
+
+#[test]
+fn test_changelog_updated_with_new_entry() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core").changelog(ChangelogFixture::Valid)),
+        )
+        .branch("feature")
+        .touch_code("core")
+        .bump_version("core", "0.2.0")
+        .add_changelog_entry("core", "0.2.0", "feat(core): new api")
+        .commit("Change core with a changelog entry")
+        .build()
+        .unwrap();
+
+    let (graph, analysis) = analyze(repo.path());
+
+    assert!(issue_types(&graph, &analysis, "core").is_empty());
+    assert_eq!(analysis.total_errors, 0);
+    assert_eq!(analysis.crates_with_valid_changelog.len(), 1);
+}
+
+#[test]
+fn test_malformed_missing_entry_and_missing_changelog() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("malformed").changelog(ChangelogFixture::Malformed))
+                .crate_entry(
+                    TestCrate::new("outdated").changelog(ChangelogFixture::MissingVersionEntry),
+                )
+                .crate_entry(TestCrate::new("undocumented")),
+        )
+        .branch("feature")
+        .touch_code("malformed")
+        .touch_code("outdated")
+        .touch_code("undocumented")
+        .commit("Touch every crate")
+        .build()
+        .unwrap();
+
+    let (graph, analysis) = analyze(repo.path());
+
+    let malformed = issue_types(&graph, &analysis, "malformed");
+    assert!(malformed.contains(&IssueType::BadFormat));
+    let outdated = issue_types(&graph, &analysis, "outdated");
+    assert!(outdated.contains(&IssueType::MissingVersionEntry));
+    assert!(!outdated.contains(&IssueType::BadFormat));
+    let undocumented = issue_types(&graph, &analysis, "undocumented");
+    assert_eq!(undocumented, vec![IssueType::MissingChangelog]);
+
+    assert_eq!(analysis.crates_missing_changelog.len(), 1);
+    assert!(analysis.total_errors > 0);
+}
+
+#[test]
+fn test_breaking_changes_follow_version_policy() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core").changelog(ChangelogFixture::Valid))
+                .crate_entry(
+                    TestCrate::new("api")
+                        .version("1.0.0")
+                        .changelog(ChangelogFixture::Valid),
+                ),
+        )
+        .branch("feature")
+        .touch_code("core")
+        .bump_version("core", "0.1.1")
+        .add_changelog_entry("core", "0.1.1", "feat!: drop the old parser")
+        .touch_code("api")
+        .bump_version("api", "1.1.0")
+        .add_changelog_entry("api", "1.1.0", "fix: BREAKING rename the client")
+        .commit("Break core and api")
+        .build()
+        .unwrap();
+    let path = repo.path();
+    let graph = CrateDependencyGraph::build_from_repository(path).unwrap();
+    let base_ref = GitRef::Branch("master".to_string());
+    let impact = graph
+        .analyze_git_changes(path, &base_ref, &GitRef::Branch("feature".to_string()))
+        .unwrap();
+
+    let analyze = |config: &DeptrackConfig| {
+        graph
+            .analyze_version_bumps_with_config(
+                path,
+                &base_ref,
+                &impact.all_affected_crates,
+                &impact.directly_affected_crates,
+                config,
+            )
+            .unwrap()
+    };
+    let status = |analysis: &VersionBumpAnalysis, name: &str| {
+        analysis.crate_versions[&graph.find_crate_by_name(name).unwrap().id].clone()
+    };
+
+    // pre-1.0 breaking changes need a minor bump, 1.x ones a major bump
+    let analysis = analyze(&DeptrackConfig::default());
+    let core = status(&analysis, "core");
+    assert!(core.breaking);
+    assert_eq!(core.required_bump, RequiredBump::Minor);
+    assert!(!core.is_bumped);
+    assert!(
+        core.issues[0]
+            .message
+            .starts_with("breaking change to a pre_release crate needs a minor bump")
+    );
+    let api = status(&analysis, "api");
+    assert_eq!(api.required_bump, RequiredBump::Major);
+    assert!(!api.is_bumped);
+    assert_eq!(analysis.crates_needing_bump.len(), 2);
+
+    let relaxed: DeptrackConfig = toml::from_str(
+        "[version_policy]\nstable = { change = \"patch\", breaking = \"minor\" }\n\
+         pre_release = { change = \"patch\", breaking = \"patch\" }\n",
+    )
+    .unwrap();
+    let analysis = analyze(&relaxed);
+    assert!(analysis.all_bumped());
+    assert_eq!(analysis.total_errors, 0);
+}
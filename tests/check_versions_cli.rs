@@ -0,0 +1,77 @@
+// check-versions run through the binary, for what only the command decides
+
+use deptrack::utils::testing::TestScenario;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn deptrack(repo_path: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_deptrack"))
+        .arg("--path")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_broken_manifest_without_changes() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let path = repo.path();
+    std::fs::create_dir_all(path.join("broken")).unwrap();
+    std::fs::write(path.join("broken/Cargo.toml"), "[package\n").unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Add a broken manifest").unwrap();
+
+    // nothing changed between the refs, the broken manifest still fails
+    let output = deptrack(
+        path,
+        &[
+            "--strict-parse",
+            "--format",
+            "json",
+            "check-versions",
+            "HEAD",
+            "HEAD",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["changed_files"], 0);
+    assert_eq!(
+        report["membership"]["broken_manifests"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(report["issues"][0]["type"], "broken_manifest");
+    assert_eq!(report["issues"][0]["severity"], "error");
+
+    // without --strict-parse it is only a warning
+    let output = deptrack(path, &["check-versions", "HEAD", "HEAD"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("broken manifests: 1"));
+    let output = deptrack(
+        path,
+        &["check-versions", "HEAD", "HEAD", "--fail-on", "warning"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_notify_webhook_from_environment() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+
+    // the webhook is taken from the environment and kept out of the warning
+    let output = Command::new(env!("CARGO_BIN_EXE_deptrack"))
+        .arg("--path")
+        .arg(repo.path())
+        .args(["check-versions", "HEAD", "HEAD"])
+        .env("DEPTRACK_NOTIFY_WEBHOOK", "file:///nonexistent/T0123/secret")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("failed to post to the webhook"), "{}", stderr);
+    assert!(!stderr.contains("secret"), "{}", stderr);
+}
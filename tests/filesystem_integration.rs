@@ -414,7 +414,7 @@ fn verify_rfc3339_recursive(directory: &FsDirectory) {
                     assert!(
                         DateTime::parse_from_rfc3339(created).is_ok(),
                         "File {} created time not RFC 3339: {}",
-                        file.path,
+                        file.display(),
                         created
                     );
                 }
@@ -422,7 +422,7 @@ fn verify_rfc3339_recursive(directory: &FsDirectory) {
                     assert!(
                         DateTime::parse_from_rfc3339(modified).is_ok(),
                         "File {} modified time not RFC 3339: {}",
-                        file.path,
+                        file.display(),
                         modified
                     );
                 }
@@ -430,7 +430,7 @@ fn verify_rfc3339_recursive(directory: &FsDirectory) {
                     assert!(
                         DateTime::parse_from_rfc3339(accessed).is_ok(),
                         "File {} accessed time not RFC 3339: {}",
-                        file.path,
+                        file.display(),
                         accessed
                     );
                 }
@@ -438,6 +438,7 @@ fn verify_rfc3339_recursive(directory: &FsDirectory) {
             FsElement::Directory(dir) => {
                 verify_rfc3339_recursive(dir);
             }
+            FsElement::Symlink(_) => {}
         }
     }
 }
@@ -492,6 +493,7 @@ fn test_integration_extended_attributes() {
                 let attrs = dir.ext_attributes_scan();
                 assert!(attrs.is_ok());
             }
+            FsElement::Symlink(_) => {}
         }
     }
 }
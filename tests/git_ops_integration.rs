@@ -1,6 +1,7 @@
 use deptrack::GitOps;
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 use tempfile::TempDir;
 
 fn create_git_repo_with_commits(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
@@ -228,3 +229,65 @@ fn test_integration_complex_directory_structure() {
     let file_root = GitOps::detect_repository_root(&file_path).unwrap();
     assert_eq!(file_root, expected_root);
 }
+
+// runs git in `dir` with commit dates `tick` seconds after a fixed start, so
+// commits made in the same second still have an order
+fn git_at(dir: &Path, tick: u32, args: &[&str]) -> String {
+    let date = format!("{} +0000", 1_700_000_000 + tick);
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_DATE", &date)
+        .env("GIT_COMMITTER_DATE", &date)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_integration_merge_base_criss_cross() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    git_at(dir, 0, &["init", "-q", "-b", "main"]);
+    git_at(dir, 0, &["config", "user.name", "test"]);
+    git_at(dir, 0, &["config", "user.email", "test@example.com"]);
+    let commit = |tick: u32, message: &str| {
+        git_at(dir, tick, &["commit", "-q", "--allow-empty", "-m", message]);
+        git_at(dir, tick, &["rev-parse", "HEAD"])
+    };
+
+    // a long shared history the walk should not need to read
+    let mut root = String::new();
+    for tick in 1..=50 {
+        root = commit(tick, "shared");
+    }
+    git_at(dir, 51, &["branch", "other"]);
+    let x1 = commit(52, "x1");
+    git_at(dir, 53, &["checkout", "-q", "other"]);
+    let y1 = commit(54, "y1");
+
+    // each side merges the other's first commit
+    git_at(dir, 55, &["merge", "-q", "--no-ff", "-m", "y2", &x1]);
+    git_at(dir, 56, &["checkout", "-q", "main"]);
+    git_at(dir, 57, &["merge", "-q", "--no-ff", "-m", "x2", &y1]);
+
+    // x1 and y1 are both best common ancestors, git picks either
+    let base = GitOps::merge_base(dir, "main", "other").unwrap();
+    assert!(base == x1 || base == y1, "unexpected merge base {}", base);
+    assert_ne!(base, root);
+
+    // an ancestor is its own merge base with a descendant
+    assert_eq!(GitOps::merge_base(dir, &x1, "main").unwrap(), x1);
+    assert_eq!(GitOps::merge_base(dir, &root, "other").unwrap(), root);
+
+    // a base below another common ancestor is not the best one
+    git_at(dir, 58, &["checkout", "-q", "-b", "late", &y1]);
+    let late = commit(59, "late");
+    assert_eq!(GitOps::merge_base(dir, &late, "main").unwrap(), y1);
+
+    // unrelated histories have none
+    git_at(dir, 60, &["checkout", "-q", "--orphan", "unrelated"]);
+    commit(61, "unrelated");
+    assert!(GitOps::merge_base(dir, "unrelated", "main").is_err());
+}
@@ -0,0 +1,342 @@
+// release planning and execution against generated repositories
+
+use deptrack::utils::cargo_ops::CrateDependencyGraph;
+use deptrack::utils::git_ops::GitRef;
+use deptrack::utils::testing::{
+    ChangelogFixture, TestCrate, TestRepoBuilder, TestScenario, TestWorkspace,
+};
+use deptrack::utils::toml_ops::TomlReader;
+use deptrack::{
+    BumpLevel, DeptrackConfig, ReleaseAction, ReleaseExecutor, ReleaseOptions, ReleasePlan,
+    ReleasePlanner,
+};
+use std::path::Path;
+use std::process::Command;
+
+const CORE_CHANGELOG: &str = "# CHANGELOG\n\n## [Unreleased]\n\n* fix(core): faster parsing\n\n## [0.1.0]\n\n* feat(core): initial release\n";
+
+const APP_MANIFEST: &str = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ncore = { path = \"../core\", version = \"0.1\" }\n";
+
+fn plan(repo_path: &Path, options: &ReleaseOptions) -> ReleasePlan {
+    let graph = CrateDependencyGraph::build_from_repository(repo_path).unwrap();
+    ReleasePlanner::plan(
+        &graph,
+        repo_path,
+        &GitRef::Branch("master".to_string()),
+        &DeptrackConfig::default(),
+        options,
+    )
+    .unwrap()
+}
+
+fn git(repo_path: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn version_of(repo_path: &Path, crate_name: &str) -> Option<String> {
+    TomlReader::read_cargo_toml(repo_path.join("workspace").join(crate_name))
+        .unwrap()
+        .get_version()
+}
+
+#[test]
+fn test_release_plan_and_execute() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(
+                    TestCrate::new("core")
+                        .changelog(ChangelogFixture::Custom(CORE_CHANGELOG.to_string())),
+                )
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .modify_crate("app", "Cargo.toml", APP_MANIFEST)
+        .commit("Require core 0.1 from app")
+        .branch("feature")
+        .touch_code("core")
+        .commit("Speed up core")
+        .build()
+        .unwrap();
+    let path = repo.path();
+
+    let plan = plan(path, &ReleaseOptions::new().level(BumpLevel::Minor));
+
+    let versions: Vec<(&str, String, Option<BumpLevel>)> = plan
+        .bumps
+        .iter()
+        .map(|b| {
+            (
+                b.crate_id.name.as_str(),
+                b.next_version.to_string(),
+                b.level,
+            )
+        })
+        .collect();
+    assert_eq!(
+        versions,
+        vec![
+            ("app", "0.1.1".to_string(), Some(BumpLevel::Patch)),
+            ("core", "0.2.0".to_string(), Some(BumpLevel::Minor)),
+        ]
+    );
+
+    assert!(plan.actions.iter().any(|a| matches!(
+        a,
+        ReleaseAction::UpdateRequirement { dependency, from, to, .. }
+            if dependency == "core" && from == "0.1" && to == "0.2.0"
+    )));
+    assert!(
+        plan.actions
+            .iter()
+            .any(|a| matches!(a, ReleaseAction::PromoteChangelog { .. }))
+    );
+    let tags: Vec<&str> = plan
+        .actions
+        .iter()
+        .filter_map(|a| match a {
+            ReleaseAction::CreateTag { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tags, vec!["app-v0.1.1", "core-v0.2.0"]);
+
+    let order: Vec<&str> = plan
+        .publish_order
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(order, vec!["core", "app"]);
+
+    // planning does not touch the tree
+    assert_eq!(version_of(path, "core"), Some("0.1.0".to_string()));
+    assert!(git(path, &["status", "--porcelain"]).is_empty());
+
+    ReleaseExecutor::execute(&plan, path).unwrap();
+
+    assert_eq!(version_of(path, "core"), Some("0.2.0".to_string()));
+    assert_eq!(version_of(path, "app"), Some("0.1.1".to_string()));
+    let app_manifest = std::fs::read_to_string(path.join("workspace/app/Cargo.toml")).unwrap();
+    assert!(app_manifest.contains("core = { path = \"../core\", version = \"0.2.0\" }"));
+    let changelog = std::fs::read_to_string(path.join("workspace/core/CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("## [0.2.0]\n\n* fix(core): faster parsing"));
+    assert!(!changelog.contains("Unreleased"));
+
+    assert!(git(path, &["status", "--porcelain"]).is_empty());
+    assert_eq!(
+        git(path, &["log", "-1", "--format=%s"]),
+        "release app 0.1.1, core 0.2.0"
+    );
+    assert_eq!(git(path, &["tag", "-l"]), "app-v0.1.1\ncore-v0.2.0");
+}
+
+#[test]
+fn test_release_keeps_existing_bumps_without_commit() {
+    let repo = TestScenario::SimpleChain
+        .builder()
+        .branch("feature")
+        .touch_code("crate_A")
+        .bump_version("crate_A", "0.3.0")
+        .commit("Change crate_A")
+        .build()
+        .unwrap();
+    let path = repo.path();
+
+    let plan = plan(path, &ReleaseOptions::new().commit(false));
+
+    let crate_a = plan
+        .bumps
+        .iter()
+        .find(|b| b.crate_id.name == "crate_A")
+        .unwrap();
+    assert_eq!(crate_a.level, None);
+    assert_eq!(crate_a.next_version.to_string(), "0.3.0");
+
+    // crate_A keeps its version, its dependents get patch bumps and nothing is committed or tagged
+    let set_versions: Vec<String> = plan
+        .actions
+        .iter()
+        .filter_map(|a| match a {
+            ReleaseAction::SetVersion { crate_id, to, .. } => {
+                Some(format!("{} {}", crate_id.name, to))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(set_versions, vec!["crate_B 0.1.1", "crate_C 0.1.1"]);
+    assert!(plan.actions.iter().all(|a| !matches!(
+        a,
+        ReleaseAction::Commit { .. } | ReleaseAction::CreateTag { .. }
+    )));
+
+    let order: Vec<&str> = plan
+        .publish_order
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(order, vec!["crate_A", "crate_B", "crate_C"]);
+
+    ReleaseExecutor::execute(&plan, path).unwrap();
+    assert_eq!(version_of(path, "crate_B"), Some("0.1.1".to_string()));
+    assert!(!git(path, &["status", "--porcelain"]).is_empty());
+    assert!(git(path, &["tag", "-l"]).is_empty());
+}
+
+#[test]
+fn test_release_tag_patterns() {
+    let repo = TestRepoBuilder::new()
+        .workspace(TestWorkspace::new("workspace").crate_entry(TestCrate::new("core")))
+        .tag("core_0.0.9")
+        .branch("feature")
+        .touch_code("core")
+        .commit("Speed up core")
+        .build()
+        .unwrap();
+    let path = repo.path();
+    let graph = CrateDependencyGraph::build_from_repository(path).unwrap();
+
+    // new tags use the first pattern, the older scheme is still recognized
+    let config: DeptrackConfig = toml::from_str(
+        "[releases]\ntag_pattern = [\"{crate}@{version}\", \"{crate}_{version}\"]\n",
+    )
+    .unwrap();
+    let plan = ReleasePlanner::plan(
+        &graph,
+        path,
+        &GitRef::Branch("master".to_string()),
+        &config,
+        &ReleaseOptions::new().commit(false),
+    )
+    .unwrap();
+    assert_eq!(plan.bumps[0].previous_tag.as_deref(), Some("core_0.0.9"));
+
+    let plan = ReleasePlanner::plan(
+        &graph,
+        path,
+        &GitRef::Branch("master".to_string()),
+        &config,
+        &ReleaseOptions::new(),
+    )
+    .unwrap();
+    assert!(plan.actions.iter().any(|a| matches!(
+        a,
+        ReleaseAction::CreateTag { name, .. } if name == "core@0.1.1"
+    )));
+
+    // the next version is already tagged under the older scheme
+    git(path, &["tag", "core_0.1.1"]);
+    let error = ReleasePlanner::plan(
+        &graph,
+        path,
+        &GitRef::Branch("master".to_string()),
+        &config,
+        &ReleaseOptions::new(),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("tag core_0.1.1 already exists"));
+}
+
+#[test]
+fn test_release_requirement_update_modes() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core").version("1.0.0"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .modify_crate(
+            "app",
+            "Cargo.toml",
+            APP_MANIFEST.replace("version = \"0.1\"", "version = \"1\""),
+        )
+        .commit("Require core 1 from app")
+        .branch("feature")
+        .touch_code("core")
+        .commit("Extend core")
+        .build()
+        .unwrap();
+    let path = repo.path();
+    let graph = CrateDependencyGraph::build_from_repository(path).unwrap();
+    let requirement_updates = |config: &str, level: BumpLevel| {
+        let config: DeptrackConfig = toml::from_str(config).unwrap();
+        ReleasePlanner::plan(
+            &graph,
+            path,
+            &GitRef::Branch("master".to_string()),
+            &config,
+            &ReleaseOptions::new().level(level).commit(false),
+        )
+        .unwrap()
+        .actions
+        .into_iter()
+        .filter_map(|a| match a {
+            ReleaseAction::UpdateRequirement { from, to, .. } => Some((from, to)),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+    };
+
+    // "1" still accepts 1.1.0, only `minor` raises it
+    assert!(requirement_updates("", BumpLevel::Minor).is_empty());
+    assert_eq!(
+        requirement_updates(
+            "[releases]\nupdate_requirements = \"minor\"\n",
+            BumpLevel::Minor
+        ),
+        vec![("1".to_string(), "1.1.0".to_string())]
+    );
+    assert!(
+        requirement_updates(
+            "[releases]\nupdate_requirements = \"minor\"\n",
+            BumpLevel::Patch
+        )
+        .is_empty()
+    );
+    assert_eq!(
+        requirement_updates("", BumpLevel::Major),
+        vec![("1".to_string(), "2.0.0".to_string())]
+    );
+    assert!(
+        requirement_updates(
+            "[releases]\nupdate_requirements = \"never\"\n",
+            BumpLevel::Major
+        )
+        .is_empty()
+    );
+}
+
+#[test]
+fn test_release_skips_dev_only_dependents() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                .crate_entry(TestCrate::new("bench").dev_dependency("core")),
+        )
+        .branch("feature")
+        .touch_code("core")
+        .commit("Speed up core")
+        .build()
+        .unwrap();
+    let path = repo.path();
+
+    // bench only tests against core, its published code does not change
+    let plan = plan(path, &ReleaseOptions::new());
+    let released: Vec<&str> = plan
+        .bumps
+        .iter()
+        .map(|b| b.crate_id.name.as_str())
+        .collect();
+    assert_eq!(released, vec!["app", "core"]);
+    assert!(plan.actions.iter().all(|a| match a {
+        ReleaseAction::SetVersion { crate_id, .. } | ReleaseAction::CreateTag { crate_id, .. } => {
+            crate_id.name != "bench"
+        }
+        _ => true,
+    }));
+}
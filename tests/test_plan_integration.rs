@@ -0,0 +1,58 @@
+// test plans for changes in generated repositories
+
+use deptrack::utils::cargo_ops::CrateDependencyGraph;
+use deptrack::utils::git_ops::GitRef;
+use deptrack::utils::testing::{TestCrate, TestRepoBuilder, TestWorkspace};
+use deptrack::{TestPlanner, TestTargetKind};
+
+#[test]
+fn test_plan_covers_changed_crates_and_dependents() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                .crate_entry(TestCrate::new("cli")),
+        )
+        .modify_crate("app", "tests/smoke.rs", "#[test]\nfn smoke() {}\n")
+        .commit("Add app smoke test")
+        .branch("feature")
+        .touch_code("core")
+        .commit("Change core")
+        .build()
+        .unwrap();
+    let path = repo.path();
+
+    let graph = CrateDependencyGraph::build_from_repository(path).unwrap();
+    let impact = graph
+        .analyze_git_changes(
+            path,
+            &GitRef::Branch("master".to_string()),
+            &GitRef::Branch("feature".to_string()),
+        )
+        .unwrap();
+    let plan = TestPlanner::plan(&graph, &impact, path).unwrap();
+
+    let crates: Vec<(&str, bool)> = plan
+        .crates
+        .iter()
+        .map(|c| (c.crate_id.name.as_str(), c.directly_affected))
+        .collect();
+    assert_eq!(crates, vec![("app", false), ("core", true)]);
+
+    let app_kinds: Vec<TestTargetKind> = plan.crates[0].targets.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        app_kinds,
+        vec![
+            TestTargetKind::Lib,
+            TestTargetKind::Doc,
+            TestTargetKind::Integration
+        ]
+    );
+
+    assert_eq!(plan.commands.len(), 1);
+    assert_eq!(
+        plan.commands[0].to_string(),
+        "cargo test --manifest-path workspace/Cargo.toml -p app -p core"
+    );
+}
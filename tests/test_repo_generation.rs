@@ -1,9 +1,16 @@
 // integration tests for test repository generation
 
-use deptrack::SeverityConfig;
-use deptrack::utils::cargo_ops::CrateDependencyGraph;
-use deptrack::utils::git_ops::{GitOps, GitRef};
+use deptrack::utils::cargo_ops::{CrateDependencyGraph, DependencyType};
+use deptrack::utils::git_ops::{ChangeType, DiffMode, GitOps, GitRef, Pathspec};
 use deptrack::utils::testing::{TestCrate, TestRepoBuilder, TestScenario, TestWorkspace};
+use deptrack::utils::toml_ops::TomlSection;
+use deptrack::{
+    AnalysisCache, BaseRef, BaseRefSource, ChangelogChecker, ChangelogCoverage, CrateDetail,
+    CurrentRef, DeptrackConfig, FIXES_DIR, FingerprintSnapshot, FixPlanner, FsDirectory,
+    GeneratedFiles, HashType, ImpactConfig, ImpactPropagation, IssueSeverity, IssueType,
+    ManifestChecker, MembershipChecker, RequirementSync, RequirementSyncReport, Scope,
+    SeverityConfig, SeverityPreset, SeverityScope, UnifiedReport,
+};
 
 #[test]
 fn test_simple_chain_scenario() {
@@ -140,6 +147,17 @@ fn test_change_impact_analysis_with_generated_repo() {
     // crate_D was changed, so it should be directly affected
     let crate_d = graph.find_crate_by_name("crate_D").unwrap();
     assert!(impact.directly_affected_crates.contains(&crate_d.id));
+
+    // with the file that triggered it, relative to the repository
+    let files = impact.get_changed_files_for_crate(&crate_d.id);
+    assert_eq!(files.len(), 1);
+    assert!(files[0].path.ends_with("crate_D/src/lib.rs"));
+    assert!(files[0].path.is_relative());
+    assert_eq!(files[0].change_type, ChangeType::Modified);
+    assert_eq!(
+        impact.changed_files_by_crate()[&crate_d.id.display_name()],
+        files
+    );
 }
 
 #[test]
@@ -196,6 +214,79 @@ fn test_version_bump_analysis_with_generated_repo() {
     }
 }
 
+#[test]
+fn test_new_crate_rules() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let base_ref = GitRef::Hash(repo.head_commit().unwrap());
+    let workspace_dir = repo.path().join("workspace");
+    let workspace_toml = std::fs::read_to_string(workspace_dir.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        workspace_dir.join("Cargo.toml"),
+        workspace_toml.replacen("members = [\n", "members = [\n    \"crate_new\",\n", 1),
+    )
+    .unwrap();
+    std::fs::create_dir_all(workspace_dir.join("crate_new/src")).unwrap();
+    std::fs::write(
+        workspace_dir.join("crate_new/Cargo.toml"),
+        "[package]\nname = \"crate_new\"\nversion = \"0.3.0\"\nedition = \"2021\"\npublish = false\n",
+    )
+    .unwrap();
+    std::fs::write(workspace_dir.join("crate_new/src/lib.rs"), "").unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Add crate_new").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let crate_new = graph.find_crate_by_name("crate_new").unwrap().id.clone();
+    let impact = graph
+        .analyze_git_changes(repo.path(), &base_ref, &GitRef::Head)
+        .unwrap();
+    let analyze = |config: &DeptrackConfig| {
+        let versions = graph
+            .analyze_version_bumps_with_config(
+                repo.path(),
+                &base_ref,
+                &impact.all_affected_crates,
+                &impact.directly_affected_crates,
+                config,
+            )
+            .unwrap();
+        let changelogs = ChangelogChecker::analyze_for_changes(
+            &graph,
+            repo.path(),
+            &config.changelog,
+            &config.direct_severity,
+            &config.transitive_severity,
+            &versions,
+            &impact,
+        )
+        .unwrap();
+        (versions, changelogs)
+    };
+
+    // no bump is asked of a new crate, but a changelog is, even where
+    // changelogs are optional
+    let mut config = DeptrackConfig::default();
+    config.changelog.require_changelog = false;
+    let (versions, changelogs) = analyze(&config);
+    let status = &versions.crate_versions[&crate_new];
+    assert!(status.is_new);
+    assert!(status.is_bumped);
+    assert!(status.issues.is_empty());
+    assert_eq!(versions.new_crates(), std::slice::from_ref(&crate_new));
+    assert_eq!(
+        changelogs.statuses[&crate_new].issues[0].issue_type,
+        IssueType::MissingChangelog
+    );
+
+    config.new_crates.skip_version_bump = false;
+    config.changelog.require_for_new_crates = false;
+    let (versions, changelogs) = analyze(&config);
+    let status = &versions.crate_versions[&crate_new];
+    assert_eq!(status.issues[0].issue_type, IssueType::NoVersionBump);
+    assert!(status.issues[0].message.starts_with("new crate 0.3.0"));
+    assert!(changelogs.statuses[&crate_new].issues.is_empty());
+}
+
 #[test]
 fn test_git_integration_with_generated_repo() {
     let repo = TestScenario::SimpleChain.build().unwrap();
@@ -206,3 +297,1600 @@ fn test_git_integration_with_generated_repo() {
     let repo_info = GitOps::get_repository_info(repo.path()).unwrap();
     assert_eq!(repo_info.root_path, repo.path());
 }
+
+#[test]
+fn test_manifest_changes_with_generated_repo() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let workspace_dir = repo.path().join("workspace");
+
+    // crate_A inherits serde from the workspace
+    let workspace_toml = std::fs::read_to_string(workspace_dir.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        workspace_dir.join("Cargo.toml"),
+        format!(
+            "{}\n[workspace.dependencies]\nserde = \"1.0\"\n",
+            workspace_toml
+        ),
+    )
+    .unwrap();
+    let crate_a_toml = std::fs::read_to_string(workspace_dir.join("crate_A/Cargo.toml")).unwrap();
+    std::fs::write(
+        workspace_dir.join("crate_A/Cargo.toml"),
+        format!(
+            "{}[dependencies]\nserde = {{ workspace = true }}\n",
+            crate_a_toml
+        ),
+    )
+    .unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Inherit serde").unwrap();
+
+    // bump the workspace requirement and crate_C's version on a branch
+    repo.create_branch("feature").unwrap();
+    let workspace_toml = std::fs::read_to_string(workspace_dir.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        workspace_dir.join("Cargo.toml"),
+        workspace_toml.replace("serde = \"1.0\"", "serde = \"1.1\""),
+    )
+    .unwrap();
+    repo.update_version("workspace", "crate_C", "0.2.0")
+        .unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Update serde").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = graph
+        .analyze_git_changes(
+            repo.path(),
+            &GitRef::Branch("master".to_string()),
+            &GitRef::Branch("feature".to_string()),
+        )
+        .unwrap();
+
+    let crate_a = graph.find_crate_by_name("crate_A").unwrap();
+    let crate_b = graph.find_crate_by_name("crate_B").unwrap();
+    let crate_c = graph.find_crate_by_name("crate_C").unwrap();
+
+    // the workspace manifest belongs to no crate, the inheritance rule picks up crate_A
+    assert!(impact.directly_affected_crates.contains(&crate_a.id));
+    assert!(!impact.directly_affected_crates.contains(&crate_b.id));
+    assert!(impact.all_affected_crates.contains(&crate_b.id));
+
+    let diff = &impact.manifest_changes[&crate_c.id];
+    assert_eq!(
+        diff.version_change(),
+        Some(("0.1.0".to_string(), "0.2.0".to_string()))
+    );
+    assert!(!impact.manifest_changes.contains_key(&crate_a.id));
+}
+
+#[test]
+fn test_manifest_sections_policy() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let workspace_dir = repo.path().join("workspace");
+    let append = |path: &str, content: &str| {
+        let path = workspace_dir.join(path);
+        let existing = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{}\n{}", existing, content)).unwrap();
+    };
+    append(
+        "Cargo.toml",
+        "[workspace.lints.rust]\nunsafe_code = \"forbid\"\n",
+    );
+    append("crate_A/Cargo.toml", "[lints]\nworkspace = true\n");
+    repo.stage_all().unwrap();
+    repo.commit("Add lints").unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+
+    append(
+        "crate_B/Cargo.toml",
+        "[package.metadata.docs.rs]\nall-features = true\n\n[lints.clippy]\npedantic = \"warn\"\n",
+    );
+    let workspace_toml = std::fs::read_to_string(workspace_dir.join("Cargo.toml")).unwrap();
+    std::fs::write(
+        workspace_dir.join("Cargo.toml"),
+        workspace_toml.replace("\"forbid\"", "\"deny\""),
+    )
+    .unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Tighten lints").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let crate_a = graph.find_crate_by_name("crate_A").unwrap().id.clone();
+    let crate_b = graph.find_crate_by_name("crate_B").unwrap().id.clone();
+
+    // lints and metadata do not count by default
+    let impact = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    assert!(impact.directly_affected_crates.is_empty());
+    assert_eq!(impact.ignored_manifest_crates, vec![crate_b.clone()]);
+    assert!(impact.manifest_changes.is_empty());
+
+    // counting lints affects crate_B and crate_A, which inherits the workspace lints
+    let config = ImpactConfig {
+        ignore_manifest_sections: vec![TomlSection::Metadata],
+        ..Default::default()
+    };
+    let impact = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &config)
+        .unwrap();
+    assert!(impact.directly_affected_crates.contains(&crate_a));
+    assert!(impact.directly_affected_crates.contains(&crate_b));
+    assert!(impact.ignored_manifest_crates.is_empty());
+    assert_eq!(
+        impact.manifest_changes[&crate_b].sections(),
+        vec![TomlSection::Metadata, TomlSection::Lints]
+    );
+}
+
+#[test]
+fn test_generated_files_ignored() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let workspace_dir = repo.path().join("workspace");
+    let write = |path: &str, content: &str| {
+        let path = workspace_dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write("crate_A/src/generated/schema.rs", "pub struct Schema;\n");
+    write(
+        "crate_B/src/api.rs",
+        "// This file is @generated by prost-build.\npub struct Api;\n",
+    );
+    repo.stage_all().unwrap();
+    repo.commit("Add generated code").unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+
+    write(
+        "crate_A/src/generated/schema.rs",
+        "pub struct Schema(u8);\n",
+    );
+    write(
+        "crate_B/src/api.rs",
+        "// This file is @generated by prost-build.\npub struct Api(u8);\n",
+    );
+    repo.stage_all().unwrap();
+    repo.commit("Regenerate").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let crate_b = graph.find_crate_by_name("crate_B").unwrap().id.clone();
+    let config = ImpactConfig {
+        generated: GeneratedFiles::new(["**/generated/**"], true).unwrap(),
+        ..Default::default()
+    };
+    let impact = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &config)
+        .unwrap();
+    assert!(impact.directly_affected_crates.is_empty());
+    assert!(impact.changed_files.is_empty());
+    assert_eq!(impact.generated_files.len(), 2);
+
+    // without markers only the pattern applies
+    let config = ImpactConfig {
+        generated: GeneratedFiles::new(["**/generated/**"], false).unwrap(),
+        ..Default::default()
+    };
+    let impact = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &config)
+        .unwrap();
+    assert_eq!(impact.directly_affected_crates, vec![crate_b]);
+    assert_eq!(
+        impact.generated_files,
+        vec![std::path::PathBuf::from(
+            "workspace/crate_A/src/generated/schema.rs"
+        )]
+    );
+
+    // uncommitted regenerated files are read from the working directory
+    write(
+        "crate_B/src/api.rs",
+        "// This file is @generated by prost-build.\npub struct Api(u16);\n",
+    );
+    let config = ImpactConfig {
+        generated: GeneratedFiles::new(Vec::<String>::new(), true).unwrap(),
+        ..Default::default()
+    };
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    let impact = graph
+        .analyze_changes_since(repo.path(), &base, &config)
+        .unwrap();
+    assert!(impact.directly_affected_crates.is_empty());
+    assert_eq!(impact.generated_files.len(), 1);
+}
+
+#[test]
+fn test_fix_patches_apply() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.modify_file("workspace", "crate_A", "src/lib.rs", "// modified\n")
+        .unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Modify crate_A").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let config = DeptrackConfig::default();
+    let impact = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    let versions = graph
+        .analyze_version_bumps_with_config(
+            repo.path(),
+            &base,
+            &impact.all_affected_crates,
+            &impact.directly_affected_crates,
+            &config,
+        )
+        .unwrap();
+    let changelogs = ChangelogChecker::analyze_for_changes(
+        &graph,
+        repo.path(),
+        &config.changelog,
+        &config.direct_severity,
+        &config.transitive_severity,
+        &versions,
+        &impact,
+    )
+    .unwrap();
+    let patches = FixPlanner::plan(
+        &graph,
+        repo.path(),
+        &versions,
+        Some(&changelogs),
+        &config.changelog,
+        &RequirementSyncReport::default(),
+    )
+    .unwrap();
+
+    let patch = patches
+        .iter()
+        .find(|patch| patch.file_name == "crate_A.patch")
+        .unwrap();
+    assert_eq!(
+        patch.fixes,
+        vec![
+            "bump version 0.1.0 -> 0.1.1 (patch change since 0.1.0)",
+            "add a [0.1.1] section to CHANGELOG.md, its entries are up to you",
+        ]
+    );
+    assert_eq!(
+        patch.files,
+        vec![
+            std::path::PathBuf::from("workspace/crate_A/CHANGELOG.md"),
+            std::path::PathBuf::from("workspace/crate_A/Cargo.toml"),
+        ]
+    );
+
+    // nothing was written until the patches are applied
+    let dir = repo.path().join(FIXES_DIR);
+    let written = FixPlanner::write_patches(&patches, &dir).unwrap();
+    assert_eq!(written.len(), patches.len());
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    assert_eq!(
+        graph.find_crate_by_name("crate_A").unwrap().version,
+        "0.1.0"
+    );
+
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo.path())
+        .arg("apply")
+        .args(&written)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    assert_eq!(
+        graph.find_crate_by_name("crate_A").unwrap().version,
+        "0.1.1"
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.path().join("workspace/crate_A/CHANGELOG.md")).unwrap(),
+        "# CHANGELOG\n\n## [0.1.1]\n"
+    );
+}
+
+#[test]
+fn test_removed_crate_detection() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    let crate_a_dir = repo.crate_path("crate_A").unwrap();
+    std::fs::remove_dir_all(&crate_a_dir).unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Remove crate_A").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    let crate_b = graph.find_crate_by_name("crate_B").unwrap();
+
+    assert_eq!(impact.removed_crates.len(), 1);
+    let removed = &impact.removed_crates[0];
+    assert_eq!(removed.name, "crate_A");
+    assert_eq!(removed.version.as_deref(), Some("0.1.0"));
+    assert_eq!(
+        repo.path().join(&removed.path),
+        crate_a_dir,
+        "path is relative to the repository"
+    );
+    // crate_B still points at it
+    assert_eq!(removed.referenced_by, std::slice::from_ref(&crate_b.id));
+
+    let severity = SeverityConfig::default_direct();
+    let mut membership = MembershipChecker::analyze(&graph, repo.path(), &severity).unwrap();
+    MembershipChecker::check_removed_crates(
+        &mut membership,
+        &graph,
+        repo.path(),
+        &impact.removed_crates,
+        &severity,
+    );
+    assert_eq!(
+        membership.stale_references,
+        std::slice::from_ref(&crate_b.id)
+    );
+    let issue = &membership.statuses[&crate_b.id].issues[0];
+    assert_eq!(issue.issue_type, IssueType::RemovedCrateReferenced);
+    assert!(
+        issue
+            .message
+            .starts_with("depends on crate_A, which was removed")
+    );
+
+    // dropping the dependency clears the reference, the removal stays
+    let manifest = crate_b.cargo_toml_path.clone();
+    let content = std::fs::read_to_string(&manifest).unwrap();
+    let content: String = content
+        .lines()
+        .filter(|line| !line.starts_with("crate_A"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    std::fs::write(&manifest, content).unwrap();
+    repo.stage_all().unwrap();
+    repo.commit("Drop crate_A").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    assert_eq!(impact.removed_crates[0].name, "crate_A");
+    assert!(impact.removed_crates[0].referenced_by.is_empty());
+}
+
+#[test]
+fn test_scripted_history_with_builder() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .tag("v0.1.0")
+        .branch("feature")
+        .modify_crate("core", "src/extra.rs", "pub fn extra() {}\n")
+        .bump_version("core", "0.2.0")
+        .commit("Extend core")
+        .build()
+        .unwrap();
+
+    assert_eq!(repo.current_branch().unwrap(), "feature");
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    assert_eq!(graph.find_crate_by_name("core").unwrap().version, "0.2.0");
+
+    let impact = graph
+        .analyze_git_changes(
+            repo.path(),
+            &GitRef::Tag("v0.1.0".to_string()),
+            &GitRef::Branch("feature".to_string()),
+        )
+        .unwrap();
+    let core = graph.find_crate_by_name("core").unwrap();
+    let app = graph.find_crate_by_name("app").unwrap();
+    assert_eq!(impact.directly_affected_crates, vec![core.id.clone()]);
+    assert!(impact.all_affected_crates.contains(&app.id));
+
+    // helpers are also usable after the build
+    repo.checkout("master").unwrap();
+    repo.bump_version("app", "0.1.1").unwrap();
+    repo.commit("Bump app").unwrap();
+    repo.tag("v0.1.1").unwrap();
+    assert!(repo.modify_crate("missing", "src/lib.rs", "").is_err());
+    assert!(repo.tag("v0.1.1").is_err());
+}
+
+#[test]
+fn test_changes_since_include_working_directory() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                .crate_entry(TestCrate::new("util")),
+        )
+        .tag("v0.1.0")
+        .modify_crate("core", "src/extra.rs", "pub fn extra() {}\n")
+        .commit("Extend core")
+        .build()
+        .unwrap();
+
+    // uncommitted: an untracked file in a new directory and a manifest edit
+    repo.modify_crate("util", "src/nested/mod.rs", "pub fn nested() {}\n")
+        .unwrap();
+    repo.bump_version("app", "0.1.1").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let core = graph.find_crate_by_name("core").unwrap();
+    let app = graph.find_crate_by_name("app").unwrap();
+    let util = graph.find_crate_by_name("util").unwrap();
+
+    let impact = graph
+        .analyze_changes_since(
+            repo.path(),
+            &GitRef::Tag("v0.1.0".to_string()),
+            &ImpactConfig::default(),
+        )
+        .unwrap();
+    for crate_id in [&core.id, &app.id, &util.id] {
+        assert!(impact.directly_affected_crates.contains(crate_id));
+    }
+    assert_eq!(
+        impact.manifest_changes[&app.id].version_change(),
+        Some(("0.1.0".to_string(), "0.1.1".to_string()))
+    );
+
+    // the committed range alone misses the working directory
+    let committed = graph
+        .analyze_git_changes(
+            repo.path(),
+            &GitRef::Tag("v0.1.0".to_string()),
+            &GitRef::Head,
+        )
+        .unwrap();
+    assert_eq!(committed.directly_affected_crates, vec![core.id.clone()]);
+}
+
+#[test]
+fn test_analysis_cache() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .tag("v0.1.0")
+        .modify_crate("core", "src/extra.rs", "pub fn extra() {}\n")
+        .commit("Extend core")
+        .build()
+        .unwrap();
+
+    let config = DeptrackConfig::default();
+    let from = GitRef::Tag("v0.1.0".to_string());
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = graph
+        .analyze_git_changes(repo.path(), &from, &GitRef::Head)
+        .unwrap();
+    let versions = graph
+        .analyze_version_bumps_with_config(
+            repo.path(),
+            &from,
+            &impact.all_affected_crates,
+            &impact.directly_affected_crates,
+            &config,
+        )
+        .unwrap();
+
+    let cache = AnalysisCache::for_refs(repo.path(), &from, &GitRef::Head, &config)
+        .unwrap()
+        .unwrap();
+    assert!(cache.load().is_none());
+    cache.store(&impact, &versions).unwrap();
+
+    // the cache directory ignores itself, the tree stays clean
+    let cache = AnalysisCache::for_refs(repo.path(), &from, &GitRef::Head, &config)
+        .unwrap()
+        .unwrap();
+    let (cached_impact, cached_versions) = cache.load().unwrap();
+    assert_eq!(
+        cached_impact.directly_affected_crates,
+        impact.directly_affected_crates
+    );
+    assert_eq!(cached_impact.changed_files, impact.changed_files);
+    assert_eq!(cached_versions.all_bumped(), versions.all_bumped());
+    assert_eq!(
+        cached_versions.crate_versions.len(),
+        versions.crate_versions.len()
+    );
+
+    // another config misses
+    let other = DeptrackConfig {
+        direct_severity: SeverityConfig::default_transitive(),
+        ..Default::default()
+    };
+    let cache = AnalysisCache::for_refs(repo.path(), &from, &GitRef::Head, &other)
+        .unwrap()
+        .unwrap();
+    assert!(cache.load().is_none());
+
+    // neither do the issue history and patches next to it
+    std::fs::create_dir_all(repo.path().join(FIXES_DIR)).unwrap();
+    std::fs::write(repo.path().join(FIXES_DIR).join("core.patch"), "").unwrap();
+    std::fs::write(repo.path().join(deptrack::HISTORY_FILE), "{\"runs\": []}").unwrap();
+    let cache = AnalysisCache::for_refs(repo.path(), &from, &GitRef::Head, &config)
+        .unwrap()
+        .unwrap();
+    assert!(cache.load().is_some());
+
+    // uncommitted changes are not cached
+    repo.bump_version("core", "0.1.1").unwrap();
+    assert!(
+        AnalysisCache::for_refs(repo.path(), &from, &GitRef::Head, &config)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_crate_detail() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("lib").dependency("core"))
+                .crate_entry(TestCrate::new("app").dependency("lib")),
+        )
+        .tag("core-v0.1.0")
+        .modify_crate("core", "src/extra.rs", "pub fn extra() {}\n")
+        .commit("Extend core")
+        .modify_crate("app", "src/extra.rs", "pub fn extra() {}\n")
+        .commit("Extend app")
+        .build()
+        .unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let core = graph.find_crate_by_name("core").unwrap().id.clone();
+    let config = DeptrackConfig::load_or_default(repo.path());
+    let detail = CrateDetail::collect(&graph, repo.path(), &core, &config, 5)
+        .unwrap()
+        .unwrap();
+
+    let names = |ids: &[deptrack::CrateId]| -> Vec<String> {
+        ids.iter().map(|id| id.name.clone()).collect()
+    };
+    assert!(detail.dependencies.is_empty());
+    assert_eq!(names(&detail.dependents), vec!["lib"]);
+    assert_eq!(names(&detail.transitive_dependents), vec!["app"]);
+    assert_eq!(detail.last_release.unwrap().tag, "core-v0.1.0");
+    assert!(detail.changelog.is_some());
+    // only commits touching core, newest first
+    assert_eq!(detail.recent_changes[0].subject, "Extend core");
+    assert!(
+        detail
+            .recent_changes
+            .iter()
+            .all(|commit| commit.subject != "Extend app")
+    );
+
+    let missing = deptrack::CrateId::new("workspace".to_string(), "nope".to_string());
+    assert!(
+        CrateDetail::collect(&graph, repo.path(), &missing, &config, 5)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_fingerprint_change_detection() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let before = FingerprintSnapshot::collect(&graph, repo.path()).unwrap();
+    assert_eq!(before.crates.len(), 3);
+    assert!(before.changed_crates(&before).is_empty());
+
+    // survives a round trip through the file
+    let file = repo.path().join("fingerprints.json");
+    before.save(&file).unwrap();
+    let loaded = FingerprintSnapshot::load(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    repo.touch_code("crate_A").unwrap();
+    let after = FingerprintSnapshot::collect(&graph, repo.path()).unwrap();
+    let changed = loaded.changed_crates(&after);
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].name, "crate_A");
+
+    let impact = graph
+        .analyze_fingerprint_changes(repo.path(), &loaded, &after, &ImpactConfig::default())
+        .unwrap();
+    assert_eq!(impact.changed_files.len(), 1);
+    assert!(impact.changed_files[0].ends_with("src/lib.rs"));
+    assert!(impact.changed_files[0].is_relative());
+    assert_eq!(impact.directly_affected_crates, changed);
+    // crate_B and crate_C depend on crate_A
+    assert_eq!(impact.all_affected_crates.len(), 3);
+}
+
+#[test]
+fn test_snapshot_change_detection() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let snapshot = || {
+        let mut directory = FsDirectory::new_root(repo.path());
+        directory.scan().unwrap();
+        directory.hash(HashType::BLAKE3).unwrap();
+        directory
+    };
+    let before = snapshot();
+
+    repo.touch_code("crate_B").unwrap();
+    std::fs::write(repo.path().join("NOTES.md"), "not part of any crate\n").unwrap();
+    let after = snapshot();
+
+    let impact = graph
+        .analyze_snapshot_changes(repo.path(), &before, &after, &ImpactConfig::default())
+        .unwrap();
+    assert_eq!(impact.changed_files.len(), 2);
+    assert!(impact.changed_files.iter().all(|path| path.is_relative()));
+    assert_eq!(impact.directly_affected_crates.len(), 1);
+    assert_eq!(impact.directly_affected_crates[0].name, "crate_B");
+    // crate_C depends on crate_B, crate_A does not
+    assert_eq!(impact.all_affected_crates.len(), 2);
+}
+
+#[test]
+fn test_requirement_sync() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("workspace")
+                .crate_entry(TestCrate::new("core").version("0.2.0"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .modify_crate(
+            "app",
+            "Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ncore = { path = \"../core\", version = \"=0.1.0\" } # pinned\n",
+        )
+        .commit("Pin core")
+        .build()
+        .unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let report = RequirementSync::analyze(&graph).unwrap();
+    assert_eq!(report.checked, 1);
+    assert_eq!(report.stale.len(), 1);
+    assert_eq!(report.stale[0].dependency.name, "core");
+    assert_eq!(report.stale[0].requirement, "=0.1.0");
+    assert_eq!(report.stale[0].synced, "=0.2.0");
+
+    assert_eq!(RequirementSync::apply(&report).unwrap(), 1);
+    let manifest = std::fs::read_to_string(repo.path().join("workspace/app/Cargo.toml")).unwrap();
+    assert!(manifest.contains("core = { path = \"../core\", version = \"=0.2.0\" } # pinned"));
+    assert!(RequirementSync::analyze(&graph).unwrap().is_consistent());
+}
+
+#[test]
+fn test_base_ref_detection() {
+    let repo = TestRepoBuilder::new()
+        .workspace(TestWorkspace::new("workspace").crate_entry(TestCrate::new("core")))
+        .build()
+        .unwrap();
+    let patterns = [deptrack::TagPattern::default()];
+    let names = ["core".to_string()];
+    let detect = || BaseRef::detect(repo.path(), &patterns, &names).unwrap();
+
+    assert_eq!(detect(), None);
+
+    repo.tag("unrelated").unwrap();
+    repo.tag("core-v0.1.0").unwrap();
+    let release = detect().unwrap();
+    assert_eq!(release.source, BaseRefSource::ReleaseTag);
+    assert_eq!(release.git_ref, "refs/tags/core-v0.1.0");
+
+    // a branch tracking the default one, with a commit of its own
+    let default_branch = repo.current_branch().unwrap();
+    let fork_point = repo.head_commit().unwrap();
+    repo.branch("feature").unwrap();
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo.path())
+        .args(["branch", "--set-upstream-to", &default_branch])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // level with the upstream, so the release tag is still the base
+    assert_eq!(detect().unwrap().source, BaseRefSource::ReleaseTag);
+
+    repo.touch_code("core").unwrap();
+    repo.commit("Touch core").unwrap();
+    let base = detect().unwrap();
+    assert_eq!(base.source, BaseRefSource::UpstreamMergeBase);
+    assert_eq!(base.name, default_branch);
+    assert_eq!(base.git_ref, fork_point);
+}
+
+#[test]
+fn test_current_ref_detached_head() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let branch = repo.current_branch().unwrap();
+    assert_eq!(
+        GitOps::get_current_branch(repo.path()).unwrap(),
+        CurrentRef::Branch(branch)
+    );
+
+    let head = repo.head_commit().unwrap();
+    repo.checkout(&head).unwrap();
+    let current = GitOps::get_current_branch(repo.path()).unwrap();
+    assert!(current.is_detached());
+    assert_eq!(current, CurrentRef::Detached(head));
+    assert_eq!(current.branch(), None);
+}
+
+#[test]
+fn test_list_tags_and_remote_branches() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let head = repo.head_commit().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    repo.tag("v0.1.0").unwrap();
+    git(&["tag", "-a", "v0.2.0", "-m", "second release\n\nwith notes"]);
+    git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+    git(&[
+        "symbolic-ref",
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/main",
+    ]);
+
+    let tags = GitOps::list_tags(repo.path()).unwrap();
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].name, "v0.1.0");
+    assert_eq!(tags[0].target, head);
+    assert!(!tags[0].is_annotated());
+    assert!(!tags[0].date.is_empty());
+    assert_eq!(tags[1].name, "v0.2.0");
+    // annotated tags are peeled to their commit
+    assert_eq!(tags[1].target, head);
+    assert_eq!(
+        tags[1].message.as_deref(),
+        Some("second release\n\nwith notes")
+    );
+
+    assert_eq!(
+        GitOps::list_tag_names(repo.path()).unwrap(),
+        ["v0.1.0", "v0.2.0"]
+    );
+    assert_eq!(
+        GitOps::list_remote_branches(repo.path()).unwrap(),
+        ["origin/main"]
+    );
+}
+
+#[test]
+fn test_commits_in_range() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let base = repo.head_commit().unwrap();
+    repo.touch_code("crate_A").unwrap();
+    repo.commit("change crate_A").unwrap();
+    repo.touch_code("crate_B").unwrap();
+    repo.commit("change crate_B").unwrap();
+
+    let crate_a = repo.crate_path("crate_A").unwrap();
+    let commits = GitOps::commits_in_range(repo.path(), &base, "HEAD", &crate_a).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].subject, "change crate_A");
+    assert_eq!(commits[0].author, "Deptrack Test");
+
+    let crate_c = repo.crate_path("crate_C").unwrap();
+    assert!(
+        GitOps::commits_in_range(repo.path(), &base, "HEAD", &crate_c)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_changed_files_matching_pathspec() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("crate_A").unwrap();
+    repo.touch_code("crate_B").unwrap();
+    repo.add_changelog_entry("crate_A", "0.1.1", "touched")
+        .unwrap();
+    repo.commit("change crate_A and crate_B").unwrap();
+
+    let head = GitRef::Hash(repo.head_commit().unwrap());
+    let cancel = Default::default();
+    let changed = |pathspec: Pathspec| -> Vec<String> {
+        let mut paths: Vec<String> =
+            GitOps::list_changed_files_matching(repo.path(), &base, &head, &pathspec, &cancel)
+                .unwrap()
+                .changes
+                .into_iter()
+                .map(|change| change.path.to_string_lossy().replace('\\', "/"))
+                .collect();
+        paths.sort();
+        paths
+    };
+
+    assert_eq!(changed(Pathspec::default()).len(), 3);
+    let crate_a = repo.crate_path("crate_A").unwrap();
+    let crate_a = crate_a.strip_prefix(repo.path()).unwrap().to_string_lossy();
+    let only_a = changed(Pathspec::new(vec![crate_a.to_string()], Vec::new()));
+    assert_eq!(only_a.len(), 2);
+    assert!(only_a.iter().all(|path| path.starts_with(crate_a.as_ref())));
+
+    // exclusions apply on their own and on top of inclusions
+    let code = changed(Pathspec::new(Vec::new(), vec!["**/*.md".to_string()]));
+    assert_eq!(code.len(), 2);
+    assert!(code.iter().all(|path| path.ends_with("src/lib.rs")));
+    let a_code = changed(Pathspec::new(
+        vec![crate_a.to_string()],
+        vec!["**/*.md".to_string()],
+    ));
+    assert_eq!(a_code, [format!("{}/src/lib.rs", crate_a)]);
+}
+
+#[test]
+fn test_two_dot_and_three_dot_changes() {
+    let repo = TestScenario::SimpleChain.build().unwrap();
+    let main = repo.current_branch().unwrap();
+    let fork_point = repo.head_commit().unwrap();
+    repo.branch("feature").unwrap();
+    repo.touch_code("crate_A").unwrap();
+    repo.commit("change crate_A").unwrap();
+    repo.checkout(&main).unwrap();
+    repo.touch_code("crate_B").unwrap();
+    repo.commit("change crate_B").unwrap();
+
+    assert_eq!(
+        GitOps::merge_base(repo.path(), &main, "feature").unwrap(),
+        fork_point
+    );
+
+    let main_ref = GitRef::Branch(main);
+    let feature = GitRef::Branch("feature".to_string());
+    let changed = |mode: DiffMode| -> Vec<String> {
+        let mut paths: Vec<String> = GitOps::list_changes(
+            repo.path(),
+            &main_ref,
+            &feature,
+            mode,
+            &Pathspec::default(),
+            &Default::default(),
+        )
+        .unwrap()
+        .changes
+        .into_iter()
+        .map(|change| change.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+        paths.sort();
+        paths
+    };
+
+    // two-dot also reports the change only main made, three-dot just the
+    // feature branch's own
+    let two_dot = changed(DiffMode::TwoDot);
+    assert_eq!(two_dot.len(), 2);
+    let three_dot = changed(DiffMode::ThreeDot);
+    assert_eq!(three_dot.len(), 1);
+    assert!(three_dot[0].contains("crate_A"));
+    assert_eq!(
+        GitOps::list_unique_changes(repo.path(), &main_ref, &feature)
+            .unwrap()
+            .changes
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_impact_propagation_skips_dev_dependents() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                .crate_entry(TestCrate::new("bench").dev_dependency("core"))
+                .crate_entry(TestCrate::new("cli").dependency("bench")),
+        )
+        .build()
+        .unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("core").unwrap();
+    repo.commit("change core").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let id = |name: &str| graph.find_crate_by_name(name).unwrap().id.clone();
+    let mut impact = ImpactConfig::default();
+
+    // by default dev-dependents and everything depending on them are affected
+    let all = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &impact)
+        .unwrap();
+    assert_eq!(all.all_affected_crates.len(), 4);
+    assert!(all.dev_only_affected.is_empty());
+
+    impact.propagation = ImpactPropagation::NoDev;
+    let no_dev = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &impact)
+        .unwrap();
+    let mut affected: Vec<_> = no_dev
+        .all_affected_crates
+        .iter()
+        .map(|id| &id.name)
+        .collect();
+    affected.sort();
+    assert_eq!(affected, ["app", "core"]);
+    assert_eq!(no_dev.dev_only_affected, [id("bench")]);
+    // dev-dependents still have to be rebuilt
+    assert_eq!(no_dev.needs_rebuild.len(), 4);
+    assert_eq!(no_dev.needs_release, no_dev.all_affected_crates);
+}
+
+#[test]
+fn test_impact_max_transitive_depth() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("log"))
+                .crate_entry(TestCrate::new("core").dependency("log"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                // reached at depth 1 as well, so not cut off
+                .crate_entry(TestCrate::new("cli").dependency("app").dependency("log")),
+        )
+        .build()
+        .unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("log").unwrap();
+    repo.commit("change log").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = ImpactConfig {
+        max_transitive_depth: Some(1),
+        ..Default::default()
+    };
+    let analysis = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &impact)
+        .unwrap();
+
+    let mut affected: Vec<_> = analysis
+        .all_affected_crates
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    affected.sort();
+    assert_eq!(affected, ["cli", "core", "log"]);
+    let beyond: Vec<_> = analysis
+        .beyond_max_depth
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(beyond, ["app"]);
+}
+
+#[test]
+fn test_rebuild_and_release_impact() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core"))
+                .crate_entry(TestCrate::new("tool").dependency("core"))
+                .crate_entry(TestCrate::new("bench").dev_dependency("core")),
+        )
+        .build()
+        .unwrap();
+    // keep tool out of releases
+    let manifest = repo.crate_path("tool").unwrap().join("Cargo.toml");
+    let content = std::fs::read_to_string(&manifest).unwrap();
+    std::fs::write(
+        &manifest,
+        content.replacen("[package]\n", "[package]\npublish = false\n", 1),
+    )
+    .unwrap();
+    repo.commit("unpublished tool").unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("core").unwrap();
+    repo.commit("change core").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let analysis = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    let names = |ids: &[deptrack::CrateId]| {
+        let mut names: Vec<_> = ids.iter().map(|id| id.name.clone()).collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(
+        names(&analysis.needs_rebuild),
+        ["app", "bench", "core", "tool"]
+    );
+    assert_eq!(names(&analysis.needs_release), ["app", "core"]);
+}
+
+#[test]
+fn test_impact_stops_at_versioned_boundaries() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("pinned").dependency("core"))
+                .crate_entry(TestCrate::new("facade").dependency("core"))
+                .crate_entry(TestCrate::new("app").dependency("pinned"))
+                .crate_entry(TestCrate::new("cli").dependency("facade")),
+        )
+        .build()
+        .unwrap();
+    let edit = |name: &str, edit: &dyn Fn(String) -> String| {
+        let manifest = repo.crate_path(name).unwrap().join("Cargo.toml");
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        std::fs::write(&manifest, edit(content)).unwrap();
+    };
+    edit("pinned", &|content| {
+        content.replace(
+            "core = { path = \"../core\" }",
+            "core = { path = \"../core\", version = \"=0.1.0\" }",
+        )
+    });
+    edit("facade", &|content| {
+        content + "\n[package.metadata.deptrack]\nstable-reexports = [\"core\"]\n"
+    });
+    repo.commit("mark boundaries").unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("core").unwrap();
+    repo.commit("change core").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let names = |ids: &[deptrack::CrateId]| {
+        let mut names: Vec<_> = ids.iter().map(|id| id.name.clone()).collect();
+        names.sort();
+        names
+    };
+    let mut impact = ImpactConfig::default();
+    let analysis = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &impact)
+        .unwrap();
+    assert_eq!(analysis.all_affected_crates.len(), 5);
+
+    impact.stop_at_boundaries = true;
+    let analysis = graph
+        .analyze_git_changes_with_config(repo.path(), &base, &GitRef::Head, &impact)
+        .unwrap();
+    assert_eq!(
+        names(&analysis.all_affected_crates),
+        ["core", "facade", "pinned"]
+    );
+    assert_eq!(names(&analysis.boundary_crates), ["facade", "pinned"]);
+    // rebuilding still reaches everything
+    assert_eq!(analysis.needs_rebuild.len(), 5);
+}
+
+#[test]
+fn test_unified_report() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .build()
+        .unwrap();
+    let manifest = repo.crate_path("core").unwrap().join("Cargo.toml");
+    let content = std::fs::read_to_string(&manifest).unwrap();
+    std::fs::write(
+        &manifest,
+        content.replacen("[package]\n", "[package]\nlicense = \"MIT\"\n", 1),
+    )
+    .unwrap();
+    repo.commit("license core").unwrap();
+    let base = repo.head_commit().unwrap();
+    repo.touch_code("core").unwrap();
+    repo.commit("change core").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let mut config = DeptrackConfig::default();
+    let report = UnifiedReport::build(&graph, repo.path(), &base, "HEAD", &config).unwrap();
+
+    let analysis = report.analysis.as_ref().unwrap();
+    assert_eq!(analysis.statistics.crate_count, 2);
+    assert_eq!(analysis.directly_affected, ["ws::core"]);
+    assert_eq!(report.versions.as_ref().unwrap().needing_bump.len(), 2);
+    assert!(report.cycles.as_ref().unwrap().production.is_empty());
+    let licenses = &report.licenses.as_ref().unwrap().licenses;
+    assert_eq!(licenses["MIT"], ["ws::core"]);
+    assert_eq!(licenses["unlicensed"], ["ws::app"]);
+    assert_eq!(report.manifests.as_ref().unwrap().checked, 2);
+    assert!(report.issues.iter().any(|issue| issue.check == "version"
+        && issue.crate_name == "ws::core"
+        && issue.issue_type == "no_version_bump"));
+    assert!(report.error_count() > 0);
+    assert!(report.to_markdown().contains("#### licenses"));
+
+    // the header tells how each crate's issues were graded
+    let severity = &report.severity;
+    assert_eq!(severity.preset, SeverityPreset::Standard);
+    assert_eq!(severity.crates["ws::core"], SeverityScope::Direct);
+    assert_eq!(severity.crates["ws::app"], SeverityScope::Transitive);
+    assert_eq!(
+        severity.resolve("ws::core", "no_version_bump"),
+        Some(IssueSeverity::Error)
+    );
+    assert_eq!(
+        severity.resolve("ws::app", "no_version_bump"),
+        Some(IssueSeverity::Warning)
+    );
+    let rule = |name: &str| severity.rules.iter().find(|rule| rule.rule == name);
+    // membership grades every crate with the direct table
+    assert_eq!(
+        rule("missing_member").unwrap().transitive,
+        IssueSeverity::Error
+    );
+    assert_eq!(
+        rule("analysis_failed").unwrap().checks,
+        ["manifest", "msrv", "features", "lockfile"]
+    );
+    assert!(rule("dependency_cycle").is_some());
+    assert!(rule("external_check").is_none());
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["severity"]["crates"]["ws::app"], "transitive");
+
+    // disabled sections are neither run nor reported
+    config.report.versions = false;
+    config.report.manifests = false;
+    let report = UnifiedReport::build(&graph, repo.path(), &base, "HEAD", &config).unwrap();
+    assert!(report.versions.is_none() && report.manifests.is_none());
+    assert!(report.issues.is_empty());
+    let rules: Vec<&str> = report
+        .severity
+        .rules
+        .iter()
+        .map(|rule| rule.rule.as_str())
+        .collect();
+    assert_eq!(rules, ["dependency_cycle"]);
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json.get("versions").is_none());
+    assert!(json.get("licenses").is_some());
+}
+
+#[test]
+fn test_scoped_analysis() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("net").dependency("core"))
+                .crate_entry(TestCrate::new("app").dependency("net")),
+        )
+        .build()
+        .unwrap();
+    let base = GitRef::Hash(repo.head_commit().unwrap());
+    repo.touch_code("core").unwrap();
+    repo.touch_code("net").unwrap();
+    repo.commit("change core and net").unwrap();
+
+    let net = repo.crate_path("net").unwrap();
+    let net = net.strip_prefix(repo.path()).unwrap();
+    let scope = Scope::new([format!("{}/**", net.display())]).unwrap();
+    let graph = CrateDependencyGraph::build_scoped(repo.path(), scope).unwrap();
+    assert_eq!(graph.crates.len(), 1);
+    assert!(graph.find_crate_by_name("net").is_some());
+    assert_eq!(graph.workspaces.len(), 1);
+
+    let analysis = graph
+        .analyze_git_changes(repo.path(), &base, &GitRef::Head)
+        .unwrap();
+    assert!(!analysis.changed_files.is_empty());
+    assert!(
+        analysis
+            .changed_files
+            .iter()
+            .all(|path| path.starts_with(net))
+    );
+    let names: Vec<_> = analysis
+        .all_affected_crates
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(names, ["net"]);
+}
+
+#[test]
+fn test_changelog_coverage() {
+    use deptrack::utils::testing::ChangelogFixture;
+
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core").changelog(ChangelogFixture::Valid))
+                .crate_entry(TestCrate::new("io").changelog(ChangelogFixture::Valid)),
+        )
+        .build()
+        .unwrap();
+    let base = repo.head_commit().unwrap();
+    for i in 0..3 {
+        repo.touch_code("core").unwrap();
+        repo.commit(&format!("fix(parser): edge case {}", i))
+            .unwrap();
+    }
+    repo.add_changelog_entry("core", "0.2.0", "fix: bug fixes")
+        .unwrap();
+    repo.commit("core changelog").unwrap();
+    repo.touch_code("io").unwrap();
+    repo.add_changelog_entry("io", "0.2.0", "fix(io): retry interrupted reads")
+        .unwrap();
+    repo.commit("fix(io): retry interrupted reads").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let crates: Vec<_> = graph.crates.keys().cloned().collect();
+    let config = DeptrackConfig::default();
+    let coverage = ChangelogCoverage::analyze(
+        &graph,
+        repo.path(),
+        &base,
+        "HEAD",
+        &crates,
+        &config.changelog,
+        3,
+    )
+    .unwrap();
+
+    assert_eq!(coverage.crates.len(), 2);
+    let core = &coverage.crates[0];
+    assert_eq!(core.crate_id.name, "core");
+    // the commit touching only the changelog is not counted
+    assert_eq!((core.commits, core.new_entries), (3, 1));
+    assert_eq!(core.missing_scopes, ["parser"]);
+    assert!(core.flagged);
+    let io = &coverage.crates[1];
+    assert_eq!((io.commits, io.new_entries), (1, 1));
+    assert!(io.missing_scopes.is_empty() && !io.flagged);
+    assert_eq!(coverage.overall_coverage(), 50.0);
+}
+
+#[test]
+fn test_task_list() {
+    use deptrack::{OwnerSource, TaskList};
+
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .build()
+        .unwrap();
+    std::fs::create_dir_all(repo.path().join(".github")).unwrap();
+    std::fs::write(
+        repo.path().join(".github/CODEOWNERS"),
+        "/ws/core/ @org/core\n",
+    )
+    .unwrap();
+    repo.commit("add CODEOWNERS").unwrap();
+    let base = repo.head_commit().unwrap();
+    repo.touch_code("core").unwrap();
+    repo.touch_code("app").unwrap();
+    repo.commit("change core and app").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let config = DeptrackConfig::default();
+    let report = UnifiedReport::build(&graph, repo.path(), &base, "HEAD", &config).unwrap();
+    let tasks = TaskList::from_report(&graph, repo.path(), &report, false).unwrap();
+
+    let owners: Vec<_> = tasks
+        .owners
+        .iter()
+        .map(|owner| (owner.owner.as_str(), owner.source))
+        .collect();
+    assert_eq!(
+        owners,
+        [
+            ("@org/core", OwnerSource::Codeowners),
+            ("Deptrack Test", OwnerSource::Commits)
+        ]
+    );
+    assert!(
+        tasks.owners[0]
+            .tasks
+            .iter()
+            .all(|t| t.crate_name == "ws::core")
+    );
+    assert!(
+        tasks.owners[1]
+            .tasks
+            .iter()
+            .all(|t| t.crate_name == "ws::app")
+    );
+    assert_eq!(tasks.task_count(), report.issues.len());
+    let markdown = tasks.to_markdown();
+    assert!(markdown.contains("### @org/core\n\n- [ ] `ws::core` (ERROR, version)"));
+
+    let errors = TaskList::from_report(&graph, repo.path(), &report, true).unwrap();
+    assert_eq!(errors.task_count(), report.error_count());
+}
+
+#[test]
+fn test_statistics_per_workspace() {
+    let repo = TestScenario::MultiWorkspace.build().unwrap();
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let stats = graph.get_statistics_per_workspace();
+
+    let rows: Vec<_> = stats
+        .workspaces
+        .iter()
+        .map(|w| {
+            (
+                w.workspace.as_str(),
+                w.crate_count,
+                w.internal_dependencies,
+                w.outgoing_dependencies,
+                w.incoming_dependencies,
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("apps", 2, 0, 2, 0),
+            ("core", 1, 0, 0, 2),
+            ("services", 1, 0, 1, 1),
+        ]
+    );
+    assert_eq!(stats.cross_workspace_matrix["apps"]["core"], 1);
+    assert_eq!(stats.cross_workspace_matrix["apps"]["services"], 1);
+    assert_eq!(stats.cross_workspace_matrix["services"]["core"], 1);
+    assert!(!stats.cross_workspace_matrix.contains_key("core"));
+    // the plain statistics leave the breakdown out of the json
+    let json = serde_json::to_value(graph.get_statistics()).unwrap();
+    assert!(json.get("workspaces").is_none());
+
+    // one cycle inside a workspace, one across two
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("left")
+                .crate_entry(TestCrate::new("a").dependency("b"))
+                .crate_entry(TestCrate::new("b").dependency("a"))
+                .crate_entry(TestCrate::new("c").dependency("d")),
+        )
+        .workspace(TestWorkspace::new("right").crate_entry(TestCrate::new("d").dependency("c")))
+        .build()
+        .unwrap();
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let stats = graph.get_statistics_per_workspace();
+    let cycles: Vec<_> = stats
+        .workspaces
+        .iter()
+        .map(|w| (w.workspace.as_str(), w.confined_cycles, w.spanning_cycles))
+        .collect();
+    assert_eq!(cycles, [("left", 1, 1), ("right", 0, 1)]);
+}
+
+#[test]
+fn test_change_analysis_survives_broken_manifests() {
+    let repo = TestScenario::SimpleChain
+        .builder()
+        .branch("feature")
+        .touch_code("crate_A")
+        .modify_crate("crate_C", "Cargo.toml", "[package\nname = \"crate_C\"\n")
+        .commit("Change crate_A, break crate_C")
+        .build()
+        .unwrap();
+    let path = repo.path();
+
+    // the unparsable manifest is skipped, the change to crate_A still
+    // reaches its dependents
+    let graph = CrateDependencyGraph::build_from_repository(path).unwrap();
+    assert_eq!(graph.broken_manifests.len(), 1);
+    assert!(graph.find_crate_by_name("crate_C").is_none());
+    let impact = graph
+        .analyze_git_changes(path, &GitRef::Branch("master".to_string()), &GitRef::Head)
+        .unwrap();
+    let names =
+        |crates: &[deptrack::CrateId]| crates.iter().map(|id| id.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&impact.directly_affected_crates), vec!["crate_A"]);
+    assert_eq!(
+        names(&impact.all_affected_crates),
+        vec!["crate_A", "crate_B"]
+    );
+    assert!(impact.manifest_changes.is_empty());
+
+    // and is reported as an issue of its own
+    let membership =
+        MembershipChecker::analyze(&graph, path, &SeverityConfig::default_direct()).unwrap();
+    assert_eq!(membership.broken_manifests.len(), 1);
+    let broken = &membership.statuses[&membership.broken_manifests[0]].issues[0];
+    assert_eq!(broken.issue_type, IssueType::BrokenManifest);
+    assert_eq!(broken.severity, IssueSeverity::Warning);
+
+    // a manifest that stops parsing after discovery fails only its own crate
+    let crate_b = graph.find_crate_by_name("crate_B").unwrap();
+    std::fs::write(&crate_b.cargo_toml_path, "[package").unwrap();
+    let manifests = ManifestChecker::analyze(
+        &graph,
+        &impact.all_affected_crates,
+        &impact.directly_affected_crates,
+        &SeverityConfig::default_direct(),
+        &SeverityConfig::default_transitive(),
+    )
+    .unwrap();
+    assert_eq!(manifests.statuses.len(), 2);
+    let failure = &manifests.statuses[&crate_b.id].issues[0];
+    assert_eq!(failure.issue_type, IssueType::AnalysisFailed);
+    assert!(failure.message.starts_with("manifest could not be checked"));
+    let crate_a = graph.find_crate_by_name("crate_A").unwrap();
+    assert!(
+        manifests.statuses[&crate_a.id]
+            .issues
+            .iter()
+            .all(|issue| issue.issue_type != IssueType::AnalysisFailed)
+    );
+}
+
+#[test]
+fn test_dev_dependency_cycle_components() {
+    let repo = TestScenario::DevDependencyCycle.build().unwrap();
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+
+    // the cycle goes through a dev-dependency, so there is no production
+    // cycle to report, but the crates still form a component
+    assert!(graph.find_production_cycles().is_empty());
+    let components = graph.strongly_connected_components();
+    let members: Vec<(usize, Vec<&str>, bool)> = components
+        .iter()
+        .map(|c| {
+            let names = c.members.iter().map(String::as_str).collect();
+            (c.id, names, c.is_trivial())
+        })
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            (0, vec!["workspace::crate_A", "workspace::crate_B"], false),
+            (1, vec!["workspace::crate_C"], true),
+        ]
+    );
+
+    let edges = &components[0].edges;
+    assert_eq!(edges.len(), 2);
+    assert_eq!(
+        (edges[0].from.as_str(), edges[0].to.as_str()),
+        ("workspace::crate_A", "workspace::crate_B")
+    );
+    let dev = &edges[0].declarations[0];
+    assert_eq!(dev.kind, DependencyType::Dev);
+    assert_eq!(dev.section, "dev-dependencies");
+    let manifest = std::fs::read_to_string(&dev.manifest).unwrap();
+    let line = manifest
+        .lines()
+        .position(|line| line.starts_with("crate_B"))
+        .unwrap();
+    assert_eq!(dev.line, Some(line + 1));
+    assert_eq!(edges[1].declarations[0].kind, DependencyType::Normal);
+
+    // `analyze --sccs` lists the non-trivial components as they serialize
+    let sccs: Vec<_> = components.iter().filter(|c| !c.is_trivial()).collect();
+    let json = serde_json::to_value(&sccs).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["id"], 0);
+    assert_eq!(
+        json[0]["members"],
+        serde_json::json!(["workspace::crate_A", "workspace::crate_B"])
+    );
+    assert_eq!(json[0]["edges"][0]["declarations"][0]["kind"], "Dev");
+    assert_eq!(
+        json[0]["edges"][0]["declarations"][0]["section"],
+        "dev-dependencies"
+    );
+}
+
+#[test]
+fn test_report_severity_grading() {
+    let repo = TestRepoBuilder::new()
+        .workspace(
+            TestWorkspace::new("ws")
+                .crate_entry(TestCrate::new("core"))
+                .crate_entry(TestCrate::new("app").dependency("core")),
+        )
+        .build()
+        .unwrap();
+    let base = repo.head_commit().unwrap();
+    repo.touch_code("core").unwrap();
+    repo.commit("change core").unwrap();
+
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let report = UnifiedReport::build(
+        &graph,
+        repo.path(),
+        &base,
+        "HEAD",
+        &DeptrackConfig::default(),
+    )
+    .unwrap();
+
+    // core changed and is graded with the direct table, app only through
+    // core with the transitive one, so the same issue differs in severity
+    assert!(report.error_count() > 0);
+    assert!(report.warning_count() > 0);
+    let bump = |crate_name: &str| {
+        report
+            .issues
+            .iter()
+            .find(|i| i.crate_name == crate_name && i.issue_type == "no_version_bump")
+            .unwrap()
+            .severity
+    };
+    assert_eq!(bump("ws::core"), IssueSeverity::Error);
+    assert_eq!(bump("ws::app"), IssueSeverity::Warning);
+
+    // every issue of a graded crate has the severity the header resolves
+    let mut graded = 0;
+    for issue in &report.issues {
+        if let Some(severity) = report
+            .severity
+            .resolve(&issue.crate_name, &issue.issue_type)
+        {
+            assert_eq!(severity, issue.severity, "{:?}", issue);
+            graded += 1;
+        }
+    }
+    assert!(graded >= 2);
+
+    let json = serde_json::to_value(&report).unwrap();
+    let severity = &json["severity"];
+    assert_eq!(severity["preset"], "standard");
+    assert!(severity.get("escalate_after").is_none());
+    assert_eq!(
+        severity["crates"],
+        serde_json::json!({"ws::app": "transitive", "ws::core": "direct"})
+    );
+    let rule = severity["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|rule| rule["rule"] == "no_version_bump")
+        .unwrap();
+    assert_eq!(
+        *rule,
+        serde_json::json!({
+            "rule": "no_version_bump",
+            "checks": ["version"],
+            "direct": "error",
+            "transitive": "warning",
+        })
+    );
+}
+
+#[test]
+fn test_target_module_maps_to_its_crate() {
+    let repo = TestScenario::SimpleChain
+        .builder()
+        .branch("feature")
+        .modify_crate("crate_A", "src/target/mod.rs", "pub fn triple() {}\n")
+        .commit("Add a target module to crate_A")
+        .build()
+        .unwrap();
+
+    // only a `target` beside a Cargo.toml is build output
+    let graph = CrateDependencyGraph::build_from_repository(repo.path()).unwrap();
+    let impact = graph
+        .analyze_git_changes(
+            repo.path(),
+            &GitRef::Branch("master".to_string()),
+            &GitRef::Head,
+        )
+        .unwrap();
+    let directly: Vec<&str> = impact
+        .directly_affected_crates
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(directly, vec!["crate_A"]);
+}